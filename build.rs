@@ -12,6 +12,10 @@ fn main() {
     if cfg!(feature = "remove_blossom_v") {
         try_include_blossom_v = false;
     }
+    // the `no_std` build does not link to the C++ standard library
+    if env::var("CARGO_FEATURE_STD").is_err() {
+        try_include_blossom_v = false;
+    }
 
     if try_include_blossom_v && Path::new("./blossomV/PerfectMatching.h").exists() {
         println!("cargo:rustc-cfg=feature=\"blossom_v\"");
@@ -38,7 +38,7 @@ fn demo_aps2023_decoding_graph_growing() {
     // create dual nodes and grow them by half length
     let dual_node_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
     for _ in 0..2 {
-        dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight);
+        dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight).unwrap();
         visualizer
             .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
@@ -187,7 +187,7 @@ fn demo_aps2023_syndrome_graph_growing() {
     // create dual nodes and grow them by half length
     let dual_node_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
     for _ in 0..2 {
-        dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight);
+        dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight).unwrap();
         visualizer
             .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
@@ -422,12 +422,12 @@ fn demo_aps2023_example_decoding_graph_grow_single() {
         .snapshot_combined("syndrome".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
     let dual_node_ptr = interface_ptr.read_recursive().nodes[2].clone().unwrap();
-    dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight);
+    dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight).unwrap();
     visualizer
         .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
     let dual_node_ptr_2 = interface_ptr.read_recursive().nodes[1].clone().unwrap();
-    dual_module.grow_dual_node(&dual_node_ptr_2, 2 * half_weight);
+    dual_module.grow_dual_node(&dual_node_ptr_2, 2 * half_weight).unwrap();
     visualizer
         .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
@@ -449,12 +449,12 @@ fn demo_aps2023_example_syndrome_graph_grow_single() {
         .snapshot_combined("syndrome".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
     let dual_node_ptr = interface_ptr.read_recursive().nodes[2].clone().unwrap();
-    dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight);
+    dual_module.grow_dual_node(&dual_node_ptr, 2 * half_weight).unwrap();
     visualizer
         .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
     let dual_node_ptr_2 = interface_ptr.read_recursive().nodes[1].clone().unwrap();
-    dual_module.grow_dual_node(&dual_node_ptr_2, 2 * half_weight);
+    dual_module.grow_dual_node(&dual_node_ptr_2, 2 * half_weight).unwrap();
     visualizer
         .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
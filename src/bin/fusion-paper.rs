@@ -622,7 +622,7 @@ fn fusion_paper_overlay_decoding_graph() {
         .snapshot_combined("initial".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
     for _ in 0..8 {
-        dual_module.grow_dual_node(&dual_node_ptr, half_weight);
+        dual_module.grow_dual_node(&dual_node_ptr, half_weight).unwrap();
         visualizer
             .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
@@ -646,7 +646,7 @@ fn fusion_paper_overlay_syndrome_graph() {
         .snapshot_combined("initial".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
     for _ in 0..8 {
-        dual_module.grow_dual_node(&dual_node_ptr, half_weight);
+        dual_module.grow_dual_node(&dual_node_ptr, half_weight).unwrap();
         visualizer
             .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
@@ -678,7 +678,7 @@ fn fusion_paper_pseudo_cover_island() {
         .snapshot_combined("initial".to_string(), vec![&interface_ptr, &dual_module])
         .unwrap();
     for dual_node_ptr in node_ptr_vec.iter() {
-        dual_module.grow_dual_node(dual_node_ptr, 2 * half_weight);
+        dual_module.grow_dual_node(dual_node_ptr, 2 * half_weight).unwrap();
     }
     for _ in 0..2 {
         dual_module.prepare_dual_node_growth_single(&node_ptr_vec[2], true);
@@ -1,9 +1,12 @@
 use super::dual_module::*;
 use super::example_codes::*;
 use super::example_partition;
+use super::fuzz::*;
 use super::mwpm_solver::*;
+use super::partition_tuner::*;
 use super::primal_module::*;
 use super::util::*;
+use super::verifier::*;
 use super::visualize::*;
 #[cfg(feature = "qecp_integrate")]
 use crate::qecp;
@@ -41,6 +44,15 @@ pub struct BenchmarkParameters {
     /// rounds of noisy measurement, valid only when multiple rounds
     #[clap(short = 'e', long, default_value_t = 0.)]
     pub pe: f64,
+    /// per-edge erasure probabilities from a JSON file of a list, overriding `--pe`
+    #[clap(long)]
+    pub pe_file: Option<String>,
+    /// per-edge error probabilities from a JSON file of an object keyed by edge index, see [`ErrorRateMap`]
+    #[clap(long)]
+    pub error_rate_file: Option<String>,
+    /// correlated erasure bursts in JSON, e.g. {"probability": 0.01, "radius": 2}, see [`ErasureBurst`]
+    #[clap(long)]
+    pub erasure_burst: Option<String>,
     /// rounds of noisy measurement, valid only when multiple rounds
     #[clap(short = 'n', long, default_value_t = 0)]
     pub noisy_measurements: VertexNum,
@@ -92,6 +104,12 @@ pub struct BenchmarkParameters {
     /// skip some iterations, useful when debugging
     #[clap(long, default_value_t = 0)]
     pub starting_iteration: usize,
+    /// accumulate the matched edges of all shots into an edge-usage heatmap at visualize/data/<heatmap_filename.json>
+    #[clap(long)]
+    pub heatmap_filename: Option<String>,
+    /// render the decoding graph, the syndrome and the correction of the last shot to an SVG or PNG file
+    #[clap(long)]
+    pub render_static: Option<String>,
 }
 
 #[derive(Subcommand, Clone, Derivative)]
@@ -102,6 +120,14 @@ pub enum Commands {
     Benchmark(BenchmarkParameters),
     #[cfg(feature = "qecp_integrate")]
     Qecp(qecp::cli::BenchmarkParameters),
+    /// serve decoding requests over a socket, see [`crate::server`] for the protocol
+    Server(ServerParameters),
+    /// decode the shots in a file and write the corrections to another file
+    Decode(DecodeParameters),
+    /// compare the weights of two solver configurations on sampled or supplied shots
+    Verify(VerifyParameters),
+    /// profile a partition configuration on a calibration workload and write a revised one with balanced unit runtimes
+    TunePartition(TunePartitionParameters),
     /// built-in tests
     Test {
         #[clap(subcommand)]
@@ -121,6 +147,107 @@ pub struct VisualizeSyndromesParameters {
     pub visualizer_filename: String,
 }
 
+#[derive(Parser, Clone, Debug)]
+pub struct DecodeParameters {
+    /// the decoding graph, a [`SolverInitializer`] in JSON format
+    #[clap(long)]
+    pub graph: String,
+    /// the shots in the b8 format, with one bit for each real vertex in the increasing order of vertex index
+    #[clap(long)]
+    pub syndromes: String,
+    /// the corrections in the b8 format, with one bit for each edge
+    #[clap(long)]
+    pub output: String,
+    /// the number of threads
+    #[clap(long, default_value_t = 1)]
+    pub parallel: usize,
+    /// `none` decodes the shots independently with serial solvers, `auto` partitions the decoding graph into `parallel`
+    /// units for the parallel solver, otherwise the path of an [`ExternalPartition`] in JSON format
+    #[clap(long, default_value_t = ("none").to_string())]
+    pub partition: String,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct VerifyParameters {
+    /// the decoding graph, a [`SolverInitializer`] in JSON format
+    #[clap(long)]
+    pub graph: String,
+    /// the reference solver configuration, a [`SolverConfig`] in JSON format
+    #[clap(long, default_value_t = ("{\"type\":\"serial\"}").to_string())]
+    pub config_a: String,
+    /// the solver configuration under test, a [`SolverConfig`] in JSON format
+    #[clap(long)]
+    pub config_b: String,
+    /// the shots in the b8 format as in the `decode` command; random shots are sampled if not given
+    #[clap(long)]
+    pub syndromes: Option<String>,
+    /// the number of sampled shots
+    #[clap(long, default_value_t = 1000)]
+    pub shots: usize,
+    /// the probability of an error on each edge when sampling
+    #[clap(short = 'p', long, default_value_t = 0.05)]
+    pub error_probability: f64,
+    /// the probability of an edge being erased when sampling
+    #[clap(long, default_value_t = 0.)]
+    pub erasure_probability: f64,
+    /// the seed of sampling
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+    /// save the minimized reproducer of each mismatch as a JSON file in this folder, see [`ConfigMismatch`]
+    #[clap(long)]
+    pub dump_folder: Option<String>,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct TunePartitionParameters {
+    /// the decoding graph, a [`SolverInitializer`] in JSON format, in the vertex order of the partition configuration
+    #[clap(long)]
+    pub graph: String,
+    /// the partition configuration to start with, see [`PartitionConfigFile`]
+    #[clap(long)]
+    pub partition: String,
+    /// the number of vertices in each layer, see [`PartitionConfig::rebalance_layers`]
+    #[clap(long)]
+    pub layer_vertex_num: VertexNum,
+    /// the revised partition configuration, in TOML if the name ends with `.toml` and in JSON otherwise
+    #[clap(long)]
+    pub output: String,
+    /// the calibration shots in the b8 format as in the `decode` command; random shots are sampled if not given
+    #[clap(long)]
+    pub syndromes: Option<String>,
+    /// the number of sampled shots
+    #[clap(long, default_value_t = 1000)]
+    pub shots: usize,
+    /// the probability of an error on each edge when sampling
+    #[clap(short = 'p', long, default_value_t = 0.05)]
+    pub error_probability: f64,
+    /// the seed of sampling
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+    /// the number of profiling rounds, each moving the boundaries further
+    #[clap(long, default_value_t = 3)]
+    pub iterations: usize,
+    /// the configuration of the parallel solver used in calibration, as in [`SolverParallel::new`]
+    #[clap(long, default_value_t = ("{}").to_string())]
+    pub primal_dual_config: String,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct ServerParameters {
+    /// the solver initializer file in JSON format
+    #[clap(value_parser)]
+    pub initializer: String,
+    /// the TCP address to listen on, e.g. 127.0.0.1:8300
+    #[clap(long)]
+    pub tcp: Option<String>,
+    /// the Unix socket path to listen on
+    #[clap(long)]
+    pub unix: Option<String>,
+    /// the number of solvers, i.e. the maximum number of syndromes decoded at the same time
+    #[clap(long, default_value_t = 4)]
+    pub solver_num: usize,
+}
+
 #[derive(Subcommand, Clone, Debug)]
 pub enum TestCommands {
     /// test serial implementation
@@ -193,10 +320,19 @@ pub enum ExampleCodeType {
     CircuitLevelPlanarCodeParallel,
     /// read from error pattern file, generated using option `--primal-dual-type error-pattern-logger`
     ErrorPatternReader,
+    /// read code layout from a JSON file of `CodeDescription`, pass `{"filename": "..."}` using `--code-config`
+    CodeFromFile,
     /// rotated surface code with perfect stabilizer measurement
     CodeCapacityRotatedCode,
     /// rotated surface code with phenomenological noise model
     PhenomenologicalRotatedCode,
+    /// heavy-hexagon lattice with perfect stabilizer measurement
+    CodeCapacityHeavyHexCode,
+    /// honeycomb (Floquet) code with perfect stabilizer measurement
+    CodeCapacityHoneycombCode,
+    /// XZZX surface code with perfect stabilizer measurement under biased noise, pass `{"bias_eta": 100}` using `--code-config`
+    #[serde(rename = "code-capacity-xzzx-code")]
+    CodeCapacityXZZXCode,
     /// code constructed by QEC-Playground, pass configurations using `--code-config`
     #[serde(rename = "qec-playground-code")]
     QECPlaygroundCode,
@@ -216,6 +352,8 @@ pub enum PartitionStrategy {
     PhenomenologicalPlanarCodeTimePartition,
     /// partition a phenomenological (or circuit-level) rotated code with time axis
     PhenomenologicalRotatedCodeTimePartition,
+    /// partition a phenomenological repetition code with time axis
+    PhenomenologicalRepetitionCodeTimePartition,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Debug)]
@@ -240,6 +378,8 @@ pub enum Verifier {
     BlossomV,
     /// use the serial version of fusion algorithm to verify the correctness of result
     FusionSerial,
+    /// enumerate all possible matchings to verify the correctness of result, only for tiny instances
+    BruteForce,
 }
 
 pub struct RunnableBenchmarkParameters {
@@ -268,6 +408,9 @@ impl From<BenchmarkParameters> for RunnableBenchmarkParameters {
             code_config,
             partition_config,
             benchmark_profiler_output,
+            pe_file,
+            error_rate_file,
+            erasure_burst,
             ..
         } = parameters.clone();
         let code_config: serde_json::Value = serde_json::from_str(&code_config).unwrap();
@@ -281,6 +424,21 @@ impl From<BenchmarkParameters> for RunnableBenchmarkParameters {
         if pe != 0. {
             code.set_erasure_probability(pe);
         }
+        if let Some(pe_file) = pe_file {
+            let pes = std::fs::read_to_string(pe_file).expect("cannot read erasure probability file");
+            let pes: Vec<f64> = serde_json::from_str(&pes).expect("erasure probability file must be a JSON list");
+            code.set_erasure_probabilities(&pes);
+        }
+        if let Some(error_rate_file) = error_rate_file {
+            let error_rate_map = std::fs::read_to_string(error_rate_file).expect("cannot read error rate file");
+            let error_rate_map: ErrorRateMap =
+                serde_json::from_str(&error_rate_map).expect("error rate file must be a JSON object keyed by edge index");
+            code.with_error_rate_map(&error_rate_map, max_half_weight).unwrap();
+        }
+        if let Some(erasure_burst) = erasure_burst {
+            let burst: ErasureBurst = serde_json::from_str(&erasure_burst).expect("invalid erasure burst");
+            code = Box::new(ErasureBurstCode::new(code, burst));
+        }
         if enable_visualizer {
             // print visualizer file path only once
             print_visualize_link(visualizer_filename.clone());
@@ -318,6 +476,8 @@ impl RunnableBenchmarkParameters {
                     pb_message,
                     enable_visualizer,
                     visualizer_filename,
+                    heatmap_filename,
+                    render_static,
                     ..
                 },
         } = self;
@@ -346,6 +506,8 @@ impl RunnableBenchmarkParameters {
             .unwrap();
             visualizer = Some(new_visualizer);
         }
+        let mut heatmap = heatmap_filename.as_ref().map(|_| EdgeHeatmap::new(&code.get_initializer()));
+        let mut last_shot = None;
         for round in (starting_iteration as u64)..(total_rounds as u64) {
             pb.as_mut().map(|pb| pb.set(round));
             let seed = if use_deterministic_seed { round } else { rng.gen() };
@@ -357,6 +519,12 @@ impl RunnableBenchmarkParameters {
             primal_dual_solver.solve_visualizer(&syndrome_pattern, visualizer.as_mut());
             benchmark_profiler.event("decoded".to_string());
             result_verifier.verify(&mut primal_dual_solver, &syndrome_pattern, visualizer.as_mut());
+            if let Some(heatmap) = heatmap.as_mut() {
+                heatmap.add_subgraph(&primal_dual_solver.subgraph());
+            }
+            if render_static.is_some() {
+                last_shot = Some((syndrome_pattern.defect_vertices.clone(), primal_dual_solver.subgraph()));
+            }
             benchmark_profiler.event("verified".to_string());
             primal_dual_solver.clear(); // also count the clear operation
             benchmark_profiler.end(Some(&*primal_dual_solver));
@@ -367,6 +535,20 @@ impl RunnableBenchmarkParameters {
                 }
             }
         }
+        if let (Some(heatmap), Some(heatmap_filename)) = (heatmap, heatmap_filename) {
+            let mut heatmap_visualizer = Visualizer::new(
+                Some(visualize_data_folder() + heatmap_filename.as_str()),
+                code.get_positions(),
+                true,
+            )
+            .unwrap();
+            heatmap_visualizer.snapshot("heatmap".to_string(), &heatmap).unwrap();
+            print_visualize_link(heatmap_filename);
+        }
+        if let (Some((defect_vertices, subgraph)), Some(render_static)) = (last_shot, render_static) {
+            let (initializer, positions) = (code.get_initializer(), code.get_positions());
+            crate::visualize::render_static(&initializer, &positions, &defect_vertices, &subgraph, &render_static).unwrap();
+        }
         if disable_progress_bar {
             // always print out brief
             println!("{}", benchmark_profiler.brief());
@@ -379,6 +561,173 @@ impl RunnableBenchmarkParameters {
     }
 }
 
+fn read_graph(filepath: &str) -> Result<SolverInitializer, String> {
+    let initializer = std::fs::read_to_string(filepath).map_err(|err| format!("cannot read graph: {err}"))?;
+    serde_json::from_str(&initializer).map_err(|err| format!("invalid graph: {err}"))
+}
+
+/// read the shots in the b8 format, with one bit for each real vertex in the increasing order of vertex index
+#[allow(clippy::unnecessary_cast)]
+fn read_b8_syndromes(initializer: &SolverInitializer, filepath: &str) -> Result<Vec<SyndromePattern>, String> {
+    let shots = std::fs::read(filepath).map_err(|err| format!("cannot read syndromes: {err}"))?;
+    let mut is_virtual = vec![false; initializer.vertex_num as usize];
+    for &vertex_index in initializer.virtual_vertices.iter() {
+        is_virtual[vertex_index as usize] = true;
+    }
+    let real_vertices: Vec<VertexIndex> = (0..initializer.vertex_num)
+        .filter(|&vertex_index| !is_virtual[vertex_index as usize])
+        .collect();
+    Ok(read_b8_shots(&shots, real_vertices.len())?
+        .into_iter()
+        .map(|bits| SyndromePattern::new_vertices(bits.into_iter().map(|bit| real_vertices[bit]).collect()))
+        .collect())
+}
+
+impl DecodeParameters {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn run(self) -> Result<(), String> {
+        let initializer = read_graph(&self.graph)?;
+        let syndrome_patterns = read_b8_syndromes(&initializer, &self.syndromes)?;
+        let partition = match self.partition.as_str() {
+            "none" => None,
+            "auto" => Some(ExternalPartition::auto(&initializer, self.parallel)),
+            filepath => {
+                let partition = std::fs::read_to_string(filepath).map_err(|err| format!("cannot read partition: {err}"))?;
+                Some(serde_json::from_str(&partition).map_err(|err| format!("invalid partition: {err}"))?)
+            }
+        };
+        let subgraphs: Vec<Vec<EdgeIndex>> = match partition {
+            None => SolverPool::new(&initializer, self.parallel).solve_batch(&syndrome_patterns),
+            Some(partition) => {
+                // the edges keep their indices in the reordered decoding graph, only the defect vertices are translated
+                let (reordered_vertices, reordered_initializer, partition_config) = partition.build(&initializer)?;
+                let thread_pool_size = json!({ "thread_pool_size": self.parallel });
+                let primal_dual_config = json!({ "dual": thread_pool_size, "primal": thread_pool_size });
+                let mut solver = SolverParallel::new(&reordered_initializer, &partition_config.info(), primal_dual_config);
+                (syndrome_patterns.iter())
+                    .map(|syndrome_pattern| {
+                        let mut defect_vertices =
+                            translated_defect_to_reordered(&reordered_vertices, &syndrome_pattern.defect_vertices);
+                        defect_vertices.sort();
+                        solver.solve(&SyndromePattern::new_vertices(defect_vertices));
+                        let subgraph = solver.subgraph();
+                        solver.clear();
+                        subgraph
+                    })
+                    .collect()
+            }
+        };
+        let corrections: Vec<Vec<usize>> = (subgraphs.into_iter())
+            .map(|subgraph| subgraph.into_iter().map(|edge_index| edge_index as usize).collect())
+            .collect();
+        std::fs::write(&self.output, write_b8_shots(&corrections, initializer.weighted_edges.len()))
+            .map_err(|err| format!("cannot write corrections: {err}"))
+    }
+}
+
+impl VerifyParameters {
+    /// returns the number of mismatches
+    pub fn run(self) -> Result<usize, String> {
+        let initializer = read_graph(&self.graph)?;
+        let parse_config = |config: &str| -> Result<SolverConfig, String> {
+            serde_json::from_str(config).map_err(|err| format!("invalid solver config: {err}"))
+        };
+        let (config_a, config_b) = (parse_config(&self.config_a)?, parse_config(&self.config_b)?);
+        let syndrome_patterns = match self.syndromes.as_ref() {
+            Some(filepath) => read_b8_syndromes(&initializer, filepath)?,
+            None => {
+                let fuzz_config = FuzzConfig {
+                    error_probability: self.error_probability,
+                    erasure_probability: self.erasure_probability,
+                    ..Default::default()
+                };
+                let mut fuzzer = Fuzzer::new(fuzz_config, self.seed);
+                (0..self.shots).map(|_| fuzzer.generate_syndrome(&initializer)).collect()
+            }
+        };
+        if let Some(dump_folder) = self.dump_folder.as_ref() {
+            std::fs::create_dir_all(dump_folder).map_err(|err| format!("cannot create dump folder: {err}"))?;
+        }
+        let mut solver_a = config_a.build(&initializer);
+        let mut solver_b = config_b.build(&initializer);
+        let mut mismatch_num = 0;
+        for (shot_index, syndrome_pattern) in syndrome_patterns.iter().enumerate() {
+            let Err(reason) = compare_solvers(solver_a.as_mut(), solver_b.as_mut(), syndrome_pattern) else {
+                continue;
+            };
+            mismatch_num += 1;
+            let mismatch = ConfigMismatch {
+                initializer: initializer.clone(),
+                config_a: config_a.clone(),
+                config_b: config_b.clone(),
+                syndrome_pattern: syndrome_pattern.clone(),
+                reason,
+            }
+            .shrink();
+            println!(
+                "shot {shot_index}: {}, minimized to {} defect vertices and {} erasures",
+                mismatch.reason,
+                mismatch.syndrome_pattern.defect_vertices.len(),
+                mismatch.syndrome_pattern.erasures.len()
+            );
+            if let Some(dump_folder) = self.dump_folder.as_ref() {
+                let filepath = format!("{dump_folder}/mismatch_{shot_index}.json");
+                std::fs::write(&filepath, serde_json::to_string(&mismatch).unwrap())
+                    .map_err(|err| format!("cannot write reproducer: {err}"))?;
+                println!("    reproducer saved to {filepath}");
+            }
+            // a solver may be left in an invalid state by a panic
+            solver_a = config_a.build(&initializer);
+            solver_b = config_b.build(&initializer);
+        }
+        println!("{} shots, {mismatch_num} mismatches", syndrome_patterns.len());
+        Ok(mismatch_num)
+    }
+}
+
+impl TunePartitionParameters {
+    /// returns the profile of each tuning round
+    pub fn run(self) -> Result<Vec<PartitionProfile>, String> {
+        let initializer = read_graph(&self.graph)?;
+        let (config, reordered_vertices) = PartitionConfig::from_file(&self.partition)?;
+        if reordered_vertices.is_some() {
+            return Err("the graph must be given in the partitioned vertex order, without reordering".to_string());
+        }
+        if config.vertex_num != initializer.vertex_num {
+            return Err(format!(
+                "partition has {} vertices but the graph has {}",
+                config.vertex_num, initializer.vertex_num
+            ));
+        }
+        let primal_dual_config: serde_json::Value =
+            serde_json::from_str(&self.primal_dual_config).map_err(|err| format!("invalid primal dual config: {err}"))?;
+        let syndrome_patterns = match self.syndromes.as_ref() {
+            Some(filepath) => read_b8_syndromes(&initializer, filepath)?,
+            None => {
+                // the parallel solver doesn't support erasures
+                let fuzz_config = FuzzConfig {
+                    error_probability: self.error_probability,
+                    erasure_probability: 0.,
+                    ..Default::default()
+                };
+                let mut fuzzer = Fuzzer::new(fuzz_config, self.seed);
+                (0..self.shots).map(|_| fuzzer.generate_syndrome(&initializer)).collect()
+            }
+        };
+        let tuner = PartitionTuner::new(&initializer, self.layer_vertex_num, primal_dual_config);
+        let result = tuner.tune(&config, &syndrome_patterns, self.iterations)?;
+        for (round_index, profile) in result.rounds.iter().enumerate() {
+            println!(
+                "round {round_index}: critical path {:.3e}s, leaf skew {:.3}, partitions {:?}, fusions {:?}",
+                profile.critical_path_time, profile.leaf_skew, profile.config.partitions, profile.config.fusions
+            );
+        }
+        result.best.to_file(&self.output, None)?;
+        println!("revised partition saved to {}", self.output);
+        Ok(result.rounds)
+    }
+}
+
 impl Cli {
     pub fn run(self) {
         match self.command {
@@ -416,6 +765,35 @@ impl Cli {
                 .collect();
                 execute_in_cli(command.iter(), true);
             }
+            Commands::Server(parameters) => {
+                let initializer = std::fs::read_to_string(&parameters.initializer).expect("cannot read initializer file");
+                let initializer: SolverInitializer = serde_json::from_str(&initializer).expect("invalid initializer file");
+                let server = crate::server::DecodingServer::new(&initializer, parameters.solver_num);
+                match (parameters.tcp, parameters.unix) {
+                    (Some(address), None) => {
+                        let listener = std::net::TcpListener::bind(address).expect("cannot bind TCP address");
+                        server.serve_tcp(listener).unwrap();
+                    }
+                    #[cfg(unix)]
+                    (None, Some(path)) => {
+                        let listener = std::os::unix::net::UnixListener::bind(path).expect("cannot bind Unix socket");
+                        server.serve_unix(listener).unwrap();
+                    }
+                    _ => panic!("exactly one of --tcp and --unix must be given"),
+                }
+            }
+            Commands::Decode(parameters) => {
+                parameters.run().unwrap();
+            }
+            Commands::Verify(parameters) => {
+                let mismatch_num = parameters.run().unwrap();
+                if mismatch_num > 0 {
+                    std::process::exit(1);
+                }
+            }
+            Commands::TunePartition(parameters) => {
+                parameters.run().unwrap();
+            }
             Commands::Test { command } => {
                 match command {
                     TestCommands::Serial {
@@ -819,6 +1197,10 @@ impl ExampleCodeType {
                 assert_eq!(code_config, json!({}), "config not supported");
                 Box::new(CodeCapacityRepetitionCode::new(d, p, max_half_weight))
             }
+            Self::PhenomenologicalRepetitionCode => {
+                assert_eq!(code_config, json!({}), "config not supported");
+                Box::new(PhenomenologicalRepetitionCode::new(d, noisy_measurements, p, max_half_weight))
+            }
             Self::CodeCapacityPlanarCode => {
                 assert_eq!(code_config, json!({}), "config not supported");
                 Box::new(CodeCapacityPlanarCode::new(d, p, max_half_weight))
@@ -854,6 +1236,7 @@ impl ExampleCodeType {
                 ))
             }
             Self::ErrorPatternReader => Box::new(ErrorPatternReader::new(code_config)),
+            Self::CodeFromFile => Box::new(CodeFromFile::new(code_config, max_half_weight)),
             Self::CodeCapacityRotatedCode => {
                 assert_eq!(code_config, json!({}), "config not supported");
                 Box::new(CodeCapacityRotatedCode::new(d, p, max_half_weight))
@@ -862,6 +1245,27 @@ impl ExampleCodeType {
                 assert_eq!(code_config, json!({}), "config not supported");
                 Box::new(PhenomenologicalRotatedCode::new(d, noisy_measurements, p, max_half_weight))
             }
+            Self::CodeCapacityHeavyHexCode => {
+                assert_eq!(code_config, json!({}), "config not supported");
+                Box::new(CodeCapacityHeavyHexCode::new(d, p, max_half_weight))
+            }
+            Self::CodeCapacityHoneycombCode => {
+                assert_eq!(code_config, json!({}), "config not supported");
+                Box::new(CodeCapacityHoneycombCode::new(d, p, max_half_weight))
+            }
+            Self::CodeCapacityXZZXCode => {
+                let mut bias_eta = 0.5;
+                let config = code_config.as_object_mut().expect("config must be JSON object");
+                if let Some(value) = config.remove("bias_eta") {
+                    bias_eta = value.as_f64().expect("bias_eta number");
+                }
+                assert!(
+                    config.is_empty(),
+                    "unknown config keys: {:?}",
+                    config.keys().collect::<Vec<_>>()
+                );
+                Box::new(CodeCapacityXZZXCode::new(d, p, bias_eta, max_half_weight))
+            }
             #[cfg(feature = "qecp_integrate")]
             Self::QECPlaygroundCode => Box::new(QECPlaygroundCode::new(d as usize, p, code_config)),
             _ => unimplemented!(),
@@ -947,6 +1351,18 @@ impl PartitionStrategy {
                 )
                 .build_apply(code)
             }
+            Self::PhenomenologicalRepetitionCodeTimePartition => {
+                let config = partition_config.as_object_mut().expect("config must be JSON object");
+                let mut partition = PhenomenologicalRepetitionCodeTimePartition::auto(d, noisy_measurements);
+                if let Some(value) = config.remove("partition_num") {
+                    let partition_num = value.as_u64().expect("partition_num: usize") as usize;
+                    partition = PhenomenologicalRepetitionCodeTimePartition::new(d, noisy_measurements, partition_num);
+                }
+                if !config.is_empty() {
+                    panic!("unknown config keys: {:?}", config.keys().collect::<Vec<&String>>());
+                }
+                partition.build_apply(code)
+            }
         };
         (code.get_initializer(), partition_config)
     }
@@ -991,6 +1407,7 @@ impl Verifier {
                 subgraph_builder: SubGraphBuilder::new(initializer),
             }),
             Self::FusionSerial => Box::new(VerifierFusionSerial::new(initializer)),
+            Self::BruteForce => Box::new(VerifierBruteForce::new(initializer)),
         }
     }
 }
@@ -1144,3 +1561,46 @@ impl ResultVerifier for VerifierFusionSerial {
         }
     }
 }
+
+pub struct VerifierBruteForce {
+    pub decoder: BruteForceDecoder,
+    pub subgraph_builder: SubGraphBuilder,
+}
+
+impl VerifierBruteForce {
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        Self {
+            decoder: BruteForceDecoder::new(initializer),
+            subgraph_builder: SubGraphBuilder::new(initializer),
+        }
+    }
+}
+
+impl ResultVerifier for VerifierBruteForce {
+    fn verify(
+        &mut self,
+        primal_dual_solver: &mut Box<dyn PrimalDualSolver>,
+        syndrome_pattern: &SyndromePattern,
+        visualizer: Option<&mut Visualizer>,
+    ) {
+        let standard_total_weight = self.decoder.solve_weight(syndrome_pattern);
+        assert_eq!(
+            primal_dual_solver.sum_dual_variables(),
+            standard_total_weight,
+            "unexpected final dual variable sum"
+        );
+        self.subgraph_builder.clear();
+        self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+        self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        let mwpm = primal_dual_solver.perfect_matching();
+        self.subgraph_builder.load_perfect_matching(&mwpm);
+        assert_eq!(
+            self.subgraph_builder.total_weight(),
+            standard_total_weight,
+            "unexpected perfect matching weight"
+        );
+        if visualizer.is_some() {
+            primal_dual_solver.subgraph_visualizer(visualizer);
+        }
+    }
+}
@@ -318,6 +318,83 @@ impl PrebuiltCompleteGraph {
     }
 }
 
+/// shortest-path trees rooted at every vertex, pre-computed from a [`SolverInitializer`] so that a minimum-weight path
+/// can be reconstructed in O(path length) instead of running Dijkstra's algorithm for every matched pair;
+/// it takes O(|V|^2) memory and only applies to the original weights, i.e. without erasures or dynamic weights
+#[derive(Debug, Clone)]
+pub struct ShortestPathTrees {
+    /// number of vertices
+    pub vertex_num: VertexNum,
+    /// `trees[a][b]` is the previous vertex of `b` on the path from `a` and the distance between `a` and `b`;
+    /// `(VertexIndex::MAX, Weight::MAX)` if `b` is not reachable from `a` or `a == b`
+    trees: Vec<Vec<(VertexIndex, Weight)>>,
+}
+
+impl ShortestPathTrees {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new_threaded(initializer: &SolverInitializer, thread_pool_size: usize) -> Self {
+        let mut thread_pool_builder = rayon::ThreadPoolBuilder::new();
+        if thread_pool_size != 0 {
+            thread_pool_builder = thread_pool_builder.num_threads(thread_pool_size);
+        }
+        let thread_pool = thread_pool_builder.build().expect("creating thread pool failed");
+        let vertex_num = initializer.vertex_num as usize;
+        let mut trees: Vec<Vec<(VertexIndex, Weight)>> = vec![];
+        thread_pool.scope(|_| {
+            (0..vertex_num)
+                .into_par_iter()
+                .map_init(
+                    || CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges),
+                    |complete_graph, vertex_index| {
+                        let mut tree = vec![(VertexIndex::MAX, Weight::MAX); vertex_num];
+                        for (peer, previous_weight) in complete_graph.all_edges(vertex_index as VertexIndex) {
+                            tree[peer as usize] = previous_weight;
+                        }
+                        tree
+                    },
+                )
+                .collect_into_vec(&mut trees);
+        });
+        Self {
+            vertex_num: initializer.vertex_num,
+            trees,
+        }
+    }
+
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        Self::new_threaded(initializer, 0)
+    }
+
+    /// the minimum weight between two different vertices, `None` if not connected
+    #[allow(clippy::unnecessary_cast)]
+    pub fn get_distance(&self, a: VertexIndex, b: VertexIndex) -> Option<Weight> {
+        let (_, weight) = self.trees[a as usize][b as usize];
+        if weight == Weight::MAX {
+            None
+        } else {
+            Some(weight)
+        }
+    }
+
+    /// the same as [`CompleteGraph::get_path`], but without running Dijkstra's algorithm
+    #[allow(clippy::unnecessary_cast)]
+    pub fn get_path(&self, a: VertexIndex, b: VertexIndex) -> (Vec<(VertexIndex, Weight)>, Weight) {
+        assert_ne!(a, b, "cannot get path between the same vertex");
+        let tree = &self.trees[a as usize];
+        let total_weight = self.get_distance(a, b).expect("vertices are not connected");
+        let mut path = Vec::new();
+        let mut vertex = b;
+        while vertex != a {
+            let (previous, weight) = tree[vertex as usize];
+            let previous_weight = if previous == a { 0 } else { tree[previous as usize].1 };
+            path.push((vertex, weight - previous_weight));
+            vertex = previous;
+        }
+        path.reverse();
+        (path, total_weight)
+    }
+}
+
 #[derive(Eq, Debug)]
 pub struct PriorityElement {
     pub weight: Weight,
@@ -350,3 +427,29 @@ impl PriorityElement {
         Self { weight, previous }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::*;
+
+    #[test]
+    fn complete_graph_shortest_path_trees() {
+        // cargo test complete_graph_shortest_path_trees -- --nocapture
+        let code = PhenomenologicalPlanarCode::new(5, 5, 0.1, 500);
+        let initializer = code.get_initializer();
+        let shortest_path_trees = ShortestPathTrees::new(&initializer);
+        let mut complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges);
+        for a in (0..initializer.vertex_num).step_by(7) {
+            for b in (0..initializer.vertex_num).step_by(3) {
+                if a != b {
+                    assert_eq!(
+                        shortest_path_trees.get_path(a, b),
+                        complete_graph.get_path(a, b),
+                        "{a} -> {b}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -8,12 +8,15 @@
 use core::cmp::Ordering;
 use std::collections::{BTreeMap, HashSet};
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 #[cfg(not(feature = "dangerous_pointer"))]
 use std::sync::Arc;
 
 use nonzero::nonzero as nz;
+use serde::{Deserialize, Serialize};
 
 use crate::derivative::Derivative;
+use crate::weak_table::PtrWeakHashSet;
 
 use super::pointers::*;
 use super::util::*;
@@ -39,7 +42,7 @@ impl DualNodeClass {
 }
 
 /// Three possible states: Grow (+1), Stay (+0), Shrink (-1)
-#[derive(Derivative, PartialEq, Eq, Clone, Copy)]
+#[derive(Derivative, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 #[derivative(Debug)]
 pub enum DualNodeGrowState {
     Grow,
@@ -57,7 +60,7 @@ impl DualNodeGrowState {
 }
 
 /// synchronize request on vertices, when a vertex is mirrored
-#[derive(Derivative)]
+#[derive(Derivative, Clone)]
 #[derivative(Debug)]
 pub struct SyncRequest {
     /// the unit that owns this vertex
@@ -84,26 +87,52 @@ impl SyncRequest {
     }
 }
 
+/// a compact reference to a dual node that doesn't keep it alive: the index of the node in its home interface and the epoch
+/// of that interface; it stays valid after fusion and is resolved by [`DualModuleInterface::get_node_by_key`]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DualNodeKey {
+    /// the index of the dual node in its home interface, i.e. [`DualNode::home_index`]
+    pub index: NodeIndex,
+    /// the epoch of the home interface when the dual node is created, see [`DualModuleInterface::epoch`]
+    pub epoch: usize,
+}
+
+impl std::fmt::Debug for DualNodeKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}@{}", self.index, self.epoch)
+    }
+}
+
+/// every interface takes a new epoch when created or cleared, so that the keys of a previous decoding problem never resolve
+static INTERFACE_EPOCH: AtomicUsize = AtomicUsize::new(0);
+
+fn next_interface_epoch() -> usize {
+    INTERFACE_EPOCH.fetch_add(1, AtomicOrdering::Relaxed)
+}
+
 /// gives the maximum absolute length to grow, if not possible, give the reason;
-/// note that strong reference is stored in `MaxUpdateLength` so dropping these temporary messages are necessary to avoid memory leakage;
-/// the strong reference is required when multiple `BlossomNeedExpand` event is reported in different partitions and sorting them requires a reference
+/// the dual nodes are referred to by [`DualNodeKey`] so that queuing many conflicts neither keeps removed nodes alive nor
+/// touches the reference counts; the primal module resolves them by [`MaxUpdateLength::resolve`] when handling a conflict
 #[derive(Derivative, PartialEq, Eq, Clone)]
 #[derivative(Debug)]
-pub enum MaxUpdateLength {
+pub enum MaxUpdateLength<N = DualNodeKey> {
     /// non-zero maximum update length, has_empty_boundary_node (useful in fusion)
     NonZeroGrow((Weight, bool)),
     /// conflicting growth
-    Conflicting((DualNodePtr, DualNodePtr), (DualNodePtr, DualNodePtr)), // (node_1, touching_1), (node_2, touching_2)
+    Conflicting((N, N), (N, N)), // (node_1, touching_1), (node_2, touching_2)
     /// conflicting growth because of touching virtual node
-    TouchingVirtual((DualNodePtr, DualNodePtr), (VertexIndex, bool)), // (node, touching), (virtual_vertex, is_mirror)
+    TouchingVirtual((N, N), (VertexIndex, bool)), // (node, touching), (virtual_vertex, is_mirror)
     /// blossom hitting 0 dual variable while shrinking
-    BlossomNeedExpand(DualNodePtr),
+    BlossomNeedExpand(N),
     /// node hitting 0 dual variable while shrinking: note that this should have the lowest priority, normally it won't show up in a normal primal module;
     /// in case that the dual module is partitioned and nobody can report this conflicting event, one needs to embed the potential conflicts using the second
     /// argument so that dual module can gather two `VertexShrinkStop` events to form a single `Conflicting` event
-    VertexShrinkStop((DualNodePtr, Option<(DualNodePtr, DualNodePtr)>)),
+    VertexShrinkStop((N, Option<(N, N)>)),
 }
 
+/// a [`MaxUpdateLength`] with the dual nodes resolved in the current interface, which is what the primal module handles
+pub type ResolvedMaxUpdateLength = MaxUpdateLength<DualNodePtr>;
+
 cfg_if::cfg_if! {
     if #[cfg(feature="ordered_conflicts")] {
         use std::collections::BinaryHeap;
@@ -118,8 +147,10 @@ cfg_if::cfg_if! {
 pub enum GroupMaxUpdateLength {
     /// non-zero maximum update length, has_empty_boundary_node (useful in fusion)
     NonZeroGrow((Weight, bool)),
-    /// conflicting reasons and pending VertexShrinkStop events (empty in a single serial dual module)
-    Conflicts((ConflictList, BTreeMap<VertexIndex, MaxUpdateLength>)),
+    /// conflicting reasons and pending VertexShrinkStop events of each dual node (empty in a single serial dual module);
+    /// the pending events are keyed by the dual node rather than its representative vertex, so that the events of a blossom and
+    /// its first child, which share the representative vertex, are never merged into a `Conflicting` event
+    Conflicts((ConflictList, BTreeMap<DualNodeKey, MaxUpdateLength>)),
 }
 
 impl Default for GroupMaxUpdateLength {
@@ -133,42 +164,25 @@ impl GroupMaxUpdateLength {
         Self::NonZeroGrow((Weight::MAX, false))
     }
 
-    /// update all the interface nodes to be up-to-date, only necessary in existence of fusion
-    #[inline(never)]
-    pub fn update(&self) {
-        if let Self::Conflicts((list, pending_stops)) = &self {
-            for max_update_length in list.iter() {
-                max_update_length.update();
-            }
-            for (_, max_update_length) in pending_stops.iter() {
-                max_update_length.update();
-            }
-        }
-    }
-
+    /// a `VertexShrinkStop` event is merged with the pending one of the same dual node, see [`MaxUpdateLength::merge_vertex_shrink_stops`]
     pub fn add_pending_stop(
         list: &mut ConflictList,
-        pending_stops: &mut BTreeMap<VertexIndex, MaxUpdateLength>,
+        pending_stops: &mut BTreeMap<DualNodeKey, MaxUpdateLength>,
         max_update_length: MaxUpdateLength,
     ) {
-        if let Some(dual_node_ptr) = max_update_length.get_vertex_shrink_stop() {
-            let vertex_index = dual_node_ptr.get_representative_vertex();
-            if let Some(existing_length) = pending_stops.get(&vertex_index) {
-                if let MaxUpdateLength::VertexShrinkStop((_, Some(weak_pair))) = &max_update_length {
-                    // otherwise don't update
-                    if let MaxUpdateLength::VertexShrinkStop((_, Some(existing_weak_pair))) = existing_length {
-                        if weak_pair.0 != existing_weak_pair.0 {
-                            // two such conflicts form a Conflicting event
-                            list.push(MaxUpdateLength::Conflicting(weak_pair.clone(), existing_weak_pair.clone()));
-                            pending_stops.remove(&vertex_index);
-                        }
-                    } else {
-                        pending_stops.insert(vertex_index, max_update_length.clone());
-                        // update the entry
-                    }
+        if let Some(node_key) = max_update_length.get_vertex_shrink_stop() {
+            if let Some(existing_length) = pending_stops.get(&node_key) {
+                if let Some(conflicting) = max_update_length.merge_vertex_shrink_stops(existing_length) {
+                    list.push(conflicting);
+                    pending_stops.remove(&node_key);
+                } else if matches!(max_update_length, MaxUpdateLength::VertexShrinkStop((_, Some(_))))
+                    && matches!(existing_length, MaxUpdateLength::VertexShrinkStop((_, None)))
+                {
+                    // a potential conflict is more useful than none, otherwise don't update
+                    pending_stops.insert(node_key, max_update_length);
                 }
             } else {
-                pending_stops.insert(vertex_index, max_update_length);
+                pending_stops.insert(node_key, max_update_length);
             }
         } else {
             list.push(max_update_length);
@@ -185,9 +199,8 @@ impl GroupMaxUpdateLength {
                 } else {
                     let mut list = ConflictList::new();
                     let mut pending_stops = BTreeMap::new();
-                    if let Some(dual_node_ptr) = max_update_length.get_vertex_shrink_stop() {
-                        let vertex_index = dual_node_ptr.get_representative_vertex();
-                        pending_stops.insert(vertex_index, max_update_length);
+                    if let Some(node_key) = max_update_length.get_vertex_shrink_stop() {
+                        pending_stops.insert(node_key, max_update_length);
                     } else {
                         list.push(max_update_length);
                     }
@@ -258,15 +271,25 @@ impl GroupMaxUpdateLength {
                 panic!("please call GroupMaxUpdateLength::get_none_zero_growth to check if this group is none_zero_growth");
             }
             Self::Conflicts((list, pending_stops)) => {
-                list.pop().or(if let Some(key) = pending_stops.keys().next().cloned() {
+                // lazily, otherwise a pending stop would be dropped whenever the list is not empty
+                list.pop().or_else(|| {
+                    let key = pending_stops.keys().next().cloned()?;
                     pending_stops.remove(&key)
-                } else {
-                    None
                 })
             }
         }
     }
 
+    /// the pending [`MaxUpdateLength::VertexShrinkStop`] events in the order they would be popped, without popping them,
+    /// e.g. for a primal module to pair them with [`MaxUpdateLength::merge_vertex_shrink_stops`] in advance
+    pub fn pending_stops(&self) -> impl Iterator<Item = &MaxUpdateLength> {
+        let pending_stops = match self {
+            Self::NonZeroGrow(_) => None,
+            Self::Conflicts((_, pending_stops)) => Some(pending_stops.values()),
+        };
+        pending_stops.into_iter().flatten()
+    }
+
     pub fn peek(&self) -> Option<&MaxUpdateLength> {
         match self {
             Self::NonZeroGrow(_) => {
@@ -306,8 +329,16 @@ pub struct DualNode {
     pub dual_variable_cache: (Weight, Weight),
     /// belonging of the dual module interface; a dual node is never standalone
     pub belonging: DualModuleInterfaceWeak,
+    /// the interface that creates this dual node; unlike `belonging`, it's never updated when the interface is fused
+    pub home_interface: DualModuleInterfaceWeak,
+    /// the index of this dual node in `home_interface`, which stays valid after fusion without any renumbering
+    pub home_index: NodeIndex,
+    /// the epoch of `home_interface` when this dual node is created, see [`DualNodePtr::key`]
+    pub home_epoch: usize,
     /// how many defect vertices in this dual node
     pub defect_size: NonZeroUsize,
+    /// nesting depth of this dual node: 0 for a defect vertex, 1 + the maximum depth of its children for a blossom
+    pub blossom_depth: usize,
 }
 
 impl DualNode {
@@ -398,6 +429,15 @@ impl DualNodePtr {
         self.read_recursive().index
     }
 
+    /// the compact reference of this dual node, which never changes
+    pub fn key(&self) -> DualNodeKey {
+        let node = self.read_recursive();
+        DualNodeKey {
+            index: node.home_index,
+            epoch: node.home_epoch,
+        }
+    }
+
     /// helper function to set grow state with sanity check
     fn set_grow_state(&self, grow_state: DualNodeGrowState) {
         let mut dual_node = self.write();
@@ -501,6 +541,171 @@ pub struct DualModuleInterface {
     /// the two children of this interface, when fused; following the length of this child,
     /// given that fused children interface will not have new nodes anymore
     pub children: Option<((DualModuleInterfaceWeak, NodeIndex), (DualModuleInterfaceWeak, NodeIndex))>,
+    /// the concatenated view of the descendants when fused: the index of the first node of every descendant interface
+    /// that owns nodes, in ascending order, so that locating a node only locks the interface that owns it
+    pub segments: Vec<(NodeIndex, DualModuleInterfaceWeak)>,
+    /// a unique number of this interface that changes whenever it's cleared, used in [`DualNodeKey`]
+    pub epoch: usize,
+    /// the index bias of every descendant interface by its epoch, when fused
+    pub descendant_biases: BTreeMap<usize, NodeIndex>,
+    /// statistics of the blossoms created in this interface (including the fused children)
+    pub blossom_statistics: BlossomStatistics,
+    /// the nodes created by this interface that are currently growing or shrinking, maintained incrementally by
+    /// [`DualModuleInterfacePtr::set_grow_state`]; they stay here after fusion, see [`Self::active_descendants`]
+    #[derivative(Debug = "ignore")]
+    active_nodes: PtrWeakHashSet<DualNodeWeak>,
+    /// every fused descendant interface, whose active nodes are counted as mine; fusion only appends the children
+    /// here, so its cost grows with the number of fused interfaces instead of the number of active nodes
+    pub active_descendants: Vec<DualModuleInterfaceWeak>,
+    /// the behavior when [`DualModuleInterface::sum_dual_variables`] or the global progress overflows; kept when cleared
+    pub weight_overflow: WeightOverflow,
+    /// the first accumulator that overflowed under [`WeightOverflow::Error`], see [`DualModuleInterfacePtr::check_overflow`]
+    overflow: Option<&'static str>,
+}
+
+/// statistics of blossom nesting, useful to understand the worst-case latency of a decoding problem
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlossomStatistics {
+    /// the number of blossoms ever created
+    pub blossom_count: usize,
+    /// the maximum nesting depth of the blossoms, 1 if no blossom contains another blossom
+    pub max_depth: usize,
+    /// the maximum number of nodes in the circle of a blossom
+    pub max_circle_size: usize,
+    /// the maximum number of defect vertices in a blossom
+    pub max_defect_size: usize,
+    /// `depth_histogram[d]` is the number of blossoms created with depth `d`
+    pub depth_histogram: Vec<usize>,
+}
+
+impl BlossomStatistics {
+    pub fn record(&mut self, depth: usize, circle_size: usize, defect_size: usize) {
+        self.blossom_count += 1;
+        self.max_depth = std::cmp::max(self.max_depth, depth);
+        self.max_circle_size = std::cmp::max(self.max_circle_size, circle_size);
+        self.max_defect_size = std::cmp::max(self.max_defect_size, defect_size);
+        if self.depth_histogram.len() <= depth {
+            self.depth_histogram.resize(depth + 1, 0);
+        }
+        self.depth_histogram[depth] += 1;
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.blossom_count += other.blossom_count;
+        self.max_depth = std::cmp::max(self.max_depth, other.max_depth);
+        self.max_circle_size = std::cmp::max(self.max_circle_size, other.max_circle_size);
+        self.max_defect_size = std::cmp::max(self.max_defect_size, other.max_defect_size);
+        if self.depth_histogram.len() < other.depth_histogram.len() {
+            self.depth_histogram.resize(other.depth_histogram.len(), 0);
+        }
+        for (count, other_count) in self.depth_histogram.iter_mut().zip(other.depth_histogram.iter()) {
+            *count += other_count;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.blossom_count = 0;
+        self.max_depth = 0;
+        self.max_circle_size = 0;
+        self.max_defect_size = 0;
+        self.depth_histogram.clear();
+    }
+}
+
+/// the result of splitting the circle of an expanding blossom by its two entry points,
+/// see [`DualModuleInterfacePtr::expand_blossom_with_entries`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlossomExpansionSequences {
+    /// the children matched in pairs, in ascending order of the circle
+    pub match_sequence: Vec<usize>,
+    /// the children on the path from the parent-touching child to the child-touching child
+    pub tree_sequence: Vec<usize>,
+    /// whether the tree sequence follows the ascending order of the circle
+    pub is_tree_sequence_ascending: bool,
+}
+
+/// split the circle of an expanding blossom given the indices of the two entry points; the tree sequence always has odd length
+pub fn blossom_expansion_sequences(
+    circle_len: usize,
+    parent_touching_index: usize,
+    child_touching_index: usize,
+) -> BlossomExpansionSequences {
+    let mut is_tree_sequence_ascending = true;
+    // tree sequence is from parent to child
+    let mut match_sequence = Vec::new();
+    let mut tree_sequence = Vec::new();
+    match parent_touching_index.cmp(&child_touching_index) {
+        Ordering::Equal => {
+            tree_sequence.push(parent_touching_index);
+            for i in parent_touching_index + 1..circle_len {
+                match_sequence.push(i);
+            }
+            for i in 0..parent_touching_index {
+                match_sequence.push(i);
+            }
+        }
+        Ordering::Greater => {
+            if (parent_touching_index - child_touching_index) % 2 == 0 {
+                // [... c <----- p ...]
+                for i in (child_touching_index..parent_touching_index + 1).rev() {
+                    tree_sequence.push(i);
+                }
+                is_tree_sequence_ascending = false;
+                for i in parent_touching_index + 1..circle_len {
+                    match_sequence.push(i);
+                }
+                for i in 0..child_touching_index {
+                    match_sequence.push(i);
+                }
+            } else {
+                // [--> c ...... p ---]
+                for i in parent_touching_index..circle_len {
+                    tree_sequence.push(i);
+                }
+                for i in 0..child_touching_index + 1 {
+                    tree_sequence.push(i);
+                }
+                for i in child_touching_index + 1..parent_touching_index {
+                    match_sequence.push(i);
+                }
+            }
+        }
+        Ordering::Less => {
+            if (child_touching_index - parent_touching_index) % 2 == 0 {
+                // [... p -----> c ...]
+                for i in parent_touching_index..child_touching_index + 1 {
+                    tree_sequence.push(i);
+                }
+                for i in child_touching_index + 1..circle_len {
+                    match_sequence.push(i);
+                }
+                for i in 0..parent_touching_index {
+                    match_sequence.push(i);
+                }
+            } else {
+                // [--- p ...... c <--]
+                for i in (0..parent_touching_index + 1).rev() {
+                    tree_sequence.push(i);
+                }
+                for i in (child_touching_index..circle_len).rev() {
+                    tree_sequence.push(i);
+                }
+                is_tree_sequence_ascending = false;
+                for i in parent_touching_index + 1..child_touching_index {
+                    match_sequence.push(i);
+                }
+            }
+        }
+    }
+    debug_assert!(
+        match_sequence.len() % 2 == 0 && tree_sequence.len() % 2 == 1,
+        "parity of sequence wrong"
+    );
+    BlossomExpansionSequences {
+        match_sequence,
+        tree_sequence,
+        is_tree_sequence_ascending,
+    }
 }
 
 pub type DualModuleInterfacePtr = ArcManualSafeLock<DualModuleInterface>;
@@ -572,8 +777,8 @@ pub trait DualModuleImpl {
         _dual_node_ptr: &DualNodePtr,
         _is_grow: bool,
         _simultaneous_update: bool,
-    ) -> MaxUpdateLength {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+    ) -> Result<MaxUpdateLength, FusionError> {
+        Err(FusionError::UnsupportedOperation("compute_maximum_update_length_dual_node"))
     }
 
     /// check the maximum length to grow (shrink) for all nodes, return a list of conflicting reason and a single number indicating the maximum length to grow:
@@ -581,8 +786,8 @@ pub trait DualModuleImpl {
     fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength;
 
     /// An optional function that can manipulate individual dual node, not necessarily supported by all implementations
-    fn grow_dual_node(&mut self, _dual_node_ptr: &DualNodePtr, _length: Weight) {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+    fn grow_dual_node(&mut self, _dual_node_ptr: &DualNodePtr, _length: Weight) -> Result<(), FusionError> {
+        Err(FusionError::UnsupportedOperation("grow_dual_node"))
     }
 
     /// grow a specific length globally, length must be positive.
@@ -591,26 +796,47 @@ pub trait DualModuleImpl {
 
     /// optional support for edge modifier. for example, erasure errors temporarily set some edges to 0 weight.
     /// When it clears, those edges must be reverted back to the original weight
-    fn load_edge_modifier(&mut self, _edge_modifier: &[(EdgeIndex, Weight)]) {
-        unimplemented!(
-            "load_edge_modifier is an optional interface, and the current dual module implementation doesn't support it"
-        );
+    fn load_edge_modifier(&mut self, _edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
+        Err(FusionError::UnsupportedOperation("load_edge_modifier"))
     }
 
     /// an erasure error means this edge is totally uncertain: p=0.5, so new weight = ln((1-p)/p) = 0
-    fn load_erasures(&mut self, erasures: &[EdgeIndex]) {
+    fn load_erasures(&mut self, erasures: &[EdgeIndex]) -> Result<(), FusionError> {
         let edge_modifier: Vec<_> = erasures.iter().map(|edge_index| (*edge_index, 0)).collect();
-        self.load_edge_modifier(&edge_modifier);
+        self.load_edge_modifier(&edge_modifier)
+    }
+
+    /// erasures with a residual error probability `p` each, e.g. heralded errors that are not fully randomizing; the new
+    /// weight is computed by [`EdgeWeightModifier::erasure_weight`], so that `p = 0.5` is the same as [`Self::load_erasures`]
+    fn load_erasure_probabilities(&mut self, erasures: &[(EdgeIndex, f64)], weight_scale: f64) -> Result<(), FusionError> {
+        let edge_modifier: Vec<_> = (erasures.iter())
+            .map(|&(edge_index, p)| (edge_index, EdgeWeightModifier::erasure_weight(p, weight_scale)))
+            .collect();
+        self.load_edge_modifier(&edge_modifier)
     }
 
-    fn load_dynamic_weights(&mut self, dynamic_weights: &[(EdgeIndex, Weight)]) {
+    fn load_dynamic_weights(&mut self, dynamic_weights: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
         let edge_modifier = dynamic_weights.to_vec();
-        self.load_edge_modifier(&edge_modifier);
+        self.load_edge_modifier(&edge_modifier)
+    }
+
+    /// optional support for introspecting the dual solution: returns `(growth, weight)` of an edge, where growth is
+    /// the sum of the growth from both ends; an edge is tight when `growth == weight`
+    fn get_edge_growth(&self, _edge_index: EdgeIndex) -> Result<(Weight, Weight), FusionError> {
+        Err(FusionError::UnsupportedOperation("get_edge_growth"))
+    }
+
+    /// bulk version of [`DualModuleImpl::get_edge_growth`]
+    fn get_edge_growths(&self, edge_indices: &[EdgeIndex]) -> Result<Vec<(Weight, Weight)>, FusionError> {
+        edge_indices
+            .iter()
+            .map(|&edge_index| self.get_edge_growth(edge_index))
+            .collect()
     }
 
     /// prepare a list of nodes as shrinking state; useful in creating a blossom
-    fn prepare_nodes_shrink(&mut self, _nodes_circle: &[DualNodePtr]) -> &mut Vec<SyncRequest> {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+    fn prepare_nodes_shrink(&mut self, _nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        Err(FusionError::UnsupportedOperation("prepare_nodes_shrink"))
     }
 
     /// performance profiler report
@@ -618,51 +844,67 @@ pub trait DualModuleImpl {
         json!({})
     }
 
+    /// report the first overflow of the accumulated weights since the last clear, see [`WeightOverflow`];
+    /// by default nothing overflows
+    fn check_overflow(&self) -> Result<(), FusionError> {
+        Ok(())
+    }
+
+    /// check the consistency of the graph state (edge growth, slack and node regions), complementary to
+    /// [`DualModuleInterfacePtr::sanity_check`] which only checks the node forest; by default nothing is checked
+    fn sanity_check(&self, _interface_ptr: &DualModuleInterfacePtr) -> Result<(), String> {
+        Ok(())
+    }
+
     /*
      * the following apis are only required when this dual module can be used as a partitioned one
      */
 
     /// create a partitioned dual module (hosting only a subgraph and subset of dual nodes) to be used in the parallel dual module
-    fn new_partitioned(_partitioned_initializer: &PartitionedSolverInitializer) -> Self
+    fn new_partitioned(_partitioned_initializer: &PartitionedSolverInitializer) -> Result<Self, FusionError>
     where
         Self: std::marker::Sized,
     {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+        Err(FusionError::UnsupportedOperation("new_partitioned"))
     }
 
     /// prepare the growing or shrinking state of all nodes and return a list of sync requests in case of mirrored vertices are changed
-    fn prepare_all(&mut self) -> &mut Vec<SyncRequest> {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+    fn prepare_all(&mut self) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        Err(FusionError::UnsupportedOperation("prepare_all"))
+    }
+
+    /// prepare a single node to grow or shrink and return a list of sync requests in case of mirrored vertices are changed
+    fn prepare_dual_node(
+        &mut self,
+        _dual_node_ptr: &DualNodePtr,
+        _is_grow: bool,
+    ) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        Err(FusionError::UnsupportedOperation("prepare_dual_node"))
     }
 
     /// execute a synchronize event by updating the state of a vertex and also update the internal dual node accordingly
-    fn execute_sync_event(&mut self, _sync_event: &SyncRequest) {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+    fn execute_sync_event(&mut self, _sync_event: &SyncRequest) -> Result<(), FusionError> {
+        Err(FusionError::UnsupportedOperation("execute_sync_event"))
     }
 
     /// judge whether the current module hosts the dual node
-    fn contains_dual_node(&self, _dual_node_ptr: &DualNodePtr) -> bool {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+    fn contains_dual_node(&self, _dual_node_ptr: &DualNodePtr) -> Result<bool, FusionError> {
+        Err(FusionError::UnsupportedOperation("contains_dual_node"))
     }
 
     /// judge whether the current module hosts any of these dual node
-    fn contains_dual_nodes_any(&self, dual_node_ptrs: &[DualNodePtr]) -> bool {
+    fn contains_dual_nodes_any(&self, dual_node_ptrs: &[DualNodePtr]) -> Result<bool, FusionError> {
         for dual_node_ptr in dual_node_ptrs.iter() {
-            if self.contains_dual_node(dual_node_ptr) {
-                return true;
+            if self.contains_dual_node(dual_node_ptr)? {
+                return Ok(true);
             }
         }
-        false
+        Ok(false)
     }
 
     /// judge whether the current module hosts a vertex
-    fn contains_vertex(&self, _vertex_index: VertexIndex) -> bool {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
-    }
-
-    /// bias the global dual node indices
-    fn bias_dual_node_index(&mut self, _bias: NodeIndex) {
-        panic!("the dual module implementation doesn't support this function, please use another dual module")
+    fn contains_vertex(&self, _vertex_index: VertexIndex) -> Result<bool, FusionError> {
+        Err(FusionError::UnsupportedOperation("contains_vertex"))
     }
 }
 
@@ -675,12 +917,40 @@ pub trait DualModuleParallelImpl {
 
 impl FusionVisualizer for DualModuleInterfacePtr {
     fn snapshot(&self, abbrev: bool) -> serde_json::Value {
-        // do the sanity check first before taking snapshot
-        let flattened_nodes = self.sanity_check().unwrap();
+        let flattened_nodes = if STRICT_CHECKS {
+            self.sanity_check().unwrap()
+        } else {
+            let mut flattened_nodes = vec![];
+            self.flatten_nodes(&mut flattened_nodes);
+            flattened_nodes
+        };
+        self.snapshot_nodes(abbrev, &flattened_nodes, |_| true)
+    }
+}
+
+impl DualModuleInterfacePtr {
+    /// take a snapshot of only the nodes matching the predicate, e.g. only blossoms or only active nodes, without the
+    /// sanity check; this is much cheaper than [`FusionVisualizer::snapshot`] with [`STRICT_CHECKS`]. Filtered nodes are
+    /// null so that node indices are kept
+    pub fn snapshot_filtered(&self, abbrev: bool, predicate: impl Fn(&DualNode) -> bool) -> serde_json::Value {
+        let mut flattened_nodes = vec![];
+        self.flatten_nodes(&mut flattened_nodes);
+        self.snapshot_nodes(abbrev, &flattened_nodes, predicate)
+    }
+
+    fn snapshot_nodes(
+        &self,
+        abbrev: bool,
+        flattened_nodes: &[Option<DualNodePtr>],
+        predicate: impl Fn(&DualNode) -> bool,
+    ) -> serde_json::Value {
         let interface = self.read_recursive();
         let mut dual_nodes = Vec::<serde_json::Value>::new();
         for dual_node_ptr in flattened_nodes.iter() {
-            if let Some(dual_node_ptr) = &dual_node_ptr {
+            let dual_node_ptr = dual_node_ptr
+                .as_ref()
+                .filter(|dual_node_ptr| predicate(&dual_node_ptr.read_recursive()));
+            if let Some(dual_node_ptr) = dual_node_ptr {
                 let dual_node = dual_node_ptr.read_recursive();
                 dual_nodes.push(json!({
                     if abbrev { "o" } else { "blossom" }: match &dual_node.class {
@@ -724,6 +994,63 @@ impl FusionVisualizer for DualModuleInterfacePtr {
 }
 
 impl DualModuleInterface {
+    fn record_overflow(&mut self, accumulator: &'static str) {
+        self.overflow.get_or_insert(accumulator);
+    }
+
+    /// add or remove the node in the active nodes of the interface that creates it, which is either myself (`self_ptr`)
+    /// or one of my fused descendants
+    fn set_node_active(
+        &mut self,
+        self_ptr: &DualModuleInterfacePtr,
+        node: &DualNode,
+        dual_node_ptr: &DualNodePtr,
+        is_active: bool,
+    ) {
+        let set_active = |active_nodes: &mut PtrWeakHashSet<DualNodeWeak>| {
+            if is_active {
+                active_nodes.insert(dual_node_ptr.clone());
+            } else {
+                active_nodes.remove(dual_node_ptr);
+            }
+        };
+        if node.home_interface == self_ptr.downgrade() {
+            set_active(&mut self.active_nodes);
+        } else {
+            set_active(&mut node.home_interface.upgrade_force().write().active_nodes);
+        }
+    }
+
+    /// append the segments of a fused child interface, including its own nodes which never grow after fusion
+    fn inherit_segments(&mut self, child_weak: DualModuleInterfaceWeak, child: &Self, bias: NodeIndex) {
+        for (start, interface_weak) in child.segments.iter() {
+            self.segments.push((bias + start, interface_weak.clone()));
+        }
+        if child.nodes_length > 0 {
+            self.segments
+                .push((bias + child.nodes_count() - child.nodes_length as NodeNum, child_weak));
+        }
+    }
+
+    /// record the index bias of a fused child interface and its descendants, so that their keys resolve in this interface
+    fn inherit_descendant_biases(&mut self, child: &Self, bias: NodeIndex) {
+        self.descendant_biases.insert(child.epoch, bias);
+        for (&epoch, &child_bias) in child.descendant_biases.iter() {
+            self.descendant_biases.insert(epoch, bias + child_bias);
+        }
+    }
+
+    /// add the dual variables of a fused child, which also inherits its overflow
+    fn fuse_sum_dual_variables(&mut self, other: &Self) {
+        if let Some(accumulator) = other.overflow {
+            self.record_overflow(accumulator);
+        }
+        match self.weight_overflow.add(self.sum_dual_variables, other.sum_dual_variables) {
+            Some(sum_dual_variables) => self.sum_dual_variables = sum_dual_variables,
+            None => self.record_overflow("sum_dual_variables"),
+        }
+    }
+
     /// return the count of all nodes including those of the children interfaces
     pub fn nodes_count(&self) -> NodeNum {
         let mut count = self.nodes_length as NodeNum;
@@ -733,48 +1060,106 @@ impl DualModuleInterface {
         count
     }
 
+    /// the nodes that are currently growing or shrinking, in arbitrary order; this is maintained incrementally
+    /// so it doesn't need to scan all the nodes
+    pub fn active_nodes(&self) -> Vec<DualNodePtr> {
+        let mut active_nodes: Vec<_> = self.active_nodes.iter().collect();
+        for descendant_weak in self.active_descendants.iter() {
+            active_nodes.extend(descendant_weak.upgrade_force().read_recursive().active_nodes.iter());
+        }
+        active_nodes
+    }
+
+    /// the number of nodes that are currently growing or shrinking
+    pub fn active_nodes_count(&self) -> usize {
+        let descendants_count: usize = (self.active_descendants.iter())
+            .map(|descendant_weak| descendant_weak.upgrade_force().read_recursive().active_nodes.len())
+            .sum();
+        self.active_nodes.len() + descendants_count
+    }
+
+    /// record the fused child and its descendants, whose active nodes stay where they are
+    fn inherit_active_descendants(&mut self, child_weak: DualModuleInterfaceWeak, child: &Self) {
+        self.active_descendants.push(child_weak);
+        self.active_descendants.extend(child.active_descendants.iter().cloned());
+    }
+
+    /// the estimated memory usage of my own nodes, excluding those of the children interfaces
+    pub fn memory_usage(&self) -> MemoryReport {
+        let mut dual_nodes = MemoryReport::vec(&self.nodes);
+        for node_ptr in self.nodes.iter().flatten() {
+            dual_nodes += MemoryReport::shared::<DualNode>();
+            if let DualNodeClass::Blossom {
+                nodes_circle,
+                touching_children,
+            } = &node_ptr.read_recursive().class
+            {
+                dual_nodes += MemoryReport::vec(nodes_circle) + MemoryReport::vec(touching_children);
+            }
+        }
+        let auxiliary = self.active_nodes.capacity() * std::mem::size_of::<DualNodeWeak>()
+            + MemoryReport::vec(&self.active_descendants)
+            + MemoryReport::vec(&self.segments)
+            + self.descendant_biases.len() * std::mem::size_of::<(usize, NodeIndex)>()
+            + MemoryReport::vec(&self.blossom_statistics.depth_histogram);
+        MemoryReport {
+            dual_nodes,
+            auxiliary,
+            ..Default::default()
+        }
+    }
+
+    /// the total length that growing nodes have grown since the syndrome is loaded
+    pub fn dual_variable_global_progress(&self) -> Weight {
+        self.dual_variable_global_progress
+    }
+
+    /// the descendant interface that owns the node and the index of its first node, or None if the node is owned by myself
+    fn locate_segment(&self, relative_node_index: NodeIndex) -> Option<(NodeIndex, DualModuleInterfacePtr)> {
+        if relative_node_index >= self.nodes_count() - self.nodes_length as NodeNum {
+            return None;
+        }
+        let position = self.segments.partition_point(|(start, _)| *start <= relative_node_index) - 1;
+        let (start, interface_weak) = &self.segments[position];
+        Some((*start, interface_weak.upgrade_force()))
+    }
+
     /// get node ptr by index; if calling from the ancestor interface, node_index is absolute, otherwise it's relative
     #[allow(clippy::unnecessary_cast)]
     pub fn get_node(&self, relative_node_index: NodeIndex) -> Option<DualNodePtr> {
         debug_assert!(relative_node_index < self.nodes_count(), "cannot find node in this interface");
-        let mut bias = 0;
-        if let Some(((left_weak, left_count), (right_weak, right_count))) = &self.children {
-            if relative_node_index < *left_count {
-                // this node belongs to the left
-                return left_weak.upgrade_force().read_recursive().get_node(relative_node_index);
-            } else if relative_node_index < *left_count + *right_count {
-                // this node belongs to the right
-                return right_weak
-                    .upgrade_force()
-                    .read_recursive()
-                    .get_node(relative_node_index - *left_count);
+        match self.locate_segment(relative_node_index) {
+            Some((start, interface_ptr)) => {
+                interface_ptr.read_recursive().nodes[(relative_node_index - start) as usize].clone()
+            }
+            None => {
+                let bias = self.nodes_count() - self.nodes_length as NodeNum;
+                self.nodes[(relative_node_index - bias) as usize].clone()
             }
-            bias = left_count + right_count;
         }
-        self.nodes[(relative_node_index - bias) as usize].clone()
+    }
+
+    /// get node ptr by key, or None if the node is removed or it belongs to neither this interface nor its descendants
+    pub fn get_node_by_key(&self, key: DualNodeKey) -> Option<DualNodePtr> {
+        let bias = if key.epoch == self.epoch {
+            0
+        } else {
+            *self.descendant_biases.get(&key.epoch)?
+        };
+        self.get_node(key.index + bias)
     }
 
     /// set the corresponding node index to None
     #[allow(clippy::unnecessary_cast)]
     pub fn remove_node(&mut self, relative_node_index: NodeIndex) {
         debug_assert!(relative_node_index < self.nodes_count(), "cannot find node in this interface");
-        let mut bias = 0;
-        if let Some(((left_weak, left_count), (right_weak, right_count))) = &self.children {
-            if relative_node_index < *left_count {
-                // this node belongs to the left
-                left_weak.upgrade_force().write().remove_node(relative_node_index);
-                return;
-            } else if relative_node_index < *left_count + *right_count {
-                // this node belongs to the right
-                right_weak
-                    .upgrade_force()
-                    .write()
-                    .remove_node(relative_node_index - *left_count);
-                return;
+        match self.locate_segment(relative_node_index) {
+            Some((start, interface_ptr)) => interface_ptr.write().nodes[(relative_node_index - start) as usize] = None,
+            None => {
+                let bias = self.nodes_count() - self.nodes_length as NodeNum;
+                self.nodes[(relative_node_index - bias) as usize] = None;
             }
-            bias = left_count + right_count;
         }
-        self.nodes[(relative_node_index - bias) as usize] = None;
     }
 }
 
@@ -793,9 +1178,26 @@ impl DualModuleInterfacePtr {
             parent: None,
             index_bias: 0,
             children: None,
+            segments: Vec::new(),
+            epoch: next_interface_epoch(),
+            descendant_biases: BTreeMap::new(),
+            blossom_statistics: BlossomStatistics::default(),
+            active_nodes: PtrWeakHashSet::new(),
+            active_descendants: Vec::new(),
+            weight_overflow: WeightOverflow::default(),
+            overflow: None,
         })
     }
 
+    /// create an empty interface with room for the dual nodes of a decoding problem within the capacity hint
+    pub fn new_capacity(initializer: &SolverInitializer) -> Self {
+        let interface_ptr = Self::new_empty();
+        if let Some(capacity_hint) = initializer.capacity_hint {
+            interface_ptr.write().nodes.reserve(capacity_hint.max_node_num());
+        }
+        interface_ptr
+    }
+
     /// a dual module interface MUST be created given a concrete implementation of the dual module
     pub fn new_load(syndrome_pattern: &SyndromePattern, dual_module_impl: &mut impl DualModuleImpl) -> Self {
         let interface_ptr = Self::new_empty();
@@ -803,20 +1205,44 @@ impl DualModuleInterfacePtr {
         interface_ptr
     }
 
+    /// load a syndrome that is known to be valid, otherwise use [`DualModuleInterfacePtr::load_checked`]
     pub fn load(&self, syndrome_pattern: &SyndromePattern, dual_module_impl: &mut impl DualModuleImpl) {
-        for vertex_idx in syndrome_pattern.defect_vertices.iter() {
-            self.create_defect_node(*vertex_idx, dual_module_impl);
+        if let Err(error) = self.try_load(syndrome_pattern, dual_module_impl) {
+            panic!("{error}")
         }
+    }
+
+    /// load a syndrome, failing if the dual module doesn't support the erasures or dynamic weights in it
+    pub fn try_load(
+        &self,
+        syndrome_pattern: &SyndromePattern,
+        dual_module_impl: &mut impl DualModuleImpl,
+    ) -> Result<(), FusionError> {
+        if !syndrome_pattern.erasures.is_empty() && !syndrome_pattern.dynamic_weights.is_empty() {
+            return Err(SyndromeError::ErasuresWithDynamicWeights.into());
+        }
+        // the fallible loads go first, so that no defect is loaded on failure
         if !syndrome_pattern.erasures.is_empty() {
-            assert!(
-                syndrome_pattern.dynamic_weights.is_empty(),
-                "erasures and dynamic_weights cannot be provided at the same time"
-            );
-            dual_module_impl.load_erasures(&syndrome_pattern.erasures);
+            dual_module_impl.load_erasures(&syndrome_pattern.erasures)?;
         }
         if !syndrome_pattern.dynamic_weights.is_empty() {
-            dual_module_impl.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+            dual_module_impl.load_dynamic_weights(&syndrome_pattern.dynamic_weights)?;
         }
+        for vertex_idx in syndrome_pattern.defect_vertices.iter() {
+            self.create_defect_node(*vertex_idx, dual_module_impl);
+        }
+        Ok(())
+    }
+
+    /// validate the syndrome before loading it, see [`SyndromeValidator`]; no defect is loaded if it's invalid
+    pub fn load_checked(
+        &self,
+        syndrome_pattern: &SyndromePattern,
+        validator: &SyndromeValidator,
+        dual_module_impl: &mut impl DualModuleImpl,
+    ) -> Result<(), FusionError> {
+        let syndrome_pattern = validator.validate(syndrome_pattern)?;
+        self.try_load(&syndrome_pattern, dual_module_impl)
     }
 
     /// a constant clear function, without dropping anything;
@@ -832,6 +1258,13 @@ impl DualModuleInterfacePtr {
         interface.parent = None;
         interface.index_bias = 0;
         interface.children = None;
+        interface.segments.clear();
+        interface.epoch = next_interface_epoch();
+        interface.descendant_biases.clear();
+        interface.blossom_statistics.clear();
+        interface.active_nodes.clear();
+        interface.active_descendants.clear();
+        interface.overflow = None;
     }
 
     /// DFS flatten the nodes
@@ -876,8 +1309,12 @@ impl DualModuleInterfacePtr {
             node.grow_state = DualNodeGrowState::Grow;
             node.parent_blossom = None;
             node.dual_variable_cache = (0, interface.dual_variable_global_progress);
-            node.belonging = belonging;
+            node.belonging = belonging.clone();
+            node.home_interface = belonging;
+            node.home_index = node_index;
+            node.home_epoch = interface.epoch;
             node.defect_size = nz!(1usize);
+            node.blossom_depth = 0;
             drop(node);
             node_ptr
         } else {
@@ -889,8 +1326,12 @@ impl DualModuleInterfacePtr {
                 grow_state: DualNodeGrowState::Grow,
                 parent_blossom: None,
                 dual_variable_cache: (0, interface.dual_variable_global_progress),
-                belonging,
+                belonging: belonging.clone(),
+                home_interface: belonging,
+                home_index: node_index,
+                home_epoch: interface.epoch,
                 defect_size: nz!(1usize),
+                blossom_depth: 0,
             })
         };
         interface.nodes_length += 1;
@@ -898,6 +1339,7 @@ impl DualModuleInterfacePtr {
             interface.nodes.push(None);
         }
         let cloned_node_ptr = node_ptr.clone();
+        interface.active_nodes.insert(node_ptr.clone());
         interface.nodes[local_node_index] = Some(node_ptr); // feature `dangerous_pointer`: must push the owner
         drop(interface);
         dual_module_impl.add_defect_node(&cloned_node_ptr);
@@ -923,9 +1365,21 @@ impl DualModuleInterfacePtr {
     pub fn create_blossom(
         &self,
         nodes_circle: Vec<DualNodePtr>,
-        mut touching_children: Vec<(DualNodeWeak, DualNodeWeak)>,
+        touching_children: Vec<(DualNodeWeak, DualNodeWeak)>,
         dual_module_impl: &mut impl DualModuleImpl,
     ) -> DualNodePtr {
+        self.try_create_blossom(nodes_circle, touching_children, dual_module_impl)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// create a blossom, failing if the dual module doesn't support [`DualModuleImpl::prepare_nodes_shrink`]; the
+    /// interface should be cleared after a failure, since the blossom is already half created
+    pub fn try_create_blossom(
+        &self,
+        nodes_circle: Vec<DualNodePtr>,
+        mut touching_children: Vec<(DualNodeWeak, DualNodeWeak)>,
+        dual_module_impl: &mut impl DualModuleImpl,
+    ) -> Result<DualNodePtr, FusionError> {
         let belonging = self.downgrade();
         let mut interface = self.write();
         if touching_children.is_empty() {
@@ -940,6 +1394,14 @@ impl DualModuleInterfacePtr {
             .map(|iter| iter.read_recursive().defect_size)
             .reduce(|a, b| a.saturating_add(b.get()))
             .unwrap();
+        let blossom_depth = 1 + nodes_circle
+            .iter()
+            .map(|iter| iter.read_recursive().blossom_depth)
+            .max()
+            .unwrap();
+        interface
+            .blossom_statistics
+            .record(blossom_depth, nodes_circle.len(), defect_size.get());
 
         let blossom_node_ptr = if !interface.is_fusion
             && local_node_index < interface.nodes.len()
@@ -955,8 +1417,12 @@ impl DualModuleInterfacePtr {
             node.grow_state = DualNodeGrowState::Grow;
             node.parent_blossom = None;
             node.dual_variable_cache = (0, interface.dual_variable_global_progress);
-            node.belonging = belonging;
+            node.belonging = belonging.clone();
+            node.home_interface = belonging;
+            node.home_index = node_index;
+            node.home_epoch = interface.epoch;
             node.defect_size = defect_size;
+            node.blossom_depth = blossom_depth;
             drop(node);
             node_ptr
         } else {
@@ -969,8 +1435,12 @@ impl DualModuleInterfacePtr {
                 grow_state: DualNodeGrowState::Grow,
                 parent_blossom: None,
                 dual_variable_cache: (0, interface.dual_variable_global_progress),
-                belonging,
+                belonging: belonging.clone(),
+                home_interface: belonging,
+                home_index: node_index,
+                home_epoch: interface.epoch,
                 defect_size,
+                blossom_depth,
             })
         };
         drop(interface);
@@ -1008,19 +1478,69 @@ impl DualModuleInterfacePtr {
                 interface.nodes.push(None);
             }
             drop(node);
+            interface.active_nodes.insert(blossom_node_ptr.clone());
             interface.nodes[local_node_index] = Some(blossom_node_ptr); // feature `dangerous_pointer`: must push the owner
         }
         interface.sum_grow_speed += 1;
         drop(interface);
-        dual_module_impl.prepare_nodes_shrink(&nodes_circle);
+        dual_module_impl.prepare_nodes_shrink(&nodes_circle)?;
         dual_module_impl.add_blossom(&cloned_blossom_node_ptr);
-        cloned_blossom_node_ptr
+        Ok(cloned_blossom_node_ptr)
     }
 
     /// expand a blossom: note that different from Blossom V library, we do not maintain tree structure after a blossom is expanded;
     /// this is because we're growing all trees together, and due to the natural of quantum codes, this operation is not likely to cause
     /// bottleneck as long as physical error rate is well below the threshold. All internal nodes will have a [`DualNodeGrowState::Grow`] state afterwards.
     pub fn expand_blossom(&self, blossom_node_ptr: DualNodePtr, dual_module_impl: &mut impl DualModuleImpl) {
+        self.expand_blossom_grow_states(blossom_node_ptr, |_| DualNodeGrowState::Grow, dual_module_impl);
+    }
+
+    /// expand a blossom in an alternating tree given the two entry points: the child touching the parent of the blossom in the
+    /// tree and the child touching the child of the blossom in the tree. The children on the odd-length path between them
+    /// alternately shrink and grow, and the others are matched in pairs and stay, so no progress is lost by re-forming the same
+    /// blossom. Returns the expansion sequences, see [`blossom_expansion_sequences`]
+    pub fn expand_blossom_with_entries(
+        &self,
+        blossom_node_ptr: DualNodePtr,
+        entries: (&DualNodePtr, &DualNodePtr),
+        dual_module_impl: &mut impl DualModuleImpl,
+    ) -> BlossomExpansionSequences {
+        let sequences = {
+            let blossom = blossom_node_ptr.read_recursive();
+            let DualNodeClass::Blossom { nodes_circle, .. } = &blossom.class else {
+                unreachable!("the expanding node is not a blossom")
+            };
+            let (parent_entry, child_entry) = (entries.0.downgrade(), entries.1.downgrade());
+            let parent_touching_index = nodes_circle
+                .iter()
+                .position(|ptr| ptr == &parent_entry)
+                .expect("touching node should be in the blossom circle");
+            let child_touching_index = nodes_circle
+                .iter()
+                .position(|ptr| ptr == &child_entry)
+                .expect("touching node should be in the blossom circle");
+            blossom_expansion_sequences(nodes_circle.len(), parent_touching_index, child_touching_index)
+        };
+        let mut grow_states = vec![DualNodeGrowState::Stay; sequences.match_sequence.len() + sequences.tree_sequence.len()];
+        for (idx, &circle_index) in sequences.tree_sequence.iter().enumerate() {
+            grow_states[circle_index] = if idx % 2 == 0 {
+                DualNodeGrowState::Shrink
+            } else {
+                DualNodeGrowState::Grow
+            };
+        }
+        self.expand_blossom_grow_states(blossom_node_ptr, |circle_index| grow_states[circle_index], dual_module_impl);
+        sequences
+    }
+
+    /// expand a blossom and set the grow state of each child given its index in the blossom circle; a child that stays
+    /// doesn't trigger [`DualModuleImpl::set_grow_state`]
+    pub fn expand_blossom_grow_states(
+        &self,
+        blossom_node_ptr: DualNodePtr,
+        child_grow_state: impl Fn(usize) -> DualNodeGrowState,
+        dual_module_impl: &mut impl DualModuleImpl,
+    ) {
         let interface = self.read_recursive();
         if interface.debug_print_actions {
             let node = blossom_node_ptr.read_recursive();
@@ -1053,6 +1573,7 @@ impl DualModuleInterfacePtr {
             }
             DualNodeGrowState::Stay => {}
         }
+        interface.set_node_active(self, &node, &blossom_node_ptr, false);
         let node_idx = node.index;
         debug_assert!(
             interface.get_node(node_idx).is_some(),
@@ -1065,7 +1586,7 @@ impl DualModuleInterfacePtr {
         drop(interface);
         match &node.class {
             DualNodeClass::Blossom { nodes_circle, .. } => {
-                for node_weak in nodes_circle.iter() {
+                for (circle_index, node_weak) in nodes_circle.iter().enumerate() {
                     let node_ptr = node_weak.upgrade_force();
                     let mut node = node_ptr.write();
                     debug_assert!(
@@ -1079,14 +1600,12 @@ impl DualModuleInterfacePtr {
                     );
                     node.parent_blossom = None;
                     drop(node);
-                    {
-                        // safest way: to avoid sub-optimal result being found, set all nodes to growing state
-                        // WARNING: expanding a blossom like this way MAY CAUSE DEADLOCK!
-                        // think about this extreme case: after a blossom is expanded, they may gradually form a new blossom and needs expanding again!
-                        self.set_grow_state(&node_ptr, DualNodeGrowState::Grow, dual_module_impl);
-                        // the solution is to provide two entry points, the two children of this blossom that directly connect to the two + node in the alternating tree
-                        // only in that way it's guaranteed to make some progress without re-constructing this blossom
-                        // It's the primal module's responsibility to avoid this happening, using the dual module's API: [``]
+                    // without the entry points, the safest way is to set all nodes to growing state to avoid sub-optimal result;
+                    // however, they may gradually form the same blossom again and need expanding again, which never makes progress.
+                    // The primal module should use [`DualModuleInterfacePtr::expand_blossom_with_entries`] to avoid it
+                    let grow_state = child_grow_state(circle_index);
+                    if grow_state != DualNodeGrowState::Stay {
+                        self.set_grow_state(&node_ptr, grow_state, dual_module_impl);
                     }
                 }
             }
@@ -1137,6 +1656,12 @@ impl DualModuleInterfacePtr {
             node.dual_variable_cache = (current_dual_variable, interface.dual_variable_global_progress);
             // update the cache
         }
+        interface.set_node_active(
+            self,
+            &dual_node_ptr.read_recursive(),
+            dual_node_ptr,
+            grow_state != DualNodeGrowState::Stay,
+        );
         drop(interface);
         dual_module_impl.set_grow_state(dual_node_ptr, grow_state); // call this before dual node actually sets; to give history information
         dual_node_ptr.set_grow_state(grow_state);
@@ -1151,21 +1676,30 @@ impl DualModuleInterfacePtr {
     /// if a dual module spontaneously grow some value (e.g. with primal offloading), this function should be called
     pub fn notify_grown(&self, length: Weight) {
         let mut interface = self.write();
-        interface.sum_dual_variables += length * interface.sum_grow_speed;
-        interface.dual_variable_global_progress += length;
+        let weight_overflow = interface.weight_overflow;
+        match weight_overflow.mul_add(interface.sum_dual_variables, length, interface.sum_grow_speed) {
+            Some(sum_dual_variables) => interface.sum_dual_variables = sum_dual_variables,
+            None => interface.record_overflow("sum_dual_variables"),
+        }
+        match weight_overflow.add(interface.dual_variable_global_progress, length) {
+            Some(progress) => interface.dual_variable_global_progress = progress,
+            None => interface.record_overflow("dual_variable_global_progress"),
+        }
     }
 
-    /// grow a specific length globally but iteratively: will try to keep growing that much
-    pub fn grow_iterative(&self, mut length: Weight, dual_module_impl: &mut impl DualModuleImpl) {
+    /// grow a specific length globally but iteratively: will try to keep growing that much, failing with
+    /// [`FusionError::UnresolvedConflict`] if a conflict shows up before that
+    pub fn grow_iterative(&self, mut length: Weight, dual_module_impl: &mut impl DualModuleImpl) -> Result<(), FusionError> {
         while length > 0 {
             let max_update_length = dual_module_impl.compute_maximum_update_length();
             let safe_growth = max_update_length
                 .get_none_zero_growth()
-                .unwrap_or_else(|| panic!("iterative grow failed because of conflicts {max_update_length:?}"));
+                .ok_or_else(|| FusionError::UnresolvedConflict(format!("{max_update_length:?}")))?;
             let growth = std::cmp::min(length, safe_growth);
             self.grow(growth, dual_module_impl);
             length -= growth;
         }
+        Ok(())
     }
 
     /// fuse two interfaces by copying the nodes in `other` into myself
@@ -1194,8 +1728,11 @@ impl DualModuleInterfacePtr {
                 }
                 interface.nodes[(bias + other_node_index) as usize] = node_ptr.clone();
             }
-            interface.sum_dual_variables += other_interface.sum_dual_variables;
+            interface.inherit_descendant_biases(&other_interface, bias);
+            interface.fuse_sum_dual_variables(&other_interface);
             interface.sum_grow_speed += other_interface.sum_grow_speed;
+            interface.blossom_statistics.merge(&other_interface.blossom_statistics);
+            interface.inherit_active_descendants(other.downgrade(), &other_interface);
         }
     }
 
@@ -1219,12 +1756,20 @@ impl DualModuleInterfacePtr {
         left_interface.index_bias = 0;
         right_interface.index_bias = left_interface.nodes_count();
         interface.children = Some((
-            (left_weak, left_interface.nodes_count()),
-            (right_weak, right_interface.nodes_count()),
+            (left_weak.clone(), left_interface.nodes_count()),
+            (right_weak.clone(), right_interface.nodes_count()),
         ));
+        for (other_weak, other_interface) in [(left_weak, &left_interface), (right_weak, &right_interface)] {
+            let bias = other_interface.index_bias;
+            interface.inherit_segments(other_weak.clone(), other_interface, bias);
+            interface.inherit_active_descendants(other_weak, other_interface);
+        }
         for other_interface in [left_interface, right_interface] {
-            interface.sum_dual_variables += other_interface.sum_dual_variables;
+            let bias = other_interface.index_bias;
+            interface.inherit_descendant_biases(&other_interface, bias);
+            interface.fuse_sum_dual_variables(&other_interface);
             interface.sum_grow_speed += other_interface.sum_grow_speed;
+            interface.blossom_statistics.merge(&other_interface.blossom_statistics);
         }
     }
 
@@ -1245,6 +1790,16 @@ impl DualModuleInterfacePtr {
             if let Some(dual_node_ptr) = dual_node_ptr {
                 let dual_node = dual_node_ptr.read_recursive();
                 sum_individual_dual_variable += dual_node.get_dual_variable(&interface);
+                let is_active = if dual_node.home_interface == self.downgrade() {
+                    interface.active_nodes.contains(dual_node_ptr)
+                } else {
+                    let home_interface_ptr = dual_node.home_interface.upgrade_force();
+                    let is_active = home_interface_ptr.read_recursive().active_nodes.contains(dual_node_ptr);
+                    is_active
+                };
+                if (dual_node.grow_state != DualNodeGrowState::Stay) != is_active {
+                    return Err(format!("active node set is out of sync with node {}", dual_node.index));
+                }
                 if dual_node.index != index as NodeIndex {
                     return Err(format!(
                         "dual node index wrong: expected {}, actual {}",
@@ -1375,6 +1930,173 @@ impl DualModuleInterfacePtr {
     pub fn sum_dual_variables(&self) -> Weight {
         self.read_recursive().sum_dual_variables
     }
+
+    /// report the first overflow of the accumulated weights since the last clear, under [`WeightOverflow::Error`]
+    pub fn check_overflow(&self) -> Result<(), FusionError> {
+        match self.read_recursive().overflow {
+            Some(accumulator) => Err(FusionError::WeightOverflow(accumulator)),
+            None => Ok(()),
+        }
+    }
+
+    /// performance profiler report, currently the blossom statistics
+    pub fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "blossom": self.read_recursive().blossom_statistics,
+        })
+    }
+}
+
+/// serializable state of a [`DualNode`], where pointers are replaced by node indices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DualNodeCheckpoint {
+    pub class: DualNodeClassCheckpoint,
+    pub grow_state: DualNodeGrowState,
+    pub parent_blossom: Option<NodeIndex>,
+    pub dual_variable: Weight,
+    pub defect_size: usize,
+    #[serde(default)]
+    pub blossom_depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DualNodeClassCheckpoint {
+    Blossom {
+        nodes_circle: Vec<NodeIndex>,
+        touching_children: Vec<(NodeIndex, NodeIndex)>,
+    },
+    DefectVertex {
+        defect_index: VertexIndex,
+    },
+}
+
+/// serializable state of a [`DualModuleInterface`], so that a partially-completed solve can be saved and resumed;
+/// only interfaces that are not fused are supported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DualModuleInterfaceCheckpoint {
+    pub nodes: Vec<Option<DualNodeCheckpoint>>,
+    pub sum_grow_speed: Weight,
+    pub sum_dual_variables: Weight,
+}
+
+impl DualModuleInterfacePtr {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn checkpoint(&self) -> DualModuleInterfaceCheckpoint {
+        let interface = self.read_recursive();
+        assert!(
+            interface.parent.is_none() && interface.children.is_none(),
+            "checkpoint of a fused interface is not supported"
+        );
+        let get_index = |weak: &DualNodeWeak| weak.upgrade_force().read_recursive().index;
+        let nodes = interface.nodes[..interface.nodes_length]
+            .iter()
+            .map(|node_ptr| {
+                node_ptr.as_ref().map(|node_ptr| {
+                    let node = node_ptr.read_recursive();
+                    DualNodeCheckpoint {
+                        class: match &node.class {
+                            DualNodeClass::Blossom {
+                                nodes_circle,
+                                touching_children,
+                            } => DualNodeClassCheckpoint::Blossom {
+                                nodes_circle: nodes_circle.iter().map(get_index).collect(),
+                                touching_children: touching_children
+                                    .iter()
+                                    .map(|(left, right)| (get_index(left), get_index(right)))
+                                    .collect(),
+                            },
+                            DualNodeClass::DefectVertex { defect_index } => DualNodeClassCheckpoint::DefectVertex {
+                                defect_index: *defect_index,
+                            },
+                        },
+                        grow_state: node.grow_state,
+                        parent_blossom: node.parent_blossom.as_ref().map(get_index),
+                        dual_variable: node.get_dual_variable(&interface),
+                        defect_size: node.defect_size.get(),
+                        blossom_depth: node.blossom_depth,
+                    }
+                })
+            })
+            .collect();
+        DualModuleInterfaceCheckpoint {
+            nodes,
+            sum_grow_speed: interface.sum_grow_speed,
+            sum_dual_variables: interface.sum_dual_variables,
+        }
+    }
+
+    /// create a new interface from a checkpoint; the dual module and primal module should then load their own checkpoints
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new_checkpoint(checkpoint: &DualModuleInterfaceCheckpoint) -> Self {
+        let interface_ptr = Self::new_empty();
+        let belonging = interface_ptr.downgrade();
+        let home_epoch = interface_ptr.read_recursive().epoch;
+        let nodes: Vec<Option<DualNodePtr>> = checkpoint
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(node_index, node)| {
+                node.as_ref().map(|node| {
+                    DualNodePtr::new_value(DualNode {
+                        index: node_index as NodeIndex,
+                        class: DualNodeClass::DefectVertex { defect_index: 0 }, // filled later
+                        grow_state: node.grow_state,
+                        parent_blossom: None,
+                        dual_variable_cache: (node.dual_variable, 0),
+                        belonging: belonging.clone(),
+                        home_interface: belonging.clone(),
+                        home_index: node_index as NodeIndex,
+                        home_epoch,
+                        defect_size: NonZeroUsize::new(node.defect_size).expect("defect size must be positive"),
+                        blossom_depth: node.blossom_depth,
+                    })
+                })
+            })
+            .collect();
+        let get_weak = |node_index: &NodeIndex| {
+            nodes[*node_index as usize]
+                .as_ref()
+                .expect("checkpoint refers to a removed node")
+                .downgrade()
+        };
+        for (node_ptr, node_checkpoint) in nodes.iter().zip(checkpoint.nodes.iter()) {
+            if let (Some(node_ptr), Some(node_checkpoint)) = (node_ptr, node_checkpoint) {
+                let mut node = node_ptr.write();
+                node.class = match &node_checkpoint.class {
+                    DualNodeClassCheckpoint::Blossom {
+                        nodes_circle,
+                        touching_children,
+                    } => DualNodeClass::Blossom {
+                        nodes_circle: nodes_circle.iter().map(get_weak).collect(),
+                        touching_children: touching_children
+                            .iter()
+                            .map(|(left, right)| (get_weak(left), get_weak(right)))
+                            .collect(),
+                    },
+                    DualNodeClassCheckpoint::DefectVertex { defect_index } => DualNodeClass::DefectVertex {
+                        defect_index: *defect_index,
+                    },
+                };
+                node.parent_blossom = node_checkpoint.parent_blossom.as_ref().map(get_weak);
+            }
+        }
+        {
+            let mut interface = interface_ptr.write();
+            interface.nodes_length = nodes.len();
+            interface.nodes = nodes;
+            interface.sum_grow_speed = checkpoint.sum_grow_speed;
+            interface.sum_dual_variables = checkpoint.sum_dual_variables;
+            let active_nodes: Vec<_> = interface
+                .nodes
+                .iter()
+                .flatten()
+                .filter(|node_ptr| node_ptr.read_recursive().grow_state != DualNodeGrowState::Stay)
+                .cloned()
+                .collect();
+            interface.active_nodes.extend(active_nodes);
+        }
+        interface_ptr
+    }
 }
 
 impl Ord for MaxUpdateLength {
@@ -1406,7 +2128,7 @@ impl Ord for MaxUpdateLength {
                     .get_vertex_shrink_stop()
                     .unwrap()
                     .cmp(&other.get_vertex_shrink_stop().unwrap())
-            } // don't care, just compare pointer
+            } // don't care, just compare keys
             _ => {}
         }
         // then, blossom expanding has the low priority, because it's infrequent and expensive
@@ -1421,7 +2143,7 @@ impl Ord for MaxUpdateLength {
                     .get_blossom_need_expand()
                     .unwrap()
                     .cmp(&other.get_blossom_need_expand().unwrap())
-            } // don't care, just compare pointer
+            } // don't care, just compare keys
             _ => {}
         }
         // We'll prefer match nodes internally instead of to boundary, because there might be less path connecting to boundary
@@ -1436,7 +2158,7 @@ impl Ord for MaxUpdateLength {
                 let (a, c) = self.get_touching_virtual().unwrap();
                 let (b, d) = other.get_touching_virtual().unwrap();
                 return a.cmp(&b).reverse().then(c.cmp(&d).reverse());
-            } // don't care, just compare pointer
+            } // don't care, just compare keys
             _ => {}
         }
         // last, both of them MUST be MaxUpdateLength::Conflicting
@@ -1453,47 +2175,53 @@ impl PartialOrd for MaxUpdateLength {
 }
 
 impl MaxUpdateLength {
-    /// update all the interface nodes to be up-to-date
-    pub fn update(&self) {
-        match self {
-            Self::NonZeroGrow(_) => {}
-            Self::Conflicting((a, b), (c, d)) => {
-                for x in [a, b, c, d] {
-                    x.update();
-                }
-            }
-            Self::TouchingVirtual((a, b), _) => {
-                for x in [a, b] {
-                    x.update();
-                }
+    /// resolve the dual nodes in the interface that the dual module is currently working with, which is the home interface
+    /// of the dual nodes or one of its fused ancestors; None if any dual node has been removed, i.e. an out-of-date event
+    pub fn resolve(&self, interface: &DualModuleInterface) -> Option<ResolvedMaxUpdateLength> {
+        let get_node = |key: &DualNodeKey| {
+            let node_ptr = interface.get_node_by_key(*key)?;
+            if interface.is_fusion {
+                node_ptr.update(); // the index of the dual node may be out-of-date after fusion
             }
-            Self::BlossomNeedExpand(x) => {
-                x.update();
-            }
-            Self::VertexShrinkStop((a, option)) => {
-                a.update();
-                if let Some((b, c)) = option {
-                    b.update();
-                    c.update();
-                }
+            Some(node_ptr)
+        };
+        Some(match self {
+            Self::NonZeroGrow(length) => MaxUpdateLength::NonZeroGrow(*length),
+            Self::Conflicting((node_1, touching_1), (node_2, touching_2)) => MaxUpdateLength::Conflicting(
+                (get_node(node_1)?, get_node(touching_1)?),
+                (get_node(node_2)?, get_node(touching_2)?),
+            ),
+            Self::TouchingVirtual((node, touching), virtual_vertex) => {
+                MaxUpdateLength::TouchingVirtual((get_node(node)?, get_node(touching)?), *virtual_vertex)
             }
-        }
+            Self::BlossomNeedExpand(node) => MaxUpdateLength::BlossomNeedExpand(get_node(node)?),
+            Self::VertexShrinkStop((node, potential_conflict)) => MaxUpdateLength::VertexShrinkStop((
+                get_node(node)?,
+                match potential_conflict {
+                    Some((node_2, touching_2)) => Some((get_node(node_2)?, get_node(touching_2)?)),
+                    None => None,
+                },
+            )),
+        })
     }
 
     /// useful function to assert expected case
     #[allow(dead_code)]
     pub fn is_conflicting(&self, a: &DualNodePtr, b: &DualNodePtr) -> bool {
         if let MaxUpdateLength::Conflicting((n1, _), (n2, _)) = self {
-            if n1 == a && n2 == b {
+            let (a, b) = (a.key(), b.key());
+            if n1 == &a && n2 == &b {
                 return true;
             }
-            if n1 == b && n2 == a {
+            if n1 == &b && n2 == &a {
                 return true;
             }
         }
         false
     }
+}
 
+impl<N: Clone> MaxUpdateLength<N> {
     /// helper function that get values out of the enum
     #[allow(dead_code)]
     #[inline(always)]
@@ -1507,7 +2235,7 @@ impl MaxUpdateLength {
     /// helper function that get values out of the enum
     #[allow(dead_code)]
     #[inline(always)]
-    pub fn get_conflicting(&self) -> Option<(DualNodePtr, DualNodePtr)> {
+    pub fn get_conflicting(&self) -> Option<(N, N)> {
         match self {
             Self::Conflicting((a, _), (b, _)) => Some((a.clone(), b.clone())),
             _ => None,
@@ -1517,7 +2245,7 @@ impl MaxUpdateLength {
     /// helper function that get values out of the enum
     #[allow(dead_code)]
     #[inline(always)]
-    pub fn get_touching_virtual(&self) -> Option<(DualNodePtr, VertexIndex)> {
+    pub fn get_touching_virtual(&self) -> Option<(N, VertexIndex)> {
         match self {
             Self::TouchingVirtual((a, _), (b, _)) => Some((a.clone(), *b)),
             _ => None,
@@ -1527,7 +2255,7 @@ impl MaxUpdateLength {
     /// helper function that get values out of the enum
     #[allow(dead_code)]
     #[inline(always)]
-    pub fn get_blossom_need_expand(&self) -> Option<DualNodePtr> {
+    pub fn get_blossom_need_expand(&self) -> Option<N> {
         match self {
             Self::BlossomNeedExpand(a) => Some(a.clone()),
             _ => None,
@@ -1537,12 +2265,46 @@ impl MaxUpdateLength {
     /// helper function that get values out of the enum
     #[allow(dead_code)]
     #[inline(always)]
-    pub fn get_vertex_shrink_stop(&self) -> Option<DualNodePtr> {
+    pub fn get_vertex_shrink_stop(&self) -> Option<N> {
         match self {
             Self::VertexShrinkStop((a, _)) => Some(a.clone()),
             _ => None,
         }
     }
+
+    /// the same event with every dual node mapped by `f`
+    pub fn map_nodes<M>(&self, f: impl Fn(&N) -> M) -> MaxUpdateLength<M> {
+        match self {
+            Self::NonZeroGrow(length) => MaxUpdateLength::NonZeroGrow(*length),
+            Self::Conflicting((node_1, touching_1), (node_2, touching_2)) => {
+                MaxUpdateLength::Conflicting((f(node_1), f(touching_1)), (f(node_2), f(touching_2)))
+            }
+            Self::TouchingVirtual((node, touching), virtual_vertex) => {
+                MaxUpdateLength::TouchingVirtual((f(node), f(touching)), *virtual_vertex)
+            }
+            Self::BlossomNeedExpand(node) => MaxUpdateLength::BlossomNeedExpand(f(node)),
+            Self::VertexShrinkStop((node, potential)) => MaxUpdateLength::VertexShrinkStop((
+                f(node),
+                potential.as_ref().map(|(node, touching)| (f(node), f(touching))),
+            )),
+        }
+    }
+}
+
+impl<N: Clone + PartialEq> MaxUpdateLength<N> {
+    /// two [`MaxUpdateLength::VertexShrinkStop`] events of the same node whose potential conflicts come from different
+    /// nodes form a [`MaxUpdateLength::Conflicting`] event between those; `None` if they are not compatible. This is done
+    /// when they meet in [`GroupMaxUpdateLength::add`], e.g. from different units of a partitioned dual module
+    pub fn merge_vertex_shrink_stops(&self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (Self::VertexShrinkStop((node, Some(pair))), Self::VertexShrinkStop((other_node, Some(other_pair))))
+                if node == other_node && pair.0 != other_pair.0 =>
+            {
+                Some(Self::Conflicting(pair.clone(), other_pair.clone()))
+            }
+            _ => None,
+        }
+    }
 }
 
 /// temporarily remember the weights that has been changed, so that it can revert back
@@ -1550,6 +2312,8 @@ impl MaxUpdateLength {
 pub struct EdgeWeightModifier {
     /// edge with changed weighted caused by the erasure or X/Z correlation
     pub modified: Vec<(EdgeIndex, Weight)>,
+    /// the residual error probability of the erasures loaded by [`DualModuleImpl::load_erasure_probabilities`]
+    pub erasure_probabilities: Vec<(EdgeIndex, f64)>,
 }
 
 impl Default for EdgeWeightModifier {
@@ -1560,7 +2324,30 @@ impl Default for EdgeWeightModifier {
 
 impl EdgeWeightModifier {
     pub fn new() -> Self {
-        Self { modified: vec![] }
+        Self {
+            modified: vec![],
+            erasure_probabilities: vec![],
+        }
+    }
+
+    /// the weight `weight_scale * ln((1-p)/p)` rounded to an even number, where `weight_scale` is the weight of a unit
+    /// log-likelihood ratio in the decoding graph; a full erasure `p = 0.5` has zero weight
+    pub fn erasure_weight(p: f64, weight_scale: f64) -> Weight {
+        assert!(p > 0. && p <= 0.5, "the residual error probability {p} must be in (0, 0.5]");
+        assert!(weight_scale >= 0., "the weight scale {weight_scale} must be non-negative");
+        let half_weight = (weight_scale * ((1. - p) / p).ln() / 2.).round() as Weight;
+        2 * half_weight
+    }
+
+    pub fn push_erasure_probability(&mut self, erasure_edge: EdgeIndex, p: f64) {
+        self.erasure_probabilities.push((erasure_edge, p));
+    }
+
+    /// the residual error probability of an erased edge, or None if it's not loaded with a probability
+    pub fn erasure_probability(&self, edge_index: EdgeIndex) -> Option<f64> {
+        (self.erasure_probabilities.iter().rev())
+            .find(|(erasure_edge, _)| *erasure_edge == edge_index)
+            .map(|(_, p)| *p)
     }
 
     /// record the modified edge
@@ -1588,3 +2375,101 @@ impl std::ops::Deref for EdgeWeightModifier {
         &self.modified
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::dual_module_serial::*;
+    use super::super::example_codes::*;
+    use super::*;
+
+    #[test]
+    fn dual_module_pending_stops() {
+        // cargo test dual_module_pending_stops -- --nocapture
+        let key = |index: NodeIndex| DualNodeKey { index, epoch: 0 };
+        let stop = |node: NodeIndex, pair: Option<(NodeIndex, NodeIndex)>| {
+            MaxUpdateLength::VertexShrinkStop((key(node), pair.map(|(a, b)| (key(a), key(b)))))
+        };
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        assert_eq!(group_max_update_length.pending_stops().count(), 0);
+        group_max_update_length.add(stop(5, None));
+        group_max_update_length.add(stop(3, Some((1, 3))));
+        // a potential conflict replaces the stop without one
+        group_max_update_length.add(stop(5, Some((2, 5))));
+        let pending: Vec<_> = group_max_update_length.pending_stops().cloned().collect();
+        assert_eq!(pending, vec![stop(3, Some((1, 3))), stop(5, Some((2, 5)))]);
+        // the pending stops can be paired explicitly
+        assert_eq!(
+            stop(5, Some((4, 5))).merge_vertex_shrink_stops(&pending[1]),
+            Some(MaxUpdateLength::Conflicting((key(4), key(5)), (key(2), key(5))))
+        );
+        assert_eq!(stop(5, Some((2, 5))).merge_vertex_shrink_stops(&pending[1]), None);
+        assert_eq!(stop(3, Some((4, 3))).merge_vertex_shrink_stops(&pending[1]), None);
+        assert_eq!(stop(5, None).merge_vertex_shrink_stops(&pending[1]), None);
+        // which is what happens when they meet in the group
+        group_max_update_length.add(stop(5, Some((4, 5))));
+        assert_eq!(group_max_update_length.pending_stops().count(), 1);
+        assert_eq!(
+            group_max_update_length.pop(),
+            Some(MaxUpdateLength::Conflicting((key(4), key(5)), (key(2), key(5))))
+        );
+        assert_eq!(group_max_update_length.pop(), Some(stop(3, Some((1, 3)))));
+        assert_eq!(group_max_update_length.pop(), None);
+    }
+
+    #[test]
+    fn dual_module_pending_stops_keyed_by_node() {
+        // cargo test dual_module_pending_stops_keyed_by_node -- --nocapture
+        let half_weight = 500;
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, half_weight);
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        code.vertices[19].is_defect = true;
+        code.vertices[26].is_defect = true;
+        code.vertices[35].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        let nodes: Vec<_> = (0..3)
+            .map(|index| interface_ptr.read_recursive().nodes[index].clone().unwrap())
+            .collect();
+        interface_ptr.grow(2 * half_weight, &mut dual_module);
+        interface_ptr.set_grow_state(&nodes[1], DualNodeGrowState::Shrink, &mut dual_module);
+        let blossom = interface_ptr.create_blossom(nodes.clone(), vec![], &mut dual_module);
+        // the blossom and its first child share the representative vertex but not the key
+        assert_eq!(blossom.get_representative_vertex(), nodes[0].get_representative_vertex());
+        assert_ne!(blossom.key(), nodes[0].key());
+        let stop = |node: &DualNodePtr, other: &DualNodePtr| {
+            MaxUpdateLength::VertexShrinkStop((node.key(), Some((other.key(), node.key()))))
+        };
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        group_max_update_length.add(stop(&blossom, &nodes[1]));
+        group_max_update_length.add(stop(&nodes[0], &nodes[2]));
+        assert_eq!(
+            group_max_update_length.pending_stops().count(),
+            2,
+            "stops of different nodes are not merged"
+        );
+        group_max_update_length.add(stop(&blossom, &nodes[2]));
+        assert_eq!(group_max_update_length.pending_stops().count(), 1);
+        assert_eq!(
+            group_max_update_length.pop(),
+            Some(MaxUpdateLength::Conflicting(
+                (nodes[2].key(), blossom.key()),
+                (nodes[1].key(), blossom.key())
+            ))
+        );
+        // a node of the next decoding problem has the same index but a different epoch, which is shown in the debug message
+        let old_key = nodes[0].key();
+        drop(blossom);
+        drop(nodes);
+        interface_ptr.clear();
+        dual_module.clear();
+        interface_ptr.load(&code.get_syndrome(), &mut dual_module);
+        let new_key = interface_ptr.read_recursive().nodes[0].clone().unwrap().key();
+        assert_eq!(new_key.index, old_key.index);
+        assert_ne!(format!("{new_key:?}"), format!("{old_key:?}"));
+        assert!(interface_ptr.read_recursive().get_node_by_key(old_key).is_none());
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        group_max_update_length.add(MaxUpdateLength::VertexShrinkStop((old_key, Some((old_key, old_key)))));
+        group_max_update_length.add(MaxUpdateLength::VertexShrinkStop((new_key, Some((new_key, new_key)))));
+        assert_eq!(group_max_update_length.pending_stops().count(), 2);
+    }
+}
@@ -0,0 +1,952 @@
+//! Distributed Dual Module
+//!
+//! A dual module whose partition units live in separate processes or machines, e.g. when the decoding graph of a large
+//! space-time volume doesn't fit in the memory of a single node. Each worker builds the serial dual module of a single
+//! unit from only its share of the decoding graph, and replicates the dual nodes in its own interface. The coordinator
+//! runs the primal module and exchanges [`DistributedMessage`]s with the workers over a [`DistributedChannel`].
+//!
+//! Mirrored vertices follow the same protocol as [`super::dual_module_parallel::DualModuleParallel`]: the [`SyncRequest`]s
+//! reported by any unit are executed by every unit that holds the vertex, until no unit reports anything. On the wire,
+//! dual nodes are referred to by their [`DualNode::index`], which is the same in the coordinator and all the replicas
+//! because they create and remove dual nodes in the same order. All units are fused from the beginning, like in
+//! [`super::mwpm_solver::SolverDualParallel`]; the primal module stays on the coordinator because its state only scales
+//! with the number of defects.
+//!
+
+#![cfg_attr(feature = "unsafe_pointer", allow(dropping_references))]
+use super::dual_module::*;
+use super::dual_module_parallel::*;
+use super::dual_module_serial::*;
+use super::pointers::*;
+use super::util::*;
+use crate::serde_json;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+
+/// the share of the decoding graph hosted by a worker, see [`PartitionedSolverInitializer`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributedUnitInitializer {
+    pub unit_index: usize,
+    /// the number of units in the partition
+    pub unit_count: usize,
+    pub vertex_num: VertexNum,
+    pub edge_num: usize,
+    pub owning_range: VertexRange,
+    /// whether all the owning vertices are mirrored in the descendants, i.e. this is a fusion unit
+    pub is_fusion_unit: bool,
+    /// (ancestor unit index, list of vertices owned by this ancestor unit and whether it's virtual)
+    pub interfaces: Vec<(usize, Vec<(VertexIndex, bool)>)>,
+    pub weighted_edges: Vec<(VertexIndex, VertexIndex, Weight, EdgeIndex)>,
+    pub virtual_vertices: Vec<VertexIndex>,
+}
+
+/// a [`SyncRequest`] with the dual nodes referred to by their index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributedSyncRequest {
+    pub mirror_unit_index: usize,
+    pub vertex_index: VertexIndex,
+    /// (dual node index, dual variable, representative vertex)
+    pub propagated_dual_node: Option<(NodeIndex, Weight, VertexIndex)>,
+    pub propagated_grandson_dual_node: Option<(NodeIndex, Weight, VertexIndex)>,
+}
+
+/// a [`MaxUpdateLength`] with the dual nodes referred to by their index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DistributedMaxUpdateLength {
+    NonZeroGrow((Weight, bool)),
+    Conflicting((NodeIndex, NodeIndex), (NodeIndex, NodeIndex)),
+    TouchingVirtual((NodeIndex, NodeIndex), (VertexIndex, bool)),
+    BlossomNeedExpand(NodeIndex),
+    VertexShrinkStop((NodeIndex, Option<(NodeIndex, NodeIndex)>)),
+}
+
+/// a [`GroupMaxUpdateLength`] with the dual nodes referred to by their index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DistributedGroupMaxUpdateLength {
+    NonZeroGrow((Weight, bool)),
+    /// conflicts and pending `VertexShrinkStop` events
+    Conflicts((Vec<DistributedMaxUpdateLength>, Vec<DistributedMaxUpdateLength>)),
+}
+
+/// messages between the coordinator and the workers; only queries are answered, by [`DistributedMessage::SyncRequests`]
+/// or [`DistributedMessage::MaximumUpdateLength`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DistributedMessage {
+    Initialize(DistributedUnitInitializer),
+    Clear,
+    AddDefectNode {
+        node_index: NodeIndex,
+        vertex_index: VertexIndex,
+    },
+    AddBlossom {
+        node_index: NodeIndex,
+        nodes_circle: Vec<NodeIndex>,
+    },
+    RemoveBlossom {
+        node_index: NodeIndex,
+    },
+    SetGrowState {
+        node_index: NodeIndex,
+        grow_state: DualNodeGrowState,
+    },
+    Grow {
+        length: Weight,
+    },
+    GrowDualNode {
+        node_index: NodeIndex,
+        length: Weight,
+    },
+    /// edges are referred to by their global index, each worker only modifies the edges it hosts;
+    /// like in the parallel dual module, erasures (0-weight edges) are not supported by partitioned units yet
+    LoadEdgeModifier {
+        edge_modifier: Vec<(EdgeIndex, Weight)>,
+    },
+    /// query: prepare all dual nodes to grow or shrink
+    PrepareAll,
+    /// query: prepare the nodes of a blossom to shrink
+    PrepareNodesShrink {
+        nodes_circle: Vec<NodeIndex>,
+    },
+    /// query: prepare a single dual node to grow or shrink
+    PrepareDualNode {
+        node_index: NodeIndex,
+        is_grow: bool,
+    },
+    ExecuteSyncRequests {
+        sync_requests: Vec<DistributedSyncRequest>,
+    },
+    /// query: the maximum update length of the whole unit
+    ComputeMaximumUpdateLength,
+    /// query: the maximum update length of a single dual node in this unit, assuming it's already prepared
+    ComputeMaximumUpdateLengthDualNode {
+        node_index: NodeIndex,
+        is_grow: bool,
+    },
+    Shutdown,
+    SyncRequests(Vec<DistributedSyncRequest>),
+    MaximumUpdateLength(DistributedGroupMaxUpdateLength),
+}
+
+/// an ordered and reliable message channel between the coordinator and a worker
+pub trait DistributedChannel: Send {
+    fn send(&mut self, message: &DistributedMessage) -> Result<(), String>;
+
+    fn recv(&mut self) -> Result<DistributedMessage, String>;
+}
+
+/// a channel within the same process, e.g. with the workers running in threads
+pub struct LocalChannel {
+    sender: mpsc::Sender<DistributedMessage>,
+    receiver: mpsc::Receiver<DistributedMessage>,
+}
+
+impl LocalChannel {
+    /// create the two ends of a channel
+    pub fn pair() -> (Self, Self) {
+        let (sender_1, receiver_1) = mpsc::channel();
+        let (sender_2, receiver_2) = mpsc::channel();
+        (
+            Self {
+                sender: sender_1,
+                receiver: receiver_2,
+            },
+            Self {
+                sender: sender_2,
+                receiver: receiver_1,
+            },
+        )
+    }
+}
+
+impl DistributedChannel for LocalChannel {
+    fn send(&mut self, message: &DistributedMessage) -> Result<(), String> {
+        self.sender.send(message.clone()).map_err(|error| error.to_string())
+    }
+
+    fn recv(&mut self) -> Result<DistributedMessage, String> {
+        self.receiver.recv().map_err(|error| error.to_string())
+    }
+}
+
+/// a TCP connection carrying JSON messages, each prefixed by its length as a little-endian `u64`
+pub struct TcpChannel {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl TcpChannel {
+    pub fn new(stream: TcpStream) -> Result<Self, String> {
+        stream.set_nodelay(true).map_err(|error| error.to_string())?;
+        let reader = BufReader::new(stream.try_clone().map_err(|error| error.to_string())?);
+        Ok(Self {
+            reader,
+            writer: BufWriter::new(stream),
+        })
+    }
+}
+
+impl DistributedChannel for TcpChannel {
+    fn send(&mut self, message: &DistributedMessage) -> Result<(), String> {
+        let bytes = serde_json::to_vec(message).map_err(|error| error.to_string())?;
+        self.writer
+            .write_all(&(bytes.len() as u64).to_le_bytes())
+            .and_then(|_| self.writer.write_all(&bytes))
+            .and_then(|_| self.writer.flush())
+            .map_err(|error| error.to_string())
+    }
+
+    fn recv(&mut self) -> Result<DistributedMessage, String> {
+        let mut length = [0; 8];
+        self.reader.read_exact(&mut length).map_err(|error| error.to_string())?;
+        let mut bytes = vec![0; u64::from_le_bytes(length) as usize];
+        self.reader.read_exact(&mut bytes).map_err(|error| error.to_string())?;
+        serde_json::from_slice(&bytes).map_err(|error| error.to_string())
+    }
+}
+
+fn node_index_of(dual_node_ptr: &DualNodePtr) -> NodeIndex {
+    dual_node_ptr.read_recursive().index
+}
+
+/// the interfaces of both the coordinator and the workers are never fused, so the home index is the node index
+fn key_index_of(dual_node_key: &DualNodeKey) -> NodeIndex {
+    dual_node_key.index
+}
+
+impl DistributedSyncRequest {
+    pub fn new(sync_request: &SyncRequest) -> Self {
+        let translate = |propagated: &Option<(DualNodeWeak, Weight, VertexIndex)>| {
+            propagated
+                .as_ref()
+                .map(|(dual_node_weak, dual_variable, representative_vertex)| {
+                    (
+                        node_index_of(&dual_node_weak.upgrade_force()),
+                        *dual_variable,
+                        *representative_vertex,
+                    )
+                })
+        };
+        Self {
+            mirror_unit_index: sync_request.mirror_unit_weak.upgrade_force().read_recursive().unit_index,
+            vertex_index: sync_request.vertex_index,
+            propagated_dual_node: translate(&sync_request.propagated_dual_node),
+            propagated_grandson_dual_node: translate(&sync_request.propagated_grandson_dual_node),
+        }
+    }
+}
+
+impl DistributedMaxUpdateLength {
+    pub fn new(max_update_length: &MaxUpdateLength) -> Self {
+        match max_update_length {
+            MaxUpdateLength::NonZeroGrow(length) => Self::NonZeroGrow(*length),
+            MaxUpdateLength::Conflicting((node_1, touching_1), (node_2, touching_2)) => Self::Conflicting(
+                (key_index_of(node_1), key_index_of(touching_1)),
+                (key_index_of(node_2), key_index_of(touching_2)),
+            ),
+            MaxUpdateLength::TouchingVirtual((node, touching), virtual_vertex) => {
+                Self::TouchingVirtual((key_index_of(node), key_index_of(touching)), *virtual_vertex)
+            }
+            MaxUpdateLength::BlossomNeedExpand(node) => Self::BlossomNeedExpand(key_index_of(node)),
+            MaxUpdateLength::VertexShrinkStop((node, potential_conflict)) => Self::VertexShrinkStop((
+                key_index_of(node),
+                potential_conflict
+                    .as_ref()
+                    .map(|(node_2, touching_2)| (key_index_of(node_2), key_index_of(touching_2))),
+            )),
+        }
+    }
+
+    pub fn translate(&self, get_key: &impl Fn(NodeIndex) -> DualNodeKey) -> MaxUpdateLength {
+        match self {
+            Self::NonZeroGrow(length) => MaxUpdateLength::NonZeroGrow(*length),
+            Self::Conflicting((node_1, touching_1), (node_2, touching_2)) => MaxUpdateLength::Conflicting(
+                (get_key(*node_1), get_key(*touching_1)),
+                (get_key(*node_2), get_key(*touching_2)),
+            ),
+            Self::TouchingVirtual((node, touching), virtual_vertex) => {
+                MaxUpdateLength::TouchingVirtual((get_key(*node), get_key(*touching)), *virtual_vertex)
+            }
+            Self::BlossomNeedExpand(node) => MaxUpdateLength::BlossomNeedExpand(get_key(*node)),
+            Self::VertexShrinkStop((node, potential_conflict)) => MaxUpdateLength::VertexShrinkStop((
+                get_key(*node),
+                potential_conflict.map(|(node_2, touching_2)| (get_key(node_2), get_key(touching_2))),
+            )),
+        }
+    }
+}
+
+impl DistributedGroupMaxUpdateLength {
+    pub fn new(group_max_update_length: &GroupMaxUpdateLength) -> Self {
+        match group_max_update_length {
+            GroupMaxUpdateLength::NonZeroGrow(length) => Self::NonZeroGrow(*length),
+            GroupMaxUpdateLength::Conflicts((list, pending_stops)) => Self::Conflicts((
+                list.iter().map(DistributedMaxUpdateLength::new).collect(),
+                pending_stops.values().map(DistributedMaxUpdateLength::new).collect(),
+            )),
+        }
+    }
+
+    pub fn translate(&self, get_key: &impl Fn(NodeIndex) -> DualNodeKey) -> GroupMaxUpdateLength {
+        match self {
+            Self::NonZeroGrow(length) => GroupMaxUpdateLength::NonZeroGrow(*length),
+            Self::Conflicts((list, pending_stops)) => {
+                let mut conflict_list = ConflictList::new();
+                for max_update_length in list.iter() {
+                    conflict_list.push(max_update_length.translate(get_key));
+                }
+                let mut pending_stop_map = BTreeMap::new();
+                for max_update_length in pending_stops.iter() {
+                    let max_update_length = max_update_length.translate(get_key);
+                    pending_stop_map.insert(max_update_length.get_vertex_shrink_stop().unwrap(), max_update_length);
+                }
+                GroupMaxUpdateLength::Conflicts((conflict_list, pending_stop_map))
+            }
+        }
+    }
+}
+
+/// the dual module of a unit in a worker, driven by the replica interface: it only forwards the operations on the dual
+/// nodes that this unit holds, like [`DualModuleParallelUnit`] does for its own serial module
+struct DistributedUnit {
+    serial_module: DualModuleSerial,
+    owning_range: VertexRange,
+    /// the local index of every edge hosted by this unit
+    edge_indices: HashMap<EdgeIndex, EdgeIndex>,
+    /// always empty, because the coordinator prepares the nodes before creating a blossom
+    empty_sync_request: Vec<SyncRequest>,
+}
+
+impl DistributedUnit {
+    fn holds_dual_node(&self, dual_node_ptr: &DualNodePtr) -> Result<bool, FusionError> {
+        Ok(self.owning_range.contains(dual_node_ptr.get_representative_vertex())
+            || self.serial_module.contains_dual_node(dual_node_ptr)?)
+    }
+}
+
+impl DualModuleImpl for DistributedUnit {
+    fn new_empty(_initializer: &SolverInitializer) -> Self {
+        panic!("a distributed unit must be created from a `DistributedUnitInitializer`")
+    }
+
+    fn clear(&mut self) {
+        self.serial_module.clear();
+    }
+
+    fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+        let node = dual_node_ptr.read_recursive();
+        match &node.class {
+            DualNodeClass::DefectVertex { defect_index } => {
+                if self
+                    .serial_module
+                    .contains_vertex(*defect_index)
+                    .unwrap_or_else(|error| panic!("{error}"))
+                {
+                    self.serial_module.add_dual_node(dual_node_ptr);
+                }
+            }
+            DualNodeClass::Blossom { nodes_circle, .. } => {
+                let nodes_circle_ptrs: Vec<_> = nodes_circle.iter().map(|weak| weak.upgrade_force()).collect();
+                let nodes_circle_vertices: Vec<_> =
+                    nodes_circle_ptrs.iter().map(|ptr| ptr.get_representative_vertex()).collect();
+                if self.owning_range.contains_any(&nodes_circle_vertices)
+                    || self
+                        .serial_module
+                        .contains_dual_nodes_any(&nodes_circle_ptrs)
+                        .unwrap_or_else(|error| panic!("{error}"))
+                {
+                    self.serial_module.add_blossom(dual_node_ptr);
+                }
+            }
+        }
+    }
+
+    fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+        if self.holds_dual_node(&dual_node_ptr).unwrap_or_else(|error| panic!("{error}")) {
+            self.serial_module.remove_blossom(dual_node_ptr);
+        }
+    }
+
+    fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+        if self.holds_dual_node(dual_node_ptr).unwrap_or_else(|error| panic!("{error}")) {
+            self.serial_module.set_grow_state(dual_node_ptr, grow_state);
+        }
+    }
+
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        self.serial_module.compute_maximum_update_length()
+    }
+
+    fn grow(&mut self, length: Weight) {
+        self.serial_module.grow(length);
+    }
+
+    fn prepare_nodes_shrink(&mut self, _nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        Ok(&mut self.empty_sync_request)
+    }
+}
+
+/// a worker hosting a single unit of [`DualModuleDistributed`]
+pub struct DistributedWorker {
+    interface_ptr: DualModuleInterfacePtr,
+    unit: DistributedUnit,
+    partition_units: Vec<PartitionUnitPtr>,
+}
+
+impl DistributedWorker {
+    pub fn new(unit_initializer: &DistributedUnitInitializer) -> Self {
+        // all the units are fused from the beginning
+        let partition_units: Vec<PartitionUnitPtr> = (0..unit_initializer.unit_count)
+            .map(|unit_index| {
+                PartitionUnitPtr::new_value(PartitionUnit {
+                    unit_index,
+                    enabled: true,
+                })
+            })
+            .collect();
+        let partitioned_initializer = PartitionedSolverInitializer {
+            unit_index: unit_initializer.unit_index,
+            vertex_num: unit_initializer.vertex_num,
+            edge_num: unit_initializer.edge_num,
+            owning_range: unit_initializer.owning_range,
+            owning_interface: if unit_initializer.is_fusion_unit {
+                Some(partition_units[unit_initializer.unit_index].downgrade())
+            } else {
+                None
+            },
+            interfaces: unit_initializer
+                .interfaces
+                .iter()
+                .map(|(unit_index, vertices)| (partition_units[*unit_index].downgrade(), vertices.clone()))
+                .collect(),
+            weighted_edges: unit_initializer.weighted_edges.clone(),
+            virtual_vertices: unit_initializer.virtual_vertices.clone(),
+        };
+        let edge_indices = unit_initializer
+            .weighted_edges
+            .iter()
+            .enumerate()
+            .map(|(local_index, &(_, _, _, edge_index))| (edge_index, local_index as EdgeIndex))
+            .collect();
+        Self {
+            interface_ptr: DualModuleInterfacePtr::new_empty(),
+            unit: DistributedUnit {
+                serial_module: DualModuleSerial::new_partitioned(&partitioned_initializer)
+                    .expect("serial dual module supports partition"),
+                owning_range: unit_initializer.owning_range,
+                edge_indices,
+                empty_sync_request: vec![],
+            },
+            partition_units,
+        }
+    }
+
+    /// wait for the [`DistributedMessage::Initialize`] message and then serve the coordinator until it shuts down
+    pub fn serve(channel: &mut impl DistributedChannel) -> Result<(), String> {
+        let mut worker = match channel.recv()? {
+            DistributedMessage::Initialize(unit_initializer) => Self::new(&unit_initializer),
+            message => return Err(format!("expecting initialization but received {message:?}")),
+        };
+        loop {
+            match channel.recv()? {
+                DistributedMessage::Shutdown => return Ok(()),
+                message => {
+                    if let Some(response) = worker.handle(message)? {
+                        channel.send(&response)?;
+                    }
+                }
+            }
+        }
+    }
+
+    fn get_node(&self, node_index: NodeIndex) -> Result<DualNodePtr, String> {
+        self.interface_ptr
+            .read_recursive()
+            .get_node(node_index)
+            .ok_or_else(|| format!("unknown dual node {node_index}"))
+    }
+
+    fn take_sync_requests(sync_requests: &mut Vec<SyncRequest>) -> DistributedMessage {
+        DistributedMessage::SyncRequests(sync_requests.drain(..).map(|x| DistributedSyncRequest::new(&x)).collect())
+    }
+
+    fn check_node_index(dual_node_ptr: &DualNodePtr, node_index: NodeIndex) -> Result<(), String> {
+        let replica_index = node_index_of(dual_node_ptr);
+        if replica_index != node_index {
+            return Err(format!("dual node {node_index} is created as {replica_index} in the replica"));
+        }
+        Ok(())
+    }
+
+    /// execute a message and return the response if it's a query
+    pub fn handle(&mut self, message: DistributedMessage) -> Result<Option<DistributedMessage>, String> {
+        match message {
+            DistributedMessage::Clear => {
+                self.interface_ptr.clear();
+                self.unit.clear();
+            }
+            DistributedMessage::AddDefectNode {
+                node_index,
+                vertex_index,
+            } => {
+                let dual_node_ptr = self.interface_ptr.create_defect_node(vertex_index, &mut self.unit);
+                Self::check_node_index(&dual_node_ptr, node_index)?;
+            }
+            DistributedMessage::AddBlossom {
+                node_index,
+                nodes_circle,
+            } => {
+                let nodes_circle = nodes_circle
+                    .into_iter()
+                    .map(|node_index| self.get_node(node_index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let dual_node_ptr = self
+                    .interface_ptr
+                    .try_create_blossom(nodes_circle, vec![], &mut self.unit)
+                    .map_err(|error| error.to_string())?;
+                Self::check_node_index(&dual_node_ptr, node_index)?;
+            }
+            DistributedMessage::RemoveBlossom { node_index } => {
+                let dual_node_ptr = self.get_node(node_index)?;
+                // the children that don't stay are set by the following `SetGrowState` messages
+                self.interface_ptr
+                    .expand_blossom_grow_states(dual_node_ptr, |_| DualNodeGrowState::Stay, &mut self.unit);
+            }
+            DistributedMessage::SetGrowState { node_index, grow_state } => {
+                let dual_node_ptr = self.get_node(node_index)?;
+                self.interface_ptr.set_grow_state(&dual_node_ptr, grow_state, &mut self.unit);
+            }
+            DistributedMessage::Grow { length } => {
+                self.interface_ptr.grow(length, &mut self.unit);
+            }
+            DistributedMessage::GrowDualNode { node_index, length } => {
+                let dual_node_ptr = self.get_node(node_index)?;
+                if self.unit.holds_dual_node(&dual_node_ptr).map_err(|error| error.to_string())? {
+                    self.unit
+                        .serial_module
+                        .grow_dual_node(&dual_node_ptr, length)
+                        .map_err(|error| error.to_string())?;
+                }
+            }
+            DistributedMessage::LoadEdgeModifier { edge_modifier } => {
+                let local_edge_modifier: Vec<_> = edge_modifier
+                    .iter()
+                    .filter_map(|(edge_index, weight)| {
+                        self.unit
+                            .edge_indices
+                            .get(edge_index)
+                            .map(|local_index| (*local_index, *weight))
+                    })
+                    .collect();
+                self.unit
+                    .serial_module
+                    .load_edge_modifier(&local_edge_modifier)
+                    .map_err(|error| error.to_string())?;
+            }
+            DistributedMessage::PrepareAll => {
+                let sync_requests = self.unit.serial_module.prepare_all().map_err(|error| error.to_string())?;
+                return Ok(Some(Self::take_sync_requests(sync_requests)));
+            }
+            DistributedMessage::PrepareNodesShrink { nodes_circle } => {
+                let nodes_circle = nodes_circle
+                    .into_iter()
+                    .map(|node_index| self.get_node(node_index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let sync_requests = self
+                    .unit
+                    .serial_module
+                    .prepare_nodes_shrink(&nodes_circle)
+                    .map_err(|error| error.to_string())?;
+                return Ok(Some(Self::take_sync_requests(sync_requests)));
+            }
+            DistributedMessage::PrepareDualNode { node_index, is_grow } => {
+                let dual_node_ptr = self.get_node(node_index)?;
+                let sync_requests = (self.unit.serial_module)
+                    .prepare_dual_node(&dual_node_ptr, is_grow)
+                    .map_err(|error| error.to_string())?;
+                return Ok(Some(Self::take_sync_requests(sync_requests)));
+            }
+            DistributedMessage::ExecuteSyncRequests { sync_requests } => {
+                for sync_request in sync_requests.iter() {
+                    let contains_vertex = (self.unit.serial_module)
+                        .contains_vertex(sync_request.vertex_index)
+                        .map_err(|error| error.to_string())?;
+                    if !contains_vertex {
+                        continue;
+                    }
+                    let translate = |propagated: &Option<(NodeIndex, Weight, VertexIndex)>| match propagated {
+                        Some((node_index, dual_variable, representative_vertex)) => self
+                            .get_node(*node_index)
+                            .map(|dual_node_ptr| Some((dual_node_ptr.downgrade(), *dual_variable, *representative_vertex))),
+                        None => Ok(None),
+                    };
+                    let sync_request = SyncRequest {
+                        mirror_unit_weak: self
+                            .partition_units
+                            .get(sync_request.mirror_unit_index)
+                            .ok_or_else(|| format!("unknown unit {}", sync_request.mirror_unit_index))?
+                            .downgrade(),
+                        vertex_index: sync_request.vertex_index,
+                        propagated_dual_node: translate(&sync_request.propagated_dual_node)?,
+                        propagated_grandson_dual_node: translate(&sync_request.propagated_grandson_dual_node)?,
+                    };
+                    sync_request.update();
+                    (self.unit.serial_module)
+                        .execute_sync_event(&sync_request)
+                        .map_err(|error| error.to_string())?;
+                }
+            }
+            DistributedMessage::ComputeMaximumUpdateLength => {
+                let group_max_update_length = self.unit.serial_module.compute_maximum_update_length();
+                return Ok(Some(DistributedMessage::MaximumUpdateLength(
+                    DistributedGroupMaxUpdateLength::new(&group_max_update_length),
+                )));
+            }
+            DistributedMessage::ComputeMaximumUpdateLengthDualNode { node_index, is_grow } => {
+                let dual_node_ptr = self.get_node(node_index)?;
+                let mut group_max_update_length = GroupMaxUpdateLength::new();
+                if self.unit.holds_dual_node(&dual_node_ptr).map_err(|error| error.to_string())? {
+                    group_max_update_length.add(
+                        self.unit
+                            .serial_module
+                            .compute_maximum_update_length_dual_node(&dual_node_ptr, is_grow, true)
+                            .map_err(|error| error.to_string())?,
+                    );
+                }
+                return Ok(Some(DistributedMessage::MaximumUpdateLength(
+                    DistributedGroupMaxUpdateLength::new(&group_max_update_length),
+                )));
+            }
+            message => return Err(format!("unexpected message {message:?}")),
+        }
+        Ok(None)
+    }
+}
+
+/// the coordinator of the distributed dual module, with one channel for each unit of the partition
+pub struct DualModuleDistributed {
+    channels: Vec<Box<dyn DistributedChannel>>,
+    /// the dual nodes by their index, to translate the responses of the workers
+    nodes: Vec<Option<DualNodeWeak>>,
+    /// always empty, because the sync requests are executed by the workers
+    empty_sync_request: Vec<SyncRequest>,
+}
+
+impl DualModuleDistributed {
+    /// send every worker its share of the decoding graph; `channels[i]` must connect to a worker serving unit `i`
+    pub fn new(
+        initializer: &SolverInitializer,
+        partition_info: &PartitionInfo,
+        channels: Vec<Box<dyn DistributedChannel>>,
+    ) -> Result<Self, String> {
+        let unit_count = partition_info.units.len();
+        if channels.len() != unit_count {
+            return Err(format!("{} channels are given for {unit_count} units", channels.len()));
+        }
+        let partition_units: Vec<PartitionUnitPtr> = (0..unit_count)
+            .map(|unit_index| {
+                PartitionUnitPtr::new_value(PartitionUnit {
+                    unit_index,
+                    enabled: true,
+                })
+            })
+            .collect();
+        let partitioned_initializers = build_partitioned_initializers(
+            initializer,
+            partition_info,
+            &DualModuleParallelConfig::default(),
+            &partition_units,
+        );
+        let mut dual_module = Self {
+            channels,
+            nodes: vec![],
+            empty_sync_request: vec![],
+        };
+        for (channel, partitioned_initializer) in dual_module.channels.iter_mut().zip(partitioned_initializers) {
+            let unit_initializer = DistributedUnitInitializer {
+                unit_index: partitioned_initializer.unit_index,
+                unit_count,
+                vertex_num: partitioned_initializer.vertex_num,
+                edge_num: partitioned_initializer.edge_num,
+                owning_range: partitioned_initializer.owning_range,
+                is_fusion_unit: partitioned_initializer.owning_interface.is_some(),
+                interfaces: partitioned_initializer
+                    .interfaces
+                    .iter()
+                    .map(|(unit_weak, vertices)| (unit_weak.upgrade_force().read_recursive().unit_index, vertices.clone()))
+                    .collect(),
+                weighted_edges: partitioned_initializer.weighted_edges,
+                virtual_vertices: partitioned_initializer.virtual_vertices,
+            };
+            channel.send(&DistributedMessage::Initialize(unit_initializer))?;
+        }
+        Ok(dual_module)
+    }
+
+    fn broadcast(&mut self, message: &DistributedMessage) {
+        for (unit_index, channel) in self.channels.iter_mut().enumerate() {
+            channel
+                .send(message)
+                .unwrap_or_else(|error| panic!("failed to send to unit {unit_index}: {error}"));
+        }
+    }
+
+    fn gather(&mut self) -> Vec<DistributedMessage> {
+        self.channels
+            .iter_mut()
+            .enumerate()
+            .map(|(unit_index, channel)| {
+                channel
+                    .recv()
+                    .unwrap_or_else(|error| panic!("failed to receive from unit {unit_index}: {error}"))
+            })
+            .collect()
+    }
+
+    /// repeat the preparation until no unit changes its mirrored vertices
+    fn synchronize(&mut self, prepare: DistributedMessage) {
+        loop {
+            self.broadcast(&prepare);
+            let mut sync_requests = vec![];
+            for response in self.gather() {
+                match response {
+                    DistributedMessage::SyncRequests(requests) => sync_requests.extend(requests),
+                    response => panic!("expecting sync requests but received {response:?}"),
+                }
+            }
+            if sync_requests.is_empty() {
+                break;
+            }
+            self.broadcast(&DistributedMessage::ExecuteSyncRequests { sync_requests });
+        }
+    }
+
+    fn gather_max_update_length(&mut self) -> GroupMaxUpdateLength {
+        let responses = self.gather();
+        let get_key = |node_index: NodeIndex| self.get_node(node_index).key();
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        for response in responses {
+            match response {
+                DistributedMessage::MaximumUpdateLength(local_group_max_update_length) => {
+                    group_max_update_length.extend(local_group_max_update_length.translate(&get_key))
+                }
+                response => panic!("expecting maximum update length but received {response:?}"),
+            }
+        }
+        group_max_update_length
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn get_node(&self, node_index: NodeIndex) -> DualNodePtr {
+        self.nodes
+            .get(node_index as usize)
+            .and_then(|node| node.as_ref())
+            .unwrap_or_else(|| panic!("a worker reported an unknown node {node_index}"))
+            .upgrade_force()
+    }
+}
+
+impl Drop for DualModuleDistributed {
+    fn drop(&mut self) {
+        for channel in self.channels.iter_mut() {
+            let _ = channel.send(&DistributedMessage::Shutdown); // the worker may have already exited
+        }
+    }
+}
+
+impl DualModuleImpl for DualModuleDistributed {
+    fn new_empty(_initializer: &SolverInitializer) -> Self {
+        panic!("a distributed dual module must be connected to its workers, see `DualModuleDistributed::new`")
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.broadcast(&DistributedMessage::Clear);
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+        let node = dual_node_ptr.read_recursive();
+        let node_index = node.index;
+        if self.nodes.len() <= node_index as usize {
+            self.nodes.resize(node_index as usize + 1, None);
+        }
+        self.nodes[node_index as usize] = Some(dual_node_ptr.downgrade());
+        let message = match &node.class {
+            DualNodeClass::DefectVertex { defect_index } => DistributedMessage::AddDefectNode {
+                node_index,
+                vertex_index: *defect_index,
+            },
+            DualNodeClass::Blossom { nodes_circle, .. } => {
+                let nodes_circle_ptrs: Vec<_> = nodes_circle.iter().map(|weak| weak.upgrade_force()).collect();
+                // first set all children dual nodes as shrinking, to be safe
+                self.prepare_nodes_shrink(&nodes_circle_ptrs)
+                    .unwrap_or_else(|error| panic!("{error}"));
+                DistributedMessage::AddBlossom {
+                    node_index,
+                    nodes_circle: nodes_circle_ptrs.iter().map(node_index_of).collect(),
+                }
+            }
+        };
+        drop(node);
+        self.broadcast(&message);
+    }
+
+    fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+        self.broadcast(&DistributedMessage::RemoveBlossom {
+            node_index: node_index_of(&dual_node_ptr),
+        });
+    }
+
+    fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+        self.broadcast(&DistributedMessage::SetGrowState {
+            node_index: node_index_of(dual_node_ptr),
+            grow_state,
+        });
+    }
+
+    fn compute_maximum_update_length_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+        simultaneous_update: bool,
+    ) -> Result<MaxUpdateLength, FusionError> {
+        let node_index = node_index_of(dual_node_ptr);
+        if !simultaneous_update {
+            self.synchronize(DistributedMessage::PrepareDualNode { node_index, is_grow });
+        }
+        self.broadcast(&DistributedMessage::ComputeMaximumUpdateLengthDualNode { node_index, is_grow });
+        // the mirrored vertices may report `VertexShrinkStop` in multiple units, which are merged into a single conflict
+        Ok(match self.gather_max_update_length() {
+            GroupMaxUpdateLength::NonZeroGrow(length) => MaxUpdateLength::NonZeroGrow(length),
+            mut group_max_update_length => group_max_update_length.pop().unwrap(),
+        })
+    }
+
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        self.synchronize(DistributedMessage::PrepareAll);
+        self.broadcast(&DistributedMessage::ComputeMaximumUpdateLength);
+        self.gather_max_update_length()
+    }
+
+    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) -> Result<(), FusionError> {
+        self.broadcast(&DistributedMessage::GrowDualNode {
+            node_index: node_index_of(dual_node_ptr),
+            length,
+        });
+        Ok(())
+    }
+
+    fn grow(&mut self, length: Weight) {
+        self.broadcast(&DistributedMessage::Grow { length });
+    }
+
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
+        self.broadcast(&DistributedMessage::LoadEdgeModifier {
+            edge_modifier: edge_modifier.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        self.synchronize(DistributedMessage::PrepareNodesShrink {
+            nodes_circle: nodes_circle.iter().map(node_index_of).collect(),
+        });
+        Ok(&mut self.empty_sync_request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
+    use super::super::primal_module::*;
+    use super::super::primal_module_serial::*;
+    use super::*;
+    use std::net::TcpListener;
+
+    fn distributed_partition_info(initializer: &SolverInitializer) -> PartitionInfo {
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 48),    // unit 0
+            VertexRange::new(64, 112),  // unit 1
+            VertexRange::new(128, 176), // unit 2
+            VertexRange::new(192, 240), // unit 3
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 4, by fusing 0 and 1
+            (2, 3), // unit 5, by fusing 2 and 3
+            (4, 5), // unit 6, by fusing 4 and 5
+        ];
+        partition_config.info()
+    }
+
+    fn distributed_decode_compare(
+        code: &mut CodeCapacityPlanarCode,
+        channels: Vec<Box<dyn DistributedChannel>>,
+        seeds: std::ops::Range<u64>,
+    ) {
+        let initializer = code.get_initializer();
+        let partition_info = distributed_partition_info(&initializer);
+        let mut dual_module = DualModuleDistributed::new(&initializer, &partition_info, channels).unwrap();
+        let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in seeds {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            primal_module.solve(&interface_ptr, &syndrome_pattern, &mut dual_module);
+            solver.solve(&syndrome_pattern);
+            assert_eq!(interface_ptr.sum_dual_variables(), solver.sum_dual_variables(), "seed {seed}");
+            let perfect_matching = primal_module.perfect_matching(&interface_ptr, &mut dual_module);
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_perfect_matching(&perfect_matching);
+            assert_eq!(subgraph_builder.total_weight(), solver.sum_dual_variables(), "seed {seed}");
+            primal_module.clear();
+            interface_ptr.clear();
+            dual_module.clear();
+            solver.clear();
+        }
+    }
+
+    #[test]
+    fn dual_module_distributed_local() {
+        // cargo test dual_module_distributed_local -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(15, 0.1, 500);
+        let mut channels: Vec<Box<dyn DistributedChannel>> = vec![];
+        let mut workers = vec![];
+        for _ in 0..7 {
+            let (coordinator_channel, mut worker_channel) = LocalChannel::pair();
+            channels.push(Box::new(coordinator_channel));
+            workers.push(std::thread::spawn(move || DistributedWorker::serve(&mut worker_channel)));
+        }
+        distributed_decode_compare(&mut code, channels, 0..50);
+        for worker in workers {
+            worker.join().unwrap().unwrap();
+        }
+    }
+
+    #[test]
+    fn dual_module_distributed_tcp() {
+        // cargo test dual_module_distributed_tcp -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(15, 0.1, 500);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let workers: Vec<_> = (0..7)
+            .map(|_| {
+                std::thread::spawn(move || {
+                    let mut channel = TcpChannel::new(TcpStream::connect(address).unwrap())?;
+                    DistributedWorker::serve(&mut channel)
+                })
+            })
+            .collect();
+        let channels: Vec<Box<dyn DistributedChannel>> = (0..7)
+            .map(|_| Box::new(TcpChannel::new(listener.accept().unwrap().0).unwrap()) as Box<dyn DistributedChannel>)
+            .collect();
+        distributed_decode_compare(&mut code, channels, 0..10);
+        for worker in workers {
+            worker.join().unwrap().unwrap();
+        }
+    }
+}
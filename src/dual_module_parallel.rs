@@ -24,7 +24,9 @@ use crate::serde_json;
 use crate::weak_table::PtrWeakHashSet;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashSet};
-use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, Weak};
 
 pub struct DualModuleParallel<SerialModule: DualModuleImpl + Send + Sync> {
     /// the basic wrapped serial modules at the beginning, afterwards the fused units are appended after them
@@ -37,6 +39,9 @@ pub struct DualModuleParallel<SerialModule: DualModuleImpl + Send + Sync> {
     pub thread_pool: Arc<rayon::ThreadPool>,
     /// an empty sync requests queue just to implement the trait
     pub empty_sync_request: Vec<SyncRequest>,
+    /// the interface of each non-root unit used by [`DualModuleParallel::load_sharded`], so that units create their dual
+    /// nodes without contending on a shared interface
+    pub shard_interfaces: Vec<DualModuleInterfacePtr>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +60,10 @@ pub struct DualModuleParallelConfig {
     /// enable parallel execution of a fused dual module
     #[serde(default = "dual_module_parallel_default_configs::enable_parallel_execution")]
     pub enable_parallel_execution: bool,
+    /// load the defects of each unit into its own interface in parallel and fuse the interfaces afterwards, see
+    /// [`DualModuleParallel::load_sharded`]; otherwise all the dual nodes are created in a single shared interface
+    #[serde(default = "dual_module_parallel_default_configs::sharded_interface")]
+    pub sharded_interface: bool,
 }
 
 impl Default for DualModuleParallelConfig {
@@ -74,6 +83,9 @@ pub mod dual_module_parallel_default_configs {
     pub fn enable_parallel_execution() -> bool {
         false
     } // by default disabled: parallel execution may cause too much context switch, yet not much speed benefit
+    pub fn sharded_interface() -> bool {
+        false
+    } // by default disabled: the shared interface is the reference implementation
 }
 
 pub struct DualModuleParallelUnit<SerialModule: DualModuleImpl + Send + Sync> {
@@ -104,10 +116,69 @@ pub struct DualModuleParallelUnit<SerialModule: DualModuleImpl + Send + Sync> {
     pub elevated_dual_nodes: PtrWeakHashSet<DualNodeWeak>,
     /// an empty sync requests queue just to implement the trait
     pub empty_sync_request: Vec<SyncRequest>,
+    /// the scratch buffers of the sync requests, shared by all the units of a parallel dual module
+    pub sync_pool: Arc<SyncRequestPool>,
     /// run things in thread pool
     pub enable_parallel_execution: bool,
     /// whether any descendant unit has active dual node
     pub has_active_node: bool,
+    /// the sender of my sync inbox, cloned by the parent unit when the units are linked
+    pub sync_sender: Sender<SyncBatch>,
+    /// the senders of the sync inboxes of the children, so that a batch is sent to a child without locking it
+    children_sync_senders: Option<(Sender<SyncBatch>, Sender<SyncBatch>)>,
+    /// the batches sent by the parent unit, executed by [`DualModuleParallelUnit::execute_sync_inbox`] the next time this
+    /// unit is used; it's only received with `&mut self`, the mutex is never contended and only makes the unit `Sync`
+    sync_receiver: Mutex<Receiver<SyncBatch>>,
+}
+
+/// the sync requests of a round sent to a unit, shared by the units that mirror the same vertices
+pub type SyncBatch = Vec<Arc<SyncRequest>>;
+
+/// spare vectors of the sync requests, so that the vectors of each grow round are reused in the next rounds and decoding
+/// problems instead of being allocated again; a vector is taken when a round begins and recycled when it ends
+#[derive(Debug, Default)]
+pub struct SyncRequestPool {
+    /// the vectors to gather the sync requests from the units
+    requests: Mutex<Vec<Vec<SyncRequest>>>,
+    /// the vectors of the batches sent to the units
+    batches: Mutex<Vec<Vec<Arc<SyncRequest>>>>,
+    /// how many vectors are allocated because the pool is empty
+    allocation_count: AtomicUsize,
+}
+
+impl SyncRequestPool {
+    fn take<T>(&self, spare_vectors: &Mutex<Vec<Vec<T>>>) -> Vec<T> {
+        spare_vectors.lock().unwrap().pop().unwrap_or_else(|| {
+            self.allocation_count.fetch_add(1, Ordering::Relaxed);
+            Vec::new()
+        })
+    }
+
+    fn recycle<T>(spare_vectors: &Mutex<Vec<Vec<T>>>, mut vector: Vec<T>) {
+        vector.clear();
+        spare_vectors.lock().unwrap().push(vector);
+    }
+
+    pub fn take_requests(&self) -> Vec<SyncRequest> {
+        self.take(&self.requests)
+    }
+
+    pub fn recycle_requests(&self, requests: Vec<SyncRequest>) {
+        Self::recycle(&self.requests, requests)
+    }
+
+    pub fn take_batch(&self) -> Vec<Arc<SyncRequest>> {
+        self.take(&self.batches)
+    }
+
+    pub fn recycle_batch(&self, batch: Vec<Arc<SyncRequest>>) {
+        Self::recycle(&self.batches, batch)
+    }
+
+    /// the number of vectors ever allocated by this pool, which stops increasing once the pool is warmed up
+    pub fn allocation_count(&self) -> usize {
+        self.allocation_count.load(Ordering::Relaxed)
+    }
 }
 
 pub type DualModuleParallelUnitPtr<SerialModule> = ArcManualSafeLock<DualModuleParallelUnit<SerialModule>>;
@@ -142,12 +213,6 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
         let thread_pool = thread_pool_builder.build().expect("creating thread pool failed");
         let mut units = vec![];
         let unit_count = partition_info.units.len();
-        let complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges); // build the graph to construct the NN data structure
-        let mut contained_vertices_vec: Vec<BTreeSet<VertexIndex>> = vec![]; // all vertices maintained by each unit
-        let mut is_vertex_virtual: Vec<_> = (0..initializer.vertex_num).map(|_| false).collect();
-        for virtual_vertex in initializer.virtual_vertices.iter() {
-            is_vertex_virtual[*virtual_vertex as usize] = true;
-        }
         let partition_units: Vec<PartitionUnitPtr> = (0..unit_count)
             .map(|unit_index| {
                 PartitionUnitPtr::new_value(PartitionUnit {
@@ -156,189 +221,16 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                 })
             })
             .collect();
-        let mut partitioned_initializers: Vec<PartitionedSolverInitializer> = (0..unit_count)
-            .map(|unit_index| {
-                let mut interfaces = vec![];
-                let mut current_index = unit_index;
-                let owning_range = &partition_info.units[unit_index].owning_range;
-                let mut contained_vertices = BTreeSet::new();
-                for vertex_index in owning_range.iter() {
-                    contained_vertices.insert(vertex_index);
-                }
-                while let Some(parent_index) = &partition_info.units[current_index].parent {
-                    let mut mirror_vertices = vec![];
-                    if config.edges_in_fusion_unit {
-                        for vertex_index in partition_info.units[*parent_index].owning_range.iter() {
-                            let mut is_incident = false;
-                            for (peer_index, _) in complete_graph.vertices[vertex_index as usize].edges.iter() {
-                                if owning_range.contains(*peer_index) {
-                                    is_incident = true;
-                                    break;
-                                }
-                            }
-                            if is_incident {
-                                mirror_vertices.push((vertex_index, is_vertex_virtual[vertex_index as usize]));
-                                contained_vertices.insert(vertex_index);
-                            }
-                        }
-                    } else {
-                        // first check if there EXISTS any vertex that's adjacent of it's contains vertex
-                        let mut has_incident = false;
-                        for vertex_index in partition_info.units[*parent_index].owning_range.iter() {
-                            for (peer_index, _) in complete_graph.vertices[vertex_index as usize].edges.iter() {
-                                if contained_vertices.contains(peer_index) {
-                                    // important diff: as long as it has an edge with contained vertex, add it
-                                    has_incident = true;
-                                    break;
-                                }
-                            }
-                            if has_incident {
-                                break;
-                            }
-                        }
-                        if has_incident {
-                            // add all vertices as mirrored
-                            for vertex_index in partition_info.units[*parent_index].owning_range.iter() {
-                                mirror_vertices.push((vertex_index, is_vertex_virtual[vertex_index as usize]));
-                                contained_vertices.insert(vertex_index);
-                            }
-                        }
-                    }
-                    if !mirror_vertices.is_empty() {
-                        // only add non-empty mirrored parents is enough
-                        interfaces.push((partition_units[*parent_index].downgrade(), mirror_vertices));
-                    }
-                    current_index = *parent_index;
-                }
-                contained_vertices_vec.push(contained_vertices);
-                PartitionedSolverInitializer {
-                    unit_index,
-                    vertex_num: initializer.vertex_num,
-                    edge_num: initializer.weighted_edges.len(),
-                    owning_range: *owning_range,
-                    owning_interface: if unit_index < partition_info.config.partitions.len() {
-                        None
-                    } else {
-                        Some(partition_units[unit_index].downgrade())
-                    },
-                    weighted_edges: vec![], // to be filled later
-                    interfaces,
-                    virtual_vertices: owning_range
-                        .iter()
-                        .filter(|vertex_index| is_vertex_virtual[*vertex_index as usize])
-                        .collect(),
-                } // note that all fields can be modified later
-            })
-            .collect();
-        // assign each edge to its unique partition
-        for (edge_index, &(i, j, weight)) in initializer.weighted_edges.iter().enumerate() {
-            assert_ne!(i, j, "invalid edge from and to the same vertex {}", i);
-            assert!(
-                i < initializer.vertex_num,
-                "edge ({}, {}) connected to an invalid vertex {}",
-                i,
-                j,
-                i
-            );
-            assert!(
-                j < initializer.vertex_num,
-                "edge ({}, {}) connected to an invalid vertex {}",
-                i,
-                j,
-                j
-            );
-            let i_unit_index = partition_info.vertex_to_owning_unit[i as usize];
-            let j_unit_index = partition_info.vertex_to_owning_unit[j as usize];
-            // either left is ancestor of right or right is ancestor of left, otherwise the edge is invalid (because crossing two independent partitions)
-            let is_i_ancestor = partition_info.units[i_unit_index].descendants.contains(&j_unit_index);
-            let is_j_ancestor = partition_info.units[j_unit_index].descendants.contains(&i_unit_index);
-            assert!(
-                is_i_ancestor || is_j_ancestor || i_unit_index == j_unit_index,
-                "violating edge ({}, {}) crossing two independent partitions {} and {}",
-                i,
-                j,
-                i_unit_index,
-                j_unit_index
-            );
-            let ancestor_unit_index = if is_i_ancestor { i_unit_index } else { j_unit_index };
-            let descendant_unit_index = if is_i_ancestor { j_unit_index } else { i_unit_index };
-            if config.edges_in_fusion_unit {
-                // the edge should be added to the descendant, and it's guaranteed that the descendant unit contains (although not necessarily owned) the vertex
-                partitioned_initializers[descendant_unit_index]
-                    .weighted_edges
-                    .push((i, j, weight, edge_index as EdgeIndex));
-            } else {
-                // add edge to every unit from the descendant (including) and the ancestor (excluding) who mirrored the vertex
-                if ancestor_unit_index < partition_info.config.partitions.len() {
-                    // leaf unit holds every unit
-                    partitioned_initializers[descendant_unit_index].weighted_edges.push((
-                        i,
-                        j,
-                        weight,
-                        edge_index as EdgeIndex,
-                    ));
-                } else {
-                    // iterate every leaf unit of the `descendant_unit_index` to see if adding the edge or not
-                    struct DfsInfo<'a> {
-                        partition_config: &'a PartitionConfig,
-                        partition_info: &'a PartitionInfo,
-                        i: VertexIndex,
-                        j: VertexIndex,
-                        weight: Weight,
-                        contained_vertices_vec: &'a Vec<BTreeSet<VertexIndex>>,
-                        edge_index: EdgeIndex,
-                    }
-                    let dfs_info = DfsInfo {
-                        partition_config: &partition_info.config,
-                        partition_info: &partition_info,
-                        i,
-                        j,
-                        weight,
-                        contained_vertices_vec: &contained_vertices_vec,
-                        edge_index: edge_index as EdgeIndex,
-                    };
-                    fn dfs_add(
-                        unit_index: usize,
-                        dfs_info: &DfsInfo,
-                        partitioned_initializers: &mut Vec<PartitionedSolverInitializer>,
-                    ) {
-                        if unit_index >= dfs_info.partition_config.partitions.len() {
-                            let (left_index, right_index) = &dfs_info.partition_info.units[unit_index]
-                                .children
-                                .expect("fusion unit must have children");
-                            dfs_add(*left_index, dfs_info, partitioned_initializers);
-                            dfs_add(*right_index, dfs_info, partitioned_initializers);
-                        } else {
-                            let contain_i = dfs_info.contained_vertices_vec[unit_index].contains(&dfs_info.i);
-                            let contain_j = dfs_info.contained_vertices_vec[unit_index].contains(&dfs_info.j);
-                            assert!(
-                                !(contain_i ^ contain_j),
-                                "{} and {} must either be both contained or not contained by {}",
-                                dfs_info.i,
-                                dfs_info.j,
-                                unit_index
-                            );
-                            if contain_i {
-                                partitioned_initializers[unit_index].weighted_edges.push((
-                                    dfs_info.i,
-                                    dfs_info.j,
-                                    dfs_info.weight,
-                                    dfs_info.edge_index,
-                                ));
-                            }
-                        }
-                    }
-                    dfs_add(descendant_unit_index, &dfs_info, &mut partitioned_initializers);
-                }
-            }
-        }
+        let partitioned_initializers =
+            build_partitioned_initializers(initializer, &partition_info, &config, &partition_units);
         // println!("partitioned_initializers: {:?}", partitioned_initializers);
         thread_pool.scope(|_| {
             (0..unit_count)
                 .into_par_iter()
                 .map(|unit_index| {
                     // println!("unit_index: {unit_index}");
-                    let dual_module = SerialModule::new_partitioned(&partitioned_initializers[unit_index]);
+                    let dual_module = SerialModule::new_partitioned(&partitioned_initializers[unit_index])
+                        .unwrap_or_else(|error| panic!("{error}"));
                     DualModuleParallelUnitPtr::new_wrapper(
                         dual_module,
                         unit_index,
@@ -350,13 +242,19 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                 .collect_into_vec(&mut units);
         });
         // fill in the children and parent references
+        let sync_pool = Arc::new(SyncRequestPool::default());
         for unit_index in 0..unit_count {
             let mut unit = units[unit_index].write();
+            unit.sync_pool = sync_pool.clone();
             if let Some((left_children_index, right_children_index)) = &partition_info.units[unit_index].children {
                 unit.children = Some((
                     units[*left_children_index].downgrade(),
                     units[*right_children_index].downgrade(),
-                ))
+                ));
+                unit.children_sync_senders = Some((
+                    units[*left_children_index].read_recursive().sync_sender.clone(),
+                    units[*right_children_index].read_recursive().sync_sender.clone(),
+                ));
             }
             if let Some(parent_index) = &partition_info.units[unit_index].parent {
                 unit.parent = Some(units[*parent_index].downgrade());
@@ -391,13 +289,87 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
             }
             // println!("{} extra_descendant_mirrored_vertices: {:?}", unit.unit_index, unit.extra_descendant_mirrored_vertices);
         }
+        let shard_interfaces = (0..unit_count)
+            .map(|unit_index| {
+                let interface_ptr = DualModuleInterfacePtr::new_empty();
+                interface_ptr.write().unit_index = unit_index;
+                interface_ptr
+            })
+            .collect();
         Self {
             units,
             config,
             partition_info,
             thread_pool: Arc::new(thread_pool),
             empty_sync_request: vec![],
+            shard_interfaces,
+        }
+    }
+
+    /// load the defects into per-unit interfaces: the base units create their dual nodes in parallel, each in its own
+    /// interface, and then the fusion units fuse the interfaces of their children (biasing the node indices of the right
+    /// child) before loading their own defects; the root unit uses `interface_ptr`, which must be empty.
+    /// Fails with [`FusionError::UnsupportedOperation`] if the units don't form a single fusion tree
+    pub fn load_sharded(
+        &mut self,
+        interface_ptr: &DualModuleInterfacePtr,
+        syndrome_pattern: &SyndromePattern,
+    ) -> Result<(), FusionError> {
+        debug_assert_eq!(
+            interface_ptr.read_recursive().nodes_count(),
+            0,
+            "root interface must be empty"
+        );
+        let root_index = self.units.len() - 1;
+        if !(0..root_index).all(|unit_index| self.partition_info.units[unit_index].parent.is_some()) {
+            return Err(FusionError::UnsupportedOperation(
+                "sharded loading of units that are not fused into the last unit",
+            ));
+        }
+        if !syndrome_pattern.erasures.is_empty() && !syndrome_pattern.dynamic_weights.is_empty() {
+            return Err(SyndromeError::ErasuresWithDynamicWeights.into());
+        }
+        let partitioned_syndrome = self.partition_info.partition_syndrome_unordered(syndrome_pattern);
+        let shard_interfaces: Vec<_> = (0..self.units.len())
+            .map(|unit_index| {
+                if unit_index == root_index {
+                    interface_ptr.clone()
+                } else {
+                    self.shard_interfaces[unit_index].clear();
+                    self.shard_interfaces[unit_index].clone()
+                }
+            })
+            .collect();
+        let partition_num = self.partition_info.config.partitions.len();
+        self.thread_pool.scope(|_| {
+            self.units[..partition_num]
+                .par_iter()
+                .enumerate()
+                .for_each(|(unit_index, unit_ptr)| {
+                    lock_write!(unit, unit_ptr);
+                    for defect_vertex in partitioned_syndrome[unit_index].defect_vertices.iter() {
+                        shard_interfaces[unit_index].create_defect_node(*defect_vertex, &mut *unit);
+                    }
+                });
+        });
+        for unit_index in partition_num..self.units.len() {
+            let (left_index, right_index) = self.partition_info.units[unit_index].children.unwrap();
+            lock_write!(unit, self.units[unit_index]);
+            unit.fuse(
+                &shard_interfaces[unit_index],
+                (&shard_interfaces[left_index], &shard_interfaces[right_index]),
+            );
+            for defect_vertex in partitioned_syndrome[unit_index].defect_vertices.iter() {
+                shard_interfaces[unit_index].create_defect_node(*defect_vertex, &mut *unit);
+            }
         }
+        if !syndrome_pattern.erasures.is_empty() {
+            self.load_erasures(&syndrome_pattern.erasures)?;
+        }
+        if !syndrome_pattern.dynamic_weights.is_empty() {
+            self.load_dynamic_weights(&syndrome_pattern.dynamic_weights)?;
+        }
+        Ok(())
     }
 
     /// find the active ancestor to handle this dual node (should be unique, i.e. any time only one ancestor is active)
@@ -431,6 +403,15 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
         Some(owning_unit_ptr)
     }
 
+    /// execute the pending sync batches of all the units, from the root towards the leaves, before reading the serial
+    /// modules of the units directly
+    pub fn execute_sync_inboxes(&self) {
+        for unit_ptr in self.units.iter().rev() {
+            lock_write!(unit, unit_ptr);
+            unit.execute_sync_inbox().unwrap_or_else(|error| panic!("{error}"));
+        }
+    }
+
     /// statically fuse them all, may be called at any state (meaning each unit may not necessarily be solved locally)
     pub fn static_fuse_all(&mut self) {
         for unit_ptr in self.units.iter() {
@@ -456,6 +437,219 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
     }
 }
 
+/// split the decoding graph into the initializers of every unit, each holding its own vertices and the mirrored vertices of
+/// its ancestors; `partition_units` are the units referred to by the interfaces of the partitioned initializers
+#[allow(clippy::unnecessary_cast)]
+pub fn build_partitioned_initializers(
+    initializer: &SolverInitializer,
+    partition_info: &PartitionInfo,
+    config: &DualModuleParallelConfig,
+    partition_units: &[PartitionUnitPtr],
+) -> Vec<PartitionedSolverInitializer> {
+    assert!(
+        initializer.virtual_vertex_groups.is_empty(),
+        "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`"
+    );
+    let unit_count = partition_info.units.len();
+    let complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges); // build the graph to construct the NN data structure
+    let mut contained_vertices_vec: Vec<BTreeSet<VertexIndex>> = vec![]; // all vertices maintained by each unit
+    let mut is_vertex_virtual: Vec<_> = (0..initializer.vertex_num).map(|_| false).collect();
+    for virtual_vertex in initializer.virtual_vertices.iter() {
+        is_vertex_virtual[*virtual_vertex as usize] = true;
+    }
+    let mut partitioned_initializers: Vec<PartitionedSolverInitializer> = (0..unit_count)
+        .map(|unit_index| {
+            let mut interfaces = vec![];
+            let mut current_index = unit_index;
+            let owning_range = &partition_info.units[unit_index].owning_range;
+            let mut contained_vertices = BTreeSet::new();
+            for vertex_index in owning_range.iter() {
+                contained_vertices.insert(vertex_index);
+            }
+            while let Some(parent_index) = &partition_info.units[current_index].parent {
+                let mut mirror_vertices = vec![];
+                if config.edges_in_fusion_unit {
+                    for vertex_index in partition_info.units[*parent_index].owning_range.iter() {
+                        let mut is_incident = false;
+                        for (peer_index, _) in complete_graph.vertices[vertex_index as usize].edges.iter() {
+                            if owning_range.contains(*peer_index) {
+                                is_incident = true;
+                                break;
+                            }
+                        }
+                        if is_incident {
+                            mirror_vertices.push((vertex_index, is_vertex_virtual[vertex_index as usize]));
+                            contained_vertices.insert(vertex_index);
+                        }
+                    }
+                } else {
+                    // first check if there EXISTS any vertex that's adjacent of it's contains vertex
+                    let mut has_incident = false;
+                    for vertex_index in partition_info.units[*parent_index].owning_range.iter() {
+                        for (peer_index, _) in complete_graph.vertices[vertex_index as usize].edges.iter() {
+                            if contained_vertices.contains(peer_index) {
+                                // important diff: as long as it has an edge with contained vertex, add it
+                                has_incident = true;
+                                break;
+                            }
+                        }
+                        if has_incident {
+                            break;
+                        }
+                    }
+                    if has_incident {
+                        // add all vertices as mirrored
+                        for vertex_index in partition_info.units[*parent_index].owning_range.iter() {
+                            mirror_vertices.push((vertex_index, is_vertex_virtual[vertex_index as usize]));
+                            contained_vertices.insert(vertex_index);
+                        }
+                    }
+                }
+                if !mirror_vertices.is_empty() {
+                    // only add non-empty mirrored parents is enough
+                    interfaces.push((partition_units[*parent_index].downgrade(), mirror_vertices));
+                }
+                current_index = *parent_index;
+            }
+            contained_vertices_vec.push(contained_vertices);
+            PartitionedSolverInitializer {
+                unit_index,
+                vertex_num: initializer.vertex_num,
+                edge_num: initializer.weighted_edges.len(),
+                owning_range: *owning_range,
+                owning_interface: if unit_index < partition_info.config.partitions.len() {
+                    None
+                } else {
+                    Some(partition_units[unit_index].downgrade())
+                },
+                weighted_edges: vec![], // to be filled later
+                interfaces,
+                virtual_vertices: owning_range
+                    .iter()
+                    .filter(|vertex_index| is_vertex_virtual[*vertex_index as usize])
+                    .collect(),
+            } // note that all fields can be modified later
+        })
+        .collect();
+    // assign each edge to its unique partition
+    for (edge_index, &(i, j, weight)) in initializer.weighted_edges.iter().enumerate() {
+        assert_ne!(i, j, "invalid edge from and to the same vertex {}", i);
+        assert!(
+            i < initializer.vertex_num,
+            "edge ({}, {}) connected to an invalid vertex {}",
+            i,
+            j,
+            i
+        );
+        assert!(
+            j < initializer.vertex_num,
+            "edge ({}, {}) connected to an invalid vertex {}",
+            i,
+            j,
+            j
+        );
+        let i_unit_index = partition_info.vertex_to_owning_unit[i as usize];
+        let j_unit_index = partition_info.vertex_to_owning_unit[j as usize];
+        // either left is ancestor of right or right is ancestor of left, otherwise the edge is invalid (because crossing two independent partitions)
+        let is_i_ancestor = partition_info.units[i_unit_index].descendants.contains(&j_unit_index);
+        let is_j_ancestor = partition_info.units[j_unit_index].descendants.contains(&i_unit_index);
+        assert!(
+            is_i_ancestor || is_j_ancestor || i_unit_index == j_unit_index,
+            "violating edge ({}, {}) crossing two independent partitions {} and {}",
+            i,
+            j,
+            i_unit_index,
+            j_unit_index
+        );
+        let ancestor_unit_index = if is_i_ancestor { i_unit_index } else { j_unit_index };
+        let descendant_unit_index = if is_i_ancestor { j_unit_index } else { i_unit_index };
+        if config.edges_in_fusion_unit {
+            // the edge should be added to the descendant, and it's guaranteed that the descendant unit contains (although not necessarily owned) the vertex
+            partitioned_initializers[descendant_unit_index]
+                .weighted_edges
+                .push((i, j, weight, edge_index as EdgeIndex));
+        } else {
+            // add edge to every unit from the descendant (including) and the ancestor (excluding) who mirrored the vertex
+            if ancestor_unit_index < partition_info.config.partitions.len() {
+                // leaf unit holds every unit
+                partitioned_initializers[descendant_unit_index]
+                    .weighted_edges
+                    .push((i, j, weight, edge_index as EdgeIndex));
+            } else {
+                // iterate every leaf unit of the `descendant_unit_index` to see if adding the edge or not
+                struct DfsInfo<'a> {
+                    partition_config: &'a PartitionConfig,
+                    partition_info: &'a PartitionInfo,
+                    i: VertexIndex,
+                    j: VertexIndex,
+                    weight: Weight,
+                    contained_vertices_vec: &'a Vec<BTreeSet<VertexIndex>>,
+                    edge_index: EdgeIndex,
+                }
+                let dfs_info = DfsInfo {
+                    partition_config: &partition_info.config,
+                    partition_info,
+                    i,
+                    j,
+                    weight,
+                    contained_vertices_vec: &contained_vertices_vec,
+                    edge_index: edge_index as EdgeIndex,
+                };
+                fn dfs_add(
+                    unit_index: usize,
+                    dfs_info: &DfsInfo,
+                    partitioned_initializers: &mut Vec<PartitionedSolverInitializer>,
+                ) {
+                    if unit_index >= dfs_info.partition_config.partitions.len() {
+                        let (left_index, right_index) = &dfs_info.partition_info.units[unit_index]
+                            .children
+                            .expect("fusion unit must have children");
+                        dfs_add(*left_index, dfs_info, partitioned_initializers);
+                        dfs_add(*right_index, dfs_info, partitioned_initializers);
+                    } else {
+                        let contain_i = dfs_info.contained_vertices_vec[unit_index].contains(&dfs_info.i);
+                        let contain_j = dfs_info.contained_vertices_vec[unit_index].contains(&dfs_info.j);
+                        assert!(
+                            !(contain_i ^ contain_j),
+                            "{} and {} must either be both contained or not contained by {}",
+                            dfs_info.i,
+                            dfs_info.j,
+                            unit_index
+                        );
+                        if contain_i {
+                            partitioned_initializers[unit_index].weighted_edges.push((
+                                dfs_info.i,
+                                dfs_info.j,
+                                dfs_info.weight,
+                                dfs_info.edge_index,
+                            ));
+                        }
+                    }
+                }
+                dfs_add(descendant_unit_index, &dfs_info, &mut partitioned_initializers);
+            }
+        }
+    }
+    partitioned_initializers
+}
+
+impl DualModuleParallel<DualModuleSerial> {
+    /// the estimated memory usage of each unit, where a mirrored vertex is counted in every unit that contains it
+    pub fn memory_usage(&self) -> MemoryReport {
+        self.execute_sync_inboxes();
+        MemoryReport::from_units(
+            (self.units.iter())
+                .map(|unit_ptr| {
+                    let unit = unit_ptr.read_recursive();
+                    let mut report = unit.serial_module.memory_usage();
+                    report.auxiliary += unit.elevated_dual_nodes.capacity() * std::mem::size_of::<DualNodeWeak>();
+                    report
+                })
+                .collect(),
+        )
+    }
+}
+
 impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModuleParallel<SerialModule> {
     /// initialize the dual module, which is supposed to be reused for multiple decoding tasks with the same structure
     fn new_empty(initializer: &SolverInitializer) -> Self {
@@ -511,7 +705,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
         dual_node_ptr: &DualNodePtr,
         is_grow: bool,
         simultaneous_update: bool,
-    ) -> MaxUpdateLength {
+    ) -> Result<MaxUpdateLength, FusionError> {
         let unit_ptr = self.find_active_ancestor(dual_node_ptr);
         self.thread_pool.scope(|_| {
             lock_write!(unit, unit_ptr);
@@ -540,11 +734,11 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
         })
     }
 
-    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
+    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) -> Result<(), FusionError> {
         let unit_ptr = self.find_active_ancestor(dual_node_ptr);
         self.thread_pool.scope(|_| {
             lock_write!(unit, unit_ptr);
-            unit.grow_dual_node(dual_node_ptr, length);
+            unit.grow_dual_node(dual_node_ptr, length)
         })
     }
 
@@ -560,25 +754,34 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
         })
     }
 
-    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) {
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
         self.thread_pool.scope(|_| {
-            self.units.par_iter().for_each(|unit_ptr| {
+            self.units.par_iter().try_for_each(|unit_ptr| {
                 lock_write!(unit, unit_ptr);
                 if !unit.is_active {
-                    return;
+                    return Ok(());
                 }
-                unit.load_edge_modifier(edge_modifier);
-            });
+                unit.load_edge_modifier(edge_modifier)
+            })
         })
     }
 
-    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> &mut Vec<SyncRequest> {
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
         let unit_ptr = self.find_active_ancestor(&nodes_circle[0]);
         self.thread_pool.scope(|_| {
             lock_write!(unit, unit_ptr);
-            unit.prepare_nodes_shrink(nodes_circle);
-        });
-        &mut self.empty_sync_request
+            unit.prepare_nodes_shrink(nodes_circle).map(|_| ())
+        })?;
+        Ok(&mut self.empty_sync_request)
+    }
+
+    /// the report of the serial module of every unit
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        self.execute_sync_inboxes();
+        let units: Vec<_> = (self.units.iter())
+            .map(|unit_ptr| unit_ptr.read_recursive().serial_module.generate_profiler_report())
+            .collect();
+        json!({ "units": units })
     }
 }
 
@@ -617,16 +820,13 @@ impl<SerialModule: DualModuleImpl + FusionVisualizer + Send + Sync> FusionVisual
     fn snapshot(&self, abbrev: bool) -> serde_json::Value {
         let mut value = self.serial_module.snapshot(abbrev);
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
-            snapshot_combine_values(
-                &mut value,
-                left_child_weak.upgrade_force().read_recursive().snapshot(abbrev),
-                abbrev,
-            );
-            snapshot_combine_values(
-                &mut value,
-                right_child_weak.upgrade_force().read_recursive().snapshot(abbrev),
-                abbrev,
-            );
+            for child_weak in [left_child_weak, right_child_weak] {
+                // the child may not have executed the batches sent by me yet
+                let child_ptr = child_weak.upgrade_force();
+                let mut child = child_ptr.write();
+                child.execute_sync_inbox().unwrap_or_else(|error| panic!("{error}"));
+                snapshot_combine_values(&mut value, child.snapshot(abbrev), abbrev);
+            }
         }
         value
     }
@@ -659,79 +859,131 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         children_interfaces: (&DualModuleInterfacePtr, &DualModuleInterfacePtr),
     ) {
         self.static_fuse();
+        // the units locate their dual nodes by the home interface, so no dual node index in the children is rewritten
         let (left_interface, right_interface) = children_interfaces;
-        let right_child_ptr = self.children.as_ref().unwrap().1.upgrade_force();
-        lock_write!(right_child, right_child_ptr);
-        // change the index of dual nodes in the right children
-        let bias = left_interface.read_recursive().nodes_count();
-        right_child.iterative_bias_dual_node_index(bias);
         parent_interface.fuse(left_interface, right_interface);
     }
 
-    pub fn iterative_bias_dual_node_index(&mut self, bias: NodeIndex) {
-        // depth-first search
-        if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
-            if self.enable_parallel_execution {
-                rayon::join(
-                    || {
-                        left_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
-                    },
-                    || {
-                        right_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
-                    },
-                );
-            } else {
-                left_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
-                right_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
-            }
-        }
-        // my serial module
-        self.serial_module.bias_dual_node_index(bias);
-    }
-
     /// if any descendant unit mirror or own the vertex
     pub fn is_vertex_in_descendant(&self, vertex_index: VertexIndex) -> bool {
         self.whole_range.contains(vertex_index) || self.extra_descendant_mirrored_vertices.contains(&vertex_index)
     }
 
     /// no need to deduplicate the events: the result will always be consistent with the last one
-    fn execute_sync_events(&mut self, sync_requests: &[SyncRequest]) {
+    fn execute_sync_events(&mut self, sync_requests: &mut Vec<SyncRequest>) -> Result<(), FusionError> {
         // println!("sync_requests: {sync_requests:?}");
-        for sync_request in sync_requests.iter() {
+        let mut batch = self.sync_pool.take_batch();
+        batch.extend(sync_requests.drain(..).map(|sync_request| {
             sync_request.update();
-            self.execute_sync_event(sync_request);
+            Arc::new(sync_request)
+        }));
+        self.execute_sync_batch(&batch)?;
+        self.sync_pool.recycle_batch(batch);
+        Ok(())
+    }
+
+    /// execute the sync requests of a round: the ones relevant to the descendants are sent to the inbox of each child as a
+    /// single batch, which the child executes when it's used next time, so that no child is locked here
+    fn execute_sync_batch(&mut self, sync_requests: &[Arc<SyncRequest>]) -> Result<(), FusionError> {
+        // if the vertex is not hold by any descendant, simply skip it
+        let mut batch = self.sync_pool.take_batch();
+        batch.extend(
+            sync_requests
+                .iter()
+                .filter(|sync_request| self.is_vertex_in_descendant(sync_request.vertex_index))
+                .cloned(),
+        );
+        if batch.is_empty() {
+            self.sync_pool.recycle_batch(batch);
+            return Ok(());
+        }
+        self.has_active_node = true;
+        for sync_request in batch.iter() {
+            self.execute_local_sync_event(sync_request)?;
+        }
+        match self.children_sync_senders.as_ref() {
+            Some((left_sender, right_sender)) => {
+                let mut left_batch = self.sync_pool.take_batch();
+                left_batch.extend_from_slice(&batch);
+                left_sender.send(left_batch).unwrap();
+                right_sender.send(batch).unwrap();
+            }
+            None => self.sync_pool.recycle_batch(batch),
+        }
+        Ok(())
+    }
+
+    /// execute the batches sent by the parent unit in order; every entry of the unit calls this first, so that it never
+    /// reads a state that misses the sync requests of previous rounds
+    fn execute_sync_inbox(&mut self) -> Result<(), FusionError> {
+        loop {
+            let Ok(batch) = self.sync_receiver.get_mut().unwrap().try_recv() else {
+                return Ok(());
+            };
+            self.execute_sync_batch(&batch)?;
+            self.sync_pool.recycle_batch(batch);
         }
     }
 
+    /// execute a sync event on this unit only, given that the vertex is held by some descendant
+    fn execute_local_sync_event(&mut self, sync_event: &SyncRequest) -> Result<(), FusionError> {
+        // update on my serial module
+        if self.serial_module.contains_vertex(sync_event.vertex_index)? {
+            // println!("update: vertex {}, unit index {}", sync_event.vertex_index, self.unit_index);
+            self.serial_module.execute_sync_event(sync_event)?;
+        }
+        // if I'm not on the representative path of this dual node, I need to register the propagated_dual_node
+        // note that I don't need to register propagated_grandson_dual_node because it's never gonna grow inside the blossom
+        if let Some((propagated_dual_node_weak, _, representative_vertex)) = sync_event.propagated_dual_node.as_ref() {
+            if !self.whole_range.contains(*representative_vertex) {
+                self.elevated_dual_nodes.insert(propagated_dual_node_weak.upgrade_force());
+            }
+        }
+        if let Some((propagated_dual_node_weak, _, representative_vertex)) =
+            sync_event.propagated_grandson_dual_node.as_ref()
+        {
+            if !self.whole_range.contains(*representative_vertex) {
+                self.elevated_dual_nodes.insert(propagated_dual_node_weak.upgrade_force());
+            }
+        }
+        Ok(())
+    }
+
     /// iteratively prepare all growing and shrinking and append the sync requests
-    fn iterative_prepare_all(&mut self, sync_requests: &mut Vec<SyncRequest>) {
+    fn iterative_prepare_all(&mut self, sync_requests: &mut Vec<SyncRequest>) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         if !self.has_active_node {
-            return; // early return to avoid going through all units
+            return Ok(()); // early return to avoid going through all units
         }
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
-                let mut sync_requests_2 = vec![];
-                rayon::join(
-                    || {
-                        left_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests);
-                    },
+                let mut sync_requests_2 = self.sync_pool.take_requests();
+                let (left_result, right_result) = rayon::join(
+                    || left_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests),
                     || {
                         right_child_weak
                             .upgrade_force()
                             .write()
-                            .iterative_prepare_all(&mut sync_requests_2);
+                            .iterative_prepare_all(&mut sync_requests_2)
                     },
                 );
+                left_result?;
+                right_result?;
                 sync_requests.append(&mut sync_requests_2);
+                self.sync_pool.recycle_requests(sync_requests_2);
             } else {
-                left_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests);
-                right_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests);
+                left_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests)?;
+                right_child_weak
+                    .upgrade_force()
+                    .write()
+                    .iterative_prepare_all(sync_requests)?;
             }
         }
         // my serial module
-        let local_sync_requests = self.serial_module.prepare_all();
+        let local_sync_requests = self.serial_module.prepare_all()?;
         sync_requests.append(local_sync_requests);
+        Ok(())
     }
 
     /// iteratively set grow state
@@ -740,9 +992,10 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         dual_node_ptr: &DualNodePtr,
         grow_state: DualNodeGrowState,
         representative_vertex: VertexIndex,
-    ) {
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
-            return; // no descendant related to this dual node
+            return Ok(()); // no descendant related to this dual node
         }
         if grow_state != DualNodeGrowState::Stay {
             self.has_active_node = true;
@@ -753,16 +1006,17 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
                 dual_node_ptr,
                 grow_state,
                 representative_vertex,
-            );
+            )?;
             right_child_weak.upgrade_force().write().iterative_set_grow_state(
                 dual_node_ptr,
                 grow_state,
                 representative_vertex,
-            );
+            )?;
         }
-        if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr) {
+        if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr)? {
             self.serial_module.set_grow_state(dual_node_ptr, grow_state);
         }
+        Ok(())
     }
 
     /// check if elevated_dual_nodes contains any dual node in the list
@@ -781,47 +1035,52 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         nodes_circle: &[DualNodePtr],
         nodes_circle_vertices: &[VertexIndex],
         sync_requests: &mut Vec<SyncRequest>,
-    ) {
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         if !self.whole_range.contains_any(nodes_circle_vertices) && !self.elevated_dual_nodes_contains_any(nodes_circle) {
-            return; // no descendant related to this dual node
+            return Ok(()); // no descendant related to this dual node
         }
         self.has_active_node = true;
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
-                let mut sync_requests_2 = vec![];
-                rayon::join(
+                let mut sync_requests_2 = self.sync_pool.take_requests();
+                let (left_result, right_result) = rayon::join(
                     || {
                         left_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
                             nodes_circle,
                             nodes_circle_vertices,
                             sync_requests,
-                        );
+                        )
                     },
                     || {
                         right_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
                             nodes_circle,
                             nodes_circle_vertices,
                             &mut sync_requests_2,
-                        );
+                        )
                     },
                 );
+                left_result?;
+                right_result?;
                 sync_requests.append(&mut sync_requests_2);
+                self.sync_pool.recycle_requests(sync_requests_2);
             } else {
                 left_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
                     nodes_circle,
                     nodes_circle_vertices,
                     sync_requests,
-                );
+                )?;
                 right_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
                     nodes_circle,
                     nodes_circle_vertices,
                     sync_requests,
-                );
+                )?;
             }
         }
-        let local_sync_requests = self.serial_module.prepare_nodes_shrink(nodes_circle);
+        let local_sync_requests = self.serial_module.prepare_nodes_shrink(nodes_circle)?;
         sync_requests.append(local_sync_requests);
+        Ok(())
     }
 
     fn iterative_add_blossom(
@@ -830,22 +1089,23 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         nodes_circle: &[DualNodePtr],
         representative_vertex: VertexIndex,
         nodes_circle_vertices: &[VertexIndex],
-    ) {
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         if !self.whole_range.contains_any(nodes_circle_vertices) && !self.elevated_dual_nodes_contains_any(nodes_circle) {
-            return; // no descendant related to this dual node
+            return Ok(()); // no descendant related to this dual node
         }
         self.has_active_node = true;
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
-                rayon::join(
+                let (left_result, right_result) = rayon::join(
                     || {
                         left_child_weak.upgrade_force().write().iterative_add_blossom(
                             blossom_ptr,
                             nodes_circle,
                             representative_vertex,
                             nodes_circle_vertices,
-                        );
+                        )
                     },
                     || {
                         right_child_weak.upgrade_force().write().iterative_add_blossom(
@@ -853,25 +1113,28 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
                             nodes_circle,
                             representative_vertex,
                             nodes_circle_vertices,
-                        );
+                        )
                     },
                 );
+                left_result?;
+                right_result?;
             } else {
                 left_child_weak.upgrade_force().write().iterative_add_blossom(
                     blossom_ptr,
                     nodes_circle,
                     representative_vertex,
                     nodes_circle_vertices,
-                );
+                )?;
                 right_child_weak.upgrade_force().write().iterative_add_blossom(
                     blossom_ptr,
                     nodes_circle,
                     representative_vertex,
                     nodes_circle_vertices,
-                );
+                )?;
             }
         }
-        if self.owning_range.contains_any(nodes_circle_vertices) || self.serial_module.contains_dual_nodes_any(nodes_circle)
+        if self.owning_range.contains_any(nodes_circle_vertices)
+            || self.serial_module.contains_dual_nodes_any(nodes_circle)?
         {
             self.serial_module.add_blossom(blossom_ptr);
         }
@@ -880,45 +1143,53 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         if !self.whole_range.contains(representative_vertex) {
             self.elevated_dual_nodes.insert(blossom_ptr.clone());
         }
+        Ok(())
     }
 
-    fn iterative_add_defect_node(&mut self, dual_node_ptr: &DualNodePtr, vertex_index: VertexIndex) {
+    fn iterative_add_defect_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        vertex_index: VertexIndex,
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         // if the vertex is not hold by any descendant, simply return
         if !self.is_vertex_in_descendant(vertex_index) {
-            return;
+            return Ok(());
         }
         self.has_active_node = true;
         // println!("sync_prepare_growth_update_sync_event: vertex {}, unit index {}", sync_event.vertex_index, self.unit_index);
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
-                rayon::join(
+                let (left_result, right_result) = rayon::join(
                     || {
                         left_child_weak
                             .upgrade_force()
                             .write()
-                            .iterative_add_defect_node(dual_node_ptr, vertex_index);
+                            .iterative_add_defect_node(dual_node_ptr, vertex_index)
                     },
                     || {
                         right_child_weak
                             .upgrade_force()
                             .write()
-                            .iterative_add_defect_node(dual_node_ptr, vertex_index);
+                            .iterative_add_defect_node(dual_node_ptr, vertex_index)
                     },
                 );
+                left_result?;
+                right_result?;
             } else {
                 left_child_weak
                     .upgrade_force()
                     .write()
-                    .iterative_add_defect_node(dual_node_ptr, vertex_index);
+                    .iterative_add_defect_node(dual_node_ptr, vertex_index)?;
                 right_child_weak
                     .upgrade_force()
                     .write()
-                    .iterative_add_defect_node(dual_node_ptr, vertex_index);
+                    .iterative_add_defect_node(dual_node_ptr, vertex_index)?;
             }
         }
         // update on my serial module
-        if self.serial_module.contains_vertex(vertex_index) {
+        if self.serial_module.contains_vertex(vertex_index)? {
             self.serial_module.add_defect_node(dual_node_ptr);
         }
         // if I'm not on the representative path of this dual node, I need to register the propagated_dual_node
@@ -926,9 +1197,11 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         if !self.whole_range.contains(vertex_index) {
             self.elevated_dual_nodes.insert(dual_node_ptr.clone());
         }
+        Ok(())
     }
 
     fn iterative_compute_maximum_update_length(&mut self, group_max_update_length: &mut GroupMaxUpdateLength) -> bool {
+        self.execute_sync_inbox().unwrap_or_else(|error| panic!("{error}"));
         // early terminate if no active dual nodes anywhere in the descendant
         if !self.has_active_node {
             return false;
@@ -976,47 +1249,187 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         self.has_active_node
     }
 
-    fn iterative_grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight, representative_vertex: VertexIndex) {
+    /// query every descendant unit that holds (part of) this dual node
+    /// prepare a dual node to grow or shrink in every descendant unit that holds (part of) it
+    fn iterative_prepare_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+        representative_vertex: VertexIndex,
+        sync_requests: &mut Vec<SyncRequest>,
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
-            return; // no descendant related to this dual node
+            return Ok(()); // no descendant related to this dual node
         }
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
-                rayon::join(
+                let mut sync_requests_2 = self.sync_pool.take_requests();
+                let (left_result, right_result) = rayon::join(
+                    || {
+                        left_child_weak.upgrade_force().write().iterative_prepare_dual_node(
+                            dual_node_ptr,
+                            is_grow,
+                            representative_vertex,
+                            sync_requests,
+                        )
+                    },
+                    || {
+                        right_child_weak.upgrade_force().write().iterative_prepare_dual_node(
+                            dual_node_ptr,
+                            is_grow,
+                            representative_vertex,
+                            &mut sync_requests_2,
+                        )
+                    },
+                );
+                left_result?;
+                right_result?;
+                sync_requests.append(&mut sync_requests_2);
+                self.sync_pool.recycle_requests(sync_requests_2);
+            } else {
+                left_child_weak.upgrade_force().write().iterative_prepare_dual_node(
+                    dual_node_ptr,
+                    is_grow,
+                    representative_vertex,
+                    sync_requests,
+                )?;
+                right_child_weak.upgrade_force().write().iterative_prepare_dual_node(
+                    dual_node_ptr,
+                    is_grow,
+                    representative_vertex,
+                    sync_requests,
+                )?;
+            }
+        }
+        let local_sync_requests = self.serial_module.prepare_dual_node(dual_node_ptr, is_grow)?;
+        sync_requests.append(local_sync_requests);
+        Ok(())
+    }
+
+    fn iterative_compute_maximum_update_length_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+        representative_vertex: VertexIndex,
+        group_max_update_length: &mut GroupMaxUpdateLength,
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
+        if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
+            return Ok(()); // no descendant related to this dual node
+        }
+        if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
+            if self.enable_parallel_execution {
+                let mut group_max_update_length_2 = GroupMaxUpdateLength::new();
+                let (left_result, right_result) = rayon::join(
+                    || {
+                        left_child_weak
+                            .upgrade_force()
+                            .write()
+                            .iterative_compute_maximum_update_length_dual_node(
+                                dual_node_ptr,
+                                is_grow,
+                                representative_vertex,
+                                group_max_update_length,
+                            )
+                    },
+                    || {
+                        right_child_weak
+                            .upgrade_force()
+                            .write()
+                            .iterative_compute_maximum_update_length_dual_node(
+                                dual_node_ptr,
+                                is_grow,
+                                representative_vertex,
+                                &mut group_max_update_length_2,
+                            )
+                    },
+                );
+                left_result?;
+                right_result?;
+                group_max_update_length.extend(group_max_update_length_2);
+            } else {
+                left_child_weak
+                    .upgrade_force()
+                    .write()
+                    .iterative_compute_maximum_update_length_dual_node(
+                        dual_node_ptr,
+                        is_grow,
+                        representative_vertex,
+                        group_max_update_length,
+                    )?;
+                right_child_weak
+                    .upgrade_force()
+                    .write()
+                    .iterative_compute_maximum_update_length_dual_node(
+                        dual_node_ptr,
+                        is_grow,
+                        representative_vertex,
+                        group_max_update_length,
+                    )?;
+            }
+        }
+        if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr)? {
+            group_max_update_length.add(self.serial_module.compute_maximum_update_length_dual_node(
+                dual_node_ptr,
+                is_grow,
+                true,
+            )?);
+        }
+        Ok(())
+    }
+
+    fn iterative_grow_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        length: Weight,
+        representative_vertex: VertexIndex,
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
+        if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
+            return Ok(()); // no descendant related to this dual node
+        }
+        if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
+            if self.enable_parallel_execution {
+                let (left_result, right_result) = rayon::join(
                     || {
                         left_child_weak.upgrade_force().write().iterative_grow_dual_node(
                             dual_node_ptr,
                             length,
                             representative_vertex,
-                        );
+                        )
                     },
                     || {
                         right_child_weak.upgrade_force().write().iterative_grow_dual_node(
                             dual_node_ptr,
                             length,
                             representative_vertex,
-                        );
+                        )
                     },
                 );
+                left_result?;
+                right_result?;
             } else {
                 left_child_weak.upgrade_force().write().iterative_grow_dual_node(
                     dual_node_ptr,
                     length,
                     representative_vertex,
-                );
+                )?;
                 right_child_weak.upgrade_force().write().iterative_grow_dual_node(
                     dual_node_ptr,
                     length,
                     representative_vertex,
-                );
+                )?;
             }
         }
-        if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr) {
-            self.serial_module.grow_dual_node(dual_node_ptr, length);
+        if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr)? {
+            self.serial_module.grow_dual_node(dual_node_ptr, length)?;
         }
+        Ok(())
     }
 
     fn iterative_grow(&mut self, length: Weight) {
+        self.execute_sync_inbox().unwrap_or_else(|error| panic!("{error}"));
         // early terminate if no active dual nodes anywhere in the descendant
         if !self.has_active_node {
             return;
@@ -1039,41 +1452,49 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         }
     }
 
-    fn iterative_remove_blossom(&mut self, dual_node_ptr: &DualNodePtr, representative_vertex: VertexIndex) {
+    fn iterative_remove_blossom(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        representative_vertex: VertexIndex,
+    ) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
-            return; // no descendant related to this dual node
+            return Ok(()); // no descendant related to this dual node
         }
         self.has_active_node = true;
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
-                rayon::join(
+                let (left_result, right_result) = rayon::join(
                     || {
                         left_child_weak
                             .upgrade_force()
                             .write()
-                            .iterative_remove_blossom(dual_node_ptr, representative_vertex);
+                            .iterative_remove_blossom(dual_node_ptr, representative_vertex)
                     },
                     || {
                         right_child_weak
                             .upgrade_force()
                             .write()
-                            .iterative_remove_blossom(dual_node_ptr, representative_vertex);
+                            .iterative_remove_blossom(dual_node_ptr, representative_vertex)
                     },
                 );
+                left_result?;
+                right_result?;
             } else {
                 left_child_weak
                     .upgrade_force()
                     .write()
-                    .iterative_remove_blossom(dual_node_ptr, representative_vertex);
+                    .iterative_remove_blossom(dual_node_ptr, representative_vertex)?;
                 right_child_weak
                     .upgrade_force()
                     .write()
-                    .iterative_remove_blossom(dual_node_ptr, representative_vertex);
+                    .iterative_remove_blossom(dual_node_ptr, representative_vertex)?;
             }
         }
-        if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr) {
+        if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr)? {
             self.serial_module.remove_blossom(dual_node_ptr.clone());
         }
+        Ok(())
     }
 }
 
@@ -1087,6 +1508,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnitPtr<Seria
         enable_parallel_execution: bool,
     ) -> Self {
         let partition_unit_info = &partition_info.units[unit_index];
+        let (sync_sender, sync_receiver) = channel();
         Self::new_value(DualModuleParallelUnit {
             unit_index,
             partition_info: partition_info.clone(),
@@ -1100,8 +1522,12 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnitPtr<Seria
             parent: None,   // to be filled later
             elevated_dual_nodes: PtrWeakHashSet::new(),
             empty_sync_request: vec![],
+            sync_pool: Arc::new(SyncRequestPool::default()), // replaced by the shared one of the parallel dual module
             enable_parallel_execution,
             has_active_node: true, // by default to true, because children may have active nodes
+            sync_sender,
+            children_sync_senders: None, // to be filled later
+            sync_receiver: Mutex::new(sync_receiver),
         })
     }
 }
@@ -1116,6 +1542,10 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
     /// clear all growth and existing dual nodes
     fn clear(&mut self) {
         self.has_active_node = true;
+        // the pending batches are about the previous decoding problem
+        while let Ok(batch) = self.sync_receiver.get_mut().unwrap().try_recv() {
+            self.sync_pool.recycle_batch(batch);
+        }
         self.serial_module.clear()
     }
 
@@ -1128,7 +1558,8 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
             DualNodeClass::DefectVertex { defect_index } => {
                 if self.owning_range.contains(representative_vertex) {
                     // fast path: the most common one
-                    self.iterative_add_defect_node(dual_node_ptr, *defect_index);
+                    self.iterative_add_defect_node(dual_node_ptr, *defect_index)
+                        .unwrap_or_else(|error| panic!("{error}"));
                 } else {
                     // find the one that owns it and add the dual node, and then add the serial_module
                     if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
@@ -1163,7 +1594,9 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
                             }
                         }
                         lock_write!(child, child_ptr);
-                        child.iterative_add_defect_node(dual_node_ptr, *defect_index);
+                        child
+                            .iterative_add_defect_node(dual_node_ptr, *defect_index)
+                            .unwrap_or_else(|error| panic!("{error}"));
                     } else {
                         unreachable!()
                     }
@@ -1178,20 +1611,23 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
                     .iter()
                     .map(|weak| weak.upgrade_force().get_representative_vertex())
                     .collect();
-                self.prepare_nodes_shrink(&nodes_circle_ptrs);
+                self.prepare_nodes_shrink(&nodes_circle_ptrs)
+                    .unwrap_or_else(|error| panic!("{error}"));
                 self.iterative_add_blossom(
                     dual_node_ptr,
                     &nodes_circle_ptrs,
                     representative_vertex,
                     &nodes_circle_vertices,
-                );
+                )
+                .unwrap_or_else(|error| panic!("{error}"));
             }
         }
     }
 
     fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
         let representative_vertex = dual_node_ptr.get_representative_vertex();
-        self.iterative_remove_blossom(&dual_node_ptr, representative_vertex);
+        self.iterative_remove_blossom(&dual_node_ptr, representative_vertex)
+            .unwrap_or_else(|error| panic!("{error}"));
     }
 
     fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
@@ -1202,7 +1638,8 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
             self.whole_range.contains(representative_vertex),
             "cannot set growth state of dual node outside of the scope"
         );
-        self.iterative_set_grow_state(dual_node_ptr, grow_state, representative_vertex);
+        self.iterative_set_grow_state(dual_node_ptr, grow_state, representative_vertex)
+            .unwrap_or_else(|error| panic!("{error}"));
     }
 
     fn compute_maximum_update_length_dual_node(
@@ -1210,114 +1647,112 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
         dual_node_ptr: &DualNodePtr,
         is_grow: bool,
         simultaneous_update: bool,
-    ) -> MaxUpdateLength {
-        // TODO: execute on all nodes that handles this dual node
-        let max_update_length =
-            self.serial_module
-                .compute_maximum_update_length_dual_node(dual_node_ptr, is_grow, simultaneous_update);
-        if !(self.children.is_none() && self.is_active) {
-            // for those base partitions without being fused, we don't need to update
-            max_update_length.update(); // only necessary after involved in fusion
+    ) -> Result<MaxUpdateLength, FusionError> {
+        let representative_vertex = dual_node_ptr.get_representative_vertex();
+        debug_assert!(
+            self.whole_range.contains(representative_vertex),
+            "cannot compute maximum update length of dual node outside of the scope"
+        );
+        if !simultaneous_update {
+            // preparing the dual node may change mirrored vertices, which must be synchronized before the query
+            let mut sync_requests = self.sync_pool.take_requests();
+            loop {
+                self.iterative_prepare_dual_node(dual_node_ptr, is_grow, representative_vertex, &mut sync_requests)?;
+                if sync_requests.is_empty() {
+                    break;
+                }
+                self.execute_sync_events(&mut sync_requests)?;
+            }
+            self.sync_pool.recycle_requests(sync_requests);
         }
-        max_update_length
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        self.iterative_compute_maximum_update_length_dual_node(
+            dual_node_ptr,
+            is_grow,
+            representative_vertex,
+            &mut group_max_update_length,
+        )?;
+        // the mirrored vertices may report `VertexShrinkStop` in multiple units, which are merged into a single conflict
+        Ok(match group_max_update_length {
+            GroupMaxUpdateLength::NonZeroGrow(length) => MaxUpdateLength::NonZeroGrow(length),
+            mut group_max_update_length => group_max_update_length.pop().unwrap(),
+        })
     }
 
     fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
         // first prepare all dual node for growth and shrink accordingly and synchronize them
-        self.prepare_all();
+        self.prepare_all().unwrap_or_else(|error| panic!("{error}"));
         // them do the functions independently
         let mut group_max_update_length = GroupMaxUpdateLength::new();
         self.iterative_compute_maximum_update_length(&mut group_max_update_length);
-        if !(self.children.is_none() && self.is_active) {
-            // for those base partitions without being fused, we don't need to update
-            group_max_update_length.update(); // only necessary after involved in fusion
-        }
         group_max_update_length
     }
 
-    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
+    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) -> Result<(), FusionError> {
         let representative_vertex = dual_node_ptr.get_representative_vertex();
         debug_assert!(
             self.whole_range.contains(representative_vertex),
             "cannot grow dual node outside of the scope"
         );
-        self.iterative_grow_dual_node(dual_node_ptr, length, representative_vertex);
+        self.iterative_grow_dual_node(dual_node_ptr, length, representative_vertex)
     }
 
     fn grow(&mut self, length: Weight) {
         self.iterative_grow(length);
     }
 
-    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) {
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
         // TODO: split the edge modifier and then load them to individual descendant units
         // hint: each edge could appear in any unit that mirrors the two vertices
+        self.execute_sync_inbox()?;
         self.serial_module.load_edge_modifier(edge_modifier)
     }
 
-    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> &mut Vec<SyncRequest> {
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
         let nodes_circle_vertices: Vec<_> = nodes_circle.iter().map(|ptr| ptr.get_representative_vertex()).collect();
-        let mut sync_requests = vec![];
+        let mut sync_requests = self.sync_pool.take_requests();
         loop {
-            self.iterative_prepare_nodes_shrink(nodes_circle, &nodes_circle_vertices, &mut sync_requests);
+            self.iterative_prepare_nodes_shrink(nodes_circle, &nodes_circle_vertices, &mut sync_requests)?;
             if sync_requests.is_empty() {
                 break;
             }
             // println!("sync_requests: {sync_requests:?}");
-            self.execute_sync_events(&sync_requests);
-            sync_requests.clear();
+            self.execute_sync_events(&mut sync_requests)?;
         }
-        &mut self.empty_sync_request
+        self.sync_pool.recycle_requests(sync_requests);
+        Ok(&mut self.empty_sync_request)
     }
 
-    fn prepare_all(&mut self) -> &mut Vec<SyncRequest> {
+    fn prepare_all(&mut self) -> Result<&mut Vec<SyncRequest>, FusionError> {
         if self.children.is_none() {
             // don't do anything, not even prepare the growth because it will be done in the serial module
         } else {
-            let mut sync_requests = vec![];
+            let mut sync_requests = self.sync_pool.take_requests();
             loop {
-                self.iterative_prepare_all(&mut sync_requests);
+                self.iterative_prepare_all(&mut sync_requests)?;
                 if sync_requests.is_empty() {
                     break;
                 }
                 // println!("sync_requests: {sync_requests:?}");
-                self.execute_sync_events(&sync_requests);
-                sync_requests.clear();
+                self.execute_sync_events(&mut sync_requests)?;
             }
+            self.sync_pool.recycle_requests(sync_requests);
         }
-        &mut self.empty_sync_request
+        Ok(&mut self.empty_sync_request)
     }
 
-    fn execute_sync_event(&mut self, sync_event: &SyncRequest) {
+    fn execute_sync_event(&mut self, sync_event: &SyncRequest) -> Result<(), FusionError> {
+        self.execute_sync_inbox()?;
         // if the vertex is not hold by any descendant, simply return
         if !self.is_vertex_in_descendant(sync_event.vertex_index) {
-            return;
+            return Ok(());
         }
-        self.has_active_node = true;
         // println!("sync_prepare_growth_update_sync_event: vertex {}, unit index {}", sync_event.vertex_index, self.unit_index);
-        // depth-first search
-        if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
-            left_child_weak.upgrade_force().write().execute_sync_event(sync_event);
-            right_child_weak.upgrade_force().write().execute_sync_event(sync_event);
-        }
-        // update on my serial module
-        if self.serial_module.contains_vertex(sync_event.vertex_index) {
-            // println!("update: vertex {}, unit index {}", sync_event.vertex_index, self.unit_index);
-            self.serial_module.execute_sync_event(sync_event);
-        }
-        // if I'm not on the representative path of this dual node, I need to register the propagated_dual_node
-        // note that I don't need to register propagated_grandson_dual_node because it's never gonna grow inside the blossom
-        if let Some((propagated_dual_node_weak, _, representative_vertex)) = sync_event.propagated_dual_node.as_ref() {
-            if !self.whole_range.contains(*representative_vertex) {
-                self.elevated_dual_nodes.insert(propagated_dual_node_weak.upgrade_force());
-            }
-        }
-        if let Some((propagated_dual_node_weak, _, representative_vertex)) =
-            sync_event.propagated_grandson_dual_node.as_ref()
-        {
-            if !self.whole_range.contains(*representative_vertex) {
-                self.elevated_dual_nodes.insert(propagated_dual_node_weak.upgrade_force());
-            }
-        }
+        let mut batch = self.sync_pool.take_batch();
+        batch.push(Arc::new(sync_event.clone()));
+        self.execute_sync_batch(&batch)?;
+        self.sync_pool.recycle_batch(batch);
+        Ok(())
     }
 }
 
@@ -1341,6 +1776,7 @@ pub struct Interface {
 #[cfg(test)]
 pub mod tests {
     use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
     use super::super::primal_module::*;
     use super::super::primal_module_serial::*;
     use super::*;
@@ -1920,4 +2356,271 @@ pub mod tests {
         });
         println!("results: {results:?}");
     }
+
+    /// the per-node query should agree with the serial dual module, even when the dual node spans multiple units
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn dual_module_parallel_compute_maximum_update_length_dual_node_1() {
+        // cargo test dual_module_parallel_compute_maximum_update_length_dual_node_1 -- --nocapture
+        let half_weight = 500;
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, half_weight);
+        code.set_defect_vertices(&[39, 52, 63, 90, 100]);
+        let syndrome_pattern = code.get_syndrome();
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 60),   // unit 0
+            VertexRange::new(72, 132), // unit 1
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 2, by fusing 0 and 1
+        ];
+        let partition_info = partition_config.info();
+        let mut parallel_dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        parallel_dual_module.static_fuse_all();
+        let parallel_interface_ptr = DualModuleInterfacePtr::new_load(&syndrome_pattern, &mut parallel_dual_module);
+        let mut serial_dual_module = DualModuleSerial::new_empty(&initializer);
+        let serial_interface_ptr = DualModuleInterfacePtr::new_load(&syndrome_pattern, &mut serial_dual_module);
+        let summary = |max_update_length: MaxUpdateLength| match max_update_length {
+            MaxUpdateLength::NonZeroGrow((length, _)) => format!("grow {length}"),
+            MaxUpdateLength::Conflicting(..) => "conflicting".to_string(),
+            MaxUpdateLength::TouchingVirtual(..) => "touching virtual".to_string(),
+            MaxUpdateLength::BlossomNeedExpand(..) => "blossom need expand".to_string(),
+            MaxUpdateLength::VertexShrinkStop(..) => "vertex shrink stop".to_string(),
+        };
+        loop {
+            for node_index in 0..syndrome_pattern.defect_vertices.len() as NodeIndex {
+                let parallel_node_ptr = parallel_interface_ptr.read_recursive().nodes[node_index as usize]
+                    .clone()
+                    .unwrap();
+                let serial_node_ptr = serial_interface_ptr.read_recursive().nodes[node_index as usize]
+                    .clone()
+                    .unwrap();
+                let parallel_result = summary(
+                    parallel_dual_module
+                        .compute_maximum_update_length_dual_node(&parallel_node_ptr, true, false)
+                        .unwrap(),
+                );
+                let serial_result = summary(
+                    serial_dual_module
+                        .compute_maximum_update_length_dual_node(&serial_node_ptr, true, false)
+                        .unwrap(),
+                );
+                println!("node {node_index}: {parallel_result}");
+                assert_eq!(parallel_result, serial_result, "node {node_index}");
+            }
+            let parallel_group_max_update_length = parallel_dual_module.compute_maximum_update_length();
+            let serial_group_max_update_length = serial_dual_module.compute_maximum_update_length();
+            let Some(length) = serial_group_max_update_length.get_none_zero_growth() else {
+                break;
+            };
+            assert_eq!(parallel_group_max_update_length.get_none_zero_growth(), Some(length));
+            parallel_interface_ptr.grow(length, &mut parallel_dual_module);
+            serial_interface_ptr.grow(length, &mut serial_dual_module);
+        }
+    }
+
+    #[test]
+    fn dual_module_parallel_sharded_interface() {
+        // cargo test dual_module_parallel_sharded_interface -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(15, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 48),    // unit 0
+            VertexRange::new(64, 112),  // unit 1
+            VertexRange::new(128, 176), // unit 2
+            VertexRange::new(192, 240), // unit 3
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 4, by fusing 0 and 1
+            (2, 3), // unit 5, by fusing 2 and 3
+            (4, 5), // unit 6, by fusing 4 and 5
+        ];
+        let partition_info = partition_config.info();
+        let mut sharded_solver =
+            SolverDualParallel::new(&initializer, &partition_info, json!({ "sharded_interface": true }));
+        let mut shared_solver = SolverDualParallel::new(&initializer, &partition_info, json!({}));
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            sharded_solver.solve(&syndrome_pattern);
+            shared_solver.solve(&syndrome_pattern);
+            serial_solver.solve(&syndrome_pattern);
+            let expected = serial_solver.sum_dual_variables();
+            assert_eq!(sharded_solver.sum_dual_variables(), expected, "seed {seed}");
+            assert_eq!(shared_solver.sum_dual_variables(), expected, "seed {seed}");
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_subgraph(&sharded_solver.subgraph());
+            assert_eq!(subgraph_builder.total_weight(), expected, "seed {seed}");
+            sharded_solver.clear();
+            shared_solver.clear();
+            serial_solver.clear();
+        }
+        // a visualized solve takes the same path
+        let visualize_filename = "dual_module_parallel_sharded_interface.json".to_string();
+        let mut visualizer = Visualizer::new(
+            Some(visualize_data_folder() + visualize_filename.as_str()),
+            code.get_positions(),
+            true,
+        )
+        .unwrap();
+        print_visualize_link(visualize_filename);
+        let syndrome_pattern = code.generate_random_errors(0);
+        sharded_solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+        serial_solver.solve(&syndrome_pattern);
+        assert_eq!(sharded_solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+        // the units must form a single fusion tree, which a partition info from `PartitionConfig::info` always does
+        let mut dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        Arc::make_mut(&mut dual_module.partition_info).units[0].parent = None;
+        assert!(matches!(
+            dual_module.load_sharded(&DualModuleInterfacePtr::new_empty(), &syndrome_pattern),
+            Err(FusionError::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn dual_module_parallel_interface_segments() {
+        // cargo test dual_module_parallel_interface_segments -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(15, 0.2, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 48),    // unit 0
+            VertexRange::new(64, 112),  // unit 1
+            VertexRange::new(128, 176), // unit 2
+            VertexRange::new(192, 240), // unit 3
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 4, by fusing 0 and 1
+            (2, 3), // unit 5, by fusing 2 and 3
+            (4, 5), // unit 6, by fusing 4 and 5
+        ];
+        let partition_info = partition_config.info();
+        let mut dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        let syndrome_pattern = code.generate_random_errors(0);
+        dual_module.load_sharded(&interface_ptr, &syndrome_pattern).unwrap();
+        let mut flattened_nodes = vec![];
+        interface_ptr.flatten_nodes(&mut flattened_nodes);
+        let interface = interface_ptr.read_recursive();
+        assert!(!interface.segments.is_empty());
+        assert!(interface.segments.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(flattened_nodes.len() as NodeNum, interface.nodes_count());
+        for (node_index, node_ptr) in flattened_nodes.iter().enumerate() {
+            assert_eq!(&interface.get_node(node_index as NodeIndex), node_ptr);
+            let node_ptr = node_ptr.as_ref().unwrap();
+            assert_eq!(interface.get_node_by_key(node_ptr.key()).as_ref(), Some(node_ptr));
+        }
+    }
+
+    /// the sync requests are forwarded to the children in batches, which must give the same result when the children
+    /// execute them in parallel
+    #[test]
+    fn dual_module_parallel_batched_sync() {
+        // cargo test dual_module_parallel_batched_sync -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(7, 12, 0.03, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        let round_vertex_num = initializer.vertex_num / 13;
+        partition_config.partitions = vec![
+            VertexRange::new(0, 2 * round_vertex_num),                      // unit 0
+            VertexRange::new(3 * round_vertex_num, 6 * round_vertex_num),   // unit 1
+            VertexRange::new(7 * round_vertex_num, 9 * round_vertex_num),   // unit 2
+            VertexRange::new(10 * round_vertex_num, 13 * round_vertex_num), // unit 3
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 4, by fusing 0 and 1
+            (2, 3), // unit 5, by fusing 2 and 3
+            (4, 5), // unit 6, by fusing 4 and 5
+        ];
+        let partition_info = partition_config.info();
+        let mut parallel_solver =
+            SolverDualParallel::new(&initializer, &partition_info, json!({ "enable_parallel_execution": true }));
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            parallel_solver.solve(&syndrome_pattern);
+            serial_solver.solve(&syndrome_pattern);
+            let expected = serial_solver.sum_dual_variables();
+            assert_eq!(parallel_solver.sum_dual_variables(), expected, "seed {seed}");
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_subgraph(&parallel_solver.subgraph());
+            assert_eq!(subgraph_builder.total_weight(), expected, "seed {seed}");
+            parallel_solver.clear();
+            serial_solver.clear();
+        }
+    }
+
+    #[test]
+    fn dual_module_parallel_sync_request_pool() {
+        // cargo test dual_module_parallel_sync_request_pool -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(7, 12, 0.03, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        let round_vertex_num = initializer.vertex_num / 13;
+        partition_config.partitions = vec![
+            VertexRange::new(0, 6 * round_vertex_num),                     // unit 0
+            VertexRange::new(7 * round_vertex_num, 13 * round_vertex_num), // unit 1
+        ];
+        partition_config.fusions = vec![(0, 1)]; // unit 2, by fusing 0 and 1
+        let partition_info = partition_config.info();
+        let mut parallel_solver = SolverDualParallel::new(&initializer, &partition_info, json!({}));
+        let sync_pool = parallel_solver.dual_module.units[0].read_recursive().sync_pool.clone();
+        assert!((parallel_solver.dual_module.units.iter())
+            .all(|unit_ptr| Arc::ptr_eq(&unit_ptr.read_recursive().sync_pool, &sync_pool)));
+        let syndrome_patterns: Vec<_> = (0..10).map(|seed| code.generate_random_errors(seed)).collect();
+        let mut allocation_counts = vec![];
+        for _ in 0..2 {
+            for syndrome_pattern in syndrome_patterns.iter() {
+                parallel_solver.solve(syndrome_pattern);
+                parallel_solver.clear();
+            }
+            allocation_counts.push(sync_pool.allocation_count());
+        }
+        println!("allocation counts: {allocation_counts:?}");
+        assert!(allocation_counts[0] > 0);
+        assert_eq!(allocation_counts[0], allocation_counts[1], "a warmed-up pool never allocates");
+    }
+
+    /// a unit sends the sync batch to the inbox of each child without locking it, and the child executes the batch the next
+    /// time it's used
+    #[test]
+    fn dual_module_parallel_sync_inbox() {
+        // cargo test dual_module_parallel_sync_inbox -- --nocapture
+        let code = PhenomenologicalPlanarCode::new(7, 12, 0.03, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        let round_vertex_num = initializer.vertex_num / 13;
+        partition_config.partitions = vec![
+            VertexRange::new(0, 6 * round_vertex_num),                     // unit 0
+            VertexRange::new(7 * round_vertex_num, 13 * round_vertex_num), // unit 1
+        ];
+        partition_config.fusions = vec![(0, 1)]; // unit 2, by fusing 0 and 1
+        let partition_info = partition_config.info();
+        let dual_module = DualModuleParallel::<DualModuleSerial>::new_config(
+            &initializer,
+            &partition_info,
+            DualModuleParallelConfig::default(),
+        );
+        let (left_child_ptr, parent_ptr) = (dual_module.units[0].clone(), dual_module.units[2].clone());
+        let sync_request = Arc::new(SyncRequest {
+            mirror_unit_weak: parent_ptr.read_recursive().partition_unit.downgrade(),
+            vertex_index: 6 * round_vertex_num, // owned by the parent and mirrored by both children
+            propagated_dual_node: None,
+            propagated_grandson_dual_node: None,
+        });
+        {
+            let _left_child = left_child_ptr.read_recursive(); // would deadlock if the parent locked the child
+            parent_ptr.write().execute_sync_batch(&[sync_request]).unwrap();
+        }
+        let mut left_child = left_child_ptr.write();
+        let batch = left_child.sync_receiver.get_mut().unwrap().try_recv().unwrap();
+        assert_eq!(batch.len(), 1);
+        left_child.execute_sync_batch(&batch).unwrap();
+        assert!(left_child.sync_receiver.get_mut().unwrap().try_recv().is_err());
+    }
 }
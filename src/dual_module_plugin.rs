@@ -0,0 +1,613 @@
+//! Dual Module Plugin
+//!
+//! A stable C ABI for third-party dual modules, e.g. a driver of an FPGA implementation of the dual module, so that they
+//! can be compiled separately as shared libraries and loaded at runtime. A plugin exports a symbol named
+//! [`DUAL_MODULE_PLUGIN_SYMBOL`] of type [`DualModulePluginVTable`]; [`DualModulePlugin`] adapts it to [`DualModuleImpl`].
+//!
+//! All dual nodes are referred to by their [`DualNode::index`], all indices are `u64` and all weights are `i64`,
+//! regardless of the `u32_index` or `i32_weight` features of this library. Creating a blossom implies setting all its
+//! children to [`DualNodeGrowState::Stay`], and removing a blossom implies setting all its children to
+//! [`DualNodeGrowState::Grow`]; the plugin may still receive these speed changes explicitly.
+//!
+
+#![cfg_attr(feature = "unsafe_pointer", allow(dropping_references))]
+use super::dual_module::*;
+use super::pointers::*;
+use super::util::*;
+use std::ffi::c_void;
+use std::sync::Arc;
+
+/// the version of [`DualModulePluginVTable`], increased on any incompatible change
+pub const DUAL_MODULE_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// the name of the exported [`DualModulePluginVTable`] in a plugin library
+pub const DUAL_MODULE_PLUGIN_SYMBOL: &str = "fusion_blossom_dual_module_plugin";
+
+pub const PLUGIN_GROW_STATE_SHRINK: i32 = -1;
+pub const PLUGIN_GROW_STATE_STAY: i32 = 0;
+pub const PLUGIN_GROW_STATE_GROW: i32 = 1;
+
+/// two growing nodes touch each other: `(node_1, touching_1)` and `(node_2, touching_2)`
+pub const PLUGIN_OBSTACLE_CONFLICTING: u32 = 1;
+/// a growing node touches a virtual vertex: `(node_1, touching_1)` and the virtual vertex `node_2`; `flag` is non-zero if
+/// the virtual vertex is a mirror
+pub const PLUGIN_OBSTACLE_TOUCHING_VIRTUAL: u32 = 2;
+/// a shrinking blossom `node_1` reaches zero dual variable
+pub const PLUGIN_OBSTACLE_BLOSSOM_NEED_EXPAND: u32 = 3;
+/// a shrinking defect node `node_1` reaches zero dual variable; `flag` is non-zero if it's touching a growing node
+/// `(node_2, touching_2)`
+pub const PLUGIN_OBSTACLE_VERTEX_SHRINK_STOP: u32 = 4;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PluginWeightedEdge {
+    pub vertex_1: u64,
+    pub vertex_2: u64,
+    pub weight: i64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PluginEdgeModifier {
+    pub edge_index: u64,
+    pub weight: i64,
+}
+
+/// a conflict reported by the plugin, see `PLUGIN_OBSTACLE_*` for the meaning of the fields
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PluginObstacle {
+    pub kind: u32,
+    pub flag: u32,
+    pub node_1: u64,
+    pub touching_1: u64,
+    pub node_2: u64,
+    pub touching_2: u64,
+}
+
+/// the functions of a plugin; `instance` is the opaque pointer returned by `create`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DualModulePluginVTable {
+    /// must be [`DUAL_MODULE_PLUGIN_ABI_VERSION`]
+    pub abi_version: u32,
+    /// create an instance on a decoding graph, returning null on failure
+    pub create: unsafe extern "C" fn(
+        vertex_num: u64,
+        weighted_edges: *const PluginWeightedEdge,
+        edge_num: u64,
+        virtual_vertices: *const u64,
+        virtual_vertex_num: u64,
+    ) -> *mut c_void,
+    pub destroy: unsafe extern "C" fn(instance: *mut c_void),
+    /// remove all the dual nodes and restore all the edge weights
+    pub clear: unsafe extern "C" fn(instance: *mut c_void),
+    pub add_defect_node: unsafe extern "C" fn(instance: *mut c_void, node_index: u64, vertex_index: u64),
+    pub add_blossom:
+        unsafe extern "C" fn(instance: *mut c_void, node_index: u64, nodes_circle: *const u64, nodes_circle_length: u64),
+    pub remove_blossom: unsafe extern "C" fn(instance: *mut c_void, node_index: u64),
+    /// `grow_state` is one of `PLUGIN_GROW_STATE_*`
+    pub set_grow_state: unsafe extern "C" fn(instance: *mut c_void, node_index: u64, grow_state: i32),
+    /// write at most `capacity` obstacles and return the number of all obstacles; if there is none, write the maximum
+    /// length to grow to `max_length`, or `i64::MAX` if nothing is growing. Reporting only some of the obstacles is fine
+    /// because the remaining ones are reported again by the next call
+    pub compute_maximum_update_length: unsafe extern "C" fn(
+        instance: *mut c_void,
+        obstacles: *mut PluginObstacle,
+        capacity: u64,
+        max_length: *mut i64,
+    ) -> u64,
+    pub grow: unsafe extern "C" fn(instance: *mut c_void, length: i64),
+    pub load_edge_modifier:
+        unsafe extern "C" fn(instance: *mut c_void, edge_modifier: *const PluginEdgeModifier, edge_modifier_length: u64),
+}
+
+/// a plugin library loaded at runtime, which stays loaded as long as any [`DualModulePlugin`] created from it is alive
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct DualModulePluginLibrary {
+    handle: *mut c_void,
+    pub vtable: DualModulePluginVTable,
+}
+
+#[cfg(unix)]
+unsafe impl Send for DualModulePluginLibrary {}
+#[cfg(unix)]
+unsafe impl Sync for DualModulePluginLibrary {}
+
+#[cfg(unix)]
+impl DualModulePluginLibrary {
+    pub fn open(filepath: &str) -> Result<Arc<Self>, String> {
+        let c_filepath = std::ffi::CString::new(filepath).map_err(|err| format!("{err:?}"))?;
+        let c_symbol = std::ffi::CString::new(DUAL_MODULE_PLUGIN_SYMBOL).unwrap();
+        unsafe {
+            let handle = libc::dlopen(c_filepath.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+            if handle.is_null() {
+                return Err(format!("cannot load dual module plugin {filepath}: {}", Self::last_error()));
+            }
+            let symbol = libc::dlsym(handle, c_symbol.as_ptr()) as *const DualModulePluginVTable;
+            if symbol.is_null() {
+                let error = Self::last_error();
+                libc::dlclose(handle);
+                return Err(format!("cannot find {DUAL_MODULE_PLUGIN_SYMBOL} in {filepath}: {error}"));
+            }
+            // the vtable of another ABI version may have a different size, so only the leading version is read before the check
+            let abi_version = (*symbol).abi_version;
+            if abi_version != DUAL_MODULE_PLUGIN_ABI_VERSION {
+                libc::dlclose(handle);
+                return Err(format!(
+                    "dual module plugin {filepath} has ABI version {abi_version} but {DUAL_MODULE_PLUGIN_ABI_VERSION} is required"
+                ));
+            }
+            Ok(Arc::new(Self { handle, vtable: *symbol }))
+        }
+    }
+
+    unsafe fn last_error() -> String {
+        let error = libc::dlerror();
+        if error.is_null() {
+            "unknown error".to_string()
+        } else {
+            std::ffi::CStr::from_ptr(error).to_string_lossy().into_owned()
+        }
+    }
+
+    pub fn new_dual_module(self: &Arc<Self>, initializer: &SolverInitializer) -> Result<DualModulePlugin, String> {
+        let mut dual_module = DualModulePlugin::new(self.vtable, initializer)?;
+        dual_module.library = Some(self.clone());
+        Ok(dual_module)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for DualModulePluginLibrary {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dlclose(self.handle);
+        }
+    }
+}
+
+/// a dual module implemented by a plugin
+pub struct DualModulePlugin {
+    vtable: DualModulePluginVTable,
+    instance: *mut c_void,
+    /// the dual nodes by their index, to translate the obstacles reported by the plugin
+    nodes: Vec<Option<DualNodeWeak>>,
+    /// temporary buffer of obstacles, enlarged whenever the plugin reports more obstacles than its capacity
+    obstacles: Vec<PluginObstacle>,
+    /// always empty, because the plugin prepares the nodes by itself when creating a blossom
+    sync_requests: Vec<SyncRequest>,
+    #[cfg(unix)]
+    library: Option<Arc<DualModulePluginLibrary>>,
+}
+
+impl DualModulePlugin {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(vtable: DualModulePluginVTable, initializer: &SolverInitializer) -> Result<Self, String> {
+        if vtable.abi_version != DUAL_MODULE_PLUGIN_ABI_VERSION {
+            return Err(format!(
+                "dual module plugin has ABI version {} but {DUAL_MODULE_PLUGIN_ABI_VERSION} is required",
+                vtable.abi_version
+            ));
+        }
+        let weighted_edges: Vec<PluginWeightedEdge> = initializer
+            .weighted_edges
+            .iter()
+            .map(|&(vertex_1, vertex_2, weight)| PluginWeightedEdge {
+                vertex_1: vertex_1 as u64,
+                vertex_2: vertex_2 as u64,
+                weight: weight as i64,
+            })
+            .collect();
+        let virtual_vertices: Vec<u64> = initializer.virtual_vertices.iter().map(|&vertex| vertex as u64).collect();
+        let instance = unsafe {
+            (vtable.create)(
+                initializer.vertex_num as u64,
+                weighted_edges.as_ptr(),
+                weighted_edges.len() as u64,
+                virtual_vertices.as_ptr(),
+                virtual_vertices.len() as u64,
+            )
+        };
+        if instance.is_null() {
+            return Err("dual module plugin failed to create an instance".to_string());
+        }
+        Ok(Self {
+            vtable,
+            instance,
+            nodes: vec![],
+            obstacles: vec![PluginObstacle::default(); 16],
+            sync_requests: vec![],
+            #[cfg(unix)]
+            library: None,
+        })
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn get_node(&self, node_index: u64) -> DualNodePtr {
+        self.nodes
+            .get(node_index as usize)
+            .and_then(|node| node.as_ref())
+            .unwrap_or_else(|| panic!("dual module plugin reported an unknown node {node_index}"))
+            .upgrade_force()
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn translate_obstacle(&self, obstacle: &PluginObstacle) -> MaxUpdateLength {
+        match obstacle.kind {
+            PLUGIN_OBSTACLE_CONFLICTING => MaxUpdateLength::Conflicting(
+                (self.get_node(obstacle.node_1).key(), self.get_node(obstacle.touching_1).key()),
+                (self.get_node(obstacle.node_2).key(), self.get_node(obstacle.touching_2).key()),
+            ),
+            PLUGIN_OBSTACLE_TOUCHING_VIRTUAL => MaxUpdateLength::TouchingVirtual(
+                (self.get_node(obstacle.node_1).key(), self.get_node(obstacle.touching_1).key()),
+                (obstacle.node_2 as VertexIndex, obstacle.flag != 0),
+            ),
+            PLUGIN_OBSTACLE_BLOSSOM_NEED_EXPAND => MaxUpdateLength::BlossomNeedExpand(self.get_node(obstacle.node_1).key()),
+            PLUGIN_OBSTACLE_VERTEX_SHRINK_STOP => MaxUpdateLength::VertexShrinkStop((
+                self.get_node(obstacle.node_1).key(),
+                (obstacle.flag != 0)
+                    .then(|| (self.get_node(obstacle.node_2).key(), self.get_node(obstacle.touching_2).key())),
+            )),
+            kind => panic!("dual module plugin reported an unknown obstacle kind {kind}"),
+        }
+    }
+}
+
+impl Drop for DualModulePlugin {
+    fn drop(&mut self) {
+        unsafe {
+            (self.vtable.destroy)(self.instance);
+        }
+    }
+}
+
+impl DualModuleImpl for DualModulePlugin {
+    fn new_empty(_initializer: &SolverInitializer) -> Self {
+        panic!("a dual module plugin must be created from its vtable, see `DualModulePlugin::new`")
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        unsafe { (self.vtable.clear)(self.instance) }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+        let node = dual_node_ptr.read_recursive();
+        let node_index = node.index as usize;
+        if self.nodes.len() <= node_index {
+            self.nodes.resize(node_index + 1, None);
+        }
+        self.nodes[node_index] = Some(dual_node_ptr.downgrade());
+        match &node.class {
+            DualNodeClass::DefectVertex { defect_index } => unsafe {
+                (self.vtable.add_defect_node)(self.instance, node_index as u64, *defect_index as u64)
+            },
+            DualNodeClass::Blossom { nodes_circle, .. } => {
+                let nodes_circle: Vec<u64> = nodes_circle
+                    .iter()
+                    .map(|node_weak| node_weak.upgrade_force().read_recursive().index as u64)
+                    .collect();
+                unsafe {
+                    (self.vtable.add_blossom)(
+                        self.instance,
+                        node_index as u64,
+                        nodes_circle.as_ptr(),
+                        nodes_circle.len() as u64,
+                    )
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+        let node_index = dual_node_ptr.read_recursive().index;
+        unsafe { (self.vtable.remove_blossom)(self.instance, node_index as u64) }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+        let node_index = dual_node_ptr.read_recursive().index;
+        let grow_state = match grow_state {
+            DualNodeGrowState::Grow => PLUGIN_GROW_STATE_GROW,
+            DualNodeGrowState::Stay => PLUGIN_GROW_STATE_STAY,
+            DualNodeGrowState::Shrink => PLUGIN_GROW_STATE_SHRINK,
+        };
+        unsafe { (self.vtable.set_grow_state)(self.instance, node_index as u64, grow_state) }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        let mut max_length = i64::MAX;
+        let obstacle_num = unsafe {
+            (self.vtable.compute_maximum_update_length)(
+                self.instance,
+                self.obstacles.as_mut_ptr(),
+                self.obstacles.len() as u64,
+                &mut max_length,
+            )
+        } as usize;
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        if obstacle_num == 0 {
+            if max_length != i64::MAX {
+                group_max_update_length.add(MaxUpdateLength::NonZeroGrow((max_length as Weight, false)));
+            }
+            return group_max_update_length;
+        }
+        for obstacle in self.obstacles[..std::cmp::min(obstacle_num, self.obstacles.len())].iter() {
+            group_max_update_length.add(self.translate_obstacle(obstacle));
+        }
+        if obstacle_num > self.obstacles.len() {
+            self.obstacles.resize(obstacle_num, PluginObstacle::default());
+        }
+        group_max_update_length
+    }
+
+    fn grow(&mut self, length: Weight) {
+        unsafe { (self.vtable.grow)(self.instance, length as i64) }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
+        let edge_modifier: Vec<PluginEdgeModifier> = edge_modifier
+            .iter()
+            .map(|&(edge_index, weight)| PluginEdgeModifier {
+                edge_index: edge_index as u64,
+                weight: weight as i64,
+            })
+            .collect();
+        unsafe { (self.vtable.load_edge_modifier)(self.instance, edge_modifier.as_ptr(), edge_modifier.len() as u64) }
+        Ok(())
+    }
+
+    fn prepare_nodes_shrink(&mut self, _nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        Ok(&mut self.sync_requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dual_module_serial::*;
+    use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
+    use super::super::primal_module::*;
+    use super::super::primal_module_serial::*;
+    use super::*;
+
+    /// a plugin that runs the serial dual module behind the C ABI, driving it with its own interface
+    struct SerialPlugin {
+        interface_ptr: DualModuleInterfacePtr,
+        dual_module: DualModuleSerial,
+    }
+
+    impl SerialPlugin {
+        #[allow(clippy::unnecessary_cast)]
+        fn get_node(&self, node_index: u64) -> DualNodePtr {
+            self.interface_ptr.read_recursive().get_node(node_index as NodeIndex).unwrap()
+        }
+    }
+
+    unsafe extern "C" fn serial_create(
+        vertex_num: u64,
+        weighted_edges: *const PluginWeightedEdge,
+        edge_num: u64,
+        virtual_vertices: *const u64,
+        virtual_vertex_num: u64,
+    ) -> *mut c_void {
+        let weighted_edges = std::slice::from_raw_parts(weighted_edges, edge_num as usize)
+            .iter()
+            .map(|edge| {
+                (
+                    edge.vertex_1 as VertexIndex,
+                    edge.vertex_2 as VertexIndex,
+                    edge.weight as Weight,
+                )
+            })
+            .collect();
+        let virtual_vertices = std::slice::from_raw_parts(virtual_vertices, virtual_vertex_num as usize)
+            .iter()
+            .map(|&vertex| vertex as VertexIndex)
+            .collect();
+        let initializer = SolverInitializer::new(vertex_num as VertexNum, weighted_edges, virtual_vertices);
+        Box::into_raw(Box::new(SerialPlugin {
+            interface_ptr: DualModuleInterfacePtr::new_empty(),
+            dual_module: DualModuleSerial::new_empty(&initializer),
+        })) as *mut c_void
+    }
+
+    unsafe extern "C" fn serial_destroy(instance: *mut c_void) {
+        drop(Box::from_raw(instance as *mut SerialPlugin));
+    }
+
+    unsafe extern "C" fn serial_clear(instance: *mut c_void) {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        plugin.interface_ptr.clear();
+        plugin.dual_module.clear();
+    }
+
+    unsafe extern "C" fn serial_add_defect_node(instance: *mut c_void, node_index: u64, vertex_index: u64) {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        let node_ptr = plugin
+            .interface_ptr
+            .create_defect_node(vertex_index as VertexIndex, &mut plugin.dual_module);
+        assert_eq!(node_ptr.read_recursive().index as u64, node_index);
+    }
+
+    unsafe extern "C" fn serial_add_blossom(
+        instance: *mut c_void,
+        node_index: u64,
+        nodes_circle: *const u64,
+        nodes_circle_length: u64,
+    ) {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        let nodes_circle = std::slice::from_raw_parts(nodes_circle, nodes_circle_length as usize)
+            .iter()
+            .map(|&node_index| plugin.get_node(node_index))
+            .collect();
+        let node_ptr = plugin
+            .interface_ptr
+            .create_blossom(nodes_circle, vec![], &mut plugin.dual_module);
+        assert_eq!(node_ptr.read_recursive().index as u64, node_index);
+    }
+
+    unsafe extern "C" fn serial_remove_blossom(instance: *mut c_void, node_index: u64) {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        let node_ptr = plugin.get_node(node_index);
+        // the children that don't stay are set by the following `set_grow_state` calls
+        plugin
+            .interface_ptr
+            .expand_blossom_grow_states(node_ptr, |_| DualNodeGrowState::Stay, &mut plugin.dual_module);
+    }
+
+    unsafe extern "C" fn serial_set_grow_state(instance: *mut c_void, node_index: u64, grow_state: i32) {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        let grow_state = match grow_state {
+            PLUGIN_GROW_STATE_GROW => DualNodeGrowState::Grow,
+            PLUGIN_GROW_STATE_STAY => DualNodeGrowState::Stay,
+            _ => DualNodeGrowState::Shrink,
+        };
+        let node_ptr = plugin.get_node(node_index);
+        plugin
+            .interface_ptr
+            .set_grow_state(&node_ptr, grow_state, &mut plugin.dual_module);
+    }
+
+    unsafe extern "C" fn serial_compute_maximum_update_length(
+        instance: *mut c_void,
+        obstacles: *mut PluginObstacle,
+        capacity: u64,
+        max_length: *mut i64,
+    ) -> u64 {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        // the interface of a plugin is never fused, so the home index is the node index
+        let index_of = |node_key: &DualNodeKey| node_key.index as u64;
+        match plugin.dual_module.compute_maximum_update_length() {
+            GroupMaxUpdateLength::NonZeroGrow((length, _)) => {
+                *max_length = if length == Weight::MAX { i64::MAX } else { length as i64 };
+                0
+            }
+            GroupMaxUpdateLength::Conflicts((list, pending_stops)) => {
+                let obstacles = std::slice::from_raw_parts_mut(obstacles, capacity as usize);
+                let mut count = 0;
+                for max_update_length in list.iter().chain(pending_stops.values()) {
+                    let obstacle = match max_update_length {
+                        MaxUpdateLength::Conflicting((node_1, touching_1), (node_2, touching_2)) => PluginObstacle {
+                            kind: PLUGIN_OBSTACLE_CONFLICTING,
+                            flag: 0,
+                            node_1: index_of(node_1),
+                            touching_1: index_of(touching_1),
+                            node_2: index_of(node_2),
+                            touching_2: index_of(touching_2),
+                        },
+                        MaxUpdateLength::TouchingVirtual((node, touching), (virtual_vertex, is_mirror)) => PluginObstacle {
+                            kind: PLUGIN_OBSTACLE_TOUCHING_VIRTUAL,
+                            flag: *is_mirror as u32,
+                            node_1: index_of(node),
+                            touching_1: index_of(touching),
+                            node_2: *virtual_vertex as u64,
+                            touching_2: 0,
+                        },
+                        MaxUpdateLength::BlossomNeedExpand(node) => PluginObstacle {
+                            kind: PLUGIN_OBSTACLE_BLOSSOM_NEED_EXPAND,
+                            node_1: index_of(node),
+                            ..Default::default()
+                        },
+                        MaxUpdateLength::VertexShrinkStop((node, potential_conflict)) => {
+                            let (node_2, touching_2) = potential_conflict
+                                .as_ref()
+                                .map_or((0, 0), |(node_2, touching_2)| (index_of(node_2), index_of(touching_2)));
+                            PluginObstacle {
+                                kind: PLUGIN_OBSTACLE_VERTEX_SHRINK_STOP,
+                                flag: potential_conflict.is_some() as u32,
+                                node_1: index_of(node),
+                                touching_1: 0,
+                                node_2,
+                                touching_2,
+                            }
+                        }
+                        MaxUpdateLength::NonZeroGrow(_) => unreachable!(),
+                    };
+                    if count < obstacles.len() {
+                        obstacles[count] = obstacle;
+                    }
+                    count += 1;
+                }
+                count as u64
+            }
+        }
+    }
+
+    unsafe extern "C" fn serial_grow(instance: *mut c_void, length: i64) {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        plugin.interface_ptr.grow(length as Weight, &mut plugin.dual_module);
+    }
+
+    unsafe extern "C" fn serial_load_edge_modifier(
+        instance: *mut c_void,
+        edge_modifier: *const PluginEdgeModifier,
+        edge_modifier_length: u64,
+    ) {
+        let plugin = &mut *(instance as *mut SerialPlugin);
+        let edge_modifier: Vec<(EdgeIndex, Weight)> =
+            std::slice::from_raw_parts(edge_modifier, edge_modifier_length as usize)
+                .iter()
+                .map(|modifier| (modifier.edge_index as EdgeIndex, modifier.weight as Weight))
+                .collect();
+        plugin
+            .dual_module
+            .load_edge_modifier(&edge_modifier)
+            .expect("serial dual module supports edge modifier");
+    }
+
+    const SERIAL_PLUGIN: DualModulePluginVTable = DualModulePluginVTable {
+        abi_version: DUAL_MODULE_PLUGIN_ABI_VERSION,
+        create: serial_create,
+        destroy: serial_destroy,
+        clear: serial_clear,
+        add_defect_node: serial_add_defect_node,
+        add_blossom: serial_add_blossom,
+        remove_blossom: serial_remove_blossom,
+        set_grow_state: serial_set_grow_state,
+        compute_maximum_update_length: serial_compute_maximum_update_length,
+        grow: serial_grow,
+        load_edge_modifier: serial_load_edge_modifier,
+    };
+
+    #[test]
+    fn dual_module_plugin_serial() {
+        // cargo test dual_module_plugin_serial -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        code.set_erasure_probability(0.05);
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModulePlugin::new(SERIAL_PLUGIN, &initializer).unwrap();
+        let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            dual_module.load_erasures(&syndrome_pattern.erasures).unwrap();
+            primal_module.solve(
+                &interface_ptr,
+                &SyndromePattern::new_vertices(syndrome_pattern.defect_vertices.clone()),
+                &mut dual_module,
+            );
+            solver.solve(&syndrome_pattern);
+            assert_eq!(interface_ptr.sum_dual_variables(), solver.sum_dual_variables(), "seed {seed}");
+            let perfect_matching = primal_module.perfect_matching(&interface_ptr, &mut dual_module);
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+            subgraph_builder.load_perfect_matching(&perfect_matching);
+            assert_eq!(subgraph_builder.total_weight(), solver.sum_dual_variables(), "seed {seed}");
+            primal_module.clear();
+            interface_ptr.clear();
+            dual_module.clear();
+            solver.clear();
+        }
+        let mut incompatible = SERIAL_PLUGIN;
+        incompatible.abi_version += 1;
+        assert!(DualModulePlugin::new(incompatible, &initializer).is_err());
+    }
+}
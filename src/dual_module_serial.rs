@@ -14,8 +14,13 @@ use super::pointers::*;
 use super::util::*;
 use super::visualize::*;
 use crate::derivative::Derivative;
+use crate::priority_queue::PriorityQueue;
+use crate::rayon::prelude::*;
 use crate::weak_table::PtrWeakKeyHashMap;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::mem::size_of;
 
 pub struct DualModuleSerial {
     /// all vertices including virtual ones
@@ -51,6 +56,193 @@ pub struct DualModuleSerial {
     updated_boundary: Vec<(bool, EdgeWeak)>,
     /// temporary variable to reduce reallocation
     propagating_vertices: Vec<(VertexWeak, Option<DualNodeInternalWeak>)>,
+    /// use a priority queue of predicted events in [`DualModuleImpl::compute_maximum_update_length`] instead of scanning all the
+    /// active nodes every round; only effective when not partitioned, enabled by default
+    pub enable_event_queue: bool,
+    /// the predicted time of the next event (edge becomes tight, dual variable or edge growth reaches zero) of each active node,
+    /// indexed by the local node index; the prediction holds as long as the node and its neighbors are not changed
+    event_queue: EventQueue,
+    /// nodes whose predicted event is no longer valid, because the node itself or one of its neighbors has changed
+    event_dirty_nodes: BTreeSet<NodeIndex>,
+    /// the accumulated growth, which serves as the time axis of the predicted events
+    event_time: Weight,
+    /// recompute all the active nodes in the next round, e.g. when the edge weights are modified
+    event_full_scan: bool,
+    /// scan the active nodes with multiple threads in [`DualModuleImpl::compute_maximum_update_length`] when there are at
+    /// least this many of them, using the current rayon thread pool; only effective when the event queue is disabled
+    pub parallel_scan_threshold: Option<usize>,
+    /// the behavior when the total growth of an edge overflows
+    pub weight_overflow: WeightOverflow,
+    /// the first overflow under [`WeightOverflow::Error`] since the last clear
+    overflow: Option<&'static str>,
+}
+
+/// the queue of predicted events of the active dual nodes, see [`DualModuleSerial::enable_event_queue`]
+pub enum EventQueue {
+    /// general-purpose binary heap
+    Heap(PriorityQueue<NodeIndex, Reverse<Weight>>),
+    /// monotone bucket queue, see [`DualModuleSerial::use_bucket_queue`]
+    Bucket(BucketQueue),
+}
+
+impl EventQueue {
+    /// the bucket queue if no weight exceeds [`BucketQueue::DEFAULT_MAX_WEIGHT`], otherwise the binary heap
+    pub fn new(max_weight: Weight) -> Self {
+        if max_weight <= BucketQueue::DEFAULT_MAX_WEIGHT {
+            Self::Bucket(BucketQueue::new())
+        } else {
+            Self::Heap(PriorityQueue::new())
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match self {
+            Self::Heap(queue) => queue.clear(),
+            Self::Bucket(queue) => queue.clear(),
+        }
+    }
+
+    /// insert or update the event time of a node
+    pub fn push(&mut self, node_index: NodeIndex, event_time: Weight) {
+        match self {
+            Self::Heap(queue) => {
+                queue.push(node_index, Reverse(event_time));
+            }
+            Self::Bucket(queue) => queue.push(node_index, event_time),
+        }
+    }
+
+    pub fn remove(&mut self, node_index: NodeIndex) {
+        match self {
+            Self::Heap(queue) => {
+                queue.remove(&node_index);
+            }
+            Self::Bucket(queue) => queue.remove(node_index),
+        }
+    }
+
+    /// the earliest event time
+    pub fn peek(&mut self) -> Option<Weight> {
+        match self {
+            Self::Heap(queue) => queue.peek().map(|(_, Reverse(event_time))| *event_time),
+            Self::Bucket(queue) => queue.peek(),
+        }
+    }
+
+    /// pop the node with the earliest event
+    pub fn pop(&mut self) -> Option<(NodeIndex, Weight)> {
+        match self {
+            Self::Heap(queue) => queue.pop().map(|(node_index, Reverse(event_time))| (node_index, event_time)),
+            Self::Bucket(queue) => queue.pop(),
+        }
+    }
+
+    /// the estimated bytes of the allocations, see [`MemoryReport`]
+    pub fn memory_usage(&self) -> usize {
+        match self {
+            Self::Heap(queue) => queue.capacity() * (size_of::<(NodeIndex, Reverse<Weight>)>() + 2 * size_of::<usize>()),
+            Self::Bucket(queue) => {
+                queue.buckets.iter().map(MemoryReport::vec).sum::<usize>()
+                    + queue.buckets.capacity() * size_of::<Vec<NodeIndex>>()
+                    + MemoryReport::vec(&queue.event_times)
+            }
+        }
+    }
+}
+
+/// a monotone bucket queue with one bucket per integer time: pushing and popping an event takes O(1) time, as long as
+/// the events are close to the current time, e.g. when all the weights are small integers
+#[derive(Debug, Clone, Default)]
+pub struct BucketQueue {
+    /// the time of the first bucket
+    base_time: Weight,
+    /// `buckets[i]` contains the nodes with event at `base_time + i`, including stale entries which are lazily removed
+    buckets: VecDeque<Vec<NodeIndex>>,
+    /// the current event time of each node, used to recognize stale entries
+    event_times: Vec<Option<Weight>>,
+    /// the number of nodes without any event, i.e. event time of [`Weight::MAX`]
+    unbounded_count: usize,
+}
+
+impl BucketQueue {
+    /// the bucket queue is used by default when all the weights are at most this, see [`EventQueue::new`]
+    pub const DEFAULT_MAX_WEIGHT: Weight = 64;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.base_time = 0;
+        self.buckets.clear();
+        self.event_times.clear();
+        self.unbounded_count = 0;
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn push(&mut self, node_index: NodeIndex, event_time: Weight) {
+        self.remove(node_index);
+        if self.event_times.len() <= node_index as usize {
+            self.event_times.resize(node_index as usize + 1, None);
+        }
+        self.event_times[node_index as usize] = Some(event_time);
+        if event_time == Weight::MAX {
+            self.unbounded_count += 1;
+            return;
+        }
+        if self.buckets.is_empty() {
+            self.base_time = event_time;
+        }
+        while event_time < self.base_time {
+            self.buckets.push_front(vec![]);
+            self.base_time -= 1;
+        }
+        let offset = (event_time - self.base_time) as usize;
+        if self.buckets.len() <= offset {
+            self.buckets.resize(offset + 1, vec![]);
+        }
+        self.buckets[offset].push(node_index);
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn remove(&mut self, node_index: NodeIndex) {
+        if let Some(Some(event_time)) = self.event_times.get_mut(node_index as usize).map(|slot| slot.take()) {
+            if event_time == Weight::MAX {
+                self.unbounded_count -= 1;
+            }
+        }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn peek(&mut self) -> Option<Weight> {
+        while let Some(bucket) = self.buckets.front_mut() {
+            while let Some(&node_index) = bucket.last() {
+                if self.event_times[node_index as usize] == Some(self.base_time) {
+                    return Some(self.base_time);
+                }
+                bucket.pop(); // stale
+            }
+            self.buckets.pop_front();
+            self.base_time += 1;
+        }
+        if self.unbounded_count > 0 {
+            Some(Weight::MAX)
+        } else {
+            None
+        }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn pop(&mut self) -> Option<(NodeIndex, Weight)> {
+        match self.peek() {
+            Some(Weight::MAX) | None => None,
+            Some(event_time) => {
+                let node_index = self.buckets[0].pop().unwrap();
+                self.event_times[node_index as usize] = None;
+                Some((node_index, event_time))
+            }
+        }
+    }
 }
 
 /// records information only available when used as a unit in the partitioned dual module
@@ -61,7 +253,9 @@ pub struct UnitModuleInfo {
     pub unit_index: usize,
     /// all mirrored vertices (excluding owned ones) to query if this module contains the vertex
     pub mirrored_vertices: HashMap<VertexIndex, VertexIndex>,
-    /// owned dual nodes range
+    /// the interface that creates the owned dual nodes, see [`DualNode::home_interface`]
+    pub owning_dual_interface: Option<DualModuleInterfaceWeak>,
+    /// owned dual nodes range, in terms of [`DualNode::home_index`] so that it stays valid when the interfaces are fused
     pub owning_dual_range: NodeRange,
     /// hash table for mapping [`DualNodePtr`] to internal [`DualNodeInternalPtr`]
     pub dual_node_pointers: PtrWeakKeyHashMap<DualNodeWeak, usize>,
@@ -177,6 +371,14 @@ pub struct Edge {
     pub dedup_timestamp: (FastClearTimestamp, FastClearTimestamp),
 }
 
+impl Edge {
+    /// the growth from both ends, which saturates instead of overflowing so that it's still comparable to the weight
+    #[inline]
+    pub fn total_growth(&self) -> Weight {
+        self.left_growth.saturating_add(self.right_growth)
+    }
+}
+
 pub type EdgePtr = FastClearArcManualSafeLockDangerous<Edge>;
 pub type EdgeWeak = FastClearWeakManualSafeLockDangerous<Edge>;
 
@@ -199,6 +401,10 @@ impl DualModuleImpl for DualModuleSerial {
     /// initialize the dual module, which is supposed to be reused for multiple decoding tasks with the same structure
     #[allow(clippy::unnecessary_cast)]
     fn new_empty(initializer: &SolverInitializer) -> Self {
+        assert!(
+            initializer.virtual_vertex_groups.is_empty(),
+            "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`"
+        );
         let active_timestamp = 0;
         // create vertices
         let vertices: Vec<VertexPtr> = (0..initializer.vertex_num)
@@ -281,9 +487,14 @@ impl DualModuleImpl for DualModuleSerial {
             }
             edges.push(edge_ptr);
         }
+        let capacity_hint = initializer.capacity_hint.unwrap_or_default();
+        let max_weight = (initializer.weighted_edges.iter())
+            .map(|&(_, _, weight)| weight)
+            .max()
+            .unwrap_or(0);
         Self {
             vertices,
-            nodes: vec![],
+            nodes: Vec::with_capacity(capacity_hint.max_node_num()),
             nodes_length: 0,
             edges,
             active_timestamp: 0,
@@ -291,13 +502,21 @@ impl DualModuleImpl for DualModuleSerial {
             edge_num: initializer.weighted_edges.len(),
             owning_range: VertexRange::new(0, initializer.vertex_num),
             unit_module_info: None, // disabled
-            active_list: vec![],
+            active_list: Vec::with_capacity(capacity_hint.max_node_num()),
             current_cycle: 0,
             edge_modifier: EdgeWeightModifier::new(),
             edge_dedup_timestamp: 0,
             sync_requests: vec![],
-            updated_boundary: vec![],
-            propagating_vertices: vec![],
+            updated_boundary: Vec::with_capacity(capacity_hint.active_vertex_num),
+            propagating_vertices: Vec::with_capacity(capacity_hint.active_vertex_num),
+            enable_event_queue: true,
+            event_queue: EventQueue::new(max_weight),
+            event_dirty_nodes: BTreeSet::new(),
+            event_time: 0,
+            event_full_scan: false,
+            parallel_scan_threshold: None,
+            weight_overflow: WeightOverflow::default(),
+            overflow: None,
         }
     }
 
@@ -311,13 +530,20 @@ impl DualModuleImpl for DualModuleSerial {
             let mut edge = edge_ptr.write(self.active_timestamp);
             edge.weight = original_weight;
         }
+        self.edge_modifier.erasure_probabilities.clear();
         self.clear_graph();
         self.nodes_length = 0; // without actually dropping all the nodes, to enable constant time clear
         if let Some(unit_module_info) = self.unit_module_info.as_mut() {
+            unit_module_info.owning_dual_interface = None;
             unit_module_info.owning_dual_range = VertexRange::new(0, 0);
             unit_module_info.dual_node_pointers = PtrWeakKeyHashMap::<DualNodeWeak, usize>::new();
         }
         self.active_list.clear();
+        self.event_queue.clear();
+        self.event_dirty_nodes.clear();
+        self.event_time = 0;
+        self.event_full_scan = false;
+        self.overflow = None;
     }
 
     /// add a new dual node from dual module root
@@ -431,6 +657,7 @@ impl DualModuleImpl for DualModuleSerial {
                 }
             }
         }
+        self.mark_event_dirty(&node_internal_ptr);
         self.active_list.push(node_internal_ptr.downgrade());
         self.nodes_length += 1;
         if self.nodes.len() < self.nodes_length {
@@ -463,6 +690,8 @@ impl DualModuleImpl for DualModuleSerial {
             self.nodes[node_idx as usize].as_ref().unwrap() == &dual_node_internal_ptr,
             "the blossom doesn't belong to this DualModuleInterface"
         );
+        // neighbors will see the children instead
+        self.mark_event_dirty(&dual_node_internal_ptr);
         // recover edge belongings
         for (is_left, edge_weak) in dual_node_internal.boundary.iter() {
             let edge_ptr = edge_weak.upgrade_force();
@@ -512,6 +741,8 @@ impl DualModuleImpl for DualModuleSerial {
             unreachable!()
         }
         self.nodes[node_idx as usize] = None; // simply remove this blossom node
+        self.event_queue.remove(node_idx);
+        self.event_dirty_nodes.remove(&node_idx);
     }
 
     fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
@@ -520,342 +751,78 @@ impl DualModuleImpl for DualModuleSerial {
             let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
             self.active_list.push(dual_node_internal_ptr.downgrade())
         }
+        if self.is_event_queue_enabled() {
+            let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
+            self.mark_event_dirty(&dual_node_internal_ptr);
+        }
     }
 
-    #[allow(clippy::collapsible_else_if)]
     fn compute_maximum_update_length_dual_node(
         &mut self,
         dual_node_ptr: &DualNodePtr,
         is_grow: bool,
         simultaneous_update: bool,
-    ) -> MaxUpdateLength {
-        let active_timestamp = self.active_timestamp;
+    ) -> Result<MaxUpdateLength, FusionError> {
         if !simultaneous_update {
             // when `simultaneous_update` is set, it's assumed that all nodes are prepared to grow or shrink
             // this is because if we dynamically prepare them, it would be inefficient
             self.prepare_dual_node_growth(dual_node_ptr, is_grow);
         }
-        let mut max_length_abs = Weight::MAX;
-        let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
-        let dual_node_internal = dual_node_internal_ptr.read_recursive();
-        if !is_grow {
-            if dual_node_internal.dual_variable == 0 {
-                let dual_node = dual_node_ptr.read_recursive();
-                match dual_node.class {
-                    DualNodeClass::Blossom { .. } => return MaxUpdateLength::BlossomNeedExpand(dual_node_ptr.clone()),
-                    DualNodeClass::DefectVertex { defect_index } => {
-                        // try to report Conflicting event or give a VertexShrinkStop with potential conflicting node
-                        if let Some(vertex_index) = self.get_vertex_index(defect_index) {
-                            // since propagated node is never removed, this event could happen with no vertex
-                            let vertex_ptr = &self.vertices[vertex_index];
-                            let vertex = vertex_ptr.read_recursive(active_timestamp);
-                            let mut potential_conflict: Option<(DualNodePtr, DualNodePtr)> = None;
-                            for edge_weak in vertex.edges.iter() {
-                                let edge_ptr = edge_weak.upgrade_force();
-                                let edge = edge_ptr.read_recursive(active_timestamp);
-                                let is_left = vertex_ptr.downgrade() == edge.left;
-                                let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
-                                if remaining_length == 0 {
-                                    let peer_dual_node = if is_left {
-                                        &edge.right_dual_node
-                                    } else {
-                                        &edge.left_dual_node
-                                    };
-                                    if let Some(peer_dual_node_ptr) = peer_dual_node {
-                                        let peer_grandson_dual_node = if is_left {
-                                            &edge.right_grandson_dual_node
-                                        } else {
-                                            &edge.left_grandson_dual_node
-                                        };
-                                        let peer_dual_node_ptr =
-                                            peer_dual_node_ptr.upgrade_force().read_recursive().origin.upgrade_force();
-                                        let peer_grandson_dual_node_ptr = peer_grandson_dual_node
-                                            .as_ref()
-                                            .unwrap()
-                                            .upgrade_force()
-                                            .read_recursive()
-                                            .origin
-                                            .upgrade_force();
-                                        if peer_dual_node_ptr.read_recursive().grow_state == DualNodeGrowState::Grow {
-                                            if let Some((other_dual_node_ptr, other_grandson_dual_node)) =
-                                                &potential_conflict
-                                            {
-                                                if &peer_dual_node_ptr != other_dual_node_ptr {
-                                                    return MaxUpdateLength::Conflicting(
-                                                        (other_dual_node_ptr.clone(), other_grandson_dual_node.clone()),
-                                                        (peer_dual_node_ptr, peer_grandson_dual_node_ptr),
-                                                    );
-                                                }
-                                            } else {
-                                                potential_conflict = Some((peer_dual_node_ptr, peer_grandson_dual_node_ptr));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            return MaxUpdateLength::VertexShrinkStop((dual_node_ptr.clone(), potential_conflict));
-                        } else {
-                            return MaxUpdateLength::VertexShrinkStop((dual_node_ptr.clone(), None));
-                        }
-                    }
-                }
+        Ok(self.compute_maximum_update_length_prepared_dual_node(dual_node_ptr, is_grow))
+    }
+
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        if self.is_event_queue_enabled() {
+            return self.compute_maximum_update_length_event_queue();
+        }
+        // first prepare all nodes for individual grow or shrink; Stay nodes will be prepared to shrink in order to minimize effect on others
+        self.prepare_all_nodes();
+        // after preparing all the growth, there should be no sync requests
+        debug_assert!(
+            self.sync_requests.is_empty(),
+            "no sync requests should arise here; make sure to deal with all sync requests before growing"
+        );
+        if matches!(self.parallel_scan_threshold, Some(threshold) if self.active_list.len() >= threshold) {
+            // scan chunks in parallel and merge them in order, so that the result doesn't depend on the thread count
+            let chunk_size = std::cmp::max(1, self.active_list.len().div_ceil(4 * rayon::current_num_threads()));
+            let chunk_results: Vec<GroupMaxUpdateLength> = self
+                .active_list
+                .par_chunks(chunk_size)
+                .map(|chunk| self.compute_maximum_update_length_active_nodes(chunk))
+                .collect();
+            let mut group_max_update_length = GroupMaxUpdateLength::new();
+            for chunk_result in chunk_results.into_iter() {
+                group_max_update_length.extend(chunk_result);
             }
-            if !dual_node_internal.overgrown_stack.is_empty() {
-                let last_index = dual_node_internal.overgrown_stack.len() - 1;
-                let (_, overgrown) = &dual_node_internal.overgrown_stack[last_index];
-                max_length_abs = std::cmp::min(max_length_abs, *overgrown);
-            }
-            max_length_abs = std::cmp::min(max_length_abs, dual_node_internal.dual_variable);
-        }
-        for (is_left, edge_weak) in dual_node_internal.boundary.iter() {
-            let edge_ptr = edge_weak.upgrade_force();
-            let is_left = *is_left;
-            let edge = edge_ptr.read_recursive(active_timestamp);
-            if is_grow {
-                // first check if both side belongs to the same tree node, if so, no constraint on this edge
-                let peer_dual_node_internal_ptr: Option<DualNodeInternalPtr> = if is_left {
-                    edge.right_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
-                } else {
-                    edge.left_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
-                };
-                match peer_dual_node_internal_ptr {
-                    Some(peer_dual_node_internal_ptr) => {
-                        if peer_dual_node_internal_ptr == dual_node_internal_ptr {
-                            continue;
-                        } else {
-                            let peer_dual_node_internal = peer_dual_node_internal_ptr.read_recursive();
-                            let peer_dual_node_ptr = peer_dual_node_internal.origin.upgrade_force();
-                            let peer_dual_node = peer_dual_node_ptr.read_recursive();
-                            let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
-                            let local_max_length_abs = match peer_dual_node.grow_state {
-                                DualNodeGrowState::Grow => {
-                                    debug_assert!(remaining_length % 2 == 0, "there is odd gap between two growing nodes, please make sure all weights are even numbers");
-                                    remaining_length / 2
-                                }
-                                DualNodeGrowState::Shrink => {
-                                    // Yue 2022.9.5: remove Conflicting event detection here, move it to the 0-dual syndrome node
-                                    continue;
-                                }
-                                DualNodeGrowState::Stay => remaining_length,
-                            };
-                            if local_max_length_abs == 0 {
-                                let peer_grandson_ptr = if is_left {
-                                    edge.right_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                } else {
-                                    edge.left_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                };
-                                let grandson_ptr = if is_left {
-                                    edge.left_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                } else {
-                                    edge.right_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                };
-                                return MaxUpdateLength::Conflicting(
-                                    (peer_dual_node_ptr.clone(), peer_grandson_ptr),
-                                    (dual_node_ptr.clone(), grandson_ptr),
-                                );
-                            }
-                            max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
-                        }
-                    }
-                    None => {
-                        let local_max_length_abs = edge.weight - edge.left_growth - edge.right_growth;
-                        if local_max_length_abs == 0 {
-                            // check if peer is virtual node
-                            let peer_vertex_ptr = if is_left {
-                                edge.right.upgrade_force()
-                            } else {
-                                edge.left.upgrade_force()
-                            };
-                            let peer_vertex = peer_vertex_ptr.read_recursive(active_timestamp);
-                            if peer_vertex.is_virtual || peer_vertex.is_mirror_blocked() {
-                                let grandson_ptr = if is_left {
-                                    edge.left_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                } else {
-                                    edge.right_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                };
-                                return MaxUpdateLength::TouchingVirtual(
-                                    (dual_node_ptr.clone(), grandson_ptr),
-                                    (peer_vertex.vertex_index, peer_vertex.is_mirror_blocked()),
-                                );
-                            } else {
-                                println!("edge: {edge_ptr:?}, peer_vertex_ptr: {peer_vertex_ptr:?}");
-                                unreachable!("this edge should've been removed from boundary because it's already fully grown, and it's peer vertex is not virtual")
-                            }
-                        }
-                        max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
-                    }
-                }
-            } else {
-                if is_left {
-                    if edge.left_growth == 0 {
-                        unreachable!()
-                    }
-                    max_length_abs = std::cmp::min(max_length_abs, edge.left_growth);
-                } else {
-                    if edge.right_growth == 0 {
-                        unreachable!()
-                    }
-                    max_length_abs = std::cmp::min(max_length_abs, edge.right_growth);
-                }
-            }
-        }
-        MaxUpdateLength::NonZeroGrow((max_length_abs, dual_node_internal.boundary.is_empty()))
-    }
-
-    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
-        // first prepare all nodes for individual grow or shrink; Stay nodes will be prepared to shrink in order to minimize effect on others
-        self.prepare_all();
-        // after preparing all the growth, there should be no sync requests
-        debug_assert!(
-            self.sync_requests.is_empty(),
-            "no sync requests should arise here; make sure to deal with all sync requests before growing"
-        );
-        let mut group_max_update_length = GroupMaxUpdateLength::new();
-        for i in 0..self.active_list.len() {
-            let dual_node_ptr = {
-                let internal_dual_node_ptr = self.active_list[i].upgrade_force();
-                let dual_node_internal = internal_dual_node_ptr.read_recursive();
-                dual_node_internal.origin.upgrade_force()
-            };
-            let dual_node = dual_node_ptr.read_recursive();
-            let is_grow = match dual_node.grow_state {
-                DualNodeGrowState::Grow => true,
-                DualNodeGrowState::Shrink => false,
-                DualNodeGrowState::Stay => continue,
-            };
-            drop(dual_node); // unlock, otherwise it causes deadlock when updating the dual node
-            let max_update_length = self.compute_maximum_update_length_dual_node(&dual_node_ptr, is_grow, true);
-            group_max_update_length.add(max_update_length);
-        }
-        group_max_update_length
-    }
-
-    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
-        let active_timestamp = self.active_timestamp;
-        if length == 0 {
-            eprintln!("[warning] calling `grow_dual_node` with zero length, nothing to do");
-            return;
-        }
-        self.prepare_dual_node_growth(dual_node_ptr, length > 0);
-        let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
-        {
-            // update node dual variable and do sanity check
-            let mut dual_node_internal = dual_node_internal_ptr.write();
-            dual_node_internal.dual_variable += length;
-            debug_assert!(
-                dual_node_internal.dual_variable >= 0,
-                "shrinking to negative dual variable is forbidden"
-            );
-            // update over-grown vertices
-            if !dual_node_internal.overgrown_stack.is_empty() {
-                let last_index = dual_node_internal.overgrown_stack.len() - 1;
-                let (_, overgrown) = &mut dual_node_internal.overgrown_stack[last_index];
-                if length < 0 {
-                    debug_assert!(*overgrown >= -length, "overgrown vertex cannot shrink so much");
-                }
-                *overgrown += length;
-            }
-        }
-        let dual_node_internal = dual_node_internal_ptr.read_recursive();
-        for (is_left, edge_weak) in dual_node_internal.boundary.iter() {
-            let edge_ptr = edge_weak.upgrade_force();
-            let is_left = *is_left;
-            let (growth, weight) = {
-                // minimize writer lock acquisition
-                let mut edge = edge_ptr.write(active_timestamp);
-                if is_left {
-                    edge.left_growth += length;
-                    debug_assert!(edge.left_growth >= 0, "negative growth forbidden");
-                } else {
-                    edge.right_growth += length;
-                    debug_assert!(edge.right_growth >= 0, "negative growth forbidden");
-                }
-                (edge.left_growth + edge.right_growth, edge.weight)
-            };
-            let edge = edge_ptr.read_recursive(active_timestamp);
-            if growth > weight {
-                // first check for if both side belongs to the same dual node, if so, it's ok
-                let dual_node_internal_ptr_2: &Option<DualNodeInternalWeak> = if is_left {
-                    &edge.right_dual_node
-                } else {
-                    &edge.left_dual_node
-                };
-                if dual_node_internal_ptr_2.is_none()
-                    || dual_node_internal_ptr_2.as_ref().unwrap() != &dual_node_internal_ptr.downgrade()
-                {
-                    let left_ptr = edge.left.upgrade_force();
-                    let right_ptr = edge.right.upgrade_force();
-                    panic!(
-                        "over-grown edge ({},{}): {}/{}",
-                        left_ptr.read_recursive(active_timestamp).vertex_index,
-                        right_ptr.read_recursive(active_timestamp).vertex_index,
-                        growth,
-                        weight
-                    );
-                }
-            } else if growth < 0 {
-                let left_ptr = edge.left.upgrade_force();
-                let right_ptr = edge.right.upgrade_force();
-                panic!(
-                    "under-grown edge ({},{}): {}/{}",
-                    left_ptr.read_recursive(active_timestamp).vertex_index,
-                    right_ptr.read_recursive(active_timestamp).vertex_index,
-                    growth,
-                    weight
-                );
-            }
-        }
-    }
-
-    fn grow(&mut self, length: Weight) {
-        debug_assert!(length > 0, "only positive growth is supported");
-        self.renew_active_list();
-        // first handle shrinks and then grow, to make sure they don't conflict
-        for i in 0..self.active_list.len() {
-            let dual_node_ptr = {
-                let internal_dual_node_ptr = self.active_list[i].upgrade_force();
-                let dual_node_internal = internal_dual_node_ptr.read_recursive();
-                dual_node_internal.origin.upgrade_force()
-            };
-            let dual_node = dual_node_ptr.read_recursive();
-            if matches!(dual_node.grow_state, DualNodeGrowState::Shrink) {
-                self.grow_dual_node(&dual_node_ptr, -length);
+            return group_max_update_length;
+        }
+        self.compute_maximum_update_length_active_nodes(&self.active_list)
+    }
+
+    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) -> Result<(), FusionError> {
+        if self.is_event_queue_enabled() {
+            // growing a single node breaks the prediction of itself and its neighbors
+            let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
+            self.mark_event_dirty(&dual_node_internal_ptr);
+        }
+        self.apply_dual_node_growth(dual_node_ptr, length);
+        self.strict_check_growth();
+        Ok(())
+    }
+
+    fn grow(&mut self, length: Weight) {
+        debug_assert!(length > 0, "only positive growth is supported");
+        self.renew_active_list();
+        // first handle shrinks and then grow, to make sure they don't conflict
+        for i in 0..self.active_list.len() {
+            let dual_node_ptr = {
+                let internal_dual_node_ptr = self.active_list[i].upgrade_force();
+                let dual_node_internal = internal_dual_node_ptr.read_recursive();
+                dual_node_internal.origin.upgrade_force()
+            };
+            let dual_node = dual_node_ptr.read_recursive();
+            if matches!(dual_node.grow_state, DualNodeGrowState::Shrink) {
+                self.apply_dual_node_growth(&dual_node_ptr, -length);
             }
         }
         // then grow those needed
@@ -867,17 +834,22 @@ impl DualModuleImpl for DualModuleSerial {
             };
             let dual_node = dual_node_ptr.read_recursive();
             if matches!(dual_node.grow_state, DualNodeGrowState::Grow) {
-                self.grow_dual_node(&dual_node_ptr, length);
+                self.apply_dual_node_growth(&dual_node_ptr, length);
             }
         }
+        self.event_time += length;
+        self.strict_check_growth();
     }
 
     #[allow(clippy::unnecessary_cast)]
-    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) {
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
         debug_assert!(
             !self.edge_modifier.has_modified_edges(),
             "the current erasure modifier is not clean, probably forget to clean the state?"
         );
+        if let Some(&(edge_index, weight)) = edge_modifier.iter().find(|(_, weight)| *weight < 0) {
+            return Err(SyndromeError::NegativeDynamicWeight { edge_index, weight }.into());
+        }
         let active_timestamp = self.active_timestamp;
         for (edge_index, target_weight) in edge_modifier.iter() {
             let edge_ptr = &self.edges[*edge_index as usize];
@@ -887,72 +859,56 @@ impl DualModuleImpl for DualModuleSerial {
             edge.weight = *target_weight;
             self.edge_modifier.push_modified_edge(*edge_index, original_weight);
         }
+        self.event_full_scan = true;
+        Ok(())
     }
 
-    fn prepare_all(&mut self) -> &mut Vec<SyncRequest> {
-        debug_assert!(
-            self.sync_requests.is_empty(),
-            "make sure to remove all sync requests before prepare to avoid out-dated requests"
-        );
-        self.renew_active_list();
-        for i in 0..self.active_list.len() {
-            let dual_node_ptr = {
-                if let Some(internal_dual_node_ptr) = self.active_list[i].upgrade() {
-                    let dual_node_internal = internal_dual_node_ptr.read_recursive();
-                    dual_node_internal.origin.upgrade_force()
-                } else {
-                    continue; // a blossom could be in the active list even after it's been removed
-                }
-            };
-            let dual_node = dual_node_ptr.read_recursive();
-            match dual_node.grow_state {
-                DualNodeGrowState::Grow => {}
-                DualNodeGrowState::Shrink => {
-                    self.prepare_dual_node_growth(&dual_node_ptr, false);
-                }
-                DualNodeGrowState::Stay => {} // do not touch, Stay nodes might have become a part of a blossom, so it's not safe to change the boundary
-            };
-        }
-        for i in 0..self.active_list.len() {
-            let dual_node_ptr = {
-                if let Some(internal_dual_node_ptr) = self.active_list[i].upgrade() {
-                    let dual_node_internal = internal_dual_node_ptr.read_recursive();
-                    dual_node_internal.origin.upgrade_force()
-                } else {
-                    continue; // a blossom could be in the active list even after it's been removed
-                }
-            };
-            let dual_node = dual_node_ptr.read_recursive();
-            match dual_node.grow_state {
-                DualNodeGrowState::Grow => {
-                    self.prepare_dual_node_growth(&dual_node_ptr, true);
-                }
-                DualNodeGrowState::Shrink => {}
-                DualNodeGrowState::Stay => {} // do not touch, Stay nodes might have become a part of a blossom, so it's not safe to change the boundary
-            };
+    fn load_erasure_probabilities(&mut self, erasures: &[(EdgeIndex, f64)], weight_scale: f64) -> Result<(), FusionError> {
+        let edge_modifier: Vec<_> = (erasures.iter())
+            .map(|&(edge_index, p)| (edge_index, EdgeWeightModifier::erasure_weight(p, weight_scale)))
+            .collect();
+        self.load_edge_modifier(&edge_modifier)?;
+        for &(edge_index, p) in erasures.iter() {
+            self.edge_modifier.push_erasure_probability(edge_index, p);
         }
-        &mut self.sync_requests
+        Ok(())
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn get_edge_growth(&self, edge_index: EdgeIndex) -> Result<(Weight, Weight), FusionError> {
+        let edge_ptr = &self.edges[edge_index as usize];
+        edge_ptr.dynamic_clear(self.active_timestamp); // may visit stale edges
+        let edge = edge_ptr.read_recursive(self.active_timestamp);
+        debug_assert_eq!(
+            edge.edge_index, edge_index,
+            "edge index mismatch, partitioned module is not supported"
+        );
+        Ok((edge.total_growth(), edge.weight))
     }
 
-    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> &mut Vec<SyncRequest> {
+    fn prepare_all(&mut self) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        Ok(self.prepare_all_nodes())
+    }
+
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
         debug_assert!(
             self.sync_requests.is_empty(),
             "make sure to remove all sync requests before prepare to avoid out-dated requests"
         );
         for dual_node_ptr in nodes_circle.iter() {
-            if self.contains_dual_node(dual_node_ptr) {
+            if self.contains_dual_node(dual_node_ptr)? {
                 self.prepare_dual_node_growth(dual_node_ptr, false); // prepare to shrink
             }
         }
-        &mut self.sync_requests
+        Ok(&mut self.sync_requests)
     }
 
-    fn contains_dual_node(&self, dual_node_ptr: &DualNodePtr) -> bool {
-        self.get_dual_node_index(dual_node_ptr).is_some()
+    fn contains_dual_node(&self, dual_node_ptr: &DualNodePtr) -> Result<bool, FusionError> {
+        Ok(self.get_dual_node_index(dual_node_ptr).is_some())
     }
 
     #[allow(clippy::unnecessary_cast)]
-    fn new_partitioned(partitioned_initializer: &PartitionedSolverInitializer) -> Self {
+    fn new_partitioned(partitioned_initializer: &PartitionedSolverInitializer) -> Result<Self, FusionError> {
         let active_timestamp = 0;
         // create vertices
         let mut vertices: Vec<VertexPtr> = partitioned_initializer
@@ -1065,7 +1021,7 @@ impl DualModuleImpl for DualModuleSerial {
             }
             edges.push(edge_ptr);
         }
-        Self {
+        Ok(Self {
             vertices,
             nodes: vec![],
             nodes_length: 0,
@@ -1077,6 +1033,7 @@ impl DualModuleImpl for DualModuleSerial {
             unit_module_info: Some(UnitModuleInfo {
                 unit_index: partitioned_initializer.unit_index,
                 mirrored_vertices,
+                owning_dual_interface: None,
                 owning_dual_range: VertexRange::new(0, 0),
                 dual_node_pointers: PtrWeakKeyHashMap::<DualNodeWeak, usize>::new(),
             }),
@@ -1087,20 +1044,111 @@ impl DualModuleImpl for DualModuleSerial {
             sync_requests: vec![],
             updated_boundary: vec![],
             propagating_vertices: vec![],
+            enable_event_queue: true,
+            event_queue: EventQueue::Heap(PriorityQueue::new()), // not used when partitioned
+            event_dirty_nodes: BTreeSet::new(),
+            event_time: 0,
+            event_full_scan: false,
+            parallel_scan_threshold: None,
+            weight_overflow: WeightOverflow::default(),
+            overflow: None,
+        })
+    }
+
+    fn contains_vertex(&self, vertex_index: VertexIndex) -> Result<bool, FusionError> {
+        Ok(self.get_vertex_index(vertex_index).is_some())
+    }
+
+    fn check_overflow(&self) -> Result<(), FusionError> {
+        match self.overflow {
+            Some(accumulator) => Err(FusionError::WeightOverflow(accumulator)),
+            None => Ok(()),
         }
     }
 
-    fn contains_vertex(&self, vertex_index: VertexIndex) -> bool {
-        self.get_vertex_index(vertex_index).is_some()
+    #[allow(clippy::unnecessary_cast)]
+    fn sanity_check(&self, interface_ptr: &DualModuleInterfacePtr) -> Result<(), String> {
+        self.sanity_check_propagation()?;
+        self.sanity_check_edges()?;
+        let active_timestamp = self.active_timestamp;
+        // the internal dual variable must agree with the interface
+        let interface_weak = interface_ptr.downgrade();
+        let interface = interface_ptr.read_recursive();
+        for node_ptr in self.nodes[..self.nodes_length].iter().flatten() {
+            let dual_node_internal = node_ptr.read_recursive();
+            let dual_node_ptr = dual_node_internal.origin.upgrade_force();
+            let dual_node = dual_node_ptr.read_recursive();
+            if dual_node.belonging != interface_weak {
+                continue; // belongs to a fused child interface, whose global progress may not be up-to-date
+            }
+            let dual_variable = dual_node.get_dual_variable(&interface);
+            if dual_variable != dual_node_internal.dual_variable {
+                return Err(format!(
+                    "dual node {} has dual variable {} in the interface but {} in the dual module",
+                    dual_node.index, dual_variable, dual_node_internal.dual_variable
+                ));
+            }
+        }
+        drop(interface);
+        if self.unit_module_info.is_some() {
+            return Ok(()); // the region of a dual node may span multiple units
+        }
+        // every covered vertex must connect to its propagated grandson (a defect vertex) through tight edges
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        let mut parent: Vec<usize> = (0..self.vertices.len()).collect();
+        for edge_ptr in self.edges.iter() {
+            let edge = edge_ptr.read_recursive(active_timestamp);
+            if edge.total_growth() == edge.weight {
+                let left = edge.left.upgrade_force().read_recursive(active_timestamp).vertex_index;
+                let right = edge.right.upgrade_force().read_recursive(active_timestamp).vertex_index;
+                let left_root = find(&mut parent, left as usize);
+                let right_root = find(&mut parent, right as usize);
+                parent[left_root] = right_root;
+            }
+        }
+        for vertex_ptr in self.vertices.iter() {
+            let vertex = vertex_ptr.read_recursive(active_timestamp);
+            if let Some(grandson_weak) = vertex.propagated_grandson_dual_node.as_ref() {
+                let grandson_ptr = grandson_weak.upgrade_force().read_recursive().origin.upgrade_force();
+                let defect_vertex = match &grandson_ptr.read_recursive().class {
+                    DualNodeClass::DefectVertex { defect_index } => *defect_index,
+                    _ => unreachable!("checked by the grandson sanity check"),
+                };
+                if find(&mut parent, vertex.vertex_index as usize) != find(&mut parent, defect_vertex as usize) {
+                    return Err(format!(
+                        "vertex {} is not connected to its propagated defect vertex {} by tight edges",
+                        vertex.vertex_index, defect_vertex
+                    ));
+                }
+            }
+        }
+        Ok(())
     }
 
-    fn bias_dual_node_index(&mut self, bias: NodeIndex) {
-        self.unit_module_info.as_mut().unwrap().owning_dual_range.bias_by(bias);
+    fn prepare_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+    ) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        debug_assert!(
+            self.sync_requests.is_empty(),
+            "make sure to remove all sync requests before prepare to avoid out-dated requests"
+        );
+        if self.contains_dual_node(dual_node_ptr)? {
+            self.prepare_dual_node_growth(dual_node_ptr, is_grow);
+        }
+        Ok(&mut self.sync_requests)
     }
 
-    fn execute_sync_event(&mut self, sync_event: &SyncRequest) {
+    fn execute_sync_event(&mut self, sync_event: &SyncRequest) -> Result<(), FusionError> {
         let active_timestamp = self.active_timestamp;
-        debug_assert!(self.contains_vertex(sync_event.vertex_index));
+        debug_assert!(self.get_vertex_index(sync_event.vertex_index).is_some());
         let propagated_dual_node_internal_ptr =
             sync_event
                 .propagated_dual_node
@@ -1236,6 +1284,7 @@ impl DualModuleImpl for DualModuleSerial {
                 self.active_list.push(dual_node_internal_ptr.downgrade());
             }
         }
+        Ok(())
     }
 }
 
@@ -1309,11 +1358,46 @@ impl DualModuleSerial {
         self.active_timestamp = 0;
     }
 
-    /// soft clear all growth
-    pub fn clear_graph(&mut self) {
-        if self.active_timestamp == FastClearTimestamp::MAX {
-            // rarely happens
-            self.hard_clear_graph();
+    /// the estimated memory usage of the vertices, the edges and the dual nodes, including the stale ones that are kept
+    /// for constant-time clear
+    pub fn memory_usage(&self) -> MemoryReport {
+        let mut vertices = MemoryReport::vec(&self.vertices);
+        for vertex_ptr in self.vertices.iter() {
+            vertices += MemoryReport::shared::<Vertex>() + MemoryReport::vec(&vertex_ptr.read_recursive_force().edges);
+        }
+        let edges = MemoryReport::vec(&self.edges) + self.edges.len() * MemoryReport::shared::<Edge>();
+        let mut dual_nodes = MemoryReport::vec(&self.nodes);
+        for node_ptr in self.nodes.iter().flatten() {
+            let node = node_ptr.read_recursive();
+            dual_nodes += MemoryReport::shared::<DualNodeInternal>()
+                + MemoryReport::vec(&node.boundary)
+                + MemoryReport::vec(&node.overgrown_stack);
+        }
+        let mut auxiliary = MemoryReport::vec(&self.active_list)
+            + MemoryReport::vec(&self.edge_modifier.modified)
+            + MemoryReport::vec(&self.sync_requests)
+            + MemoryReport::vec(&self.updated_boundary)
+            + MemoryReport::vec(&self.propagating_vertices)
+            + self.event_queue.memory_usage()
+            + self.event_dirty_nodes.len() * size_of::<NodeIndex>();
+        if let Some(unit_module_info) = self.unit_module_info.as_ref() {
+            auxiliary += unit_module_info.mirrored_vertices.capacity() * size_of::<(VertexIndex, VertexIndex)>()
+                + unit_module_info.dual_node_pointers.capacity() * size_of::<(DualNodeWeak, usize)>();
+        }
+        MemoryReport {
+            vertices,
+            edges,
+            dual_nodes,
+            auxiliary,
+            ..Default::default()
+        }
+    }
+
+    /// soft clear all growth
+    pub fn clear_graph(&mut self) {
+        if self.active_timestamp == FastClearTimestamp::MAX {
+            // rarely happens
+            self.hard_clear_graph();
         }
         self.active_timestamp += 1; // implicitly clear all edges growth
     }
@@ -1427,9 +1511,55 @@ impl DualModuleSerial {
         Ok(())
     }
 
+    /// edge growth must be non-negative and never exceed the weight between different nodes, i.e. the slack is non-negative
+    pub fn sanity_check_edges(&self) -> Result<(), String> {
+        let active_timestamp = self.active_timestamp;
+        for edge_ptr in self.edges.iter() {
+            edge_ptr.dynamic_clear(active_timestamp);
+            let edge = edge_ptr.read_recursive(active_timestamp);
+            if edge.left_growth < 0 || edge.right_growth < 0 {
+                return Err(format!(
+                    "edge {} has negative growth {}/{}",
+                    edge.edge_index, edge.left_growth, edge.right_growth
+                ));
+            }
+            let outer_node = |dual_node: &Option<DualNodeInternalWeak>| {
+                dual_node.as_ref().map(|dual_node_weak| {
+                    let mut dual_node_ptr = dual_node_weak.upgrade_force().read_recursive().origin.upgrade_force();
+                    loop {
+                        let parent_blossom = dual_node_ptr.read_recursive().parent_blossom.clone();
+                        match parent_blossom {
+                            Some(parent_blossom_weak) => dual_node_ptr = parent_blossom_weak.upgrade_force(),
+                            None => break dual_node_ptr,
+                        }
+                    }
+                })
+            };
+            let left_outer_node = outer_node(&edge.left_dual_node);
+            if left_outer_node.is_some() && left_outer_node == outer_node(&edge.right_dual_node) {
+                continue; // edges inside a single node are allowed to be over-grown
+            }
+            if edge.total_growth() > edge.weight {
+                return Err(format!(
+                    "edge {} has negative slack: growth {}+{} > weight {}",
+                    edge.edge_index, edge.left_growth, edge.right_growth, edge.weight
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// the invariant checks after every growth, only enabled by [`STRICT_CHECKS`] because they take O(|V|+|E|) time each
+    fn strict_check_growth(&self) {
+        if STRICT_CHECKS {
+            self.sanity_check_propagation().unwrap();
+            self.sanity_check_edges().unwrap();
+        }
+    }
+
     /// do a sanity check of if all the nodes are in consistent state
     #[allow(clippy::unnecessary_cast)]
-    pub fn sanity_check(&self) -> Result<(), String> {
+    pub fn sanity_check_propagation(&self) -> Result<(), String> {
         let active_timestamp = self.active_timestamp;
         for vertex_ptr in self.vertices.iter() {
             vertex_ptr.dynamic_clear(active_timestamp);
@@ -1489,11 +1619,205 @@ impl DualModuleSerial {
 Implementing visualization functions
 */
 
+/// serializable state of a [`DualNodeInternal`], where pointers are replaced by indices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DualNodeInternalCheckpoint {
+    pub dual_variable: Weight,
+    pub boundary: Vec<(bool, EdgeIndex)>,
+    pub overgrown_stack: Vec<(VertexIndex, Weight)>,
+}
+
+/// serializable state of a [`Vertex`], only the fields that change during solving
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VertexCheckpoint {
+    pub is_defect: bool,
+    pub propagated_dual_node: Option<NodeIndex>,
+    pub propagated_grandson_dual_node: Option<NodeIndex>,
+}
+
+/// serializable state of an [`Edge`], only the fields that change during solving
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeCheckpoint {
+    pub weight: Weight,
+    pub left_growth: Weight,
+    pub right_growth: Weight,
+    pub left_dual_node: Option<NodeIndex>,
+    pub left_grandson_dual_node: Option<NodeIndex>,
+    pub right_dual_node: Option<NodeIndex>,
+    pub right_grandson_dual_node: Option<NodeIndex>,
+}
+
+/// serializable state of a [`DualModuleSerial`], see [`DualModuleSerial::checkpoint`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DualModuleSerialCheckpoint {
+    pub nodes: Vec<Option<DualNodeInternalCheckpoint>>,
+    pub vertices: Vec<VertexCheckpoint>,
+    pub edges: Vec<EdgeCheckpoint>,
+    pub active_list: Vec<NodeIndex>,
+    /// edges with modified weights and their original weights, in the order of modification
+    pub modified_edges: Vec<(EdgeIndex, Weight)>,
+    /// see [`EdgeWeightModifier::erasure_probabilities`]
+    #[serde(default)]
+    pub erasure_probabilities: Vec<(EdgeIndex, f64)>,
+}
+
+impl DualModuleSerial {
+    /// save the state of a non-partitioned dual module; the node indices are the same as in the [`DualModuleInterface`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn checkpoint(&self) -> DualModuleSerialCheckpoint {
+        assert!(
+            self.unit_module_info.is_none(),
+            "checkpoint of a partitioned dual module is not supported"
+        );
+        let active_timestamp = self.active_timestamp;
+        let get_index = |weak: &DualNodeInternalWeak| weak.upgrade_force().read_recursive().index;
+        let nodes = self.nodes[..self.nodes_length]
+            .iter()
+            .map(|node_ptr| {
+                node_ptr.as_ref().map(|node_ptr| {
+                    let node = node_ptr.read_recursive();
+                    DualNodeInternalCheckpoint {
+                        dual_variable: node.dual_variable,
+                        boundary: node
+                            .boundary
+                            .iter()
+                            .map(|(is_left, edge_weak)| {
+                                (*is_left, edge_weak.upgrade_force().read_recursive_force().edge_index)
+                            })
+                            .collect(),
+                        overgrown_stack: node
+                            .overgrown_stack
+                            .iter()
+                            .map(|(vertex_weak, weight)| {
+                                (vertex_weak.upgrade_force().read_recursive_force().vertex_index, *weight)
+                            })
+                            .collect(),
+                    }
+                })
+            })
+            .collect();
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|vertex_ptr| {
+                vertex_ptr.dynamic_clear(active_timestamp);
+                let vertex = vertex_ptr.read_recursive(active_timestamp);
+                VertexCheckpoint {
+                    is_defect: vertex.is_defect,
+                    propagated_dual_node: vertex.propagated_dual_node.as_ref().map(get_index),
+                    propagated_grandson_dual_node: vertex.propagated_grandson_dual_node.as_ref().map(get_index),
+                }
+            })
+            .collect();
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge_ptr| {
+                edge_ptr.dynamic_clear(active_timestamp);
+                let edge = edge_ptr.read_recursive(active_timestamp);
+                EdgeCheckpoint {
+                    weight: edge.weight,
+                    left_growth: edge.left_growth,
+                    right_growth: edge.right_growth,
+                    left_dual_node: edge.left_dual_node.as_ref().map(get_index),
+                    left_grandson_dual_node: edge.left_grandson_dual_node.as_ref().map(get_index),
+                    right_dual_node: edge.right_dual_node.as_ref().map(get_index),
+                    right_grandson_dual_node: edge.right_grandson_dual_node.as_ref().map(get_index),
+                }
+            })
+            .collect();
+        DualModuleSerialCheckpoint {
+            nodes,
+            vertices,
+            edges,
+            active_list: self
+                .active_list
+                .iter()
+                .filter_map(|weak| weak.upgrade().map(|ptr| ptr.read_recursive().index))
+                .collect(),
+            modified_edges: self.edge_modifier.modified.clone(),
+            erasure_probabilities: self.edge_modifier.erasure_probabilities.clone(),
+        }
+    }
+
+    /// restore the state from a checkpoint, given the interface restored by [`DualModuleInterfacePtr::new_checkpoint`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn load_checkpoint(&mut self, checkpoint: &DualModuleSerialCheckpoint, interface_ptr: &DualModuleInterfacePtr) {
+        assert!(
+            self.unit_module_info.is_none(),
+            "checkpoint of a partitioned dual module is not supported"
+        );
+        assert_eq!(checkpoint.vertices.len(), self.vertices.len(), "vertex number mismatch");
+        assert_eq!(checkpoint.edges.len(), self.edges.len(), "edge number mismatch");
+        self.clear();
+        let active_timestamp = self.active_timestamp;
+        let interface = interface_ptr.read_recursive();
+        self.nodes = checkpoint
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(node_index, node)| {
+                node.as_ref().map(|node| {
+                    DualNodeInternalPtr::new_value(DualNodeInternal {
+                        origin: interface.nodes[node_index]
+                            .as_ref()
+                            .expect("dual node exists in the interface")
+                            .downgrade(),
+                        index: node_index as NodeIndex,
+                        dual_variable: node.dual_variable,
+                        boundary: node
+                            .boundary
+                            .iter()
+                            .map(|(is_left, edge_index)| (*is_left, self.edges[*edge_index as usize].downgrade()))
+                            .collect(),
+                        overgrown_stack: node
+                            .overgrown_stack
+                            .iter()
+                            .map(|(vertex_index, weight)| (self.vertices[*vertex_index as usize].downgrade(), *weight))
+                            .collect(),
+                        last_visit_cycle: 0,
+                    })
+                })
+            })
+            .collect();
+        self.nodes_length = self.nodes.len();
+        let get_weak = |node_index: &NodeIndex| {
+            self.nodes[*node_index as usize]
+                .as_ref()
+                .expect("checkpoint refers to a removed node")
+                .downgrade()
+        };
+        for (vertex_ptr, vertex_checkpoint) in self.vertices.iter().zip(checkpoint.vertices.iter()) {
+            vertex_ptr.dynamic_clear(active_timestamp);
+            let mut vertex = vertex_ptr.write(active_timestamp);
+            vertex.is_defect = vertex_checkpoint.is_defect;
+            vertex.propagated_dual_node = vertex_checkpoint.propagated_dual_node.as_ref().map(get_weak);
+            vertex.propagated_grandson_dual_node = vertex_checkpoint.propagated_grandson_dual_node.as_ref().map(get_weak);
+        }
+        for (edge_ptr, edge_checkpoint) in self.edges.iter().zip(checkpoint.edges.iter()) {
+            edge_ptr.dynamic_clear(active_timestamp);
+            let mut edge = edge_ptr.write(active_timestamp);
+            edge.weight = edge_checkpoint.weight;
+            edge.left_growth = edge_checkpoint.left_growth;
+            edge.right_growth = edge_checkpoint.right_growth;
+            edge.left_dual_node = edge_checkpoint.left_dual_node.as_ref().map(get_weak);
+            edge.left_grandson_dual_node = edge_checkpoint.left_grandson_dual_node.as_ref().map(get_weak);
+            edge.right_dual_node = edge_checkpoint.right_dual_node.as_ref().map(get_weak);
+            edge.right_grandson_dual_node = edge_checkpoint.right_grandson_dual_node.as_ref().map(get_weak);
+        }
+        self.active_list = checkpoint.active_list.iter().map(get_weak).collect();
+        self.edge_modifier.modified = checkpoint.modified_edges.clone();
+        self.edge_modifier.erasure_probabilities = checkpoint.erasure_probabilities.clone();
+        self.event_full_scan = true;
+    }
+}
+
 impl FusionVisualizer for DualModuleSerial {
     #[allow(clippy::unnecessary_cast)]
     fn snapshot(&self, abbrev: bool) -> serde_json::Value {
-        // do the sanity check first before taking snapshot
-        self.sanity_check().unwrap();
+        if STRICT_CHECKS {
+            self.sanity_check_propagation().unwrap();
+        }
         let active_timestamp = self.active_timestamp;
         let mut vertices: Vec<serde_json::Value> = (0..self.vertex_num).map(|_| serde_json::Value::Null).collect();
         for vertex_ptr in self.vertices.iter() {
@@ -1638,141 +1962,590 @@ impl FusionVisualizer for DualModuleSerial {
                 .unwrap()
                 .insert("dual_nodes".to_string(), json!(dual_nodes));
         }
-        value
+        value
+    }
+}
+
+/*
+Implement internal helper functions that maintains the state of dual clusters
+*/
+
+impl DualModuleSerial {
+    /// register a new dual node ptr, but not creating the internal dual node
+    fn register_dual_node_ptr(&mut self, dual_node_ptr: &DualNodePtr) {
+        // println!("unit {:?}, register_dual_node_ptr: {:?}", self.unit_module_info, dual_node_ptr);
+        let node = dual_node_ptr.read_recursive();
+        if let Some(unit_module_info) = self.unit_module_info.as_mut() {
+            if unit_module_info.owning_dual_range.is_empty() {
+                // set the range instead of inserting into the lookup table, to minimize table lookup
+                unit_module_info.owning_dual_interface = Some(node.home_interface.clone());
+                unit_module_info.owning_dual_range = VertexRange::new(node.home_index, node.home_index);
+            }
+            if unit_module_info.owning_dual_interface.as_ref() == Some(&node.home_interface)
+                && unit_module_info.owning_dual_range.end() == node.home_index
+                && self.nodes_length == unit_module_info.owning_dual_range.len()
+            {
+                // it's able to append into the owning range, minimizing table lookup and thus better performance
+                unit_module_info.owning_dual_range.append_by(1);
+            } else {
+                // will be inserted at this place
+                unit_module_info
+                    .dual_node_pointers
+                    .insert(dual_node_ptr.clone(), self.nodes_length);
+            }
+        } else {
+            debug_assert!(
+                self.nodes_length as NodeIndex == node.index,
+                "dual node must be created in a sequential manner: no missing or duplicating"
+            );
+        }
+        // println!("unit {:?}, register_dual_node_ptr: {:?}", self.unit_module_info, dual_node_ptr);
+    }
+
+    /// get the local index of a dual node, thus has usize type
+    #[allow(clippy::unnecessary_cast)]
+    pub fn get_dual_node_index(&self, dual_node_ptr: &DualNodePtr) -> Option<usize> {
+        let dual_node = dual_node_ptr.read_recursive();
+        if let Some(unit_module_info) = self.unit_module_info.as_ref() {
+            if unit_module_info.owning_dual_interface.as_ref() == Some(&dual_node.home_interface)
+                && unit_module_info.owning_dual_range.contains(dual_node.home_index)
+            {
+                Some((dual_node.home_index - unit_module_info.owning_dual_range.start()) as usize)
+            } else {
+                // println!("from unit {:?}, dual_node: {}", self.unit_module_info, dual_node.index);
+                unit_module_info.dual_node_pointers.get(dual_node_ptr).copied()
+            }
+        } else {
+            Some(dual_node.index as usize)
+        }
+    }
+
+    /// get the local index of a vertex, thus has usize type
+    #[allow(clippy::unnecessary_cast)]
+    pub fn get_vertex_index(&self, vertex_index: VertexIndex) -> Option<usize> {
+        if self.owning_range.contains(vertex_index) {
+            return Some((vertex_index - self.owning_range.start()) as usize);
+        }
+        if let Some(unit_module_info) = self.unit_module_info.as_ref() {
+            if let Some(index) = unit_module_info.mirrored_vertices.get(&vertex_index) {
+                return Some(*index as usize);
+            }
+        }
+        None
+    }
+
+    pub fn get_dual_node_internal_ptr(&self, dual_node_ptr: &DualNodePtr) -> DualNodeInternalPtr {
+        self.get_dual_node_internal_ptr_optional(dual_node_ptr).unwrap()
+    }
+
+    /// dual node ptr may not hold in this module
+    pub fn get_dual_node_internal_ptr_optional(&self, dual_node_ptr: &DualNodePtr) -> Option<DualNodeInternalPtr> {
+        self.get_dual_node_index(dual_node_ptr).map(|dual_node_index| {
+            let dual_node_internal_ptr = self.nodes[dual_node_index].as_ref().expect("internal dual node must exists");
+            debug_assert!(
+                dual_node_ptr == &dual_node_internal_ptr.read_recursive().origin.upgrade_force(),
+                "dual node and dual internal node must corresponds to each other"
+            );
+            dual_node_internal_ptr.clone()
+        })
+    }
+
+    /// possibly add dual node only when sync_event is provided
+    #[allow(clippy::unnecessary_cast)]
+    pub fn get_otherwise_add_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        dual_variable: Weight,
+    ) -> DualNodeInternalPtr {
+        let dual_node_index = self.get_dual_node_index(dual_node_ptr).unwrap_or_else(|| {
+            // add a new internal dual node corresponding to the dual_node_ptr
+            self.register_dual_node_ptr(dual_node_ptr);
+            let node_index = self.nodes_length as NodeIndex;
+            let node_internal_ptr =
+                if node_index < self.nodes.len() as NodeIndex && self.nodes[node_index as usize].is_some() {
+                    let node_ptr = self.nodes[node_index as usize].as_ref().unwrap().clone();
+                    let mut node = node_ptr.write();
+                    node.origin = dual_node_ptr.downgrade();
+                    node.index = node_index;
+                    node.dual_variable = dual_variable;
+                    node.boundary.clear();
+                    node.overgrown_stack.clear();
+                    node.last_visit_cycle = 0;
+                    drop(node);
+                    node_ptr
+                } else {
+                    DualNodeInternalPtr::new_value(DualNodeInternal {
+                        origin: dual_node_ptr.downgrade(),
+                        index: node_index,
+                        dual_variable,
+                        boundary: Vec::new(),
+                        overgrown_stack: Vec::new(),
+                        last_visit_cycle: 0,
+                    })
+                };
+            self.active_list.push(node_internal_ptr.downgrade());
+            self.nodes_length += 1;
+            if self.nodes.len() < self.nodes_length {
+                self.nodes.push(None);
+            }
+            self.nodes[node_index as usize] = Some(node_internal_ptr);
+            node_index as usize
+        });
+        let dual_node_internal_ptr = self.nodes[dual_node_index].as_ref().expect("internal dual node must exists");
+        debug_assert!(
+            dual_node_ptr == &dual_node_internal_ptr.read_recursive().origin.upgrade_force(),
+            "dual node and dual internal node must corresponds to each other"
+        );
+        dual_node_internal_ptr.clone()
+    }
+
+    /// grow a single dual node without invalidating the predicted events, used by [`DualModuleImpl::grow`] where all the active nodes grow together
+    fn apply_dual_node_growth(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
+        let active_timestamp = self.active_timestamp;
+        if length == 0 {
+            eprintln!("[warning] calling `grow_dual_node` with zero length, nothing to do");
+            return;
+        }
+        self.prepare_dual_node_growth(dual_node_ptr, length > 0);
+        let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
+        {
+            // update node dual variable and do sanity check
+            let mut dual_node_internal = dual_node_internal_ptr.write();
+            dual_node_internal.dual_variable += length;
+            debug_assert!(
+                dual_node_internal.dual_variable >= 0,
+                "shrinking to negative dual variable is forbidden"
+            );
+            // update over-grown vertices
+            if !dual_node_internal.overgrown_stack.is_empty() {
+                let last_index = dual_node_internal.overgrown_stack.len() - 1;
+                let (_, overgrown) = &mut dual_node_internal.overgrown_stack[last_index];
+                if length < 0 {
+                    debug_assert!(*overgrown >= -length, "overgrown vertex cannot shrink so much");
+                }
+                *overgrown += length;
+            }
+        }
+        let dual_node_internal = dual_node_internal_ptr.read_recursive();
+        let weight_overflow = self.weight_overflow;
+        for (is_left, edge_weak) in dual_node_internal.boundary.iter() {
+            let edge_ptr = edge_weak.upgrade_force();
+            let is_left = *is_left;
+            let (growth, weight) = {
+                // minimize writer lock acquisition
+                let mut edge = edge_ptr.write(active_timestamp);
+                if is_left {
+                    edge.left_growth += length;
+                    debug_assert!(edge.left_growth >= 0, "negative growth forbidden");
+                } else {
+                    edge.right_growth += length;
+                    debug_assert!(edge.right_growth >= 0, "negative growth forbidden");
+                }
+                if weight_overflow.add(edge.left_growth, edge.right_growth).is_none() {
+                    self.overflow.get_or_insert("edge growth");
+                }
+                (edge.total_growth(), edge.weight)
+            };
+            let edge = edge_ptr.read_recursive(active_timestamp);
+            if growth > weight {
+                // first check for if both side belongs to the same dual node, if so, it's ok
+                let dual_node_internal_ptr_2: &Option<DualNodeInternalWeak> = if is_left {
+                    &edge.right_dual_node
+                } else {
+                    &edge.left_dual_node
+                };
+                if dual_node_internal_ptr_2.is_none()
+                    || dual_node_internal_ptr_2.as_ref().unwrap() != &dual_node_internal_ptr.downgrade()
+                {
+                    let left_ptr = edge.left.upgrade_force();
+                    let right_ptr = edge.right.upgrade_force();
+                    panic!(
+                        "over-grown edge ({},{}): {}/{}",
+                        left_ptr.read_recursive(active_timestamp).vertex_index,
+                        right_ptr.read_recursive(active_timestamp).vertex_index,
+                        growth,
+                        weight
+                    );
+                }
+            } else if growth < 0 {
+                let left_ptr = edge.left.upgrade_force();
+                let right_ptr = edge.right.upgrade_force();
+                panic!(
+                    "under-grown edge ({},{}): {}/{}",
+                    left_ptr.read_recursive(active_timestamp).vertex_index,
+                    right_ptr.read_recursive(active_timestamp).vertex_index,
+                    growth,
+                    weight
+                );
+            }
+        }
+    }
+
+    /// use a monotone bucket queue for the predicted events instead of a binary heap, which is faster when the weights
+    /// are small integers (e.g. uniform weights) but uses memory proportional to the range of the event times; by default
+    /// it's chosen according to the maximum weight, see [`EventQueue::new`]
+    pub fn use_bucket_queue(&mut self, enabled: bool) {
+        self.event_queue = if enabled {
+            EventQueue::Bucket(BucketQueue::new())
+        } else {
+            EventQueue::Heap(PriorityQueue::new())
+        };
+        self.event_full_scan = true; // the existing events are dropped
+    }
+
+    /// scan the prepared active nodes for the maximum update length
+    fn compute_maximum_update_length_active_nodes(&self, active_nodes: &[DualNodeInternalWeak]) -> GroupMaxUpdateLength {
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        for internal_dual_node_weak in active_nodes.iter() {
+            let dual_node_ptr = {
+                let internal_dual_node_ptr = internal_dual_node_weak.upgrade_force();
+                let dual_node_internal = internal_dual_node_ptr.read_recursive();
+                dual_node_internal.origin.upgrade_force()
+            };
+            let dual_node = dual_node_ptr.read_recursive();
+            let is_grow = match dual_node.grow_state {
+                DualNodeGrowState::Grow => true,
+                DualNodeGrowState::Shrink => false,
+                DualNodeGrowState::Stay => continue,
+            };
+            drop(dual_node); // unlock, otherwise it causes deadlock when updating the dual node
+            let max_update_length = self.compute_maximum_update_length_prepared_dual_node(&dual_node_ptr, is_grow);
+            group_max_update_length.add(max_update_length);
+        }
+        group_max_update_length
+    }
+
+    /// the maximum update length of a single dual node that is already prepared to grow or shrink; it only reads the
+    /// state and thus can run on multiple dual nodes in parallel
+    #[allow(clippy::collapsible_else_if)]
+    fn compute_maximum_update_length_prepared_dual_node(
+        &self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+    ) -> MaxUpdateLength {
+        let active_timestamp = self.active_timestamp;
+        let mut max_length_abs = Weight::MAX;
+        let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
+        let dual_node_internal = dual_node_internal_ptr.read_recursive();
+        if !is_grow {
+            if dual_node_internal.dual_variable == 0 {
+                let dual_node = dual_node_ptr.read_recursive();
+                match dual_node.class {
+                    DualNodeClass::Blossom { .. } => return MaxUpdateLength::BlossomNeedExpand(dual_node_ptr.key()),
+                    DualNodeClass::DefectVertex { defect_index } => {
+                        // try to report Conflicting event or give a VertexShrinkStop with potential conflicting node
+                        if let Some(vertex_index) = self.get_vertex_index(defect_index) {
+                            // since propagated node is never removed, this event could happen with no vertex
+                            let vertex_ptr = &self.vertices[vertex_index];
+                            let vertex = vertex_ptr.read_recursive(active_timestamp);
+                            let mut potential_conflict: Option<(DualNodePtr, DualNodePtr)> = None;
+                            for edge_weak in vertex.edges.iter() {
+                                let edge_ptr = edge_weak.upgrade_force();
+                                let edge = edge_ptr.read_recursive(active_timestamp);
+                                let is_left = vertex_ptr.downgrade() == edge.left;
+                                let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
+                                if remaining_length == 0 {
+                                    let peer_dual_node = if is_left {
+                                        &edge.right_dual_node
+                                    } else {
+                                        &edge.left_dual_node
+                                    };
+                                    if let Some(peer_dual_node_ptr) = peer_dual_node {
+                                        let peer_grandson_dual_node = if is_left {
+                                            &edge.right_grandson_dual_node
+                                        } else {
+                                            &edge.left_grandson_dual_node
+                                        };
+                                        let peer_dual_node_ptr =
+                                            peer_dual_node_ptr.upgrade_force().read_recursive().origin.upgrade_force();
+                                        let peer_grandson_dual_node_ptr = peer_grandson_dual_node
+                                            .as_ref()
+                                            .unwrap()
+                                            .upgrade_force()
+                                            .read_recursive()
+                                            .origin
+                                            .upgrade_force();
+                                        if peer_dual_node_ptr.read_recursive().grow_state == DualNodeGrowState::Grow {
+                                            if let Some((other_dual_node_ptr, other_grandson_dual_node)) =
+                                                &potential_conflict
+                                            {
+                                                if &peer_dual_node_ptr != other_dual_node_ptr {
+                                                    return MaxUpdateLength::Conflicting(
+                                                        (other_dual_node_ptr.key(), other_grandson_dual_node.key()),
+                                                        (peer_dual_node_ptr.key(), peer_grandson_dual_node_ptr.key()),
+                                                    );
+                                                }
+                                            } else {
+                                                potential_conflict = Some((peer_dual_node_ptr, peer_grandson_dual_node_ptr));
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            return MaxUpdateLength::VertexShrinkStop((
+                                dual_node_ptr.key(),
+                                potential_conflict.map(|(node_ptr, grandson_ptr)| (node_ptr.key(), grandson_ptr.key())),
+                            ));
+                        } else {
+                            return MaxUpdateLength::VertexShrinkStop((dual_node_ptr.key(), None));
+                        }
+                    }
+                }
+            }
+            if !dual_node_internal.overgrown_stack.is_empty() {
+                let last_index = dual_node_internal.overgrown_stack.len() - 1;
+                let (_, overgrown) = &dual_node_internal.overgrown_stack[last_index];
+                max_length_abs = std::cmp::min(max_length_abs, *overgrown);
+            }
+            max_length_abs = std::cmp::min(max_length_abs, dual_node_internal.dual_variable);
+        }
+        for (is_left, edge_weak) in dual_node_internal.boundary.iter() {
+            let edge_ptr = edge_weak.upgrade_force();
+            let is_left = *is_left;
+            let edge = edge_ptr.read_recursive(active_timestamp);
+            if is_grow {
+                // first check if both side belongs to the same tree node, if so, no constraint on this edge
+                let peer_dual_node_internal_ptr: Option<DualNodeInternalPtr> = if is_left {
+                    edge.right_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
+                } else {
+                    edge.left_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
+                };
+                match peer_dual_node_internal_ptr {
+                    Some(peer_dual_node_internal_ptr) => {
+                        if peer_dual_node_internal_ptr == dual_node_internal_ptr {
+                            continue;
+                        } else {
+                            let peer_dual_node_internal = peer_dual_node_internal_ptr.read_recursive();
+                            let peer_dual_node_ptr = peer_dual_node_internal.origin.upgrade_force();
+                            let peer_dual_node = peer_dual_node_ptr.read_recursive();
+                            let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
+                            let local_max_length_abs = match peer_dual_node.grow_state {
+                                DualNodeGrowState::Grow => {
+                                    debug_assert!(remaining_length % 2 == 0, "there is odd gap between two growing nodes, please make sure all weights are even numbers");
+                                    remaining_length / 2
+                                }
+                                DualNodeGrowState::Shrink => {
+                                    // Yue 2022.9.5: remove Conflicting event detection here, move it to the 0-dual syndrome node
+                                    continue;
+                                }
+                                DualNodeGrowState::Stay => remaining_length,
+                            };
+                            if local_max_length_abs == 0 {
+                                let peer_grandson_ptr = if is_left {
+                                    edge.right_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                } else {
+                                    edge.left_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                };
+                                let grandson_ptr = if is_left {
+                                    edge.left_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                } else {
+                                    edge.right_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                };
+                                return MaxUpdateLength::Conflicting(
+                                    (peer_dual_node_ptr.key(), peer_grandson_ptr.key()),
+                                    (dual_node_ptr.key(), grandson_ptr.key()),
+                                );
+                            }
+                            max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
+                        }
+                    }
+                    None => {
+                        let local_max_length_abs = edge.weight - edge.left_growth - edge.right_growth;
+                        if local_max_length_abs == 0 {
+                            // check if peer is virtual node
+                            let peer_vertex_ptr = if is_left {
+                                edge.right.upgrade_force()
+                            } else {
+                                edge.left.upgrade_force()
+                            };
+                            let peer_vertex = peer_vertex_ptr.read_recursive(active_timestamp);
+                            if peer_vertex.is_virtual || peer_vertex.is_mirror_blocked() {
+                                let grandson_ptr = if is_left {
+                                    edge.left_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                } else {
+                                    edge.right_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                };
+                                return MaxUpdateLength::TouchingVirtual(
+                                    (dual_node_ptr.key(), grandson_ptr.key()),
+                                    (peer_vertex.vertex_index, peer_vertex.is_mirror_blocked()),
+                                );
+                            } else {
+                                println!("edge: {edge_ptr:?}, peer_vertex_ptr: {peer_vertex_ptr:?}");
+                                unreachable!("this edge should've been removed from boundary because it's already fully grown, and it's peer vertex is not virtual")
+                            }
+                        }
+                        max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
+                    }
+                }
+            } else {
+                if is_left {
+                    if edge.left_growth == 0 {
+                        unreachable!()
+                    }
+                    max_length_abs = std::cmp::min(max_length_abs, edge.left_growth);
+                } else {
+                    if edge.right_growth == 0 {
+                        unreachable!()
+                    }
+                    max_length_abs = std::cmp::min(max_length_abs, edge.right_growth);
+                }
+            }
+        }
+        MaxUpdateLength::NonZeroGrow((max_length_abs, dual_node_internal.boundary.is_empty()))
     }
-}
 
-/*
-Implement internal helper functions that maintains the state of dual clusters
-*/
+    fn is_event_queue_enabled(&self) -> bool {
+        self.enable_event_queue && self.unit_module_info.is_none()
+    }
 
-impl DualModuleSerial {
-    /// register a new dual node ptr, but not creating the internal dual node
-    fn register_dual_node_ptr(&mut self, dual_node_ptr: &DualNodePtr) {
-        // println!("unit {:?}, register_dual_node_ptr: {:?}", self.unit_module_info, dual_node_ptr);
-        let node = dual_node_ptr.read_recursive();
-        if let Some(unit_module_info) = self.unit_module_info.as_mut() {
-            if unit_module_info.owning_dual_range.is_empty() {
-                // set the range instead of inserting into the lookup table, to minimize table lookup
-                unit_module_info.owning_dual_range = VertexRange::new(node.index, node.index);
-            }
-            if unit_module_info.owning_dual_range.end() == node.index
-                && self.nodes_length == unit_module_info.owning_dual_range.len()
-            {
-                // it's able to append into the owning range, minimizing table lookup and thus better performance
-                unit_module_info.owning_dual_range.append_by(1);
-            } else {
-                // will be inserted at this place
-                unit_module_info
-                    .dual_node_pointers
-                    .insert(dual_node_ptr.clone(), self.nodes_length);
-            }
-        } else {
-            debug_assert!(
-                self.nodes_length as NodeIndex == node.index,
-                "dual node must be created in a sequential manner: no missing or duplicating"
-            );
+    /// invalidate the predicted events of a node and all its neighbors
+    fn mark_event_dirty(&mut self, dual_node_internal_ptr: &DualNodeInternalPtr) {
+        if !self.is_event_queue_enabled() {
+            return;
         }
-        // println!("unit {:?}, register_dual_node_ptr: {:?}", self.unit_module_info, dual_node_ptr);
+        let dual_node_internal = dual_node_internal_ptr.read_recursive();
+        self.event_dirty_nodes.insert(dual_node_internal.index);
+        Self::collect_boundary_peers(
+            &dual_node_internal.boundary,
+            self.active_timestamp,
+            &mut self.event_dirty_nodes,
+        );
     }
 
-    /// get the local index of a dual node, thus has usize type
-    #[allow(clippy::unnecessary_cast)]
-    pub fn get_dual_node_index(&self, dual_node_ptr: &DualNodePtr) -> Option<usize> {
-        let dual_node = dual_node_ptr.read_recursive();
-        if let Some(unit_module_info) = self.unit_module_info.as_ref() {
-            if unit_module_info.owning_dual_range.contains(dual_node.index) {
-                debug_assert!(
-                    dual_node.belonging.upgrade_force().read_recursive().parent.is_none(),
-                    "dual node is not updated"
-                );
-                Some((dual_node.index - unit_module_info.owning_dual_range.start()) as usize)
+    fn collect_boundary_peers(
+        boundary: &[(bool, EdgeWeak)],
+        active_timestamp: FastClearTimestamp,
+        peers: &mut BTreeSet<NodeIndex>,
+    ) {
+        for (is_left, edge_weak) in boundary.iter() {
+            let edge_ptr = edge_weak.upgrade_force();
+            let edge = edge_ptr.read_recursive(active_timestamp);
+            let peer_dual_node = if *is_left {
+                &edge.right_dual_node
             } else {
-                // println!("from unit {:?}, dual_node: {}", self.unit_module_info, dual_node.index);
-                unit_module_info.dual_node_pointers.get(dual_node_ptr).copied()
+                &edge.left_dual_node
+            };
+            if let Some(peer_dual_node_weak) = peer_dual_node {
+                peers.insert(peer_dual_node_weak.upgrade_force().read_recursive().index);
             }
-        } else {
-            Some(dual_node.index as usize)
         }
     }
 
-    /// get the local index of a vertex, thus has usize type
+    /// only recompute the nodes whose predicted events are due or invalidated, rather than scanning all the active nodes
     #[allow(clippy::unnecessary_cast)]
-    pub fn get_vertex_index(&self, vertex_index: VertexIndex) -> Option<usize> {
-        if self.owning_range.contains(vertex_index) {
-            return Some((vertex_index - self.owning_range.start()) as usize);
+    fn compute_maximum_update_length_event_queue(&mut self) -> GroupMaxUpdateLength {
+        if self.event_full_scan {
+            self.event_full_scan = false;
+            self.renew_active_list();
+            for internal_dual_node_weak in self.active_list.iter() {
+                let internal_dual_node_ptr = internal_dual_node_weak.upgrade_force();
+                self.event_dirty_nodes.insert(internal_dual_node_ptr.read_recursive().index);
+            }
         }
-        if let Some(unit_module_info) = self.unit_module_info.as_ref() {
-            if let Some(index) = unit_module_info.mirrored_vertices.get(&vertex_index) {
-                return Some(*index as usize);
+        while let Some(event_time) = self.event_queue.peek() {
+            if event_time > self.event_time {
+                break;
             }
+            let (node_index, _) = self.event_queue.pop().unwrap();
+            self.event_dirty_nodes.insert(node_index);
         }
-        None
-    }
-
-    pub fn get_dual_node_internal_ptr(&self, dual_node_ptr: &DualNodePtr) -> DualNodeInternalPtr {
-        self.get_dual_node_internal_ptr_optional(dual_node_ptr).unwrap()
-    }
-
-    /// dual node ptr may not hold in this module
-    pub fn get_dual_node_internal_ptr_optional(&self, dual_node_ptr: &DualNodePtr) -> Option<DualNodeInternalPtr> {
-        self.get_dual_node_index(dual_node_ptr).map(|dual_node_index| {
-            let dual_node_internal_ptr = self.nodes[dual_node_index].as_ref().expect("internal dual node must exists");
-            debug_assert!(
-                dual_node_ptr == &dual_node_internal_ptr.read_recursive().origin.upgrade_force(),
-                "dual node and dual internal node must corresponds to each other"
-            );
-            dual_node_internal_ptr.clone()
-        })
-    }
-
-    /// possibly add dual node only when sync_event is provided
-    #[allow(clippy::unnecessary_cast)]
-    pub fn get_otherwise_add_dual_node(
-        &mut self,
-        dual_node_ptr: &DualNodePtr,
-        dual_variable: Weight,
-    ) -> DualNodeInternalPtr {
-        let dual_node_index = self.get_dual_node_index(dual_node_ptr).unwrap_or_else(|| {
-            // add a new internal dual node corresponding to the dual_node_ptr
-            self.register_dual_node_ptr(dual_node_ptr);
-            let node_index = self.nodes_length as NodeIndex;
-            let node_internal_ptr =
-                if node_index < self.nodes.len() as NodeIndex && self.nodes[node_index as usize].is_some() {
-                    let node_ptr = self.nodes[node_index as usize].as_ref().unwrap().clone();
-                    let mut node = node_ptr.write();
-                    node.origin = dual_node_ptr.downgrade();
-                    node.index = node_index;
-                    node.dual_variable = dual_variable;
-                    node.boundary.clear();
-                    node.overgrown_stack.clear();
-                    node.last_visit_cycle = 0;
-                    drop(node);
-                    node_ptr
+        // preparing the boundary of a node may further invalidate the events of its neighbors
+        let mut updated_nodes = BTreeSet::new();
+        while !self.event_dirty_nodes.is_empty() {
+            let dirty_nodes = std::mem::take(&mut self.event_dirty_nodes);
+            let mut dual_nodes = Vec::with_capacity(dirty_nodes.len());
+            for &node_index in dirty_nodes.iter() {
+                if let Some(Some(internal_dual_node_ptr)) = self.nodes[..self.nodes_length].get(node_index as usize) {
+                    let dual_node_ptr = internal_dual_node_ptr.read_recursive().origin.upgrade_force();
+                    let grow_state = dual_node_ptr.read_recursive().grow_state;
+                    dual_nodes.push((dual_node_ptr, grow_state));
+                }
+            }
+            // same as `prepare_all`: first shrink and then grow; Stay nodes are not touched
+            for (dual_node_ptr, grow_state) in dual_nodes.iter() {
+                if *grow_state == DualNodeGrowState::Shrink {
+                    self.prepare_dual_node_growth(dual_node_ptr, false);
+                }
+            }
+            for (dual_node_ptr, grow_state) in dual_nodes.iter() {
+                if *grow_state == DualNodeGrowState::Grow {
+                    self.prepare_dual_node_growth(dual_node_ptr, true);
+                }
+            }
+            updated_nodes.extend(dirty_nodes);
+        }
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        for node_index in updated_nodes {
+            let dual_node_ptr = match self.nodes[..self.nodes_length].get(node_index as usize) {
+                Some(Some(internal_dual_node_ptr)) => internal_dual_node_ptr.read_recursive().origin.upgrade_force(),
+                _ => {
+                    self.event_queue.remove(node_index);
+                    continue;
+                }
+            };
+            let is_grow = match dual_node_ptr.read_recursive().grow_state {
+                DualNodeGrowState::Grow => true,
+                DualNodeGrowState::Shrink => false,
+                DualNodeGrowState::Stay => {
+                    self.event_queue.remove(node_index);
+                    continue;
+                }
+            };
+            let max_update_length = self.compute_maximum_update_length_prepared_dual_node(&dual_node_ptr, is_grow);
+            let event_time = match &max_update_length {
+                MaxUpdateLength::NonZeroGrow((length, _)) => self.event_time.saturating_add(*length),
+                _ => self.event_time, // keep reporting it until resolved
+            };
+            self.event_queue.push(node_index, event_time);
+            group_max_update_length.add(max_update_length);
+        }
+        // the predicted events of other nodes are still valid
+        if let GroupMaxUpdateLength::NonZeroGrow((length, _)) = &mut group_max_update_length {
+            if let Some(event_time) = self.event_queue.peek() {
+                *length = if event_time == Weight::MAX {
+                    Weight::MAX
                 } else {
-                    DualNodeInternalPtr::new_value(DualNodeInternal {
-                        origin: dual_node_ptr.downgrade(),
-                        index: node_index,
-                        dual_variable,
-                        boundary: Vec::new(),
-                        overgrown_stack: Vec::new(),
-                        last_visit_cycle: 0,
-                    })
+                    event_time - self.event_time
                 };
-            self.active_list.push(node_internal_ptr.downgrade());
-            self.nodes_length += 1;
-            if self.nodes.len() < self.nodes_length {
-                self.nodes.push(None);
             }
-            self.nodes[node_index as usize] = Some(node_internal_ptr);
-            node_index as usize
-        });
-        let dual_node_internal_ptr = self.nodes[dual_node_index].as_ref().expect("internal dual node must exists");
-        debug_assert!(
-            dual_node_ptr == &dual_node_internal_ptr.read_recursive().origin.upgrade_force(),
-            "dual node and dual internal node must corresponds to each other"
-        );
-        dual_node_internal_ptr.clone()
+        }
+        group_max_update_length
     }
 
     /// this is equivalent to [`DualModuleSerial::prepare_dual_node_growth`] when there are no 0 weight edges, but when it encounters zero-weight edges, it will report `true`
@@ -1782,6 +2555,7 @@ impl DualModuleSerial {
         self.propagating_vertices.clear();
         let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
         let mut newly_propagated_edge_has_zero_weight = false;
+        let mut is_overgrown_vertex_removed = false;
         if is_grow {
             // gracefully update the boundary to ease growing
             let dual_node_internal = dual_node_internal_ptr.read_recursive();
@@ -1794,7 +2568,7 @@ impl DualModuleSerial {
                 } else {
                     &edge.left_dual_node
                 };
-                if edge.left_growth + edge.right_growth == edge.weight && peer_dual_node.is_none() {
+                if edge.total_growth() == edge.weight && peer_dual_node.is_none() {
                     // need to propagate to a new node
                     let peer_vertex_ptr = if is_left {
                         edge.right.upgrade_force()
@@ -1920,6 +2694,7 @@ impl DualModuleSerial {
                     let last_index = dual_node_internal.overgrown_stack.len() - 1;
                     let (_, overgrown) = &dual_node_internal.overgrown_stack[last_index];
                     if *overgrown == 0 {
+                        is_overgrown_vertex_removed = true;
                         let (vertex_weak, _) = dual_node_internal.overgrown_stack.pop().unwrap();
                         let vertex_ptr = vertex_weak.upgrade_force();
                         // push the surrounding edges back to the boundary
@@ -2070,7 +2845,7 @@ impl DualModuleSerial {
                             // fully grown edge is where to shrink
                             let newly_propagated_edge = edge.left_dual_node == Some(dual_node_internal_ptr.downgrade())
                                 && edge.right_dual_node == Some(dual_node_internal_ptr.downgrade())
-                                && edge.left_growth + edge.right_growth >= edge.weight;
+                                && edge.total_growth() >= edge.weight;
                             debug_assert!(
                                 {
                                     newly_propagated_edge || {
@@ -2144,9 +2919,63 @@ impl DualModuleSerial {
                 "the boundary of a dual cluster is never empty"
             );
         }
+        drop(dual_node_internal);
+        if (is_overgrown_vertex_removed || !self.propagating_vertices.is_empty()) && self.is_event_queue_enabled() {
+            // the neighbors on both the old and the new boundary may see a different node
+            let dual_node_internal = dual_node_internal_ptr.read_recursive();
+            self.event_dirty_nodes.insert(dual_node_internal.index);
+            Self::collect_boundary_peers(&self.updated_boundary, active_timestamp, &mut self.event_dirty_nodes);
+            Self::collect_boundary_peers(&dual_node_internal.boundary, active_timestamp, &mut self.event_dirty_nodes);
+        }
         newly_propagated_edge_has_zero_weight
     }
 
+    /// prepare the growing or shrinking state of all nodes, see [`DualModuleImpl::prepare_all`]
+    fn prepare_all_nodes(&mut self) -> &mut Vec<SyncRequest> {
+        debug_assert!(
+            self.sync_requests.is_empty(),
+            "make sure to remove all sync requests before prepare to avoid out-dated requests"
+        );
+        self.renew_active_list();
+        for i in 0..self.active_list.len() {
+            let dual_node_ptr = {
+                if let Some(internal_dual_node_ptr) = self.active_list[i].upgrade() {
+                    let dual_node_internal = internal_dual_node_ptr.read_recursive();
+                    dual_node_internal.origin.upgrade_force()
+                } else {
+                    continue; // a blossom could be in the active list even after it's been removed
+                }
+            };
+            let dual_node = dual_node_ptr.read_recursive();
+            match dual_node.grow_state {
+                DualNodeGrowState::Grow => {}
+                DualNodeGrowState::Shrink => {
+                    self.prepare_dual_node_growth(&dual_node_ptr, false);
+                }
+                DualNodeGrowState::Stay => {} // do not touch, Stay nodes might have become a part of a blossom, so it's not safe to change the boundary
+            };
+        }
+        for i in 0..self.active_list.len() {
+            let dual_node_ptr = {
+                if let Some(internal_dual_node_ptr) = self.active_list[i].upgrade() {
+                    let dual_node_internal = internal_dual_node_ptr.read_recursive();
+                    dual_node_internal.origin.upgrade_force()
+                } else {
+                    continue; // a blossom could be in the active list even after it's been removed
+                }
+            };
+            let dual_node = dual_node_ptr.read_recursive();
+            match dual_node.grow_state {
+                DualNodeGrowState::Grow => {
+                    self.prepare_dual_node_growth(&dual_node_ptr, true);
+                }
+                DualNodeGrowState::Shrink => {}
+                DualNodeGrowState::Stay => {} // do not touch, Stay nodes might have become a part of a blossom, so it's not safe to change the boundary
+            };
+        }
+        &mut self.sync_requests
+    }
+
     /// adjust the boundary of each dual node to fit into the need of growing (`length` > 0) or shrinking (`length` < 0)
     pub fn prepare_dual_node_growth(&mut self, dual_node_ptr: &DualNodePtr, is_grow: bool) {
         let mut need_another = self.prepare_dual_node_growth_single(dual_node_ptr, is_grow);
@@ -2201,33 +3030,33 @@ mod tests {
         // create dual nodes and grow them by half length
         let dual_node_19_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
         let dual_node_25_ptr = interface_ptr.read_recursive().nodes[1].clone().unwrap();
-        dual_module.grow_dual_node(&dual_node_19_ptr, half_weight);
-        dual_module.grow_dual_node(&dual_node_25_ptr, half_weight);
+        dual_module.grow_dual_node(&dual_node_19_ptr, half_weight).unwrap();
+        dual_module.grow_dual_node(&dual_node_25_ptr, half_weight).unwrap();
         visualizer
             .snapshot_combined("grow to 0.5".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
-        dual_module.grow_dual_node(&dual_node_19_ptr, half_weight);
-        dual_module.grow_dual_node(&dual_node_25_ptr, half_weight);
+        dual_module.grow_dual_node(&dual_node_19_ptr, half_weight).unwrap();
+        dual_module.grow_dual_node(&dual_node_25_ptr, half_weight).unwrap();
         visualizer
             .snapshot_combined("grow to 1".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
-        dual_module.grow_dual_node(&dual_node_19_ptr, half_weight);
-        dual_module.grow_dual_node(&dual_node_25_ptr, half_weight);
+        dual_module.grow_dual_node(&dual_node_19_ptr, half_weight).unwrap();
+        dual_module.grow_dual_node(&dual_node_25_ptr, half_weight).unwrap();
         visualizer
             .snapshot_combined("grow to 1.5".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
-        dual_module.grow_dual_node(&dual_node_19_ptr, -half_weight);
-        dual_module.grow_dual_node(&dual_node_25_ptr, -half_weight);
+        dual_module.grow_dual_node(&dual_node_19_ptr, -half_weight).unwrap();
+        dual_module.grow_dual_node(&dual_node_25_ptr, -half_weight).unwrap();
         visualizer
             .snapshot_combined("shrink to 1".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
-        dual_module.grow_dual_node(&dual_node_19_ptr, -half_weight);
-        dual_module.grow_dual_node(&dual_node_25_ptr, -half_weight);
+        dual_module.grow_dual_node(&dual_node_19_ptr, -half_weight).unwrap();
+        dual_module.grow_dual_node(&dual_node_25_ptr, -half_weight).unwrap();
         visualizer
             .snapshot_combined("shrink to 0.5".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
-        dual_module.grow_dual_node(&dual_node_19_ptr, -half_weight);
-        dual_module.grow_dual_node(&dual_node_25_ptr, -half_weight);
+        dual_module.grow_dual_node(&dual_node_19_ptr, -half_weight).unwrap();
+        dual_module.grow_dual_node(&dual_node_25_ptr, -half_weight).unwrap();
         visualizer
             .snapshot_combined("shrink to 0".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
@@ -2502,8 +3331,8 @@ mod tests {
         // cannot grow anymore, find out the reason
         let group_max_update_length = dual_module.compute_maximum_update_length();
         assert!(
-            group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.clone(), 23))
-                || group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.clone(), 39)),
+            group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.key(), 23))
+                || group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.key(), 39)),
             "unexpected: {:?}",
             group_max_update_length
         );
@@ -2546,7 +3375,7 @@ mod tests {
         // cannot shrink anymore, find out the reason
         let group_max_update_length = dual_module.compute_maximum_update_length();
         assert!(
-            group_max_update_length.peek().unwrap() == &MaxUpdateLength::BlossomNeedExpand(dual_node_blossom.clone()),
+            group_max_update_length.peek().unwrap() == &MaxUpdateLength::BlossomNeedExpand(dual_node_blossom.key()),
             "unexpected: {:?}",
             group_max_update_length
         );
@@ -2675,8 +3504,8 @@ mod tests {
         // cannot grow anymore, find out the reason
         let group_max_update_length = dual_module.compute_maximum_update_length();
         assert!(
-            group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.clone(), 23))
-                || group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.clone(), 39)),
+            group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.key(), 23))
+                || group_max_update_length.peek().unwrap().get_touching_virtual() == Some((dual_node_blossom.key(), 39)),
             "unexpected: {:?}",
             group_max_update_length
         );
@@ -2711,7 +3540,7 @@ mod tests {
         // cannot shrink anymore, find out the reason
         let group_max_update_length = dual_module.compute_maximum_update_length();
         assert!(
-            group_max_update_length.peek().unwrap() == &MaxUpdateLength::BlossomNeedExpand(dual_node_blossom.clone()),
+            group_max_update_length.peek().unwrap() == &MaxUpdateLength::BlossomNeedExpand(dual_node_blossom.key()),
             "unexpected: {:?}",
             group_max_update_length
         );
@@ -2757,7 +3586,7 @@ mod tests {
             .snapshot_combined("syndrome".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
         // create dual nodes and grow them by half length
-        interface_ptr.grow_iterative(4 * half_weight, &mut dual_module);
+        interface_ptr.grow_iterative(4 * half_weight, &mut dual_module).unwrap();
         visualizer
             .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
@@ -2768,7 +3597,7 @@ mod tests {
         interface_ptr.set_grow_state(&dual_node_39_ptr, DualNodeGrowState::Shrink, &mut dual_module);
         interface_ptr.set_grow_state(&dual_node_65_ptr, DualNodeGrowState::Shrink, &mut dual_module);
         interface_ptr.set_grow_state(&dual_node_87_ptr, DualNodeGrowState::Shrink, &mut dual_module);
-        interface_ptr.grow_iterative(4 * half_weight, &mut dual_module);
+        interface_ptr.grow_iterative(4 * half_weight, &mut dual_module).unwrap();
         visualizer
             .snapshot_combined("shrink".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
@@ -2822,7 +3651,7 @@ mod tests {
             .unwrap();
         // create dual nodes and grow them by half length
         for _ in 0..3 {
-            interface_ptr.grow_iterative(2 * half_weight, &mut dual_module);
+            interface_ptr.grow_iterative(2 * half_weight, &mut dual_module).unwrap();
             visualizer
                 .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
@@ -2832,7 +3661,7 @@ mod tests {
         interface_ptr.set_grow_state(&dual_node_ptr, DualNodeGrowState::Shrink, &mut dual_module);
         // shrink them back, to make sure the operation is reversible
         for _ in 0..3 {
-            interface_ptr.grow_iterative(2 * half_weight, &mut dual_module);
+            interface_ptr.grow_iterative(2 * half_weight, &mut dual_module).unwrap();
             visualizer
                 .snapshot_combined("shrink".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
@@ -2848,10 +3677,242 @@ mod tests {
             .snapshot_combined("after clear".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
         for _ in 0..3 {
-            interface_ptr.grow_iterative(2 * half_weight, &mut dual_module);
+            interface_ptr.grow_iterative(2 * half_weight, &mut dual_module).unwrap();
             visualizer
                 .snapshot_combined("grow".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
         }
     }
+
+    /// the graph-level sanity check should pass in a normal run and catch an inconsistent dual variable
+    #[test]
+    fn dual_module_serial_sanity_check_graph() {
+        // cargo test dual_module_serial_sanity_check_graph -- --nocapture
+        let half_weight = 500;
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, half_weight);
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        code.vertices[19].is_defect = true;
+        code.vertices[25].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        DualModuleImpl::sanity_check(&dual_module, &interface_ptr).unwrap();
+        interface_ptr.grow(half_weight, &mut dual_module);
+        DualModuleImpl::sanity_check(&dual_module, &interface_ptr).unwrap();
+        // corrupt the dual variable of a node
+        let dual_node_internal_ptr = dual_module.nodes[0].clone().unwrap();
+        dual_node_internal_ptr.write().dual_variable += 1;
+        let error = DualModuleImpl::sanity_check(&dual_module, &interface_ptr).unwrap_err();
+        println!("{error}");
+        assert!(error.contains("dual variable"));
+        // corrupt the growth of an edge, which is also caught by the per-grow strict checks
+        dual_module.sanity_check_edges().unwrap();
+        dual_module.edges[0].write(dual_module.active_timestamp).left_growth = -1;
+        let error = dual_module.sanity_check_edges().unwrap_err();
+        println!("{error}");
+        assert!(error.contains("negative growth"));
+    }
+
+    #[test]
+    fn dual_module_serial_get_edge_growth() {
+        // cargo test dual_module_serial_get_edge_growth -- --nocapture
+        let half_weight = 500;
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, half_weight);
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        code.vertices[19].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        interface_ptr.grow(half_weight, &mut dual_module);
+        let edge_indices: Vec<EdgeIndex> = (0..initializer.weighted_edges.len() as EdgeIndex).collect();
+        let edge_growths = dual_module.get_edge_growths(&edge_indices).unwrap();
+        for (edge_index, &(i, j, weight)) in initializer.weighted_edges.iter().enumerate() {
+            let expected_growth = if i == 19 || j == 19 { half_weight } else { 0 };
+            assert_eq!(edge_growths[edge_index], (expected_growth, weight));
+            assert_eq!(
+                dual_module.get_edge_growth(edge_index as EdgeIndex).unwrap(),
+                edge_growths[edge_index]
+            );
+        }
+    }
+
+    #[test]
+    fn dual_module_serial_constant_time_clear() {
+        // cargo test dual_module_serial_constant_time_clear -- --nocapture
+        use crate::mwpm_solver::*;
+        let mut code = CircuitLevelPlanarCode::new(5, 5, 0.02, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let syndrome_pattern = code.generate_random_errors(1);
+        solver.solve(&syndrome_pattern);
+        let expected_weight = solver.sum_dual_variables();
+        solver.clear();
+        // clearing does not visit the graph: the stale vertices and edges are only cleared when accessed again
+        let active_timestamp = solver.dual_module.active_timestamp;
+        assert!(solver
+            .dual_module
+            .edges
+            .iter()
+            .all(|edge_ptr| edge_ptr.read_recursive_force().timestamp != active_timestamp));
+        for (edge_index, &(_, _, weight)) in initializer.weighted_edges.iter().enumerate() {
+            assert_eq!(
+                solver.dual_module.get_edge_growth(edge_index as EdgeIndex).unwrap(),
+                (0, weight)
+            );
+        }
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), expected_weight);
+        // the timestamp wraps around through a hard clear
+        solver.dual_module.active_timestamp = FastClearTimestamp::MAX;
+        solver.clear();
+        assert_eq!(solver.dual_module.active_timestamp, 1);
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), expected_weight);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn dual_module_serial_erasure_probabilities() {
+        // cargo test dual_module_serial_erasure_probabilities -- --nocapture
+        use crate::primal_module::*;
+        use crate::primal_module_serial::*;
+        assert_eq!(EdgeWeightModifier::erasure_weight(0.5, 1000.), 0);
+        assert_eq!(EdgeWeightModifier::erasure_weight(0.1, 100.), 220);
+        let half_weight = 500;
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, half_weight);
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        // a full erasure and a partial one, mixed in the same shot
+        dual_module.load_erasure_probabilities(&[(3, 0.5), (5, 0.1)], 100.).unwrap();
+        assert_eq!(dual_module.get_edge_growth(3).unwrap(), (0, 0));
+        assert_eq!(dual_module.get_edge_growth(5).unwrap(), (0, 220));
+        assert_eq!(dual_module.edge_modifier.erasure_probability(5), Some(0.1));
+        assert_eq!(dual_module.edge_modifier.erasure_probability(4), None);
+        // the edge 5 is cheaper than the surrounding edges, so the defects on its ends are matched through it
+        let (left, right, _) = initializer.weighted_edges[5];
+        code.vertices[left as usize].is_defect = true;
+        code.vertices[right as usize].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+        primal_module.solve(&interface_ptr, &code.get_syndrome(), &mut dual_module);
+        assert_eq!(interface_ptr.sum_dual_variables(), 220);
+        // the weights and the probabilities are reverted when cleared
+        dual_module.clear();
+        assert_eq!(dual_module.get_edge_growth(5).unwrap(), (0, initializer.weighted_edges[5].2));
+        assert!(dual_module.edge_modifier.erasure_probabilities.is_empty());
+    }
+
+    #[test]
+    fn dual_module_serial_active_nodes() {
+        // cargo test dual_module_serial_active_nodes -- --nocapture
+        let half_weight = 500;
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, half_weight);
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        code.vertices[19].is_defect = true;
+        code.vertices[25].is_defect = true;
+        code.vertices[33].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        assert_eq!(interface_ptr.read_recursive().active_nodes_count(), 3);
+        let dual_node_19_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
+        let dual_node_25_ptr = interface_ptr.read_recursive().nodes[1].clone().unwrap();
+        let dual_node_33_ptr = interface_ptr.read_recursive().nodes[2].clone().unwrap();
+        interface_ptr.set_grow_state(&dual_node_19_ptr, DualNodeGrowState::Stay, &mut dual_module);
+        interface_ptr.set_grow_state(&dual_node_25_ptr, DualNodeGrowState::Shrink, &mut dual_module);
+        assert_eq!(interface_ptr.read_recursive().active_nodes_count(), 2);
+        assert!(!interface_ptr.read_recursive().active_nodes().contains(&dual_node_19_ptr));
+        interface_ptr.set_grow_state(&dual_node_19_ptr, DualNodeGrowState::Grow, &mut dual_module);
+        // a blossom replaces its children in the active nodes
+        let nodes_circle = vec![dual_node_19_ptr.clone(), dual_node_33_ptr.clone(), dual_node_25_ptr.clone()];
+        let dual_node_blossom = interface_ptr.create_blossom(nodes_circle, vec![], &mut dual_module);
+        assert_eq!(interface_ptr.read_recursive().active_nodes(), vec![dual_node_blossom.clone()]);
+        interface_ptr.sanity_check().unwrap();
+        interface_ptr.expand_blossom(dual_node_blossom, &mut dual_module);
+        assert_eq!(interface_ptr.read_recursive().active_nodes_count(), 3);
+        interface_ptr.sanity_check().unwrap();
+        interface_ptr.clear();
+        assert_eq!(interface_ptr.read_recursive().active_nodes_count(), 0);
+    }
+
+    #[test]
+    fn dual_module_serial_event_queue_consistency() {
+        // cargo test dual_module_serial_event_queue_consistency -- --nocapture
+        use crate::mwpm_solver::*;
+        use crate::primal_module::*;
+        let mut code = CircuitLevelPlanarCode::new(7, 7, 0.01, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        assert!(matches!(solver.dual_module.event_queue, EventQueue::Heap(_)));
+        let mut baseline = SolverSerial::new(&initializer);
+        baseline.dual_module.enable_event_queue = false;
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            baseline.solve(&syndrome_pattern);
+            assert_eq!(solver.sum_dual_variables(), baseline.sum_dual_variables(), "seed {seed}");
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_perfect_matching(&solver.perfect_matching());
+            assert_eq!(subgraph_builder.total_weight(), solver.sum_dual_variables(), "seed {seed}");
+            solver.clear();
+            baseline.clear();
+        }
+    }
+
+    #[test]
+    fn dual_module_serial_parallel_scan() {
+        // cargo test dual_module_serial_parallel_scan -- --nocapture
+        use crate::mwpm_solver::*;
+        let mut code = CircuitLevelPlanarCode::new(7, 7, 0.02, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        solver.dual_module.enable_event_queue = false;
+        solver.dual_module.parallel_scan_threshold = Some(0);
+        let mut baseline = SolverSerial::new(&initializer);
+        let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        thread_pool.install(|| {
+            for seed in 0..50 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                solver.solve(&syndrome_pattern);
+                baseline.solve(&syndrome_pattern);
+                assert_eq!(solver.sum_dual_variables(), baseline.sum_dual_variables(), "seed {seed}");
+                solver.clear();
+                baseline.clear();
+            }
+        });
+    }
+
+    #[test]
+    fn dual_module_serial_bucket_queue() {
+        // cargo test dual_module_serial_bucket_queue -- --nocapture
+        use crate::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(7, 7, 0.03, 1);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        assert!(
+            matches!(solver.dual_module.event_queue, EventQueue::Bucket(_)),
+            "small weights use the bucket queue"
+        );
+        let mut baseline = SolverSerial::new(&initializer);
+        baseline.dual_module.enable_event_queue = false;
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            baseline.solve(&syndrome_pattern);
+            assert_eq!(solver.sum_dual_variables(), baseline.sum_dual_variables(), "seed {seed}");
+            solver.clear();
+            baseline.clear();
+        }
+        // events are popped in order and stale entries are skipped
+        let mut queue = BucketQueue::new();
+        queue.push(0, 5);
+        queue.push(1, 3);
+        queue.push(2, Weight::MAX);
+        queue.push(0, 4);
+        assert_eq!(queue.pop(), Some((1, 3)));
+        queue.push(1, 3);
+        assert_eq!(queue.pop(), Some((1, 3)));
+        assert_eq!(queue.pop(), Some((0, 4)));
+        assert_eq!(queue.peek(), Some(Weight::MAX));
+        assert_eq!(queue.pop(), None);
+        queue.remove(2);
+        assert_eq!(queue.peek(), None);
+    }
 }
@@ -0,0 +1,943 @@
+//! Embedded Solver
+//!
+//! A fixed-capacity dual module and primal module that only depend on `core`: every state lives in arrays sized at
+//! compile time, without `Arc`, `RwLock`, heap allocation or `serde`. This is the part of the library that remains
+//! when building with `--no-default-features`, e.g. for decoders running on FPGA soft-cores or microcontrollers.
+//!
+//! The dual module keeps the all-pair distances of the decoding graph, precomputed when constructed, so that the
+//! primal module runs the blossom algorithm on the complete graph of the defect vertices. Every defect vertex has a
+//! mirrored vertex standing for its nearest boundary, and the mirrored vertices are connected to each other with zero
+//! weight, so that a perfect matching always exists as long as every defect can reach the boundary or another defect.
+//!
+//! The distance table takes O(`VERTEX_NUM`^2) memory, which can easily exceed the stack of a microcontroller, so it is
+//! never owned by the dual module: the caller provides the storage, e.g. a `static mut` array or a region of memory
+//! initialized offline, and the dual module only borrows it.
+//!
+
+use core::ops::{Index, IndexMut};
+
+/// the weight type of the embedded solver; edge weights are doubled and shifted internally, so the distance between any
+/// two vertices must be no larger than `Weight::MAX / 8`
+pub type Weight = i32;
+
+/// the distance between two disconnected vertices
+const INFINITY: Weight = Weight::MAX;
+
+/// how a defect vertex is matched in the minimum-weight perfect matching
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddedMatch {
+    /// matched to another defect vertex
+    Peer(usize),
+    /// matched to the boundary through this virtual vertex
+    VirtualVertex(usize),
+}
+
+/// a directed edge of the matching graph pointing to `to`; the reversed one is the other endpoint of the same edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Endpoint {
+    from: usize,
+    to: usize,
+}
+
+impl Endpoint {
+    fn new(from: usize, to: usize) -> Self {
+        Self { from, to }
+    }
+
+    fn reversed(self) -> Self {
+        Self::new(self.to, self.from)
+    }
+
+    fn reversed_if(self, condition: bool) -> Self {
+        if condition {
+            self.reversed()
+        } else {
+            self
+        }
+    }
+}
+
+/// an array of nodes: the first `N` are the vertices of the matching graph and the next `N` are blossoms
+#[derive(Debug, Clone, Copy)]
+struct NodeArray<T, const N: usize>([T; N], [T; N]);
+
+impl<T: Copy, const N: usize> NodeArray<T, N> {
+    fn new(value: T) -> Self {
+        Self([value; N], [value; N])
+    }
+
+    fn fill(&mut self, value: T) {
+        self.0 = [value; N];
+        self.1 = [value; N];
+    }
+}
+
+impl<T, const N: usize> Index<usize> for NodeArray<T, N> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T {
+        if index < N {
+            &self.0[index]
+        } else {
+            &self.1[index - N]
+        }
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for NodeArray<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        if index < N {
+            &mut self.0[index]
+        } else {
+            &mut self.1[index - N]
+        }
+    }
+}
+
+/// whether `candidate` should replace the current best value, which always holds when there is none yet
+fn none_or<T>(best: Option<T>, candidate_is_better: impl FnOnce(T) -> bool) -> bool {
+    match best {
+        Some(best) => candidate_is_better(best),
+        None => true,
+    }
+}
+
+/// the dual module holds the decoding graph of `VERTEX_NUM` vertices as a distance table, together with the dual
+/// variables of the matching graph; `NODE_NUM` must be at least twice the maximum number of defect vertices
+#[derive(Debug, Clone)]
+pub struct EmbeddedDualModule<'a, const VERTEX_NUM: usize, const NODE_NUM: usize> {
+    /// the shortest distance between every pair of vertices, [`Weight::MAX`] if disconnected; owned by the caller
+    distances: &'a [[Weight; VERTEX_NUM]; VERTEX_NUM],
+    /// the nearest virtual vertex of each vertex and the distance to it
+    boundaries: [Option<(usize, Weight)>; VERTEX_NUM],
+    /// the decoding graph vertex of each loaded defect
+    defect_vertices: [usize; NODE_NUM],
+    /// the number of loaded defect vertices
+    defect_num: usize,
+    /// the largest weight in the matching graph, used to turn minimum-weight into maximum-weight matching
+    max_weight: Weight,
+    /// dual variables of the vertices and blossoms in the matching graph
+    dual_variables: NodeArray<Weight, NODE_NUM>,
+}
+
+impl<'a, const VERTEX_NUM: usize, const NODE_NUM: usize> EmbeddedDualModule<'a, VERTEX_NUM, NODE_NUM> {
+    /// precompute the distances into the caller-provided `distances` using Floyd-Warshall algorithm, which takes
+    /// O(|V|^3) time and is meant to run offline
+    pub fn new(
+        distances: &'a mut [[Weight; VERTEX_NUM]; VERTEX_NUM],
+        weighted_edges: &[(usize, usize, Weight)],
+        virtual_vertices: &[usize],
+    ) -> Self {
+        for (vertex_index, row) in distances.iter_mut().enumerate() {
+            row.fill(INFINITY);
+            row[vertex_index] = 0;
+        }
+        for &(i, j, weight) in weighted_edges.iter() {
+            assert!(
+                i < VERTEX_NUM && j < VERTEX_NUM,
+                "edge ({i}, {j}) out of capacity {VERTEX_NUM}"
+            );
+            assert!(weight >= 0, "edge weight must be non-negative");
+            distances[i][j] = core::cmp::min(distances[i][j], weight);
+            distances[j][i] = distances[i][j];
+        }
+        for k in 0..VERTEX_NUM {
+            for i in 0..VERTEX_NUM {
+                if distances[i][k] == INFINITY {
+                    continue;
+                }
+                for j in 0..VERTEX_NUM {
+                    let distance = distances[i][k].saturating_add(distances[k][j]);
+                    if distance < distances[i][j] {
+                        distances[i][j] = distance;
+                    }
+                }
+            }
+        }
+        Self::from_distances(distances, virtual_vertices)
+    }
+
+    /// use a distance table precomputed by [`Self::new`], e.g. one that is stored in read-only memory
+    pub fn from_distances(distances: &'a [[Weight; VERTEX_NUM]; VERTEX_NUM], virtual_vertices: &[usize]) -> Self {
+        let mut boundaries = [None; VERTEX_NUM];
+        for (vertex_index, boundary) in boundaries.iter_mut().enumerate() {
+            for &virtual_vertex in virtual_vertices.iter() {
+                let distance = distances[vertex_index][virtual_vertex];
+                if distance != INFINITY && none_or(*boundary, |(_, weight)| distance < weight) {
+                    *boundary = Some((virtual_vertex, distance));
+                }
+            }
+        }
+        Self {
+            distances,
+            boundaries,
+            defect_vertices: [0; NODE_NUM],
+            defect_num: 0,
+            max_weight: 0,
+            dual_variables: NodeArray::new(0),
+        }
+    }
+
+    /// load the defect vertices and initialize the dual variables
+    pub fn load(&mut self, defect_vertices: &[usize]) {
+        assert!(
+            2 * defect_vertices.len() <= NODE_NUM,
+            "{} defect vertices exceed the capacity of {} nodes",
+            defect_vertices.len(),
+            NODE_NUM
+        );
+        self.defect_num = defect_vertices.len();
+        self.defect_vertices[..self.defect_num].copy_from_slice(defect_vertices);
+        self.max_weight = 0;
+        for i in 0..self.vertex_num() {
+            for j in (i + 1)..self.vertex_num() {
+                if let Some(weight) = self.matching_weight(i, j) {
+                    self.max_weight = core::cmp::max(self.max_weight, weight);
+                }
+            }
+        }
+        assert!(self.max_weight <= Weight::MAX / 8, "weight too large: {}", self.max_weight);
+        // every vertex starts with the largest (transformed) weight, which is a mirror-to-mirror edge if any
+        let mut max_transformed_weight = 0;
+        for i in 0..self.vertex_num() {
+            for j in (i + 1)..self.vertex_num() {
+                if let Some(weight) = self.weight(i, j) {
+                    max_transformed_weight = core::cmp::max(max_transformed_weight, weight);
+                }
+            }
+        }
+        self.dual_variables.fill(0);
+        for vertex in 0..self.vertex_num() {
+            self.dual_variables[vertex] = max_transformed_weight;
+        }
+    }
+
+    /// the number of vertices in the matching graph, i.e. the defect vertices and their mirrors
+    pub fn vertex_num(&self) -> usize {
+        2 * self.defect_num
+    }
+
+    /// the weight of the edge between two vertices in the matching graph, `None` if they're not connected
+    fn matching_weight(&self, i: usize, j: usize) -> Option<Weight> {
+        debug_assert!(i != j);
+        let defect_num = self.defect_num;
+        let weight = match (i < defect_num, j < defect_num) {
+            (true, true) => self.distances[self.defect_vertices[i]][self.defect_vertices[j]],
+            (true, false) if j - defect_num == i => self.boundaries[self.defect_vertices[i]]?.1,
+            (false, true) if i - defect_num == j => self.boundaries[self.defect_vertices[j]]?.1,
+            (false, false) => 0,
+            _ => INFINITY,
+        };
+        if weight == INFINITY {
+            None
+        } else {
+            Some(weight)
+        }
+    }
+
+    /// the weight in the maximum-weight matching problem, doubled so that all the dual variables stay integers
+    fn weight(&self, i: usize, j: usize) -> Option<Weight> {
+        self.matching_weight(i, j).map(|weight| 2 * (self.max_weight - weight))
+    }
+
+    fn slack(&self, endpoint: Endpoint) -> Weight {
+        let weight = self.weight(endpoint.from, endpoint.to).unwrap();
+        self.dual_variables[endpoint.from] + self.dual_variables[endpoint.to] - 2 * weight
+    }
+}
+
+/// the primal module runs the blossom algorithm on the matching graph of at most `NODE_NUM` vertices; the labels follow
+/// the convention of Edmonds' algorithm: 1 for an outer (S) node, 2 for an inner (T) node and 0 for a free node
+#[derive(Debug, Clone)]
+pub struct EmbeddedPrimalModule<const NODE_NUM: usize> {
+    /// the matched edge of each vertex, pointing to its peer
+    mate: [Option<Endpoint>; NODE_NUM],
+    /// the label of each vertex and top-level blossom
+    label: NodeArray<i8, NODE_NUM>,
+    /// the edge through which a node gets its label, pointing to the node in the alternating tree closer to the root
+    label_end: NodeArray<Option<Endpoint>, NODE_NUM>,
+    /// the top-level blossom containing each vertex
+    in_blossom: [usize; NODE_NUM],
+    blossom_parent: NodeArray<Option<usize>, NODE_NUM>,
+    blossom_base: NodeArray<Option<usize>, NODE_NUM>,
+    /// the children of each blossom along the odd cycle, starting from the base
+    blossom_children: [[usize; NODE_NUM]; NODE_NUM],
+    /// the edge between each child and the next one, pointing to the latter
+    blossom_endpoints: [[Endpoint; NODE_NUM]; NODE_NUM],
+    blossom_length: [usize; NODE_NUM],
+    /// the edge with the least slack to a different outer node
+    best_edge: NodeArray<Option<Endpoint>, NODE_NUM>,
+    /// the least-slack edges from an outer blossom to every other outer node, `None` if not computed yet
+    blossom_best_edges: [[Endpoint; NODE_NUM]; NODE_NUM],
+    blossom_best_edges_length: [Option<usize>; NODE_NUM],
+    unused_blossoms: [usize; NODE_NUM],
+    unused_blossoms_length: usize,
+    /// whether an edge is tight enough to be used in the alternating tree
+    allowed: [[bool; NODE_NUM]; NODE_NUM],
+    /// the outer vertices to be scanned
+    queue: [usize; NODE_NUM],
+    queue_length: usize,
+}
+
+impl<const NODE_NUM: usize> Default for EmbeddedPrimalModule<NODE_NUM> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const NODE_NUM: usize> EmbeddedPrimalModule<NODE_NUM> {
+    pub fn new() -> Self {
+        Self {
+            mate: [None; NODE_NUM],
+            label: NodeArray::new(0),
+            label_end: NodeArray::new(None),
+            in_blossom: [0; NODE_NUM],
+            blossom_parent: NodeArray::new(None),
+            blossom_base: NodeArray::new(None),
+            blossom_children: [[0; NODE_NUM]; NODE_NUM],
+            blossom_endpoints: [[Endpoint::default(); NODE_NUM]; NODE_NUM],
+            blossom_length: [0; NODE_NUM],
+            best_edge: NodeArray::new(None),
+            blossom_best_edges: [[Endpoint::default(); NODE_NUM]; NODE_NUM],
+            blossom_best_edges_length: [None; NODE_NUM],
+            unused_blossoms: [0; NODE_NUM],
+            unused_blossoms_length: 0,
+            allowed: [[false; NODE_NUM]; NODE_NUM],
+            queue: [0; NODE_NUM],
+            queue_length: 0,
+        }
+    }
+
+    /// the peer of a vertex in the matching graph
+    pub fn peer(&self, vertex: usize) -> Option<usize> {
+        self.mate[vertex].map(|endpoint| endpoint.to)
+    }
+
+    fn child(&self, blossom: usize, position: isize) -> usize {
+        let length = self.blossom_length[blossom - NODE_NUM];
+        self.blossom_children[blossom - NODE_NUM][position.rem_euclid(length as isize) as usize]
+    }
+
+    fn child_endpoint(&self, blossom: usize, position: isize) -> Endpoint {
+        let length = self.blossom_length[blossom - NODE_NUM];
+        self.blossom_endpoints[blossom - NODE_NUM][position.rem_euclid(length as isize) as usize]
+    }
+
+    fn child_position(&self, blossom: usize, child: usize) -> usize {
+        let length = self.blossom_length[blossom - NODE_NUM];
+        self.blossom_children[blossom - NODE_NUM][..length]
+            .iter()
+            .position(|&node| node == child)
+            .unwrap()
+    }
+
+    fn allow(&mut self, endpoint: Endpoint) {
+        self.allowed[endpoint.from][endpoint.to] = true;
+        self.allowed[endpoint.to][endpoint.from] = true;
+    }
+
+    fn push_queue(&mut self, vertex: usize) {
+        self.queue[self.queue_length] = vertex;
+        self.queue_length += 1;
+    }
+
+    /// collect all the vertices inside a node
+    fn blossom_leaves(&self, node: usize, leaves: &mut [usize; NODE_NUM], length: &mut usize) {
+        if node < NODE_NUM {
+            leaves[*length] = node;
+            *length += 1;
+        } else {
+            for position in 0..self.blossom_length[node - NODE_NUM] {
+                self.blossom_leaves(self.blossom_children[node - NODE_NUM][position], leaves, length);
+            }
+        }
+    }
+
+    fn assign_label(&mut self, vertex: usize, label: i8, label_end: Option<Endpoint>) {
+        let blossom = self.in_blossom[vertex];
+        debug_assert!(self.label[vertex] == 0 && self.label[blossom] == 0);
+        self.label[vertex] = label;
+        self.label[blossom] = label;
+        self.label_end[vertex] = label_end;
+        self.label_end[blossom] = label_end;
+        self.best_edge[vertex] = None;
+        self.best_edge[blossom] = None;
+        if label == 1 {
+            let (mut leaves, mut length) = ([0; NODE_NUM], 0);
+            self.blossom_leaves(blossom, &mut leaves, &mut length);
+            for &leaf in leaves[..length].iter() {
+                self.push_queue(leaf);
+            }
+        } else if label == 2 {
+            let base = self.blossom_base[blossom].unwrap();
+            let mate = self.mate[base].expect("an inner node must be matched");
+            self.assign_label(mate.to, 1, Some(mate.reversed()));
+        }
+    }
+
+    /// trace back from two outer vertices to find the base of a new blossom, or `None` if they're in different trees
+    fn scan_blossom(&mut self, v: usize, w: usize) -> Option<usize> {
+        let (mut path, mut path_length) = ([0; NODE_NUM], 0);
+        let mut base = None;
+        let (mut v, mut w) = (Some(v), Some(w));
+        while let Some(vertex) = v {
+            let blossom = self.in_blossom[vertex];
+            if self.label[blossom] & 4 != 0 {
+                base = self.blossom_base[blossom];
+                break;
+            }
+            debug_assert_eq!(self.label[blossom], 1);
+            path[path_length] = blossom;
+            path_length += 1;
+            self.label[blossom] = 5; // breadcrumb
+            v = self.label_end[blossom].map(|label_end| {
+                let inner_blossom = self.in_blossom[label_end.to];
+                debug_assert_eq!(self.label[inner_blossom], 2);
+                self.label_end[inner_blossom].unwrap().to
+            });
+            if w.is_some() {
+                core::mem::swap(&mut v, &mut w);
+            }
+        }
+        for &blossom in path[..path_length].iter() {
+            self.label[blossom] = 1;
+        }
+        base
+    }
+
+    /// create a new blossom given the base and the edge connecting two outer vertices in the same tree
+    fn add_blossom<const VERTEX_NUM: usize>(
+        &mut self,
+        dual_module: &mut EmbeddedDualModule<'_, VERTEX_NUM, NODE_NUM>,
+        base: usize,
+        edge: Endpoint,
+    ) {
+        let base_blossom = self.in_blossom[base];
+        let mut bv = self.in_blossom[edge.from];
+        let mut bw = self.in_blossom[edge.to];
+        self.unused_blossoms_length -= 1;
+        let blossom = self.unused_blossoms[self.unused_blossoms_length];
+        let slot = blossom - NODE_NUM;
+        self.blossom_base[blossom] = Some(base);
+        self.blossom_parent[blossom] = None;
+        self.blossom_parent[base_blossom] = Some(blossom);
+        let mut length = 0;
+        while bv != base_blossom {
+            self.blossom_parent[bv] = Some(blossom);
+            let label_end = self.label_end[bv].unwrap();
+            self.blossom_children[slot][length] = bv;
+            self.blossom_endpoints[slot][length] = label_end;
+            length += 1;
+            bv = self.in_blossom[label_end.to];
+        }
+        self.blossom_children[slot][length] = base_blossom;
+        self.blossom_children[slot][..length + 1].reverse();
+        self.blossom_endpoints[slot][..length].reverse();
+        self.blossom_endpoints[slot][length] = edge.reversed();
+        length += 1;
+        while bw != base_blossom {
+            self.blossom_parent[bw] = Some(blossom);
+            let label_end = self.label_end[bw].unwrap();
+            self.blossom_children[slot][length] = bw;
+            self.blossom_endpoints[slot][length] = label_end.reversed();
+            length += 1;
+            bw = self.in_blossom[label_end.to];
+        }
+        self.blossom_length[slot] = length;
+        debug_assert_eq!(self.label[base_blossom], 1);
+        self.label[blossom] = 1;
+        self.label_end[blossom] = self.label_end[base_blossom];
+        dual_module.dual_variables[blossom] = 0;
+        let (mut leaves, mut leaves_length) = ([0; NODE_NUM], 0);
+        self.blossom_leaves(blossom, &mut leaves, &mut leaves_length);
+        for &leaf in leaves[..leaves_length].iter() {
+            if self.label[self.in_blossom[leaf]] == 2 {
+                // an inner vertex becomes outer
+                self.push_queue(leaf);
+            }
+            self.in_blossom[leaf] = blossom;
+        }
+        // compute the least-slack edges to the neighboring outer nodes
+        let mut best_edge_to: NodeArray<Option<Endpoint>, NODE_NUM> = NodeArray::new(None);
+        let mut consider = |primal_module: &Self, edge: Endpoint| {
+            let edge = edge.reversed_if(primal_module.in_blossom[edge.to] == blossom);
+            let bj = primal_module.in_blossom[edge.to];
+            if bj != blossom
+                && primal_module.label[bj] == 1
+                && none_or(best_edge_to[bj], |best| dual_module.slack(edge) < dual_module.slack(best))
+            {
+                best_edge_to[bj] = Some(edge);
+            }
+        };
+        for position in 0..length {
+            let child = self.blossom_children[slot][position];
+            match (child >= NODE_NUM)
+                .then(|| self.blossom_best_edges_length[child - NODE_NUM])
+                .flatten()
+            {
+                Some(best_edges_length) => {
+                    for &edge in self.blossom_best_edges[child - NODE_NUM][..best_edges_length].iter() {
+                        consider(self, edge);
+                    }
+                }
+                None => {
+                    let (mut child_leaves, mut child_leaves_length) = ([0; NODE_NUM], 0);
+                    self.blossom_leaves(child, &mut child_leaves, &mut child_leaves_length);
+                    for &leaf in child_leaves[..child_leaves_length].iter() {
+                        for peer in 0..dual_module.vertex_num() {
+                            if peer != leaf && dual_module.weight(leaf, peer).is_some() {
+                                consider(self, Endpoint::new(leaf, peer));
+                            }
+                        }
+                    }
+                }
+            }
+            if child >= NODE_NUM {
+                self.blossom_best_edges_length[child - NODE_NUM] = None;
+            }
+            self.best_edge[child] = None;
+        }
+        let mut best_edges_length = 0;
+        let mut best_edge: Option<Endpoint> = None;
+        for edge in best_edge_to.0.iter().chain(best_edge_to.1.iter()).flatten() {
+            self.blossom_best_edges[slot][best_edges_length] = *edge;
+            best_edges_length += 1;
+            if none_or(best_edge, |best| dual_module.slack(*edge) < dual_module.slack(best)) {
+                best_edge = Some(*edge);
+            }
+        }
+        self.blossom_best_edges_length[slot] = Some(best_edges_length);
+        self.best_edge[blossom] = best_edge;
+    }
+
+    /// expand a blossom: either an inner blossom whose dual variable hits zero, or an outer one at the end of a stage
+    fn expand_blossom<const VERTEX_NUM: usize>(
+        &mut self,
+        dual_module: &mut EmbeddedDualModule<'_, VERTEX_NUM, NODE_NUM>,
+        blossom: usize,
+        end_stage: bool,
+    ) {
+        let slot = blossom - NODE_NUM;
+        for position in 0..self.blossom_length[slot] {
+            let child = self.blossom_children[slot][position];
+            self.blossom_parent[child] = None;
+            if child < NODE_NUM {
+                self.in_blossom[child] = child;
+            } else if end_stage && dual_module.dual_variables[child] == 0 {
+                self.expand_blossom(dual_module, child, end_stage);
+            } else {
+                let (mut leaves, mut length) = ([0; NODE_NUM], 0);
+                self.blossom_leaves(child, &mut leaves, &mut length);
+                for &leaf in leaves[..length].iter() {
+                    self.in_blossom[leaf] = child;
+                }
+            }
+        }
+        if !end_stage && self.label[blossom] == 2 {
+            // relabel the children on the even-length path from the entry child to the base
+            let label_end = self.label_end[blossom].unwrap();
+            let entry_child = self.in_blossom[label_end.from];
+            let mut j = self.child_position(blossom, entry_child) as isize;
+            let (step, trick) = if j & 1 != 0 {
+                j -= self.blossom_length[slot] as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut p = label_end;
+            while j != 0 {
+                self.label[p.from] = 0;
+                let q = self.child_endpoint(blossom, j - trick);
+                self.label[q.reversed_if(trick == 1).reversed().to] = 0;
+                self.assign_label(p.from, 2, Some(p));
+                self.allow(q);
+                j += step;
+                p = self.child_endpoint(blossom, j - trick).reversed_if(trick == 1);
+                self.allow(p);
+                j += step;
+            }
+            let bv = self.child(blossom, j);
+            self.label[p.from] = 2;
+            self.label[bv] = 2;
+            self.label_end[p.from] = Some(p);
+            self.label_end[bv] = Some(p);
+            self.best_edge[bv] = None;
+            j += step;
+            // the children on the other path become free unless they're reachable from outside
+            while self.child(blossom, j) != entry_child {
+                let bv = self.child(blossom, j);
+                j += step;
+                if self.label[bv] == 1 {
+                    continue;
+                }
+                let (mut leaves, mut length) = ([0; NODE_NUM], 0);
+                self.blossom_leaves(bv, &mut leaves, &mut length);
+                if let Some(&vertex) = leaves[..length].iter().find(|&&leaf| self.label[leaf] != 0) {
+                    debug_assert_eq!(self.label[vertex], 2);
+                    debug_assert_eq!(self.in_blossom[vertex], bv);
+                    self.label[vertex] = 0;
+                    let base_mate = self.mate[self.blossom_base[bv].unwrap()].unwrap();
+                    self.label[base_mate.to] = 0;
+                    self.assign_label(vertex, 2, self.label_end[vertex]);
+                }
+            }
+        }
+        self.label[blossom] = -1;
+        self.label_end[blossom] = None;
+        self.blossom_length[slot] = 0;
+        self.blossom_base[blossom] = None;
+        self.blossom_best_edges_length[slot] = None;
+        self.best_edge[blossom] = None;
+        self.unused_blossoms[self.unused_blossoms_length] = blossom;
+        self.unused_blossoms_length += 1;
+    }
+
+    /// swap matched and unmatched edges along the even-length path from `vertex` to the base of the blossom
+    fn augment_blossom(&mut self, blossom: usize, vertex: usize) {
+        let mut t = vertex;
+        while self.blossom_parent[t] != Some(blossom) {
+            t = self.blossom_parent[t].unwrap();
+        }
+        if t >= NODE_NUM {
+            self.augment_blossom(t, vertex);
+        }
+        let slot = blossom - NODE_NUM;
+        let i = self.child_position(blossom, t);
+        let mut j = i as isize;
+        let (step, trick) = if i & 1 != 0 {
+            j -= self.blossom_length[slot] as isize;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += step;
+            let t = self.child(blossom, j);
+            let p = self.child_endpoint(blossom, j - trick).reversed_if(trick == 1);
+            if t >= NODE_NUM {
+                self.augment_blossom(t, p.to);
+            }
+            j += step;
+            let t = self.child(blossom, j);
+            if t >= NODE_NUM {
+                self.augment_blossom(t, p.from);
+            }
+            self.mate[p.to] = Some(p.reversed());
+            self.mate[p.from] = Some(p);
+        }
+        let length = self.blossom_length[slot];
+        self.blossom_children[slot][..length].rotate_left(i);
+        self.blossom_endpoints[slot][..length].rotate_left(i);
+        self.blossom_base[blossom] = self.blossom_base[self.blossom_children[slot][0]];
+        debug_assert_eq!(self.blossom_base[blossom], Some(vertex));
+    }
+
+    /// augment the matching along the path through `edge` between the roots of two alternating trees
+    fn augment_matching(&mut self, edge: Endpoint) {
+        for (mut s, mut p) in [(edge.from, edge), (edge.to, edge.reversed())] {
+            loop {
+                let bs = self.in_blossom[s];
+                debug_assert_eq!(self.label[bs], 1);
+                if bs >= NODE_NUM {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = Some(p);
+                let Some(label_end) = self.label_end[bs] else {
+                    break; // reach the root
+                };
+                let bt = self.in_blossom[label_end.to];
+                debug_assert_eq!(self.label[bt], 2);
+                let inner_label_end = self.label_end[bt].unwrap();
+                s = inner_label_end.to;
+                let j = inner_label_end.from;
+                if bt >= NODE_NUM {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = Some(inner_label_end);
+                p = inner_label_end.reversed();
+            }
+        }
+    }
+
+    /// find a maximum-cardinality maximum-weight matching on the loaded matching graph, which is a minimum-weight
+    /// perfect matching of the defect vertices and their mirrors
+    pub fn solve<const VERTEX_NUM: usize>(&mut self, dual_module: &mut EmbeddedDualModule<'_, VERTEX_NUM, NODE_NUM>) {
+        let vertex_num = dual_module.vertex_num();
+        let blossoms = NODE_NUM..NODE_NUM + vertex_num;
+        self.mate = [None; NODE_NUM];
+        self.blossom_parent.fill(None);
+        self.blossom_base.fill(None);
+        for vertex in 0..vertex_num {
+            self.in_blossom[vertex] = vertex;
+            self.blossom_base[vertex] = Some(vertex);
+        }
+        self.blossom_length = [0; NODE_NUM];
+        self.blossom_best_edges_length = [None; NODE_NUM];
+        for (index, blossom) in blossoms.clone().enumerate() {
+            self.unused_blossoms[index] = blossom;
+        }
+        self.unused_blossoms_length = vertex_num;
+        // each stage finds an augmenting path
+        for _ in 0..vertex_num {
+            self.label.fill(0);
+            self.best_edge.fill(None);
+            self.blossom_best_edges_length = [None; NODE_NUM];
+            for row in self.allowed[..vertex_num].iter_mut() {
+                row[..vertex_num].fill(false);
+            }
+            self.queue_length = 0;
+            for vertex in 0..vertex_num {
+                if self.mate[vertex].is_none() && self.label[self.in_blossom[vertex]] == 0 {
+                    self.assign_label(vertex, 1, None);
+                }
+            }
+            let mut augmented = false;
+            loop {
+                while self.queue_length > 0 && !augmented {
+                    self.queue_length -= 1;
+                    let v = self.queue[self.queue_length];
+                    debug_assert_eq!(self.label[self.in_blossom[v]], 1);
+                    for w in 0..vertex_num {
+                        if w == v || self.in_blossom[v] == self.in_blossom[w] || dual_module.weight(v, w).is_none() {
+                            continue;
+                        }
+                        let edge = Endpoint::new(v, w);
+                        let mut slack = 0;
+                        if !self.allowed[v][w] {
+                            slack = dual_module.slack(edge);
+                            if slack <= 0 {
+                                self.allow(edge);
+                            }
+                        }
+                        if self.allowed[v][w] {
+                            if self.label[self.in_blossom[w]] == 0 {
+                                self.assign_label(w, 2, Some(edge.reversed()));
+                            } else if self.label[self.in_blossom[w]] == 1 {
+                                if let Some(base) = self.scan_blossom(v, w) {
+                                    self.add_blossom(dual_module, base, edge);
+                                } else {
+                                    self.augment_matching(edge);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                debug_assert_eq!(self.label[self.in_blossom[w]], 2);
+                                self.label[w] = 2;
+                                self.label_end[w] = Some(edge.reversed());
+                            }
+                        } else if self.label[self.in_blossom[w]] == 1 {
+                            let b = self.in_blossom[v];
+                            if none_or(self.best_edge[b], |best| slack < dual_module.slack(best)) {
+                                self.best_edge[b] = Some(edge);
+                            }
+                        } else if self.label[w] == 0 && none_or(self.best_edge[w], |best| slack < dual_module.slack(best)) {
+                            self.best_edge[w] = Some(edge);
+                        }
+                    }
+                }
+                if augmented {
+                    break;
+                }
+                // no more tight edges: update the dual variables
+                #[derive(Clone, Copy)]
+                enum Delta {
+                    Finish,
+                    FreeVertex(Endpoint),
+                    OuterPair(Endpoint),
+                    ExpandBlossom(usize),
+                }
+                let mut best: Option<(Weight, Delta)> = None;
+                let mut update = |delta: Weight, kind: Delta| {
+                    if none_or(best, |(best_delta, _)| delta < best_delta) {
+                        best = Some((delta, kind));
+                    }
+                };
+                for vertex in 0..vertex_num {
+                    if self.label[self.in_blossom[vertex]] == 0 {
+                        if let Some(edge) = self.best_edge[vertex] {
+                            update(dual_module.slack(edge), Delta::FreeVertex(edge));
+                        }
+                    }
+                }
+                for node in (0..vertex_num).chain(blossoms.clone()) {
+                    if self.blossom_parent[node].is_none() && self.label[node] == 1 {
+                        if let Some(edge) = self.best_edge[node] {
+                            let slack = dual_module.slack(edge);
+                            debug_assert_eq!(slack % 2, 0);
+                            update(slack / 2, Delta::OuterPair(edge));
+                        }
+                    }
+                }
+                for blossom in blossoms.clone() {
+                    if self.blossom_base[blossom].is_some()
+                        && self.blossom_parent[blossom].is_none()
+                        && self.label[blossom] == 2
+                    {
+                        update(dual_module.dual_variables[blossom], Delta::ExpandBlossom(blossom));
+                    }
+                }
+                let (delta, kind) = best.unwrap_or_else(|| {
+                    let min_dual = (0..vertex_num).map(|vertex| dual_module.dual_variables[vertex]).min();
+                    (core::cmp::max(0, min_dual.unwrap_or(0)), Delta::Finish)
+                });
+                for vertex in 0..vertex_num {
+                    match self.label[self.in_blossom[vertex]] {
+                        1 => dual_module.dual_variables[vertex] -= delta,
+                        2 => dual_module.dual_variables[vertex] += delta,
+                        _ => {}
+                    }
+                }
+                for blossom in blossoms.clone() {
+                    if self.blossom_base[blossom].is_some() && self.blossom_parent[blossom].is_none() {
+                        match self.label[blossom] {
+                            1 => dual_module.dual_variables[blossom] += delta,
+                            2 => dual_module.dual_variables[blossom] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+                match kind {
+                    Delta::Finish => break,
+                    Delta::FreeVertex(edge) => {
+                        self.allow(edge);
+                        let edge = edge.reversed_if(self.label[self.in_blossom[edge.from]] == 0);
+                        debug_assert_eq!(self.label[self.in_blossom[edge.from]], 1);
+                        self.push_queue(edge.from);
+                    }
+                    Delta::OuterPair(edge) => {
+                        self.allow(edge);
+                        debug_assert_eq!(self.label[self.in_blossom[edge.from]], 1);
+                        self.push_queue(edge.from);
+                    }
+                    Delta::ExpandBlossom(blossom) => self.expand_blossom(dual_module, blossom, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+            // expand the outer blossoms with zero dual variable at the end of each stage
+            for blossom in blossoms.clone() {
+                if self.blossom_parent[blossom].is_none()
+                    && self.blossom_base[blossom].is_some()
+                    && self.label[blossom] == 1
+                    && dual_module.dual_variables[blossom] == 0
+                {
+                    self.expand_blossom(dual_module, blossom, true);
+                }
+            }
+        }
+    }
+}
+
+/// a fixed-capacity solver for a decoding graph of `VERTEX_NUM` vertices and at most `NODE_NUM / 2` defect vertices;
+/// the memory usage is O(`NODE_NUM`^2) besides the O(`VERTEX_NUM`^2) distance table provided by the caller, all known at
+/// compile time
+#[derive(Debug, Clone)]
+pub struct EmbeddedSolver<'a, const VERTEX_NUM: usize, const NODE_NUM: usize> {
+    pub dual_module: EmbeddedDualModule<'a, VERTEX_NUM, NODE_NUM>,
+    pub primal_module: EmbeddedPrimalModule<NODE_NUM>,
+}
+
+impl<'a, const VERTEX_NUM: usize, const NODE_NUM: usize> EmbeddedSolver<'a, VERTEX_NUM, NODE_NUM> {
+    pub fn new(
+        distances: &'a mut [[Weight; VERTEX_NUM]; VERTEX_NUM],
+        weighted_edges: &[(usize, usize, Weight)],
+        virtual_vertices: &[usize],
+    ) -> Self {
+        Self {
+            dual_module: EmbeddedDualModule::new(distances, weighted_edges, virtual_vertices),
+            primal_module: EmbeddedPrimalModule::new(),
+        }
+    }
+
+    pub fn solve(&mut self, defect_vertices: &[usize]) {
+        self.dual_module.load(defect_vertices);
+        self.primal_module.solve(&mut self.dual_module);
+    }
+
+    /// how the `defect_index`-th defect vertex is matched, `None` if it cannot be matched
+    pub fn get_match(&self, defect_index: usize) -> Option<EmbeddedMatch> {
+        let dual_module = &self.dual_module;
+        debug_assert!(defect_index < dual_module.defect_num);
+        let peer = self.primal_module.peer(defect_index)?;
+        if peer < dual_module.defect_num {
+            Some(EmbeddedMatch::Peer(dual_module.defect_vertices[peer]))
+        } else {
+            let (virtual_vertex, _) = dual_module.boundaries[dual_module.defect_vertices[defect_index]]?;
+            Some(EmbeddedMatch::VirtualVertex(virtual_vertex))
+        }
+    }
+
+    /// the total weight of the matching
+    pub fn sum_weight(&self) -> Weight {
+        let dual_module = &self.dual_module;
+        let mut sum_weight = 0;
+        for defect_index in 0..dual_module.defect_num {
+            if let Some(peer) = self.primal_module.peer(defect_index) {
+                if peer > defect_index {
+                    sum_weight += dual_module.matching_weight(defect_index, peer).unwrap();
+                }
+            }
+        }
+        sum_weight
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
+    use super::*;
+
+    #[allow(clippy::unnecessary_cast)]
+    fn embedded_solver_compare_serial<const VERTEX_NUM: usize, const NODE_NUM: usize>(mut code: impl ExampleCode) {
+        let initializer = code.get_initializer();
+        assert_eq!(initializer.vertex_num as usize, VERTEX_NUM);
+        let weighted_edges: Vec<(usize, usize, Weight)> = initializer
+            .weighted_edges
+            .iter()
+            .map(|&(i, j, weight)| (i as usize, j as usize, weight as Weight))
+            .collect();
+        let virtual_vertices: Vec<usize> = initializer.virtual_vertices.iter().map(|&vertex| vertex as usize).collect();
+        let mut distances = vec![[0; VERTEX_NUM]; VERTEX_NUM];
+        let distances: &mut [[Weight; VERTEX_NUM]; VERTEX_NUM] = distances.as_mut_slice().try_into().unwrap();
+        let mut embedded_solver = EmbeddedSolver::<VERTEX_NUM, NODE_NUM>::new(distances, &weighted_edges, &virtual_vertices);
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut solved_count = 0;
+        for seed in 0..200 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            if 2 * syndrome_pattern.defect_vertices.len() > NODE_NUM {
+                continue;
+            }
+            serial_solver.solve(&syndrome_pattern);
+            let expected_weight = serial_solver.sum_dual_variables();
+            serial_solver.clear();
+            let defect_vertices: Vec<usize> = syndrome_pattern.defect_vertices.iter().map(|&v| v as usize).collect();
+            embedded_solver.solve(&defect_vertices);
+            solved_count += 1;
+            assert_eq!(embedded_solver.sum_weight() as isize, expected_weight as isize, "seed {seed}");
+            for (defect_index, &defect_vertex) in defect_vertices.iter().enumerate() {
+                match embedded_solver.get_match(defect_index).unwrap() {
+                    EmbeddedMatch::Peer(peer_vertex) => {
+                        let peer_index = defect_vertices.iter().position(|&vertex| vertex == peer_vertex).unwrap();
+                        assert_eq!(
+                            embedded_solver.get_match(peer_index),
+                            Some(EmbeddedMatch::Peer(defect_vertex))
+                        );
+                    }
+                    EmbeddedMatch::VirtualVertex(virtual_vertex) => assert!(virtual_vertices.contains(&virtual_vertex)),
+                }
+            }
+        }
+        println!("solved {solved_count} shots within the capacity");
+        assert!(solved_count >= 100);
+    }
+
+    #[test]
+    fn embedded_solver_basic() {
+        // cargo test embedded_solver_basic -- --nocapture
+        embedded_solver_compare_serial::<30, 24>(CodeCapacityPlanarCode::new(5, 0.1, 500));
+        embedded_solver_compare_serial::<56, 32>(CodeCapacityPlanarCode::new(7, 0.15, 500));
+        embedded_solver_compare_serial::<36, 32>(PhenomenologicalPlanarCode::new(3, 2, 0.1, 500));
+        embedded_solver_compare_serial::<36, 32>(CircuitLevelPlanarCode::new(3, 2, 0.02, 500));
+    }
+}
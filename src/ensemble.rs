@@ -0,0 +1,210 @@
+//! Ensemble
+//!
+//! Decode the same syndrome pattern with several reweightings of one decoding graph in parallel and keep the correction
+//! with the lowest weight in the original graph. A minimum-weight solution of a perturbed graph is a different guess of
+//! the most likely error, so the ensemble recovers some of the accuracy lost by the minimum-weight approximation of the
+//! maximum-likelihood decoder on hard shots, at the cost of one solver per member.
+//!
+//! The members only store the edges whose weights they change, which are loaded as dynamic weights of each syndrome
+//! pattern; the decoding graph itself is shared.
+//!
+
+use super::mwpm_solver::*;
+use super::util::*;
+use crate::rand_xoshiro::rand_core::SeedableRng;
+use crate::rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// a reweighting of the decoding graph
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnsembleMember {
+    /// (edge index, weight) of the edges whose weights are changed
+    pub reweighting: Vec<(EdgeIndex, Weight)>,
+}
+
+impl EnsembleMember {
+    /// the original decoding graph
+    pub fn original() -> Self {
+        Self::default()
+    }
+
+    pub fn reweighted(reweighting: Vec<(EdgeIndex, Weight)>) -> Self {
+        Self { reweighting }
+    }
+
+    /// scale every weight by an independent random factor in `[1 - strength, 1 + strength]`, rounded to an even number
+    #[allow(clippy::unnecessary_cast)]
+    pub fn perturbed(initializer: &SolverInitializer, strength: f64, seed: u64) -> Self {
+        assert!((0. ..=1.).contains(&strength), "the strength must be in [0, 1]");
+        let mut rng = DeterministicRng::seed_from_u64(seed);
+        let reweighting = (initializer.weighted_edges.iter().enumerate())
+            .map(|(edge_index, &(_, _, weight))| {
+                let factor = 1. + strength * (2. * rng.next_f64() - 1.);
+                let half_weight = (weight as f64 * factor / 2.).round() as Weight;
+                (edge_index as EdgeIndex, 2 * half_weight)
+            })
+            .collect();
+        Self { reweighting }
+    }
+
+    /// the syndrome pattern given to the solver of this member: the dynamic weights and erasures of the syndrome
+    /// pattern take precedence over the reweighting, and the erasures become zero dynamic weights because they cannot
+    /// be loaded together with dynamic weights
+    pub fn syndrome_pattern<'a>(&self, syndrome_pattern: &'a SyndromePattern) -> Cow<'a, SyndromePattern> {
+        if self.reweighting.is_empty() {
+            return Cow::Borrowed(syndrome_pattern);
+        }
+        let mut dynamic_weights: BTreeMap<EdgeIndex, Weight> = self.reweighting.iter().cloned().collect();
+        dynamic_weights.extend(syndrome_pattern.dynamic_weights.iter().cloned());
+        dynamic_weights.extend(syndrome_pattern.erasures.iter().map(|&edge_index| (edge_index, 0)));
+        Cow::Owned(SyndromePattern::new_dynamic_weights(
+            syndrome_pattern.defect_vertices.clone(),
+            vec![],
+            dynamic_weights.into_iter().collect(),
+        ))
+    }
+}
+
+/// the result of [`Ensemble::decode`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnsembleResult {
+    /// the correction with the lowest weight
+    pub subgraph: Vec<EdgeIndex>,
+    /// the weight of the correction in the original decoding graph, including the erasures and dynamic weights of the
+    /// syndrome pattern
+    pub weight: Weight,
+    /// the member that found the correction; ties are broken by the lowest index
+    pub member: usize,
+    /// the weight of the correction of each member, in the same graph as `weight`
+    pub member_weights: Vec<Weight>,
+}
+
+/// a solver for each member of the ensemble, see module documentation
+pub struct Ensemble {
+    pub initializer: Arc<SolverInitializer>,
+    pub members: Vec<EnsembleMember>,
+    /// how many times each member has found the selected correction
+    pub selection_counts: Vec<usize>,
+    solvers: Vec<SolverSerial>,
+}
+
+impl Ensemble {
+    /// the solvers are constructed in parallel, one for each member
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: Arc<SolverInitializer>, members: Vec<EnsembleMember>) -> Self {
+        assert!(!members.is_empty(), "at least one member is required");
+        for member in members.iter() {
+            if let Some(&(edge_index, weight)) = (member.reweighting.iter())
+                .find(|&&(edge_index, weight)| edge_index as usize >= initializer.weighted_edges.len() || weight < 0)
+            {
+                panic!("invalid reweighting of edge {edge_index} to {weight}");
+            }
+        }
+        let solvers = (0..members.len())
+            .into_par_iter()
+            .map(|_| SolverSerial::new(&initializer))
+            .collect();
+        Self {
+            selection_counts: vec![0; members.len()],
+            initializer,
+            members,
+            solvers,
+        }
+    }
+
+    /// the original member followed by `perturbed_num` members perturbed with the given strength
+    pub fn new_perturbed(initializer: Arc<SolverInitializer>, perturbed_num: usize, strength: f64, seed: u64) -> Self {
+        let members = std::iter::once(EnsembleMember::original())
+            .chain((0..perturbed_num as u64).map(|index| EnsembleMember::perturbed(&initializer, strength, seed + index)))
+            .collect();
+        Self::new(initializer, members)
+    }
+
+    /// the weight of a subgraph in the decoding graph modified by the erasures and dynamic weights of the syndrome
+    #[allow(clippy::unnecessary_cast)]
+    pub fn subgraph_weight(&self, syndrome_pattern: &SyndromePattern, subgraph: &[EdgeIndex]) -> Weight {
+        let mut modified: BTreeMap<EdgeIndex, Weight> = syndrome_pattern.dynamic_weights.iter().cloned().collect();
+        modified.extend(syndrome_pattern.erasures.iter().map(|&edge_index| (edge_index, 0)));
+        (subgraph.iter())
+            .map(|edge_index| {
+                (modified.get(edge_index).cloned()).unwrap_or(self.initializer.weighted_edges[*edge_index as usize].2)
+            })
+            .sum()
+    }
+
+    /// decode the syndrome pattern with every member in parallel and select the lowest-weight correction
+    pub fn decode(&mut self, syndrome_pattern: &SyndromePattern) -> EnsembleResult {
+        let subgraphs: Vec<Vec<EdgeIndex>> = (self.solvers.par_iter_mut())
+            .zip(self.members.par_iter())
+            .map(|(solver, member)| {
+                solver.solve(&member.syndrome_pattern(syndrome_pattern));
+                let subgraph = solver.subgraph();
+                solver.clear();
+                subgraph
+            })
+            .collect();
+        let member_weights: Vec<Weight> = (subgraphs.iter())
+            .map(|subgraph| self.subgraph_weight(syndrome_pattern, subgraph))
+            .collect();
+        let member = (0..member_weights.len()).min_by_key(|&index| member_weights[index]).unwrap();
+        self.selection_counts[member] += 1;
+        EnsembleResult {
+            subgraph: subgraphs.into_iter().nth(member).unwrap(),
+            weight: member_weights[member],
+            member,
+            member_weights,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::*;
+
+    #[test]
+    fn ensemble_lowest_weight() {
+        // cargo test ensemble_lowest_weight -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(5, 5, 0.05, 500);
+        let initializer = Arc::new(code.get_initializer());
+        let mut ensemble = Ensemble::new_perturbed(initializer.clone(), 3, 0.3, 0);
+        assert_eq!(ensemble.members.len(), 4);
+        assert!(ensemble.members[1..]
+            .iter()
+            .all(|member| member.reweighting.len() == initializer.weighted_edges.len()));
+        assert_ne!(ensemble.members[1], ensemble.members[2]);
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let result = ensemble.decode(&syndrome_pattern);
+            // the original member is a minimum-weight solution, so no other member can be lower
+            serial_solver.solve(&syndrome_pattern);
+            assert_eq!(result.weight, serial_solver.sum_dual_variables(), "seed {seed}");
+            assert_eq!(result.member_weights[0], result.weight, "seed {seed}");
+            assert!(result.member_weights.iter().all(|&weight| weight >= result.weight));
+            assert_eq!(result.weight, ensemble.subgraph_weight(&syndrome_pattern, &result.subgraph));
+            let defect_vertices: std::collections::BTreeSet<VertexIndex> =
+                syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(initializer.syndrome_of(&result.subgraph), defect_vertices);
+            serial_solver.clear();
+        }
+        assert_eq!(ensemble.selection_counts.iter().sum::<usize>(), 30);
+        // without the original member, the ensemble picks the best of the perturbed corrections
+        let members = ensemble.members[1..].to_vec();
+        let mut ensemble = Ensemble::new(initializer.clone(), members);
+        let syndrome_pattern = code.generate_random_errors(1);
+        let erased_syndrome_pattern = SyndromePattern::new(syndrome_pattern.defect_vertices.clone(), vec![0, 1]);
+        assert!(ensemble.members[0]
+            .syndrome_pattern(&erased_syndrome_pattern)
+            .erasures
+            .is_empty());
+        let result = ensemble.decode(&erased_syndrome_pattern);
+        assert_eq!(
+            result.weight,
+            *result.member_weights.iter().min().unwrap(),
+            "the selected correction must be the lowest"
+        );
+    }
+}
@@ -17,7 +17,7 @@ use crate::rayon::prelude::*;
 use crate::serde_json;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::{self, BufRead};
 
@@ -99,6 +99,12 @@ pub fn weight_of_p(p: f64) -> f64 {
     ((1. - p) / p).ln()
 }
 
+/// per-edge error probabilities, e.g. from device calibration data; in JSON it's an object keyed by edge index
+pub type ErrorRateMap = BTreeMap<EdgeIndex, f64>;
+
+/// error probabilities are clamped to `[MIN_ERROR_RATE, 0.5]` so that the weights stay finite and non-negative
+pub const MIN_ERROR_RATE: f64 = 1e-9;
+
 pub trait ExampleCode {
     /// get mutable references to vertices and edges
     fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>);
@@ -182,6 +188,43 @@ pub trait ExampleCode {
         }
     }
 
+    /// set independent erasure probabilities of individual edges, e.g. measured per-qubit loss rates
+    fn set_erasure_probabilities(&mut self, pes: &[f64]) {
+        let (_vertices, edges) = self.vertices_edges();
+        assert_eq!(pes.len(), edges.len(), "must provide one erasure probability per edge");
+        for (edge, &pe) in edges.iter_mut().zip(pes.iter()) {
+            assert!((0. ..=1.).contains(&pe), "erasure probability must be between 0 and 1");
+            edge.pe = pe;
+        }
+    }
+
+    /// set the error probabilities of the edges in the map, clamped to `[MIN_ERROR_RATE, 0.5]`, and recompute all the
+    /// weights as ln((1-p)/p); edges not in the map keep their probabilities
+    #[allow(clippy::unnecessary_cast)]
+    fn with_error_rate_map(&mut self, error_rate_map: &ErrorRateMap, max_half_weight: Weight) -> Result<(), String> {
+        let (_vertices, edges) = self.vertices_edges();
+        for (&edge_index, &p) in error_rate_map.iter() {
+            if edge_index as usize >= edges.len() {
+                return Err(format!(
+                    "edge {} out of range, there are only {} edges",
+                    edge_index,
+                    edges.len()
+                ));
+            }
+            if !(0. ..=1.).contains(&p) {
+                return Err(format!(
+                    "error probability {} of edge {} must be between 0 and 1",
+                    p, edge_index
+                ));
+            }
+        }
+        for (&edge_index, &p) in error_rate_map.iter() {
+            edges[edge_index as usize].p = p.clamp(MIN_ERROR_RATE, 0.5);
+        }
+        self.compute_weights(max_half_weight);
+        Ok(())
+    }
+
     /// automatically create vertices given edges
     #[allow(clippy::unnecessary_cast)]
     fn fill_vertices(&mut self, vertex_num: VertexNum) {
@@ -232,6 +275,32 @@ pub trait ExampleCode {
             vertex_num,
             weighted_edges,
             virtual_vertices,
+            capacity_hint: Some(self.capacity_hint()),
+            virtual_vertex_groups: vec![],
+            vertex_annotations: BTreeMap::new(),
+            edge_annotations: BTreeMap::new(),
+            edge_fault_ids: BTreeMap::new(),
+        }
+    }
+
+    /// estimate the size of a decoding problem from the error rates: the number of errors is bounded by its mean plus
+    /// 4 standard deviations, each error creates at most 2 defects, and each defect covers its neighborhood
+    #[allow(clippy::unnecessary_cast)]
+    fn capacity_hint(&self) -> CapacityHint {
+        let (vertices, edges) = self.immutable_vertices_edges();
+        let mean: f64 = edges.iter().map(|edge| edge.p).sum();
+        let variance: f64 = edges.iter().map(|edge| edge.p * (1. - edge.p)).sum();
+        let max_error_num = (mean + 4. * variance.sqrt()).ceil() as usize + 1;
+        let real_vertex_num = vertices.iter().filter(|vertex| !vertex.is_virtual).count();
+        let max_defect_num = std::cmp::min(2 * max_error_num, real_vertex_num);
+        let average_degree = if vertices.is_empty() {
+            0
+        } else {
+            (2 * edges.len()).div_ceil(vertices.len())
+        };
+        CapacityHint {
+            max_defect_num,
+            active_vertex_num: std::cmp::min(max_defect_num * (1 + average_degree), vertices.len()),
         }
     }
 
@@ -417,6 +486,18 @@ macro_rules! bind_trait_example_code {
             fn trait_set_erasure_probability(&mut self, p: f64) {
                 self.set_erasure_probability(p)
             }
+            #[pyo3(name = "set_erasure_probabilities")]
+            fn trait_set_erasure_probabilities(&mut self, pes: Vec<f64>) {
+                self.set_erasure_probabilities(&pes)
+            }
+            #[pyo3(name = "with_error_rate_map")]
+            fn trait_with_error_rate_map(
+                &mut self,
+                error_rate_map: ErrorRateMap,
+                max_half_weight: Weight,
+            ) -> Option<String> {
+                self.with_error_rate_map(&error_rate_map, max_half_weight).err()
+            }
             #[pyo3(name = "fill_vertices")]
             fn trait_fill_vertices(&mut self, vertex_num: VertexNum) {
                 self.fill_vertices(vertex_num)
@@ -582,6 +663,90 @@ impl CodeCapacityRepetitionCode {
     }
 }
 
+/// quantum repetition code with multiple rounds of noisy measurement, forming a 2D strip of the code along the time axis
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct PhenomenologicalRepetitionCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for PhenomenologicalRepetitionCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {PhenomenologicalRepetitionCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl PhenomenologicalRepetitionCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, noisy_measurements, p, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, noisy_measurements: VertexNum, p: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d, noisy_measurements);
+        code.set_probability(p);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn create_code(d: VertexNum, noisy_measurements: VertexNum) -> Self {
+        assert!(d >= 3 && d % 2 == 1, "d must be odd integer >= 3");
+        let round_vertex_num = (d - 1) + 2; // two virtual vertices at left and right
+        let td = noisy_measurements + 1; // a perfect measurement round is capped at the end
+        let vertex_num = round_vertex_num * td; // `td` rounds
+                                                // create edges
+        let mut edges = Vec::new();
+        for t in 0..td {
+            let bias = t * round_vertex_num;
+            for i in 0..d - 1 {
+                edges.push(CodeEdge::new(bias + i, bias + i + 1));
+            }
+            edges.push(CodeEdge::new(bias, bias + d)); // the left-most edge
+            if t + 1 < td {
+                for i in 0..d - 1 {
+                    edges.push(CodeEdge::new(bias + i, bias + i + round_vertex_num));
+                }
+            }
+        }
+        let mut code = Self {
+            vertices: Vec::new(),
+            edges,
+        };
+        // create vertices
+        code.fill_vertices(vertex_num);
+        for t in 0..td {
+            let bias = t * round_vertex_num;
+            code.vertices[(bias + d - 1) as usize].is_virtual = true;
+            code.vertices[(bias + d) as usize].is_virtual = true;
+        }
+        let mut positions = Vec::new();
+        for t in 0..td {
+            let pos_t = t as f64;
+            for i in 0..d {
+                positions.push(VisualizePosition::new(0., i as f64, pos_t));
+            }
+            positions.push(VisualizePosition::new(0., -1., pos_t));
+        }
+        for (i, position) in positions.into_iter().enumerate() {
+            code.vertices[i].position = position;
+        }
+        code
+    }
+}
+
 /// code capacity noise model is a single measurement round with perfect stabilizer measurements;
 /// e.g. this is the decoding graph of a CSS surface code (standard one, not rotated one) with X-type stabilizers
 #[derive(Clone, Debug)]
@@ -1117,6 +1282,255 @@ impl PhenomenologicalRotatedCode {
     }
 }
 
+/// heavy-hexagon lattice as in IBM devices: a hexagonal (brick-wall) lattice of degree-3 vertices where every edge is
+/// split by a degree-2 vertex, e.g. a flag qubit between two ancilla qubits; `d` rows, each with `d-1` degree-3
+/// vertices and a virtual vertex at the left and right boundary
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct CodeCapacityHeavyHexCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for CodeCapacityHeavyHexCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {CodeCapacityHeavyHexCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl CodeCapacityHeavyHexCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, p, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, p: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d);
+        code.set_probability(p);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn create_code(d: VertexNum) -> Self {
+        assert!(d >= 3 && d % 2 == 1, "d must be odd integer >= 3");
+        let row_vertex_num = (d - 1) + 2; // two virtual vertices at left and right
+        let mut positions = Vec::new();
+        for row in 0..d {
+            for i in 0..row_vertex_num {
+                positions.push(VisualizePosition::new(2. * row as f64, 2. * i as f64, 0.));
+            }
+        }
+        let mut edges = Vec::new();
+        // split the edge between two degree-3 vertices by adding a degree-2 vertex in the middle
+        let mut add_heavy_edge = |a: VertexIndex, b: VertexIndex, positions: &mut Vec<VisualizePosition>| {
+            let (position_a, position_b) = (&positions[a as usize], &positions[b as usize]);
+            let middle = VisualizePosition::new((position_a.i + position_b.i) / 2., (position_a.j + position_b.j) / 2., 0.);
+            let middle_vertex = positions.len() as VertexIndex;
+            positions.push(middle);
+            edges.push(CodeEdge::new(a, middle_vertex));
+            edges.push(CodeEdge::new(middle_vertex, b));
+        };
+        for row in 0..d {
+            let bias = row * row_vertex_num;
+            for i in 1..d - 1 {
+                add_heavy_edge(bias + i, bias + i + 1, &mut positions);
+            }
+            // brick-wall vertical edges that alternate between the rows, forming hexagons
+            if row + 1 < d {
+                for i in 1..d {
+                    if (row + i) % 2 == 0 {
+                        add_heavy_edge(bias + i, bias + row_vertex_num + i, &mut positions);
+                    }
+                }
+            }
+        }
+        for row in 0..d {
+            let bias = row * row_vertex_num;
+            edges.push(CodeEdge::new(bias, bias + 1)); // left boundary
+            edges.push(CodeEdge::new(bias + d - 1, bias + d)); // right boundary
+        }
+        let mut code = Self {
+            vertices: Vec::new(),
+            edges,
+        };
+        // create vertices
+        code.fill_vertices(positions.len() as VertexNum);
+        for row in 0..d {
+            let bias = row * row_vertex_num;
+            code.vertices[bias as usize].is_virtual = true;
+            code.vertices[(bias + d) as usize].is_virtual = true;
+        }
+        for (i, position) in positions.into_iter().enumerate() {
+            code.vertices[i].position = position;
+        }
+        code
+    }
+}
+
+/// the plaquettes of a honeycomb (Floquet) code: each single-qubit Pauli error flips two of the three hexagonal
+/// plaquettes around the qubit, so the decoding graph is a triangular lattice of degree-6 vertices; `d` rows, each with
+/// `d-1` plaquettes and a virtual vertex at the left and right boundary
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct CodeCapacityHoneycombCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for CodeCapacityHoneycombCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {CodeCapacityHoneycombCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl CodeCapacityHoneycombCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, p, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, p: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d);
+        code.set_probability(p);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn create_code(d: VertexNum) -> Self {
+        assert!(d >= 3 && d % 2 == 1, "d must be odd integer >= 3");
+        let row_vertex_num = (d - 1) + 2; // two virtual vertices at left and right
+        let vertex_num = row_vertex_num * d; // `d` rows
+                                             // create edges
+        let mut edges = Vec::new();
+        for row in 0..d {
+            let bias = row * row_vertex_num;
+            for i in 0..d {
+                edges.push(CodeEdge::new(bias + i, bias + i + 1));
+            }
+            if row + 1 < d {
+                for i in 1..d {
+                    edges.push(CodeEdge::new(bias + i, bias + row_vertex_num + i));
+                }
+                for i in 0..d {
+                    edges.push(CodeEdge::new(bias + i, bias + row_vertex_num + i + 1));
+                }
+            }
+        }
+        let mut code = Self {
+            vertices: Vec::new(),
+            edges,
+        };
+        // create vertices
+        code.fill_vertices(vertex_num);
+        for row in 0..d {
+            let bias = row * row_vertex_num;
+            code.vertices[bias as usize].is_virtual = true;
+            code.vertices[(bias + d) as usize].is_virtual = true;
+        }
+        let mut positions = Vec::new();
+        for row in 0..d {
+            let pos_i = row as f64 * 3f64.sqrt() / 2.;
+            for i in 0..row_vertex_num {
+                positions.push(VisualizePosition::new(pos_i, i as f64 - row as f64 / 2., 0.));
+            }
+        }
+        for (i, position) in positions.into_iter().enumerate() {
+            code.vertices[i].position = position;
+        }
+        code
+    }
+}
+
+/// XZZX surface code under biased Pauli noise with bias `eta = pz / (px + py)` and `px = py`; it has the decoding graph
+/// of [`CodeCapacityPlanarCode`], but Z and Y errors flip the horizontal edges while X and Y errors flip the vertical
+/// edges, so the two directions have very different weights under a strong bias; `eta = 0.5` is depolarizing noise
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct CodeCapacityXZZXCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for CodeCapacityXZZXCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {CodeCapacityXZZXCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl CodeCapacityXZZXCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, p, bias_eta = 0.5, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, p: f64, bias_eta: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d);
+        code.set_bias(p, bias_eta);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    pub fn create_code(d: VertexNum) -> Self {
+        let CodeCapacityPlanarCode { vertices, edges } = CodeCapacityPlanarCode::create_code(d);
+        Self { vertices, edges }
+    }
+
+    /// set the probabilities of the horizontal and vertical edges given the total error rate `p` and the bias;
+    /// an infinite bias leaves the vertical edges with [`MIN_ERROR_RATE`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn set_bias(&mut self, p: f64, bias_eta: f64) {
+        assert!(bias_eta > 0., "bias must be positive");
+        let px = if bias_eta.is_infinite() {
+            0.
+        } else {
+            p / (1. + bias_eta) / 2.
+        };
+        let py = px;
+        let pz = p - 2. * px;
+        let (vertices, edges) = self.vertices_edges();
+        for edge in edges.iter_mut() {
+            let (vertex_1, vertex_2) = edge.vertices;
+            let is_horizontal = vertices[vertex_1 as usize].position.i == vertices[vertex_2 as usize].position.i;
+            edge.p = if is_horizontal { pz + py } else { px + py }.max(MIN_ERROR_RATE);
+        }
+    }
+}
+
 /// example code with QEC-Playground as simulator
 #[cfg(feature = "qecp_integrate")]
 #[cfg_attr(feature = "python_binding", cfg_eval)]
@@ -1484,6 +1898,181 @@ impl ErrorPatternReader {
     }
 }
 
+/// declarative description of a vertex in [`CodeDescription`]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CodeVertexDescription {
+    pub position: VisualizePosition,
+    #[serde(default)]
+    pub is_virtual: bool,
+}
+
+/// declarative description of an edge in [`CodeDescription`]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CodeEdgeDescription {
+    pub vertices: (VertexIndex, VertexIndex),
+    /// error probability, used to generate random errors and to compute the weight if `half_weight` is not given
+    #[serde(default)]
+    pub p: f64,
+    /// erasure probability
+    #[serde(default)]
+    pub pe: f64,
+    /// the integer weight; either all edges or none of the edges should provide it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub half_weight: Option<Weight>,
+}
+
+/// declarative description of a decoding graph, so that new layouts can be tested without writing Rust
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CodeDescription {
+    pub vertices: Vec<CodeVertexDescription>,
+    pub edges: Vec<CodeEdgeDescription>,
+    /// each logical operator is a set of edges; an edge set flips the logical observable if it overlaps with odd number of them
+    #[serde(default)]
+    pub logical_operators: Vec<Vec<EdgeIndex>>,
+}
+
+impl CodeDescription {
+    /// describe an existing code, e.g. to export it as a starting point of a new layout
+    pub fn from_code(code: &impl ExampleCode) -> Self {
+        let (vertices, edges) = code.immutable_vertices_edges();
+        Self {
+            vertices: vertices
+                .iter()
+                .map(|vertex| CodeVertexDescription {
+                    position: vertex.position.clone(),
+                    is_virtual: vertex.is_virtual,
+                })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|edge| CodeEdgeDescription {
+                    vertices: edge.vertices,
+                    p: edge.p,
+                    pe: edge.pe,
+                    half_weight: Some(edge.half_weight),
+                })
+                .collect(),
+            logical_operators: vec![],
+        }
+    }
+}
+
+/// code layout loaded from a JSON file of [`CodeDescription`]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct CodeFromFile {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+    /// logical operators, each is a set of edges
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub logical_operators: Vec<Vec<EdgeIndex>>,
+}
+
+impl ExampleCode for CodeFromFile {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl CodeFromFile {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (filename, max_half_weight = 500)))]
+    pub fn py_new(filename: String, max_half_weight: Weight) -> Self {
+        Self::new(json!({ "filename": filename }), max_half_weight)
+    }
+
+    /// whether each logical observable is flipped by the given set of edges, e.g. the error pattern or the correction
+    #[allow(clippy::unnecessary_cast)]
+    pub fn logical_observables(&self, edges: Vec<EdgeIndex>) -> Vec<bool> {
+        let mut is_flipped = vec![false; self.edges.len()];
+        for edge_index in edges {
+            is_flipped[edge_index as usize] = !is_flipped[edge_index as usize];
+        }
+        self.logical_operators
+            .iter()
+            .map(|operator| operator.iter().filter(|&&edge_index| is_flipped[edge_index as usize]).count() % 2 == 1)
+            .collect()
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {CodeFromFile}
+
+impl CodeFromFile {
+    /// the config is `{"filename": "path/to/code.json"}`; `max_half_weight` is only used when the file doesn't provide weights
+    pub fn new(mut config: serde_json::Value, max_half_weight: Weight) -> Self {
+        let config = config.as_object_mut().expect("config must be JSON object");
+        let filename = config
+            .remove("filename")
+            .expect("filename must be provided")
+            .as_str()
+            .expect("filename string")
+            .to_string();
+        if !config.is_empty() {
+            panic!("unknown config keys: {:?}", config.keys().collect::<Vec<&String>>());
+        }
+        let file = File::open(&filename).unwrap_or_else(|error| panic!("cannot open {filename}: {error}"));
+        let description: CodeDescription = serde_json::from_reader(io::BufReader::new(file))
+            .unwrap_or_else(|error| panic!("invalid code description in {filename}: {error}"));
+        Self::from_description(description, max_half_weight)
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn from_description(description: CodeDescription, max_half_weight: Weight) -> Self {
+        let weight_given_count = description.edges.iter().filter(|edge| edge.half_weight.is_some()).count();
+        assert!(
+            weight_given_count == 0 || weight_given_count == description.edges.len(),
+            "either all edges or none of the edges should provide `half_weight`"
+        );
+        let mut code = Self {
+            vertices: Vec::with_capacity(description.vertices.len()),
+            edges: description
+                .edges
+                .iter()
+                .map(|edge| CodeEdge {
+                    vertices: edge.vertices,
+                    p: edge.p,
+                    pe: edge.pe,
+                    half_weight: edge.half_weight.unwrap_or(0),
+                    is_erasure: false,
+                })
+                .collect(),
+            logical_operators: description.logical_operators,
+        };
+        code.fill_vertices(description.vertices.len() as VertexNum);
+        for (vertex, vertex_description) in code.vertices.iter_mut().zip(description.vertices) {
+            vertex.position = vertex_description.position;
+            vertex.is_virtual = vertex_description.is_virtual;
+        }
+        for operator in code.logical_operators.iter() {
+            for &edge_index in operator.iter() {
+                assert!(
+                    (edge_index as usize) < code.edges.len(),
+                    "invalid edge {edge_index} in logical operator"
+                );
+            }
+        }
+        if weight_given_count == 0 {
+            code.compute_weights(max_half_weight);
+        }
+        code.sanity_check().unwrap();
+        code
+    }
+}
+
 /// generate error patterns in parallel by hold multiple instances of the same code type
 pub struct ExampleCodeParallel<CodeType: ExampleCode + Sync + Send + Clone> {
     /// used to provide graph
@@ -1536,6 +2125,93 @@ impl<CodeType: ExampleCode + Sync + Send + Clone> ExampleCode for ExampleCodePar
     }
 }
 
+/// correlated erasure bursts, e.g. a cosmic ray that erases all qubits in a disk for a period of time
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ErasureBurst {
+    /// the probability of a burst in each shot
+    pub probability: f64,
+    /// the radius of the disk in the (i, j) plane; an edge is erased if its middle point is in the disk
+    pub radius: f64,
+    /// the number of rounds a burst lasts along the time axis; a burst lasts for the whole shot if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+}
+
+impl ErasureBurst {
+    /// the edges erased by a burst centered at a vertex position
+    #[allow(clippy::unnecessary_cast)]
+    pub fn erased_edges(&self, code: &dyn ExampleCode, center: &VisualizePosition) -> Vec<EdgeIndex> {
+        let (vertices, edges) = code.immutable_vertices_edges();
+        let mut erased_edges = vec![];
+        for (edge_index, edge) in edges.iter().enumerate() {
+            let (position_1, position_2) = (
+                &vertices[edge.vertices.0 as usize].position,
+                &vertices[edge.vertices.1 as usize].position,
+            );
+            let i = (position_1.i + position_2.i) / 2. - center.i;
+            let j = (position_1.j + position_2.j) / 2. - center.j;
+            let t = (position_1.t + position_2.t) / 2. - center.t;
+            if i * i + j * j <= self.radius * self.radius
+                && !matches!(self.duration, Some(duration) if t.abs() * 2. > duration)
+            {
+                erased_edges.push(edge_index as EdgeIndex);
+            }
+        }
+        erased_edges
+    }
+}
+
+/// a code with correlated erasure bursts on top of the independent errors and erasures of each edge;
+/// when a burst happens, the erased edges are sampled as if their erasure probability were 1
+pub struct ErasureBurstCode {
+    pub code: Box<dyn ExampleCode>,
+    pub burst: ErasureBurst,
+}
+
+impl ErasureBurstCode {
+    pub fn new(code: Box<dyn ExampleCode>, burst: ErasureBurst) -> Self {
+        assert!(
+            (0. ..=1.).contains(&burst.probability),
+            "burst probability must be between 0 and 1"
+        );
+        Self { code, burst }
+    }
+}
+
+impl ExampleCode for ErasureBurstCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        self.code.vertices_edges()
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        self.code.immutable_vertices_edges()
+    }
+    /// only works with codes using the default sampler, which reads the erasure probability of each edge
+    #[allow(clippy::unnecessary_cast)]
+    fn generate_random_errors(&mut self, seed: u64) -> SyndromePattern {
+        // use a different stream than the code, so that the bursts don't correlate with the independent errors
+        let mut rng = DeterministicRng::seed_from_u64(seed ^ 0x6275727374);
+        let mut burst_edges = vec![];
+        if rng.next_f64() < self.burst.probability {
+            let vertex_num = self.code.vertex_num() as usize;
+            let center_vertex = std::cmp::min((rng.next_f64() * vertex_num as f64) as usize, vertex_num - 1);
+            let center = self.code.immutable_vertices_edges().0[center_vertex].position.clone();
+            burst_edges = self.burst.erased_edges(&*self.code, &center);
+        }
+        let (_vertices, edges) = self.code.vertices_edges();
+        let original_pes: Vec<f64> = burst_edges.iter().map(|&edge_index| edges[edge_index as usize].pe).collect();
+        for &edge_index in burst_edges.iter() {
+            edges[edge_index as usize].pe = 1.;
+        }
+        let syndrome_pattern = self.code.generate_random_errors(seed);
+        let (_vertices, edges) = self.code.vertices_edges();
+        for (&edge_index, &pe) in burst_edges.iter().zip(original_pes.iter()) {
+            edges[edge_index as usize].pe = pe;
+        }
+        syndrome_pattern
+    }
+}
+
 #[cfg(feature = "python_binding")]
 #[pyfunction]
 pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
@@ -1543,12 +2219,17 @@ pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<CodeEdge>()?;
     m.add_function(wrap_pyfunction!(weight_of_p, m)?)?;
     m.add_class::<CodeCapacityRepetitionCode>()?;
+    m.add_class::<PhenomenologicalRepetitionCode>()?;
     m.add_class::<CodeCapacityPlanarCode>()?;
     m.add_class::<PhenomenologicalPlanarCode>()?;
     m.add_class::<CircuitLevelPlanarCode>()?;
     m.add_class::<CodeCapacityRotatedCode>()?;
     m.add_class::<PhenomenologicalRotatedCode>()?;
+    m.add_class::<CodeCapacityHeavyHexCode>()?;
+    m.add_class::<CodeCapacityHoneycombCode>()?;
+    m.add_class::<CodeCapacityXZZXCode>()?;
     m.add_class::<ErrorPatternReader>()?;
+    m.add_class::<CodeFromFile>()?;
     Ok(())
 }
 
@@ -1571,6 +2252,43 @@ pub fn visualize_code(code: &mut impl ExampleCode, visualize_filename: String) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn example_code_from_file() {
+        // cargo test example_code_from_file -- --nocapture
+        let d = 5;
+        let mut code = CodeCapacityRepetitionCode::new(d, 0.1, 500);
+        let mut description = CodeDescription::from_code(&code);
+        // the logical operator of a repetition code is any single data qubit, here choose the left-most edge
+        description.logical_operators = vec![vec![0]];
+        let filename = format!("{}example_code_from_file.json", visualize_data_folder());
+        std::fs::write(&filename, serde_json::to_string(&description).unwrap()).unwrap();
+        let mut loaded_code = CodeFromFile::new(json!({ "filename": filename }), 500);
+        assert_eq!(
+            serde_json::to_value(loaded_code.get_initializer()).unwrap(),
+            serde_json::to_value(code.get_initializer()).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(loaded_code.get_positions()).unwrap(),
+            serde_json::to_value(code.get_positions()).unwrap()
+        );
+        assert_eq!(
+            loaded_code.generate_random_errors(123).defect_vertices,
+            code.generate_random_errors(123).defect_vertices
+        );
+        assert_eq!(loaded_code.logical_observables(vec![0, 1]), vec![true]);
+        assert_eq!(loaded_code.logical_observables(vec![1, 2]), vec![false]);
+        // weights can also be computed from probabilities
+        for edge in description.edges.iter_mut() {
+            edge.half_weight = None;
+        }
+        let computed_code = CodeFromFile::from_description(description, 500);
+        assert_eq!(
+            serde_json::to_value(computed_code.get_initializer()).unwrap(),
+            serde_json::to_value(code.get_initializer()).unwrap()
+        );
+        visualize_code(&mut loaded_code, "example_code_from_file_visualize.json".to_string());
+    }
+
     #[test]
     fn example_code_capacity_repetition_code() {
         // cargo test example_code_capacity_repetition_code -- --nocapture
@@ -1579,6 +2297,14 @@ mod tests {
         visualize_code(&mut code, "example_code_capacity_repetition_code.json".to_string());
     }
 
+    #[test]
+    fn example_phenomenological_repetition_code() {
+        // cargo test example_phenomenological_repetition_code -- --nocapture
+        let mut code = PhenomenologicalRepetitionCode::new(7, 7, 0.01, 500);
+        code.sanity_check().unwrap();
+        visualize_code(&mut code, "example_phenomenological_repetition_code.json".to_string());
+    }
+
     #[test]
     fn example_code_capacity_planar_code() {
         // cargo test example_code_capacity_planar_code -- --nocapture
@@ -1627,6 +2353,81 @@ mod tests {
         visualize_code(&mut code, "example_code_phenomenological_rotated_code.json".to_string());
     }
 
+    #[test]
+    fn example_code_capacity_heavy_hex_code() {
+        // cargo test example_code_capacity_heavy_hex_code -- --nocapture
+        let mut code = CodeCapacityHeavyHexCode::new(5, 0.1, 500);
+        code.sanity_check().unwrap();
+        let degrees: Vec<usize> = (code.vertices.iter().filter(|vertex| !vertex.is_virtual))
+            .map(|vertex| vertex.neighbor_edges.len())
+            .collect();
+        assert!(degrees.iter().all(|&degree| degree == 2 || degree == 3));
+        assert!(degrees.contains(&3));
+        // every edge between two degree-3 vertices is split into two edges
+        let initializer = code.get_initializer();
+        let edge_weight = initializer.weighted_edges[0].2;
+        assert_eq!(initializer.boundary_distances().distances[2], 3 * edge_weight);
+        visualize_code(&mut code, "example_code_capacity_heavy_hex_code.json".to_string());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_code_capacity_honeycomb_code() {
+        // cargo test example_code_capacity_honeycomb_code -- --nocapture
+        let mut code = CodeCapacityHoneycombCode::new(5, 0.1, 500);
+        code.sanity_check().unwrap();
+        let max_degree = code.vertices.iter().map(|vertex| vertex.neighbor_edges.len()).max().unwrap();
+        assert_eq!(max_degree, 6);
+        for seed in 0..10 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            assert!(syndrome_pattern
+                .defect_vertices
+                .iter()
+                .all(|&vertex| !code.is_virtual(vertex as usize)));
+        }
+        visualize_code(&mut code, "example_code_capacity_honeycomb_code.json".to_string());
+    }
+
+    #[test]
+    fn example_code_capacity_xzzx_code() {
+        // cargo test example_code_capacity_xzzx_code -- --nocapture
+        use crate::mwpm_solver::*;
+        use std::collections::BTreeSet;
+        let d = 7;
+        let depolarizing = CodeCapacityXZZXCode::new(d, 0.1, 0.5, 500);
+        let half_weights: BTreeSet<Weight> = depolarizing.edges.iter().map(|edge| edge.half_weight).collect();
+        assert_eq!(half_weights.len(), 1, "depolarizing noise is isotropic");
+        let mut code = CodeCapacityXZZXCode::new(d, 0.1, 100., 500);
+        code.sanity_check().unwrap();
+        let half_weights: BTreeSet<Weight> = code.edges.iter().map(|edge| edge.half_weight).collect();
+        assert_eq!(half_weights.len(), 2);
+        assert!(half_weights.last().unwrap() > &(3 * half_weights.first().unwrap()));
+        // the solvers agree on the strongly non-uniform weights
+        let initializer = code.get_initializer();
+        let partition_info = PartitionConfig::new(initializer.vertex_num).info();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut parallel_solver = SolverDualParallel::new(&initializer, &partition_info, json!({}));
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            parallel_solver.solve(&syndrome_pattern);
+            let subgraph = serial_solver.subgraph();
+            let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(initializer.syndrome_of(&subgraph), defect_vertices, "seed {seed}");
+            assert_eq!(
+                serial_solver.sum_dual_variables(),
+                parallel_solver.sum_dual_variables(),
+                "seed {seed}"
+            );
+            serial_solver.clear();
+            parallel_solver.clear();
+        }
+        // infinite bias still has finite weights
+        let infinite_bias = CodeCapacityXZZXCode::new(d, 0.1, f64::INFINITY, 500);
+        assert!(infinite_bias.edges.iter().all(|edge| edge.half_weight > 0));
+        visualize_code(&mut code, "example_code_capacity_xzzx_code.json".to_string());
+    }
+
     #[cfg(feature = "qecp_integrate")]
     #[test]
     fn example_qec_playground_code() {
@@ -1639,4 +2440,112 @@ mod tests {
         code.sanity_check().unwrap();
         visualize_code(&mut code, "example_qec_playground_code.json".to_string());
     }
+
+    #[test]
+    fn example_code_capacity_hint() {
+        // cargo test example_code_capacity_hint -- --nocapture
+        use crate::mwpm_solver::*;
+        let mut code = CodeCapacityPlanarCode::new(11, 0.05, 500);
+        let initializer = code.get_initializer();
+        let capacity_hint = initializer.capacity_hint.unwrap();
+        println!("capacity_hint: {capacity_hint:?}");
+        let mut solver = SolverSerial::new(&initializer);
+        let capacities = |solver: &SolverSerial| {
+            (
+                solver.interface_ptr.read_recursive().nodes.capacity(),
+                solver.dual_module.nodes.capacity(),
+                solver.primal_module.read_recursive().nodes.capacity(),
+            )
+        };
+        let initial_capacities = capacities(&solver);
+        assert!(initial_capacities.0 >= capacity_hint.max_node_num());
+        for seed in 0..200 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            assert!(syndrome_pattern.defect_vertices.len() <= capacity_hint.max_defect_num);
+            solver.solve(&syndrome_pattern);
+            solver.clear();
+        }
+        assert_eq!(capacities(&solver), initial_capacities, "no reallocation within the hint");
+    }
+
+    #[test]
+    fn example_code_error_rate_map() {
+        // cargo test example_code_error_rate_map -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let error_rate_map: ErrorRateMap = serde_json::from_str(r#"{"0": 0.001, "1": 0, "2": 0.9}"#).unwrap();
+        code.with_error_rate_map(&error_rate_map, 500).unwrap();
+        assert_eq!(code.edges[0].p, 0.001);
+        assert_eq!(code.edges[1].p, MIN_ERROR_RATE);
+        assert_eq!(code.edges[2].p, 0.5);
+        assert_eq!(code.edges[3].p, 0.1);
+        // the most reliable edge has the maximum weight and an unreliable edge still has a positive weight
+        assert_eq!(code.edges[1].half_weight, 500);
+        assert!(code.edges[0].half_weight > code.edges[3].half_weight);
+        assert_eq!(code.edges[2].half_weight, 1);
+        let edge_num = code.edges.len() as EdgeIndex;
+        assert!(code.with_error_rate_map(&ErrorRateMap::from([(edge_num, 0.1)]), 500).is_err());
+        assert!(code.with_error_rate_map(&ErrorRateMap::from([(0, f64::NAN)]), 500).is_err());
+        assert!(code.with_error_rate_map(&ErrorRateMap::from([(0, -0.1)]), 500).is_err());
+        code.sanity_check().unwrap();
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_code_erasure_burst() {
+        // cargo test example_code_erasure_burst -- --nocapture
+        use crate::mwpm_solver::*;
+        let mut code = CodeCapacityPlanarCode::new(11, 0.01, 500);
+        let mut pes = vec![0.; code.edges.len()];
+        pes[7] = 1.;
+        code.set_erasure_probabilities(&pes);
+        assert_eq!(code.generate_random_errors(0).erasures, vec![7]);
+        code.set_erasure_probability(0.);
+        // without bursts, the wrapper samples exactly like the code
+        let mut never = ErasureBurstCode::new(
+            Box::new(code.clone()),
+            ErasureBurst {
+                probability: 0.,
+                radius: 2.,
+                duration: None,
+            },
+        );
+        for seed in 0..10 {
+            let (burst_syndrome, syndrome) = (never.generate_random_errors(seed), code.generate_random_errors(seed));
+            assert_eq!(burst_syndrome.defect_vertices, syndrome.defect_vertices);
+            assert_eq!(burst_syndrome.erasures, syndrome.erasures);
+        }
+        let radius = 2.;
+        let mut always = ErasureBurstCode::new(
+            Box::new(code.clone()),
+            ErasureBurst {
+                probability: 1.,
+                radius,
+                duration: None,
+            },
+        );
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..20 {
+            let syndrome_pattern = always.generate_random_errors(seed);
+            assert!(!syndrome_pattern.erasures.is_empty());
+            let middle = |edge_index: EdgeIndex| {
+                let (v1, v2) = code.edges[edge_index as usize].vertices;
+                let (p1, p2) = (&code.vertices[v1 as usize].position, &code.vertices[v2 as usize].position);
+                ((p1.i + p2.i) / 2., (p1.j + p2.j) / 2.)
+            };
+            for &edge_1 in syndrome_pattern.erasures.iter() {
+                for &edge_2 in syndrome_pattern.erasures.iter() {
+                    let ((i1, j1), (i2, j2)) = (middle(edge_1), middle(edge_2));
+                    assert!(
+                        (i1 - i2).powi(2) + (j1 - j2).powi(2) <= (2. * radius).powi(2),
+                        "erasures must be in a disk"
+                    );
+                }
+            }
+            // the pe of the burst edges is restored after sampling
+            assert!(always.immutable_vertices_edges().1.iter().all(|edge| edge.pe == 0.));
+            solver.solve(&syndrome_pattern);
+            solver.clear();
+        }
+    }
 }
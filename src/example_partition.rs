@@ -539,6 +539,69 @@ impl ExamplePartition for PhenomenologicalRotatedCodeTimePartition {
     }
 }
 
+/// partition a phenomenological repetition code, a 2D strip, into consecutive blocks of rounds fused sequentially
+pub struct PhenomenologicalRepetitionCodeTimePartition {
+    d: VertexNum,
+    noisy_measurements: VertexNum,
+    /// the number of partition
+    partition_num: usize,
+}
+
+impl PhenomenologicalRepetitionCodeTimePartition {
+    pub fn new(d: VertexNum, noisy_measurements: VertexNum, partition_num: usize) -> Self {
+        Self {
+            d,
+            noisy_measurements,
+            partition_num,
+        }
+    }
+
+    /// choose the partition number such that each partition has at least `d` rounds, so that most of the matchings
+    /// stay within a partition
+    #[allow(clippy::unnecessary_cast)]
+    pub fn auto(d: VertexNum, noisy_measurements: VertexNum) -> Self {
+        let partition_num = std::cmp::max(1, (noisy_measurements + 1) / d) as usize;
+        Self::new(d, noisy_measurements, partition_num)
+    }
+}
+
+impl ExamplePartition for PhenomenologicalRepetitionCodeTimePartition {
+    #[allow(clippy::unnecessary_cast)]
+    fn build_partition(&mut self, code: &dyn ExampleCode) -> PartitionConfig {
+        let (d, noisy_measurements, partition_num) = (self.d, self.noisy_measurements, self.partition_num);
+        let round_vertex_num = d + 1;
+        let vertex_num = round_vertex_num * (noisy_measurements + 1);
+        assert_eq!(code.vertex_num(), vertex_num, "code size incompatible");
+        assert!(partition_num >= 1 && partition_num <= noisy_measurements as usize + 1);
+        let mut config = PartitionConfig::new(vertex_num);
+        config.partitions.clear();
+        for partition_index in 0..partition_num as VertexIndex {
+            let start_round_index = partition_index * (noisy_measurements + 1) / partition_num as VertexNum;
+            let end_round_index = (partition_index + 1) * (noisy_measurements + 1) / partition_num as VertexNum;
+            assert!(end_round_index > start_round_index, "empty partition occurs");
+            // the first round of each following partition is the interface with the previous one
+            let start_round_index = if partition_index == 0 {
+                start_round_index
+            } else {
+                start_round_index + 1
+            };
+            config.partitions.push(VertexRange::new(
+                start_round_index * round_vertex_num,
+                end_round_index * round_vertex_num,
+            ));
+        }
+        config.fusions.clear();
+        for unit_index in partition_num..(2 * partition_num - 1) {
+            if unit_index == partition_num {
+                config.fusions.push((0, 1));
+            } else {
+                config.fusions.push((unit_index - 1, unit_index - partition_num + 1));
+            }
+        }
+        config
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::super::dual_module::*;
@@ -737,6 +800,29 @@ pub mod tests {
         );
     }
 
+    /// a minimal demo of fusion: a repetition code with noisy measurements partitioned along the time axis
+    #[test]
+    fn example_partition_repetition_code_time_partition() {
+        // cargo test example_partition_repetition_code_time_partition -- --nocapture
+        use super::super::mwpm_solver::*;
+        let (d, noisy_measurements) = (7, 20);
+        let mut code = PhenomenologicalRepetitionCode::new(d, noisy_measurements, 0.05, 500);
+        let partition = PhenomenologicalRepetitionCodeTimePartition::auto(d, noisy_measurements);
+        assert_eq!(partition.partition_num, 3);
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let syndrome_pattern = code.generate_random_errors(0);
+        serial_solver.solve(&syndrome_pattern);
+        example_partition_standard_syndrome(
+            &mut code,
+            "example_partition_repetition_code_time_partition.json".to_string(),
+            syndrome_pattern.defect_vertices,
+            true,
+            serial_solver.sum_dual_variables() / 2,
+            partition,
+        );
+    }
+
     /// a demo to show how partition works in phenomenological planar code
     #[test]
     fn example_partition_demo_1() {
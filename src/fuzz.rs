@@ -0,0 +1,441 @@
+//! Fuzz
+//!
+//! Generate random decoding graphs, syndrome patterns and partitions, and cross-check the serial and parallel solvers;
+//! a failing case is shrunk to a minimal reproducer that can be saved as a JSON file and turned into a debug test case
+//!
+
+use super::mwpm_solver::*;
+use super::primal_module::*;
+use super::util::*;
+use super::verifier::*;
+use crate::rand_xoshiro::rand_core::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// the configuration of the random case generator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FuzzConfig {
+    /// the minimum number of vertices in the decoding graph
+    #[serde(default = "fuzz_default_configs::min_vertex_num")]
+    pub min_vertex_num: VertexNum,
+    /// the maximum number of vertices in the decoding graph
+    #[serde(default = "fuzz_default_configs::max_vertex_num")]
+    pub max_vertex_num: VertexNum,
+    /// the maximum number of leaf partitions; they are fused in a chain
+    #[serde(default = "fuzz_default_configs::max_partition_num")]
+    pub max_partition_num: usize,
+    /// the number of extra edges per vertex besides the spanning tree
+    #[serde(default = "fuzz_default_configs::edge_density")]
+    pub edge_density: f64,
+    /// the probability of a vertex being virtual
+    #[serde(default = "fuzz_default_configs::virtual_probability")]
+    pub virtual_probability: f64,
+    /// the maximum half weight of an edge
+    #[serde(default = "fuzz_default_configs::max_half_weight")]
+    pub max_half_weight: Weight,
+    /// the probability of an error happening on each edge
+    #[serde(default = "fuzz_default_configs::error_probability")]
+    pub error_probability: f64,
+    /// the probability of an edge being erased
+    #[serde(default = "fuzz_default_configs::erasure_probability")]
+    pub erasure_probability: f64,
+    /// also compare with the brute force decoder if the number of defect vertices is small enough
+    #[serde(default = "fuzz_default_configs::brute_force")]
+    pub brute_force: bool,
+}
+
+pub mod fuzz_default_configs {
+    use super::*;
+    pub fn min_vertex_num() -> VertexNum {
+        4
+    }
+    pub fn max_vertex_num() -> VertexNum {
+        30
+    }
+    pub fn max_partition_num() -> usize {
+        3
+    }
+    pub fn edge_density() -> f64 {
+        1.
+    }
+    pub fn virtual_probability() -> f64 {
+        0.15
+    }
+    pub fn max_half_weight() -> Weight {
+        50
+    }
+    pub fn error_probability() -> f64 {
+        0.1
+    }
+    pub fn erasure_probability() -> f64 {
+        0.02
+    }
+    pub fn brute_force() -> bool {
+        true
+    }
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+/// a self-contained test case that can be serialized to reproduce a failure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzCase {
+    pub initializer: SolverInitializer,
+    pub partition_config: PartitionConfig,
+    pub syndrome_pattern: SyndromePattern,
+}
+
+/// generate random cases deterministically from a seed
+pub struct Fuzzer {
+    pub config: FuzzConfig,
+    rng: DeterministicRng,
+}
+
+impl Fuzzer {
+    pub fn new(config: FuzzConfig, seed: u64) -> Self {
+        Self {
+            config,
+            rng: DeterministicRng::seed_from_u64(seed),
+        }
+    }
+
+    fn random_range(&mut self, min: usize, max: usize) -> usize {
+        min + (self.rng.next_u64() % (max - min + 1) as u64) as usize
+    }
+
+    /// generate a random connected decoding graph together with a valid partition of it: the vertices are laid out as
+    /// `[leaf 0][interface 0][leaf 1][interface 1]...[leaf n-1]` and every edge connects vertices whose owning units are
+    /// either the same or ancestor and descendant
+    #[allow(clippy::unnecessary_cast)]
+    pub fn generate_graph(&mut self) -> (SolverInitializer, PartitionConfig) {
+        let vertex_num = self.random_range(self.config.min_vertex_num as usize, self.config.max_vertex_num as usize);
+        // each leaf needs at least one vertex and each interface needs at least one vertex
+        let max_partition_num = std::cmp::max(1, std::cmp::min(self.config.max_partition_num, vertex_num - vertex_num / 2));
+        let partition_num = self.random_range(1, max_partition_num);
+        // choose the boundaries of `2 * partition_num - 1` non-empty consecutive ranges
+        let range_num = 2 * partition_num - 1;
+        let mut cuts: Vec<usize> = vec![0, vertex_num];
+        while cuts.len() < range_num + 1 {
+            let cut = self.random_range(1, vertex_num - 1);
+            if !cuts.contains(&cut) {
+                cuts.push(cut);
+            }
+        }
+        cuts.sort();
+        let mut partition_config = PartitionConfig::new(vertex_num as VertexNum);
+        partition_config.partitions = (0..partition_num)
+            .map(|i| VertexRange::new(cuts[2 * i] as VertexIndex, cuts[2 * i + 1] as VertexIndex))
+            .collect();
+        partition_config.fusions = (0..partition_num - 1)
+            .map(|i| (if i == 0 { 0 } else { partition_num + i - 1 }, i + 1))
+            .collect();
+        let partition_info = partition_config.info();
+        let is_edge_valid = |i: usize, j: usize| {
+            let i_unit = partition_info.vertex_to_owning_unit[i];
+            let j_unit = partition_info.vertex_to_owning_unit[j];
+            i_unit == j_unit
+                || partition_info.units[i_unit].descendants.contains(&j_unit)
+                || partition_info.units[j_unit].descendants.contains(&i_unit)
+        };
+        let mut edges = std::collections::BTreeSet::new();
+        for j in 1..vertex_num {
+            // spanning tree to guarantee connectivity; the previous vertex is always a valid candidate
+            let mut i = self.random_range(0, j - 1);
+            if !is_edge_valid(i, j) {
+                i = j - 1;
+            }
+            edges.insert((i, j));
+        }
+        let extra_edge_num = (vertex_num as f64 * self.config.edge_density) as usize;
+        for _ in 0..extra_edge_num {
+            let i = self.random_range(0, vertex_num - 1);
+            let j = self.random_range(0, vertex_num - 1);
+            if i != j && is_edge_valid(i, j) {
+                edges.insert((std::cmp::min(i, j), std::cmp::max(i, j)));
+            }
+        }
+        let max_half_weight = self.config.max_half_weight;
+        let weighted_edges = edges
+            .into_iter()
+            .map(|(i, j)| {
+                let half_weight = 1 + (self.rng.next_u64() % max_half_weight as u64) as Weight;
+                (i as VertexIndex, j as VertexIndex, 2 * half_weight)
+            })
+            .collect();
+        let mut virtual_vertices: Vec<VertexIndex> = (0..vertex_num)
+            .filter(|_| self.rng.next_f64() < self.config.virtual_probability)
+            .map(|i| i as VertexIndex)
+            .collect();
+        if virtual_vertices.is_empty() {
+            // at least one virtual vertex so that any defect pattern has a perfect matching
+            virtual_vertices.push(self.random_range(0, vertex_num - 1) as VertexIndex);
+        }
+        let initializer = SolverInitializer::new(vertex_num as VertexNum, weighted_edges, virtual_vertices);
+        (initializer, partition_config)
+    }
+
+    /// generate a random syndrome pattern by flipping random edges, so that a perfect matching always exists
+    #[allow(clippy::unnecessary_cast)]
+    pub fn generate_syndrome(&mut self, initializer: &SolverInitializer) -> SyndromePattern {
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        let mut is_defect = vec![false; initializer.vertex_num as usize];
+        let mut erasures = vec![];
+        for (edge_index, &(i, j, _)) in initializer.weighted_edges.iter().enumerate() {
+            let p = if self.rng.next_f64() < self.config.erasure_probability {
+                erasures.push(edge_index as EdgeIndex);
+                0.5
+            } else {
+                self.config.error_probability
+            };
+            if self.rng.next_f64() < p {
+                for vertex_index in [i, j] {
+                    if !is_virtual[vertex_index as usize] {
+                        is_defect[vertex_index as usize] = !is_defect[vertex_index as usize];
+                    }
+                }
+            }
+        }
+        let defect_vertices = (0..initializer.vertex_num)
+            .filter(|&vertex_index| is_defect[vertex_index as usize])
+            .collect();
+        SyndromePattern::new(defect_vertices, erasures)
+    }
+
+    pub fn generate_case(&mut self) -> FuzzCase {
+        let (initializer, partition_config) = self.generate_graph();
+        let syndrome_pattern = self.generate_syndrome(&initializer);
+        FuzzCase {
+            initializer,
+            partition_config,
+            syndrome_pattern,
+        }
+    }
+
+    /// run `case_num` random cases; return the first failing case after shrinking, together with the reason
+    pub fn run(&mut self, case_num: usize) -> Option<(FuzzCase, String)> {
+        for _ in 0..case_num {
+            let case = self.generate_case();
+            if case.check(self.config.brute_force).is_err() {
+                let case = case.shrink(self.config.brute_force);
+                let reason = case.check(self.config.brute_force).unwrap_err();
+                return Some((case, reason));
+            }
+        }
+        None
+    }
+}
+
+impl FuzzCase {
+    fn check_inner(&self, brute_force: bool) -> Result<(), String> {
+        let initializer = &self.initializer;
+        let syndrome_pattern = &self.syndrome_pattern;
+        let mut serial_solver = SolverSerial::new(initializer);
+        serial_solver.solve(syndrome_pattern);
+        let serial_weight = serial_solver.sum_dual_variables();
+        let mut perfect_matchings = vec![("serial", serial_solver.perfect_matching())];
+        // the parallel solver doesn't support erasures yet, in which case only the serial solver is verified
+        if syndrome_pattern.erasures.is_empty() {
+            let mut parallel_solver = SolverParallel::new(initializer, &self.partition_config.info(), json!({}));
+            parallel_solver.solve(syndrome_pattern);
+            let parallel_weight = parallel_solver.sum_dual_variables();
+            if serial_weight != parallel_weight {
+                return Err(format!(
+                    "serial sum_dual_variables {serial_weight} != parallel sum_dual_variables {parallel_weight}"
+                ));
+            }
+            perfect_matchings.push(("parallel", parallel_solver.perfect_matching()));
+        }
+        for (name, perfect_matching) in perfect_matchings.iter() {
+            let mut subgraph_builder = SubGraphBuilder::new(initializer);
+            subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+            subgraph_builder.load_perfect_matching(perfect_matching);
+            let total_weight = subgraph_builder.total_weight();
+            if total_weight != serial_weight {
+                return Err(format!(
+                    "{name} perfect matching has weight {total_weight} != sum_dual_variables {serial_weight}"
+                ));
+            }
+        }
+        if brute_force && syndrome_pattern.defect_vertices.len() <= BRUTE_FORCE_MAX_DEFECT_NUM {
+            let expected_weight = BruteForceDecoder::new(initializer).solve_weight(syndrome_pattern);
+            if expected_weight != serial_weight {
+                return Err(format!(
+                    "sum_dual_variables {serial_weight} != brute force minimum weight {expected_weight}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// run the serial and parallel solvers and compare the results; a panic is also reported as failure
+    pub fn check(&self, brute_force: bool) -> Result<(), String> {
+        catch_panic(|| self.check_inner(brute_force)).and_then(|result| result)
+    }
+
+    /// remove erasures and defect vertices while the case still fails; removing a single defect vertex is always valid
+    /// because every graph has at least one virtual vertex
+    pub fn shrink(mut self, brute_force: bool) -> Self {
+        debug_assert!(self.check(brute_force).is_err(), "only failing cases can be shrunk");
+        self.syndrome_pattern = shrink_syndrome(self.syndrome_pattern.clone(), |syndrome_pattern| {
+            let mut candidate = self.clone();
+            candidate.syndrome_pattern = syndrome_pattern.clone();
+            candidate.check(brute_force).is_err()
+        });
+        self
+    }
+}
+
+/// greedily remove erasures and single defect vertices while the syndrome still fails
+pub fn shrink_syndrome(
+    mut syndrome_pattern: SyndromePattern,
+    is_failing: impl Fn(&SyndromePattern) -> bool,
+) -> SyndromePattern {
+    let mut progress = true;
+    while progress {
+        progress = false;
+        let mut index = 0;
+        while index < syndrome_pattern.erasures.len() {
+            let mut candidate = syndrome_pattern.clone();
+            candidate.erasures.remove(index);
+            if is_failing(&candidate) {
+                syndrome_pattern = candidate;
+                progress = true;
+            } else {
+                index += 1;
+            }
+        }
+        let mut index = 0;
+        while index < syndrome_pattern.defect_vertices.len() {
+            let mut candidate = syndrome_pattern.clone();
+            candidate.defect_vertices.remove(index);
+            if is_failing(&candidate) {
+                syndrome_pattern = candidate;
+                progress = true;
+            } else {
+                index += 1;
+            }
+        }
+    }
+    syndrome_pattern
+}
+
+/// a syndrome on which two solver configurations disagree, which can be saved as a JSON file to reproduce it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigMismatch {
+    pub initializer: SolverInitializer,
+    pub config_a: SolverConfig,
+    pub config_b: SolverConfig,
+    pub syndrome_pattern: SyndromePattern,
+    pub reason: String,
+}
+
+/// decode a syndrome with two solvers and compare the weights; a panic in only one of them is also a mismatch, while a
+/// panic in both (e.g. a syndrome without any perfect matching) is not. The solvers must be rebuilt after a panic
+pub fn compare_solvers(
+    solver_a: &mut dyn SolverTrait,
+    solver_b: &mut dyn SolverTrait,
+    syndrome_pattern: &SyndromePattern,
+) -> Result<(), String> {
+    let decode = |solver: &mut dyn SolverTrait| {
+        catch_panic(|| {
+            let weight = solver.decode(syndrome_pattern).weight;
+            solver.clear();
+            weight
+        })
+    };
+    match (decode(solver_a), decode(solver_b)) {
+        (Ok(weight_a), Ok(weight_b)) if weight_a != weight_b => Err(format!("weight {weight_a} != {weight_b}")),
+        (Ok(_), Err(reason)) => Err(format!("only b failed: {reason}")),
+        (Err(reason), Ok(_)) => Err(format!("only a failed: {reason}")),
+        _ => Ok(()),
+    }
+}
+
+impl ConfigMismatch {
+    /// check the syndrome with newly built solvers
+    pub fn check(&self) -> Result<(), String> {
+        let mut solver_a = self.config_a.build(&self.initializer);
+        let mut solver_b = self.config_b.build(&self.initializer);
+        compare_solvers(solver_a.as_mut(), solver_b.as_mut(), &self.syndrome_pattern)
+    }
+
+    /// shrink the syndrome to a minimal reproducer, see [`shrink_syndrome`]
+    pub fn shrink(mut self) -> Self {
+        self.syndrome_pattern = shrink_syndrome(self.syndrome_pattern.clone(), |syndrome_pattern| {
+            let mut candidate = self.clone();
+            candidate.syndrome_pattern = syndrome_pattern.clone();
+            candidate.check().is_err()
+        });
+        if let Err(reason) = self.check() {
+            self.reason = reason;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzz_serial_parallel_consistency() {
+        // cargo test fuzz_serial_parallel_consistency -- --nocapture
+        for seed in 0..5 {
+            let mut fuzzer = Fuzzer::new(FuzzConfig::default(), seed);
+            if let Some((case, reason)) = fuzzer.run(20) {
+                panic!("{reason}\n{}", serde_json::to_string(&case).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz_shrink_failing_case() {
+        // cargo test fuzz_shrink_failing_case -- --nocapture
+        let mut fuzzer = Fuzzer::new(FuzzConfig::default(), 1);
+        let mut case = fuzzer.generate_case();
+        while case.syndrome_pattern.defect_vertices.len() < 3 {
+            case = fuzzer.generate_case();
+        }
+        // an out-of-range defect vertex makes the solvers panic, and should be the only defect left after shrinking
+        let invalid_vertex = case.initializer.vertex_num;
+        case.syndrome_pattern.defect_vertices.push(invalid_vertex);
+        let shrunk = case.shrink(false);
+        assert_eq!(shrunk.syndrome_pattern.defect_vertices, vec![invalid_vertex]);
+    }
+
+    #[test]
+    fn fuzz_compare_solver_configs() {
+        // cargo test fuzz_compare_solver_configs -- --nocapture
+        // erasures are not supported by the partitioned solver
+        let fuzz_config = FuzzConfig {
+            erasure_probability: 0.,
+            ..Default::default()
+        };
+        let mut fuzzer = Fuzzer::new(fuzz_config, 2);
+        for _ in 0..10 {
+            let case = fuzzer.generate_case();
+            let mismatch = ConfigMismatch {
+                initializer: case.initializer.clone(),
+                config_a: SolverConfig::Serial,
+                config_b: SolverConfig::Parallel {
+                    partition_config: Some(case.partition_config.clone()),
+                    primal_dual_config: None,
+                },
+                syndrome_pattern: case.syndrome_pattern.clone(),
+                reason: String::new(),
+            };
+            // the reproducer must survive a JSON round trip
+            let mismatch: ConfigMismatch = serde_json::from_str(&serde_json::to_string(&mismatch).unwrap()).unwrap();
+            if let Err(reason) = mismatch.check() {
+                panic!("{reason}\n{}", serde_json::to_string(&mismatch.shrink()).unwrap());
+            }
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! Hypergraph Decomposition
+//!
+//! Turn a hypergraph error model into a decoding graph, e.g. a detector error model of a circuit-level noise model where a
+//! correlated fault flips more than two detectors. Each hyperedge is decomposed into graph edges by a
+//! [`DecompositionStrategy`], the probabilities of the faults that end up on the same edge are combined, and the
+//! decomposition is recorded so that a correction can be mapped back to the observables and the faults.
+//!
+//! The vertices are the detectors; a boundary vertex is appended after them if any fault flips an odd number of detectors.
+//!
+
+use super::example_codes::{weight_of_p, MIN_ERROR_RATE};
+use super::util::*;
+use crate::serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// a fault that flips a set of detectors and observables with some probability
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hyperedge {
+    /// the flipped detectors in increasing order, without duplicates
+    pub detectors: Vec<VertexIndex>,
+    /// the flipped observables as a bit mask
+    pub observables: usize,
+    pub p: f64,
+}
+
+impl Hyperedge {
+    /// a detector or an observable that appears twice cancels out, like the `^` separator of a detector error model
+    pub fn new(detectors: Vec<VertexIndex>, observables: usize, p: f64) -> Self {
+        let mut odd = std::collections::BTreeSet::new();
+        for detector in detectors {
+            if !odd.remove(&detector) {
+                odd.insert(detector);
+            }
+        }
+        Self {
+            detectors: odd.into_iter().collect(),
+            observables,
+            p,
+        }
+    }
+}
+
+/// a list of faults over `detector_num` detectors
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HypergraphModel {
+    pub detector_num: VertexNum,
+    pub hyperedges: Vec<Hyperedge>,
+}
+
+impl HypergraphModel {
+    /// parse the text format of a Stim detector error model; the `repeat` blocks must be flattened beforehand, e.g. by
+    /// `stim.DetectorErrorModel.flattened()`, and the coordinates are ignored
+    #[allow(clippy::unnecessary_cast)]
+    pub fn from_dem(text: &str) -> Result<Self, String> {
+        let mut model = Self::default();
+        let mut detector_offset: VertexNum = 0;
+        let mut parse_target = |target: &str, offset: VertexNum| -> Result<VertexIndex, String> {
+            let index: VertexNum = target[1..].parse().map_err(|_| format!("invalid target {target}"))?;
+            let detector = offset + index;
+            model.detector_num = model.detector_num.max(detector + 1);
+            Ok(detector as VertexIndex)
+        };
+        let mut hyperedges = vec![];
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            // the arguments in parentheses may contain spaces, e.g. the coordinates of `detector(1, 0) D3`
+            let (instruction, targets) = match line.find(')') {
+                Some(end) => (&line[..=end], &line[end + 1..]),
+                None => line.split_once(char::is_whitespace).unwrap_or((line, "")),
+            };
+            let name = instruction.split('(').next().unwrap().trim();
+            let argument = || -> Result<f64, String> {
+                let argument = instruction
+                    .split_once('(')
+                    .map(|(_, argument)| argument.trim_end_matches(')'));
+                let p = argument.and_then(|argument| argument.split(',').next()?.trim().parse().ok());
+                p.ok_or(format!("line {}: invalid argument of {instruction}", line_index + 1))
+            };
+            match name {
+                "error" => {
+                    let p = argument()?;
+                    let mut detectors = vec![];
+                    let mut observables = 0;
+                    for target in targets.split_whitespace() {
+                        match target.chars().next() {
+                            Some('D') => detectors.push(parse_target(target, detector_offset)?),
+                            Some('L') => {
+                                let observable: usize =
+                                    target[1..].parse().map_err(|_| format!("invalid target {target}"))?;
+                                observables ^= 1 << observable;
+                            }
+                            Some('^') => {}
+                            _ => return Err(format!("line {}: invalid target {target}", line_index + 1)),
+                        }
+                    }
+                    hyperedges.push(Hyperedge::new(detectors, observables, p));
+                }
+                "detector" => {
+                    for target in targets.split_whitespace() {
+                        parse_target(target, detector_offset)?;
+                    }
+                }
+                "shift_detectors" => {
+                    let shift = targets.trim().parse::<VertexNum>();
+                    detector_offset += shift.map_err(|_| format!("line {}: invalid shift {targets}", line_index + 1))?;
+                }
+                "logical_observable" => {}
+                "repeat" | "}" => return Err(format!("line {}: repeat blocks are not supported", line_index + 1)),
+                _ => return Err(format!("line {}: unknown instruction {name}", line_index + 1)),
+            }
+        }
+        model.hyperedges = hyperedges;
+        Ok(model)
+    }
+}
+
+/// how to decompose a hyperedge that flips more than two detectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DecompositionStrategy {
+    /// into edges that are also faults by themselves, i.e. hyperedges of at most two detectors, falling back to
+    /// [`DecompositionStrategy::Chain`] if there is no such decomposition
+    #[default]
+    ExistingEdges,
+    /// pair up the consecutive detectors, and the last one is connected to the boundary if the number is odd
+    Chain,
+    /// ignore the hyperedge, which is then listed in [`HypergraphDecomposition::dropped`]
+    Drop,
+}
+
+/// the decoding graph of a [`HypergraphModel`], see module documentation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HypergraphDecomposition {
+    /// the edge fault ids are the representative hyperedge of each edge, see [`Self::representatives`]
+    pub initializer: SolverInitializer,
+    /// the boundary vertex appended after the detectors, if any
+    pub boundary_vertex: Option<VertexIndex>,
+    /// the edges of each hyperedge, empty if it's dropped
+    pub decompositions: Vec<Vec<EdgeIndex>>,
+    /// the combined probability of each edge
+    pub edge_probabilities: Vec<f64>,
+    /// the observables flipped by each edge; the observables of a hyperedge are the XOR of those of its edges
+    pub edge_observables: Vec<usize>,
+    /// the most likely hyperedge that decomposes into exactly this edge, or otherwise the most likely hyperedge containing it
+    pub representatives: Vec<usize>,
+    /// the hyperedges that are not decomposed, because they don't flip any detector or by [`DecompositionStrategy::Drop`]
+    pub dropped: Vec<usize>,
+    /// the hyperedges whose observables differ from the XOR of the observables of their edges, which are already
+    /// defined by other hyperedges
+    pub inconsistent: Vec<usize>,
+}
+
+impl HypergraphDecomposition {
+    /// the largest weight is scaled to `2 * max_half_weight` and the others are rounded proportionally
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(model: &HypergraphModel, strategy: DecompositionStrategy, max_half_weight: Weight) -> Self {
+        let boundary = model.detector_num as VertexIndex;
+        // the edges are keyed by `(min, max)` where the boundary is `detector_num`
+        let mut edge_indices = BTreeMap::<(VertexIndex, VertexIndex), EdgeIndex>::new();
+        let mut endpoints: Vec<(VertexIndex, VertexIndex)> = vec![];
+        let mut edge_probabilities: Vec<f64> = vec![];
+        let mut edge_observables: Vec<Option<usize>> = vec![];
+        let mut decompositions = vec![vec![]; model.hyperedges.len()];
+        let mut dropped = vec![];
+        let mut inconsistent = vec![];
+        let pair_of = |detectors: &[VertexIndex]| match *detectors {
+            [a] => (a, boundary),
+            [a, b] => (a, b),
+            _ => unreachable!(),
+        };
+        // the faults of at most two detectors define the existing edges before the hyperedges are decomposed
+        let (edge_faults, hyper_faults): (Vec<usize>, Vec<usize>) =
+            (0..model.hyperedges.len()).partition(|&index| model.hyperedges[index].detectors.len() <= 2);
+        for fault_index in edge_faults.into_iter().chain(hyper_faults) {
+            let hyperedge = &model.hyperedges[fault_index];
+            let components: Vec<(VertexIndex, VertexIndex)> = match hyperedge.detectors.len() {
+                0 => vec![],
+                1 | 2 => vec![pair_of(&hyperedge.detectors)],
+                _ => match strategy {
+                    DecompositionStrategy::ExistingEdges => {
+                        Self::decompose_existing(&hyperedge.detectors, boundary, &edge_indices)
+                            .unwrap_or_else(|| Self::decompose_chain(&hyperedge.detectors, boundary))
+                    }
+                    DecompositionStrategy::Chain => Self::decompose_chain(&hyperedge.detectors, boundary),
+                    DecompositionStrategy::Drop => vec![],
+                },
+            };
+            if components.is_empty() {
+                dropped.push(fault_index);
+                continue;
+            }
+            let mut residual = hyperedge.observables;
+            let mut new_edges = vec![];
+            for (a, b) in components {
+                let key = (VertexIndex::min(a, b), VertexIndex::max(a, b));
+                let edge_index = *edge_indices.entry(key).or_insert_with(|| {
+                    endpoints.push(key);
+                    edge_probabilities.push(0.);
+                    edge_observables.push(None);
+                    (endpoints.len() - 1) as EdgeIndex
+                });
+                let p = &mut edge_probabilities[edge_index as usize];
+                *p = *p * (1. - hyperedge.p) + hyperedge.p * (1. - *p);
+                match edge_observables[edge_index as usize] {
+                    Some(observables) => residual ^= observables,
+                    None => new_edges.push(edge_index),
+                }
+                decompositions[fault_index].push(edge_index);
+            }
+            // the residual observables are assigned to the first new edge
+            for (index, edge_index) in new_edges.iter().enumerate() {
+                edge_observables[*edge_index as usize] = Some(if index == 0 { residual } else { 0 });
+            }
+            if new_edges.is_empty() && residual != 0 {
+                inconsistent.push(fault_index);
+            }
+        }
+        dropped.sort_unstable();
+        inconsistent.sort_unstable();
+        // the representative prefers the hyperedges of a single edge, and then the most likely one
+        let mut representatives: Vec<Option<usize>> = vec![None; endpoints.len()];
+        for (fault_index, decomposition) in decompositions.iter().enumerate() {
+            for &edge_index in decomposition.iter() {
+                let priority = |index: usize| (decompositions[index].len() == 1, model.hyperedges[index].p);
+                let representative = &mut representatives[edge_index as usize];
+                if !matches!(*representative, Some(current) if priority(current) >= priority(fault_index)) {
+                    *representative = Some(fault_index);
+                }
+            }
+        }
+        let weights: Vec<f64> = (edge_probabilities.iter())
+            .map(|&p| weight_of_p(p.clamp(MIN_ERROR_RATE, 0.5)))
+            .collect();
+        let max_weight = weights.iter().cloned().fold(0., f64::max);
+        let weighted_edges = (endpoints.iter().zip(weights.iter()))
+            .map(|(&(a, b), &weight)| {
+                let half_weight = if max_weight > 0. {
+                    (weight / max_weight * max_half_weight as f64).round() as Weight
+                } else {
+                    0
+                };
+                (a, b, 2 * half_weight)
+            })
+            .collect();
+        let boundary_vertex = edge_indices.keys().any(|&(_, b)| b == boundary).then_some(boundary);
+        let vertex_num = model.detector_num + boundary_vertex.is_some() as VertexNum;
+        let mut initializer = SolverInitializer::new(vertex_num, weighted_edges, boundary_vertex.into_iter().collect());
+        let representatives: Vec<usize> = representatives.into_iter().map(Option::unwrap).collect();
+        for (edge_index, &representative) in representatives.iter().enumerate() {
+            initializer = initializer.with_edge_fault_ids(edge_index as EdgeIndex, vec![representative]);
+        }
+        Self {
+            initializer,
+            boundary_vertex,
+            decompositions,
+            edge_probabilities,
+            edge_observables: edge_observables.into_iter().map(Option::unwrap).collect(),
+            representatives,
+            dropped,
+            inconsistent,
+        }
+    }
+
+    /// a partition of the detectors into existing edges, where a single detector may be connected to the boundary;
+    /// searched by backtracking in the order of the detectors
+    fn decompose_existing(
+        detectors: &[VertexIndex],
+        boundary: VertexIndex,
+        edge_indices: &BTreeMap<(VertexIndex, VertexIndex), EdgeIndex>,
+    ) -> Option<Vec<(VertexIndex, VertexIndex)>> {
+        let Some((&first, rest)) = detectors.split_first() else {
+            return Some(vec![]);
+        };
+        if edge_indices.contains_key(&(first, boundary)) {
+            if let Some(mut components) = Self::decompose_existing(rest, boundary, edge_indices) {
+                components.insert(0, (first, boundary));
+                return Some(components);
+            }
+        }
+        for (index, &peer) in rest.iter().enumerate() {
+            if edge_indices.contains_key(&(first, peer)) {
+                let remaining: Vec<VertexIndex> = (rest.iter().enumerate())
+                    .filter(|&(other, _)| other != index)
+                    .map(|(_, &detector)| detector)
+                    .collect();
+                if let Some(mut components) = Self::decompose_existing(&remaining, boundary, edge_indices) {
+                    components.insert(0, (first, peer));
+                    return Some(components);
+                }
+            }
+        }
+        None
+    }
+
+    fn decompose_chain(detectors: &[VertexIndex], boundary: VertexIndex) -> Vec<(VertexIndex, VertexIndex)> {
+        (detectors.chunks(2))
+            .map(|chunk| (chunk[0], chunk.get(1).cloned().unwrap_or(boundary)))
+            .collect()
+    }
+
+    /// the observables flipped by a correction on the decoding graph
+    #[allow(clippy::unnecessary_cast)]
+    pub fn subgraph_observables(&self, subgraph: &[EdgeIndex]) -> usize {
+        (subgraph.iter()).fold(0, |observables, &edge_index| {
+            observables ^ self.edge_observables[edge_index as usize]
+        })
+    }
+
+    /// the faults of a correction on the decoding graph, i.e. the representatives of its edges that appear an odd number
+    /// of times, see [`SolverInitializer::subgraph_fault_ids`]
+    pub fn subgraph_faults(&self, subgraph: &[EdgeIndex]) -> Vec<usize> {
+        self.initializer.subgraph_fault_ids(subgraph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mwpm_solver::*;
+    use super::*;
+
+    #[test]
+    fn hypergraph_decomposition_existing_edges() {
+        // cargo test hypergraph_decomposition_existing_edges -- --nocapture
+        // a repetition code of 4 detectors with a correlated fault flipping 0, 1, 2, 3 and the observable
+        let dem = "
+            error(0.1) D0 L0
+            error(0.1) D0 D1
+            error(0.1) D1 D2
+            error(0.1) D2 D3
+            error(0.1) D3
+            error(0.01) D0 D1 D2 D3 ^ L0  # correlated
+            error(0.02) D0 D2 D1 L0
+            detector(1, 0) D3
+            logical_observable L0
+        ";
+        let model = HypergraphModel::from_dem(dem).unwrap();
+        assert_eq!(model.detector_num, 4);
+        assert_eq!(model.hyperedges[5], Hyperedge::new(vec![0, 1, 2, 3], 1, 0.01));
+        let decomposition = HypergraphDecomposition::new(&model, DecompositionStrategy::ExistingEdges, 500);
+        println!("{decomposition:?}");
+        let initializer = &decomposition.initializer;
+        assert_eq!(decomposition.boundary_vertex, Some(4));
+        assert_eq!(initializer.virtual_vertices, vec![4]);
+        // the correlated faults reuse the existing edges (0, boundary), (1, 2) and (3, boundary)
+        assert_eq!(decomposition.decompositions[5], vec![0, 2, 4]);
+        assert_eq!(decomposition.decompositions[6], vec![0, 2]);
+        assert_eq!(initializer.weighted_edges.len(), 5);
+        assert!(decomposition.inconsistent.is_empty() && decomposition.dropped.is_empty());
+        assert_eq!(decomposition.edge_observables, vec![1, 0, 0, 0, 0]);
+        // the probabilities of the faults on the same edge are combined
+        assert!((decomposition.edge_probabilities[4] - (0.1 * 0.99 + 0.01 * 0.9)).abs() < 1e-12);
+        assert!(initializer.weighted_edges[4].2 < initializer.weighted_edges[3].2);
+        assert_eq!(decomposition.representatives, vec![0, 1, 2, 3, 4]);
+        // the vertices are the detectors
+        let mut solver = SolverSerial::new(initializer);
+        solver.solve(&SyndromePattern::new_vertices(vec![1, 2]));
+        let subgraph = solver.subgraph();
+        assert_eq!(subgraph, vec![2]);
+        assert_eq!(decomposition.subgraph_observables(&subgraph), 0);
+        solver.clear();
+        solver.solve(&SyndromePattern::new_vertices(vec![0]));
+        let subgraph = solver.subgraph();
+        assert_eq!(decomposition.subgraph_observables(&subgraph), 1);
+        assert_eq!(decomposition.subgraph_faults(&subgraph), vec![0]);
+    }
+
+    #[test]
+    fn hypergraph_decomposition_strategies() {
+        // cargo test hypergraph_decomposition_strategies -- --nocapture
+        let model = HypergraphModel {
+            detector_num: 5,
+            hyperedges: vec![
+                Hyperedge::new(vec![0, 1], 0, 0.1),
+                Hyperedge::new(vec![2, 3, 4], 1, 0.05),
+                Hyperedge::new(vec![0, 2, 3], 2, 0.05),
+                Hyperedge::new(vec![], 1, 0.01),
+            ],
+        };
+        let chain = HypergraphDecomposition::new(&model, DecompositionStrategy::Chain, 100);
+        // (2, 3) + (4, boundary) and then (0, 2) + (3, boundary)
+        assert_eq!(chain.decompositions[1], vec![1, 2]);
+        assert_eq!(chain.decompositions[2], vec![3, 4]);
+        assert_eq!(chain.edge_observables, vec![0, 1, 0, 2, 0]);
+        assert_eq!(chain.dropped, vec![3]);
+        // neither hyperedge can be partitioned into the single edge (0, 1), so both fall back to the chain
+        let existing = HypergraphDecomposition::new(&model, DecompositionStrategy::ExistingEdges, 100);
+        assert_eq!(existing.initializer.weighted_edges.len(), 5);
+        let dropping = HypergraphDecomposition::new(&model, DecompositionStrategy::Drop, 100);
+        assert_eq!(dropping.dropped, vec![1, 2, 3]);
+        assert_eq!(dropping.boundary_vertex, None);
+        assert_eq!(dropping.initializer.vertex_num, 5);
+        // an edge that is also a fault by itself makes an inconsistent observable visible
+        let model = HypergraphModel {
+            detector_num: 4,
+            hyperedges: vec![
+                Hyperedge::new(vec![0, 1], 0, 0.1),
+                Hyperedge::new(vec![2, 3], 0, 0.1),
+                Hyperedge::new(vec![0, 1, 2, 3], 1, 0.01),
+            ],
+        };
+        let decomposition = HypergraphDecomposition::new(&model, DecompositionStrategy::ExistingEdges, 100);
+        assert_eq!(decomposition.decompositions[2], vec![0, 1]);
+        assert_eq!(decomposition.inconsistent, vec![2]);
+        assert!(HypergraphModel::from_dem("repeat 10 {\n}").is_err());
+        assert!(HypergraphModel::from_dem("error(0.1) X0").is_err());
+    }
+}
@@ -0,0 +1,444 @@
+//! Instruction Trace
+//!
+//! Record the primitive operations that a primal module issues to the dual module during a solve, so that a hardware
+//! implementation of [`DualModuleImpl`] can be fed with real workloads, e.g. in an RTL simulation.
+//!
+//! Dual nodes are numbered implicitly in the order they're created since the last [`Instruction::Reset`], just like
+//! [`DualNode::index`] of a serial solver. Creating a blossom implicitly sets all its children to
+//! [`DualNodeGrowState::Stay`], and expanding a blossom implicitly sets all its children to [`DualNodeGrowState::Grow`];
+//! these implicit speed changes are not recorded.
+//!
+//! The binary format starts with [`InstructionTrace::MAGIC`], followed by each instruction as an opcode byte and its
+//! operands encoded as LEB128 variable-length integers, where signed operands are zigzag-encoded.
+//!
+
+#![cfg_attr(feature = "unsafe_pointer", allow(dropping_references))]
+use super::dual_module::*;
+use super::pointers::*;
+use super::primal_module::*;
+use super::primal_module_serial::*;
+use super::util::*;
+use std::collections::BTreeSet;
+
+/// a primitive operation of the dual module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    /// clear all the dual nodes and restore all the edge weights
+    Reset,
+    /// temporarily change the weight of an edge, e.g. an erasure
+    SetWeight { edge_index: EdgeIndex, weight: Weight },
+    /// create a growing dual node on a defect vertex
+    AddDefect { vertex_index: VertexIndex },
+    /// create a growing blossom out of an odd cycle of dual nodes
+    CreateBlossom { nodes_circle: Vec<NodeIndex> },
+    /// expand a blossom and let its children grow
+    Expand { node_index: NodeIndex },
+    /// set the grow state of a dual node
+    SetSpeed {
+        node_index: NodeIndex,
+        grow_state: DualNodeGrowState,
+    },
+    /// compute the maximum length that all dual nodes can grow without conflicts
+    FindObstacle,
+    /// grow all dual nodes by `length`
+    Grow { length: Weight },
+}
+
+impl Instruction {
+    const OPCODE_RESET: u8 = 0;
+    const OPCODE_SET_WEIGHT: u8 = 1;
+    const OPCODE_ADD_DEFECT: u8 = 2;
+    const OPCODE_CREATE_BLOSSOM: u8 = 3;
+    const OPCODE_EXPAND: u8 = 4;
+    const OPCODE_SET_SPEED: u8 = 5;
+    const OPCODE_FIND_OBSTACLE: u8 = 6;
+    const OPCODE_GROW: u8 = 7;
+}
+
+/// a sequence of dual module instructions, see [`DualModuleTracer`] for recording one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstructionTrace {
+    pub instructions: Vec<Instruction>,
+}
+
+fn write_unsigned(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_signed(bytes: &mut Vec<u8>, value: i64) {
+    write_unsigned(bytes, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn read_unsigned(bytes: &[u8], cursor: &mut usize) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*cursor).ok_or("unexpected end of instruction trace")?;
+        *cursor += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(format!("integer too long at byte {}", *cursor))
+}
+
+fn read_signed(bytes: &[u8], cursor: &mut usize) -> Result<i64, String> {
+    let value = read_unsigned(bytes, cursor)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+impl InstructionTrace {
+    /// the leading bytes of the binary format, where the last byte is the version
+    pub const MAGIC: [u8; 4] = *b"FBI\x01";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Self::MAGIC.to_vec();
+        for instruction in self.instructions.iter() {
+            match instruction {
+                Instruction::Reset => bytes.push(Instruction::OPCODE_RESET),
+                Instruction::SetWeight { edge_index, weight } => {
+                    bytes.push(Instruction::OPCODE_SET_WEIGHT);
+                    write_unsigned(&mut bytes, *edge_index as u64);
+                    write_signed(&mut bytes, *weight as i64);
+                }
+                Instruction::AddDefect { vertex_index } => {
+                    bytes.push(Instruction::OPCODE_ADD_DEFECT);
+                    write_unsigned(&mut bytes, *vertex_index as u64);
+                }
+                Instruction::CreateBlossom { nodes_circle } => {
+                    bytes.push(Instruction::OPCODE_CREATE_BLOSSOM);
+                    write_unsigned(&mut bytes, nodes_circle.len() as u64);
+                    for node_index in nodes_circle.iter() {
+                        write_unsigned(&mut bytes, *node_index as u64);
+                    }
+                }
+                Instruction::Expand { node_index } => {
+                    bytes.push(Instruction::OPCODE_EXPAND);
+                    write_unsigned(&mut bytes, *node_index as u64);
+                }
+                Instruction::SetSpeed { node_index, grow_state } => {
+                    bytes.push(Instruction::OPCODE_SET_SPEED);
+                    write_unsigned(&mut bytes, *node_index as u64);
+                    bytes.push(match grow_state {
+                        DualNodeGrowState::Grow => 1,
+                        DualNodeGrowState::Stay => 0,
+                        DualNodeGrowState::Shrink => 2,
+                    });
+                }
+                Instruction::FindObstacle => bytes.push(Instruction::OPCODE_FIND_OBSTACLE),
+                Instruction::Grow { length } => {
+                    bytes.push(Instruction::OPCODE_GROW);
+                    write_signed(&mut bytes, *length as i64);
+                }
+            }
+        }
+        bytes
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.starts_with(&Self::MAGIC) {
+            return Err("not an instruction trace of a supported version".to_string());
+        }
+        let mut cursor = Self::MAGIC.len();
+        let mut instructions = vec![];
+        while cursor < bytes.len() {
+            let opcode = bytes[cursor];
+            cursor += 1;
+            let instruction = match opcode {
+                Instruction::OPCODE_RESET => Instruction::Reset,
+                Instruction::OPCODE_SET_WEIGHT => Instruction::SetWeight {
+                    edge_index: read_unsigned(bytes, &mut cursor)? as EdgeIndex,
+                    weight: read_signed(bytes, &mut cursor)? as Weight,
+                },
+                Instruction::OPCODE_ADD_DEFECT => Instruction::AddDefect {
+                    vertex_index: read_unsigned(bytes, &mut cursor)? as VertexIndex,
+                },
+                Instruction::OPCODE_CREATE_BLOSSOM => {
+                    let length = read_unsigned(bytes, &mut cursor)?;
+                    let nodes_circle = (0..length)
+                        .map(|_| read_unsigned(bytes, &mut cursor).map(|node_index| node_index as NodeIndex))
+                        .collect::<Result<_, _>>()?;
+                    Instruction::CreateBlossom { nodes_circle }
+                }
+                Instruction::OPCODE_EXPAND => Instruction::Expand {
+                    node_index: read_unsigned(bytes, &mut cursor)? as NodeIndex,
+                },
+                Instruction::OPCODE_SET_SPEED => {
+                    let node_index = read_unsigned(bytes, &mut cursor)? as NodeIndex;
+                    let grow_state = match bytes.get(cursor) {
+                        Some(0) => DualNodeGrowState::Stay,
+                        Some(1) => DualNodeGrowState::Grow,
+                        Some(2) => DualNodeGrowState::Shrink,
+                        _ => return Err(format!("invalid grow state at byte {cursor}")),
+                    };
+                    cursor += 1;
+                    Instruction::SetSpeed { node_index, grow_state }
+                }
+                Instruction::OPCODE_FIND_OBSTACLE => Instruction::FindObstacle,
+                Instruction::OPCODE_GROW => Instruction::Grow {
+                    length: read_signed(bytes, &mut cursor)? as Weight,
+                },
+                _ => return Err(format!("unknown opcode {opcode} at byte {}", cursor - 1)),
+            };
+            instructions.push(instruction);
+        }
+        Ok(Self { instructions })
+    }
+
+    /// run the instructions on a dual module through an interface, returning the result of every
+    /// [`Instruction::FindObstacle`]; this checks that a trace is self-contained and serves as the reference behavior
+    #[allow(clippy::unnecessary_cast)]
+    pub fn replay(
+        &self,
+        interface_ptr: &DualModuleInterfacePtr,
+        dual_module: &mut impl DualModuleImpl,
+    ) -> Result<Vec<GroupMaxUpdateLength>, String> {
+        let mut obstacles = vec![];
+        let mut edge_modifier = vec![];
+        let get_node = |node_index: NodeIndex| {
+            interface_ptr
+                .read_recursive()
+                .get_node(node_index)
+                .ok_or(format!("dual node {node_index} doesn't exist"))
+        };
+        for instruction in self.instructions.iter() {
+            if !matches!(instruction, Instruction::SetWeight { .. }) && !edge_modifier.is_empty() {
+                dual_module
+                    .load_edge_modifier(&edge_modifier)
+                    .map_err(|error| error.to_string())?;
+                edge_modifier.clear();
+            }
+            match instruction {
+                Instruction::Reset => {
+                    interface_ptr.clear();
+                    dual_module.clear();
+                }
+                Instruction::SetWeight { edge_index, weight } => edge_modifier.push((*edge_index, *weight)),
+                Instruction::AddDefect { vertex_index } => {
+                    interface_ptr.create_defect_node(*vertex_index, dual_module);
+                }
+                Instruction::CreateBlossom { nodes_circle } => {
+                    let nodes_circle = nodes_circle
+                        .iter()
+                        .map(|node_index| get_node(*node_index))
+                        .collect::<Result<_, _>>()?;
+                    interface_ptr.create_blossom(nodes_circle, vec![], dual_module);
+                }
+                Instruction::Expand { node_index } => {
+                    // the children that don't stay are set by the following `SetSpeed` instructions
+                    let grow_state = |_| DualNodeGrowState::Stay;
+                    interface_ptr.expand_blossom_grow_states(get_node(*node_index)?, grow_state, dual_module);
+                }
+                Instruction::SetSpeed { node_index, grow_state } => {
+                    interface_ptr.set_grow_state(&get_node(*node_index)?, *grow_state, dual_module);
+                }
+                Instruction::FindObstacle => obstacles.push(dual_module.compute_maximum_update_length()),
+                Instruction::Grow { length } => interface_ptr.grow(*length, dual_module),
+            }
+        }
+        if !edge_modifier.is_empty() {
+            dual_module
+                .load_edge_modifier(&edge_modifier)
+                .map_err(|error| error.to_string())?;
+        }
+        Ok(obstacles)
+    }
+}
+
+/// a dual module that forwards every operation to the inner dual module while recording it as an [`Instruction`]
+pub struct DualModuleTracer<D: DualModuleImpl> {
+    pub dual_module: D,
+    pub trace: InstructionTrace,
+    /// the children of the last expanded blossom whose implicit speed change is not yet observed
+    expanded_children: BTreeSet<NodeIndex>,
+}
+
+impl<D: DualModuleImpl> DualModuleTracer<D> {
+    pub fn new(dual_module: D) -> Self {
+        Self {
+            dual_module,
+            trace: InstructionTrace::new(),
+            expanded_children: BTreeSet::new(),
+        }
+    }
+
+    fn record(&mut self, instruction: Instruction) {
+        self.expanded_children.clear();
+        self.trace.instructions.push(instruction);
+    }
+
+    /// take the recorded instructions, leaving an empty trace
+    pub fn take_trace(&mut self) -> InstructionTrace {
+        std::mem::take(&mut self.trace)
+    }
+}
+
+impl<D: DualModuleImpl> DualModuleImpl for DualModuleTracer<D> {
+    fn new_empty(initializer: &SolverInitializer) -> Self {
+        Self::new(D::new_empty(initializer))
+    }
+
+    fn clear(&mut self) {
+        self.dual_module.clear();
+        self.record(Instruction::Reset);
+    }
+
+    fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+        self.dual_module.add_dual_node(dual_node_ptr);
+        let node = dual_node_ptr.read_recursive();
+        match &node.class {
+            DualNodeClass::DefectVertex { defect_index } => {
+                let vertex_index = *defect_index;
+                drop(node);
+                self.record(Instruction::AddDefect { vertex_index });
+            }
+            DualNodeClass::Blossom { nodes_circle, .. } => {
+                let nodes_circle: Vec<NodeIndex> = nodes_circle
+                    .iter()
+                    .map(|node_weak| node_weak.upgrade_force().read_recursive().index)
+                    .collect();
+                drop(node);
+                // the children are set to stay right before the blossom is created
+                while let Some(Instruction::SetSpeed {
+                    node_index,
+                    grow_state: DualNodeGrowState::Stay,
+                }) = self.trace.instructions.last()
+                {
+                    if !nodes_circle.contains(node_index) {
+                        break;
+                    }
+                    self.trace.instructions.pop();
+                }
+                self.record(Instruction::CreateBlossom { nodes_circle });
+            }
+        }
+    }
+
+    fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+        let node = dual_node_ptr.read_recursive();
+        let node_index = node.index;
+        let children: BTreeSet<NodeIndex> = match &node.class {
+            DualNodeClass::Blossom { nodes_circle, .. } => nodes_circle
+                .iter()
+                .map(|node_weak| node_weak.upgrade_force().read_recursive().index)
+                .collect(),
+            _ => unreachable!("only blossoms can be removed"),
+        };
+        drop(node);
+        self.dual_module.remove_blossom(dual_node_ptr);
+        self.record(Instruction::Expand { node_index });
+        self.expanded_children = children;
+    }
+
+    fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+        self.dual_module.set_grow_state(dual_node_ptr, grow_state);
+        let node_index = dual_node_ptr.read_recursive().index;
+        // the children of an expanded blossom are set to grow right after it's expanded
+        if grow_state == DualNodeGrowState::Grow && self.expanded_children.remove(&node_index) {
+            return;
+        }
+        self.record(Instruction::SetSpeed { node_index, grow_state });
+    }
+
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        self.record(Instruction::FindObstacle);
+        self.dual_module.compute_maximum_update_length()
+    }
+
+    fn grow(&mut self, length: Weight) {
+        self.dual_module.grow(length);
+        self.record(Instruction::Grow { length });
+    }
+
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
+        self.dual_module.load_edge_modifier(edge_modifier)?;
+        for &(edge_index, weight) in edge_modifier.iter() {
+            self.record(Instruction::SetWeight { edge_index, weight });
+        }
+        Ok(())
+    }
+
+    fn get_edge_growth(&self, edge_index: EdgeIndex) -> Result<(Weight, Weight), FusionError> {
+        self.dual_module.get_edge_growth(edge_index)
+    }
+
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        self.dual_module.prepare_nodes_shrink(nodes_circle)
+    }
+
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        self.dual_module.generate_profiler_report()
+    }
+}
+
+/// solve a syndrome pattern with the serial primal module on top of a traced dual module `D`
+pub fn trace_solve<D: DualModuleImpl>(
+    initializer: &SolverInitializer,
+    syndrome_pattern: &SyndromePattern,
+) -> InstructionTrace {
+    let mut dual_module = DualModuleTracer::<D>::new_empty(initializer);
+    let mut primal_module = PrimalModuleSerialPtr::new_empty(initializer);
+    let interface_ptr = DualModuleInterfacePtr::new_empty();
+    if !syndrome_pattern.erasures.is_empty() {
+        dual_module
+            .load_erasures(&syndrome_pattern.erasures)
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+    if !syndrome_pattern.dynamic_weights.is_empty() {
+        dual_module
+            .load_dynamic_weights(&syndrome_pattern.dynamic_weights)
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+    primal_module.solve(
+        &interface_ptr,
+        &SyndromePattern::new_vertices(syndrome_pattern.defect_vertices.clone()),
+        &mut dual_module,
+    );
+    dual_module.take_trace()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dual_module_serial::*;
+    use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
+    use super::*;
+
+    #[test]
+    fn instruction_trace_replay() {
+        // cargo test instruction_trace_replay -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        code.set_erasure_probability(0.05);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let trace = trace_solve::<DualModuleSerial>(&initializer, &syndrome_pattern);
+            let bytes = trace.to_bytes();
+            assert_eq!(InstructionTrace::from_bytes(&bytes).unwrap(), trace);
+            // the trace alone reproduces the same dual solution
+            let mut dual_module = DualModuleSerial::new_empty(&initializer);
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            let obstacles = trace.replay(&interface_ptr, &mut dual_module).unwrap();
+            assert!(
+                obstacles.last().unwrap().is_empty(),
+                "the last query should find nothing to do"
+            );
+            solver.solve(&syndrome_pattern);
+            assert_eq!(interface_ptr.sum_dual_variables(), solver.sum_dual_variables(), "seed {seed}");
+            solver.clear();
+        }
+        assert!(InstructionTrace::from_bytes(&[0, 1, 2, 3]).is_err());
+        assert!(InstructionTrace::from_bytes(&[b'F', b'B', b'I', 1, Instruction::OPCODE_GROW]).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Instrumentation
+//!
+//! Count the operations that a primal module issues to the dual module and the obstacles it gets back, by wrapping any
+//! [`DualModuleImpl`] in [`DualModuleInstrumented`]. It works as the dual module of a serial stack as well as the serial
+//! module of every unit of [`DualModuleParallel`], and the counters are reported in the profiler report of either.
+//!
+//! The counters accumulate across [`DualModuleImpl::clear`], so that the statistics of many shots can be collected;
+//! use [`DualModuleInstrumented::take_counters`] to read and reset them.
+//!
+
+use super::dual_module::*;
+use super::dual_module_parallel::*;
+use super::pointers::*;
+use super::util::*;
+use crate::serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+
+/// the number of each kind of operation and obstacle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DualModuleCounters {
+    /// the number of global grows
+    pub grow: usize,
+    /// the total length of the global grows
+    pub grow_length: Weight,
+    /// the number of grows of individual dual nodes
+    pub grow_dual_node: usize,
+    /// the number of queries of the maximum update length, globally or of individual dual nodes
+    pub find_obstacle: usize,
+    pub conflicting: usize,
+    pub touching_virtual: usize,
+    pub blossom_need_expand: usize,
+    /// including the pending ones waiting to be paired across units
+    pub vertex_shrink_stop: usize,
+    pub defect_created: usize,
+    pub blossom_created: usize,
+    pub blossom_expanded: usize,
+    /// the number of sync requests generated for the mirrored vertices of a partitioned dual module
+    pub sync_requests: usize,
+    /// the number of sync requests executed on this dual module
+    pub sync_events: usize,
+}
+
+impl AddAssign for DualModuleCounters {
+    fn add_assign(&mut self, other: Self) {
+        self.grow += other.grow;
+        self.grow_length += other.grow_length;
+        self.grow_dual_node += other.grow_dual_node;
+        self.find_obstacle += other.find_obstacle;
+        self.conflicting += other.conflicting;
+        self.touching_virtual += other.touching_virtual;
+        self.blossom_need_expand += other.blossom_need_expand;
+        self.vertex_shrink_stop += other.vertex_shrink_stop;
+        self.defect_created += other.defect_created;
+        self.blossom_created += other.blossom_created;
+        self.blossom_expanded += other.blossom_expanded;
+        self.sync_requests += other.sync_requests;
+        self.sync_events += other.sync_events;
+    }
+}
+
+impl DualModuleCounters {
+    /// the total number of obstacles of any type
+    pub fn conflicts(&self) -> usize {
+        self.conflicting + self.touching_virtual + self.blossom_need_expand + self.vertex_shrink_stop
+    }
+
+    fn count_obstacle(&mut self, max_update_length: &MaxUpdateLength) {
+        match max_update_length {
+            MaxUpdateLength::NonZeroGrow(_) => {}
+            MaxUpdateLength::Conflicting(..) => self.conflicting += 1,
+            MaxUpdateLength::TouchingVirtual(..) => self.touching_virtual += 1,
+            MaxUpdateLength::BlossomNeedExpand(_) => self.blossom_need_expand += 1,
+            MaxUpdateLength::VertexShrinkStop(_) => self.vertex_shrink_stop += 1,
+        }
+    }
+}
+
+/// a dual module that forwards every operation to the inner dual module while counting it, see module documentation
+pub struct DualModuleInstrumented<D: DualModuleImpl> {
+    pub dual_module: D,
+    pub counters: DualModuleCounters,
+}
+
+impl<D: DualModuleImpl> DualModuleInstrumented<D> {
+    pub fn new(dual_module: D) -> Self {
+        Self {
+            dual_module,
+            counters: DualModuleCounters::default(),
+        }
+    }
+
+    /// read the counters and reset them to zero
+    pub fn take_counters(&mut self) -> DualModuleCounters {
+        std::mem::take(&mut self.counters)
+    }
+}
+
+impl<D: DualModuleImpl + Send + Sync> DualModuleInstrumented<D> {
+    /// the sum of the counters of all the units of a parallel dual module
+    pub fn parallel_counters(dual_module: &DualModuleParallel<Self>) -> DualModuleCounters {
+        let mut counters = DualModuleCounters::default();
+        for unit_ptr in dual_module.units.iter() {
+            counters += unit_ptr.read_recursive().serial_module.counters;
+        }
+        counters
+    }
+}
+
+impl<D: DualModuleImpl> DualModuleImpl for DualModuleInstrumented<D> {
+    fn new_empty(initializer: &SolverInitializer) -> Self {
+        Self::new(D::new_empty(initializer))
+    }
+
+    fn clear(&mut self) {
+        self.dual_module.clear();
+    }
+
+    fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+        match dual_node_ptr.read_recursive().class {
+            DualNodeClass::DefectVertex { .. } => self.counters.defect_created += 1,
+            DualNodeClass::Blossom { .. } => self.counters.blossom_created += 1,
+        }
+        self.dual_module.add_dual_node(dual_node_ptr);
+    }
+
+    fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+        self.counters.blossom_expanded += 1;
+        self.dual_module.remove_blossom(dual_node_ptr);
+    }
+
+    fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+        self.dual_module.set_grow_state(dual_node_ptr, grow_state);
+    }
+
+    fn compute_maximum_update_length_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+        simultaneous_update: bool,
+    ) -> Result<MaxUpdateLength, FusionError> {
+        self.counters.find_obstacle += 1;
+        let max_update_length =
+            self.dual_module
+                .compute_maximum_update_length_dual_node(dual_node_ptr, is_grow, simultaneous_update)?;
+        self.counters.count_obstacle(&max_update_length);
+        Ok(max_update_length)
+    }
+
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        self.counters.find_obstacle += 1;
+        let group_max_update_length = self.dual_module.compute_maximum_update_length();
+        if let GroupMaxUpdateLength::Conflicts((conflicts, pending_stops)) = &group_max_update_length {
+            for max_update_length in conflicts.iter().chain(pending_stops.values()) {
+                self.counters.count_obstacle(max_update_length);
+            }
+        }
+        group_max_update_length
+    }
+
+    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) -> Result<(), FusionError> {
+        self.counters.grow_dual_node += 1;
+        self.dual_module.grow_dual_node(dual_node_ptr, length)
+    }
+
+    fn grow(&mut self, length: Weight) {
+        self.counters.grow += 1;
+        self.counters.grow_length += length;
+        self.dual_module.grow(length);
+    }
+
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
+        self.dual_module.load_edge_modifier(edge_modifier)
+    }
+
+    fn load_erasures(&mut self, erasures: &[EdgeIndex]) -> Result<(), FusionError> {
+        self.dual_module.load_erasures(erasures)
+    }
+
+    fn load_erasure_probabilities(&mut self, erasures: &[(EdgeIndex, f64)], weight_scale: f64) -> Result<(), FusionError> {
+        self.dual_module.load_erasure_probabilities(erasures, weight_scale)
+    }
+
+    fn load_dynamic_weights(&mut self, dynamic_weights: &[(EdgeIndex, Weight)]) -> Result<(), FusionError> {
+        self.dual_module.load_dynamic_weights(dynamic_weights)
+    }
+
+    fn get_edge_growth(&self, edge_index: EdgeIndex) -> Result<(Weight, Weight), FusionError> {
+        self.dual_module.get_edge_growth(edge_index)
+    }
+
+    fn get_edge_growths(&self, edge_indices: &[EdgeIndex]) -> Result<Vec<(Weight, Weight)>, FusionError> {
+        self.dual_module.get_edge_growths(edge_indices)
+    }
+
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        let sync_requests = self.dual_module.prepare_nodes_shrink(nodes_circle)?;
+        self.counters.sync_requests += sync_requests.len();
+        Ok(sync_requests)
+    }
+
+    /// the report of the inner dual module with the counters as `operation_counters`
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        let mut report = self.dual_module.generate_profiler_report();
+        if let Some(report) = report.as_object_mut() {
+            report.insert("operation_counters".to_string(), json!(self.counters));
+        }
+        report
+    }
+
+    fn check_overflow(&self) -> Result<(), FusionError> {
+        self.dual_module.check_overflow()
+    }
+
+    fn sanity_check(&self, interface_ptr: &DualModuleInterfacePtr) -> Result<(), String> {
+        self.dual_module.sanity_check(interface_ptr)
+    }
+
+    fn new_partitioned(partitioned_initializer: &PartitionedSolverInitializer) -> Result<Self, FusionError> {
+        Ok(Self::new(D::new_partitioned(partitioned_initializer)?))
+    }
+
+    fn prepare_all(&mut self) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        let sync_requests = self.dual_module.prepare_all()?;
+        self.counters.sync_requests += sync_requests.len();
+        Ok(sync_requests)
+    }
+
+    fn prepare_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+    ) -> Result<&mut Vec<SyncRequest>, FusionError> {
+        let sync_requests = self.dual_module.prepare_dual_node(dual_node_ptr, is_grow)?;
+        self.counters.sync_requests += sync_requests.len();
+        Ok(sync_requests)
+    }
+
+    fn execute_sync_event(&mut self, sync_event: &SyncRequest) -> Result<(), FusionError> {
+        self.counters.sync_events += 1;
+        self.dual_module.execute_sync_event(sync_event)
+    }
+
+    fn contains_dual_node(&self, dual_node_ptr: &DualNodePtr) -> Result<bool, FusionError> {
+        self.dual_module.contains_dual_node(dual_node_ptr)
+    }
+
+    fn contains_dual_nodes_any(&self, dual_node_ptrs: &[DualNodePtr]) -> Result<bool, FusionError> {
+        self.dual_module.contains_dual_nodes_any(dual_node_ptrs)
+    }
+
+    fn contains_vertex(&self, vertex_index: VertexIndex) -> Result<bool, FusionError> {
+        self.dual_module.contains_vertex(vertex_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dual_module_serial::*;
+    use super::super::example_codes::*;
+    use super::super::example_partition::*;
+    use super::super::primal_module::*;
+    use super::super::primal_module_parallel::*;
+    use super::super::primal_module_serial::*;
+    use super::*;
+
+    #[test]
+    fn instrumentation_serial_and_parallel() {
+        // cargo test instrumentation_serial_and_parallel -- --nocapture
+        let (d, noisy_measurements) = (7, 10);
+        let mut code = PhenomenologicalRepetitionCode::new(d, noisy_measurements, 0.1, 500);
+        let mut partition = PhenomenologicalRepetitionCodeTimePartition::new(d, noisy_measurements, 2);
+        let partition_info = partition.build_apply(&mut code).info();
+        let initializer = code.get_initializer();
+        let syndrome_pattern = code.generate_random_errors(0);
+        // serial stack
+        let mut dual_module = DualModuleInstrumented::<DualModuleSerial>::new_empty(&initializer);
+        let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        primal_module.solve(&interface_ptr, &syndrome_pattern, &mut dual_module);
+        let serial_weight = interface_ptr.sum_dual_variables();
+        let counters = dual_module.counters;
+        assert_eq!(counters.defect_created, syndrome_pattern.defect_vertices.len());
+        assert!(counters.grow > 0 && counters.conflicts() > 0);
+        assert_eq!(counters.sync_requests, 0);
+        assert_eq!(
+            counters.blossom_created,
+            interface_ptr.read_recursive().nodes_length - counters.defect_created
+        );
+        assert!(counters.blossom_expanded <= counters.blossom_created);
+        let report = dual_module.generate_profiler_report();
+        assert_eq!(report["operation_counters"], json!(counters));
+        assert_eq!(dual_module.take_counters(), counters);
+        assert_eq!(dual_module.counters, DualModuleCounters::default());
+        // parallel stack, where the units exchange sync requests across the fusion boundary
+        let mut dual_module = DualModuleParallel::<DualModuleInstrumented<DualModuleSerial>>::new_config(
+            &initializer,
+            &partition_info,
+            DualModuleParallelConfig::default(),
+        );
+        let mut primal_module =
+            PrimalModuleParallel::new_config(&initializer, &partition_info, PrimalModuleParallelConfig::default());
+        primal_module.parallel_solve(&syndrome_pattern, &dual_module);
+        let last_interface_ptr = primal_module.units.last().unwrap().read_recursive().interface_ptr.clone();
+        assert_eq!(last_interface_ptr.sum_dual_variables(), serial_weight);
+        let counters = DualModuleInstrumented::parallel_counters(&dual_module);
+        // a dual node is also added to the units that mirror its vertices
+        assert!(counters.defect_created >= syndrome_pattern.defect_vertices.len());
+        assert!(counters.sync_requests > 0 && counters.sync_events > 0);
+        assert!(counters.grow > 0 && counters.conflicts() > 0);
+        let report = dual_module.generate_profiler_report();
+        assert_eq!(report["units"].as_array().unwrap().len(), partition_info.units.len());
+        let mut reported = DualModuleCounters::default();
+        for unit_report in report["units"].as_array().unwrap() {
+            reported += serde_json::from_value(unit_report["operation_counters"].clone()).unwrap();
+        }
+        assert_eq!(reported, counters);
+        dual_module.clear();
+        assert_eq!(DualModuleInstrumented::parallel_counters(&dual_module), counters);
+    }
+}
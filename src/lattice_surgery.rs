@@ -0,0 +1,288 @@
+//! Lattice Surgery
+//!
+//! Merge two decoding graphs along a seam, e.g. two surface code patches during lattice surgery. The merged graph keeps
+//! the vertices of each patch in its own partition and puts the seam vertices in between, so that the parallel solvers
+//! decode each patch in its own unit and fuse them along the seam at runtime, without building a new partition plan.
+//!
+
+use super::util::*;
+use crate::serde::{Deserialize, Serialize};
+
+/// the seam between two decoding graphs `a` and `b`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Seam {
+    /// pairs of (vertex in `a`, vertex in `b`) merged into a single real vertex, e.g. the boundary stabilizers measured
+    /// across the seam; usually both of them are virtual before the merge
+    #[serde(default)]
+    pub identified_vertices: Vec<(VertexIndex, VertexIndex)>,
+    /// new edges (vertex in `a`, vertex in `b`, weight) across the seam
+    #[serde(default)]
+    pub edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+}
+
+/// the merged decoding graph of two graphs `a` and `b`; the edges are the edges of `a`, then the edges of `b` and then
+/// the seam edges, each in their original order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeamMerge {
+    pub initializer: SolverInitializer,
+    /// `a` and `b` are the two leaf partitions, and the seam vertices are the interface between them
+    pub partition_config: PartitionConfig,
+    /// the merged vertex index of each vertex in `a`
+    pub vertex_map_a: Vec<VertexIndex>,
+    /// the merged vertex index of each vertex in `b`
+    pub vertex_map_b: Vec<VertexIndex>,
+    /// the number of edges in `a`
+    pub edge_num_a: usize,
+    /// the number of edges in `b`
+    pub edge_num_b: usize,
+}
+
+impl SeamMerge {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(a: &SolverInitializer, b: &SolverInitializer, seam: &Seam) -> Result<Self, String> {
+        if !a.virtual_vertex_groups.is_empty() || !b.virtual_vertex_groups.is_empty() {
+            return Err("virtual vertex groups are not supported".to_string());
+        }
+        let (vertex_num_a, vertex_num_b) = (a.vertex_num as usize, b.vertex_num as usize);
+        let mut in_seam_a = vec![false; vertex_num_a];
+        let mut in_seam_b = vec![false; vertex_num_b];
+        // the vertex in `a` that each vertex in `b` is identified with
+        let mut identified_b: Vec<Option<VertexIndex>> = vec![None; vertex_num_b];
+        let mut identified_a = vec![false; vertex_num_a];
+        for &(vertex_a, vertex_b) in seam.identified_vertices.iter() {
+            if vertex_a as usize >= vertex_num_a || vertex_b as usize >= vertex_num_b {
+                return Err(format!("identified vertices ({vertex_a}, {vertex_b}) out of range"));
+            }
+            if identified_a[vertex_a as usize] || identified_b[vertex_b as usize].is_some() {
+                return Err(format!("identified vertices ({vertex_a}, {vertex_b}) are already identified"));
+            }
+            identified_a[vertex_a as usize] = true;
+            identified_b[vertex_b as usize] = Some(vertex_a);
+            in_seam_a[vertex_a as usize] = true;
+            in_seam_b[vertex_b as usize] = true;
+        }
+        for &(vertex_a, vertex_b, weight) in seam.edges.iter() {
+            if vertex_a as usize >= vertex_num_a || vertex_b as usize >= vertex_num_b {
+                return Err(format!("seam edge ({vertex_a}, {vertex_b}) out of range"));
+            }
+            if weight % 2 != 0 {
+                return Err(format!("seam edge ({vertex_a}, {vertex_b}) has odd weight {weight}"));
+            }
+            in_seam_a[vertex_a as usize] = true;
+            in_seam_b[vertex_b as usize] = true;
+        }
+        for &(left, right, _) in b.weighted_edges.iter() {
+            if identified_b[left as usize].is_some() && identified_b[right as usize].is_some() {
+                return Err(format!(
+                    "edge ({left}, {right}) of b between two identified vertices would duplicate the edge of a"
+                ));
+            }
+        }
+        // order: vertices only in `a`, seam vertices of `a`, seam vertices of `b` not identified, vertices only in `b`
+        let mut vertex_map_a = vec![0; vertex_num_a];
+        let mut vertex_map_b = vec![0; vertex_num_b];
+        let mut vertex_num: VertexIndex = 0;
+        let mut assign = |vertex_map: &mut Vec<VertexIndex>, vertices: &mut dyn Iterator<Item = usize>| {
+            for vertex in vertices {
+                vertex_map[vertex] = vertex_num;
+                vertex_num += 1;
+            }
+            vertex_num
+        };
+        let a_end = assign(&mut vertex_map_a, &mut (0..vertex_num_a).filter(|&vertex| !in_seam_a[vertex]));
+        assign(&mut vertex_map_a, &mut (0..vertex_num_a).filter(|&vertex| in_seam_a[vertex]));
+        let b_start = assign(
+            &mut vertex_map_b,
+            &mut (0..vertex_num_b).filter(|&vertex| in_seam_b[vertex] && identified_b[vertex].is_none()),
+        );
+        let vertex_num = assign(&mut vertex_map_b, &mut (0..vertex_num_b).filter(|&vertex| !in_seam_b[vertex]));
+        for (vertex_b, vertex_a) in identified_b.iter().enumerate() {
+            if let Some(vertex_a) = vertex_a {
+                vertex_map_b[vertex_b] = vertex_map_a[*vertex_a as usize];
+            }
+        }
+        let weighted_edges =
+            (a.weighted_edges
+                .iter()
+                .map(|&(left, right, weight)| (vertex_map_a[left as usize], vertex_map_a[right as usize], weight)))
+            .chain(
+                b.weighted_edges
+                    .iter()
+                    .map(|&(left, right, weight)| (vertex_map_b[left as usize], vertex_map_b[right as usize], weight)),
+            )
+            .chain(seam.edges.iter().map(|&(vertex_a, vertex_b, weight)| {
+                (vertex_map_a[vertex_a as usize], vertex_map_b[vertex_b as usize], weight)
+            }))
+            .collect();
+        // identified vertices become real
+        let virtual_vertices = (a.virtual_vertices.iter())
+            .filter(|&&vertex| !identified_a[vertex as usize])
+            .map(|&vertex| vertex_map_a[vertex as usize])
+            .chain(
+                (b.virtual_vertices.iter())
+                    .filter(|&&vertex| identified_b[vertex as usize].is_none())
+                    .map(|&vertex| vertex_map_b[vertex as usize]),
+            )
+            .collect();
+        let mut initializer = SolverInitializer::new(vertex_num as VertexNum, weighted_edges, virtual_vertices);
+        // the annotations of `a` take precedence on the identified vertices
+        for (&vertex, annotation) in a.vertex_annotations.iter() {
+            initializer
+                .vertex_annotations
+                .insert(vertex_map_a[vertex as usize], annotation.clone());
+        }
+        for (&vertex, annotation) in b.vertex_annotations.iter() {
+            (initializer.vertex_annotations.entry(vertex_map_b[vertex as usize])).or_insert_with(|| annotation.clone());
+        }
+        initializer.edge_annotations = (a
+            .edge_annotations
+            .iter()
+            .map(|(&edge, annotation)| (edge, annotation.clone())))
+        .chain(
+            b.edge_annotations
+                .iter()
+                .map(|(&edge, annotation)| (edge + a.weighted_edges.len() as EdgeIndex, annotation.clone())),
+        )
+        .collect();
+        initializer.edge_fault_ids = (a.edge_fault_ids.iter())
+            .map(|(&edge, fault_ids)| (edge, fault_ids.clone()))
+            .chain(
+                (b.edge_fault_ids.iter())
+                    .map(|(&edge, fault_ids)| (edge + a.weighted_edges.len() as EdgeIndex, fault_ids.clone())),
+            )
+            .collect();
+        let partition_config = PartitionConfig {
+            vertex_num: vertex_num as VertexNum,
+            partitions: vec![VertexRange::new(0, a_end), VertexRange::new(b_start, vertex_num)],
+            fusions: vec![(0, 1)],
+        };
+        Ok(Self {
+            initializer,
+            partition_config,
+            vertex_map_a,
+            vertex_map_b,
+            edge_num_a: a.weighted_edges.len(),
+            edge_num_b: b.weighted_edges.len(),
+        })
+    }
+
+    /// the syndrome of the merged graph, given the syndromes of `a` and `b` and the defect vertices in the merged
+    /// graph measured across the seam
+    #[allow(clippy::unnecessary_cast)]
+    pub fn syndrome(
+        &self,
+        a: &SyndromePattern,
+        b: &SyndromePattern,
+        seam_defect_vertices: &[VertexIndex],
+    ) -> SyndromePattern {
+        let mut defect_vertices: Vec<VertexIndex> =
+            (a.defect_vertices.iter().map(|&vertex| self.vertex_map_a[vertex as usize]))
+                .chain(b.defect_vertices.iter().map(|&vertex| self.vertex_map_b[vertex as usize]))
+                .chain(seam_defect_vertices.iter().cloned())
+                .collect();
+        defect_vertices.sort();
+        let erasures = (a.erasures.iter().cloned())
+            .chain(b.erasures.iter().map(|&edge_index| edge_index + self.edge_num_a as EdgeIndex))
+            .collect();
+        SyndromePattern::new(defect_vertices, erasures)
+    }
+
+    /// split a subgraph of the merged graph into the edges of `a`, the edges of `b` and the indices of the seam edges
+    #[allow(clippy::unnecessary_cast)]
+    pub fn split_subgraph(&self, subgraph: &[EdgeIndex]) -> (Vec<EdgeIndex>, Vec<EdgeIndex>, Vec<usize>) {
+        let (mut subgraph_a, mut subgraph_b, mut seam_edges) = (vec![], vec![], vec![]);
+        for &edge_index in subgraph.iter() {
+            let edge_index = edge_index as usize;
+            if edge_index < self.edge_num_a {
+                subgraph_a.push(edge_index as EdgeIndex);
+            } else if edge_index < self.edge_num_a + self.edge_num_b {
+                subgraph_b.push((edge_index - self.edge_num_a) as EdgeIndex);
+            } else {
+                seam_edges.push(edge_index - self.edge_num_a - self.edge_num_b);
+            }
+        }
+        (subgraph_a, subgraph_b, seam_edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
+    use super::*;
+    use crate::serde_json::json;
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn lattice_surgery_merge_planar_codes() {
+        // cargo test lattice_surgery_merge_planar_codes -- --nocapture
+        let d = 7;
+        let code = CodeCapacityPlanarCode::new(d, 0.1, 500);
+        let patch = code.get_initializer();
+        let column = |j: f64| -> Vec<VertexIndex> {
+            let mut vertices: Vec<_> = (0..code.vertices.len())
+                .filter(|&vertex| code.vertices[vertex].is_virtual && code.vertices[vertex].position.j == j)
+                .collect();
+            vertices.sort_by(|&x, &y| code.vertices[x].position.i.partial_cmp(&code.vertices[y].position.i).unwrap());
+            vertices.into_iter().map(|vertex| vertex as VertexIndex).collect()
+        };
+        let virtual_js = || (code.vertices.iter().filter(|vertex| vertex.is_virtual)).map(|vertex| vertex.position.j);
+        let (min_j, max_j) = (virtual_js().fold(f64::MAX, f64::min), virtual_js().fold(f64::MIN, f64::max));
+        let (right, left) = (column(max_j), column(min_j));
+        assert_eq!((right.len(), left.len()), (d as usize, d as usize));
+        // the right boundary of `a` is merged with the left boundary of `b`
+        let seam = Seam {
+            identified_vertices: right.iter().cloned().zip(left.iter().cloned()).collect(),
+            edges: vec![],
+        };
+        let merge = SeamMerge::new(&patch, &patch, &seam).unwrap();
+        let vertex_num = 2 * patch.vertex_num - d as VertexNum;
+        assert_eq!(merge.initializer.vertex_num, vertex_num);
+        assert_eq!(
+            merge.initializer.virtual_vertices.len(),
+            2 * patch.virtual_vertices.len() - 2 * d as usize
+        );
+        assert!(SeamMerge::new(
+            &patch,
+            &patch,
+            &Seam {
+                identified_vertices: vec![(right[0], left[0]), (right[0], left[1])],
+                edges: vec![]
+            }
+        )
+        .is_err());
+        let mut serial_solver = SolverSerial::new(&merge.initializer);
+        let partition_info = merge.partition_config.info();
+        let mut parallel_solver = SolverParallel::new(&merge.initializer, &partition_info, json!({}));
+        let (mut code_a, mut code_b) = (code.clone(), code.clone());
+        for seed in 0..50 {
+            let syndrome_a = code_a.generate_random_errors(seed);
+            let syndrome_b = code_b.generate_random_errors(seed + 1000);
+            // the merged stabilizers are measured as defects if a random error happens on the seam
+            let seam_defect_vertices: Vec<VertexIndex> = if seed % 3 == 0 {
+                vec![merge.vertex_map_a[right[1] as usize], merge.vertex_map_a[right[4] as usize]]
+            } else {
+                vec![]
+            };
+            let syndrome_pattern = merge.syndrome(&syndrome_a, &syndrome_b, &seam_defect_vertices);
+            serial_solver.solve(&syndrome_pattern);
+            parallel_solver.solve(&syndrome_pattern);
+            let subgraph = serial_solver.subgraph();
+            assert_eq!(
+                merge.initializer.syndrome_of(&subgraph),
+                syndrome_pattern.defect_vertices.iter().cloned().collect()
+            );
+            assert_eq!(
+                serial_solver.sum_dual_variables(),
+                parallel_solver.sum_dual_variables(),
+                "seed {seed}"
+            );
+            let (subgraph_a, subgraph_b, seam_edges) = merge.split_subgraph(&subgraph);
+            assert_eq!(subgraph_a.len() + subgraph_b.len(), subgraph.len());
+            assert!(seam_edges.is_empty());
+            serial_solver.clear();
+            parallel_solver.clear();
+        }
+    }
+}
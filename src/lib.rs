@@ -1,50 +1,117 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(feature = "unsafe_pointer", feature(get_mut_unchecked))]
 #![cfg_attr(feature = "unsafe_pointer", allow(unused_mut))]
 #![cfg_attr(feature = "python_binding", feature(cfg_eval))]
 
+#[cfg(feature = "std")]
 extern crate cfg_if;
+#[cfg(feature = "std")]
 extern crate libc;
+#[cfg(feature = "std")]
 extern crate parking_lot;
+#[cfg(feature = "std")]
 extern crate priority_queue;
+#[cfg(feature = "std")]
 extern crate rand_xoshiro;
+#[cfg(feature = "std")]
 extern crate serde;
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate serde_json;
+#[cfg(feature = "std")]
 extern crate chrono;
+#[cfg(feature = "std")]
 extern crate clap;
+#[cfg(feature = "std")]
 extern crate core_affinity;
+#[cfg(feature = "std")]
 extern crate derivative;
+#[cfg(feature = "std")]
 extern crate pbr;
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 extern crate petgraph;
 #[cfg(feature = "python_binding")]
 extern crate pyo3;
 #[cfg(feature = "qecp_integrate")]
 pub extern crate qecp;
+#[cfg(feature = "std")]
 extern crate rand;
+#[cfg(feature = "std")]
 extern crate rayon;
+#[cfg(feature = "std")]
+extern crate toml;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "std")]
 extern crate urlencoding;
+#[cfg(feature = "std")]
 extern crate weak_table;
 
+#[cfg(feature = "std")]
 pub mod blossom_v;
+#[cfg(feature = "std")]
 pub mod cli;
+#[cfg(feature = "std")]
 pub mod complete_graph;
+#[cfg(feature = "std")]
 pub mod dual_module;
+#[cfg(feature = "std")]
+pub mod dual_module_distributed;
+#[cfg(feature = "std")]
 pub mod dual_module_parallel;
+#[cfg(feature = "std")]
+pub mod dual_module_plugin;
+#[cfg(feature = "std")]
 pub mod dual_module_serial;
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod ensemble;
+#[cfg(feature = "std")]
 pub mod example_codes;
+#[cfg(feature = "std")]
 pub mod example_partition;
+#[cfg(feature = "std")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod hypergraph;
+#[cfg(feature = "std")]
+pub mod instruction_trace;
+#[cfg(feature = "std")]
+pub mod instrumentation;
+#[cfg(feature = "std")]
+pub mod lattice_surgery;
+#[cfg(feature = "std")]
+pub mod matching;
+#[cfg(feature = "std")]
 pub mod mwpm_solver;
+#[cfg(feature = "std")]
+pub mod partition_tuner;
+#[cfg(feature = "std")]
 pub mod pointers;
+#[cfg(feature = "std")]
 pub mod primal_module;
+#[cfg(feature = "std")]
 pub mod primal_module_parallel;
+#[cfg(feature = "std")]
 pub mod primal_module_serial;
+#[cfg(feature = "std")]
+pub mod pymatching;
+#[cfg(feature = "std")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod supervisor;
+#[cfg(feature = "std")]
 pub mod util;
+#[cfg(feature = "std")]
+pub mod verifier;
+#[cfg(feature = "std")]
 pub mod visualize;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
 
+#[cfg(feature = "std")]
 use complete_graph::*;
+#[cfg(feature = "std")]
 use util::*;
 
 #[cfg(feature = "python_binding")]
@@ -66,6 +133,7 @@ fn fusion_blossom(py: Python<'_>, m: &PyModule) -> PyResult<()> {
     Ok(())
 }
 
+#[cfg(feature = "std")]
 /// use fusion blossom to solve MWPM (to optimize speed, consider reuse a [`mwpm_solver::SolverSerial`] object)
 #[allow(clippy::unnecessary_cast)]
 pub fn fusion_mwpm(initializer: &SolverInitializer, syndrome_pattern: &SyndromePattern) -> Vec<VertexIndex> {
@@ -84,6 +152,7 @@ pub fn fusion_mwpm(initializer: &SolverInitializer, syndrome_pattern: &SyndromeP
     mwpm_solver::LegacySolverSerial::mwpm_solve(initializer, syndrome_pattern)
 }
 
+#[cfg(feature = "std")]
 /// fall back to use blossom V library to solve MWPM (install blossom V required)
 #[allow(clippy::unnecessary_cast)]
 pub fn blossom_v_mwpm(initializer: &SolverInitializer, defect_vertices: &Vec<VertexIndex>) -> Vec<VertexIndex> {
@@ -106,6 +175,7 @@ pub fn blossom_v_mwpm(initializer: &SolverInitializer, defect_vertices: &Vec<Ver
     blossom_v_mwpm_reuse(&mut complete_graph, initializer, defect_vertices)
 }
 
+#[cfg(feature = "std")]
 #[allow(clippy::unnecessary_cast)]
 pub fn blossom_v_mwpm_reuse(
     complete_graph: &mut CompleteGraph,
@@ -188,6 +258,7 @@ pub fn blossom_v_mwpm_reuse(
     mwpm_result
 }
 
+#[cfg(feature = "std")]
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct DetailedMatching {
@@ -201,6 +272,7 @@ pub struct DetailedMatching {
     pub weight: Weight,
 }
 
+#[cfg(feature = "std")]
 /// compute detailed matching information, note that the output will not include duplicated matched pairs
 #[allow(clippy::unnecessary_cast)]
 pub fn detailed_matching(
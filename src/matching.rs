@@ -0,0 +1,143 @@
+//! Matching
+//!
+//! Compare two [`PerfectMatching`] results of the same syndrome, e.g. from the serial and the parallel solver or from two
+//! decoder configurations. Two minimum-weight perfect matchings may differ; their symmetric difference then consists of
+//! cycles and paths of zero total weight change.
+//!
+
+use super::dual_module::*;
+use super::pointers::*;
+use super::primal_module::*;
+use super::util::*;
+use crate::serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// a matched pair `(defect_vertex, peer)` where peer is either another defect vertex or a virtual vertex, with the weight
+/// of the minimum-weight path between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MatchedPair {
+    pub vertex_1: VertexIndex,
+    pub vertex_2: VertexIndex,
+    pub weight: Weight,
+}
+
+/// the difference between two perfect matchings `a` and `b`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchingDiff {
+    /// pairs matched in `a` but not in `b`
+    pub only_in_a: Vec<MatchedPair>,
+    /// pairs matched in `b` but not in `a`
+    pub only_in_b: Vec<MatchedPair>,
+    /// the total weight of the subgraph of `a`
+    pub weight_a: Weight,
+    /// the total weight of the subgraph of `b`
+    pub weight_b: Weight,
+    /// edges in exactly one of the two subgraphs, which form cycles and paths
+    pub symmetric_difference: Vec<EdgeIndex>,
+}
+
+impl MatchingDiff {
+    /// `weight_b - weight_a`, which is 0 if both are minimum-weight
+    pub fn weight_delta(&self) -> Weight {
+        self.weight_b - self.weight_a
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty()
+    }
+}
+
+fn defect_vertex(dual_node_ptr: &DualNodePtr) -> VertexIndex {
+    let node = dual_node_ptr.read_recursive();
+    if let DualNodeClass::DefectVertex { defect_index } = &node.class {
+        *defect_index
+    } else {
+        unreachable!("can only be syndrome")
+    }
+}
+
+/// the matched pairs of a perfect matching, with a peer matching sorted by vertex index
+fn matched_pairs(perfect_matching: &PerfectMatching, subgraph_builder: &mut SubGraphBuilder) -> BTreeSet<MatchedPair> {
+    let vertex_pairs = perfect_matching
+        .peer_matchings
+        .iter()
+        .map(|(ptr_1, ptr_2)| {
+            let (vertex_1, vertex_2) = (defect_vertex(ptr_1), defect_vertex(ptr_2));
+            (std::cmp::min(vertex_1, vertex_2), std::cmp::max(vertex_1, vertex_2))
+        })
+        .chain(
+            perfect_matching
+                .virtual_matchings
+                .iter()
+                .map(|(ptr, virtual_vertex)| (defect_vertex(ptr), *virtual_vertex)),
+        );
+    vertex_pairs
+        .map(|(vertex_1, vertex_2)| MatchedPair {
+            vertex_1,
+            vertex_2,
+            weight: subgraph_builder.complete_graph.get_path(vertex_1, vertex_2).1,
+        })
+        .collect()
+}
+
+/// compare two perfect matchings of the same syndrome on the decoding graph of `initializer`
+pub fn diff(a: &PerfectMatching, b: &PerfectMatching, initializer: &SolverInitializer) -> MatchingDiff {
+    let mut subgraph_builder = SubGraphBuilder::new(initializer);
+    let pairs_a = matched_pairs(a, &mut subgraph_builder);
+    let pairs_b = matched_pairs(b, &mut subgraph_builder);
+    // build the subgraphs from the normalized pairs, because the path between two vertices depends on the direction
+    let mut build_subgraph = |pairs: &BTreeSet<MatchedPair>| {
+        subgraph_builder.subgraph.clear();
+        for pair in pairs.iter() {
+            subgraph_builder.add_matching(pair.vertex_1, pair.vertex_2);
+        }
+        (subgraph_builder.total_weight(), subgraph_builder.subgraph.clone())
+    };
+    let (weight_a, subgraph_a) = build_subgraph(&pairs_a);
+    let (weight_b, subgraph_b) = build_subgraph(&pairs_b);
+    MatchingDiff {
+        only_in_a: pairs_a.difference(&pairs_b).copied().collect(),
+        only_in_b: pairs_b.difference(&pairs_a).copied().collect(),
+        weight_a,
+        weight_b,
+        symmetric_difference: subgraph_a.symmetric_difference(&subgraph_b).copied().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
+    use super::*;
+    use crate::serde_json::json;
+
+    #[test]
+    fn matching_diff_serial_parallel() {
+        // cargo test matching_diff_serial_parallel -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let partition_info = PartitionConfig::new(initializer.vertex_num).info();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut parallel_solver = SolverDualParallel::new(&initializer, &partition_info, json!({}));
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            parallel_solver.solve(&syndrome_pattern);
+            let serial_matching = serial_solver.perfect_matching();
+            let parallel_matching = parallel_solver.perfect_matching();
+            let same = diff(&serial_matching, &serial_matching, &initializer);
+            assert!(same.is_identical() && same.symmetric_difference.is_empty(), "seed {seed}");
+            let matching_diff = diff(&serial_matching, &parallel_matching, &initializer);
+            assert_eq!(matching_diff.weight_delta(), 0, "seed {seed}");
+            assert_eq!(matching_diff.weight_a, serial_solver.sum_dual_variables(), "seed {seed}");
+            let only_in_a_weight: Weight = matching_diff.only_in_a.iter().map(|pair| pair.weight).sum();
+            let only_in_b_weight: Weight = matching_diff.only_in_b.iter().map(|pair| pair.weight).sum();
+            assert_eq!(only_in_a_weight, only_in_b_weight, "seed {seed}");
+            if matching_diff.is_identical() {
+                assert!(matching_diff.symmetric_difference.is_empty(), "seed {seed}");
+            }
+            serial_solver.clear();
+            parallel_solver.clear();
+        }
+    }
+}
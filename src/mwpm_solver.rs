@@ -4,27 +4,31 @@
 //! Note that you can call different primal and dual modules, even interchangeably, by following the examples in this file
 //!
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufWriter;
+use std::sync::{Arc, Condvar, Mutex};
 
 use nonzero::nonzero as nz;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::blossom_v;
 use crate::complete_graph::*;
 use crate::derivative::Derivative;
 use crate::dual_module::*;
+use crate::rand_xoshiro::rand_core::{RngCore, SeedableRng};
+use crate::rayon::prelude::*;
 
 use super::dual_module::{DualModuleImpl, DualModuleInterfacePtr};
 use super::dual_module_parallel::*;
-use super::dual_module_serial::DualModuleSerial;
+use super::dual_module_serial::{DualModuleSerial, DualModuleSerialCheckpoint, DualNodeInternal, Edge, EdgeWeak, Vertex};
 use super::pointers::*;
-use super::primal_module::{PerfectMatching, PrimalModuleImpl, SubGraphBuilder, VisualizeSubgraph};
+use super::primal_module::{PerfectMatching, PrimalModuleImpl, RoundSummary, SubGraphBuilder, VisualizeSubgraph};
 use super::primal_module_parallel::*;
-use super::primal_module_serial::PrimalModuleSerialPtr;
+use super::primal_module_serial::{PrimalModuleSerialCheckpoint, PrimalModuleSerialPtr, PrimalNodeInternal};
 use super::util::*;
 use super::visualize::*;
 
@@ -145,6 +149,21 @@ pub trait PrimalDualSolver {
     fn solve(&mut self, syndrome_pattern: &SyndromePattern) {
         self.solve_visualizer(syndrome_pattern, None)
     }
+    /// validate the syndrome before solving it, see [`SyndromeValidator`]; nothing is solved if it's invalid.
+    /// The accumulated weights are checked for overflow after solving
+    fn solve_checked(
+        &mut self,
+        syndrome_pattern: &SyndromePattern,
+        validator: &SyndromeValidator,
+    ) -> Result<(), FusionError> {
+        let syndrome_pattern = validator.validate(syndrome_pattern)?;
+        self.solve(&syndrome_pattern);
+        self.check_overflow()
+    }
+    /// report the first overflow of the accumulated weights since the last clear, see [`WeightOverflow`]
+    fn check_overflow(&self) -> Result<(), FusionError> {
+        Ok(())
+    }
     fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching;
     fn perfect_matching(&mut self) -> PerfectMatching {
         self.perfect_matching_visualizer(None)
@@ -155,6 +174,18 @@ pub trait PrimalDualSolver {
     }
     fn sum_dual_variables(&self) -> Weight;
     fn generate_profiler_report(&self) -> serde_json::Value;
+    /// the summary of each round in the last solve, or `None` if the solver is not configured to record them
+    fn round_summaries(&self) -> Option<Vec<RoundSummary>> {
+        None
+    }
+    /// the size of the internal state that persists across [`PrimalDualSolver::clear`], or `None` if not measured
+    fn state_size(&self) -> Option<SolverStateSize> {
+        None
+    }
+    /// the estimated memory usage of the solver, broken down per partition unit if partitioned, or `None` if not measured
+    fn memory_usage(&self) -> Option<MemoryReport> {
+        None
+    }
     #[allow(clippy::unnecessary_cast)]
     fn stim_integration_predict_bit_packed_data(
         &mut self,
@@ -242,6 +273,14 @@ macro_rules! bind_trait_primal_dual_solver {
             fn trait_generate_profiler_report(&self) -> PyObject {
                 json_to_pyobject(self.generate_profiler_report())
             }
+            #[pyo3(name = "round_summaries")]
+            fn trait_round_summaries(&self) -> Option<Vec<RoundSummary>> {
+                self.round_summaries()
+            }
+            #[pyo3(name = "memory_usage")]
+            fn trait_memory_usage(&self) -> Option<PyObject> {
+                self.memory_usage().map(|report| json_to_pyobject(json!(report)))
+            }
             #[pyo3(name = "stim_integration_predict_bit_packed_data")]
             fn trait_stim_integration_predict_bit_packed_data(
                 &mut self,
@@ -265,6 +304,12 @@ pub struct SolverSerial {
     pub primal_module: PrimalModuleSerialPtr,
     pub interface_ptr: DualModuleInterfacePtr,
     pub subgraph_builder: SubGraphBuilder,
+    /// the state saved by [`SolverSerial::begin_transaction`], to be restored by [`SolverSerial::rollback`]
+    transaction: Option<SolverSerialCheckpoint>,
+    /// the number of edges in the original decoding graph; the following ones enforce the virtual vertex groups
+    edge_num: usize,
+    /// the constraint vertices of odd virtual vertex groups, which are defects in every syndrome
+    constraint_defects: Vec<VertexIndex>,
 }
 
 bind_trait_fusion_visualizer!(SolverSerial);
@@ -284,24 +329,250 @@ bind_trait_primal_dual_solver! {SolverSerial}
 #[pymethods]
 impl SolverSerial {
     #[new]
-    #[pyo3(signature = (initializer, *, max_tree_size = None))]
-    pub fn new_python(initializer: &SolverInitializer, max_tree_size: Option<usize>) -> Self {
+    #[pyo3(signature = (initializer, *, max_tree_size = None, early_termination = false, max_blossom_depth = None, record_rounds = false, blossom_deferral = 0))]
+    pub fn new_python(
+        initializer: &SolverInitializer,
+        max_tree_size: Option<usize>,
+        early_termination: bool,
+        max_blossom_depth: Option<usize>,
+        record_rounds: bool,
+        blossom_deferral: Weight,
+    ) -> Self {
         let mut solver = Self::new(initializer);
         if let Some(max_tree_size) = max_tree_size {
             solver.primal_module.write().max_tree_size = max_tree_size;
         }
+        if let Some(max_blossom_depth) = max_blossom_depth {
+            solver.primal_module.write().max_blossom_depth = max_blossom_depth;
+        }
+        solver.primal_module.write().early_termination = early_termination;
+        solver.primal_module.write().blossom_deferral = blossom_deferral;
+        if record_rounds {
+            solver.primal_module.write().round_summaries = Some(vec![]);
+        }
         solver
     }
 }
 
 impl SolverSerial {
     pub fn new(initializer: &SolverInitializer) -> Self {
+        let edge_num = initializer.weighted_edges.len();
+        let (expanded_initializer, constraint_defects);
+        let initializer = if initializer.virtual_vertex_groups.is_empty() {
+            constraint_defects = vec![];
+            initializer
+        } else {
+            (expanded_initializer, constraint_defects) = initializer.expand_virtual_vertex_groups();
+            &expanded_initializer
+        };
         Self {
             dual_module: DualModuleSerial::new_empty(initializer),
             primal_module: PrimalModuleSerialPtr::new_empty(initializer),
-            interface_ptr: DualModuleInterfacePtr::new_empty(),
+            interface_ptr: DualModuleInterfacePtr::new_capacity(initializer),
             subgraph_builder: SubGraphBuilder::new(initializer),
+            transaction: None,
+            edge_num,
+            constraint_defects,
+        }
+    }
+
+    /// set the behavior when the sum of dual variables or the growth of an edge overflows, see [`WeightOverflow`]
+    pub fn set_weight_overflow(&mut self, weight_overflow: WeightOverflow) {
+        self.interface_ptr.write().weight_overflow = weight_overflow;
+        self.dual_module.weight_overflow = weight_overflow;
+    }
+
+    /// add the constraint vertices of odd virtual vertex groups to the syndrome
+    fn constrained_syndrome<'a>(&self, syndrome_pattern: &'a SyndromePattern) -> std::borrow::Cow<'a, SyndromePattern> {
+        if self.constraint_defects.is_empty() {
+            return std::borrow::Cow::Borrowed(syndrome_pattern);
+        }
+        let mut syndrome_pattern = syndrome_pattern.clone();
+        syndrome_pattern
+            .defect_vertices
+            .extend(self.constraint_defects.iter().cloned());
+        std::borrow::Cow::Owned(syndrome_pattern)
+    }
+
+    /// estimate the static memory footprint of a serial solver for this decoding graph, without constructing it;
+    /// the number of dual nodes is bounded by twice the number of real vertices (defect vertices and blossoms)
+    #[allow(clippy::unnecessary_cast)]
+    pub fn memory_footprint(initializer: &SolverInitializer) -> SolverSerialFootprint {
+        use std::mem::size_of;
+        let vertex_num = initializer.vertex_num as usize;
+        let edge_num = initializer.weighted_edges.len();
+        let max_node_num = 2 * (vertex_num - initializer.virtual_vertices.len());
+        let pointer = size_of::<usize>();
+        let vertices = vertex_num * (MemoryReport::shared::<Vertex>() + pointer) + 2 * edge_num * size_of::<EdgeWeak>();
+        let edges = edge_num * (MemoryReport::shared::<Edge>() + pointer);
+        let dual_nodes =
+            max_node_num * (MemoryReport::shared::<DualNode>() + MemoryReport::shared::<DualNodeInternal>() + 2 * pointer);
+        let primal_nodes = max_node_num * (MemoryReport::shared::<PrimalNodeInternal>() + pointer);
+        SolverSerialFootprint {
+            vertex_num,
+            edge_num,
+            max_node_num,
+            vertices,
+            edges,
+            dual_nodes,
+            primal_nodes,
+            total: vertices + edges + dual_nodes + primal_nodes,
+        }
+    }
+}
+
+/// the estimated memory usage of a [`SolverSerial`] in bytes, see [`SolverSerial::memory_footprint`];
+/// temporary buffers that depend on the syndrome (e.g. boundaries of dual nodes) are not included, which are measured
+/// by [`PrimalDualSolver::memory_usage`] once the solver is constructed
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverSerialFootprint {
+    pub vertex_num: usize,
+    pub edge_num: usize,
+    pub max_node_num: usize,
+    pub vertices: usize,
+    pub edges: usize,
+    pub dual_nodes: usize,
+    pub primal_nodes: usize,
+    pub total: usize,
+}
+
+impl SolverSerialFootprint {
+    /// whether the solver fits in a RAM budget given in bytes
+    pub fn fits(&self, ram_budget: usize) -> bool {
+        self.total <= ram_budget
+    }
+}
+
+/// the node storage of a solver, which a long-running service expects to stay bounded across millions of clears;
+/// a growing size indicates a state leak, see [`crate::supervisor::SolverSupervisor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct SolverStateSize {
+    /// the allocated capacity of the node vectors in the interfaces, dual modules and primal modules
+    pub allocated_nodes: usize,
+    /// the nodes still indexed, which should be 0 after a clear
+    pub retained_nodes: usize,
+}
+
+impl std::ops::AddAssign for SolverStateSize {
+    fn add_assign(&mut self, other: Self) {
+        self.allocated_nodes += other.allocated_nodes;
+        self.retained_nodes += other.retained_nodes;
+    }
+}
+
+/// serializable state of a [`SolverSerial`] in the middle of (or after) solving, see [`SolverSerial::checkpoint`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolverSerialCheckpoint {
+    pub interface: DualModuleInterfaceCheckpoint,
+    pub dual_module: DualModuleSerialCheckpoint,
+    pub primal_module: PrimalModuleSerialCheckpoint,
+}
+
+impl SolverSerialCheckpoint {
+    /// take a checkpoint of the modules directly, e.g. in the callback of [`PrimalModuleImpl::solve_step_callback`]
+    pub fn new(
+        interface_ptr: &DualModuleInterfacePtr,
+        dual_module: &DualModuleSerial,
+        primal_module: &PrimalModuleSerialPtr,
+    ) -> Self {
+        Self {
+            interface: interface_ptr.checkpoint(),
+            dual_module: dual_module.checkpoint(),
+            primal_module: primal_module.checkpoint(),
+        }
+    }
+
+    pub fn save(&self, filepath: &str) -> std::io::Result<()> {
+        let file = File::create(filepath)?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn load(filepath: &str) -> std::io::Result<Self> {
+        let file = File::open(filepath)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+}
+
+impl SolverSerial {
+    pub fn checkpoint(&self) -> SolverSerialCheckpoint {
+        SolverSerialCheckpoint::new(&self.interface_ptr, &self.dual_module, &self.primal_module)
+    }
+
+    /// restore a checkpoint taken from a solver with the same initializer; call [`SolverSerial::resume`] to finish the solve
+    #[allow(clippy::unnecessary_cast)]
+    pub fn load_checkpoint(&mut self, checkpoint: &SolverSerialCheckpoint) {
+        self.clear();
+        self.interface_ptr = DualModuleInterfacePtr::new_checkpoint(&checkpoint.interface);
+        self.dual_module.load_checkpoint(&checkpoint.dual_module, &self.interface_ptr);
+        self.primal_module
+            .load_checkpoint(&checkpoint.primal_module, &self.interface_ptr);
+        let dynamic_weights: Vec<(EdgeIndex, Weight)> = checkpoint
+            .dual_module
+            .modified_edges
+            .iter()
+            .map(|&(edge_index, _)| (edge_index, checkpoint.dual_module.edges[edge_index as usize].weight))
+            .collect();
+        if !dynamic_weights.is_empty() {
+            self.subgraph_builder.load_dynamic_weights(&dynamic_weights);
+        }
+    }
+
+    /// continue solving from the current state, e.g. after [`SolverSerial::load_checkpoint`]
+    pub fn resume(&mut self) {
+        self.primal_module
+            .solve_step_callback_interface_loaded(&self.interface_ptr, &mut self.dual_module, |_, _, _, _| {});
+    }
+
+    /// load the syndrome without solving it, so that the caller can drive the solving loop by [`SolverSerial::step`]
+    pub fn load_syndrome(&mut self, syndrome_pattern: &SyndromePattern) {
+        if !syndrome_pattern.erasures.is_empty() {
+            self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+        }
+        if !syndrome_pattern.dynamic_weights.is_empty() {
+            self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        }
+        self.interface_ptr
+            .load(&self.constrained_syndrome(syndrome_pattern), &mut self.dual_module);
+        self.primal_module.load(&self.interface_ptr);
+    }
+
+    /// run a single grow or resolve round of the solving loop; return false if it's already solved
+    pub fn step(&mut self) -> bool {
+        let group_max_update_length = self.dual_module.compute_maximum_update_length();
+        if group_max_update_length.is_empty() {
+            return self
+                .primal_module
+                .resume_deferred(&self.interface_ptr, &mut self.dual_module, true);
+        }
+        self.primal_module.record_round(&group_max_update_length);
+        if let Some(length) = group_max_update_length.get_none_zero_growth() {
+            self.interface_ptr.grow(length, &mut self.dual_module);
+            self.primal_module
+                .resume_deferred(&self.interface_ptr, &mut self.dual_module, false);
+        } else {
+            self.primal_module
+                .resolve(group_max_update_length, &self.interface_ptr, &mut self.dual_module);
         }
+        true
+    }
+
+    /// save the current state so that the following rounds can be undone by [`SolverSerial::rollback`];
+    /// this takes a full checkpoint, so it costs O(|V| + |E|) and is meant for look-ahead strategies rather than every round
+    pub fn begin_transaction(&mut self) {
+        self.transaction = Some(self.checkpoint());
+    }
+
+    /// keep the changes since [`SolverSerial::begin_transaction`]
+    pub fn commit(&mut self) {
+        self.transaction = None;
+    }
+
+    /// undo all the rounds since [`SolverSerial::begin_transaction`], restoring dual variables, grow states and removing
+    /// newly created blossoms; note that all existing [`DualNodePtr`] are invalidated because the interface is rebuilt
+    pub fn rollback(&mut self) {
+        let checkpoint = self.transaction.take().expect("no transaction to rollback");
+        self.load_checkpoint(&checkpoint);
     }
 }
 
@@ -311,6 +582,7 @@ impl PrimalDualSolver for SolverSerial {
         self.dual_module.clear();
         self.interface_ptr.clear();
         self.subgraph_builder.clear();
+        self.transaction = None;
     }
     fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
         if !syndrome_pattern.erasures.is_empty() {
@@ -323,8 +595,9 @@ impl PrimalDualSolver for SolverSerial {
         if !syndrome_pattern.dynamic_weights.is_empty() {
             self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
         }
+        let syndrome_pattern = self.constrained_syndrome(syndrome_pattern);
         self.primal_module
-            .solve_visualizer(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, visualizer);
+            .solve_visualizer(&self.interface_ptr, &syndrome_pattern, &mut self.dual_module, visualizer);
     }
     fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
         let perfect_matching = self
@@ -340,10 +613,13 @@ impl PrimalDualSolver for SolverSerial {
         }
         perfect_matching
     }
+    #[allow(clippy::unnecessary_cast)]
     fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
         let perfect_matching = self.perfect_matching();
         self.subgraph_builder.load_perfect_matching(&perfect_matching);
-        let subgraph = self.subgraph_builder.get_subgraph();
+        let mut subgraph = self.subgraph_builder.get_subgraph();
+        // the zero-weight edges of virtual vertex groups are not in the original decoding graph
+        subgraph.retain(|&edge_index| (edge_index as usize) < self.edge_num);
         if let Some(visualizer) = visualizer {
             visualizer
                 .snapshot_combined(
@@ -362,12 +638,34 @@ impl PrimalDualSolver for SolverSerial {
     fn sum_dual_variables(&self) -> Weight {
         self.interface_ptr.read_recursive().sum_dual_variables
     }
+    fn check_overflow(&self) -> Result<(), FusionError> {
+        self.interface_ptr.check_overflow()?;
+        self.dual_module.check_overflow()
+    }
     fn generate_profiler_report(&self) -> serde_json::Value {
         json!({
             "dual": self.dual_module.generate_profiler_report(),
             "primal": self.primal_module.generate_profiler_report(),
+            "interface": self.interface_ptr.generate_profiler_report(),
+        })
+    }
+    fn round_summaries(&self) -> Option<Vec<RoundSummary>> {
+        self.primal_module.read_recursive().round_summaries.clone()
+    }
+    fn state_size(&self) -> Option<SolverStateSize> {
+        let interface = self.interface_ptr.read_recursive();
+        let primal_module = self.primal_module.read_recursive();
+        Some(SolverStateSize {
+            allocated_nodes: interface.nodes.capacity() + self.dual_module.nodes.capacity() + primal_module.nodes.capacity(),
+            retained_nodes: interface.nodes_length + self.dual_module.nodes_length + primal_module.nodes_length,
         })
     }
+    fn memory_usage(&self) -> Option<MemoryReport> {
+        let mut report = self.dual_module.memory_usage();
+        report += self.interface_ptr.read_recursive().memory_usage();
+        report += self.primal_module.read_recursive().memory_usage();
+        Some(report)
+    }
 }
 
 #[cfg_attr(feature = "python_binding", cfg_eval)]
@@ -377,6 +675,8 @@ pub struct SolverDualParallel {
     pub primal_module: PrimalModuleSerialPtr,
     pub interface_ptr: DualModuleInterfacePtr,
     pub subgraph_builder: SubGraphBuilder,
+    /// nothing is loaded into the dual module since the last clear, so that clearing it can be skipped
+    is_clean: bool,
 }
 
 bind_trait_fusion_visualizer!(SolverDualParallel);
@@ -418,18 +718,26 @@ impl SolverDualParallel {
             primal_module: PrimalModuleSerialPtr::new_empty(initializer),
             interface_ptr: DualModuleInterfacePtr::new_empty(),
             subgraph_builder: SubGraphBuilder::new(initializer),
+            is_clean: true,
         }
     }
 }
 
 impl PrimalDualSolver for SolverDualParallel {
     fn clear(&mut self) {
-        self.dual_module.clear();
+        if !self.is_clean {
+            self.dual_module.clear();
+            self.is_clean = true;
+        }
         self.primal_module.clear();
         self.interface_ptr.clear();
         self.subgraph_builder.clear();
     }
     fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        if syndrome_pattern.is_empty() && visualizer.is_none() {
+            return; // fast path: nothing to match, and the units are left unfused
+        }
+        self.is_clean = false;
         if !syndrome_pattern.erasures.is_empty() {
             assert!(
                 syndrome_pattern.dynamic_weights.is_empty(),
@@ -440,6 +748,19 @@ impl PrimalDualSolver for SolverDualParallel {
         if !syndrome_pattern.dynamic_weights.is_empty() {
             self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
         }
+        if self.dual_module.config.sharded_interface {
+            self.dual_module
+                .load_sharded(&self.interface_ptr, syndrome_pattern)
+                .unwrap_or_else(|error| panic!("{error}"));
+            let mut flattened_nodes = vec![];
+            self.interface_ptr.flatten_nodes(&mut flattened_nodes);
+            for dual_node_ptr in flattened_nodes.iter() {
+                self.primal_module.load_defect_dual_node(dual_node_ptr.as_ref().unwrap());
+            }
+            self.primal_module
+                .solve_visualizer_interface_loaded(&self.interface_ptr, &mut self.dual_module, visualizer);
+            return;
+        }
         self.dual_module.static_fuse_all();
         self.primal_module
             .solve_visualizer(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, visualizer);
@@ -480,12 +801,35 @@ impl PrimalDualSolver for SolverDualParallel {
     fn sum_dual_variables(&self) -> Weight {
         self.interface_ptr.read_recursive().sum_dual_variables
     }
+    fn check_overflow(&self) -> Result<(), FusionError> {
+        self.interface_ptr.check_overflow()
+    }
     fn generate_profiler_report(&self) -> serde_json::Value {
         json!({
             "dual": self.dual_module.generate_profiler_report(),
             "primal": self.primal_module.generate_profiler_report(),
+            "interface": self.interface_ptr.generate_profiler_report(),
         })
     }
+    fn round_summaries(&self) -> Option<Vec<RoundSummary>> {
+        self.primal_module.read_recursive().round_summaries.clone()
+    }
+    fn state_size(&self) -> Option<SolverStateSize> {
+        let interface = self.interface_ptr.read_recursive();
+        let primal_module = self.primal_module.read_recursive();
+        let mut state_size = SolverStateSize {
+            allocated_nodes: interface.nodes.capacity() + primal_module.nodes.capacity(),
+            retained_nodes: interface.nodes_length + primal_module.nodes_length,
+        };
+        state_size += dual_module_parallel_state_size(&self.dual_module);
+        Some(state_size)
+    }
+    fn memory_usage(&self) -> Option<MemoryReport> {
+        let mut report = self.dual_module.memory_usage();
+        report += self.interface_ptr.read_recursive().memory_usage();
+        report += self.primal_module.read_recursive().memory_usage();
+        Some(report)
+    }
 }
 
 #[cfg_attr(feature = "python_binding", cfg_eval)]
@@ -494,6 +838,14 @@ pub struct SolverParallel {
     pub dual_module: DualModuleParallel<DualModuleSerial>,
     pub primal_module: PrimalModuleParallel,
     pub subgraph_builder: SubGraphBuilder,
+    /// when set, the partition boundaries are moved before each solve to balance the defects among the partitions,
+    /// see [`PartitionConfig::rebalance_layers`]
+    pub repartition_layer_vertex_num: Option<VertexNum>,
+    initializer: Arc<SolverInitializer>,
+    dual_config: DualModuleParallelConfig,
+    primal_config: PrimalModuleParallelConfig,
+    /// nothing is loaded into the dual module since the last clear, so that clearing it can be skipped
+    is_clean: bool,
 }
 
 bind_trait_fusion_visualizer!(SolverParallel);
@@ -559,6 +911,15 @@ impl SolverParallel {
     pub fn new(
         initializer: &SolverInitializer,
         partition_info: &PartitionInfo,
+        primal_dual_config: serde_json::Value,
+    ) -> Self {
+        Self::new_shared(&Arc::new(initializer.clone()), partition_info, primal_dual_config)
+    }
+
+    /// the decoding graph is shared with other solvers instead of being copied, see [`PartitionedSolverSet`]
+    pub fn new_shared(
+        initializer: &Arc<SolverInitializer>,
+        partition_info: &PartitionInfo,
         mut primal_dual_config: serde_json::Value,
     ) -> Self {
         let primal_dual_config = primal_dual_config.as_object_mut().expect("config must be JSON object");
@@ -570,6 +931,11 @@ impl SolverParallel {
         if let Some(value) = primal_dual_config.remove("primal") {
             primal_config = serde_json::from_value(value).unwrap();
         }
+        let mut repartition_layer_vertex_num = None;
+        if let Some(value) = primal_dual_config.remove("repartition_layer_vertex_num") {
+            repartition_layer_vertex_num =
+                Some(value.as_u64().expect("repartition_layer_vertex_num: VertexNum") as VertexNum);
+        }
         if !primal_dual_config.is_empty() {
             panic!(
                 "unknown primal_dual_config keys: {:?}",
@@ -577,23 +943,68 @@ impl SolverParallel {
             );
         }
         Self {
-            dual_module: DualModuleParallel::new_config(initializer, partition_info, dual_config),
-            primal_module: PrimalModuleParallel::new_config(initializer, partition_info, primal_config),
+            dual_module: DualModuleParallel::new_config(initializer, partition_info, dual_config.clone()),
+            primal_module: PrimalModuleParallel::new_config(initializer, partition_info, primal_config.clone()),
             subgraph_builder: SubGraphBuilder::new(initializer),
+            repartition_layer_vertex_num,
+            initializer: Arc::clone(initializer),
+            dual_config,
+            primal_config,
+            is_clean: true,
+        }
+    }
+
+    /// solve a syndrome that arrives round by round, see [`PrimalModuleParallel::parallel_solve_stream`]; the partition is
+    /// never rebalanced here because the syndrome is not known beforehand
+    pub fn solve_stream(&mut self, rounds: impl IntoIterator<Item = SyndromeRound>) {
+        self.is_clean = false;
+        self.primal_module.parallel_solve_stream(rounds, &self.dual_module);
+    }
+
+    /// the decode latency of the streamed shots, see [`PrimalModuleParallel::stream_latency`]
+    pub fn stream_latency(&self) -> Option<&LatencyHistogram> {
+        self.primal_module.stream_latency()
+    }
+
+    /// rebuild the dual and primal modules if the balanced partition of this syndrome differs from the current one;
+    /// the vertex indices are not changed, so the results need no remapping
+    fn repartition(&mut self, syndrome_pattern: &SyndromePattern) {
+        let Some(layer_vertex_num) = self.repartition_layer_vertex_num else {
+            return;
+        };
+        if syndrome_pattern.is_empty() {
+            return; // nothing to balance, and an empty syndrome is solved without touching the units
         }
+        let partition_config = &self.dual_module.partition_info.config;
+        let balanced_config = partition_config.rebalance_layers(layer_vertex_num, syndrome_pattern).unwrap();
+        if balanced_config.partitions == partition_config.partitions {
+            return;
+        }
+        let partition_info = balanced_config.info();
+        self.dual_module = DualModuleParallel::new_config(&self.initializer, &partition_info, self.dual_config.clone());
+        self.primal_module =
+            PrimalModuleParallel::new_config(&self.initializer, &partition_info, self.primal_config.clone());
     }
 }
 
 impl PrimalDualSolver for SolverParallel {
     fn clear(&mut self) {
-        self.dual_module.clear();
+        if !self.is_clean {
+            self.dual_module.clear();
+            self.is_clean = true;
+        }
         self.primal_module.clear();
         self.subgraph_builder.clear();
     }
     fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        // an empty syndrome is solved without touching the units, unless it's visualized
+        if !syndrome_pattern.is_empty() || visualizer.is_some() {
+            self.is_clean = false;
+        }
         if !syndrome_pattern.erasures.is_empty() {
             self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
         }
+        self.repartition(syndrome_pattern);
         self.primal_module
             .parallel_solve_visualizer(syndrome_pattern, &self.dual_module, visualizer);
     }
@@ -644,6 +1055,39 @@ impl PrimalDualSolver for SolverParallel {
             "primal": self.primal_module.generate_profiler_report(),
         })
     }
+    fn round_summaries(&self) -> Option<Vec<RoundSummary>> {
+        self.primal_module.round_summaries()
+    }
+    fn state_size(&self) -> Option<SolverStateSize> {
+        let mut state_size = dual_module_parallel_state_size(&self.dual_module);
+        for unit_ptr in self.primal_module.units.iter() {
+            let unit = unit_ptr.read_recursive();
+            let interface = unit.interface_ptr.read_recursive();
+            let primal_module = unit.serial_module.read_recursive();
+            state_size += SolverStateSize {
+                allocated_nodes: interface.nodes.capacity() + primal_module.nodes.capacity(),
+                retained_nodes: interface.nodes_length + primal_module.nodes_length,
+            };
+        }
+        Some(state_size)
+    }
+    fn memory_usage(&self) -> Option<MemoryReport> {
+        let mut report = self.dual_module.memory_usage();
+        report += self.primal_module.memory_usage();
+        Some(report)
+    }
+}
+
+fn dual_module_parallel_state_size(dual_module: &DualModuleParallel<DualModuleSerial>) -> SolverStateSize {
+    let mut state_size = SolverStateSize::default();
+    for unit_ptr in dual_module.units.iter() {
+        let unit = unit_ptr.read_recursive();
+        state_size += SolverStateSize {
+            allocated_nodes: unit.serial_module.nodes.capacity(),
+            retained_nodes: unit.serial_module.nodes_length,
+        };
+    }
+    state_size
 }
 
 #[cfg_attr(feature = "python_binding", cfg_eval)]
@@ -715,6 +1159,7 @@ pub struct SolverBlossomV {
 
 impl SolverBlossomV {
     pub fn new(initializer: &SolverInitializer) -> Self {
+        assert!(initializer.virtual_vertex_groups.is_empty(), "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`");
         Self {
             initializer: initializer.clone(),
             prebuilt_complete_graph: PrebuiltCompleteGraph::new_threaded(initializer, 0),
@@ -798,38 +1243,10 @@ impl PrimalDualSolver for SolverBlossomV {
     }
     fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
         assert!(visualizer.is_none(), "not supported");
-        let virtual_vertices: BTreeSet<VertexIndex> = self.initializer.virtual_vertices.iter().cloned().collect();
-        let mut perfect_matching = PerfectMatching::new();
-        let mut counter = 0;
-        let interface_ptr = DualModuleInterfacePtr::new_empty();
-        let mut create_dual_node = |vertex_index: VertexIndex| {
-            counter += 1;
-            DualNodePtr::new_value(DualNode {
-                index: counter,
-                class: DualNodeClass::DefectVertex {
-                    defect_index: vertex_index,
-                },
-                grow_state: DualNodeGrowState::Grow,
-                parent_blossom: None,
-                dual_variable_cache: (0, 0),
-                belonging: interface_ptr.downgrade(),
-                defect_size: nz!(1usize),
-            })
-        };
         for &(vertex_1, vertex_2) in self.matched_pairs.iter() {
-            assert!(!virtual_vertices.contains(&vertex_1)); // 1 is not virtual
-            if virtual_vertices.contains(&vertex_2) {
-                perfect_matching
-                    .virtual_matchings
-                    .push((create_dual_node(vertex_1), vertex_2));
-            } else {
-                perfect_matching
-                    .peer_matchings
-                    .push((create_dual_node(vertex_1), create_dual_node(vertex_2)));
-            }
             self.subgraph_builder.add_matching(vertex_1, vertex_2);
         }
-        perfect_matching
+        perfect_matching_of_pairs(&self.initializer, &self.matched_pairs)
     }
     fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
         assert!(visualizer.is_none(), "not supported");
@@ -858,13 +1275,3037 @@ impl PrimalDualSolver for SolverBlossomV {
     }
 }
 
-#[cfg(feature = "python_binding")]
-#[pyfunction]
-pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
-    m.add_class::<LegacySolverSerial>()?;
-    m.add_class::<SolverSerial>()?;
-    m.add_class::<SolverDualParallel>()?;
-    m.add_class::<SolverParallel>()?;
-    m.add_class::<SolverErrorPatternLogger>()?;
-    Ok(())
+/// a perfect matching of standalone dual nodes, for the solvers that compute the matched pairs without dual nodes
+fn perfect_matching_of_pairs(
+    initializer: &SolverInitializer,
+    matched_pairs: &[(VertexIndex, VertexIndex)],
+) -> PerfectMatching {
+    let virtual_vertices: BTreeSet<VertexIndex> = initializer.virtual_vertices.iter().cloned().collect();
+    let mut perfect_matching = PerfectMatching::new();
+    let mut counter = 0;
+    let interface_ptr = DualModuleInterfacePtr::new_empty();
+    let home_epoch = interface_ptr.read_recursive().epoch;
+    let mut create_dual_node = |vertex_index: VertexIndex| {
+        counter += 1;
+        DualNodePtr::new_value(DualNode {
+            index: counter,
+            class: DualNodeClass::DefectVertex {
+                defect_index: vertex_index,
+            },
+            grow_state: DualNodeGrowState::Grow,
+            parent_blossom: None,
+            dual_variable_cache: (0, 0),
+            belonging: interface_ptr.downgrade(),
+            home_interface: interface_ptr.downgrade(),
+            home_index: counter,
+            home_epoch,
+            defect_size: nz!(1usize),
+            blossom_depth: 0,
+        })
+    };
+    for &(vertex_1, vertex_2) in matched_pairs.iter() {
+        assert!(!virtual_vertices.contains(&vertex_1)); // 1 is not virtual
+        if virtual_vertices.contains(&vertex_2) {
+            perfect_matching
+                .virtual_matchings
+                .push((create_dual_node(vertex_1), vertex_2));
+        } else {
+            perfect_matching
+                .peer_matchings
+                .push((create_dual_node(vertex_1), create_dual_node(vertex_2)));
+        }
+    }
+    perfect_matching
+}
+
+/// a constraint on the pairing of two defect vertices, see [`SolverSerialConstrained`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchingConstraint {
+    /// the two defect vertices must be matched with each other
+    MustMatch(VertexIndex, VertexIndex),
+    /// the two defect vertices must not be matched with each other, but they may both match to the boundary
+    MustNotMatch(VertexIndex, VertexIndex),
+}
+
+impl MatchingConstraint {
+    pub fn vertices(&self) -> (VertexIndex, VertexIndex) {
+        match *self {
+            Self::MustMatch(a, b) | Self::MustNotMatch(a, b) => (a, b),
+        }
+    }
+}
+
+/// a serial solver that re-decodes a syndrome pattern under [`MatchingConstraint`]s, e.g. to find the best solution
+/// in the opposite logical class for soft output. The pinned pairs are matched directly, and the other defect vertices
+/// are solved on the syndrome graph, i.e. a complete graph of the defect vertices with the shortest distances as
+/// weights plus a single virtual vertex for the boundary, where the forbidden pairs are simply left out; note that the
+/// constraints must leave at least one perfect matching, otherwise the solver never terminates
+pub struct SolverSerialConstrained {
+    pub initializer: SolverInitializer,
+    /// the constraints applied to every following syndrome pattern
+    pub constraints: Vec<MatchingConstraint>,
+    pub subgraph_builder: SubGraphBuilder,
+    /// the matched pairs of the last syndrome pattern, where the second vertex may be a virtual vertex
+    pub matched_pairs: Vec<(VertexIndex, VertexIndex)>,
+    is_virtual: Vec<bool>,
+}
+
+impl SolverSerialConstrained {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        assert!(initializer.virtual_vertex_groups.is_empty(), "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`");
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        Self {
+            initializer: initializer.clone(),
+            constraints: vec![],
+            subgraph_builder: SubGraphBuilder::new(initializer),
+            matched_pairs: vec![],
+            is_virtual,
+        }
+    }
+
+    pub fn set_constraints(&mut self, constraints: Vec<MatchingConstraint>) {
+        self.constraints = constraints;
+    }
+
+    /// every constrained vertex must be a defect vertex, and each one can be pinned to at most one peer
+    fn check_constraints(&self, defect_vertices: &[VertexIndex]) -> Result<(), String> {
+        let defect_vertices: BTreeSet<VertexIndex> = defect_vertices.iter().cloned().collect();
+        let mut pinned = BTreeMap::<VertexIndex, VertexIndex>::new();
+        for constraint in self.constraints.iter() {
+            let (a, b) = constraint.vertices();
+            if a == b {
+                return Err(format!("{constraint:?} must involve two different vertices"));
+            }
+            if let Some(vertex) = [a, b].into_iter().find(|vertex| !defect_vertices.contains(vertex)) {
+                return Err(format!(
+                    "{constraint:?} refers to vertex {vertex} which is not a defect vertex"
+                ));
+            }
+            if let MatchingConstraint::MustMatch(a, b) = *constraint {
+                for (vertex, peer) in [(a, b), (b, a)] {
+                    match pinned.insert(vertex, peer) {
+                        Some(existing) if existing != peer => {
+                            return Err(format!("vertex {vertex} is pinned to both {existing} and {peer}"));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        for constraint in self.constraints.iter() {
+            if let MatchingConstraint::MustNotMatch(a, b) = *constraint {
+                if pinned.get(&a) == Some(&b) {
+                    return Err(format!("vertices {a} and {b} must both match and not match"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// solve the syndrome pattern under the constraints, or return an error without solving if they are invalid
+    #[allow(clippy::unnecessary_cast)]
+    pub fn solve_constrained(&mut self, syndrome_pattern: &SyndromePattern) -> Result<(), String> {
+        let defect_vertices = &syndrome_pattern.defect_vertices;
+        self.check_constraints(defect_vertices)?;
+        self.subgraph_builder.clear();
+        self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+        self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        self.matched_pairs.clear();
+        let mut forbidden = BTreeSet::<(VertexIndex, VertexIndex)>::new();
+        let mut is_pinned = BTreeSet::<VertexIndex>::new();
+        for constraint in self.constraints.iter() {
+            let (a, b) = constraint.vertices();
+            match constraint {
+                MatchingConstraint::MustMatch(..) => {
+                    if is_pinned.insert(a) && is_pinned.insert(b) {
+                        self.matched_pairs.push((a, b));
+                    }
+                }
+                MatchingConstraint::MustNotMatch(..) => {
+                    forbidden.insert((std::cmp::min(a, b), std::cmp::max(a, b)));
+                }
+            }
+        }
+        let remaining: Vec<VertexIndex> = (defect_vertices.iter())
+            .filter(|vertex| !is_pinned.contains(vertex))
+            .cloned()
+            .collect();
+        if remaining.is_empty() {
+            return Ok(());
+        }
+        // the syndrome graph, where vertex `i` is `remaining[i]` and the last vertex is the boundary
+        let boundary_vertex = remaining.len() as VertexIndex;
+        let mut weighted_edges = vec![];
+        let mut nearest_virtual = vec![None; remaining.len()];
+        for (i, &vertex_i) in remaining.iter().enumerate() {
+            let complete_graph_edges = self.subgraph_builder.complete_graph.all_edges(vertex_i);
+            for (&vertex, &(_, weight)) in complete_graph_edges.iter() {
+                if self.is_virtual[vertex as usize] && nearest_virtual[i].map(|(_, w)| weight < w).unwrap_or(true) {
+                    nearest_virtual[i] = Some((vertex, weight));
+                }
+            }
+            if let Some((_, weight)) = nearest_virtual[i] {
+                weighted_edges.push((i as VertexIndex, boundary_vertex, weight));
+            }
+            for (j, &vertex_j) in remaining.iter().enumerate().skip(i + 1) {
+                if forbidden.contains(&(std::cmp::min(vertex_i, vertex_j), std::cmp::max(vertex_i, vertex_j))) {
+                    continue;
+                }
+                if let Some(&(_, weight)) = complete_graph_edges.get(&vertex_j) {
+                    weighted_edges.push((i as VertexIndex, j as VertexIndex, weight));
+                }
+            }
+        }
+        let syndrome_graph = SolverInitializer::new(boundary_vertex + 1, weighted_edges, vec![boundary_vertex]);
+        let mut solver = SolverSerial::new(&syndrome_graph);
+        solver.solve(&SyndromePattern::new_vertices((0..boundary_vertex).collect()));
+        let perfect_matching = solver.perfect_matching();
+        for (node_1, node_2) in perfect_matching.peer_matchings.iter() {
+            self.matched_pairs.push((
+                remaining[node_1.get_representative_vertex() as usize],
+                remaining[node_2.get_representative_vertex() as usize],
+            ));
+        }
+        for (node, _) in perfect_matching.virtual_matchings.iter() {
+            let i = node.get_representative_vertex() as usize;
+            self.matched_pairs.push((remaining[i], nearest_virtual[i].unwrap().0));
+        }
+        Ok(())
+    }
+}
+
+impl PrimalDualSolver for SolverSerialConstrained {
+    fn clear(&mut self) {
+        self.matched_pairs.clear();
+        self.subgraph_builder.clear();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        assert!(visualizer.is_none(), "not supported");
+        self.solve_constrained(syndrome_pattern)
+            .unwrap_or_else(|error| panic!("invalid matching constraints: {error}"));
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        assert!(visualizer.is_none(), "not supported");
+        perfect_matching_of_pairs(&self.initializer, &self.matched_pairs)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        assert!(visualizer.is_none(), "not supported");
+        // keep the erasures and dynamic weights loaded in the complete graph
+        self.subgraph_builder.subgraph.clear();
+        for &(vertex_1, vertex_2) in self.matched_pairs.iter() {
+            self.subgraph_builder.add_matching(vertex_1, vertex_2);
+        }
+        self.subgraph_builder.get_subgraph()
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn sum_dual_variables(&self) -> Weight {
+        let mut complete_graph = self.subgraph_builder.complete_graph.clone();
+        (self.matched_pairs.iter())
+            .map(|&(vertex_1, vertex_2)| complete_graph.get_path(vertex_1, vertex_2).1)
+            .sum()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "constraint_num": self.constraints.len(),
+        })
+    }
+}
+
+/// the secondary objective to optimize among all minimum-weight perfect matchings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecondaryObjective {
+    /// prefer matching defects with each other rather than to the boundary
+    FewestBoundaryMatches,
+    /// prefer matching defects to the boundary rather than with each other
+    FewestMatchedPairs,
+    /// break the ties by a pseudo-random perturbation of every edge determined by the seed, so that the same solution
+    /// is chosen consistently among the degenerate ones regardless of the growth order of an implementation
+    RandomTieBreaking { seed: u64 },
+}
+
+impl SecondaryObjective {
+    /// the random perturbation of an edge is an even number in `[0, 2 * RANDOM_PERTURBATION_LEVELS)`
+    pub const RANDOM_PERTURBATION_LEVELS: u64 = 8;
+
+    /// the infinitesimal perturbation added to an edge incident to a virtual vertex, in units of the scaled weight;
+    /// note that a peer path passing through a virtual vertex is counted as two boundary matches
+    pub fn boundary_perturbation(&self) -> Weight {
+        match self {
+            Self::FewestBoundaryMatches => 2,
+            Self::FewestMatchedPairs => -2,
+            Self::RandomTieBreaking { .. } => 0,
+        }
+    }
+
+    /// the infinitesimal perturbation of each edge, in units of the scaled weight
+    #[allow(clippy::unnecessary_cast)]
+    pub fn edge_perturbations(&self, initializer: &SolverInitializer) -> Vec<Weight> {
+        if let Self::RandomTieBreaking { seed } = *self {
+            let mut rng = DeterministicRng::seed_from_u64(seed);
+            return (0..initializer.weighted_edges.len())
+                .map(|_| 2 * (rng.next_u64() % Self::RANDOM_PERTURBATION_LEVELS) as Weight)
+                .collect();
+        }
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        (initializer.weighted_edges.iter())
+            .map(|&(i, j, _)| {
+                if is_virtual[i as usize] != is_virtual[j as usize] {
+                    self.boundary_perturbation()
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+}
+
+/// a serial solver that breaks ties among minimum-weight solutions lexicographically by a [`SecondaryObjective`];
+/// the infinitesimal perturbation `ε` is handled symbolically by representing each weight `w + kε` as `w * scale + k + 2`,
+/// where the shift of 2 keeps every perturbed weight non-negative, including the zero-weight edges, and `scale` is larger
+/// than the maximum possible accumulated perturbation so that it never changes the primary order; note that the shift
+/// costs `2ε` for every edge in the solution
+pub struct SolverSerialLexicographic {
+    pub secondary_objective: SecondaryObjective,
+    /// the original decoding graph
+    pub initializer: SolverInitializer,
+    /// the inner solver running on the perturbed decoding graph
+    pub solver: SolverSerial,
+    /// the factor multiplied to every original weight
+    pub scale: Weight,
+    /// the perturbation of each edge, see [`SecondaryObjective::edge_perturbations`]
+    perturbations: Vec<Weight>,
+    /// the primary weight of the solution, i.e. the minimum weight in the original decoding graph
+    primary_weight: Weight,
+}
+
+impl SolverSerialLexicographic {
+    /// the shift added to every perturbation, which is at least `-2`
+    pub const PERTURBATION_SHIFT: Weight = 2;
+
+    /// fails with [`FusionError::WeightOverflow`] if a perturbed weight doesn't fit in [`Weight`], e.g. with `i16_weight`
+    pub fn new(initializer: &SolverInitializer, secondary_objective: SecondaryObjective) -> Result<Self, FusionError> {
+        let overflow = || FusionError::WeightOverflow("perturbed weights of the secondary objective");
+        let perturbations = secondary_objective.edge_perturbations(initializer);
+        // the shifted perturbations are non-negative, so the accumulated one of any solution is at most their sum
+        let scale = (perturbations.iter())
+            .try_fold(1 as Weight, |sum, &perturbation| {
+                sum.checked_add(perturbation + Self::PERTURBATION_SHIFT)
+            })
+            .ok_or_else(overflow)?;
+        let mut perturbed_initializer = initializer.clone();
+        for (edge_index, (_, _, weight)) in perturbed_initializer.weighted_edges.iter_mut().enumerate() {
+            *weight = Self::perturb_weight(*weight, scale, perturbations[edge_index]).ok_or_else(overflow)?;
+        }
+        Ok(Self {
+            secondary_objective,
+            initializer: initializer.clone(),
+            solver: SolverSerial::new(&perturbed_initializer),
+            scale,
+            perturbations,
+            primary_weight: 0,
+        })
+    }
+
+    /// map an original weight to the perturbed weight, None if it overflows
+    fn perturb_weight(weight: Weight, scale: Weight, perturbation: Weight) -> Option<Weight> {
+        weight
+            .checked_mul(scale)?
+            .checked_add(perturbation + Self::PERTURBATION_SHIFT)
+    }
+
+    /// the primary weight of a perturbed weight, i.e. the inverse of the perturbation for reporting; the accumulated
+    /// shifted perturbation is in `[0, scale)` and is thus removed by the floor division
+    pub fn primary_weight_of(&self, perturbed_weight: Weight) -> Weight {
+        perturbed_weight.div_euclid(self.scale)
+    }
+
+    /// the perturbed weight of the last solution, from which both objectives can be recovered
+    pub fn perturbed_weight(&self) -> Weight {
+        self.solver.sum_dual_variables()
+    }
+}
+
+impl PrimalDualSolver for SolverSerialLexicographic {
+    fn clear(&mut self) {
+        self.solver.clear();
+        self.primary_weight = 0;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        if syndrome_pattern.dynamic_weights.is_empty() {
+            self.solver.solve_visualizer(syndrome_pattern, visualizer);
+        } else {
+            let mut perturbed_syndrome_pattern = syndrome_pattern.clone();
+            for (edge_index, weight) in perturbed_syndrome_pattern.dynamic_weights.iter_mut() {
+                *weight = Self::perturb_weight(*weight, self.scale, self.perturbations[*edge_index as usize])
+                    .unwrap_or_else(|| {
+                        panic!("dynamic weight {weight} overflows when perturbed for the secondary objective")
+                    });
+            }
+            self.solver.solve_visualizer(&perturbed_syndrome_pattern, visualizer);
+        }
+        self.primary_weight = self.primary_weight_of(self.solver.sum_dual_variables());
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.solver.perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        self.solver.subgraph_visualizer(visualizer)
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.primary_weight
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        self.solver.generate_profiler_report()
+    }
+}
+
+/// a serial solver that first solves on the subgraph within a radius around each defect vertex, where each vertex just
+/// outside the subgraph acts as a virtual vertex; since any solution of the full graph restricted to the subgraph is a
+/// solution of the truncated problem, the truncated solution is optimal whenever it doesn't touch any of those vertices.
+/// Otherwise, the radii of the defect vertices matched to them are doubled and the problem is solved again
+pub struct SolverSerialProgressive {
+    /// the original decoding graph
+    pub initializer: SolverInitializer,
+    /// the initial radius of the subgraph around each defect vertex
+    pub initial_radius: Weight,
+    /// the number of truncated problems solved for the last syndrome pattern
+    pub round_num: usize,
+    /// the incident edges of each vertex: (neighbor, edge index)
+    neighbors: Vec<Vec<(VertexIndex, EdgeIndex)>>,
+    /// the inner solver of the last truncated problem, which shares the vertex indices of the original decoding graph
+    solver: Option<SolverSerial>,
+    /// the truncated decoding graph of the last round, used to replay the final round in a visualizer
+    truncated_initializer: Option<SolverInitializer>,
+    /// the original edge of each edge in the truncated decoding graph
+    edge_indices: Vec<EdgeIndex>,
+}
+
+impl SolverSerialProgressive {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, initial_radius: Weight) -> Self {
+        assert!(initializer.virtual_vertex_groups.is_empty(), "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`");
+        let mut neighbors = vec![vec![]; initializer.vertex_num as usize];
+        for (edge_index, &(i, j, _)) in initializer.weighted_edges.iter().enumerate() {
+            neighbors[i as usize].push((j, edge_index as EdgeIndex));
+            neighbors[j as usize].push((i, edge_index as EdgeIndex));
+        }
+        Self {
+            initializer: initializer.clone(),
+            initial_radius,
+            round_num: 0,
+            neighbors,
+            solver: None,
+            truncated_initializer: None,
+            edge_indices: vec![],
+        }
+    }
+
+    /// mark the vertices within `radius` from `source`, given the (possibly modified) weight of every edge
+    #[allow(clippy::unnecessary_cast)]
+    fn mark_ball(&self, weights: &[Weight], source: VertexIndex, radius: Weight, in_region: &mut [bool]) {
+        let mut distances = BTreeMap::<VertexIndex, Weight>::new();
+        let mut queue = std::collections::BinaryHeap::new();
+        distances.insert(source, 0);
+        queue.push(std::cmp::Reverse((0, source)));
+        while let Some(std::cmp::Reverse((distance, vertex))) = queue.pop() {
+            if distance > distances[&vertex] {
+                continue;
+            }
+            in_region[vertex as usize] = true;
+            for &(neighbor, edge_index) in self.neighbors[vertex as usize].iter() {
+                let neighbor_distance = distance + weights[edge_index as usize];
+                if neighbor_distance <= radius && !matches!(distances.get(&neighbor), Some(&d) if d <= neighbor_distance) {
+                    distances.insert(neighbor, neighbor_distance);
+                    queue.push(std::cmp::Reverse((neighbor_distance, neighbor)));
+                }
+            }
+        }
+    }
+
+    /// build the decoding graph truncated to the region, returning it together with the vertices just outside
+    #[allow(clippy::unnecessary_cast)]
+    fn truncate(&mut self, weights: &[Weight], in_region: &[bool]) -> (SolverInitializer, BTreeSet<VertexIndex>) {
+        let mut truncated_vertices = BTreeSet::new();
+        let mut weighted_edges = vec![];
+        self.edge_indices.clear();
+        for (edge_index, &(i, j, _)) in self.initializer.weighted_edges.iter().enumerate() {
+            if !in_region[i as usize] && !in_region[j as usize] {
+                continue;
+            }
+            for vertex in [i, j] {
+                if !in_region[vertex as usize] {
+                    truncated_vertices.insert(vertex);
+                }
+            }
+            weighted_edges.push((i, j, weights[edge_index]));
+            self.edge_indices.push(edge_index as EdgeIndex);
+        }
+        let mut virtual_vertices: BTreeSet<VertexIndex> = self.initializer.virtual_vertices.iter().cloned().collect();
+        virtual_vertices.extend(truncated_vertices.iter().cloned());
+        let initializer = SolverInitializer::new(
+            self.initializer.vertex_num,
+            weighted_edges,
+            virtual_vertices.into_iter().collect(),
+        );
+        (initializer, truncated_vertices)
+    }
+}
+
+impl PrimalDualSolver for SolverSerialProgressive {
+    fn clear(&mut self) {
+        self.solver = None;
+        self.truncated_initializer = None;
+        self.edge_indices.clear();
+        self.round_num = 0;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        let mut weights: Vec<Weight> = self.initializer.weighted_edges.iter().map(|(_, _, weight)| *weight).collect();
+        for &edge_index in syndrome_pattern.erasures.iter() {
+            weights[edge_index as usize] = 0;
+        }
+        for &(edge_index, weight) in syndrome_pattern.dynamic_weights.iter() {
+            weights[edge_index as usize] = weight;
+        }
+        let defect_syndrome = SyndromePattern::new_vertices(syndrome_pattern.defect_vertices.clone());
+        let mut radii: BTreeMap<VertexIndex, Weight> = syndrome_pattern
+            .defect_vertices
+            .iter()
+            .map(|&defect_vertex| (defect_vertex, self.initial_radius))
+            .collect();
+        self.round_num = 0;
+        loop {
+            self.round_num += 1;
+            let mut in_region = vec![false; self.initializer.vertex_num as usize];
+            for (&defect_vertex, &radius) in radii.iter() {
+                self.mark_ball(&weights, defect_vertex, radius, &mut in_region);
+            }
+            let (truncated_initializer, truncated_vertices) = self.truncate(&weights, &in_region);
+            let mut solver = SolverSerial::new(&truncated_initializer);
+            solver.solve(&defect_syndrome);
+            let mut suspicious_defects: Vec<VertexIndex> = solver
+                .perfect_matching()
+                .virtual_matchings
+                .iter()
+                .filter(|(_, virtual_vertex)| truncated_vertices.contains(virtual_vertex))
+                .map(|(dual_node_ptr, _)| dual_node_ptr.get_representative_vertex())
+                .collect();
+            let touches_truncation = solver.subgraph().iter().any(|&edge_index| {
+                let (i, j, _) = truncated_initializer.weighted_edges[edge_index as usize];
+                truncated_vertices.contains(&i) || truncated_vertices.contains(&j)
+            });
+            self.solver = Some(solver);
+            self.truncated_initializer = Some(truncated_initializer);
+            if !touches_truncation {
+                break;
+            }
+            if suspicious_defects.is_empty() {
+                // the truncated vertices are only passed through by peer matchings, expand every defect vertex
+                suspicious_defects = radii.keys().cloned().collect();
+            }
+            for defect_vertex in suspicious_defects {
+                let radius = radii.get_mut(&defect_vertex).unwrap();
+                *radius = std::cmp::max(radius.saturating_mul(2), *radius + 1);
+            }
+        }
+        if visualizer.is_some() {
+            // replay the final round so that the visualizer shows the solution that is eventually used
+            let mut solver = SolverSerial::new(self.truncated_initializer.as_ref().unwrap());
+            solver.solve_visualizer(&defect_syndrome, visualizer);
+            self.solver = Some(solver);
+        }
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.solver.as_mut().unwrap().perfect_matching_visualizer(visualizer)
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        let subgraph = self.solver.as_mut().unwrap().subgraph();
+        let subgraph: Vec<EdgeIndex> = subgraph
+            .into_iter()
+            .map(|edge_index| self.edge_indices[edge_index as usize])
+            .collect();
+        if let Some(visualizer) = visualizer {
+            visualizer
+                .snapshot_combined(
+                    "subgraph".to_string(),
+                    vec![self.solver.as_ref().unwrap(), &VisualizeSubgraph::new(&subgraph)],
+                )
+                .unwrap();
+        }
+        subgraph
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.solver.as_ref().map_or(0, |solver| solver.sum_dual_variables())
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "round_num": self.round_num,
+            "solver": self.solver.as_ref().map(|solver| solver.generate_profiler_report()),
+        })
+    }
+}
+
+/// a serial solver that skips the growth of isolated defect vertices: if the distance between a defect vertex and any
+/// other defect vertex is at least the sum of their distances to the boundary, some minimum-weight solution matches it
+/// to its nearest virtual vertex, so it's matched directly without being loaded into the inner solver. Note that the
+/// pruned defect vertices are not in [`PrimalDualSolver::perfect_matching`] because they have no dual node
+pub struct SolverSerialBoundaryPruning {
+    pub solver: SolverSerial,
+    /// precomputed when the solver is constructed
+    pub boundary_distances: BoundaryDistances,
+    /// the pruned defect vertices of the last syndrome pattern, with their nearest virtual vertex
+    pub pruned: Vec<(VertexIndex, VertexIndex)>,
+    /// the maximum finite distance to the boundary, which bounds the search radius around each defect vertex
+    max_boundary_distance: Weight,
+    neighbors: Vec<Vec<(VertexIndex, Weight)>>,
+}
+
+impl SolverSerialBoundaryPruning {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        assert!(initializer.virtual_vertex_groups.is_empty(), "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`");
+        let boundary_distances = initializer.boundary_distances();
+        let max_boundary_distance = boundary_distances
+            .distances
+            .iter()
+            .filter(|&&distance| distance != Weight::MAX)
+            .max()
+            .cloned()
+            .unwrap_or(0);
+        let mut neighbors = vec![vec![]; initializer.vertex_num as usize];
+        for &(i, j, weight) in initializer.weighted_edges.iter() {
+            neighbors[i as usize].push((j, weight));
+            neighbors[j as usize].push((i, weight));
+        }
+        Self {
+            solver: SolverSerial::new(initializer),
+            boundary_distances,
+            pruned: vec![],
+            max_boundary_distance,
+            neighbors,
+        }
+    }
+
+    /// whether every other defect vertex is at least as far as the sum of their boundary distances
+    #[allow(clippy::unnecessary_cast)]
+    fn is_isolated(&self, defect_vertex: VertexIndex, is_defect: &[bool]) -> bool {
+        let boundary_distance = self.boundary_distances.distances[defect_vertex as usize];
+        if boundary_distance == Weight::MAX {
+            return false;
+        }
+        // defect vertices beyond this radius are far enough regardless of their boundary distances
+        let radius = boundary_distance + self.max_boundary_distance;
+        let mut distances = BTreeMap::<VertexIndex, Weight>::new();
+        let mut queue = std::collections::BinaryHeap::new();
+        distances.insert(defect_vertex, 0);
+        queue.push(std::cmp::Reverse((0, defect_vertex)));
+        while let Some(std::cmp::Reverse((distance, vertex))) = queue.pop() {
+            if distance > distances[&vertex] {
+                continue;
+            }
+            if vertex != defect_vertex
+                && is_defect[vertex as usize]
+                && distance < boundary_distance + self.boundary_distances.distances[vertex as usize]
+            {
+                return false;
+            }
+            for &(neighbor, weight) in self.neighbors[vertex as usize].iter() {
+                let neighbor_distance = distance + weight;
+                if neighbor_distance < radius && !matches!(distances.get(&neighbor), Some(&d) if d <= neighbor_distance) {
+                    distances.insert(neighbor, neighbor_distance);
+                    queue.push(std::cmp::Reverse((neighbor_distance, neighbor)));
+                }
+            }
+        }
+        true
+    }
+}
+
+impl PrimalDualSolver for SolverSerialBoundaryPruning {
+    fn clear(&mut self) {
+        self.solver.clear();
+        self.pruned.clear();
+    }
+    fn reset_profiler(&mut self) {
+        self.solver.reset_profiler();
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        // the precomputed distances are invalid if the weights change
+        if !syndrome_pattern.erasures.is_empty() || !syndrome_pattern.dynamic_weights.is_empty() {
+            return self.solver.solve_visualizer(syndrome_pattern, visualizer);
+        }
+        let mut is_defect = vec![false; self.neighbors.len()];
+        for &defect_vertex in syndrome_pattern.defect_vertices.iter() {
+            is_defect[defect_vertex as usize] = true;
+        }
+        let mut remaining = vec![];
+        for &defect_vertex in syndrome_pattern.defect_vertices.iter() {
+            if self.is_isolated(defect_vertex, &is_defect) {
+                let virtual_vertex = self.boundary_distances.nearest_virtual[defect_vertex as usize].unwrap();
+                self.pruned.push((defect_vertex, virtual_vertex));
+            } else {
+                remaining.push(defect_vertex);
+            }
+        }
+        self.solver
+            .solve_visualizer(&SyndromePattern::new_vertices(remaining), visualizer);
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.solver.perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        let mut subgraph = self.solver.subgraph_visualizer(visualizer);
+        if !self.pruned.is_empty() {
+            for &(defect_vertex, virtual_vertex) in self.pruned.iter() {
+                self.solver.subgraph_builder.add_matching(defect_vertex, virtual_vertex);
+            }
+            subgraph = self.solver.subgraph_builder.get_subgraph();
+        }
+        subgraph
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn sum_dual_variables(&self) -> Weight {
+        let pruned_weight: Weight = (self.pruned.iter())
+            .map(|&(defect_vertex, _)| self.boundary_distances.distances[defect_vertex as usize])
+            .sum();
+        self.solver.sum_dual_variables() + pruned_weight
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "pruned_num": self.pruned.len(),
+            "solver": self.solver.generate_profiler_report(),
+        })
+    }
+    fn state_size(&self) -> Option<SolverStateSize> {
+        self.solver.state_size()
+    }
+    fn memory_usage(&self) -> Option<MemoryReport> {
+        self.solver.memory_usage()
+    }
+}
+
+/// the adjacency list of the decoding graph, for the solvers that search the neighborhood of the defect vertices
+#[allow(clippy::unnecessary_cast)]
+fn weighted_neighbors(initializer: &SolverInitializer) -> Vec<Vec<(VertexIndex, Weight)>> {
+    let mut neighbors = vec![vec![]; initializer.vertex_num as usize];
+    for &(i, j, weight) in initializer.weighted_edges.iter() {
+        neighbors[i as usize].push((j, weight));
+        neighbors[j as usize].push((i, weight));
+    }
+    neighbors
+}
+
+/// the vertices closer than `radius` from `source` that are candidates, with their distances
+#[allow(clippy::unnecessary_cast)]
+fn nearby_vertices(
+    neighbors: &[Vec<(VertexIndex, Weight)>],
+    source: VertexIndex,
+    radius: Weight,
+    is_candidate: impl Fn(VertexIndex) -> bool,
+) -> Vec<(VertexIndex, Weight)> {
+    let mut nearby = vec![];
+    let mut distances = BTreeMap::<VertexIndex, Weight>::new();
+    let mut queue = std::collections::BinaryHeap::new();
+    distances.insert(source, 0);
+    queue.push(std::cmp::Reverse((0, source)));
+    while let Some(std::cmp::Reverse((distance, vertex))) = queue.pop() {
+        if distance > distances[&vertex] {
+            continue;
+        }
+        if vertex != source && is_candidate(vertex) {
+            nearby.push((vertex, distance));
+        }
+        for &(neighbor, weight) in neighbors[vertex as usize].iter() {
+            let neighbor_distance = distance + weight;
+            if neighbor_distance < radius && !matches!(distances.get(&neighbor), Some(&d) if d <= neighbor_distance) {
+                distances.insert(neighbor, neighbor_distance);
+                queue.push(std::cmp::Reverse((neighbor_distance, neighbor)));
+            }
+        }
+    }
+    nearby
+}
+
+/// the matchings of a group of defect vertices that is solved independently by [`SolverSerialWarmStart`]
+#[derive(Debug, Clone)]
+struct WarmStartCluster {
+    /// (defect vertex, peer), where the peer is a virtual vertex if matched to the boundary
+    matchings: Vec<(VertexIndex, VertexIndex)>,
+    /// the sum of the dual variables of the cluster, which is the weight of the matchings
+    weight: Weight,
+}
+
+/// a serial solver seeded with the matching of the previous shot, for consecutive syndrome patterns that differ in a few
+/// defect vertices, e.g. in streaming. The region of a dual node stays within the distance of its dual variable plus
+/// those of its ancestor blossoms (the radius) from each defect vertex inside, so two groups of defect vertices whose
+/// pairwise distances are at least the sum of their radii have dual solutions that are feasible together, and their
+/// minimum-weight matchings together are a minimum-weight matching of both. The defect vertices are thus grouped into
+/// clusters connected by blossoms, matchings and the pairs closer than their radii. When the next syndrome pattern comes,
+/// the clusters that lose a defect vertex are undone and solved again together with the new defect vertices, and then
+/// any other cluster closer than the radii is undone and solved again as well, until the rest can be kept. Like
+/// [`SolverSerialBoundaryPruning`], the kept matchings have no dual node and are not in
+/// [`PrimalDualSolver::perfect_matching`]
+pub struct SolverSerialWarmStart {
+    pub solver: SolverSerial,
+    /// the matchings kept from the previous shot for the last syndrome pattern, see [`WarmStartCluster::matchings`]
+    pub kept: Vec<(VertexIndex, VertexIndex)>,
+    /// the number of defect vertices solved again for the last syndrome pattern
+    pub resolved_num: usize,
+    /// the number of times the inner solver is run for the last syndrome pattern
+    pub round_num: usize,
+    /// the clusters of the last syndrome pattern, which seed the next one
+    clusters: Vec<WarmStartCluster>,
+    /// (cluster, radius) of each defect vertex of the last syndrome pattern
+    cluster_of: BTreeMap<VertexIndex, (usize, Weight)>,
+    /// the weight of the kept matchings
+    kept_weight: Weight,
+    neighbors: Vec<Vec<(VertexIndex, Weight)>>,
+}
+
+impl SolverSerialWarmStart {
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        assert!(initializer.virtual_vertex_groups.is_empty(), "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`");
+        Self {
+            solver: SolverSerial::new(initializer),
+            kept: vec![],
+            resolved_num: 0,
+            round_num: 0,
+            clusters: vec![],
+            cluster_of: BTreeMap::new(),
+            kept_weight: 0,
+            neighbors: weighted_neighbors(initializer),
+        }
+    }
+
+    /// forget the matching of the previous shot, so that the next syndrome pattern is solved from scratch
+    pub fn clear_seed(&mut self) {
+        self.clusters.clear();
+        self.cluster_of.clear();
+    }
+
+    /// the matching of every defect vertex of the last syndrome pattern, where the peer is a virtual vertex if matched to
+    /// the boundary
+    pub fn defect_matching(&self) -> Vec<(VertexIndex, VertexIndex)> {
+        (self.clusters.iter())
+            .flat_map(|cluster| cluster.matchings.iter().cloned())
+            .collect()
+    }
+
+    /// the sum of the dual variables of a node and its ancestor blossoms, and the outermost blossom
+    #[allow(clippy::unnecessary_cast)]
+    fn outermost_blossom(nodes: &[Option<DualNodeCheckpoint>], node_index: NodeIndex) -> (Weight, NodeIndex) {
+        let mut node = nodes[node_index as usize].as_ref().unwrap();
+        let (mut radius, mut outermost) = (node.dual_variable, node_index);
+        while let Some(parent_index) = node.parent_blossom {
+            node = nodes[parent_index as usize].as_ref().unwrap();
+            radius += node.dual_variable;
+            outermost = parent_index;
+        }
+        (radius, outermost)
+    }
+
+    /// the kept clusters closer to the solved defect vertices than their radii
+    fn conflicting_clusters(&self, radii: &BTreeMap<VertexIndex, Weight>) -> BTreeSet<usize> {
+        let max_kept_radius = self.cluster_of.values().map(|&(_, radius)| radius).max().unwrap_or(0);
+        let mut conflicting = BTreeSet::new();
+        for (&vertex, &radius) in radii.iter() {
+            let nearby = nearby_vertices(&self.neighbors, vertex, radius + max_kept_radius, |peer| {
+                self.cluster_of.contains_key(&peer)
+            });
+            for (peer, distance) in nearby {
+                let (cluster_index, peer_radius) = self.cluster_of[&peer];
+                if distance < radius + peer_radius {
+                    conflicting.insert(cluster_index);
+                }
+            }
+        }
+        conflicting
+    }
+}
+
+impl PrimalDualSolver for SolverSerialWarmStart {
+    /// the matching of the last syndrome pattern is kept as the seed of the next one, see
+    /// [`SolverSerialWarmStart::clear_seed`]
+    fn clear(&mut self) {
+        self.solver.clear();
+        self.kept.clear();
+        self.kept_weight = 0;
+        self.resolved_num = 0;
+        self.round_num = 0;
+    }
+    fn reset_profiler(&mut self) {
+        self.solver.reset_profiler();
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        // the distances are invalid if the weights change
+        if !syndrome_pattern.erasures.is_empty() || !syndrome_pattern.dynamic_weights.is_empty() {
+            self.clear_seed();
+            self.resolved_num = syndrome_pattern.defect_vertices.len();
+            self.round_num = 1;
+            return self.solver.solve_visualizer(syndrome_pattern, visualizer);
+        }
+        let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+        let mut clusters: Vec<Option<WarmStartCluster>> = std::mem::take(&mut self.clusters).into_iter().map(Some).collect();
+        let mut resolved_vertices: BTreeSet<VertexIndex> = (defect_vertices.iter())
+            .filter(|vertex| !self.cluster_of.contains_key(vertex))
+            .cloned()
+            .collect();
+        let mut undone: BTreeSet<usize> = (self.cluster_of.iter())
+            .filter(|(vertex, _)| !defect_vertices.contains(vertex))
+            .map(|(_, &(cluster_index, _))| cluster_index)
+            .collect();
+        let (nodes, radii, roots) = loop {
+            for cluster_index in std::mem::take(&mut undone) {
+                let cluster = clusters[cluster_index].take().unwrap();
+                for (vertex_1, vertex_2) in cluster.matchings {
+                    for vertex in [vertex_1, vertex_2] {
+                        if self.cluster_of.remove(&vertex).is_some() && defect_vertices.contains(&vertex) {
+                            resolved_vertices.insert(vertex);
+                        }
+                    }
+                }
+            }
+            self.round_num += 1;
+            self.solver.clear();
+            self.solver
+                .solve(&SyndromePattern::new_vertices(resolved_vertices.iter().cloned().collect()));
+            // the radius and the outermost blossom of each solved defect vertex
+            let nodes = self.solver.interface_ptr.checkpoint().nodes;
+            let mut radii = BTreeMap::<VertexIndex, Weight>::new();
+            let mut roots = BTreeMap::<VertexIndex, NodeIndex>::new();
+            for (node_index, node) in nodes.iter().enumerate() {
+                if let Some(DualNodeCheckpoint {
+                    class: DualNodeClassCheckpoint::DefectVertex { defect_index },
+                    ..
+                }) = node
+                {
+                    let (radius, root) = Self::outermost_blossom(&nodes, node_index as NodeIndex);
+                    radii.insert(*defect_index, radius);
+                    roots.insert(*defect_index, root);
+                }
+            }
+            undone = self.conflicting_clusters(&radii);
+            if undone.is_empty() {
+                break (nodes, radii, roots);
+            }
+        };
+        if visualizer.is_some() {
+            // replay the final round so that the visualizer shows the solution that is eventually used
+            self.solver.clear();
+            self.solver.solve_visualizer(
+                &SyndromePattern::new_vertices(resolved_vertices.iter().cloned().collect()),
+                visualizer,
+            );
+        }
+        self.resolved_num = resolved_vertices.len();
+        // keep the other clusters
+        for cluster in clusters.into_iter().flatten() {
+            for &(vertex_1, vertex_2) in cluster.matchings.iter() {
+                for vertex in [vertex_1, vertex_2] {
+                    if let Some((cluster_index, _)) = self.cluster_of.get_mut(&vertex) {
+                        *cluster_index = self.clusters.len();
+                    }
+                }
+            }
+            self.kept.extend(cluster.matchings.iter().cloned());
+            self.kept_weight += cluster.weight;
+            self.clusters.push(cluster);
+        }
+        // group the solved defect vertices into new clusters, by the union-find of the outermost blossoms
+        let mut parents: BTreeMap<NodeIndex, NodeIndex> = roots.values().map(|&root| (root, root)).collect();
+        fn find(parents: &mut BTreeMap<NodeIndex, NodeIndex>, node_index: NodeIndex) -> NodeIndex {
+            let parent = parents[&node_index];
+            if parent == node_index {
+                return node_index;
+            }
+            let root = find(parents, parent);
+            parents.insert(node_index, root);
+            root
+        }
+        let union = |parents: &mut BTreeMap<NodeIndex, NodeIndex>, vertex_1: VertexIndex, vertex_2: VertexIndex| {
+            let (root_1, root_2) = (find(parents, roots[&vertex_1]), find(parents, roots[&vertex_2]));
+            parents.insert(root_1, root_2);
+        };
+        let max_radius = radii.values().cloned().max().unwrap_or(0);
+        for (&vertex, &radius) in radii.iter() {
+            for (peer, distance) in
+                nearby_vertices(&self.neighbors, vertex, radius + max_radius, |peer| radii.contains_key(&peer))
+            {
+                if distance < radius + radii[&peer] {
+                    union(&mut parents, vertex, peer);
+                }
+            }
+        }
+        let perfect_matching = self.solver.perfect_matching();
+        let matchings: Vec<(VertexIndex, VertexIndex)> = (perfect_matching.peer_matchings.iter())
+            .map(|(node_1, node_2)| (node_1.get_representative_vertex(), node_2.get_representative_vertex()))
+            .chain(
+                (perfect_matching.virtual_matchings.iter())
+                    .map(|(node, virtual_vertex)| (node.get_representative_vertex(), *virtual_vertex)),
+            )
+            .collect();
+        for &(vertex_1, vertex_2) in matchings.iter() {
+            if radii.contains_key(&vertex_2) {
+                union(&mut parents, vertex_1, vertex_2);
+            }
+        }
+        // the dual variables of the nodes of each cluster sum up to the weight of its matchings
+        let mut new_clusters = BTreeMap::<NodeIndex, WarmStartCluster>::new();
+        for (node_index, node) in nodes.iter().enumerate() {
+            let Some(node) = node else { continue };
+            let (_, outermost) = Self::outermost_blossom(&nodes, node_index as NodeIndex);
+            let cluster = new_clusters.entry(find(&mut parents, outermost)).or_insert(WarmStartCluster {
+                matchings: vec![],
+                weight: 0,
+            });
+            cluster.weight += node.dual_variable;
+        }
+        for (vertex_1, vertex_2) in matchings {
+            let root = find(&mut parents, roots[&vertex_1]);
+            new_clusters.get_mut(&root).unwrap().matchings.push((vertex_1, vertex_2));
+        }
+        for (root, cluster) in new_clusters {
+            for (&vertex, &radius) in radii.iter() {
+                if find(&mut parents, roots[&vertex]) == root {
+                    self.cluster_of.insert(vertex, (self.clusters.len(), radius));
+                }
+            }
+            self.clusters.push(cluster);
+        }
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.solver.perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        let mut subgraph = self.solver.subgraph_visualizer(visualizer);
+        if !self.kept.is_empty() {
+            for &(vertex_1, vertex_2) in self.kept.iter() {
+                self.solver.subgraph_builder.add_matching(vertex_1, vertex_2);
+            }
+            subgraph = self.solver.subgraph_builder.get_subgraph();
+        }
+        subgraph
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.solver.sum_dual_variables() + self.kept_weight
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "kept_num": self.kept.len(),
+            "resolved_num": self.resolved_num,
+            "round_num": self.round_num,
+            "solver": self.solver.generate_profiler_report(),
+        })
+    }
+    fn state_size(&self) -> Option<SolverStateSize> {
+        self.solver.state_size()
+    }
+    fn memory_usage(&self) -> Option<MemoryReport> {
+        self.solver.memory_usage()
+    }
+}
+
+/// the solution of a cluster of defect vertices solved by [`SolverClusterDecomposition`]
+#[derive(Debug, Clone)]
+struct DecomposedCluster {
+    defect_vertices: Vec<VertexIndex>,
+    /// (defect vertex, peer), where the peer is a virtual vertex if matched to the boundary
+    matched_pairs: Vec<(VertexIndex, VertexIndex)>,
+    subgraph: Vec<EdgeIndex>,
+    /// the sum of the dual variables of the cluster
+    weight: Weight,
+    /// the radius of each defect vertex, see [`SolverSerialWarmStart`]
+    radii: Vec<Weight>,
+}
+
+impl DecomposedCluster {
+    #[allow(clippy::unnecessary_cast)]
+    fn solve(solver: &mut SolverSerial, syndrome_pattern: &SyndromePattern) -> Self {
+        solver.solve(syndrome_pattern);
+        let nodes = solver.interface_ptr.checkpoint().nodes;
+        let mut radii = BTreeMap::<VertexIndex, Weight>::new();
+        for (node_index, node) in nodes.iter().enumerate() {
+            if let Some(DualNodeCheckpoint {
+                class: DualNodeClassCheckpoint::DefectVertex { defect_index },
+                ..
+            }) = node
+            {
+                radii.insert(
+                    *defect_index,
+                    SolverSerialWarmStart::outermost_blossom(&nodes, node_index as NodeIndex).0,
+                );
+            }
+        }
+        let perfect_matching = solver.perfect_matching();
+        let matched_pairs = (perfect_matching.peer_matchings.iter())
+            .map(|(node_1, node_2)| (node_1.get_representative_vertex(), node_2.get_representative_vertex()))
+            .chain(
+                (perfect_matching.virtual_matchings.iter())
+                    .map(|(node, virtual_vertex)| (node.get_representative_vertex(), *virtual_vertex)),
+            )
+            .collect();
+        solver.subgraph_builder.load_perfect_matching(&perfect_matching);
+        let cluster = Self {
+            defect_vertices: radii.keys().cloned().collect(),
+            matched_pairs,
+            subgraph: solver.subgraph_builder.get_subgraph(),
+            weight: solver.sum_dual_variables(),
+            radii: radii.values().cloned().collect(),
+        };
+        solver.clear();
+        cluster
+    }
+}
+
+/// a pre-pass that groups the defect vertices into clusters and solves them independently and in parallel, giving
+/// parallelism without partitioning the decoding graph, which suits the shots of low physical error rates. As explained
+/// in [`SolverSerialWarmStart`], two clusters whose pairwise distances are at least the sum of the radii of their defect
+/// vertices cannot interact, so their minimum-weight matchings together are a minimum-weight matching of both. The
+/// radius of a defect vertex is bounded by its distance to the boundary, and the clusters are first grouped by a
+/// smaller estimate, the distance to the nearest defect vertex or the boundary; after solving, the clusters closer than
+/// the actual radii are merged and solved again, until no clusters interact. Syndrome patterns with erasures or dynamic
+/// weights are solved as a single cluster, since they change the distances, and so are decoding graphs without virtual
+/// vertices, where a cluster of odd defect vertices cannot be solved alone
+pub struct SolverClusterDecomposition {
+    pub solvers: Vec<SolverSerial>,
+    /// the defect vertices of each cluster of the last syndrome pattern
+    pub clusters: Vec<Vec<VertexIndex>>,
+    /// the number of times the clusters are solved in parallel for the last syndrome pattern
+    pub round_num: usize,
+    /// the number of clusters solved for the last syndrome pattern, including the ones merged afterwards
+    pub solved_num: usize,
+    initializer: SolverInitializer,
+    neighbors: Vec<Vec<(VertexIndex, Weight)>>,
+    /// the distance from each vertex to the nearest virtual vertex, [`Weight::MAX`] if there is none
+    boundary_distances: Vec<Weight>,
+    matched_pairs: Vec<(VertexIndex, VertexIndex)>,
+    subgraph: Vec<EdgeIndex>,
+    weight: Weight,
+}
+
+impl SolverClusterDecomposition {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, solver_num: usize) -> Self {
+        assert!(solver_num > 0, "at least one solver is required");
+        assert!(initializer.virtual_vertex_groups.is_empty(), "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`");
+        let neighbors = weighted_neighbors(initializer);
+        // Dijkstra from all the virtual vertices
+        let mut boundary_distances = vec![Weight::MAX; initializer.vertex_num as usize];
+        let mut queue = std::collections::BinaryHeap::new();
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            boundary_distances[virtual_vertex as usize] = 0;
+            queue.push(std::cmp::Reverse((0, virtual_vertex)));
+        }
+        while let Some(std::cmp::Reverse((distance, vertex))) = queue.pop() {
+            if distance > boundary_distances[vertex as usize] {
+                continue;
+            }
+            for &(neighbor, weight) in neighbors[vertex as usize].iter() {
+                if distance + weight < boundary_distances[neighbor as usize] {
+                    boundary_distances[neighbor as usize] = distance + weight;
+                    queue.push(std::cmp::Reverse((distance + weight, neighbor)));
+                }
+            }
+        }
+        Self {
+            solvers: (0..solver_num)
+                .into_par_iter()
+                .map(|_| SolverSerial::new(initializer))
+                .collect(),
+            clusters: vec![],
+            round_num: 0,
+            solved_num: 0,
+            initializer: initializer.clone(),
+            neighbors,
+            boundary_distances,
+            matched_pairs: vec![],
+            subgraph: vec![],
+            weight: 0,
+        }
+    }
+
+    /// the matching of every defect vertex, where the peer is a virtual vertex if matched to the boundary
+    pub fn defect_matching(&self) -> &[(VertexIndex, VertexIndex)] {
+        &self.matched_pairs
+    }
+
+    /// group the defect vertices by the union-find of the pairs closer than the sum of their radii
+    fn group_by_radii(&self, radii: &BTreeMap<VertexIndex, (usize, Weight)>) -> Vec<Vec<usize>> {
+        let group_num = radii.values().map(|&(group, _)| group + 1).max().unwrap_or(0);
+        let mut parents: Vec<usize> = (0..group_num).collect();
+        fn find(parents: &mut [usize], group: usize) -> usize {
+            if parents[group] != group {
+                parents[group] = find(parents, parents[group]);
+            }
+            parents[group]
+        }
+        let max_radius = radii.values().map(|&(_, radius)| radius).max().unwrap_or(0);
+        for (&vertex, &(group, radius)) in radii.iter() {
+            let nearby = nearby_vertices(&self.neighbors, vertex, radius.saturating_add(max_radius), |peer| {
+                radii.contains_key(&peer)
+            });
+            for (peer, distance) in nearby {
+                let (peer_group, peer_radius) = radii[&peer];
+                if distance < radius.saturating_add(peer_radius) {
+                    let (root, peer_root) = (find(&mut parents, group), find(&mut parents, peer_group));
+                    parents[root] = peer_root;
+                }
+            }
+        }
+        let mut merged = BTreeMap::<usize, Vec<usize>>::new();
+        for group in 0..group_num {
+            merged.entry(find(&mut parents, group)).or_default().push(group);
+        }
+        merged.into_values().collect()
+    }
+
+    /// solve the syndrome patterns in parallel, distributing them to the solvers evenly
+    fn solve_clusters(&mut self, syndrome_patterns: &[SyndromePattern]) -> Vec<DecomposedCluster> {
+        self.round_num += 1;
+        self.solved_num += syndrome_patterns.len();
+        let chunk_size = std::cmp::max(syndrome_patterns.len().div_ceil(self.solvers.len()), 1);
+        let clusters: Vec<Vec<DecomposedCluster>> = (self.solvers.par_iter_mut())
+            .zip(syndrome_patterns.par_chunks(chunk_size))
+            .map(|(solver, syndrome_patterns)| {
+                (syndrome_patterns.iter())
+                    .map(|syndrome_pattern| DecomposedCluster::solve(solver, syndrome_pattern))
+                    .collect()
+            })
+            .collect();
+        clusters.into_iter().flatten().collect()
+    }
+}
+
+impl PrimalDualSolver for SolverClusterDecomposition {
+    fn clear(&mut self) {
+        self.clusters.clear();
+        self.round_num = 0;
+        self.solved_num = 0;
+        self.matched_pairs.clear();
+        self.subgraph.clear();
+        self.weight = 0;
+    }
+    fn reset_profiler(&mut self) {
+        for solver in self.solvers.iter_mut() {
+            solver.reset_profiler();
+        }
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        assert!(visualizer.is_none(), "not supported");
+        let mut solved = if !syndrome_pattern.erasures.is_empty()
+            || !syndrome_pattern.dynamic_weights.is_empty()
+            || self.initializer.virtual_vertices.is_empty()
+        {
+            self.solve_clusters(std::slice::from_ref(syndrome_pattern))
+        } else {
+            // estimate the radii by the distance to the nearest defect vertex or the boundary
+            let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            let estimated_radii: BTreeMap<VertexIndex, (usize, Weight)> = (defect_vertices.iter().enumerate())
+                .map(|(group, &vertex)| {
+                    let boundary_distance = self.boundary_distances[vertex as usize];
+                    let nearby = nearby_vertices(&self.neighbors, vertex, boundary_distance, |peer| {
+                        defect_vertices.contains(&peer)
+                    });
+                    let nearest = nearby.iter().map(|&(_, distance)| distance).min();
+                    (vertex, (group, nearest.unwrap_or(boundary_distance)))
+                })
+                .collect();
+            let defect_vertices: Vec<VertexIndex> = defect_vertices.into_iter().collect();
+            let syndrome_patterns: Vec<SyndromePattern> = (self.group_by_radii(&estimated_radii).into_iter())
+                .map(|groups| {
+                    SyndromePattern::new_vertices(groups.into_iter().map(|group| defect_vertices[group]).collect())
+                })
+                .collect();
+            let mut solved = self.solve_clusters(&syndrome_patterns);
+            loop {
+                let radii: BTreeMap<VertexIndex, (usize, Weight)> = (solved.iter().enumerate())
+                    .flat_map(|(index, cluster)| {
+                        (cluster.defect_vertices.iter().zip(cluster.radii.iter()))
+                            .map(move |(&vertex, &radius)| (vertex, (index, radius)))
+                    })
+                    .collect();
+                let merged = self.group_by_radii(&radii);
+                if merged.len() == solved.len() {
+                    break solved;
+                }
+                // solve the merged clusters again and keep the others
+                let mut solved_clusters: Vec<Option<DecomposedCluster>> = solved.into_iter().map(Some).collect();
+                solved = vec![];
+                let mut syndrome_patterns = vec![];
+                for groups in merged {
+                    if groups.len() == 1 {
+                        solved.push(solved_clusters[groups[0]].take().unwrap());
+                    } else {
+                        let defect_vertices = (groups.into_iter())
+                            .flat_map(|group| solved_clusters[group].take().unwrap().defect_vertices)
+                            .collect();
+                        syndrome_patterns.push(SyndromePattern::new_vertices(defect_vertices));
+                    }
+                }
+                solved.extend(self.solve_clusters(&syndrome_patterns));
+            }
+        };
+        solved.sort_by(|cluster_1, cluster_2| cluster_1.defect_vertices.cmp(&cluster_2.defect_vertices));
+        // the paths of different clusters may overlap and cancel each other, like in the subgraph of a single solver
+        let mut subgraph = BTreeSet::<EdgeIndex>::new();
+        for cluster in solved {
+            for edge_index in cluster.subgraph {
+                if !subgraph.remove(&edge_index) {
+                    subgraph.insert(edge_index);
+                }
+            }
+            self.matched_pairs.extend(cluster.matched_pairs);
+            self.weight += cluster.weight;
+            self.clusters.push(cluster.defect_vertices);
+        }
+        self.subgraph = subgraph.into_iter().collect();
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        assert!(visualizer.is_none(), "not supported");
+        perfect_matching_of_pairs(&self.initializer, &self.matched_pairs)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        assert!(visualizer.is_none(), "not supported");
+        self.subgraph.clone()
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.weight
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "cluster_num": self.clusters.len(),
+            "round_num": self.round_num,
+            "solved_num": self.solved_num,
+            "solvers": self.solvers.iter().map(|solver| solver.generate_profiler_report()).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// predicts the logical observables without finishing the matching when the answer is already fixed; the observables
+/// must form a cut of the decoding graph, i.e. every cycle flips each observable an even number of times, so that
+/// the observables flipped by any path between two vertices only depend on their sides of the cut.
+/// The defect vertices are grouped into clusters where the distance between two clusters is at least the sum of their
+/// boundary distances, so some minimum-weight solution matches each cluster independently; a cluster whose defect
+/// vertices are strictly closer to the virtual vertices on a single side flips the same observables in every
+/// minimum-weight solution, so only the other clusters are solved by the inner solver. Since the skipped clusters
+/// have no correction, this is not a [`PrimalDualSolver`]
+pub struct SolverSerialObservable {
+    pub solver: SolverSerial,
+    pub graph: DecodingGraph,
+    /// the observables flipped by each edge
+    pub edge_masks: Vec<usize>,
+    /// the observables flipped by a path from a reference vertex of the connected component to each vertex
+    pub sides: Vec<usize>,
+    /// the defect vertices of the last syndrome pattern whose observables are fixed without solving
+    pub fixed: Vec<VertexIndex>,
+    boundary_distances: BoundaryDistances,
+    max_boundary_distance: Weight,
+    /// the side of the virtual vertices and the distance of every vertex to the nearest virtual vertex on that side
+    side_distances: Vec<(usize, Vec<Weight>)>,
+}
+
+impl SolverSerialObservable {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, edge_masks: &[usize]) -> Result<Self, String> {
+        assert!(initializer.virtual_vertex_groups.is_empty(), "virtual vertex groups are only supported by `SolverSerial`, see `SolverInitializer::expand_virtual_vertex_groups`");
+        if edge_masks.len() != initializer.weighted_edges.len() {
+            return Err(format!(
+                "{} edge masks are given for {} edges",
+                edge_masks.len(),
+                initializer.weighted_edges.len()
+            ));
+        }
+        let graph = DecodingGraph::new(initializer);
+        let vertex_num = initializer.vertex_num as usize;
+        let mut sides: Vec<Option<usize>> = vec![None; vertex_num];
+        for root in 0..vertex_num {
+            if sides[root].is_some() {
+                continue;
+            }
+            sides[root] = Some(0);
+            let mut stack = vec![root as VertexIndex];
+            while let Some(vertex_index) = stack.pop() {
+                let side = sides[vertex_index as usize].unwrap();
+                for &(peer, edge_index) in graph.neighbors(vertex_index) {
+                    let peer_side = side ^ edge_masks[edge_index as usize];
+                    match sides[peer as usize] {
+                        None => {
+                            sides[peer as usize] = Some(peer_side);
+                            stack.push(peer);
+                        }
+                        Some(existing) if existing != peer_side => {
+                            return Err(format!(
+                                "the observables are not a cut: edge {edge_index} closes a cycle flipping {:#x}",
+                                existing ^ peer_side
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        let sides: Vec<usize> = sides.into_iter().map(Option::unwrap).collect();
+        let boundary_distances = initializer.boundary_distances();
+        let max_boundary_distance = boundary_distances
+            .distances
+            .iter()
+            .filter(|&&distance| distance != Weight::MAX)
+            .max()
+            .cloned()
+            .unwrap_or(0);
+        let mut virtual_vertices_by_side = BTreeMap::<usize, Vec<VertexIndex>>::new();
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            (virtual_vertices_by_side.entry(sides[virtual_vertex as usize]).or_default()).push(virtual_vertex);
+        }
+        let side_distances = virtual_vertices_by_side
+            .into_iter()
+            .map(|(side, virtual_vertices)| {
+                let side_initializer =
+                    SolverInitializer::new(initializer.vertex_num, initializer.weighted_edges.clone(), virtual_vertices);
+                (side, side_initializer.boundary_distances().distances)
+            })
+            .collect();
+        Ok(Self {
+            solver: SolverSerial::new(initializer),
+            graph,
+            edge_masks: edge_masks.to_vec(),
+            sides,
+            fixed: vec![],
+            boundary_distances,
+            max_boundary_distance,
+            side_distances,
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.solver.clear();
+        self.fixed.clear();
+    }
+
+    /// the side of the virtual vertices that is strictly closer than the other sides, if any
+    #[allow(clippy::unnecessary_cast)]
+    fn nearest_side(&self, vertex_index: VertexIndex) -> Option<usize> {
+        let mut nearest: Option<(usize, Weight)> = None;
+        let mut is_tie = false;
+        for (side, distances) in self.side_distances.iter() {
+            let distance = distances[vertex_index as usize];
+            match nearest {
+                Some((_, nearest_distance)) if distance > nearest_distance => {}
+                Some((_, nearest_distance)) if distance == nearest_distance => is_tie = true,
+                _ => {
+                    nearest = Some((*side, distance));
+                    is_tie = false;
+                }
+            }
+        }
+        match nearest {
+            Some((side, distance)) if !is_tie && distance != Weight::MAX => Some(side),
+            _ => None,
+        }
+    }
+
+    /// the observables flipped by some minimum-weight solution, where the inner solver only solves the clusters whose
+    /// observables are not fixed; the syndrome pattern is solved entirely if the weights are changed by erasures or
+    /// dynamic weights, because the precomputed distances no longer hold
+    #[allow(clippy::unnecessary_cast)]
+    pub fn predict_observables(&mut self, syndrome_pattern: &SyndromePattern) -> usize {
+        if !syndrome_pattern.erasures.is_empty() || !syndrome_pattern.dynamic_weights.is_empty() {
+            self.solver.solve(syndrome_pattern);
+            return self.subgraph_observables();
+        }
+        let defect_vertices = &syndrome_pattern.defect_vertices;
+        let mut defect_indices = BTreeMap::<VertexIndex, usize>::new();
+        for (defect_index, &defect_vertex) in defect_vertices.iter().enumerate() {
+            defect_indices.insert(defect_vertex, defect_index);
+        }
+        // union-find of the clusters
+        fn find(parents: &mut [usize], mut defect_index: usize) -> usize {
+            while parents[defect_index] != defect_index {
+                parents[defect_index] = parents[parents[defect_index]];
+                defect_index = parents[defect_index];
+            }
+            defect_index
+        }
+        let mut parents: Vec<usize> = (0..defect_vertices.len()).collect();
+        let boundary_distances = &self.boundary_distances.distances;
+        for (defect_index, &defect_vertex) in defect_vertices.iter().enumerate() {
+            let boundary_distance = boundary_distances[defect_vertex as usize];
+            // defect vertices beyond this radius are far enough regardless of their boundary distances
+            let radius = boundary_distance.saturating_add(self.max_boundary_distance);
+            for (vertex_index, distance) in self.graph.vertices_within(defect_vertex, radius) {
+                let Some(&peer_index) = defect_indices.get(&vertex_index) else {
+                    continue;
+                };
+                if peer_index != defect_index
+                    && distance < boundary_distance.saturating_add(boundary_distances[vertex_index as usize])
+                {
+                    let (root, peer_root) = (find(&mut parents, defect_index), find(&mut parents, peer_index));
+                    parents[root] = peer_root;
+                }
+            }
+        }
+        let mut clusters = BTreeMap::<usize, Vec<VertexIndex>>::new();
+        for (defect_index, &defect_vertex) in defect_vertices.iter().enumerate() {
+            (clusters.entry(find(&mut parents, defect_index)).or_default()).push(defect_vertex);
+        }
+        let mut observables = 0;
+        let mut remaining = vec![];
+        for cluster in clusters.into_values() {
+            match self.nearest_side(cluster[0]) {
+                Some(side)
+                    if cluster
+                        .iter()
+                        .all(|&defect_vertex| self.nearest_side(defect_vertex) == Some(side)) =>
+                {
+                    // every defect vertex is either matched within the cluster or to the nearest side, and the number
+                    // of the latter has the same parity as the cluster size
+                    for &defect_vertex in cluster.iter() {
+                        observables ^= self.sides[defect_vertex as usize];
+                    }
+                    if cluster.len() % 2 == 1 {
+                        observables ^= side;
+                    }
+                    self.fixed.extend(cluster);
+                }
+                _ => remaining.extend(cluster),
+            }
+        }
+        if !remaining.is_empty() {
+            self.solver.solve(&SyndromePattern::new_vertices(remaining));
+            observables ^= self.subgraph_observables();
+        }
+        observables
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn subgraph_observables(&mut self) -> usize {
+        (self.solver.subgraph().iter()).fold(0, |observables, &edge_index| {
+            observables ^ self.edge_masks[edge_index as usize]
+        })
+    }
+}
+
+#[cfg(feature = "python_binding")]
+#[pyfunction]
+pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<LegacySolverSerial>()?;
+    m.add_class::<SolverSerial>()?;
+    m.add_class::<SolverDualParallel>()?;
+    m.add_class::<SolverParallel>()?;
+    m.add_class::<SolverErrorPatternLogger>()?;
+    Ok(())
+}
+
+/// the idle solvers of a [`SolverPool`] and the number of released ones still being cleared
+#[derive(Default)]
+struct SolverPoolState {
+    /// all cleared, in the order they are released
+    idle: VecDeque<SolverSerial>,
+    clearing_num: usize,
+}
+
+/// independent serial solvers that decode a batch of syndromes in parallel, one shot per solver at a time; unlike
+/// [`SolverParallel`] which splits a single large shot, this maximizes the throughput of many easy shots. The solvers
+/// can also be borrowed one at a time by [`SolverPool::acquire`] from multiple threads, and a released solver is cleared
+/// on the thread pool of this solver pool, so that the latency-critical path of a real-time decoder never pays for clearing
+pub struct SolverPool {
+    state: Arc<(Mutex<SolverPoolState>, Condvar)>,
+    /// one thread per solver, running the batches and the background clearing
+    thread_pool: rayon::ThreadPool,
+}
+
+impl SolverPool {
+    /// the solvers are constructed in parallel once and then reused by all the batches
+    pub fn new(initializer: &SolverInitializer, solver_num: usize) -> Self {
+        assert!(solver_num > 0, "at least one solver is required");
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(solver_num)
+            .build()
+            .expect("creating thread pool failed");
+        let idle = thread_pool.install(|| {
+            (0..solver_num)
+                .into_par_iter()
+                .map(|_| SolverSerial::new(initializer))
+                .collect()
+        });
+        Self {
+            state: Arc::new((Mutex::new(SolverPoolState { idle, clearing_num: 0 }), Condvar::new())),
+            thread_pool,
+        }
+    }
+
+    /// the number of idle solvers, not counting the ones being cleared
+    pub fn idle_num(&self) -> usize {
+        self.state.0.lock().unwrap().idle.len()
+    }
+
+    /// borrow a cleared solver, waiting for a released one to finish clearing if there is no idle solver; the solvers
+    /// are acquired in a round-robin manner, i.e. the one released the earliest comes first. Returns `None` if all the
+    /// solvers are acquired and not yet released
+    pub fn acquire(&self) -> Option<SolverSerial> {
+        let (state, cleared) = &*self.state;
+        let mut state = state.lock().unwrap();
+        loop {
+            if let Some(solver) = state.idle.pop_front() {
+                return Some(solver);
+            }
+            if state.clearing_num == 0 {
+                return None;
+            }
+            state = cleared.wait(state).unwrap();
+        }
+    }
+
+    /// give back a solver from [`SolverPool::acquire`] without clearing it; it's cleared in the background
+    pub fn release(&self, mut solver: SolverSerial) {
+        self.state.0.lock().unwrap().clearing_num += 1;
+        let state = self.state.clone();
+        self.thread_pool.spawn(move || {
+            solver.clear();
+            let (state, cleared) = &*state;
+            let mut state = state.lock().unwrap();
+            state.idle.push_back(solver);
+            state.clearing_num -= 1;
+            cleared.notify_all();
+        });
+    }
+
+    /// decode every syndrome pattern and apply `f` on the solver before it's cleared, returning the results in order
+    pub fn solve_batch_map<T: Send>(
+        &mut self,
+        syndrome_patterns: &[SyndromePattern],
+        f: impl Fn(&mut SolverSerial, &SyndromePattern) -> T + Sync,
+    ) -> Vec<T> {
+        let (state, cleared) = &*self.state;
+        let mut state = state.lock().unwrap();
+        while state.clearing_num > 0 {
+            state = cleared.wait(state).unwrap();
+        }
+        let solvers = &mut state.idle;
+        assert!(!solvers.is_empty(), "all the solvers are acquired");
+        let chunk_size = std::cmp::max(syndrome_patterns.len().div_ceil(solvers.len()), 1);
+        let results: Vec<Vec<T>> = self.thread_pool.install(|| {
+            solvers
+                .par_iter_mut()
+                .zip(syndrome_patterns.par_chunks(chunk_size))
+                .map(|(solver, syndrome_patterns)| {
+                    syndrome_patterns
+                        .iter()
+                        .map(|syndrome_pattern| {
+                            solver.solve(syndrome_pattern);
+                            let result = f(solver, syndrome_pattern);
+                            solver.clear();
+                            result
+                        })
+                        .collect()
+                })
+                .collect()
+        });
+        results.into_iter().flatten().collect()
+    }
+
+    /// decode every syndrome pattern, returning the subgraphs in order
+    pub fn solve_batch(&mut self, syndrome_patterns: &[SyndromePattern]) -> Vec<Vec<EdgeIndex>> {
+        self.solve_batch_map(syndrome_patterns, |solver, _| solver.subgraph())
+    }
+}
+
+/// several [`SolverParallel`] prepared at startup with different partition layouts of the same decoding graph, e.g.
+/// different cut positions; each shot is solved by the layout whose cuts are farthest from the defects, so that dense
+/// regions are not split between partitions
+pub struct PartitionedSolverSet {
+    pub solvers: Vec<SolverParallel>,
+    /// how many shots each layout has solved
+    pub selection_counts: Vec<usize>,
+    /// defects within this number of hops from a cut count towards the cost of a layout; the closer the costlier
+    pub selection_radius: usize,
+    /// the number of hops from each vertex to the nearest interface vertex, for each layout
+    cut_distances: Vec<Vec<usize>>,
+    /// the layout of the last solve
+    selected: usize,
+}
+
+impl PartitionedSolverSet {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(
+        initializer: &SolverInitializer,
+        partition_infos: &[PartitionInfo],
+        primal_dual_config: serde_json::Value,
+    ) -> Self {
+        assert!(!partition_infos.is_empty(), "at least one partition layout is required");
+        let initializer = Arc::new(initializer.clone());
+        let mut neighbors = vec![vec![]; initializer.vertex_num as usize];
+        for &(i, j, _) in initializer.weighted_edges.iter() {
+            neighbors[i as usize].push(j);
+            neighbors[j as usize].push(i);
+        }
+        let cut_distances = partition_infos
+            .iter()
+            .map(|partition_info| {
+                // breadth-first search from all the vertices owned by fusion units
+                let leaf_num = partition_info.config.partitions.len();
+                let mut distances = vec![usize::MAX; initializer.vertex_num as usize];
+                let mut queue = std::collections::VecDeque::new();
+                for (vertex_index, &unit_index) in partition_info.vertex_to_owning_unit.iter().enumerate() {
+                    if unit_index >= leaf_num {
+                        distances[vertex_index] = 0;
+                        queue.push_back(vertex_index as VertexIndex);
+                    }
+                }
+                while let Some(vertex_index) = queue.pop_front() {
+                    let distance = distances[vertex_index as usize];
+                    for &neighbor in neighbors[vertex_index as usize].iter() {
+                        if distances[neighbor as usize] == usize::MAX {
+                            distances[neighbor as usize] = distance + 1;
+                            queue.push_back(neighbor);
+                        }
+                    }
+                }
+                distances
+            })
+            .collect();
+        Self {
+            solvers: partition_infos
+                .iter()
+                .map(|partition_info| SolverParallel::new_shared(&initializer, partition_info, primal_dual_config.clone()))
+                .collect(),
+            selection_counts: vec![0; partition_infos.len()],
+            selection_radius: 2,
+            cut_distances,
+            selected: 0,
+        }
+    }
+
+    /// the cost of solving this syndrome with a layout: each defect near a cut adds `selection_radius + 1 - distance`
+    #[allow(clippy::unnecessary_cast)]
+    pub fn cost(&self, layout_index: usize, syndrome_pattern: &SyndromePattern) -> usize {
+        let distances = &self.cut_distances[layout_index];
+        (syndrome_pattern.defect_vertices.iter())
+            .map(|&defect_vertex| (self.selection_radius + 1).saturating_sub(distances[defect_vertex as usize]))
+            .sum()
+    }
+
+    /// the layout with the minimum cost, preferring the earlier one when tied
+    pub fn select(&self, syndrome_pattern: &SyndromePattern) -> usize {
+        (0..self.solvers.len())
+            .min_by_key(|&layout_index| self.cost(layout_index, syndrome_pattern))
+            .unwrap()
+    }
+
+    /// the layout that solved the last syndrome
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// the fraction of shots solved by each layout
+    pub fn selection_frequencies(&self) -> Vec<f64> {
+        let total: usize = self.selection_counts.iter().sum();
+        (self.selection_counts.iter())
+            .map(|&count| if total == 0 { 0. } else { count as f64 / total as f64 })
+            .collect()
+    }
+}
+
+impl PrimalDualSolver for PartitionedSolverSet {
+    fn clear(&mut self) {
+        self.solvers[self.selected].clear();
+    }
+    fn reset_profiler(&mut self) {
+        for solver in self.solvers.iter_mut() {
+            solver.reset_profiler();
+        }
+        self.selection_counts.iter_mut().for_each(|count| *count = 0);
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.selected = self.select(syndrome_pattern);
+        self.selection_counts[self.selected] += 1;
+        self.solvers[self.selected].solve_visualizer(syndrome_pattern, visualizer);
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.solvers[self.selected].perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        self.solvers[self.selected].subgraph_visualizer(visualizer)
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.solvers[self.selected].sum_dual_variables()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "selection_counts": self.selection_counts,
+            "solvers": self.solvers.iter().map(|solver| solver.generate_profiler_report()).collect::<Vec<_>>(),
+        })
+    }
+    fn state_size(&self) -> Option<SolverStateSize> {
+        let mut state_size = SolverStateSize::default();
+        for solver in self.solvers.iter() {
+            state_size += solver.state_size()?;
+        }
+        Some(state_size)
+    }
+    /// the layouts are summed up, so the units of different layouts are added together by their unit index
+    fn memory_usage(&self) -> Option<MemoryReport> {
+        let mut report = MemoryReport::default();
+        for solver in self.solvers.iter() {
+            report += solver.memory_usage()?;
+        }
+        Some(report)
+    }
+}
+
+/// the configuration of a solver, so that the implementation can be chosen at runtime, e.g. from a config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SolverConfig {
+    Serial,
+    DualParallel {
+        /// no partition if not given
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        partition_config: Option<PartitionConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        primal_dual_config: Option<serde_json::Value>,
+    },
+    Parallel {
+        /// no partition if not given
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        partition_config: Option<PartitionConfig>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        primal_dual_config: Option<serde_json::Value>,
+    },
+    Lexicographic {
+        secondary_objective: SecondaryObjective,
+    },
+    Progressive {
+        initial_radius: Weight,
+    },
+    BoundaryPruning,
+    ClusterDecomposition {
+        solver_num: usize,
+    },
+    BlossomV,
+}
+
+impl SolverConfig {
+    pub fn build(&self, initializer: &SolverInitializer) -> Box<dyn SolverTrait> {
+        let partition_info = |partition_config: &Option<PartitionConfig>| {
+            partition_config
+                .clone()
+                .unwrap_or_else(|| PartitionConfig::new(initializer.vertex_num))
+                .info()
+        };
+        let solver: Box<dyn PrimalDualSolver + Send> = match self {
+            Self::Serial => Box::new(SolverSerial::new(initializer)),
+            Self::DualParallel {
+                partition_config,
+                primal_dual_config,
+            } => Box::new(SolverDualParallel::new(
+                initializer,
+                &partition_info(partition_config),
+                primal_dual_config.clone().unwrap_or(json!({})),
+            )),
+            Self::Parallel {
+                partition_config,
+                primal_dual_config,
+            } => Box::new(SolverParallel::new(
+                initializer,
+                &partition_info(partition_config),
+                primal_dual_config.clone().unwrap_or(json!({})),
+            )),
+            Self::Lexicographic { secondary_objective } => Box::new(
+                SolverSerialLexicographic::new(initializer, *secondary_objective).unwrap_or_else(|error| panic!("{error}")),
+            ),
+            Self::Progressive { initial_radius } => Box::new(SolverSerialProgressive::new(initializer, *initial_radius)),
+            Self::BoundaryPruning => Box::new(SolverSerialBoundaryPruning::new(initializer)),
+            Self::ClusterDecomposition { solver_num } => Box::new(SolverClusterDecomposition::new(initializer, *solver_num)),
+            Self::BlossomV => Box::new(SolverBlossomV::new(initializer)),
+        };
+        Box::new(ConfiguredSolver {
+            config: self.clone(),
+            solver,
+            edge_annotations: initializer.edge_annotations.clone(),
+            edge_fault_ids: initializer.edge_fault_ids.clone(),
+        })
+    }
+}
+
+/// the result of [`SolverTrait::decode`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecodingResult {
+    pub subgraph: Vec<EdgeIndex>,
+    pub weight: Weight,
+    /// the annotation of each edge in the subgraph, empty if the decoding graph has no edge annotations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<serde_json::Value>,
+    /// the faults flipped by the subgraph, empty if the decoding graph has no edge fault ids, see [`subgraph_fault_ids`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fault_ids: Vec<FaultId>,
+}
+
+/// an object-safe facade over the solvers, so that applications can hold different implementations in a collection
+/// and swap them at runtime; like [`PrimalDualSolver`], a solver must be cleared before decoding the next syndrome
+pub trait SolverTrait: Send {
+    fn decode(&mut self, syndrome_pattern: &SyndromePattern) -> DecodingResult;
+    fn clear(&mut self);
+    fn profiler_report(&self) -> serde_json::Value;
+    fn config(&self) -> &SolverConfig;
+}
+
+/// a solver built from [`SolverConfig::build`]
+pub struct ConfiguredSolver {
+    pub config: SolverConfig,
+    pub solver: Box<dyn PrimalDualSolver + Send>,
+    /// see [`SolverInitializer::edge_annotations`]
+    pub edge_annotations: BTreeMap<EdgeIndex, serde_json::Value>,
+    /// see [`SolverInitializer::edge_fault_ids`]
+    pub edge_fault_ids: BTreeMap<EdgeIndex, Vec<FaultId>>,
+}
+
+impl SolverTrait for ConfiguredSolver {
+    fn decode(&mut self, syndrome_pattern: &SyndromePattern) -> DecodingResult {
+        self.solver.solve(syndrome_pattern);
+        let subgraph = self.solver.subgraph();
+        let annotations = if self.edge_annotations.is_empty() {
+            vec![]
+        } else {
+            (subgraph.iter())
+                .map(|edge_index| self.edge_annotations.get(edge_index).cloned().unwrap_or_default())
+                .collect()
+        };
+        DecodingResult {
+            fault_ids: subgraph_fault_ids(&self.edge_fault_ids, &subgraph),
+            subgraph,
+            weight: self.solver.sum_dual_variables(),
+            annotations,
+        }
+    }
+    fn clear(&mut self) {
+        self.solver.clear();
+        self.solver.reset_profiler();
+    }
+    fn profiler_report(&self) -> serde_json::Value {
+        self.solver.generate_profiler_report()
+    }
+    fn config(&self) -> &SolverConfig {
+        &self.config
+    }
+}
+
+/// several independent decoding graphs, e.g. the X and Z graphs or multiple patches, each solved by a solver built from
+/// the same [`SolverConfig`]; the graphs are decoded concurrently on the rayon thread pool and the results are returned
+/// per graph, where the vertex and edge indices are local to each graph
+pub struct SolverMultiGraph {
+    pub solvers: Vec<Box<dyn SolverTrait>>,
+    /// the first vertex of each graph in the disjoint union, see [`SolverInitializer::disjoint_union`]
+    vertex_biases: Vec<VertexIndex>,
+    /// the first edge of each graph in the disjoint union
+    edge_biases: Vec<EdgeIndex>,
+}
+
+impl SolverMultiGraph {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(config: &SolverConfig, initializers: &[SolverInitializer]) -> Self {
+        assert!(!initializers.is_empty(), "at least one decoding graph is required");
+        let (mut vertex_biases, mut edge_biases) = (vec![], vec![]);
+        let (mut vertex_bias, mut edge_bias) = (0, 0);
+        for initializer in initializers.iter() {
+            vertex_biases.push(vertex_bias);
+            edge_biases.push(edge_bias);
+            vertex_bias += initializer.vertex_num;
+            edge_bias += initializer.weighted_edges.len() as EdgeIndex;
+        }
+        Self {
+            solvers: initializers.par_iter().map(|initializer| config.build(initializer)).collect(),
+            vertex_biases,
+            edge_biases,
+        }
+    }
+
+    pub fn graph_num(&self) -> usize {
+        self.solvers.len()
+    }
+
+    /// decode one syndrome pattern per graph concurrently, returning the results in the order of the graphs
+    pub fn decode(&mut self, syndrome_patterns: &[SyndromePattern]) -> Vec<DecodingResult> {
+        assert_eq!(
+            syndrome_patterns.len(),
+            self.solvers.len(),
+            "one syndrome pattern is required for each graph"
+        );
+        (self.solvers.par_iter_mut())
+            .zip(syndrome_patterns.par_iter())
+            .map(|(solver, syndrome_pattern)| solver.decode(syndrome_pattern))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.solvers.par_iter_mut().for_each(|solver| solver.clear());
+    }
+
+    pub fn profiler_report(&self) -> serde_json::Value {
+        json!({
+            "graphs": self.solvers.iter().map(|solver| solver.profiler_report()).collect::<Vec<_>>(),
+        })
+    }
+
+    /// the graph that a vertex or an edge of the disjoint union belongs to, given the first index of each graph
+    fn locate<T: Copy + Ord>(biases: &[T], index: T) -> usize {
+        biases.partition_point(|&bias| bias <= index) - 1
+    }
+
+    /// split a syndrome pattern of the disjoint union of the graphs, in the order they are given, into the syndrome
+    /// pattern of each graph; this is useful when the defects of all the graphs are measured in a single shot
+    pub fn split_syndrome(&self, syndrome_pattern: &SyndromePattern) -> Vec<SyndromePattern> {
+        let mut syndrome_patterns = vec![SyndromePattern::new_empty(); self.solvers.len()];
+        for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+            let graph_index = Self::locate(&self.vertex_biases, vertex_index);
+            (syndrome_patterns[graph_index].defect_vertices).push(vertex_index - self.vertex_biases[graph_index]);
+        }
+        for &edge_index in syndrome_pattern.erasures.iter() {
+            let graph_index = Self::locate(&self.edge_biases, edge_index);
+            (syndrome_patterns[graph_index].erasures).push(edge_index - self.edge_biases[graph_index]);
+        }
+        for &(edge_index, weight) in syndrome_pattern.dynamic_weights.iter() {
+            let graph_index = Self::locate(&self.edge_biases, edge_index);
+            (syndrome_patterns[graph_index].dynamic_weights).push((edge_index - self.edge_biases[graph_index], weight));
+        }
+        syndrome_patterns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::verifier::*;
+    use super::*;
+
+    #[test]
+    fn mwpm_solver_pool_batch() {
+        // cargo test mwpm_solver_pool_batch -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.05, 500);
+        code.set_erasure_probability(0.02);
+        let initializer = code.get_initializer();
+        let syndrome_patterns: Vec<_> = (0..103).map(|seed| code.generate_random_errors(seed)).collect();
+        let mut pool = SolverPool::new(&initializer, 4);
+        let mut solver = SolverSerial::new(&initializer);
+        for _ in 0..2 {
+            // reuse the same solvers for multiple batches
+            let results = pool.solve_batch_map(&syndrome_patterns, |solver, _| {
+                (solver.subgraph(), solver.sum_dual_variables())
+            });
+            assert_eq!(results.len(), syndrome_patterns.len());
+            for (syndrome_pattern, (subgraph, weight)) in syndrome_patterns.iter().zip(results) {
+                solver.solve(syndrome_pattern);
+                assert_eq!(subgraph, solver.subgraph());
+                assert_eq!(weight, solver.sum_dual_variables());
+                solver.clear();
+            }
+        }
+        assert_eq!(pool.solve_batch(&syndrome_patterns[..2]).len(), 2);
+        assert!(pool.solve_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn mwpm_solver_pool_acquire() {
+        // cargo test mwpm_solver_pool_acquire -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.05, 500);
+        let initializer = code.get_initializer();
+        let syndrome_patterns: Vec<_> = (0..50).map(|seed| code.generate_random_errors(seed)).collect();
+        let mut pool = SolverPool::new(&initializer, 3);
+        let expected = pool.solve_batch(&syndrome_patterns);
+        for (syndrome_pattern, subgraph) in syndrome_patterns.iter().zip(expected.iter()) {
+            let mut solver = pool.acquire().unwrap();
+            solver.solve(syndrome_pattern);
+            assert_eq!(&solver.subgraph(), subgraph);
+            pool.release(solver);
+        }
+        // all the solvers are borrowed
+        let solvers: Vec<_> = (0..3).map(|_| pool.acquire().unwrap()).collect();
+        assert!(pool.acquire().is_none());
+        for (mut solver, syndrome_pattern) in solvers.into_iter().zip(syndrome_patterns.iter()) {
+            solver.solve(syndrome_pattern);
+            pool.release(solver);
+        }
+        // a batch waits for the released solvers to be cleared
+        assert_eq!(pool.solve_batch(&syndrome_patterns), expected);
+        assert_eq!(pool.idle_num(), 3);
+        // the pool is shared by multiple threads, each borrowing a solver at a time
+        std::thread::scope(|scope| {
+            for thread_index in 0..4 {
+                let (pool, syndrome_patterns, expected) = (&pool, &syndrome_patterns, &expected);
+                scope.spawn(move || {
+                    for index in (thread_index..syndrome_patterns.len()).step_by(4) {
+                        // a thread may find all the solvers borrowed by the others
+                        let mut solver = loop {
+                            if let Some(solver) = pool.acquire() {
+                                break solver;
+                            }
+                            std::thread::yield_now();
+                        };
+                        solver.solve(&syndrome_patterns[index]);
+                        assert_eq!(solver.subgraph(), expected[index]);
+                        pool.release(solver);
+                    }
+                });
+            }
+        });
+        assert_eq!(pool.solve_batch(&syndrome_patterns), expected);
+        assert_eq!(pool.idle_num(), 3);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_virtual_vertex_groups() {
+        // cargo test mwpm_solver_virtual_vertex_groups -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(3, 0.1, 500);
+        let mut initializer = code.get_initializer();
+        let edge_num = initializer.weighted_edges.len();
+        // vary the weights so that the optimal solutions are unique in most cases
+        for (edge_index, edge) in initializer.weighted_edges.iter_mut().enumerate() {
+            edge.2 = 2 * (5 + (edge_index * 7) % 11) as Weight;
+        }
+        let (left, right): (Vec<VertexIndex>, Vec<VertexIndex>) = initializer
+            .virtual_vertices
+            .iter()
+            .partition(|&&vertex_index| code.vertices[vertex_index as usize].position.j < 1.);
+        let group_parity = |subgraph: &[EdgeIndex], group: &[VertexIndex]| {
+            let degree: usize = subgraph
+                .iter()
+                .map(|&edge_index| {
+                    let (i, j, _) = initializer.weighted_edges[edge_index as usize];
+                    group.contains(&i) as usize + group.contains(&j) as usize
+                })
+                .sum();
+            degree % 2 == 1
+        };
+        for (left_odd, right_odd) in [(true, false), (false, false), (true, true)] {
+            let groups = vec![
+                VirtualVertexGroup {
+                    vertices: left.clone(),
+                    odd: left_odd,
+                },
+                VirtualVertexGroup {
+                    vertices: right.clone(),
+                    odd: right_odd,
+                },
+            ];
+            let grouped_initializer = initializer.clone().with_virtual_vertex_groups(groups);
+            let mut solver = SolverSerial::new(&grouped_initializer);
+            for seed in 0..30 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                let defects: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+                // the total parity of matchings into the boundaries must match the parity of the defects
+                if (left_odd ^ right_odd) != (defects.len() % 2 == 1) {
+                    continue;
+                }
+                // enumerate all edge subsets to find the minimum weight of a valid correction
+                let mut expected_weight = None;
+                for mask in 0..(1usize << edge_num) {
+                    let subgraph: Vec<EdgeIndex> =
+                        (0..edge_num).filter(|i| mask >> i & 1 == 1).map(|i| i as EdgeIndex).collect();
+                    if initializer.syndrome_of(&subgraph) == defects
+                        && group_parity(&subgraph, &left) == left_odd
+                        && group_parity(&subgraph, &right) == right_odd
+                    {
+                        let weight: Weight = subgraph.iter().map(|&i| initializer.weighted_edges[i as usize].2).sum();
+                        expected_weight = Some(expected_weight.map_or(weight, |w: Weight| w.min(weight)));
+                    }
+                }
+                solver.solve(&syndrome_pattern);
+                let subgraph = solver.subgraph();
+                assert_eq!(initializer.syndrome_of(&subgraph), defects, "seed {seed}");
+                assert_eq!(group_parity(&subgraph, &left), left_odd, "seed {seed}");
+                assert_eq!(group_parity(&subgraph, &right), right_odd, "seed {seed}");
+                let weight: Weight = subgraph.iter().map(|&i| initializer.weighted_edges[i as usize].2).sum();
+                assert_eq!(Some(weight), expected_weight, "seed {seed}");
+                assert_eq!(solver.sum_dual_variables(), weight, "seed {seed}");
+                solver.clear();
+            }
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_cluster_decomposition() {
+        // cargo test mwpm_solver_cluster_decomposition -- --nocapture
+        let mut code = CircuitLevelPlanarCode::new(7, 7, 0.002, 500);
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut cluster_solver = SolverClusterDecomposition::new(&initializer, 3);
+        let (mut cluster_num, mut merged_num) = (0, 0);
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            cluster_solver.solve(&syndrome_pattern);
+            let subgraph = cluster_solver.subgraph();
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.subgraph.extend(subgraph.iter().cloned());
+            assert_eq!(
+                cluster_solver.sum_dual_variables(),
+                serial_solver.sum_dual_variables(),
+                "seed {seed}"
+            );
+            assert_eq!(
+                subgraph_builder.total_weight(),
+                serial_solver.sum_dual_variables(),
+                "seed {seed}"
+            );
+            assert_eq!(
+                initializer.syndrome_of(&subgraph),
+                syndrome_pattern.defect_vertices.iter().cloned().collect(),
+                "seed {seed}"
+            );
+            // every defect vertex is matched once
+            let matched_num: usize = (cluster_solver.defect_matching().iter())
+                .map(|(_, peer)| if initializer.virtual_vertices.contains(peer) { 1 } else { 2 })
+                .sum();
+            assert_eq!(matched_num, syndrome_pattern.defect_vertices.len(), "seed {seed}");
+            cluster_num += cluster_solver.clusters.len();
+            merged_num += cluster_solver.solved_num - cluster_solver.clusters.len();
+            serial_solver.clear();
+            cluster_solver.clear();
+        }
+        println!("cluster_num: {cluster_num}, merged_num: {merged_num}");
+        assert!(cluster_num > 100);
+        // erasures are solved as a single cluster
+        let mut syndrome_pattern = code.generate_random_errors(0);
+        syndrome_pattern.erasures = vec![0, 1, 2];
+        serial_solver.solve(&syndrome_pattern);
+        cluster_solver.solve(&syndrome_pattern);
+        assert_eq!(cluster_solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+        assert_eq!((cluster_solver.clusters.len(), cluster_solver.round_num), (1, 1));
+    }
+
+    #[test]
+    fn mwpm_solver_dyn_facade() {
+        // cargo test mwpm_solver_dyn_facade -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(9, 0.05, 500);
+        let initializer = code.get_initializer();
+        let configs: Vec<SolverConfig> = serde_json::from_value(json!([
+            { "type": "serial" },
+            { "type": "dual_parallel" },
+            { "type": "parallel", "primal_dual_config": {} },
+            { "type": "lexicographic", "secondary_objective": "FewestBoundaryMatches" },
+            { "type": "progressive", "initial_radius": 1000 },
+            { "type": "boundary_pruning" },
+            { "type": "cluster_decomposition", "solver_num": 2 },
+        ]))
+        .unwrap();
+        let mut solvers: Vec<Box<dyn SolverTrait>> = configs.iter().map(|config| config.build(&initializer)).collect();
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let weights: Vec<Weight> = solvers
+                .iter_mut()
+                .map(|solver| {
+                    let result = solver.decode(&syndrome_pattern);
+                    let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                    subgraph_builder.subgraph.extend(result.subgraph.iter().cloned());
+                    assert_eq!(subgraph_builder.total_weight(), result.weight, "{:?}", solver.config());
+                    solver.clear();
+                    result.weight
+                })
+                .collect();
+            assert!(weights.iter().all(|&weight| weight == weights[0]), "seed {seed}: {weights:?}");
+        }
+        for solver in solvers.iter() {
+            let config = serde_json::to_value(solver.config()).unwrap();
+            assert!(config["type"].is_string());
+            assert!(solver.profiler_report().is_object());
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_multi_graph() {
+        // cargo test mwpm_solver_multi_graph -- --nocapture
+        let mut code_1 = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let mut code_2 = CodeCapacityRepetitionCode::new(9, 0.1, 500);
+        code_2.set_erasure_probability(0.05);
+        let initializers = [code_1.get_initializer(), code_2.get_initializer()];
+        let union = initializers[0].disjoint_union(&initializers[1]);
+        let config = SolverConfig::Serial;
+        let mut multi_solver = SolverMultiGraph::new(&config, &initializers);
+        assert_eq!(multi_solver.graph_num(), 2);
+        let mut solvers: Vec<_> = initializers.iter().map(|initializer| config.build(initializer)).collect();
+        let mut union_solver = SolverSerial::new(&union);
+        for seed in 0..30 {
+            let syndrome_patterns = [code_1.generate_random_errors(seed), code_2.generate_random_errors(seed)];
+            let results = multi_solver.decode(&syndrome_patterns);
+            for ((solver, syndrome_pattern), result) in solvers.iter_mut().zip(syndrome_patterns.iter()).zip(results) {
+                assert_eq!(solver.decode(syndrome_pattern), result, "seed {seed}");
+                solver.clear();
+            }
+            // a shot of the disjoint union is split into the shots of each graph
+            let vertex_bias = initializers[0].vertex_num;
+            let edge_bias = initializers[0].weighted_edges.len() as EdgeIndex;
+            let mut union_pattern = syndrome_patterns[0].clone();
+            union_pattern
+                .defect_vertices
+                .extend(syndrome_patterns[1].defect_vertices.iter().map(|&v| v + vertex_bias));
+            union_pattern
+                .erasures
+                .extend(syndrome_patterns[1].erasures.iter().map(|&e| e + edge_bias));
+            let split_patterns = multi_solver.split_syndrome(&union_pattern);
+            for (split_pattern, syndrome_pattern) in split_patterns.iter().zip(syndrome_patterns.iter()) {
+                assert_eq!(split_pattern.defect_vertices, syndrome_pattern.defect_vertices);
+                assert_eq!(split_pattern.erasures, syndrome_pattern.erasures);
+            }
+            multi_solver.clear();
+            let weight: Weight = (multi_solver.decode(&split_patterns).iter())
+                .map(|result| result.weight)
+                .sum();
+            union_solver.solve(&union_pattern);
+            assert_eq!(weight, union_solver.sum_dual_variables(), "seed {seed}");
+            union_solver.clear();
+            multi_solver.clear();
+        }
+        assert_eq!(multi_solver.profiler_report()["graphs"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn mwpm_solver_boundary_pruning() {
+        // cargo test mwpm_solver_boundary_pruning -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(15, 0.03, 500);
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut pruning_solver = SolverSerialBoundaryPruning::new(&initializer);
+        let mut pruned_num = 0;
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            pruning_solver.solve(&syndrome_pattern);
+            let subgraph = pruning_solver.subgraph();
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.subgraph.extend(subgraph.iter().cloned());
+            assert_eq!(
+                subgraph_builder.total_weight(),
+                serial_solver.sum_dual_variables(),
+                "seed {seed}"
+            );
+            assert_eq!(
+                pruning_solver.sum_dual_variables(),
+                serial_solver.sum_dual_variables(),
+                "seed {seed}"
+            );
+            let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(initializer.syndrome_of(&subgraph), defect_vertices, "seed {seed}");
+            pruned_num += pruning_solver.pruned.len();
+            serial_solver.clear();
+            pruning_solver.clear();
+        }
+        assert!(pruned_num > 0);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_warm_start() {
+        // cargo test mwpm_solver_warm_start -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(31, 0.01, 500);
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut warm_solver = SolverSerialWarmStart::new(&initializer);
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let base_defect_vertices: BTreeSet<VertexIndex> =
+            code.generate_random_errors(0).defect_vertices.into_iter().collect();
+        let (mut kept_num, mut resolved_num) = (0, 0);
+        for shot in 0..100 {
+            // consecutive syndrome patterns differ in a few defect vertices
+            let mut defect_vertices = base_defect_vertices.clone();
+            let vertex = (rng.next_u64() % initializer.vertex_num as u64) as VertexIndex;
+            if !initializer.virtual_vertices.contains(&vertex) && !defect_vertices.remove(&vertex) {
+                defect_vertices.insert(vertex);
+            }
+            let syndrome_pattern = SyndromePattern::new_vertices(defect_vertices.iter().cloned().collect());
+            serial_solver.solve(&syndrome_pattern);
+            warm_solver.solve(&syndrome_pattern);
+            let subgraph = warm_solver.subgraph();
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.subgraph.extend(subgraph.iter().cloned());
+            assert_eq!(
+                subgraph_builder.total_weight(),
+                serial_solver.sum_dual_variables(),
+                "shot {shot}"
+            );
+            assert_eq!(
+                warm_solver.sum_dual_variables(),
+                serial_solver.sum_dual_variables(),
+                "shot {shot}"
+            );
+            assert_eq!(initializer.syndrome_of(&subgraph), defect_vertices, "shot {shot}");
+            assert_eq!(
+                warm_solver.defect_matching().len(),
+                warm_solver.kept.len() + {
+                    let perfect_matching = warm_solver.perfect_matching();
+                    perfect_matching.peer_matchings.len() + perfect_matching.virtual_matchings.len()
+                }
+            );
+            kept_num += warm_solver.kept.len();
+            resolved_num += warm_solver.resolved_num;
+            serial_solver.clear();
+            warm_solver.clear();
+        }
+        assert!(kept_num > 0 && resolved_num < 100 * base_defect_vertices.len() / 2);
+        // the same syndrome pattern keeps every matching
+        let syndrome_pattern = code.generate_random_errors(1);
+        for round in 0..2 {
+            warm_solver.solve(&syndrome_pattern);
+            serial_solver.solve(&syndrome_pattern);
+            assert_eq!(warm_solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+            if round == 1 {
+                assert_eq!(warm_solver.resolved_num, 0);
+                assert_eq!(warm_solver.kept.len(), warm_solver.defect_matching().len());
+            }
+            serial_solver.clear();
+            warm_solver.clear();
+        }
+        // erasures fall back to solving from scratch
+        warm_solver.clear();
+        warm_solver.solve(&SyndromePattern::new(syndrome_pattern.defect_vertices.clone(), vec![0]));
+        assert_eq!(warm_solver.resolved_num, syndrome_pattern.defect_vertices.len());
+        assert!(warm_solver.kept.is_empty());
+        warm_solver.clear();
+        warm_solver.solve(&syndrome_pattern);
+        assert_eq!(warm_solver.resolved_num, syndrome_pattern.defect_vertices.len());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_observable_early_exit() {
+        // cargo test mwpm_solver_observable_early_exit -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(15, 0.03, 500);
+        // random weights avoid ties between minimum-weight solutions that flip different observables
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let mut initializer = code.get_initializer();
+        for (_, _, weight) in initializer.weighted_edges.iter_mut() {
+            *weight = 2 * (100 + (rng.next_f64() * 1000.) as Weight);
+        }
+        // the logical observable is flipped by the edges crossing the middle column
+        let positions = code.get_positions();
+        let middle = positions.iter().map(|position| position.j).sum::<f64>() / positions.len() as f64;
+        let is_left: Vec<bool> = positions.iter().map(|position| position.j < middle).collect();
+        let edge_masks: Vec<usize> = (initializer.weighted_edges.iter())
+            .map(|&(i, j, _)| (is_left[i as usize] != is_left[j as usize]) as usize)
+            .collect();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut observable_solver = SolverSerialObservable::new(&initializer, &edge_masks).unwrap();
+        let (mut fixed_num, mut skipped_num) = (0, 0);
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            let expected =
+                (serial_solver.subgraph().iter()).fold(0, |mask, &edge_index| mask ^ edge_masks[edge_index as usize]);
+            assert_eq!(
+                observable_solver.predict_observables(&syndrome_pattern),
+                expected,
+                "seed {seed}"
+            );
+            fixed_num += observable_solver.fixed.len();
+            if observable_solver.fixed.len() == syndrome_pattern.defect_vertices.len() {
+                skipped_num += 1;
+            }
+            serial_solver.clear();
+            observable_solver.clear();
+        }
+        assert!(fixed_num > 0 && skipped_num > 0);
+        // the observables must be a cut of the decoding graph
+        let triangle = SolverInitializer::new(3, vec![(0, 1, 2), (1, 2, 2), (0, 2, 2)], vec![]);
+        assert!(SolverSerialObservable::new(&triangle, &[1, 0, 0]).is_err());
+        assert!(SolverSerialObservable::new(&triangle, &[1, 1, 0]).is_ok());
+        assert!(SolverSerialObservable::new(&triangle, &[0, 0]).is_err());
+    }
+
+    #[test]
+    fn mwpm_solver_constrained_redecode() {
+        // cargo test mwpm_solver_constrained_redecode -- --nocapture
+        // a repetition code where every edge has weight 1000 and vertex `i` is `i + 1` edges from the left boundary
+        let code = CodeCapacityRepetitionCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerialConstrained::new(&initializer);
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![4, 5]);
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), 1000);
+        solver.clear();
+        solver.set_constraints(vec![MatchingConstraint::MustNotMatch(5, 4)]);
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), 5000 + 5000);
+        assert!(!solver.matched_pairs.iter().any(|&pair| pair == (4, 5) || pair == (5, 4)));
+        assert_eq!(initializer.syndrome_of(&solver.subgraph()), BTreeSet::from([4, 5]));
+        solver.clear();
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![3, 4, 6, 7]);
+        solver.set_constraints(vec![MatchingConstraint::MustMatch(4, 6)]);
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), 2000 + 4000);
+        assert!(solver.matched_pairs.contains(&(4, 6)));
+        solver.clear();
+        solver.set_constraints(vec![
+            MatchingConstraint::MustNotMatch(3, 4),
+            MatchingConstraint::MustNotMatch(6, 7),
+        ]);
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), 6000);
+        assert_eq!(solver.perfect_matching().peer_matchings.len(), 2);
+        solver.clear();
+        // invalid constraints are rejected without solving
+        for constraints in [
+            vec![MatchingConstraint::MustMatch(3, 5)],
+            vec![MatchingConstraint::MustNotMatch(3, 3)],
+            vec![MatchingConstraint::MustMatch(3, 4), MatchingConstraint::MustMatch(4, 6)],
+            vec![MatchingConstraint::MustMatch(3, 4), MatchingConstraint::MustNotMatch(4, 3)],
+        ] {
+            solver.set_constraints(constraints);
+            assert!(solver.solve_constrained(&syndrome_pattern).is_err());
+            assert!(solver.matched_pairs.is_empty());
+        }
+        // without constraints it's a minimum-weight solution, and forbidding its pairs never decreases the weight
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut solver = SolverSerialConstrained::new(&initializer);
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            serial_solver.solve(&syndrome_pattern);
+            solver.set_constraints(vec![]);
+            solver.solve(&syndrome_pattern);
+            let weight = solver.sum_dual_variables();
+            assert_eq!(weight, serial_solver.sum_dual_variables(), "seed {seed}");
+            assert_eq!(initializer.syndrome_of(&solver.subgraph()), defect_vertices, "seed {seed}");
+            let peer_pairs: Vec<_> = (solver.matched_pairs.iter())
+                .filter(|(_, vertex)| !initializer.virtual_vertices.contains(vertex))
+                .cloned()
+                .collect();
+            solver.clear();
+            solver.set_constraints(
+                peer_pairs
+                    .iter()
+                    .map(|&(a, b)| MatchingConstraint::MustNotMatch(a, b))
+                    .collect(),
+            );
+            solver.solve(&syndrome_pattern);
+            assert!(solver.sum_dual_variables() >= weight, "seed {seed}");
+            assert!(peer_pairs.iter().all(|pair| !solver.matched_pairs.contains(pair)));
+            assert_eq!(initializer.syndrome_of(&solver.subgraph()), defect_vertices, "seed {seed}");
+            serial_solver.clear();
+            solver.clear();
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_lexicographic_secondary_objective() {
+        // cargo test mwpm_solver_lexicographic_secondary_objective -- --nocapture
+        let d = 7;
+        let mut code = CodeCapacityPlanarCode::new(d, 0.05, 500);
+        let initializer = code.get_initializer();
+        let mut brute_force = BruteForceDecoder::new(&initializer);
+        let mut fewest_boundary =
+            SolverSerialLexicographic::new(&initializer, SecondaryObjective::FewestBoundaryMatches).unwrap();
+        let mut fewest_pairs = SolverSerialLexicographic::new(&initializer, SecondaryObjective::FewestMatchedPairs).unwrap();
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            if syndrome_pattern.defect_vertices.len() > BRUTE_FORCE_MAX_DEFECT_NUM {
+                continue;
+            }
+            let expected_weight = brute_force.solve_weight(&syndrome_pattern);
+            let mut boundary_match_nums = vec![];
+            for solver in [&mut fewest_boundary, &mut fewest_pairs] {
+                solver.solve(&syndrome_pattern);
+                assert_eq!(solver.sum_dual_variables(), expected_weight, "{syndrome_pattern:?}");
+                let perfect_matching = solver.perfect_matching();
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                subgraph_builder.load_perfect_matching(&perfect_matching);
+                assert_eq!(subgraph_builder.total_weight(), expected_weight);
+                boundary_match_nums.push(perfect_matching.virtual_matchings.len());
+                solver.clear();
+            }
+            assert!(boundary_match_nums[0] <= boundary_match_nums[1], "{syndrome_pattern:?}");
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_progressive_radius() {
+        // cargo test mwpm_solver_progressive_radius -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.08, 500);
+        code.set_erasure_probability(0.02);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let mut progressive_solvers: Vec<_> = [0, 1000, 3000]
+            .into_iter()
+            .map(|initial_radius| SolverSerialProgressive::new(&initializer, initial_radius))
+            .collect();
+        let mut multi_round_count = 0;
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let expected_weight = solver.sum_dual_variables();
+            solver.clear();
+            for progressive_solver in progressive_solvers.iter_mut() {
+                progressive_solver.solve(&syndrome_pattern);
+                assert_eq!(progressive_solver.sum_dual_variables(), expected_weight, "seed {seed}");
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+                subgraph_builder.load_subgraph(&progressive_solver.subgraph());
+                assert_eq!(subgraph_builder.total_weight(), expected_weight, "seed {seed}");
+                if progressive_solver.round_num > 1 {
+                    multi_round_count += 1;
+                }
+                progressive_solver.clear();
+            }
+        }
+        assert!(multi_round_count > 0, "the radius should be expanded in some cases");
+    }
+
+    #[test]
+    fn mwpm_solver_serial_checkpoint_resume() {
+        // cargo test mwpm_solver_serial_checkpoint_resume -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.05, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let mut resumed_solver = SolverSerial::new(&initializer);
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            // take a checkpoint at every step, and resume from the middle one
+            let mut checkpoints = vec![];
+            solver.primal_module.solve_step_callback(
+                &solver.interface_ptr,
+                &syndrome_pattern,
+                &mut solver.dual_module,
+                |interface, dual_module, primal_module, _| {
+                    checkpoints.push(SolverSerialCheckpoint::new(interface, dual_module, primal_module));
+                },
+            );
+            let expected_weight = solver.sum_dual_variables();
+            solver.clear();
+            if checkpoints.is_empty() {
+                continue;
+            }
+            let checkpoint = &checkpoints[checkpoints.len() / 2];
+            // round trip through JSON to make sure the checkpoint is self-contained
+            let checkpoint: SolverSerialCheckpoint =
+                serde_json::from_str(&serde_json::to_string(checkpoint).unwrap()).unwrap();
+            resumed_solver.load_checkpoint(&checkpoint);
+            resumed_solver.resume();
+            assert_eq!(resumed_solver.sum_dual_variables(), expected_weight, "seed {seed}");
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_perfect_matching(&resumed_solver.perfect_matching());
+            assert_eq!(subgraph_builder.total_weight(), expected_weight, "seed {seed}");
+            resumed_solver.clear();
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_serial_transaction_rollback() {
+        // cargo test mwpm_solver_serial_transaction_rollback -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.05, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let expected_weight = solver.sum_dual_variables();
+            solver.clear();
+            solver.load_syndrome(&syndrome_pattern);
+            let mut round = 0;
+            loop {
+                if round % 3 == 0 {
+                    // look ahead two rounds and then undo them
+                    let before = serde_json::to_value(solver.checkpoint()).unwrap();
+                    solver.begin_transaction();
+                    solver.step();
+                    solver.step();
+                    solver.rollback();
+                    assert_eq!(serde_json::to_value(solver.checkpoint()).unwrap(), before, "seed {seed}");
+                }
+                solver.begin_transaction();
+                let progress = solver.step();
+                solver.commit();
+                if !progress {
+                    break;
+                }
+                round += 1;
+            }
+            assert_eq!(solver.sum_dual_variables(), expected_weight, "seed {seed}");
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_perfect_matching(&solver.perfect_matching());
+            assert_eq!(subgraph_builder.total_weight(), expected_weight, "seed {seed}");
+            solver.clear();
+        }
+    }
+
+    /// two defects with equal weight to match each other or both to the boundary
+    #[test]
+    fn mwpm_solver_lexicographic_tie_break() {
+        // cargo test mwpm_solver_lexicographic_tie_break -- --nocapture
+        // 0 (virtual) - 1 - 2 - 3 (virtual), all edges have the same weight
+        let initializer = SolverInitializer::new(4, vec![(0, 1, 100), (1, 2, 200), (2, 3, 100)], vec![0, 3]);
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![1, 2]);
+        let mut solver = SolverSerialLexicographic::new(&initializer, SecondaryObjective::FewestBoundaryMatches).unwrap();
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), 200);
+        let perfect_matching = solver.perfect_matching();
+        assert_eq!(perfect_matching.peer_matchings.len(), 1);
+        assert_eq!(perfect_matching.virtual_matchings.len(), 0);
+        let mut solver = SolverSerialLexicographic::new(&initializer, SecondaryObjective::FewestMatchedPairs).unwrap();
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.sum_dual_variables(), 200);
+        let perfect_matching = solver.perfect_matching();
+        assert_eq!(perfect_matching.peer_matchings.len(), 0);
+        assert_eq!(perfect_matching.virtual_matchings.len(), 2);
+        // the tie is also broken between zero-weight edges
+        let initializer = SolverInitializer::new(4, vec![(0, 1, 0), (1, 2, 0), (2, 3, 0)], vec![0, 3]);
+        for (objective, virtual_matching_num) in [
+            (SecondaryObjective::FewestBoundaryMatches, 0),
+            (SecondaryObjective::FewestMatchedPairs, 2),
+        ] {
+            let mut solver = SolverSerialLexicographic::new(&initializer, objective).unwrap();
+            solver.solve(&syndrome_pattern);
+            assert_eq!(solver.sum_dual_variables(), 0);
+            assert_eq!(solver.perfect_matching().virtual_matchings.len(), virtual_matching_num);
+        }
+        // a weight that overflows when scaled is reported
+        let initializer = SolverInitializer::new(4, vec![(0, 1, 100), (1, 2, Weight::MAX / 2), (2, 3, 100)], vec![0, 3]);
+        assert!(matches!(
+            SolverSerialLexicographic::new(&initializer, SecondaryObjective::FewestBoundaryMatches),
+            Err(FusionError::WeightOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn mwpm_solver_random_tie_breaking() {
+        // cargo test mwpm_solver_random_tie_breaking -- --nocapture
+        let initializer = SolverInitializer::new(4, vec![(0, 1, 100), (1, 2, 200), (2, 3, 100)], vec![0, 3]);
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![1, 2]);
+        let mut boundary_match_nums = BTreeSet::new();
+        for seed in 0..16 {
+            let objective = SecondaryObjective::RandomTieBreaking { seed };
+            let mut subgraphs = vec![];
+            for _ in 0..2 {
+                let mut solver = SolverSerialLexicographic::new(&initializer, objective).unwrap();
+                solver.solve(&syndrome_pattern);
+                assert_eq!(solver.sum_dual_variables(), 200);
+                boundary_match_nums.insert(solver.perfect_matching().virtual_matchings.len());
+                subgraphs.push(solver.subgraph());
+            }
+            // the same seed always picks the same solution
+            assert_eq!(subgraphs[0], subgraphs[1]);
+        }
+        assert_eq!(boundary_match_nums, BTreeSet::from([0, 2]));
+        // the primary weight is recovered from the perturbed weight, also with dynamic weights
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut solvers = [
+            SolverSerialLexicographic::new(&initializer, SecondaryObjective::RandomTieBreaking { seed: 1 }).unwrap(),
+            SolverSerialLexicographic::new(&initializer, SecondaryObjective::RandomTieBreaking { seed: 1 }).unwrap(),
+        ];
+        for seed in 0..30 {
+            let mut syndrome_pattern = code.generate_random_errors(seed);
+            if seed % 3 == 1 {
+                syndrome_pattern.dynamic_weights = vec![(0, 200), (5, 0)];
+            }
+            serial_solver.solve(&syndrome_pattern);
+            let mut subgraphs = vec![];
+            for solver in solvers.iter_mut() {
+                solver.solve(&syndrome_pattern);
+                assert_eq!(solver.sum_dual_variables(), serial_solver.sum_dual_variables(), "seed {seed}");
+                assert_eq!(
+                    solver.primary_weight_of(solver.perturbed_weight()),
+                    solver.sum_dual_variables()
+                );
+                subgraphs.push(solver.subgraph());
+                solver.clear();
+            }
+            assert_eq!(subgraphs[0], subgraphs[1], "seed {seed}");
+            serial_solver.clear();
+        }
+    }
+
+    /// the RAM budget in bytes can be changed by the `FUSION_BLOSSOM_RAM_BUDGET` environment variable,
+    /// e.g. `FUSION_BLOSSOM_RAM_BUDGET=65536 cargo test mwpm_solver_serial_memory_footprint --features low_memory`
+    #[test]
+    fn mwpm_solver_serial_memory_footprint() {
+        // cargo test mwpm_solver_serial_memory_footprint -- --nocapture
+        let ram_budget: usize = std::env::var("FUSION_BLOSSOM_RAM_BUDGET")
+            .map(|value| value.parse().expect("RAM budget in bytes"))
+            .unwrap_or(1 << 20);
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 10);
+        let initializer = code.get_initializer();
+        let footprint = SolverSerial::memory_footprint(&initializer);
+        println!("{}", serde_json::to_string_pretty(&footprint).unwrap());
+        assert!(
+            footprint.fits(ram_budget),
+            "footprint {} bytes exceeds the budget {ram_budget} bytes",
+            footprint.total
+        );
+        let mut solver = SolverSerial::new(&initializer);
+        assert_eq!(solver.dual_module.vertices.len(), footprint.vertex_num);
+        assert_eq!(solver.dual_module.edges.len(), footprint.edge_num);
+        solver.solve(&code.generate_random_errors(0));
+    }
+
+    #[test]
+    fn mwpm_solver_memory_usage() {
+        // cargo test mwpm_solver_memory_usage -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let syndrome_pattern = code.generate_random_errors(0);
+        let footprint = SolverSerial::memory_footprint(&initializer);
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve(&syndrome_pattern);
+        let report = solver.memory_usage().unwrap();
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        assert!(report.units.is_empty());
+        assert!(report.vertices >= footprint.vertices && report.edges >= footprint.edges);
+        assert!(report.dual_nodes > 0 && report.primal_nodes > 0);
+        // the nodes are kept after clear
+        solver.clear();
+        assert_eq!(solver.memory_usage().unwrap(), report);
+        // the parallel solver reports each unit, where the mirrored vertices are counted more than once
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![VertexRange::new(0, 24), VertexRange::new(32, 56)];
+        partition_config.fusions = vec![(0, 1)];
+        let partition_info = partition_config.info();
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        solver.solve(&syndrome_pattern);
+        let parallel_report = solver.memory_usage().unwrap();
+        assert_eq!(parallel_report.units.len(), 3);
+        assert_eq!(
+            parallel_report.units.iter().map(MemoryReport::total).sum::<usize>(),
+            parallel_report.total()
+        );
+        assert!(parallel_report
+            .units
+            .iter()
+            .all(|unit| unit.vertices > 0 && unit.units.is_empty()));
+        assert!(parallel_report.vertices > report.vertices);
+    }
+
+    #[test]
+    fn mwpm_solver_round_summaries() {
+        // cargo test mwpm_solver_round_summaries -- --nocapture
+        // 0 (virtual) - 1 - 2 - 3 (virtual)
+        let initializer = SolverInitializer::new(4, vec![(0, 1, 100), (1, 2, 200), (2, 3, 100)], vec![0, 3]);
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![1, 2]);
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.round_summaries(), None, "disabled by default");
+        solver.clear();
+        solver.primal_module.write().round_summaries = Some(vec![]);
+        solver.solve(&syndrome_pattern);
+        let round_summaries = solver.round_summaries().unwrap();
+        println!("round_summaries: {round_summaries:?}");
+        assert_eq!(round_summaries[0].growth, Some(100));
+        assert_eq!(round_summaries[0].conflicts(), 0);
+        for round_summary in round_summaries[1..].iter() {
+            assert!(round_summary.growth.is_some() || round_summary.conflicts() > 0);
+        }
+        solver.clear();
+        assert_eq!(solver.round_summaries(), Some(vec![]));
+        // the parallel solver concatenates the rounds of all units
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![VertexRange::new(0, 24), VertexRange::new(32, 56)];
+        partition_config.fusions = vec![(0, 1)];
+        let partition_info = partition_config.info();
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({ "primal": { "record_rounds": true } }));
+        let mut serial_solver = SolverSerial::new(&initializer);
+        serial_solver.primal_module.write().round_summaries = Some(vec![]);
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            solver.solve(&syndrome_pattern);
+            let serial_rounds = serial_solver.round_summaries().unwrap();
+            let parallel_rounds = solver.round_summaries().unwrap();
+            assert_eq!(serial_rounds.is_empty(), syndrome_pattern.defect_vertices.is_empty());
+            assert_eq!(parallel_rounds.is_empty(), syndrome_pattern.defect_vertices.is_empty());
+            serial_solver.clear();
+            solver.clear();
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_partitioned_set_selection() {
+        // cargo test mwpm_solver_partitioned_set_selection -- --nocapture
+        let (d, noisy_measurements) = (5, 11);
+        let round_vertex_num = d * (d + 1);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let initializer = code.get_initializer();
+        let vertex_num = initializer.vertex_num;
+        // two partitions with a single round of interface vertices in between, at different rounds
+        let partition_infos: Vec<PartitionInfo> = [3, 8]
+            .iter()
+            .map(|&cut_round| {
+                let mut partition_config = PartitionConfig::new(vertex_num);
+                partition_config.partitions = vec![
+                    VertexRange::new(0, cut_round * round_vertex_num),
+                    VertexRange::new((cut_round + 1) * round_vertex_num, vertex_num),
+                ];
+                partition_config.fusions = vec![(0, 1)];
+                partition_config.info()
+            })
+            .collect();
+        let mut solver_set = PartitionedSolverSet::new(&initializer, &partition_infos, json!({}));
+        // defects around the first cut select the second layout and vice versa
+        let near_first_cut = SyndromePattern::new_vertices(vec![3 * round_vertex_num + 7, 3 * round_vertex_num + 8]);
+        let near_second_cut = SyndromePattern::new_vertices(vec![8 * round_vertex_num + 7, 8 * round_vertex_num + 8]);
+        assert_eq!(solver_set.select(&near_first_cut), 1);
+        assert_eq!(solver_set.select(&near_second_cut), 0);
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut syndrome_patterns: Vec<_> = (0..20).map(|seed| code.generate_random_errors(seed)).collect();
+        syndrome_patterns.push(near_first_cut);
+        syndrome_patterns.push(near_second_cut);
+        for (index, syndrome_pattern) in syndrome_patterns.iter().enumerate() {
+            solver_set.solve(syndrome_pattern);
+            serial_solver.solve(syndrome_pattern);
+            assert_eq!(solver_set.selected(), solver_set.select(syndrome_pattern));
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_subgraph(&solver_set.subgraph());
+            assert_eq!(
+                subgraph_builder.total_weight(),
+                serial_solver.sum_dual_variables(),
+                "syndrome {index}"
+            );
+            solver_set.clear();
+            serial_solver.clear();
+            assert_eq!(solver_set.state_size().unwrap().retained_nodes, 0);
+        }
+        assert_eq!(solver_set.selection_counts.iter().sum::<usize>(), syndrome_patterns.len());
+        assert!(solver_set.selection_counts.iter().all(|&count| count > 0));
+        let frequencies = solver_set.selection_frequencies();
+        assert!((frequencies.iter().sum::<f64>() - 1.).abs() < 1e-9);
+    }
+
+    /// empty shots skip the parallel units entirely, and the following non-empty shots are not affected
+    #[test]
+    fn mwpm_solver_empty_syndrome_fast_path() {
+        // cargo test mwpm_solver_empty_syndrome_fast_path -- --nocapture
+        use crate::example_partition::*;
+        let (d, noisy_measurements) = (5, 11);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+            .build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let mut solvers: Vec<Box<dyn PrimalDualSolver>> = vec![
+            Box::new(SolverParallel::new(&initializer, &partition_info, json!({}))),
+            Box::new(SolverDualParallel::new(&initializer, &partition_info, json!({}))),
+        ];
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for seed in 0..10 {
+            for syndrome_pattern in [SyndromePattern::new_empty(), code.generate_random_errors(seed)] {
+                serial_solver.solve(&syndrome_pattern);
+                for solver in solvers.iter_mut() {
+                    solver.solve(&syndrome_pattern);
+                    assert_eq!(solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+                    let subgraph = solver.subgraph();
+                    assert_eq!(subgraph.is_empty(), syndrome_pattern.defect_vertices.is_empty());
+                    let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                    subgraph_builder.load_subgraph(&subgraph);
+                    assert_eq!(subgraph_builder.total_weight(), serial_solver.sum_dual_variables());
+                    solver.clear();
+                }
+                serial_solver.clear();
+            }
+        }
+        // the units are still reported as solved after an empty shot
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        solver.solve(&SyndromePattern::new_empty());
+        for unit_index in 0..partition_info.units.len() {
+            solver.primal_module.wait_unit(unit_index);
+        }
+        assert!(solver.primal_module.units[0].read_recursive().event_time.is_none());
+    }
+
+    #[test]
+    fn mwpm_solver_parallel_repartition() {
+        // cargo test mwpm_solver_parallel_repartition -- --nocapture
+        use crate::example_partition::*;
+        let (d, noisy_measurements) = (5, 11);
+        let round_vertex_num = d * (d + 1);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+            .build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        // defects concentrated in the first rounds are spread over all the partitions
+        let dense_syndrome = SyndromePattern::new_vertices((0..3 * round_vertex_num).step_by(7).collect());
+        let balanced_config = partition_config.rebalance_layers(round_vertex_num, &dense_syndrome).unwrap();
+        let max_partition_defects = |config: &PartitionConfig| {
+            let info = config.info();
+            let partitioned = info.partition_syndrome_unordered(&dense_syndrome);
+            partitioned.iter().map(|pattern| pattern.defect_vertices.len()).max().unwrap()
+        };
+        assert!(max_partition_defects(&balanced_config) < max_partition_defects(&partition_config));
+        assert_eq!(balanced_config.fusions, partition_config.fusions);
+        assert!(partition_config
+            .rebalance_layers(round_vertex_num + 1, &dense_syndrome)
+            .is_err());
+        // the rebalanced solver gives optimal results
+        let mut solver = SolverParallel::new(
+            &initializer,
+            &partition_info,
+            json!({ "repartition_layer_vertex_num": round_vertex_num }),
+        );
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut syndrome_patterns: Vec<_> = (0..20).map(|seed| code.generate_random_errors(seed)).collect();
+        syndrome_patterns.push(dense_syndrome);
+        for (index, syndrome_pattern) in syndrome_patterns.iter().enumerate() {
+            solver.solve(syndrome_pattern);
+            serial_solver.solve(syndrome_pattern);
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_subgraph(&solver.subgraph());
+            assert_eq!(
+                subgraph_builder.total_weight(),
+                serial_solver.sum_dual_variables(),
+                "syndrome {index}"
+            );
+            solver.clear();
+            serial_solver.clear();
+        }
+        assert_eq!(
+            solver.dual_module.partition_info.config.partitions,
+            balanced_config.partitions
+        );
+    }
+
+    /// the sum of dual variables overflows when two defects each grow more than half of the maximum weight
+    #[test]
+    fn mwpm_solver_weight_overflow() {
+        // cargo test mwpm_solver_weight_overflow -- --nocapture
+        let weight = (Weight::MAX / 4 + 1) * 2;
+        let weighted_edges = vec![(0, 1, weight), (1, 2, weight + 2), (2, 3, weight + 2), (3, 4, weight)];
+        let initializer = SolverInitializer::new(5, weighted_edges, vec![0, 4]);
+        let validator = SyndromeValidator::new(&initializer);
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![1, 3]);
+        let mut solver = SolverSerial::new(&initializer);
+        assert_eq!(
+            solver.solve_checked(&syndrome_pattern, &validator),
+            Err(FusionError::WeightOverflow("sum_dual_variables"))
+        );
+        solver.clear();
+        solver.check_overflow().unwrap();
+        solver.set_weight_overflow(WeightOverflow::Saturate);
+        solver.solve_checked(&syndrome_pattern, &validator).unwrap();
+        assert_eq!(solver.sum_dual_variables(), Weight::MAX);
+        // the matching itself is not affected by the overflow
+        let perfect_matching = solver.perfect_matching();
+        assert!(perfect_matching.peer_matchings.is_empty());
+        let mut virtual_vertices: Vec<_> = perfect_matching.virtual_matchings.iter().map(|(_, v)| *v).collect();
+        virtual_vertices.sort();
+        assert_eq!(virtual_vertices, vec![0, 4]);
+        solver.clear();
+        solver
+            .solve_checked(&SyndromePattern::new_vertices(vec![1]), &validator)
+            .unwrap();
+        assert_eq!(solver.sum_dual_variables(), weight);
+    }
 }
@@ -0,0 +1,278 @@
+//! Partition Tuner
+//!
+//! Profile-guided tuning of a [`PartitionConfig`]: a calibration workload is decoded by the parallel solver, the running time
+//! of each unit is collected from [`crate::primal_module_parallel::PrimalModuleParallelUnitEventTime`], and then the
+//! partition boundaries are moved and the fusion tree is reshaped so that the units finish at similar times.
+//!
+//! The boundaries can only be moved in a layered layout, see [`PartitionConfig::rebalance_layers`].
+//!
+
+use super::mwpm_solver::*;
+use super::pointers::*;
+use super::util::*;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// the profile of a partition configuration on the calibration workload
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionProfile {
+    /// the profiled partition configuration
+    pub config: PartitionConfig,
+    /// the average running time of each unit in seconds, leaf units first and then the fusion units
+    pub unit_times: Vec<f64>,
+    /// the estimated time of decoding a shot given unlimited threads, i.e. the longest path in the fusion tree
+    pub critical_path_time: f64,
+    /// the ratio between the maximum and the average running time of leaf units, 1 means perfectly balanced
+    pub leaf_skew: f64,
+}
+
+/// the result of iterative tuning
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionTuneResult {
+    /// the profile of each tuning round, starting from the given configuration
+    pub rounds: Vec<PartitionProfile>,
+    /// the profiled configuration with the shortest critical path
+    pub best: PartitionConfig,
+}
+
+pub struct PartitionTuner {
+    /// the decoding graph, in the same vertex order as the partition configurations
+    pub initializer: Arc<SolverInitializer>,
+    /// the number of vertices in each layer, see [`PartitionConfig::rebalance_layers`]
+    pub layer_vertex_num: VertexNum,
+    /// the configuration of [`SolverParallel`] used in calibration, which should match the one used in production
+    pub primal_dual_config: serde_json::Value,
+}
+
+impl PartitionTuner {
+    pub fn new(initializer: &SolverInitializer, layer_vertex_num: VertexNum, primal_dual_config: serde_json::Value) -> Self {
+        Self {
+            initializer: Arc::new(initializer.clone()),
+            layer_vertex_num,
+            primal_dual_config,
+        }
+    }
+
+    /// decode the calibration workload and collect the average running time of each unit
+    pub fn profile(
+        &self,
+        config: &PartitionConfig,
+        syndrome_patterns: &[SyndromePattern],
+    ) -> Result<PartitionProfile, String> {
+        config.sanity_check()?;
+        let unit_count = config.partitions.len() + config.fusions.len();
+        let mut solver = SolverParallel::new_shared(&self.initializer, &config.info(), self.primal_dual_config.clone());
+        let mut unit_times = vec![0.; unit_count];
+        for syndrome_pattern in syndrome_patterns.iter() {
+            solver.solve(syndrome_pattern);
+            for (unit_time, unit_ptr) in unit_times.iter_mut().zip(solver.primal_module.units.iter()) {
+                if let Some(event_time) = unit_ptr.read_recursive().event_time.as_ref() {
+                    *unit_time += event_time.end - event_time.start;
+                }
+            }
+            solver.clear();
+        }
+        if !syndrome_patterns.is_empty() {
+            for unit_time in unit_times.iter_mut() {
+                *unit_time /= syndrome_patterns.len() as f64;
+            }
+        }
+        let leaf_times = &unit_times[..config.partitions.len()];
+        let average_leaf_time = leaf_times.iter().sum::<f64>() / leaf_times.len() as f64;
+        let max_leaf_time = leaf_times.iter().cloned().fold(0., f64::max);
+        Ok(PartitionProfile {
+            config: config.clone(),
+            critical_path_time: critical_path_time(config, &unit_times),
+            leaf_skew: if average_leaf_time > 0. {
+                max_leaf_time / average_leaf_time
+            } else {
+                1.
+            },
+            unit_times,
+        })
+    }
+
+    /// suggest a revised configuration from a profile: the running time of each leaf unit is spread evenly over its layers
+    /// to move the boundaries, and then the fusion tree is rebuilt for the predicted leaf times
+    #[allow(clippy::unnecessary_cast)]
+    pub fn suggest(&self, profile: &PartitionProfile) -> Result<PartitionConfig, String> {
+        let config = &profile.config;
+        let partition_num = config.partitions.len();
+        let layer_vertex_num = self.layer_vertex_num as usize;
+        let layer_num = config.layer_num(self.layer_vertex_num)?;
+        let mut layer_costs = vec![0.; layer_num];
+        let mut densities = Vec::with_capacity(partition_num);
+        for (partition, &unit_time) in config.partitions.iter().zip(profile.unit_times.iter()) {
+            let (start_layer, end_layer) = (
+                partition.start() as usize / layer_vertex_num,
+                partition.end() as usize / layer_vertex_num,
+            );
+            let density = unit_time / (end_layer - start_layer).max(1) as f64;
+            for layer_cost in layer_costs[start_layer..end_layer.min(layer_num)].iter_mut() {
+                *layer_cost = density;
+            }
+            densities.push(density);
+        }
+        // an interface layer lies between two adjacent partitions
+        for (partition_index, partition) in config.partitions.iter().enumerate().take(partition_num - 1) {
+            let interface_layer = partition.end() as usize / layer_vertex_num;
+            if interface_layer < layer_num {
+                layer_costs[interface_layer] = (densities[partition_index] + densities[partition_index + 1]) / 2.;
+            }
+        }
+        let mut revised = config.rebalance_layers_by_cost(self.layer_vertex_num, &layer_costs)?;
+        let leaf_costs: Vec<f64> = (revised.partitions.iter())
+            .map(|partition| {
+                let (start_layer, end_layer) = (
+                    partition.start() as usize / layer_vertex_num,
+                    partition.end() as usize / layer_vertex_num,
+                );
+                layer_costs[start_layer..end_layer].iter().sum()
+            })
+            .collect();
+        let fusion_times = &profile.unit_times[partition_num..];
+        let fusion_time = if fusion_times.is_empty() {
+            0.
+        } else {
+            fusion_times.iter().sum::<f64>() / fusion_times.len() as f64
+        };
+        revised.fusions = fusion_tree_by_cost(&leaf_costs, fusion_time);
+        revised.sanity_check()?;
+        Ok(revised)
+    }
+
+    /// alternately profile and revise the configuration for `iterations` rounds, returning the best profiled one
+    pub fn tune(
+        &self,
+        config: &PartitionConfig,
+        syndrome_patterns: &[SyndromePattern],
+        iterations: usize,
+    ) -> Result<PartitionTuneResult, String> {
+        let mut rounds: Vec<PartitionProfile> = vec![];
+        let mut config = config.clone();
+        for _ in 0..iterations.max(1) {
+            let profile = self.profile(&config, syndrome_patterns)?;
+            config = self.suggest(&profile)?;
+            rounds.push(profile);
+        }
+        let best = rounds
+            .iter()
+            .min_by(|a, b| a.critical_path_time.total_cmp(&b.critical_path_time))
+            .unwrap()
+            .config
+            .clone();
+        Ok(PartitionTuneResult { rounds, best })
+    }
+}
+
+/// the time when the root unit finishes, given the running time of each unit and unlimited threads
+pub fn critical_path_time(config: &PartitionConfig, unit_times: &[f64]) -> f64 {
+    let partition_num = config.partitions.len();
+    let mut finish_times = unit_times[..partition_num].to_vec();
+    for (fusion_index, &(left_index, right_index)) in config.fusions.iter().enumerate() {
+        let unit_time = unit_times[partition_num + fusion_index];
+        finish_times.push(finish_times[left_index].max(finish_times[right_index]) + unit_time);
+    }
+    finish_times.last().cloned().unwrap_or(0.)
+}
+
+/// build a fusion tree over the leaf partitions sorted by their vertex ranges: each time the two adjacent subtrees that can
+/// be fused the earliest are fused, so that balanced leaves give a balanced tree and a slow leaf is fused late
+pub fn fusion_tree_by_cost(leaf_costs: &[f64], fusion_time: f64) -> Vec<(usize, usize)> {
+    // (unit index, finish time) of each subtree, in the order of vertex ranges
+    let mut subtrees: Vec<(usize, f64)> = leaf_costs.iter().cloned().enumerate().collect();
+    let mut fusions = vec![];
+    while subtrees.len() > 1 {
+        let fuse_index = (0..subtrees.len() - 1)
+            .min_by(|&a, &b| {
+                let finish = |index: usize| subtrees[index].1.max(subtrees[index + 1].1);
+                finish(a).total_cmp(&finish(b))
+            })
+            .unwrap();
+        let (left, right) = (subtrees[fuse_index], subtrees.remove(fuse_index + 1));
+        subtrees[fuse_index] = (leaf_costs.len() + fusions.len(), left.1.max(right.1) + fusion_time);
+        fusions.push((left.0, right.0));
+    }
+    fusions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::example_partition::*;
+    use super::*;
+    use serde_json::json;
+
+    #[allow(clippy::unnecessary_cast)]
+    fn layered_config(layer_vertex_num: usize, partition_layers: &[usize], fusions: Vec<(usize, usize)>) -> PartitionConfig {
+        let layer_num = partition_layers.iter().sum::<usize>() + partition_layers.len() - 1;
+        let mut config = PartitionConfig::new((layer_num * layer_vertex_num) as VertexNum);
+        config.partitions.clear();
+        let mut start_layer = 0;
+        for &layers in partition_layers.iter() {
+            config.partitions.push(VertexRange::new(
+                (start_layer * layer_vertex_num) as VertexIndex,
+                ((start_layer + layers) * layer_vertex_num) as VertexIndex,
+            ));
+            start_layer += layers + 1;
+        }
+        config.fusions = fusions;
+        config
+    }
+
+    #[test]
+    fn partition_tuner_fusion_tree() {
+        // cargo test partition_tuner_fusion_tree -- --nocapture
+        assert_eq!(fusion_tree_by_cost(&[1.; 4], 1.), vec![(0, 1), (2, 3), (4, 5)]);
+        // the slow leaf is fused last
+        assert_eq!(fusion_tree_by_cost(&[10., 1., 1., 1.], 1.), vec![(1, 2), (4, 3), (0, 5)]);
+        assert_eq!(fusion_tree_by_cost(&[1.], 1.), vec![]);
+    }
+
+    #[test]
+    fn partition_tuner_suggest() {
+        // cargo test partition_tuner_suggest -- --nocapture
+        let config = layered_config(10, &[5, 5, 5, 5], vec![(0, 1), (4, 2), (5, 3)]);
+        let initializer = SolverInitializer::new(config.vertex_num, vec![], vec![]);
+        let tuner = PartitionTuner::new(&initializer, 10, json!({}));
+        let profile = PartitionProfile {
+            unit_times: vec![12., 4., 4., 4., 1., 1., 1.],
+            critical_path_time: critical_path_time(&config, &[12., 4., 4., 4., 1., 1., 1.]),
+            leaf_skew: 2.,
+            config,
+        };
+        assert_eq!(profile.critical_path_time, 15.);
+        let revised = tuner.suggest(&profile).unwrap();
+        println!("revised: {revised:?}");
+        // the slow partition shrinks while the others grow
+        assert!(revised.partitions[0].len() < profile.config.partitions[0].len());
+        assert!(revised.partitions[3].len() > profile.config.partitions[3].len());
+        // at the granularity of layers the first partition is still the slowest, so it's fused last
+        assert_eq!(revised.fusions, vec![(1, 2), (4, 3), (0, 5)]);
+    }
+
+    #[test]
+    fn partition_tuner_calibration() {
+        // cargo test partition_tuner_calibration -- --nocapture
+        let (d, noisy_measurements) = (5, 11);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let mut partition = PhenomenologicalPlanarCodeTimePartition::new(d, noisy_measurements, 3);
+        let config = partition.build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let syndrome_patterns: Vec<_> = (0..10).map(|seed| code.generate_random_errors(seed)).collect();
+        let tuner = PartitionTuner::new(&initializer, d * (d + 1), json!({}));
+        let result = tuner.tune(&config, &syndrome_patterns, 3).unwrap();
+        assert_eq!(result.rounds.len(), 3);
+        result.best.sanity_check().unwrap();
+        // the tuned configuration decodes the same as a serial solver
+        let mut parallel_solver = SolverParallel::new(&initializer, &result.best.info(), json!({}));
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for syndrome_pattern in syndrome_patterns.iter() {
+            parallel_solver.solve(syndrome_pattern);
+            serial_solver.solve(syndrome_pattern);
+            assert_eq!(parallel_solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+            parallel_solver.clear();
+            serial_solver.clear();
+        }
+    }
+}
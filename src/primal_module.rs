@@ -12,7 +12,9 @@ use super::visualize::*;
 use crate::derivative::Derivative;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -36,6 +38,65 @@ pub struct PerfectMatching {
     pub virtual_matchings: Vec<(DualNodePtr, VertexIndex)>,
 }
 
+/// a compact summary of one round of the solving loop, i.e. one [`GroupMaxUpdateLength`] reported by the dual module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct RoundSummary {
+    /// the length to grow in this round, or `None` if this round resolves conflicts
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub growth: Option<Weight>,
+    /// number of [`MaxUpdateLength::Conflicting`] events
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub conflicting: usize,
+    /// number of [`MaxUpdateLength::TouchingVirtual`] events
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub touching_virtual: usize,
+    /// number of [`MaxUpdateLength::BlossomNeedExpand`] events
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub blossom_need_expand: usize,
+    /// number of [`MaxUpdateLength::VertexShrinkStop`] events, including the pending ones
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub vertex_shrink_stop: usize,
+}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl RoundSummary {
+    /// total number of conflicts in this round
+    pub fn conflicts(&self) -> usize {
+        self.conflicting + self.touching_virtual + self.blossom_need_expand + self.vertex_shrink_stop
+    }
+
+    #[cfg(feature = "python_binding")]
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl RoundSummary {
+    pub fn new(group_max_update_length: &GroupMaxUpdateLength) -> Self {
+        let mut summary = Self::default();
+        match group_max_update_length {
+            GroupMaxUpdateLength::NonZeroGrow((length, _)) => {
+                summary.growth = Some(*length);
+            }
+            GroupMaxUpdateLength::Conflicts((list, pending_stops)) => {
+                for max_update_length in list.iter().chain(pending_stops.values()) {
+                    match max_update_length {
+                        MaxUpdateLength::NonZeroGrow(_) => {}
+                        MaxUpdateLength::Conflicting(..) => summary.conflicting += 1,
+                        MaxUpdateLength::TouchingVirtual(..) => summary.touching_virtual += 1,
+                        MaxUpdateLength::BlossomNeedExpand(_) => summary.blossom_need_expand += 1,
+                        MaxUpdateLength::VertexShrinkStop(_) => summary.vertex_shrink_stop += 1,
+                    }
+                }
+            }
+        }
+        summary
+    }
+}
+
 /// common trait that must be implemented for each implementation of primal module
 pub trait PrimalModuleImpl {
     /// create a primal module given the dual module
@@ -134,21 +195,44 @@ pub trait PrimalModuleImpl {
         visualizer: Option<&mut Visualizer>,
     ) where
         Self: FusionVisualizer + Sized,
+    {
+        interface.load(syndrome_pattern, dual_module);
+        self.load(interface);
+        self.solve_visualizer_interface_loaded(interface, dual_module, visualizer);
+    }
+
+    /// the same as [`PrimalModuleImpl::solve_visualizer`] except that the defects are already loaded in both the interface
+    /// and the primal module, e.g. by [`crate::dual_module_parallel::DualModuleParallel::load_sharded`]
+    fn solve_visualizer_interface_loaded<D: DualModuleImpl + FusionVisualizer>(
+        &mut self,
+        interface: &DualModuleInterfacePtr,
+        dual_module: &mut D,
+        visualizer: Option<&mut Visualizer>,
+    ) where
+        Self: FusionVisualizer + Sized,
     {
         if let Some(visualizer) = visualizer {
-            self.solve_step_callback(
+            visualizer.begin_solve();
+            self.solve_step_callback_interface_loaded(
                 interface,
-                syndrome_pattern,
                 dual_module,
                 |interface, dual_module, primal_module, group_max_update_length| {
                     #[cfg(test)]
                     println!("group_max_update_length: {:?}", group_max_update_length);
+                    if cfg!(debug_assertions) {
+                        // the graph-level sanity check takes O(|V|+|E|) time, only run in debug builds
+                        dual_module.sanity_check(interface).unwrap();
+                    }
                     if let Some(length) = group_max_update_length.get_none_zero_growth() {
-                        visualizer
-                            .snapshot_combined(format!("grow {length}"), vec![interface, dual_module, primal_module])
-                            .unwrap();
-                    } else {
-                        let first_conflict = format!("{:?}", group_max_update_length.peek().unwrap());
+                        if visualizer.sample(SnapshotEvent::Grow) {
+                            visualizer
+                                .snapshot_combined(format!("grow {length}"), vec![interface, dual_module, primal_module])
+                                .unwrap();
+                        }
+                    } else if visualizer.sample(SnapshotEvent::Conflict) {
+                        // the epochs are left out so that the snapshots of different runs are named the same
+                        let first_conflict =
+                            format!("{:?}", group_max_update_length.peek().unwrap().map_nodes(|key| key.index));
                         visualizer
                             .snapshot_combined(
                                 format!("resolve {first_conflict}"),
@@ -162,7 +246,7 @@ pub trait PrimalModuleImpl {
                 .snapshot_combined("solved".to_string(), vec![interface, dual_module, self])
                 .unwrap();
         } else {
-            self.solve(interface, syndrome_pattern, dual_module);
+            self.solve_step_callback_interface_loaded(interface, dual_module, |_, _, _, _| {});
         }
     }
 
@@ -188,18 +272,57 @@ pub trait PrimalModuleImpl {
     ) where
         F: FnMut(&DualModuleInterfacePtr, &mut D, &mut Self, &GroupMaxUpdateLength),
     {
+        if interface.read_recursive().active_nodes_count() == 0 {
+            return; // fast path: no dual node is growing or shrinking, e.g. an empty syndrome
+        }
         let mut group_max_update_length = dual_module.compute_maximum_update_length();
-        while !group_max_update_length.is_empty() {
+        loop {
+            if group_max_update_length.is_empty() {
+                // nothing else is moving, so the deferred work must be resumed now
+                if !self.resume_deferred(interface, dual_module, true) {
+                    break;
+                }
+                group_max_update_length = dual_module.compute_maximum_update_length();
+                continue;
+            }
+            self.record_round(&group_max_update_length);
             callback(interface, dual_module, self, &group_max_update_length);
             if let Some(length) = group_max_update_length.get_none_zero_growth() {
+                tracing_span!(DEBUG, "grow", length);
                 interface.grow(length, dual_module);
+                self.resume_deferred(interface, dual_module, false);
             } else {
+                tracing_span!(DEBUG, "resolve");
                 self.resolve(group_max_update_length, interface, dual_module);
             }
+            if self.is_early_terminated(interface) {
+                break;
+            }
             group_max_update_length = dual_module.compute_maximum_update_length();
         }
     }
 
+    /// resume the work that the primal module has deferred, e.g. pausing an alternating tree instead of forming a blossom;
+    /// when `force` is set, all of it must be resumed because the dual module reports no more update.
+    /// return whether anything is resumed; by default nothing is ever deferred
+    fn resume_deferred<D: DualModuleImpl>(
+        &mut self,
+        _interface: &DualModuleInterfacePtr,
+        _dual_module: &mut D,
+        _force: bool,
+    ) -> bool {
+        false
+    }
+
+    /// called once per round of the solving loop before growing or resolving; by default nothing is recorded
+    fn record_round(&mut self, _group_max_update_length: &GroupMaxUpdateLength) {}
+
+    /// whether the solving loop can stop without asking the dual module again, i.e. every defect is matched and all dual
+    /// variables are stationary; by default the loop only stops when the dual module reports no more update
+    fn is_early_terminated(&self, _interface: &DualModuleInterfacePtr) -> bool {
+        false
+    }
+
     /// performance profiler report
     fn generate_profiler_report(&self) -> serde_json::Value {
         json!({})
@@ -495,6 +618,12 @@ pub struct SubGraphBuilder {
     pub complete_graph: CompleteGraph,
     /// current subgraph, assuming edges are not very much
     pub subgraph: BTreeSet<EdgeIndex>,
+    /// optional pre-computed shortest paths, used when no edge weight is modified
+    pub shortest_path_trees: Option<Arc<ShortestPathTrees>>,
+    /// optional peeling of the erased edges in the subgraph, see [`SubGraphBuilder::use_erasure_peeling`]
+    pub erasure_peeler: Option<Arc<ErasurePeeler>>,
+    /// the erasures loaded since the last clear
+    erasures: Vec<EdgeIndex>,
 }
 
 impl SubGraphBuilder {
@@ -509,17 +638,35 @@ impl SubGraphBuilder {
             vertex_pair_edges,
             complete_graph: CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges),
             subgraph: BTreeSet::new(),
+            shortest_path_trees: None,
+            erasure_peeler: None,
+            erasures: vec![],
         }
     }
 
+    /// use pre-computed shortest paths to avoid running Dijkstra's algorithm for every matched pair,
+    /// see [`ShortestPathTrees`]; it can be shared between multiple builders of the same decoding graph
+    pub fn use_shortest_path_trees(&mut self, shortest_path_trees: Arc<ShortestPathTrees>) {
+        assert_eq!(shortest_path_trees.vertex_num, self.vertex_num, "vertex number mismatch");
+        self.shortest_path_trees = Some(shortest_path_trees);
+    }
+
+    /// realize the erased edges of the subgraph by the peeling decoder instead of the arbitrary zero-weight paths found
+    /// through the erasures, which gives the same weight, see [`ErasurePeeler::peel_subgraph`]
+    pub fn use_erasure_peeling(&mut self, erasure_peeler: Arc<ErasurePeeler>) {
+        self.erasure_peeler = Some(erasure_peeler);
+    }
+
     pub fn clear(&mut self) {
         self.subgraph.clear();
         self.complete_graph.reset();
+        self.erasures.clear();
     }
 
     /// temporarily set some edges to 0 weight, and when it resets, those edges will be reverted back to the original weight
     pub fn load_erasures(&mut self, erasures: &[EdgeIndex]) {
         self.complete_graph.load_erasures(erasures);
+        self.erasures.extend(erasures.iter().cloned());
     }
 
     pub fn load_dynamic_weights(&mut self, dynamic_weights: &[(EdgeIndex, Weight)]) {
@@ -568,7 +715,12 @@ impl SubGraphBuilder {
 
     /// add a matching, finding the minimum path and XOR them into the subgraph (if adding the same pair twice, they will cancel each other)
     pub fn add_matching(&mut self, vertex_1: VertexIndex, vertex_2: VertexIndex) {
-        let (path, _) = self.complete_graph.get_path(vertex_1, vertex_2);
+        let (path, _) = match self.shortest_path_trees.as_ref() {
+            Some(shortest_path_trees) if !self.complete_graph.edge_modifier.has_modified_edges() => {
+                shortest_path_trees.get_path(vertex_1, vertex_2)
+            }
+            _ => self.complete_graph.get_path(vertex_1, vertex_2),
+        };
         let mut a = vertex_1;
         for (vertex, _) in path.iter() {
             let b = *vertex;
@@ -595,7 +747,11 @@ impl SubGraphBuilder {
 
     /// get subgraph as a vec
     pub fn get_subgraph(&self) -> Vec<EdgeIndex> {
-        self.subgraph.iter().copied().collect()
+        let subgraph: Vec<EdgeIndex> = self.subgraph.iter().copied().collect();
+        match self.erasure_peeler.as_ref() {
+            Some(erasure_peeler) if !self.erasures.is_empty() => erasure_peeler.peel_subgraph(&self.erasures, &subgraph),
+            _ => subgraph,
+        }
     }
 }
 
@@ -623,5 +779,6 @@ impl FusionVisualizer for VisualizeSubgraph<'_> {
 pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<IntermediateMatching>()?;
     m.add_class::<PerfectMatching>()?;
+    m.add_class::<RoundSummary>()?;
     Ok(())
 }
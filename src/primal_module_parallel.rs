@@ -13,8 +13,13 @@ use super::util::*;
 use super::visualize::*;
 use crate::rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
 use std::ops::DerefMut;
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
 use std::time::{Duration, Instant};
 
 pub struct PrimalModuleParallel {
@@ -28,6 +33,131 @@ pub struct PrimalModuleParallel {
     pub thread_pool: Arc<rayon::ThreadPool>,
     /// the time of calling [`PrimalModuleParallel::parallel_solve_step_callback`] method
     pub last_solve_start_time: ArcRwLock<Instant>,
+    /// notified whenever a unit is solved, see [`PrimalModuleParallel::completion`]
+    completion: Arc<UnitCompletion>,
+    /// no unit is touched since the last clear, e.g. only empty syndromes are solved, so that clearing is constant time
+    is_clean: bool,
+    /// the decode latency of every streamed shot, see [`PrimalModuleParallel::stream_latency`]
+    stream_latency: Option<LatencyHistogram>,
+}
+
+/// the completion state of each unit in the current solve, which can be waited on from other threads while the solve
+/// is running; all the units become unsolved again when the primal module is cleared.
+/// Note that a solved unit may be fused into its parent right afterwards, so read its results under its lock
+pub struct UnitCompletion {
+    units: Vec<(Mutex<UnitCompletionState>, Condvar)>,
+    /// the channels that receive the index of each solved unit, see [`UnitCompletion::subscribe`]
+    subscribers: Mutex<Vec<Sender<usize>>>,
+}
+
+#[derive(Default)]
+struct UnitCompletionState {
+    is_solved: bool,
+    wakers: Vec<Waker>,
+}
+
+impl UnitCompletion {
+    fn new(unit_num: usize) -> Self {
+        Self {
+            units: (0..unit_num).map(|_| Default::default()).collect(),
+            subscribers: Mutex::new(vec![]),
+        }
+    }
+
+    fn reset(&self) {
+        for (state, _) in self.units.iter() {
+            let mut state = state.lock().unwrap();
+            state.is_solved = false;
+            state.wakers.clear();
+        }
+        self.subscribers.lock().unwrap().clear();
+    }
+
+    fn notify(&self, unit_index: usize) {
+        let (state, condvar) = &self.units[unit_index];
+        let mut state = state.lock().unwrap();
+        state.is_solved = true;
+        for waker in state.wakers.drain(..) {
+            waker.wake();
+        }
+        condvar.notify_all();
+        drop(state);
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|sender| sender.send(unit_index).is_ok());
+        if unit_index + 1 == self.units.len() {
+            subscribers.clear(); // the last unit is solved, close the channels
+        }
+    }
+
+    /// receive the index of each unit as soon as it's solved, in the current solve or the next one if none is running;
+    /// the channel is closed when the last unit is solved or the primal module is cleared
+    pub fn subscribe(&self) -> Receiver<usize> {
+        let (sender, receiver) = channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    pub fn is_solved(&self, unit_index: usize) -> bool {
+        self.units[unit_index].0.lock().unwrap().is_solved
+    }
+
+    /// block the current thread until the unit is solved
+    pub fn wait_unit(&self, unit_index: usize) {
+        let (state, condvar) = &self.units[unit_index];
+        let mut state = state.lock().unwrap();
+        while !state.is_solved {
+            state = condvar.wait(state).unwrap();
+        }
+    }
+
+    /// a future that resolves when the unit is solved, for integration into async pipelines
+    pub fn unit_future(self: &Arc<Self>, unit_index: usize) -> UnitFuture {
+        assert!(unit_index < self.units.len(), "unit {unit_index} doesn't exist");
+        UnitFuture {
+            completion: self.clone(),
+            unit_index,
+        }
+    }
+}
+
+pub struct UnitFuture {
+    completion: Arc<UnitCompletion>,
+    unit_index: usize,
+}
+
+/// receives the result of [`PrimalModuleParallel::parallel_solve_async`] from the thread pool
+struct AsyncSolveReceiver<T> {
+    state: Arc<Mutex<(Option<T>, Option<Waker>)>>,
+}
+
+impl<T> Future for AsyncSolveReceiver<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap();
+        match state.0.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.1 = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Future for UnitFuture {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.completion.units[self.unit_index].0.lock().unwrap();
+        if state.is_solved {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(context.waker())) {
+            state.wakers.push(context.waker().clone());
+        }
+        Poll::Pending
+    }
 }
 
 pub struct PrimalModuleParallelUnit {
@@ -47,8 +177,12 @@ pub struct PrimalModuleParallelUnit {
     pub parent: Option<PrimalModuleParallelUnitWeak>,
     /// record the time of events
     pub event_time: Option<PrimalModuleParallelUnitEventTime>,
+    /// record the defect vertices loaded by this unit and the time it takes
+    pub ingestion: Option<PrimalModuleParallelUnitIngestion>,
     /// streaming decode mocker, if exists, base partition will wait until specified time and then start decoding
     pub streaming_decode_mocker: Option<StreamingDecodeMocker>,
+    /// the matching of each defect when this unit is solved, only recorded when `record_defect_owner` is enabled
+    pub defect_matches: Vec<(VertexIndex, DefectMatchTarget)>,
 }
 
 pub type PrimalModuleParallelUnitPtr = ArcManualSafeLock<PrimalModuleParallelUnit>;
@@ -67,6 +201,53 @@ impl std::fmt::Debug for PrimalModuleParallelUnitWeak {
     }
 }
 
+/// the matching target of a defect vertex
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum DefectMatchTarget {
+    /// matched to another defect vertex
+    Peer(VertexIndex),
+    /// matched to a virtual vertex (or a mirrored vertex that is temporarily blocked)
+    VirtualVertex(VertexIndex),
+}
+
+/// which unit finally decides the matching of a defect, for fusion overhead research and partition debugging
+#[derive(Debug, Clone, Serialize)]
+pub struct DefectOwner {
+    /// the unit that last changes the matching of this defect
+    pub unit_index: usize,
+    /// the level of this unit in the fusion tree, 0 for base partitions
+    pub tree_level: usize,
+    /// the final matching of this defect
+    pub match_target: DefectMatchTarget,
+    /// the matching recorded by every unit that has solved this defect, from leaf to root
+    pub history: Vec<(usize, DefectMatchTarget)>,
+}
+
+/// the kind and the name of the snapshot taken by the solving callback of a unit
+fn snapshot_event_of(group_max_update_length: Option<&GroupMaxUpdateLength>) -> (SnapshotEvent, String) {
+    match group_max_update_length {
+        Some(group_max_update_length) => match group_max_update_length.get_none_zero_growth() {
+            Some(length) => (SnapshotEvent::Grow, format!("grow {length}")),
+            None => (
+                SnapshotEvent::Conflict,
+                format!(
+                    "resolve {:?}",
+                    group_max_update_length.peek().unwrap().map_nodes(|key| key.index)
+                ),
+            ),
+        },
+        None => (SnapshotEvent::Fusion, "unit solved".to_string()),
+    }
+}
+
+/// the type of an absent callback when solving the units
+type NoCallback<DualSerialModule> = fn(
+    &DualModuleInterfacePtr,
+    &DualModuleParallelUnit<DualSerialModule>,
+    &PrimalModuleSerialPtr,
+    Option<&GroupMaxUpdateLength>,
+);
+
 /// the time of critical events, for profiling purposes
 #[derive(Debug, Clone, Serialize)]
 pub struct PrimalModuleParallelUnitEventTime {
@@ -94,6 +275,27 @@ impl PrimalModuleParallelUnitEventTime {
     }
 }
 
+/// the syndrome ingestion of a unit, i.e. loading its owned defect vertices before solving
+#[derive(Debug, Clone, Serialize)]
+pub struct PrimalModuleParallelUnitIngestion {
+    /// the number of defect vertices loaded by this unit
+    pub defect_num: usize,
+    /// the time of loading these defect vertices, in seconds
+    pub load_time: f64,
+}
+
+/// the load imbalance of syndrome ingestion across the units in a single shot;
+/// a skew is the ratio between the maximum and the average, 1 means perfectly balanced
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestionSkew {
+    pub max_defect_num: usize,
+    pub average_defect_num: f64,
+    pub defect_num_skew: f64,
+    pub max_load_time: f64,
+    pub average_load_time: f64,
+    pub load_time_skew: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct PrimalModuleParallelConfig {
@@ -119,6 +321,17 @@ pub struct PrimalModuleParallelConfig {
     /// max tree size for the serial modules, for faster speed at the cost of less accuracy
     #[serde(default = "primal_module_parallel_default_configs::max_tree_size")]
     pub max_tree_size: usize,
+    /// exit the solving loop of each unit as soon as every defect is matched
+    #[serde(default = "primal_module_parallel_default_configs::early_termination")]
+    pub early_termination: bool,
+    /// record the matching of each defect whenever a unit is solved, see [`PrimalModuleParallel::defect_owners`]
+    #[serde(default = "primal_module_parallel_default_configs::record_defect_owner")]
+    pub record_defect_owner: bool,
+    /// record the summary of each round in every unit, see [`PrimalModuleParallel::round_summaries`]
+    #[serde(default = "primal_module_parallel_default_configs::record_rounds")]
+    pub record_rounds: bool,
+    /// record the decode latency of each streamed shot, see [`PrimalModuleParallel::stream_latency`]
+    pub stream_latency_histogram: Option<LatencyHistogramConfig>,
 }
 
 impl Default for PrimalModuleParallelConfig {
@@ -150,6 +363,15 @@ pub mod primal_module_parallel_default_configs {
     pub fn max_tree_size() -> usize {
         usize::MAX
     } // by default do not limit tree size
+    pub fn early_termination() -> bool {
+        false
+    } // by default disabled to keep the original termination condition
+    pub fn record_defect_owner() -> bool {
+        false
+    } // by default disabled: it computes an intermediate matching for every unit
+    pub fn record_rounds() -> bool {
+        false
+    } // by default disabled to avoid allocation in the solving loop
 }
 
 pub struct StreamingDecodeMocker {
@@ -157,7 +379,163 @@ pub struct StreamingDecodeMocker {
     pub bias: Duration,
 }
 
+/// the defect vertices of a single measurement round, see [`PrimalModuleParallel::parallel_solve_stream`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyndromeRound {
+    /// this round covers the vertices in `[previous_vertex_end, vertex_end)`, where `previous_vertex_end` is the
+    /// `vertex_end` of the previous round (0 for the first round)
+    pub vertex_end: VertexIndex,
+    /// the defect vertices in this round, in increasing order
+    pub defect_vertices: Vec<VertexIndex>,
+}
+
+impl SyndromeRound {
+    pub fn new(vertex_end: VertexIndex, defect_vertices: Vec<VertexIndex>) -> Self {
+        Self {
+            vertex_end,
+            defect_vertices,
+        }
+    }
+}
+
+/// the buckets of [`LatencyHistogram`]: like an HDR histogram, every power of 2 between `lowest` and `highest` is
+/// split into `sub_bucket_num` linear buckets, so that every latency is recorded with a relative error of at most
+/// `1 / sub_bucket_num` regardless of its magnitude
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LatencyHistogramConfig {
+    /// the upper bound of the first bucket, in seconds
+    pub lowest: f64,
+    /// the latencies above this are only counted in an overflow bucket, in seconds
+    pub highest: f64,
+    pub sub_bucket_num: usize,
+}
+
+impl Default for LatencyHistogramConfig {
+    fn default() -> Self {
+        Self {
+            lowest: 1e-7,
+            highest: 1.,
+            sub_bucket_num: 32,
+        }
+    }
+}
+
+/// the distribution of latencies in seconds, from which the tail latencies (e.g. p99.9) can be read
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    pub config: LatencyHistogramConfig,
+    /// the upper bound of each bucket in increasing order
+    bounds: Vec<f64>,
+    /// the number of latencies in each bucket, followed by the overflow bucket
+    counts: Vec<u64>,
+    pub count: u64,
+    pub sum: f64,
+    pub max: f64,
+}
+
+impl LatencyHistogram {
+    pub fn new(config: LatencyHistogramConfig) -> Self {
+        assert!(
+            config.lowest > 0. && config.highest >= config.lowest,
+            "invalid latency range [{}, {}]",
+            config.lowest,
+            config.highest
+        );
+        assert!(config.sub_bucket_num > 0, "at least one sub-bucket is required");
+        let mut bounds = vec![config.lowest];
+        let mut base = config.lowest;
+        while base < config.highest {
+            for sub_bucket in 1..=config.sub_bucket_num {
+                bounds.push(base * (1. + sub_bucket as f64 / config.sub_bucket_num as f64));
+            }
+            base *= 2.;
+        }
+        Self {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+            config,
+            count: 0,
+            sum: 0.,
+            max: 0.,
+        }
+    }
+
+    pub fn record(&mut self, latency: f64) {
+        let bucket = self.bounds.partition_point(|&bound| bound < latency);
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += latency;
+        self.max = self.max.max(latency);
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+        self.count = 0;
+        self.sum = 0.;
+        self.max = 0.;
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+
+    /// the upper bound of the bucket that contains the `quantile` (between 0 and 1) of the recorded latencies, which is
+    /// never above the maximum latency; 0 if nothing is recorded
+    pub fn percentile(&self, quantile: f64) -> f64 {
+        let rank = std::cmp::max((quantile * self.count as f64).ceil() as u64, 1);
+        let mut accumulated = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            accumulated += count;
+            if accumulated >= rank {
+                return self.bounds.get(bucket).map_or(self.max, |&bound| bound.min(self.max));
+            }
+        }
+        self.max
+    }
+
+    /// the non-empty buckets as `(upper bound, count)`, where the upper bound of the overflow bucket is infinity
+    pub fn buckets(&self) -> Vec<(f64, u64)> {
+        (self.counts.iter().enumerate())
+            .filter(|(_, &count)| count > 0)
+            .map(|(bucket, &count)| (self.bounds.get(bucket).cloned().unwrap_or(f64::INFINITY), count))
+            .collect()
+    }
+
+    pub fn report(&self) -> serde_json::Value {
+        json!({
+            "count": self.count,
+            "mean": self.mean(),
+            "max": self.max,
+            "p50": self.percentile(0.5),
+            "p90": self.percentile(0.9),
+            "p99": self.percentile(0.99),
+            "p99.9": self.percentile(0.999),
+            // JSON has no infinity, so the overflow bucket is reported with a null upper bound
+            "buckets": self.buckets().into_iter().map(|(bound, count)| (bound.is_finite().then_some(bound), count)).collect::<Vec<_>>(),
+        })
+    }
+}
+
 impl PrimalModuleParallel {
+    /// the estimated memory usage of the interface and the primal module of each unit
+    pub fn memory_usage(&self) -> MemoryReport {
+        MemoryReport::from_units(
+            (self.units.iter())
+                .map(|unit_ptr| {
+                    let unit = unit_ptr.read_recursive();
+                    let mut report = unit.interface_ptr.read_recursive().memory_usage();
+                    report += unit.serial_module.read_recursive().memory_usage();
+                    report
+                })
+                .collect(),
+        )
+    }
+
     /// recommended way to create a new instance, given a customized configuration
     pub fn new_config(
         initializer: &SolverInitializer,
@@ -189,6 +567,10 @@ impl PrimalModuleParallel {
                     // println!("unit_index: {unit_index}");
                     let primal_module = PrimalModuleSerialPtr::new_empty(initializer);
                     primal_module.write().max_tree_size = config.max_tree_size;
+                    primal_module.write().early_termination = config.early_termination;
+                    if config.record_rounds {
+                        primal_module.write().round_summaries = Some(vec![]);
+                    }
                     PrimalModuleParallelUnitPtr::new_wrapper(primal_module, unit_index, Arc::clone(&partition_info))
                 })
                 .collect_into_vec(&mut units);
@@ -216,12 +598,55 @@ impl PrimalModuleParallel {
         }
         Self {
             units,
-            config,
             partition_info,
             thread_pool: Arc::new(thread_pool),
             last_solve_start_time: ArcRwLock::new_value(Instant::now()),
+            completion: Arc::new(UnitCompletion::new(unit_count)),
+            is_clean: true,
+            stream_latency: config.stream_latency_histogram.clone().map(LatencyHistogram::new),
+            config,
         }
     }
+
+    /// the completion notifications of the units, which can be moved to other threads before calling
+    /// [`PrimalModuleParallel::parallel_solve`]; they are bound to this instance and never notified by a rebuilt one
+    pub fn completion(&self) -> Arc<UnitCompletion> {
+        self.completion.clone()
+    }
+
+    /// block the current thread until the unit is solved
+    pub fn wait_unit(&self, unit_index: usize) {
+        self.completion.wait_unit(unit_index)
+    }
+
+    /// solve the syndrome in the thread pool without blocking the current thread, e.g. a thread of an async runtime;
+    /// the modules are given back when the last unit is solved. To be notified of each unit, call
+    /// [`UnitCompletion::subscribe`] on [`PrimalModuleParallel::completion`] beforehand. Erasures are not supported
+    /// by the partitioned solve and are reported as [`FusionError::UnsupportedOperation`] without solving; if the solve
+    /// panics, the panic is reported as [`FusionError::Corrupted`]. The modules are dropped in both cases
+    pub async fn parallel_solve_async<DualSerialModule: DualModuleImpl + Send + Sync + 'static>(
+        mut self,
+        syndrome_pattern: SyndromePattern,
+        parallel_dual_module: DualModuleParallel<DualSerialModule>,
+    ) -> Result<(Self, DualModuleParallel<DualSerialModule>), FusionError> {
+        if !syndrome_pattern.erasures.is_empty() {
+            return Err(FusionError::UnsupportedOperation("erasures in a partitioned solve"));
+        }
+        let state = Arc::new(Mutex::new((None, None::<Waker>)));
+        let sender = state.clone();
+        let thread_pool = self.thread_pool.clone();
+        thread_pool.spawn(move || {
+            let result = catch_panic(|| self.parallel_solve(&syndrome_pattern, &parallel_dual_module))
+                .map(|_| (self, parallel_dual_module))
+                .map_err(FusionError::Corrupted);
+            let mut state = sender.lock().unwrap();
+            state.0 = Some(result);
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        });
+        AsyncSolveReceiver { state }.await
+    }
 }
 
 impl PrimalModuleImpl for PrimalModuleParallel {
@@ -235,15 +660,19 @@ impl PrimalModuleImpl for PrimalModuleParallel {
 
     #[inline(never)]
     fn clear(&mut self) {
-        self.thread_pool.scope(|_| {
-            self.units.par_iter().enumerate().for_each(|(unit_idx, unit_ptr)| {
-                let mut unit = unit_ptr.write();
-                let partition_unit_info = &unit.partition_info.units[unit_idx];
-                let is_active = partition_unit_info.children.is_none();
-                unit.clear();
-                unit.is_active = is_active;
+        if !self.is_clean {
+            self.thread_pool.scope(|_| {
+                self.units.par_iter().enumerate().for_each(|(unit_idx, unit_ptr)| {
+                    let mut unit = unit_ptr.write();
+                    let partition_unit_info = &unit.partition_info.units[unit_idx];
+                    let is_active = partition_unit_info.children.is_none();
+                    unit.clear();
+                    unit.is_active = is_active;
+                });
             });
-        });
+            self.is_clean = true;
+        }
+        self.completion.reset();
     }
 
     fn load_defect_dual_node(&mut self, _dual_node_ptr: &DualNodePtr) {
@@ -277,18 +706,44 @@ impl PrimalModuleImpl for PrimalModuleParallel {
 
     fn generate_profiler_report(&self) -> serde_json::Value {
         let event_time_vec: Vec<_> = self.units.iter().map(|ptr| ptr.read_recursive().event_time.clone()).collect();
-        json!({
+        let ingestion_vec: Vec<_> = self.units.iter().map(|ptr| ptr.read_recursive().ingestion.clone()).collect();
+        let mut report = json!({
             "event_time_vec": event_time_vec,
-        })
+            "ingestion_vec": ingestion_vec,
+            "ingestion_skew": self.ingestion_skew(),
+        });
+        if self.config.record_defect_owner {
+            report
+                .as_object_mut()
+                .unwrap()
+                .insert("defect_owners".to_string(), json!(self.defect_owners()));
+        }
+        if let Some(stream_latency) = &self.stream_latency {
+            report
+                .as_object_mut()
+                .unwrap()
+                .insert("stream_latency".to_string(), stream_latency.report());
+        }
+        report
     }
 }
 
 impl PrimalModuleParallel {
+    /// an empty syndrome, see [`SyndromePattern::is_empty`], returns immediately without touching the units;
+    /// a visualized solve, like [`crate::mwpm_solver::SolverDualParallel`], always runs the units so that every step can be snapshot
     pub fn parallel_solve<DualSerialModule: DualModuleImpl + Send + Sync>(
         &mut self,
         syndrome_pattern: &SyndromePattern,
         parallel_dual_module: &DualModuleParallel<DualSerialModule>,
     ) {
+        if syndrome_pattern.is_empty() {
+            // fast path: no unit has anything to solve, so they're left untouched as cleared, with the leaves active
+            *self.last_solve_start_time.write() = Instant::now();
+            for unit_index in 0..self.units.len() {
+                self.completion.notify(unit_index);
+            }
+            return;
+        }
         self.parallel_solve_step_callback(syndrome_pattern, parallel_dual_module, |_, _, _, _| {})
     }
 
@@ -299,34 +754,46 @@ impl PrimalModuleParallel {
         visualizer: Option<&mut Visualizer>,
     ) {
         if let Some(visualizer) = visualizer {
-            self.parallel_solve_step_callback(
-                syndrome_pattern,
-                parallel_dual_module,
-                |interface_ptr, dual_module, primal_module, group_max_update_length| {
-                    if let Some(group_max_update_length) = group_max_update_length {
+            visualizer.begin_solve();
+            if self.config.debug_sequential {
+                self.parallel_solve_step_callback(
+                    syndrome_pattern,
+                    parallel_dual_module,
+                    |interface_ptr, dual_module, primal_module, group_max_update_length| {
                         if cfg!(debug_assertions) {
-                            println!("group_max_update_length: {:?}", group_max_update_length);
+                            if let Some(group_max_update_length) = group_max_update_length {
+                                println!("group_max_update_length: {:?}", group_max_update_length);
+                            }
                         }
-                        if let Some(length) = group_max_update_length.get_none_zero_growth() {
-                            visualizer
-                                .snapshot_combined(format!("grow {length}"), vec![interface_ptr, dual_module, primal_module])
-                                .unwrap();
-                        } else {
-                            let first_conflict = format!("{:?}", group_max_update_length.peek().unwrap());
+                        let (event, name) = snapshot_event_of(group_max_update_length);
+                        if visualizer.sample(event) {
                             visualizer
-                                .snapshot_combined(
-                                    format!("resolve {first_conflict}"),
-                                    vec![interface_ptr, dual_module, primal_module],
-                                )
+                                .snapshot_combined(name, vec![interface_ptr, dual_module, primal_module])
                                 .unwrap();
-                        };
-                    } else {
-                        visualizer
-                            .snapshot_combined("unit solved".to_string(), vec![interface_ptr, dual_module, primal_module])
-                            .unwrap();
-                    }
-                },
-            );
+                        }
+                    },
+                );
+            } else {
+                // the units take snapshots concurrently, which are then saved in the order of time
+                let buffer = SnapshotBuffer::new(self.units.len(), visualizer.snapshot_policy.clone());
+                self.parallel_solve_concurrent_callback(
+                    syndrome_pattern,
+                    parallel_dual_module,
+                    &|interface_ptr: &DualModuleInterfacePtr,
+                      dual_module: &DualModuleParallelUnit<DualSerialModule>,
+                      primal_module: &PrimalModuleSerialPtr,
+                      group_max_update_length: Option<&GroupMaxUpdateLength>| {
+                        let (event, name) = snapshot_event_of(group_max_update_length);
+                        buffer.snapshot_combined(
+                            dual_module.unit_index,
+                            event,
+                            name,
+                            vec![interface_ptr, dual_module, primal_module],
+                        );
+                    },
+                );
+                visualizer.save_buffered(&buffer).unwrap();
+            }
             let last_unit = self.units.last().unwrap().read_recursive();
             visualizer
                 .snapshot_combined(
@@ -339,11 +806,13 @@ impl PrimalModuleParallel {
         }
     }
 
+    /// the callback is only called when `debug_sequential` is set, otherwise see
+    /// [`PrimalModuleParallel::parallel_solve_concurrent_callback`]
     pub fn parallel_solve_step_callback<DualSerialModule: DualModuleImpl + Send + Sync, F: Send + Sync>(
         &mut self,
         syndrome_pattern: &SyndromePattern,
         parallel_dual_module: &DualModuleParallel<DualSerialModule>,
-        mut callback: F,
+        callback: F,
     ) where
         F: FnMut(
             &DualModuleInterfacePtr,
@@ -351,9 +820,59 @@ impl PrimalModuleParallel {
             &PrimalModuleSerialPtr,
             Option<&GroupMaxUpdateLength>,
         ),
+    {
+        self.parallel_solve_step_callback_impl(
+            syndrome_pattern,
+            parallel_dual_module,
+            callback,
+            None::<&NoCallback<DualSerialModule>>,
+        )
+    }
+
+    /// the callback is called by every unit even if they run concurrently, i.e. from multiple threads at the same time
+    pub fn parallel_solve_concurrent_callback<DualSerialModule: DualModuleImpl + Send + Sync, C>(
+        &mut self,
+        syndrome_pattern: &SyndromePattern,
+        parallel_dual_module: &DualModuleParallel<DualSerialModule>,
+        callback: &C,
+    ) where
+        C: Fn(
+                &DualModuleInterfacePtr,
+                &DualModuleParallelUnit<DualSerialModule>,
+                &PrimalModuleSerialPtr,
+                Option<&GroupMaxUpdateLength>,
+            ) + Send
+            + Sync,
+    {
+        self.parallel_solve_step_callback_impl(syndrome_pattern, parallel_dual_module, callback, Some(callback))
+    }
+
+    /// `callback` is used when the units run sequentially and `concurrent_callback` is used otherwise
+    fn parallel_solve_step_callback_impl<DualSerialModule: DualModuleImpl + Send + Sync, F, C>(
+        &mut self,
+        syndrome_pattern: &SyndromePattern,
+        parallel_dual_module: &DualModuleParallel<DualSerialModule>,
+        mut callback: F,
+        concurrent_callback: Option<&C>,
+    ) where
+        F: FnMut(
+                &DualModuleInterfacePtr,
+                &DualModuleParallelUnit<DualSerialModule>,
+                &PrimalModuleSerialPtr,
+                Option<&GroupMaxUpdateLength>,
+            ) + Send
+            + Sync,
+        C: Fn(
+                &DualModuleInterfacePtr,
+                &DualModuleParallelUnit<DualSerialModule>,
+                &PrimalModuleSerialPtr,
+                Option<&GroupMaxUpdateLength>,
+            ) + Send
+            + Sync,
     {
         let thread_pool = Arc::clone(&self.thread_pool);
         *self.last_solve_start_time.write() = Instant::now();
+        self.is_clean = false;
         if self.config.prioritize_base_partition {
             if self.config.debug_sequential {
                 for unit_index in 0..self.partition_info.units.len() {
@@ -381,6 +900,7 @@ impl PrimalModuleParallel {
                         let parallel_dual_module = &parallel_dual_module;
                         let streaming_decode_use_spin_lock = self.config.streaming_decode_use_spin_lock;
                         s.spawn_fifo(move |_| {
+                            let mut unit_callback = concurrent_callback;
                             let ready_pair = ready_vec[unit_index].clone();
                             let (ready, condvar, spin_ready) = &*ready_pair;
                             if streaming_decode_use_spin_lock {
@@ -399,11 +919,11 @@ impl PrimalModuleParallel {
                                         }
                                     }
                                 }
-                                unit_ptr.children_ready_solve::<DualSerialModule, F>(
+                                unit_ptr.children_ready_solve::<DualSerialModule, &C>(
                                     parallel_unit,
                                     PartitionedSyndromePattern::new(syndrome_pattern),
                                     parallel_dual_module,
-                                    &mut None,
+                                    &mut unit_callback.as_mut(),
                                 );
                                 spin_ready.store(1, Ordering::SeqCst);
                             } else {
@@ -423,11 +943,11 @@ impl PrimalModuleParallel {
                                         }
                                     }
                                 }
-                                unit_ptr.children_ready_solve::<DualSerialModule, F>(
+                                unit_ptr.children_ready_solve::<DualSerialModule, &C>(
                                     parallel_unit,
                                     PartitionedSyndromePattern::new(syndrome_pattern),
                                     parallel_dual_module,
-                                    &mut None,
+                                    &mut unit_callback.as_mut(),
                                 );
                                 *is_ready = true;
                                 condvar.notify_one();
@@ -461,12 +981,186 @@ impl PrimalModuleParallel {
                     PartitionedSyndromePattern::new(syndrome_pattern),
                     parallel_dual_module,
                     &mut Some(&mut callback),
+                    concurrent_callback,
                 )
             })
         }
     }
 }
 
+impl PrimalModuleParallel {
+    /// solve a syndrome that arrives round by round, e.g. from the measurement hardware: each unit starts as soon as the
+    /// rounds covering its owning range have arrived and its children are solved, rather than waiting for the whole
+    /// syndrome. The rounds must arrive in increasing order of vertex indices, which holds if the vertices are ordered by
+    /// time; the scheduling options in the config (e.g. `debug_sequential`) don't apply here
+    #[allow(clippy::unnecessary_cast)]
+    pub fn parallel_solve_stream<DualSerialModule: DualModuleImpl + Send + Sync>(
+        &mut self,
+        rounds: impl IntoIterator<Item = SyndromeRound>,
+        parallel_dual_module: &DualModuleParallel<DualSerialModule>,
+    ) {
+        let thread_pool = Arc::clone(&self.thread_pool);
+        *self.last_solve_start_time.write() = Instant::now();
+        self.is_clean = false;
+        // the defect vertices arrived so far, and the end of the vertices covered by the arrived rounds
+        let arrival = (Mutex::new((Vec::<VertexIndex>::new(), 0 as VertexIndex)), Condvar::new());
+        let vertex_num = self.partition_info.config.vertex_num;
+        let primal_module_parallel = &*self;
+        // the rounds are received in the current thread, so that no thread in the pool is blocked by data acquisition
+        let last_round_time = thread_pool.in_place_scope_fifo(|s| {
+            // the children are always spawned before their parent, so a unit never waits for a task that is not started
+            for unit_index in 0..primal_module_parallel.partition_info.units.len() {
+                let arrival = &arrival;
+                s.spawn_fifo(move |_| {
+                    let partition_unit_info = &primal_module_parallel.partition_info.units[unit_index];
+                    let owning_range = partition_unit_info.owning_range;
+                    let syndrome_pattern = {
+                        let (state, condvar) = arrival;
+                        let mut state = state.lock().unwrap();
+                        while state.1 < owning_range.end() {
+                            state = condvar.wait(state).unwrap();
+                        }
+                        let defect_vertices = &state.0;
+                        let start = defect_vertices.partition_point(|vertex_index| *vertex_index < owning_range.start());
+                        let end = defect_vertices.partition_point(|vertex_index| *vertex_index < owning_range.end());
+                        SyndromePattern::new_vertices(defect_vertices[start..end].to_vec())
+                    };
+                    if let Some((left_unit_index, right_unit_index)) = partition_unit_info.children {
+                        primal_module_parallel.wait_unit(left_unit_index);
+                        primal_module_parallel.wait_unit(right_unit_index);
+                    }
+                    primal_module_parallel.units[unit_index]
+                        .children_ready_solve::<DualSerialModule, NoCallback<DualSerialModule>>(
+                            primal_module_parallel,
+                            PartitionedSyndromePattern::new(&syndrome_pattern),
+                            parallel_dual_module,
+                            &mut None,
+                        );
+                });
+            }
+            let (state, condvar) = &arrival;
+            for round in rounds {
+                let mut state = state.lock().unwrap();
+                assert!(
+                    round.vertex_end >= state.1,
+                    "the rounds must arrive in increasing order of vertex indices"
+                );
+                assert!(round.vertex_end <= vertex_num, "vertex_end exceeds the number of vertices");
+                let mut last_vertex_index = None;
+                for &vertex_index in round.defect_vertices.iter() {
+                    assert!(
+                        vertex_index >= state.1 && vertex_index < round.vertex_end,
+                        "defect vertex {vertex_index} doesn't belong to this round"
+                    );
+                    assert!(
+                        last_vertex_index < Some(vertex_index),
+                        "defect vertices must be in increasing order"
+                    );
+                    last_vertex_index = Some(vertex_index);
+                }
+                state.0.extend(round.defect_vertices);
+                state.1 = round.vertex_end;
+                condvar.notify_all();
+            }
+            // no more rounds: the remaining vertices have no defect
+            state.lock().unwrap().1 = vertex_num;
+            condvar.notify_all();
+            Instant::now()
+        });
+        if let Some(stream_latency) = self.stream_latency.as_mut() {
+            stream_latency.record(last_round_time.elapsed().as_secs_f64());
+        }
+    }
+
+    /// the latency of every shot solved by [`PrimalModuleParallel::parallel_solve_stream`], from the arrival of its last
+    /// round to the end of the solve, which is what a feed-forward operation waits for; requires
+    /// `stream_latency_histogram` in the config, otherwise returns `None`. The shots are accumulated across clears until
+    /// [`PrimalModuleParallel::reset_stream_latency`]
+    pub fn stream_latency(&self) -> Option<&LatencyHistogram> {
+        self.stream_latency.as_ref()
+    }
+
+    pub fn reset_stream_latency(&mut self) {
+        if let Some(stream_latency) = self.stream_latency.as_mut() {
+            stream_latency.clear();
+        }
+    }
+
+    /// the load imbalance of syndrome ingestion in the last shot, among the units that have loaded defect vertices
+    pub fn ingestion_skew(&self) -> IngestionSkew {
+        let ingestion_vec: Vec<_> = self
+            .units
+            .iter()
+            .filter_map(|ptr| ptr.read_recursive().ingestion.clone())
+            .collect();
+        let unit_num = std::cmp::max(ingestion_vec.len(), 1) as f64;
+        let max_defect_num = ingestion_vec.iter().map(|ingestion| ingestion.defect_num).max().unwrap_or(0);
+        let average_defect_num = ingestion_vec.iter().map(|ingestion| ingestion.defect_num).sum::<usize>() as f64 / unit_num;
+        let max_load_time = ingestion_vec.iter().map(|ingestion| ingestion.load_time).fold(0., f64::max);
+        let average_load_time = ingestion_vec.iter().map(|ingestion| ingestion.load_time).sum::<f64>() / unit_num;
+        let skew = |max: f64, average: f64| if average > 0. { max / average } else { 1. };
+        IngestionSkew {
+            max_defect_num,
+            average_defect_num,
+            defect_num_skew: skew(max_defect_num as f64, average_defect_num),
+            max_load_time,
+            average_load_time,
+            load_time_skew: skew(max_load_time, average_load_time),
+        }
+    }
+
+    /// the summaries of all rounds in the last solve, concatenated in the order of unit index (i.e. from leaf to root);
+    /// requires `record_rounds` to be enabled in the config, otherwise returns `None`
+    pub fn round_summaries(&self) -> Option<Vec<RoundSummary>> {
+        if !self.config.record_rounds {
+            return None;
+        }
+        let mut round_summaries = vec![];
+        for unit_ptr in self.units.iter() {
+            let unit = unit_ptr.read_recursive();
+            let serial_module = unit.serial_module.read_recursive();
+            round_summaries.extend(serial_module.round_summaries.iter().flatten().cloned());
+        }
+        Some(round_summaries)
+    }
+
+    /// after solving, find out which unit finally decides the matching of each defect vertex;
+    /// requires `record_defect_owner` to be enabled in the config, otherwise the result is empty
+    pub fn defect_owners(&self) -> BTreeMap<VertexIndex, DefectOwner> {
+        let mut histories = BTreeMap::<VertexIndex, Vec<(usize, DefectMatchTarget)>>::new();
+        // fusion units always have larger indices than their children, so the history is ordered from leaf to root
+        for unit_ptr in self.units.iter() {
+            let unit = unit_ptr.read_recursive();
+            for (defect_vertex, match_target) in unit.defect_matches.iter() {
+                histories
+                    .entry(*defect_vertex)
+                    .or_default()
+                    .push((unit.unit_index, match_target.clone()));
+            }
+        }
+        let mut defect_owners = BTreeMap::new();
+        for (defect_vertex, history) in histories.into_iter() {
+            let (mut unit_index, match_target) = history.last().cloned().unwrap();
+            for (history_unit_index, history_match_target) in history.iter().rev() {
+                if history_match_target != &match_target {
+                    break;
+                }
+                unit_index = *history_unit_index;
+            }
+            defect_owners.insert(
+                defect_vertex,
+                DefectOwner {
+                    unit_index,
+                    tree_level: self.partition_info.tree_level(unit_index),
+                    match_target,
+                    history,
+                },
+            );
+        }
+        defect_owners
+    }
+}
+
 impl FusionVisualizer for PrimalModuleParallel {
     fn snapshot(&self, abbrev: bool) -> serde_json::Value {
         // do the sanity check first before taking snapshot
@@ -506,7 +1200,9 @@ impl PrimalModuleParallelUnitPtr {
             children: None, // to be filled later
             parent: None,   // to be filled later
             event_time: None,
+            ingestion: None,
             streaming_decode_mocker: None,
+            defect_matches: vec![],
         })
     }
 
@@ -527,6 +1223,7 @@ impl PrimalModuleParallelUnitPtr {
         ),
     {
         let mut primal_unit = self.write();
+        tracing_span!(INFO, "unit_solve", unit_index = primal_unit.unit_index);
         if let Some(mocker) = &primal_unit.streaming_decode_mocker {
             if primal_module_parallel.config.streaming_decode_use_spin_lock {
                 while primal_module_parallel.last_solve_start_time.read_recursive().elapsed() < mocker.bias {
@@ -567,12 +1264,17 @@ impl PrimalModuleParallelUnitPtr {
                 callback(&primal_unit.interface_ptr, &dual_unit, &primal_unit.serial_module, None);
             }
             primal_unit.break_matching_with_mirror(dual_unit.deref_mut());
+            let load_begin = Instant::now();
             for defect_index in owned_defect_range.whole_defect_range.iter() {
                 let defect_vertex = partitioned_syndrome_pattern.syndrome_pattern.defect_vertices[defect_index as usize];
                 primal_unit
                     .serial_module
                     .load_defect(defect_vertex, &interface_ptr, dual_unit.deref_mut());
             }
+            primal_unit.ingestion = Some(PrimalModuleParallelUnitIngestion {
+                defect_num: owned_defect_range.whole_defect_range.len(),
+                load_time: load_begin.elapsed().as_secs_f64(),
+            });
             primal_unit.serial_module.solve_step_callback_interface_loaded(
                 &interface_ptr,
                 dual_unit.deref_mut(),
@@ -587,10 +1289,16 @@ impl PrimalModuleParallelUnitPtr {
             }
         } else {
             debug_assert!(primal_unit.is_active, "leaf must be active to be solved");
+            let load_begin = Instant::now();
             let syndrome_pattern = owned_defect_range.expand();
-            primal_unit.serial_module.solve_step_callback(
+            interface_ptr.load(&syndrome_pattern, dual_unit.deref_mut());
+            primal_unit.serial_module.load(&interface_ptr);
+            primal_unit.ingestion = Some(PrimalModuleParallelUnitIngestion {
+                defect_num: syndrome_pattern.defect_vertices.len(),
+                load_time: load_begin.elapsed().as_secs_f64(),
+            });
+            primal_unit.serial_module.solve_step_callback_interface_loaded(
                 &interface_ptr,
-                &syndrome_pattern,
                 dual_unit.deref_mut(),
                 |interface, dual_module, primal_module, group_max_update_length| {
                     if let Some(callback) = callback.as_mut() {
@@ -602,6 +1310,9 @@ impl PrimalModuleParallelUnitPtr {
                 callback(&primal_unit.interface_ptr, &dual_unit, &primal_unit.serial_module, None);
             }
         }
+        if primal_module_parallel.config.record_defect_owner {
+            primal_unit.record_defect_matches(dual_unit.deref_mut());
+        }
         primal_unit.is_active = true;
         event_time.end = primal_module_parallel
             .last_solve_start_time
@@ -609,15 +1320,20 @@ impl PrimalModuleParallelUnitPtr {
             .elapsed()
             .as_secs_f64();
         primal_unit.event_time = Some(event_time);
+        let unit_index = primal_unit.unit_index;
+        drop(dual_unit);
+        drop(primal_unit);
+        primal_module_parallel.completion.notify(unit_index);
     }
 
     /// call on the last primal node, and it will spawn tasks on the previous ones
-    fn iterative_solve_step_callback<DualSerialModule: DualModuleImpl + Send + Sync, F: Send + Sync>(
+    fn iterative_solve_step_callback<DualSerialModule: DualModuleImpl + Send + Sync, F: Send + Sync, C>(
         &self,
         primal_module_parallel: &PrimalModuleParallel,
         partitioned_syndrome_pattern: PartitionedSyndromePattern,
         parallel_dual_module: &DualModuleParallel<DualSerialModule>,
         callback: &mut Option<&mut F>,
+        concurrent_callback: Option<&C>,
     ) where
         F: FnMut(
             &DualModuleInterfacePtr,
@@ -625,9 +1341,17 @@ impl PrimalModuleParallelUnitPtr {
             &PrimalModuleSerialPtr,
             Option<&GroupMaxUpdateLength>,
         ),
+        C: Fn(
+                &DualModuleInterfacePtr,
+                &DualModuleParallelUnit<DualSerialModule>,
+                &PrimalModuleSerialPtr,
+                Option<&GroupMaxUpdateLength>,
+            ) + Send
+            + Sync,
     {
         let primal_unit = self.read_recursive();
-        // only when sequentially running the tasks will the callback take effect, otherwise it's unsafe to execute it from multiple threads
+        // only when sequentially running the tasks will the `FnMut` callback take effect, otherwise it's unsafe to execute
+        // it from multiple threads and the `Fn` concurrent callback is used instead
         let debug_sequential = primal_module_parallel.config.debug_sequential;
         if let Some((left_child_weak, right_child_weak)) = primal_unit.children.as_ref() {
             // make children ready
@@ -643,33 +1367,39 @@ impl PrimalModuleParallelUnitPtr {
                     left_partitioned,
                     parallel_dual_module,
                     callback,
+                    concurrent_callback,
                 );
                 right_child_weak.upgrade_force().iterative_solve_step_callback(
                     primal_module_parallel,
                     right_partitioned,
                     parallel_dual_module,
                     callback,
+                    concurrent_callback,
                 );
             } else {
                 rayon::join(
                     || {
+                        let mut unit_callback = concurrent_callback;
                         left_child_weak
                             .upgrade_force()
-                            .iterative_solve_step_callback::<DualSerialModule, F>(
+                            .iterative_solve_step_callback::<DualSerialModule, &C, C>(
                                 primal_module_parallel,
                                 left_partitioned,
                                 parallel_dual_module,
-                                &mut None,
+                                &mut unit_callback.as_mut(),
+                                concurrent_callback,
                             )
                     },
                     || {
+                        let mut unit_callback = concurrent_callback;
                         right_child_weak
                             .upgrade_force()
-                            .iterative_solve_step_callback::<DualSerialModule, F>(
+                            .iterative_solve_step_callback::<DualSerialModule, &C, C>(
                                 primal_module_parallel,
                                 right_partitioned,
                                 parallel_dual_module,
-                                &mut None,
+                                &mut unit_callback.as_mut(),
+                                concurrent_callback,
                             )
                     },
                 );
@@ -692,6 +1422,7 @@ impl PrimalModuleParallelUnit {
         &mut self,
         dual_unit: &mut DualModuleParallelUnit<DualSerialModule>,
     ) {
+        tracing_span!(INFO, "fuse", unit_index = self.unit_index);
         let (left_child_ptr, right_child_ptr) = (
             self.children.as_ref().unwrap().0.upgrade_force(),
             self.children.as_ref().unwrap().1.upgrade_force(),
@@ -702,7 +1433,41 @@ impl PrimalModuleParallelUnit {
         self.serial_module.fuse(&left_child.serial_module, &right_child.serial_module);
     }
 
-    /// break the matched pairs of interface vertices
+    /// record the current matching of every defect vertex in this unit
+    pub fn record_defect_matches(&mut self, dual_module: &mut impl DualModuleImpl) {
+        let interface_ptr = self.interface_ptr.clone();
+        let perfect_matching = self
+            .serial_module
+            .intermediate_matching(&interface_ptr, dual_module)
+            .get_perfect_matching();
+        let defect_vertex_of = |dual_node_ptr: &DualNodePtr| -> VertexIndex {
+            let dual_node = dual_node_ptr.read_recursive();
+            if let DualNodeClass::DefectVertex { defect_index } = &dual_node.class {
+                *defect_index
+            } else {
+                unreachable!("can only be syndrome")
+            }
+        };
+        self.defect_matches.clear();
+        for (dual_node_ptr_1, dual_node_ptr_2) in perfect_matching.peer_matchings.iter() {
+            let defect_vertex_1 = defect_vertex_of(dual_node_ptr_1);
+            let defect_vertex_2 = defect_vertex_of(dual_node_ptr_2);
+            self.defect_matches
+                .push((defect_vertex_1, DefectMatchTarget::Peer(defect_vertex_2)));
+            self.defect_matches
+                .push((defect_vertex_2, DefectMatchTarget::Peer(defect_vertex_1)));
+        }
+        for (dual_node_ptr, virtual_vertex) in perfect_matching.virtual_matchings.iter() {
+            self.defect_matches.push((
+                defect_vertex_of(dual_node_ptr),
+                DefectMatchTarget::VirtualVertex(*virtual_vertex),
+            ));
+        }
+    }
+
+    /// break the matched pairs of interface vertices: only the matches to the mirror vertices owned by this unit are reopened,
+    /// because these vertices become interior after fusion; the matches to the vertices that are still mirrored are kept
+    /// (and checked again in the next fusion) so that they don't need to grow again
     #[allow(clippy::unnecessary_cast)]
     pub fn break_matching_with_mirror(&mut self, dual_module: &mut impl DualModuleImpl) {
         // use `possible_break` to efficiently break those
@@ -740,6 +1505,7 @@ impl PrimalModuleImpl for PrimalModuleParallelUnit {
     fn clear(&mut self) {
         self.serial_module.clear();
         self.interface_ptr.clear();
+        self.defect_matches.clear();
     }
 
     fn load(&mut self, interface_ptr: &DualModuleInterfacePtr) {
@@ -772,6 +1538,7 @@ impl PrimalModuleImpl for PrimalModuleParallelUnit {
 pub mod tests {
     use super::super::dual_module_serial::*;
     use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
     use super::*;
 
     pub fn primal_module_parallel_basic_standard_syndrome_optional_viz<F>(
@@ -1180,4 +1947,556 @@ pub mod tests {
             Some(json!({ "max_tree_size": 0, "debug_sequential": true })),
         );
     }
+
+    /// record which unit finally decides the matching of each defect
+    #[test]
+    fn primal_module_parallel_defect_owners_1() {
+        // cargo test primal_module_parallel_defect_owners_1 -- --nocapture
+        let visualize_filename = "primal_module_parallel_defect_owners_1.json".to_string();
+        let defect_vertices = vec![39, 52, 63, 90, 100];
+        let half_weight = 500;
+        let (primal_module, _dual_module) = primal_module_parallel_basic_standard_syndrome_optional_viz_config(
+            CodeCapacityPlanarCode::new(11, 0.1, half_weight),
+            Some(visualize_filename),
+            defect_vertices.clone(),
+            9 * half_weight,
+            |_initializer, config| {
+                config.partitions = vec![
+                    VertexRange::new(0, 60),   // unit 0
+                    VertexRange::new(72, 132), // unit 1
+                ];
+                config.fusions = vec![
+                    (0, 1), // unit 2, by fusing 0 and 1
+                ];
+            },
+            None,
+            Some(json!({ "record_defect_owner": true, "debug_sequential": true })),
+        );
+        let defect_owners = primal_module.defect_owners();
+        println!("defect_owners: {defect_owners:?}");
+        assert_eq!(defect_owners.keys().cloned().collect::<Vec<_>>(), defect_vertices);
+        for defect_owner in defect_owners.values() {
+            assert_eq!(defect_owner.tree_level, if defect_owner.unit_index == 2 { 1 } else { 0 });
+            assert_eq!(
+                defect_owner.history.last().unwrap().0,
+                2,
+                "the root unit must record every defect"
+            );
+        }
+        let report = primal_module.generate_profiler_report();
+        assert_eq!(report["defect_owners"].as_object().unwrap().len(), defect_vertices.len());
+    }
+
+    /// every unit should report the number of defect vertices it loads
+    #[test]
+    fn primal_module_parallel_ingestion_skew_1() {
+        // cargo test primal_module_parallel_ingestion_skew_1 -- --nocapture
+        let visualize_filename = "primal_module_parallel_ingestion_skew_1.json".to_string();
+        let defect_vertices = vec![39, 52, 63, 90, 100];
+        let half_weight = 500;
+        let (primal_module, _dual_module) = primal_module_parallel_basic_standard_syndrome_optional_viz_config(
+            CodeCapacityPlanarCode::new(11, 0.1, half_weight),
+            Some(visualize_filename),
+            defect_vertices,
+            9 * half_weight,
+            |_initializer, config| {
+                config.partitions = vec![
+                    VertexRange::new(0, 60),   // unit 0
+                    VertexRange::new(72, 132), // unit 1
+                ];
+                config.fusions = vec![
+                    (0, 1), // unit 2, by fusing 0 and 1
+                ];
+            },
+            None,
+            None,
+        );
+        let defect_num_vec: Vec<_> = primal_module
+            .units
+            .iter()
+            .map(|ptr| ptr.read_recursive().ingestion.as_ref().unwrap().defect_num)
+            .collect();
+        assert_eq!(defect_num_vec, vec![2, 2, 1]);
+        let ingestion_skew = primal_module.ingestion_skew();
+        println!("ingestion_skew: {ingestion_skew:?}");
+        assert_eq!(ingestion_skew.max_defect_num, 2);
+        assert!((ingestion_skew.defect_num_skew - 1.2).abs() < 1e-9);
+        let report = primal_module.generate_profiler_report();
+        assert_eq!(report["ingestion_vec"].as_array().unwrap().len(), 3);
+    }
+
+    struct ThreadWaker(std::thread::Thread);
+
+    impl std::task::Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// a minimal executor that polls the future in the current thread
+    fn block_on<T>(mut future: impl Future<Output = T> + Unpin) -> T {
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut context = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = Pin::new(&mut future).poll(&mut context) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    #[test]
+    fn primal_module_parallel_wait_unit() {
+        // cargo test primal_module_parallel_wait_unit -- --nocapture
+        use crate::example_partition::*;
+        let (d, noisy_measurements) = (5, 11);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+            .build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let unit_num = partition_info.units.len();
+        let mut dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        let mut primal_module =
+            PrimalModuleParallel::new_config(&initializer, &partition_info, PrimalModuleParallelConfig::default());
+        for seed in 0..5 {
+            let completion = primal_module.completion();
+            assert!((0..unit_num).all(|unit_index| !completion.is_solved(unit_index)));
+            let waiters: Vec<_> = (0..unit_num)
+                .map(|unit_index| {
+                    let completion = completion.clone();
+                    let children = partition_info.units[unit_index].children;
+                    std::thread::spawn(move || {
+                        if unit_index % 2 == 0 {
+                            completion.wait_unit(unit_index);
+                        } else {
+                            block_on(completion.unit_future(unit_index));
+                        }
+                        // a unit is never solved before its children
+                        if let Some((left_index, right_index)) = children {
+                            assert!(completion.is_solved(left_index) && completion.is_solved(right_index));
+                        }
+                    })
+                })
+                .collect();
+            let syndrome_pattern = code.generate_random_errors(seed);
+            primal_module.parallel_solve(&syndrome_pattern, &dual_module);
+            for waiter in waiters {
+                waiter.join().unwrap();
+            }
+            primal_module.wait_unit(unit_num - 1);
+            primal_module.clear();
+            dual_module.clear();
+        }
+    }
+
+    /// fusing interfaces never renumbers the dual nodes of the children: every node stays at its home index
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_parallel_fuse_keeps_home_index() {
+        // cargo test primal_module_parallel_fuse_keeps_home_index -- --nocapture
+        let defect_vertices = vec![14, 39, 40, 52, 63, 90, 100, 101, 125];
+        let (primal_module, _dual_module) = primal_module_parallel_basic_standard_syndrome_optional_viz(
+            CodeCapacityPlanarCode::new(11, 0.1, 500),
+            None,
+            defect_vertices,
+            5500,
+            |_initializer, config| {
+                config.partitions = vec![
+                    VertexRange::new(0, 36),   // unit 0
+                    VertexRange::new(48, 84),  // unit 1
+                    VertexRange::new(96, 132), // unit 2
+                ];
+                config.fusions = vec![
+                    (0, 1), // unit 3, by fusing 0 and 1
+                    (3, 2), // unit 4, by fusing 3 and 2
+                ];
+            },
+            None,
+        );
+        let interface_ptr = primal_module.units.last().unwrap().read_recursive().interface_ptr.clone();
+        let mut flattened_nodes = vec![];
+        interface_ptr.flatten_nodes(&mut flattened_nodes);
+        let mut home_interfaces = std::collections::BTreeSet::new();
+        for (node_index, dual_node_ptr) in flattened_nodes.iter().enumerate() {
+            let Some(dual_node_ptr) = dual_node_ptr else { continue };
+            assert_eq!(dual_node_ptr.updated_index(), node_index as NodeIndex);
+            let dual_node = dual_node_ptr.read_recursive();
+            let home_interface_ptr = dual_node.home_interface.upgrade_force();
+            let home_interface = home_interface_ptr.read_recursive();
+            assert!(home_interface.get_node(dual_node.home_index).as_ref() == Some(dual_node_ptr));
+            home_interfaces.insert(home_interface.unit_index);
+        }
+        assert!(
+            home_interfaces.len() > 1,
+            "the dual nodes should be created in multiple units"
+        );
+        // the active nodes stay in the interfaces that create them, and the root only records its descendants
+        assert_eq!(interface_ptr.read_recursive().active_descendants.len(), 4);
+    }
+
+    /// fusion only reopens the matches to mirror vertices that become interior, and keeps those still on the boundary
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_parallel_fusion_keeps_boundary_matches() {
+        // cargo test primal_module_parallel_fusion_keeps_boundary_matches -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 36),   // unit 0
+            VertexRange::new(48, 84),  // unit 1
+            VertexRange::new(96, 132), // unit 2
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 3, by fusing 0 and 1
+            (3, 2), // unit 4, by fusing 3 and 2
+        ];
+        let partition_info = partition_config.info();
+        let (mut kept_count, mut reopened_count) = (0, 0);
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let dual_module =
+                DualModuleParallel::<DualModuleSerial>::new_config(&initializer, &partition_info, Default::default());
+            let mut primal_module = PrimalModuleParallel::new_config(
+                &initializer,
+                &partition_info,
+                PrimalModuleParallelConfig {
+                    debug_sequential: true,
+                    ..Default::default()
+                },
+            );
+            // the matches to mirror vertices when each unit is solved
+            let mut mirror_matches: Vec<Vec<(DualNodePtr, VertexIndex)>> = vec![vec![]; partition_info.units.len()];
+            primal_module.parallel_solve_step_callback(
+                &syndrome_pattern,
+                &dual_module,
+                |interface_ptr, _, serial_module, group_max_update_length| {
+                    if group_max_update_length.is_some() {
+                        return;
+                    }
+                    let module = serial_module.read_recursive();
+                    let mut matches = vec![];
+                    for node_ptr in module
+                        .possible_break
+                        .iter()
+                        .filter_map(|node_index| module.get_node(*node_index))
+                    {
+                        let node = node_ptr.read_recursive();
+                        if let Some((MatchTarget::VirtualVertex(vertex_index), _)) = &node.temporary_match {
+                            matches.push((node.origin.upgrade_force(), *vertex_index));
+                        }
+                    }
+                    mirror_matches[interface_ptr.read_recursive().unit_index] = matches;
+                },
+            );
+            for child_index in [0, 1] {
+                for mirror_match in mirror_matches[child_index].iter() {
+                    let owning_unit = partition_info.vertex_to_owning_unit[mirror_match.1 as usize];
+                    if owning_unit == 3 {
+                        assert!(!mirror_matches[3].contains(mirror_match), "interior vertex cannot be matched");
+                        reopened_count += 1;
+                    } else if mirror_matches[3].contains(mirror_match) {
+                        kept_count += 1;
+                    }
+                }
+            }
+            assert!(mirror_matches[4].is_empty(), "the last unit has no mirror vertex");
+        }
+        println!("kept: {kept_count}, reopened: {reopened_count}");
+        assert!(kept_count > 0 && reopened_count > 0);
+    }
+
+    /// the syndrome streamed round by round from another thread should give the same matching weight as a whole
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_parallel_solve_stream() {
+        // cargo test primal_module_parallel_solve_stream -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 36),   // unit 0
+            VertexRange::new(48, 84),  // unit 1
+            VertexRange::new(96, 132), // unit 2
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 3, by fusing 0 and 1
+            (3, 2), // unit 4, by fusing 3 and 2
+        ];
+        let partition_info = partition_config.info();
+        let mut solver = SolverParallel::new(
+            &initializer,
+            &partition_info,
+            json!({ "primal": { "stream_latency_histogram": {} } }),
+        );
+        let vertex_num_per_round = 12;
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let expected_weight = solver.sum_dual_variables();
+            solver.clear();
+            // the trailing rounds without defect are omitted on purpose, which is the same as sending empty rounds
+            let streamed_vertex_end = syndrome_pattern
+                .defect_vertices
+                .iter()
+                .map(|vertex_index| (vertex_index / vertex_num_per_round + 1) * vertex_num_per_round)
+                .max()
+                .unwrap_or(0);
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let defect_vertices = syndrome_pattern.defect_vertices.clone();
+            let producer = std::thread::spawn(move || {
+                for vertex_end in (vertex_num_per_round..=streamed_vertex_end).step_by(vertex_num_per_round as usize) {
+                    let round_defects = defect_vertices
+                        .iter()
+                        .cloned()
+                        .filter(|vertex_index| {
+                            *vertex_index >= vertex_end - vertex_num_per_round && *vertex_index < vertex_end
+                        })
+                        .collect();
+                    sender.send(SyndromeRound::new(vertex_end, round_defects)).unwrap();
+                }
+            });
+            solver.solve_stream(receiver);
+            producer.join().unwrap();
+            assert_eq!(solver.sum_dual_variables(), expected_weight, "{syndrome_pattern:?}");
+            let subgraph = solver.subgraph();
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_subgraph(&subgraph);
+            assert_eq!(subgraph_builder.total_weight(), expected_weight);
+            solver.clear();
+        }
+        // only the streamed shots are recorded
+        let stream_latency = solver.stream_latency().unwrap();
+        assert_eq!(stream_latency.count, 20);
+        assert!(stream_latency.percentile(0.999) <= stream_latency.max);
+        assert_eq!(solver.generate_profiler_report()["primal"]["stream_latency"]["count"], 20);
+        solver.primal_module.reset_stream_latency();
+        assert_eq!(solver.stream_latency().unwrap().count, 0);
+    }
+
+    #[test]
+    fn primal_module_parallel_latency_histogram() {
+        // cargo test primal_module_parallel_latency_histogram -- --nocapture
+        let mut histogram = LatencyHistogram::new(LatencyHistogramConfig {
+            lowest: 1e-6,
+            highest: 1e-2,
+            sub_bucket_num: 10,
+        });
+        assert_eq!(histogram.percentile(0.5), 0.);
+        // 1us, 2us, ..., 1000us
+        for latency in 1..=1000 {
+            histogram.record(latency as f64 * 1e-6);
+        }
+        histogram.record(1.); // overflow
+        assert_eq!(histogram.count, 1001);
+        for (quantile, expected) in [(0.5, 501e-6), (0.9, 901e-6), (0.99, 991e-6)] {
+            let percentile = histogram.percentile(quantile);
+            assert!(
+                percentile >= expected && percentile <= expected * 1.1,
+                "{quantile}: {percentile}"
+            );
+        }
+        assert_eq!(histogram.percentile(1.), 1.);
+        assert_eq!(histogram.buckets().last(), Some(&(f64::INFINITY, 1)));
+        assert_eq!(histogram.buckets().iter().map(|(_, count)| count).sum::<u64>(), 1001);
+        assert!(histogram.report()["buckets"].as_array().unwrap().last().unwrap()[0].is_null());
+        histogram.clear();
+        assert_eq!((histogram.count, histogram.mean()), (0, 0.));
+    }
+
+    /// the async solve should give back the modules with the same result and notify every unit through the channel
+    #[test]
+    fn primal_module_parallel_solve_async() {
+        // cargo test primal_module_parallel_solve_async -- --nocapture
+        use crate::example_partition::*;
+        let (d, noisy_measurements) = (5, 11);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+            .build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let unit_num = partition_info.units.len();
+        let mut dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        let mut primal_module =
+            PrimalModuleParallel::new_config(&initializer, &partition_info, PrimalModuleParallelConfig::default());
+        let mut expected_solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        for seed in 0..5 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            expected_solver.solve(&syndrome_pattern);
+            let receiver = primal_module.completion().subscribe();
+            (primal_module, dual_module) =
+                block_on(Box::pin(primal_module.parallel_solve_async(syndrome_pattern, dual_module))).unwrap();
+            // the channel is closed after the last unit is solved
+            let solved_units: Vec<usize> = receiver.iter().collect();
+            assert_eq!(solved_units.len(), unit_num);
+            assert_eq!(solved_units.last(), Some(&(unit_num - 1)));
+            let last_interface_ptr = primal_module.units.last().unwrap().read_recursive().interface_ptr.clone();
+            assert_eq!(last_interface_ptr.sum_dual_variables(), expected_solver.sum_dual_variables());
+            expected_solver.clear();
+            primal_module.clear();
+            dual_module.clear();
+        }
+    }
+
+    /// an unsupported syndrome should complete the future with an error before anything is solved
+    #[test]
+    fn primal_module_parallel_solve_async_error() {
+        // cargo test primal_module_parallel_solve_async_error -- --nocapture
+        use crate::example_partition::*;
+        let (d, noisy_measurements) = (5, 11);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+            .build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        let primal_module =
+            PrimalModuleParallel::new_config(&initializer, &partition_info, PrimalModuleParallelConfig::default());
+        let completion = primal_module.completion();
+        // erasures cannot be partitioned yet
+        let syndrome_pattern = SyndromePattern::new(vec![0], vec![0]);
+        let result = block_on(Box::pin(primal_module.parallel_solve_async(syndrome_pattern, dual_module)));
+        assert!(matches!(result, Err(FusionError::UnsupportedOperation(_))));
+        assert!(!completion.is_solved(0), "nothing is solved");
+    }
+
+    /// the future runs on a tokio runtime without blocking its only thread
+    #[test]
+    fn primal_module_parallel_solve_async_tokio() {
+        // cargo test primal_module_parallel_solve_async_tokio -- --nocapture
+        use crate::example_partition::*;
+        let (d, noisy_measurements) = (5, 11);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.02, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+            .build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let mut dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        let mut primal_module =
+            PrimalModuleParallel::new_config(&initializer, &partition_info, PrimalModuleParallelConfig::default());
+        let mut expected_solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        for seed in 0..5 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            expected_solver.solve(&syndrome_pattern);
+            (primal_module, dual_module) = runtime.block_on(async {
+                // another task keeps running on the same thread while the solve is pending
+                let other_task = tokio::task::spawn(async move { seed });
+                let result = primal_module.parallel_solve_async(syndrome_pattern, dual_module).await;
+                assert_eq!(other_task.await.unwrap(), seed);
+                result.unwrap()
+            });
+            let last_interface_ptr = primal_module.units.last().unwrap().read_recursive().interface_ptr.clone();
+            assert_eq!(last_interface_ptr.sum_dual_variables(), expected_solver.sum_dual_variables());
+            expected_solver.clear();
+            primal_module.clear();
+            dual_module.clear();
+        }
+    }
+
+    /// the units snapshot concurrently without `debug_sequential`, and the snapshots are saved in the order of time
+    #[test]
+    fn primal_module_parallel_concurrent_visualizer() {
+        // cargo test primal_module_parallel_concurrent_visualizer -- --nocapture
+        use crate::example_partition::*;
+        let (d, noisy_measurements) = (5, 7);
+        for prioritize_base_partition in [true, false] {
+            let visualize_filename =
+                format!("primal_module_parallel_concurrent_visualizer_{prioritize_base_partition}.json");
+            let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.05, 500);
+            let partition_config =
+                PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+                    .build_apply(&mut code);
+            let initializer = code.get_initializer();
+            let partition_info = partition_config.info();
+            let dual_module: DualModuleParallel<DualModuleSerial> =
+                DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+            let primal_config = PrimalModuleParallelConfig {
+                thread_pool_size: 4,
+                prioritize_base_partition,
+                ..Default::default()
+            };
+            let mut primal_module = PrimalModuleParallel::new_config(&initializer, &partition_info, primal_config);
+            let mut visualizer = Visualizer::new(
+                Some(visualize_data_folder() + visualize_filename.as_str()),
+                code.get_positions(),
+                true,
+            )
+            .unwrap();
+            print_visualize_link(visualize_filename.clone());
+            let syndrome_pattern = code.generate_random_errors(0);
+            primal_module.parallel_solve_visualizer(&syndrome_pattern, &dual_module, Some(&mut visualizer));
+            let mut expected_solver = SolverSerial::new(&initializer);
+            expected_solver.solve(&syndrome_pattern);
+            let last_interface_ptr = primal_module.units.last().unwrap().read_recursive().interface_ptr.clone();
+            assert_eq!(last_interface_ptr.sum_dual_variables(), expected_solver.sum_dual_variables());
+            // every unit reports when it's solved, and the timestamps are non-decreasing
+            let mut last_timestamp = 0.;
+            let mut solved_units = vec![];
+            for name in visualizer.snapshots.iter().take(visualizer.snapshots.len() - 1) {
+                let (prefix, event) = name.split_once("] ").unwrap();
+                let (unit, timestamp) = prefix.trim_start_matches("[unit ").split_once(' ').unwrap();
+                let timestamp: f64 = timestamp.trim_end_matches('s').parse().unwrap();
+                assert!(timestamp >= last_timestamp);
+                last_timestamp = timestamp;
+                if event == "unit solved" {
+                    solved_units.push(unit.parse::<usize>().unwrap());
+                }
+            }
+            // a fusion unit also takes a snapshot right after fusing its children
+            solved_units.sort();
+            solved_units.dedup();
+            assert_eq!(solved_units, (0..partition_info.units.len()).collect::<Vec<_>>());
+            assert_eq!(visualizer.snapshots.last().unwrap(), "solved");
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn primal_module_parallel_tracing_spans() {
+        // cargo test primal_module_parallel_tracing_spans --features tracing -- --nocapture
+        use crate::example_partition::*;
+        use crate::tracing::span::{Attributes, Id, Record};
+        use crate::tracing::{Event, Metadata, Subscriber};
+        /// record the name of every span, from all threads
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+        impl Subscriber for SpanNames {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut names = self.0.lock().unwrap();
+                names.push(span.metadata().name());
+                Id::from_u64(names.len() as u64)
+            }
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, _event: &Event<'_>) {}
+            fn enter(&self, _span: &Id) {}
+            fn exit(&self, _span: &Id) {}
+        }
+        let names = Arc::new(Mutex::new(vec![]));
+        // the units run in the threads of the pool, so the subscriber must be global
+        crate::tracing::subscriber::set_global_default(SpanNames(names.clone())).unwrap();
+        let (d, noisy_measurements) = (5, 7);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.05, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new_tree(d, noisy_measurements, 4, true, usize::MAX)
+            .build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        solver.solve(&code.generate_random_errors(0));
+        let names = names.lock().unwrap();
+        let count = |name: &str| names.iter().filter(|&&span_name| span_name == name).count();
+        // other tests may emit spans concurrently into the same global subscriber
+        let fusion_num = partition_info.units.iter().filter(|unit| unit.children.is_some()).count();
+        assert!(count("unit_solve") >= partition_info.units.len());
+        assert!(count("fuse") >= fusion_num);
+        assert!(count("grow") > 0 && count("resolve") > 0);
+    }
 }
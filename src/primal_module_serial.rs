@@ -8,8 +8,10 @@
 
 use std::cmp::Ordering;
 use std::num::NonZeroUsize;
+use std::sync::Arc;
 
 use crate::derivative::Derivative;
+use serde::{Deserialize, Serialize};
 
 use super::dual_module::*;
 use super::pointers::*;
@@ -41,6 +43,57 @@ pub struct PrimalModuleSerial {
     pub children: Option<((PrimalModuleSerialWeak, NodeNum), (PrimalModuleSerialWeak, NodeNum))>,
     /// the maximum number of children in a tree before it collapses to a union-find decoder
     pub max_tree_size: usize,
+    /// exit the solving loop as soon as every defect is matched, see [`PrimalModuleImpl::is_early_terminated`]
+    pub early_termination: bool,
+    /// when a blossom nested deeper than this is formed inside an alternating tree, the tree collapses the same way
+    /// as [`PrimalModuleSerial::max_tree_size`]; this gives up optimality to bound the worst-case latency
+    pub max_blossom_depth: usize,
+    /// the summary of each round in the last solve, only recorded when it's `Some`, see [`PrimalModuleImpl::record_round`]
+    pub round_summaries: Option<Vec<RoundSummary>>,
+    /// instead of forming a blossom inside an alternating tree, pause the whole tree until the global progress of the
+    /// dual variables advances by this amount, hoping that the odd cycle is broken by the others meanwhile; 0 disables it
+    pub blossom_deferral: Weight,
+    /// the alternating trees paused by [`PrimalModuleSerial::blossom_deferral`] in the current solve
+    pub deferred_blossoms: Vec<DeferredBlossom>,
+    /// the policy that decides what to do with each conflict, see [`PrimalStrategy`]
+    pub strategy: Arc<dyn PrimalStrategy>,
+}
+
+/// what [`PrimalModuleSerial`] does with a conflict reported by the dual module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimalDecision {
+    /// resolve it by the standard blossom algorithm
+    Resolve,
+    /// collapse the alternating trees of the conflicting nodes into single blossoms the same way as a union-find decoder,
+    /// see [`PrimalModuleSerial::max_tree_size`]; the new blossoms report their conflicts again in the next round.
+    /// conflicts that involve no alternating tree are resolved by the standard blossom algorithm
+    CollapseTree,
+}
+
+/// the conflict-resolution policy of [`PrimalModuleSerial`], so that alternative policies can be prototyped without
+/// changing the module; the strategy can inspect the module by e.g. [`PrimalModuleSerialPtr::alternating_trees`]
+pub trait PrimalStrategy: std::fmt::Debug + Send + Sync {
+    /// decide what to do with a conflict; by default every conflict is resolved by the standard blossom algorithm
+    fn decide(&self, _primal_module: &PrimalModuleSerialPtr, _conflict: &ResolvedMaxUpdateLength) -> PrimalDecision {
+        PrimalDecision::Resolve
+    }
+}
+
+/// the standard blossom algorithm that guarantees a minimum-weight perfect matching
+#[derive(Debug, Clone, Default)]
+pub struct DefaultPrimalStrategy {}
+
+impl PrimalStrategy for DefaultPrimalStrategy {}
+
+/// an alternating tree paused instead of forming a blossom, see [`PrimalModuleSerial::blossom_deferral`]
+#[derive(Debug, Clone)]
+pub struct DeferredBlossom {
+    /// the root of the paused alternating tree
+    pub root: PrimalNodeInternalWeak,
+    /// the tree resumes when the global progress of the dual variables reaches this value
+    pub resume_progress: Weight,
+    /// a resumed tree never pauses again in the same solve
+    pub is_resumed: bool,
 }
 
 pub type PrimalModuleSerialPtr = ArcManualSafeLock<PrimalModuleSerial>;
@@ -167,10 +220,11 @@ impl PrimalNodeInternalPtr {
 }
 
 impl PrimalModuleImpl for PrimalModuleSerialPtr {
-    fn new_empty(_initializer: &SolverInitializer) -> Self {
+    fn new_empty(initializer: &SolverInitializer) -> Self {
+        let capacity_hint = initializer.capacity_hint.unwrap_or_default();
         Self::new_value(PrimalModuleSerial {
             unit_index: 0, // if necessary, manually change it
-            nodes: vec![],
+            nodes: Vec::with_capacity(capacity_hint.max_node_num()),
             nodes_length: 0,
             is_fusion: false,
             possible_break: vec![],
@@ -182,6 +236,12 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
             // max_tree_size: 0,
             // Minimum Weight Perfect Matching
             max_tree_size: usize::MAX,
+            early_termination: false,
+            max_blossom_depth: usize::MAX,
+            round_summaries: None,
+            blossom_deferral: 0,
+            deferred_blossoms: vec![],
+            strategy: Arc::new(DefaultPrimalStrategy::default()),
         })
     }
 
@@ -193,6 +253,10 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
         module.parent = None;
         module.index_bias = 0;
         module.children = None;
+        module.deferred_blossoms.clear();
+        if let Some(round_summaries) = module.round_summaries.as_mut() {
+            round_summaries.clear();
+        }
     }
 
     fn load_defect_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
@@ -244,13 +308,29 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
         let mut current_conflict_index = 0;
         let debug_resolve_only_one = self.read_recursive().debug_resolve_only_one;
         let max_tree_size = self.read_recursive().max_tree_size;
+        let max_blossom_depth = self.read_recursive().max_blossom_depth;
         while let Some(conflict) = group_max_update_length.pop() {
             current_conflict_index += 1;
             if debug_resolve_only_one && current_conflict_index > 1 {
                 // debug mode
                 break;
             }
+            let Some(conflict) = conflict.resolve(&interface_ptr.read_recursive()) else {
+                continue; // ignore out-of-date event
+            };
             // println!("conflict: {conflict:?}");
+            if self.resume_paused_tree_of_conflict(&conflict, interface_ptr, dual_module) {
+                continue; // the conflict is reported again if it's still there
+            }
+            let strategy = self.read_recursive().strategy.clone();
+            match strategy.decide(self, &conflict) {
+                PrimalDecision::Resolve => {}
+                PrimalDecision::CollapseTree => {
+                    if self.collapse_trees_of_conflict(&conflict, interface_ptr, dual_module) {
+                        continue;
+                    }
+                }
+            }
             match conflict {
                 MaxUpdateLength::Conflicting((node_ptr_1, touching_ptr_1), (node_ptr_2, touching_ptr_2)) => {
                     debug_assert!(
@@ -613,6 +693,9 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                             let root_weak = primal_node_internal_1.tree_node.as_ref().unwrap().root.clone();
                             drop(primal_node_internal_1);
                             drop(primal_node_internal_2);
+                            if self.defer_blossom(&root_weak, interface_ptr, dual_module) {
+                                continue;
+                            }
                             let tree_size = {
                                 let root_ptr = root_weak.upgrade_force();
                                 let tree_size = root_ptr.read_recursive().tree_node.as_ref().unwrap().tree_size;
@@ -783,7 +866,15 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                                     children.push((child_ptr.clone(), child_touching_ptr.clone()));
                                 }
                             }
+                            let mut collapse_root = None;
                             if lca_tree_node.parent.is_some() || !children.is_empty() {
+                                if blossom_node_ptr.read_recursive().blossom_depth > max_blossom_depth {
+                                    collapse_root = Some(if lca_tree_node.depth == 0 {
+                                        primal_node_internal_blossom_ptr.clone()
+                                    } else {
+                                        lca_tree_node.root.upgrade_force()
+                                    });
+                                }
                                 let mut primal_node_internal_blossom = primal_node_internal_blossom_ptr.write();
                                 let new_tree_root = if lca_tree_node.depth == 0 {
                                     primal_node_internal_blossom_ptr.clone()
@@ -830,6 +921,10 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                                 }
                             }
                             lca.tree_node = None;
+                            drop(lca);
+                            if let Some(collapse_root) = collapse_root {
+                                self.collapse_tree(collapse_root, interface_ptr, dual_module);
+                            }
                             continue;
                         } else {
                             drop(primal_node_internal_1); // unlock
@@ -989,90 +1084,20 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                             child_touching_child_ptr,
                         )
                     };
-                    interface_ptr.expand_blossom(node_ptr, dual_module);
-                    // now we need to re-connect all the expanded nodes, by analyzing the relationship of nodes_circle, parent_touching_ptr and child_touching_ptr
-                    let parent_touching_index = nodes_circle
-                        .iter()
-                        .position(|ptr| ptr == &parent_touching_child_ptr)
-                        .expect("touching node should be in the blossom circle");
-                    let child_touching_index = nodes_circle
-                        .iter()
-                        .position(|ptr| ptr == &child_touching_child_ptr)
-                        .expect("touching node should be in the blossom circle");
-                    let mut is_tree_sequence_ascending = true;
-                    let (match_sequence, tree_sequence) = {
-                        // tree sequence is from parent to child
-                        let mut match_sequence = Vec::new();
-                        let mut tree_sequence = Vec::new();
-                        match parent_touching_index.cmp(&child_touching_index) {
-                            Ordering::Equal => {
-                                tree_sequence.push(parent_touching_index);
-                                for i in parent_touching_index + 1..nodes_circle.len() {
-                                    match_sequence.push(i);
-                                }
-                                for i in 0..parent_touching_index {
-                                    match_sequence.push(i);
-                                }
-                            }
-                            Ordering::Greater => {
-                                if (parent_touching_index - child_touching_index) % 2 == 0 {
-                                    // [... c <----- p ...]
-                                    for i in (child_touching_index..parent_touching_index + 1).rev() {
-                                        tree_sequence.push(i);
-                                    }
-                                    is_tree_sequence_ascending = false;
-                                    for i in parent_touching_index + 1..nodes_circle.len() {
-                                        match_sequence.push(i);
-                                    }
-                                    for i in 0..child_touching_index {
-                                        match_sequence.push(i);
-                                    }
-                                } else {
-                                    // [--> c ...... p ---]
-                                    for i in parent_touching_index..nodes_circle.len() {
-                                        tree_sequence.push(i);
-                                    }
-                                    for i in 0..child_touching_index + 1 {
-                                        tree_sequence.push(i);
-                                    }
-                                    for i in child_touching_index + 1..parent_touching_index {
-                                        match_sequence.push(i);
-                                    }
-                                }
-                            }
-                            Ordering::Less => {
-                                if (child_touching_index - parent_touching_index) % 2 == 0 {
-                                    // [... p -----> c ...]
-                                    for i in parent_touching_index..child_touching_index + 1 {
-                                        tree_sequence.push(i);
-                                    }
-                                    for i in child_touching_index + 1..nodes_circle.len() {
-                                        match_sequence.push(i);
-                                    }
-                                    for i in 0..parent_touching_index {
-                                        match_sequence.push(i);
-                                    }
-                                } else {
-                                    // [--- p ...... c <--]
-                                    for i in (0..parent_touching_index + 1).rev() {
-                                        tree_sequence.push(i);
-                                    }
-                                    for i in (child_touching_index..nodes_circle.len()).rev() {
-                                        tree_sequence.push(i);
-                                    }
-                                    is_tree_sequence_ascending = false;
-                                    for i in parent_touching_index + 1..child_touching_index {
-                                        match_sequence.push(i);
-                                    }
-                                }
-                            }
-                        }
-                        (match_sequence, tree_sequence)
-                    };
-                    debug_assert!(
-                        match_sequence.len() % 2 == 0 && tree_sequence.len() % 2 == 1,
-                        "parity of sequence wrong"
+                    // the children on the path between the two entry points shrink and grow alternately, the others stay
+                    let BlossomExpansionSequences {
+                        match_sequence,
+                        tree_sequence,
+                        is_tree_sequence_ascending,
+                    } = interface_ptr.expand_blossom_with_entries(
+                        node_ptr,
+                        (
+                            &parent_touching_child_ptr.upgrade_force(),
+                            &child_touching_child_ptr.upgrade_force(),
+                        ),
+                        dual_module,
                     );
+                    // now we need to re-connect all the expanded nodes to the alternating tree
                     // match the nodes in the match sequence
                     for i in (0..match_sequence.len()).step_by(2) {
                         let primal_node_internal_ptr_1 =
@@ -1091,16 +1116,6 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                             Some((MatchTarget::Peer(primal_node_internal_ptr_2.downgrade()), touching_ptr_1));
                         primal_node_internal_2.temporary_match =
                             Some((MatchTarget::Peer(primal_node_internal_ptr_1.downgrade()), touching_ptr_2));
-                        interface_ptr.set_grow_state(
-                            &primal_node_internal_1.origin.upgrade_force(),
-                            DualNodeGrowState::Stay,
-                            dual_module,
-                        );
-                        interface_ptr.set_grow_state(
-                            &primal_node_internal_2.origin.upgrade_force(),
-                            DualNodeGrowState::Stay,
-                            dual_module,
-                        );
                     }
                     // connect the nodes in the tree sequence to the alternating tree, note that the tree sequence is from parent to child
                     for (idx, current_i) in tree_sequence.iter().enumerate() {
@@ -1155,15 +1170,6 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                             depth: tree_node.depth + idx,
                             tree_size: None,
                         });
-                        interface_ptr.set_grow_state(
-                            &current.origin.upgrade_force(),
-                            if idx % 2 == 0 {
-                                DualNodeGrowState::Shrink
-                            } else {
-                                DualNodeGrowState::Grow
-                            },
-                            dual_module,
-                        );
                     }
                     {
                         // connect parent
@@ -1205,6 +1211,44 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
         }
     }
 
+    /// every alternating tree contributes exactly 1 to `sum_grow_speed`, and a free node is a tree by itself;
+    /// thus `sum_grow_speed == 0` means there is no unmatched node and all the dual variables are stationary
+    fn record_round(&mut self, group_max_update_length: &GroupMaxUpdateLength) {
+        if let Some(round_summaries) = self.write().round_summaries.as_mut() {
+            round_summaries.push(RoundSummary::new(group_max_update_length));
+        }
+    }
+
+    fn is_early_terminated(&self, interface: &DualModuleInterfacePtr) -> bool {
+        let module = self.read_recursive();
+        module.early_termination
+            && interface.read_recursive().active_nodes_count() == 0
+            && module.deferred_blossoms.iter().all(|deferred| deferred.is_resumed)
+    }
+
+    fn resume_deferred<D: DualModuleImpl>(
+        &mut self,
+        interface: &DualModuleInterfacePtr,
+        dual_module: &mut D,
+        force: bool,
+    ) -> bool {
+        let progress = interface.read_recursive().dual_variable_global_progress();
+        let roots: Vec<PrimalNodeInternalPtr> = self
+            .write()
+            .deferred_blossoms
+            .iter_mut()
+            .filter(|deferred| !deferred.is_resumed && (force || progress >= deferred.resume_progress))
+            .map(|deferred| {
+                deferred.is_resumed = true;
+                deferred.root.upgrade_force()
+            })
+            .collect();
+        for root_ptr in roots.iter() {
+            Self::set_tree_grow_state(root_ptr, None, interface, dual_module);
+        }
+        !roots.is_empty()
+    }
+
     fn intermediate_matching<D: DualModuleImpl>(
         &mut self,
         _interface: &DualModuleInterfacePtr,
@@ -1260,10 +1304,296 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
     }
 }
 
+/// serializable state of an [`AlternatingTreeNode`], where pointers are replaced by node indices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternatingTreeNodeCheckpoint {
+    pub root: NodeIndex,
+    pub parent: Option<(NodeIndex, NodeIndex)>,
+    pub children: Vec<(NodeIndex, NodeIndex)>,
+    pub depth: usize,
+    pub tree_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MatchTargetCheckpoint {
+    Peer(NodeIndex),
+    VirtualVertex(VertexIndex),
+}
+
+/// serializable state of a [`PrimalNodeInternal`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrimalNodeInternalCheckpoint {
+    pub tree_node: Option<AlternatingTreeNodeCheckpoint>,
+    pub temporary_match: Option<(MatchTargetCheckpoint, NodeIndex)>,
+}
+
+/// serializable state of a [`PrimalModuleSerial`], see [`PrimalModuleSerialPtr::checkpoint`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrimalModuleSerialCheckpoint {
+    pub nodes: Vec<Option<PrimalNodeInternalCheckpoint>>,
+    pub max_tree_size: usize,
+    pub early_termination: bool,
+    pub max_blossom_depth: usize,
+    #[serde(default)]
+    pub blossom_deferral: Weight,
+    /// (root, resume progress, is resumed) of each [`DeferredBlossom`]
+    #[serde(default)]
+    pub deferred_blossoms: Vec<(NodeIndex, Weight, bool)>,
+}
+
+impl PrimalModuleSerialPtr {
+    /// save the state of a primal module that is not fused; the node indices are the same as in the [`DualModuleInterface`]
+    pub fn checkpoint(&self) -> PrimalModuleSerialCheckpoint {
+        let module = self.read_recursive();
+        assert!(
+            module.parent.is_none() && module.children.is_none(),
+            "checkpoint of a fused primal module is not supported"
+        );
+        let get_index = |weak: &PrimalNodeInternalWeak| weak.upgrade_force().read_recursive().index;
+        let get_dual_index = |weak: &DualNodeWeak| weak.upgrade_force().read_recursive().index;
+        let nodes = module.nodes[..module.nodes_length]
+            .iter()
+            .map(|node_ptr| {
+                node_ptr.as_ref().map(|node_ptr| {
+                    let node = node_ptr.read_recursive();
+                    PrimalNodeInternalCheckpoint {
+                        tree_node: node.tree_node.as_ref().map(|tree_node| AlternatingTreeNodeCheckpoint {
+                            root: get_index(&tree_node.root),
+                            parent: tree_node
+                                .parent
+                                .as_ref()
+                                .map(|(parent, touching)| (get_index(parent), get_dual_index(touching))),
+                            children: tree_node
+                                .children
+                                .iter()
+                                .map(|(child, touching)| (get_index(child), get_dual_index(touching)))
+                                .collect(),
+                            depth: tree_node.depth,
+                            tree_size: tree_node.tree_size.map(|tree_size| tree_size.get()),
+                        }),
+                        temporary_match: node.temporary_match.as_ref().map(|(target, touching)| {
+                            (
+                                match target {
+                                    MatchTarget::Peer(peer) => MatchTargetCheckpoint::Peer(get_index(peer)),
+                                    MatchTarget::VirtualVertex(vertex_index) => {
+                                        MatchTargetCheckpoint::VirtualVertex(*vertex_index)
+                                    }
+                                },
+                                get_dual_index(touching),
+                            )
+                        }),
+                    }
+                })
+            })
+            .collect();
+        PrimalModuleSerialCheckpoint {
+            nodes,
+            max_tree_size: module.max_tree_size,
+            early_termination: module.early_termination,
+            max_blossom_depth: module.max_blossom_depth,
+            blossom_deferral: module.blossom_deferral,
+            deferred_blossoms: module
+                .deferred_blossoms
+                .iter()
+                .map(|deferred| (get_index(&deferred.root), deferred.resume_progress, deferred.is_resumed))
+                .collect(),
+        }
+    }
+
+    /// restore the state from a checkpoint, given the interface restored by [`DualModuleInterfacePtr::new_checkpoint`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn load_checkpoint(&mut self, checkpoint: &PrimalModuleSerialCheckpoint, interface_ptr: &DualModuleInterfacePtr) {
+        self.clear();
+        let belonging = self.downgrade();
+        let interface = interface_ptr.read_recursive();
+        let get_dual_weak = |node_index: &NodeIndex| {
+            interface.nodes[*node_index as usize]
+                .as_ref()
+                .expect("checkpoint refers to a removed dual node")
+                .downgrade()
+        };
+        let nodes: Vec<Option<PrimalNodeInternalPtr>> = checkpoint
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(node_index, node)| {
+                node.as_ref().map(|_| {
+                    PrimalNodeInternalPtr::new_value(PrimalNodeInternal {
+                        origin: get_dual_weak(&(node_index as NodeIndex)),
+                        index: node_index as NodeIndex,
+                        tree_node: None, // filled later
+                        temporary_match: None,
+                        belonging: belonging.clone(),
+                    })
+                })
+            })
+            .collect();
+        let get_weak = |node_index: &NodeIndex| {
+            nodes[*node_index as usize]
+                .as_ref()
+                .expect("checkpoint refers to a removed node")
+                .downgrade()
+        };
+        for (node_ptr, node_checkpoint) in nodes.iter().zip(checkpoint.nodes.iter()) {
+            if let (Some(node_ptr), Some(node_checkpoint)) = (node_ptr, node_checkpoint) {
+                let mut node = node_ptr.write();
+                node.tree_node = node_checkpoint.tree_node.as_ref().map(|tree_node| AlternatingTreeNode {
+                    root: get_weak(&tree_node.root),
+                    parent: tree_node
+                        .parent
+                        .as_ref()
+                        .map(|(parent, touching)| (get_weak(parent), get_dual_weak(touching))),
+                    children: tree_node
+                        .children
+                        .iter()
+                        .map(|(child, touching)| (get_weak(child), get_dual_weak(touching)))
+                        .collect(),
+                    depth: tree_node.depth,
+                    tree_size: tree_node
+                        .tree_size
+                        .map(|tree_size| NonZeroUsize::new(tree_size).expect("tree size must be positive")),
+                });
+                node.temporary_match = node_checkpoint.temporary_match.as_ref().map(|(target, touching)| {
+                    (
+                        match target {
+                            MatchTargetCheckpoint::Peer(peer) => MatchTarget::Peer(get_weak(peer)),
+                            MatchTargetCheckpoint::VirtualVertex(vertex_index) => MatchTarget::VirtualVertex(*vertex_index),
+                        },
+                        get_dual_weak(touching),
+                    )
+                });
+            }
+        }
+        let deferred_blossoms = checkpoint
+            .deferred_blossoms
+            .iter()
+            .map(|(root, resume_progress, is_resumed)| DeferredBlossom {
+                root: get_weak(root),
+                resume_progress: *resume_progress,
+                is_resumed: *is_resumed,
+            })
+            .collect();
+        let mut module = self.write();
+        module.nodes_length = nodes.len();
+        module.nodes = nodes;
+        module.deferred_blossoms = deferred_blossoms;
+        module.blossom_deferral = checkpoint.blossom_deferral;
+        module.max_tree_size = checkpoint.max_tree_size;
+        module.early_termination = checkpoint.early_termination;
+        module.max_blossom_depth = checkpoint.max_blossom_depth;
+    }
+}
+
+/// the label of a node in an alternating tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlternatingTreeLabel {
+    /// a growing node at even depth, including the root
+    Plus,
+    /// a shrinking node at odd depth, always matched to its only child
+    Minus,
+}
+
+/// read-only view of a node in an alternating tree, see [`PrimalModuleSerialPtr::alternating_trees`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlternatingTreeNodeView {
+    pub node_index: NodeIndex,
+    pub label: AlternatingTreeLabel,
+    pub parent: Option<NodeIndex>,
+    pub children: Vec<NodeIndex>,
+    pub depth: usize,
+}
+
+/// read-only view of an alternating tree, the nodes are listed in depth-first order starting from the root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlternatingTreeView {
+    pub root: NodeIndex,
+    pub nodes: Vec<AlternatingTreeNodeView>,
+}
+
+/// read-only view of the temporary matches outside the alternating trees, see [`PrimalModuleSerialPtr::temporary_matches`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemporaryMatchesView {
+    /// each pair of peers is listed once with the smaller index first
+    pub peer_matchings: Vec<(NodeIndex, NodeIndex)>,
+    pub virtual_matchings: Vec<(NodeIndex, VertexIndex)>,
+}
+
+impl PrimalModuleSerialPtr {
+    /// read-only views of all the alternating trees; the node indices are the same as in the visualizer snapshot
+    pub fn alternating_trees(&self) -> Vec<AlternatingTreeView> {
+        let mut flattened_nodes = vec![];
+        self.flatten_nodes(&mut flattened_nodes);
+        let mut trees = vec![];
+        for root_ptr in flattened_nodes.iter().flatten() {
+            let root = root_ptr.read_recursive();
+            if !matches!(&root.tree_node, Some(tree_node) if tree_node.parent.is_none()) {
+                continue;
+            }
+            let root_index = root.index;
+            drop(root);
+            let mut tree_nodes = vec![];
+            root_ptr.flatten_tree(&mut tree_nodes);
+            let get_index = |weak: &PrimalNodeInternalWeak| weak.upgrade_force().read_recursive().index;
+            let nodes = tree_nodes
+                .iter()
+                .map(|node_ptr| {
+                    let node = node_ptr.read_recursive();
+                    let tree_node = node.tree_node.as_ref().unwrap();
+                    AlternatingTreeNodeView {
+                        node_index: node.index,
+                        label: if tree_node.depth % 2 == 0 {
+                            AlternatingTreeLabel::Plus
+                        } else {
+                            AlternatingTreeLabel::Minus
+                        },
+                        parent: tree_node.parent.as_ref().map(|(parent, _)| get_index(parent)),
+                        children: tree_node.children.iter().map(|(child, _)| get_index(child)).collect(),
+                        depth: tree_node.depth,
+                    }
+                })
+                .collect();
+            trees.push(AlternatingTreeView { root: root_index, nodes });
+        }
+        trees
+    }
+
+    /// the temporary matches of the outer nodes (those not wrapped in any blossom) outside the alternating trees, given that a
+    /// "-" node is implicitly matched to its only child in the tree
+    pub fn temporary_matches(&self) -> TemporaryMatchesView {
+        let mut flattened_nodes = vec![];
+        self.flatten_nodes(&mut flattened_nodes);
+        let mut matches = TemporaryMatchesView::default();
+        for node_ptr in flattened_nodes.iter().flatten() {
+            let node = node_ptr.read_recursive();
+            if node.origin.upgrade_force().read_recursive().parent_blossom.is_some() {
+                continue;
+            }
+            match &node.temporary_match {
+                Some((MatchTarget::Peer(peer_weak), _)) => {
+                    let peer_index = peer_weak.upgrade_force().read_recursive().index;
+                    if node.index < peer_index {
+                        matches.peer_matchings.push((node.index, peer_index));
+                    }
+                }
+                Some((MatchTarget::VirtualVertex(vertex_index), _)) => {
+                    matches.virtual_matchings.push((node.index, *vertex_index))
+                }
+                None => {}
+            }
+        }
+        matches
+    }
+}
+
 impl FusionVisualizer for PrimalModuleSerialPtr {
     fn snapshot(&self, abbrev: bool) -> serde_json::Value {
-        // do the sanity check first before taking snapshot
-        let flattened_nodes = self.sanity_check().unwrap();
+        let flattened_nodes = if STRICT_CHECKS {
+            self.sanity_check().unwrap()
+        } else {
+            let mut flattened_nodes = vec![];
+            self.flatten_nodes(&mut flattened_nodes);
+            flattened_nodes
+        };
         let mut primal_nodes = Vec::<serde_json::Value>::new();
         for primal_node_ptr in flattened_nodes.iter() {
             if let Some(primal_node_ptr) = &primal_node_ptr {
@@ -1299,8 +1629,26 @@ impl FusionVisualizer for PrimalModuleSerialPtr {
                 primal_nodes.push(json!(null));
             }
         }
+        let alternating_trees: Vec<serde_json::Value> = self
+            .alternating_trees()
+            .iter()
+            .map(|tree| {
+                json!({
+                    if abbrev { "r" } else { "root" }: tree.root,
+                    if abbrev { "n" } else { "nodes" }: tree.nodes.iter().map(|node| json!({
+                        if abbrev { "i" } else { "index" }: node.node_index,
+                        if abbrev { "l" } else { "label" }: match node.label {
+                            AlternatingTreeLabel::Plus => "+",
+                            AlternatingTreeLabel::Minus => "-",
+                        },
+                        if abbrev { "p" } else { "parent" }: node.parent,
+                    })).collect::<Vec<serde_json::Value>>(),
+                })
+            })
+            .collect();
         json!({
             "primal_nodes": primal_nodes,
+            "alternating_trees": alternating_trees,
         })
     }
 }
@@ -1315,6 +1663,26 @@ impl PrimalModuleSerial {
         count
     }
 
+    /// the estimated memory usage of my own nodes, excluding those of the children modules
+    pub fn memory_usage(&self) -> MemoryReport {
+        let mut primal_nodes = MemoryReport::vec(&self.nodes);
+        for node_ptr in self.nodes.iter().flatten() {
+            primal_nodes += MemoryReport::shared::<PrimalNodeInternal>();
+            if let Some(tree_node) = node_ptr.read_recursive().tree_node.as_ref() {
+                primal_nodes += MemoryReport::vec(&tree_node.children);
+            }
+        }
+        let mut auxiliary = MemoryReport::vec(&self.possible_break) + MemoryReport::vec(&self.deferred_blossoms);
+        if let Some(round_summaries) = self.round_summaries.as_ref() {
+            auxiliary += MemoryReport::vec(round_summaries);
+        }
+        MemoryReport {
+            primal_nodes,
+            auxiliary,
+            ..Default::default()
+        }
+    }
+
     /// get node ptr by index; if calling from the ancestor module, node_index is absolute, otherwise it's relative
     #[allow(clippy::unnecessary_cast)]
     pub fn get_node(&self, relative_node_index: NodeIndex) -> Option<PrimalNodeInternalPtr> {
@@ -1891,6 +2259,132 @@ impl PrimalModuleSerialPtr {
         Ok(flattened_nodes)
     }
 
+    /// pause the alternating tree instead of forming a blossom in it, see [`PrimalModuleSerial::blossom_deferral`];
+    /// return false if the deferral is disabled or the tree has been paused before in this solve
+    pub fn defer_blossom<D: DualModuleImpl>(
+        &self,
+        root_weak: &PrimalNodeInternalWeak,
+        interface_ptr: &DualModuleInterfacePtr,
+        dual_module: &mut D,
+    ) -> bool {
+        let mut module = self.write();
+        if module.blossom_deferral == 0 || module.deferred_blossoms.iter().any(|deferred| &deferred.root == root_weak) {
+            return false;
+        }
+        let resume_progress = interface_ptr.read_recursive().dual_variable_global_progress() + module.blossom_deferral;
+        module.deferred_blossoms.push(DeferredBlossom {
+            root: root_weak.clone(),
+            resume_progress,
+            is_resumed: false,
+        });
+        drop(module);
+        Self::set_tree_grow_state(
+            &root_weak.upgrade_force(),
+            Some(DualNodeGrowState::Stay),
+            interface_ptr,
+            dual_module,
+        );
+        true
+    }
+
+    /// if any node in the conflict belongs to a paused alternating tree, resume the tree and return true
+    pub fn resume_paused_tree_of_conflict<D: DualModuleImpl>(
+        &self,
+        conflict: &ResolvedMaxUpdateLength,
+        interface_ptr: &DualModuleInterfacePtr,
+        dual_module: &mut D,
+    ) -> bool {
+        if self
+            .read_recursive()
+            .deferred_blossoms
+            .iter()
+            .all(|deferred| deferred.is_resumed)
+        {
+            return false; // fast path
+        }
+        let mut resumed = false;
+        for root_weak in self.conflict_tree_roots(conflict) {
+            let mut module = self.write();
+            let Some(deferred) = module
+                .deferred_blossoms
+                .iter_mut()
+                .find(|deferred| !deferred.is_resumed && deferred.root == root_weak)
+            else {
+                continue;
+            };
+            deferred.is_resumed = true;
+            drop(module);
+            Self::set_tree_grow_state(&root_weak.upgrade_force(), None, interface_ptr, dual_module);
+            resumed = true;
+        }
+        resumed
+    }
+
+    /// collapse the alternating trees of the nodes in the conflict, see [`PrimalDecision::CollapseTree`];
+    /// return false if no node in the conflict belongs to an alternating tree
+    pub fn collapse_trees_of_conflict<D: DualModuleImpl>(
+        &self,
+        conflict: &ResolvedMaxUpdateLength,
+        interface_ptr: &DualModuleInterfacePtr,
+        dual_module: &mut D,
+    ) -> bool {
+        let roots = self.conflict_tree_roots(conflict);
+        for root_weak in roots.iter() {
+            self.collapse_tree(root_weak.upgrade_force(), interface_ptr, dual_module);
+        }
+        !roots.is_empty()
+    }
+
+    /// the roots of the alternating trees that the (outer) nodes in the conflict belong to, without duplication
+    pub fn conflict_tree_roots(&self, conflict: &ResolvedMaxUpdateLength) -> Vec<PrimalNodeInternalWeak> {
+        let dual_node_ptrs = match conflict {
+            MaxUpdateLength::Conflicting((node_ptr_1, _), (node_ptr_2, _)) => vec![node_ptr_1, node_ptr_2],
+            MaxUpdateLength::TouchingVirtual((node_ptr, _), _)
+            | MaxUpdateLength::BlossomNeedExpand(node_ptr)
+            | MaxUpdateLength::VertexShrinkStop((node_ptr, _)) => vec![node_ptr],
+            MaxUpdateLength::NonZeroGrow(_) => vec![],
+        };
+        let mut roots: Vec<PrimalNodeInternalWeak> = vec![];
+        for dual_node_ptr in dual_node_ptrs {
+            let Some(primal_node_internal_ptr) = self.get_primal_node_internal_ptr_option(dual_node_ptr) else {
+                continue; // out-of-date event
+            };
+            let outer_ptr = self.get_outer_node(primal_node_internal_ptr);
+            let outer = outer_ptr.read_recursive();
+            if let Some(tree_node) = outer.tree_node.as_ref() {
+                if !roots.contains(&tree_node.root) {
+                    roots.push(tree_node.root.clone());
+                }
+            }
+        }
+        roots
+    }
+
+    /// set the grow state of every node in the alternating tree; `None` restores the states of a running tree, i.e.
+    /// growing "+" nodes and shrinking "-" nodes
+    pub fn set_tree_grow_state<D: DualModuleImpl>(
+        root_ptr: &PrimalNodeInternalPtr,
+        grow_state: Option<DualNodeGrowState>,
+        interface_ptr: &DualModuleInterfacePtr,
+        dual_module: &mut D,
+    ) {
+        if root_ptr.read_recursive().tree_node.is_none() {
+            return; // the tree no longer exists
+        }
+        let mut flattened_nodes = vec![];
+        root_ptr.flatten_tree(&mut flattened_nodes);
+        for node_ptr in flattened_nodes.iter() {
+            let node = node_ptr.read_recursive();
+            let depth = node.tree_node.as_ref().unwrap().depth;
+            let grow_state = grow_state.unwrap_or(if depth % 2 == 0 {
+                DualNodeGrowState::Grow
+            } else {
+                DualNodeGrowState::Shrink
+            });
+            interface_ptr.set_grow_state(&node.origin.upgrade_force(), grow_state, dual_module);
+        }
+    }
+
     /// collapse a tree into a single blossom, just like what union-find decoder does. No MWPM guarantee once this is called.
     pub fn collapse_tree<D: DualModuleImpl>(
         &self,
@@ -1977,6 +2471,7 @@ impl PrimalNodeInternalPtr {
 pub mod tests {
     use super::super::dual_module_serial::*;
     use super::super::example_codes::*;
+    use super::super::instrumentation::*;
     use super::super::*;
     use super::*;
 
@@ -2611,4 +3106,223 @@ pub mod tests {
         let interface_ptr = DualModuleInterfacePtr::new_empty();
         primal_module.solve_visualizer(&interface_ptr, &code.get_syndrome(), &mut dual_module, Some(&mut visualizer));
     }
+
+    /// early termination should skip the final query once every defect is matched, without changing the result
+    #[test]
+    fn primal_module_serial_early_termination_1() {
+        // cargo test primal_module_serial_early_termination_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.01, 500);
+        let initializer = code.get_initializer();
+        let mut total_counts = [0, 0];
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let mut sum_dual_variables = vec![];
+            for (i, early_termination) in [false, true].into_iter().enumerate() {
+                let mut dual_module = DualModuleInstrumented::<DualModuleSerial>::new_empty(&initializer);
+                let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+                primal_module.write().early_termination = early_termination;
+                let interface_ptr = DualModuleInterfacePtr::new_empty();
+                primal_module.solve(&interface_ptr, &syndrome_pattern, &mut dual_module);
+                let perfect_matching = primal_module.perfect_matching(&interface_ptr, &mut dual_module);
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                subgraph_builder.load_perfect_matching(&perfect_matching);
+                assert_eq!(subgraph_builder.total_weight(), interface_ptr.sum_dual_variables());
+                sum_dual_variables.push(interface_ptr.sum_dual_variables());
+                total_counts[i] += dual_module.counters.find_obstacle;
+            }
+            assert_eq!(sum_dual_variables[0], sum_dual_variables[1], "{syndrome_pattern:?}");
+        }
+        println!("find_obstacle queries: {total_counts:?}");
+        assert!(total_counts[1] < total_counts[0]);
+    }
+
+    /// blossom statistics should be recorded, and capping the blossom depth should still give a valid perfect matching
+    #[test]
+    fn primal_module_serial_max_blossom_depth_1() {
+        // cargo test primal_module_serial_max_blossom_depth_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut max_depth = 0;
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let mut total_weights = vec![];
+            for max_blossom_depth in [usize::MAX, 1] {
+                let mut dual_module = DualModuleSerial::new_empty(&initializer);
+                let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+                primal_module.write().max_blossom_depth = max_blossom_depth;
+                let interface_ptr = DualModuleInterfacePtr::new_empty();
+                primal_module.solve(&interface_ptr, &syndrome_pattern, &mut dual_module);
+                let perfect_matching = primal_module.perfect_matching(&interface_ptr, &mut dual_module);
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                subgraph_builder.load_perfect_matching(&perfect_matching);
+                total_weights.push(subgraph_builder.total_weight());
+                let statistics = interface_ptr.read_recursive().blossom_statistics.clone();
+                assert_eq!(statistics.depth_histogram.iter().sum::<usize>(), statistics.blossom_count);
+                if max_blossom_depth == usize::MAX {
+                    assert_eq!(subgraph_builder.total_weight(), interface_ptr.sum_dual_variables());
+                    max_depth = std::cmp::max(max_depth, statistics.max_depth);
+                }
+            }
+            assert!(total_weights[1] >= total_weights[0], "{syndrome_pattern:?}");
+        }
+        println!("max blossom depth: {max_depth}");
+        assert!(max_depth > 1);
+    }
+
+    /// deferring the blossoms should still give a minimum-weight perfect matching
+    #[test]
+    fn primal_module_serial_blossom_deferral_1() {
+        // cargo test primal_module_serial_blossom_deferral_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut deferred_count = 0;
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let mut total_weights = vec![];
+            for blossom_deferral in [0, 300, 5000] {
+                let mut dual_module = DualModuleSerial::new_empty(&initializer);
+                let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+                primal_module.write().blossom_deferral = blossom_deferral;
+                let interface_ptr = DualModuleInterfacePtr::new_empty();
+                primal_module.solve(&interface_ptr, &syndrome_pattern, &mut dual_module);
+                deferred_count += primal_module.read_recursive().deferred_blossoms.len();
+                let perfect_matching = primal_module.perfect_matching(&interface_ptr, &mut dual_module);
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                subgraph_builder.load_perfect_matching(&perfect_matching);
+                assert_eq!(subgraph_builder.total_weight(), interface_ptr.sum_dual_variables());
+                total_weights.push(subgraph_builder.total_weight());
+            }
+            assert!(
+                total_weights.iter().all(|weight| *weight == total_weights[0]),
+                "{syndrome_pattern:?}"
+            );
+        }
+        println!("deferred blossoms: {deferred_count}");
+        assert!(deferred_count > 0);
+    }
+
+    /// the alternating tree views should be consistent in every round and match the visualizer snapshot
+    #[test]
+    fn primal_module_serial_alternating_tree_views() {
+        // cargo test primal_module_serial_alternating_tree_views -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut max_tree_nodes = 0;
+        for seed in 0..10 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let mut dual_module = DualModuleSerial::new_empty(&initializer);
+            let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            primal_module.solve_step_callback(
+                &interface_ptr,
+                &syndrome_pattern,
+                &mut dual_module,
+                |_, _, primal_module, _| {
+                    let trees = primal_module.alternating_trees();
+                    let peer_matchings = primal_module.temporary_matches().peer_matchings;
+                    for tree in trees.iter() {
+                        assert_eq!(tree.nodes[0].node_index, tree.root);
+                        assert_eq!(tree.nodes[0].parent, None);
+                        max_tree_nodes = std::cmp::max(max_tree_nodes, tree.nodes.len());
+                        for node in tree.nodes.iter() {
+                            if let Some(parent_index) = node.parent {
+                                let parent = tree.nodes.iter().find(|parent| parent.node_index == parent_index).unwrap();
+                                assert!(parent.children.contains(&node.node_index));
+                                assert_ne!(parent.label, node.label);
+                                assert_eq!(parent.depth + 1, node.depth);
+                            } else {
+                                assert_eq!(node.label, AlternatingTreeLabel::Plus);
+                            }
+                            if node.label == AlternatingTreeLabel::Minus {
+                                assert_eq!(node.children.len(), 1);
+                            }
+                            assert!(peer_matchings
+                                .iter()
+                                .all(|(peer_1, peer_2)| *peer_1 != node.node_index && *peer_2 != node.node_index));
+                        }
+                    }
+                    let snapshot = primal_module.snapshot(true);
+                    assert_eq!(snapshot["alternating_trees"].as_array().unwrap().len(), trees.len());
+                },
+            );
+            assert!(primal_module.alternating_trees().is_empty());
+        }
+        println!("max tree nodes: {max_tree_nodes}");
+        assert!(max_tree_nodes > 1);
+    }
+
+    #[derive(Debug)]
+    struct CollapseTreeStrategy {}
+
+    impl PrimalStrategy for CollapseTreeStrategy {
+        fn decide(&self, _primal_module: &PrimalModuleSerialPtr, _conflict: &ResolvedMaxUpdateLength) -> PrimalDecision {
+            PrimalDecision::CollapseTree
+        }
+    }
+
+    /// a custom strategy should still give a valid perfect matching, which is no better than the default strategy
+    #[test]
+    fn primal_module_serial_custom_strategy() {
+        // cargo test primal_module_serial_custom_strategy -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut suboptimal_count = 0;
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let mut total_weights = vec![];
+            for use_default_strategy in [true, false] {
+                let mut dual_module = DualModuleSerial::new_empty(&initializer);
+                let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+                if !use_default_strategy {
+                    primal_module.write().strategy = Arc::new(CollapseTreeStrategy {});
+                }
+                let interface_ptr = DualModuleInterfacePtr::new_empty();
+                primal_module.solve(&interface_ptr, &syndrome_pattern, &mut dual_module);
+                let perfect_matching = primal_module.perfect_matching(&interface_ptr, &mut dual_module);
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                subgraph_builder.load_perfect_matching(&perfect_matching);
+                if use_default_strategy {
+                    assert_eq!(subgraph_builder.total_weight(), interface_ptr.sum_dual_variables());
+                }
+                total_weights.push(subgraph_builder.total_weight());
+            }
+            assert!(total_weights[1] >= total_weights[0], "{syndrome_pattern:?}");
+            if total_weights[1] > total_weights[0] {
+                suboptimal_count += 1;
+            }
+        }
+        println!("suboptimal count: {suboptimal_count}");
+        assert!(suboptimal_count > 0);
+    }
+
+    /// the two entry points split a blossom circle into an odd path and matched pairs of adjacent children
+    #[test]
+    fn primal_module_serial_blossom_expansion_sequences() {
+        // cargo test primal_module_serial_blossom_expansion_sequences -- --nocapture
+        for circle_len in [3, 5, 7] {
+            for parent_touching_index in 0..circle_len {
+                for child_touching_index in 0..circle_len {
+                    let sequences = blossom_expansion_sequences(circle_len, parent_touching_index, child_touching_index);
+                    let tree_sequence = &sequences.tree_sequence;
+                    assert_eq!(tree_sequence.first(), Some(&parent_touching_index));
+                    assert_eq!(tree_sequence.last(), Some(&child_touching_index));
+                    assert_eq!(tree_sequence.len() % 2, 1);
+                    let mut all: Vec<usize> = tree_sequence.iter().chain(sequences.match_sequence.iter()).cloned().collect();
+                    all.sort();
+                    assert_eq!(all, (0..circle_len).collect::<Vec<_>>());
+                    for pair in sequences.match_sequence.chunks(2) {
+                        assert_eq!((pair[0] + 1) % circle_len, pair[1]);
+                    }
+                    for window in tree_sequence.windows(2) {
+                        let (from, to) = if sequences.is_tree_sequence_ascending {
+                            (window[0], window[1])
+                        } else {
+                            (window[1], window[0])
+                        };
+                        assert_eq!((from + 1) % circle_len, to);
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,281 @@
+//! PyMatching Interchange
+//!
+//! Conversion between [`SolverInitializer`] and the graph representation of PyMatching, i.e. a check matrix whose
+//! columns are the edges (at most 2 non-zero entries each, a single one meaning a boundary edge), the weights of the
+//! columns and the boundary detectors; the same graph can also be written in the NetworkX node-link JSON format, which
+//! is loaded by `pymatching.Matching.from_networkx(networkx.node_link_graph(data))`.
+//!
+//! Both directions keep the vertex and edge indices: detector `i` is vertex `i` and column `j` is edge `j`, so that the
+//! syndromes and the corrections can be compared between the two decoders without translation.
+//!
+
+use super::util::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// the graph representation of PyMatching, see module documentation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PyMatchingGraph {
+    /// the number of rows of the check matrix
+    pub num_detectors: usize,
+    /// the rows of the non-zero entries of each column, i.e. the check matrix in the compressed sparse column format
+    pub columns: Vec<Vec<usize>>,
+    /// the weight of each column
+    pub weights: Vec<f64>,
+    /// the detectors that are boundary nodes
+    pub boundary: Vec<usize>,
+}
+
+impl PyMatchingGraph {
+    /// export a decoding graph, where the virtual vertices become the boundary
+    #[allow(clippy::unnecessary_cast)]
+    pub fn from_initializer(initializer: &SolverInitializer) -> Self {
+        Self {
+            num_detectors: initializer.vertex_num as usize,
+            columns: initializer
+                .weighted_edges
+                .iter()
+                .map(|&(left, right, _)| vec![left as usize, right as usize])
+                .collect(),
+            weights: initializer
+                .weighted_edges
+                .iter()
+                .map(|&(_, _, weight)| weight as f64)
+                .collect(),
+            boundary: initializer.virtual_vertices.iter().map(|&vertex| vertex as usize).collect(),
+        }
+    }
+
+    /// the dense check matrix with `num_detectors` rows, e.g. to construct a numpy array
+    pub fn check_matrix(&self) -> Vec<Vec<u8>> {
+        let mut check_matrix = vec![vec![0; self.columns.len()]; self.num_detectors];
+        for (column_index, rows) in self.columns.iter().enumerate() {
+            for &row in rows.iter() {
+                check_matrix[row][column_index] = 1;
+            }
+        }
+        check_matrix
+    }
+
+    /// import a dense check matrix, see [`PyMatchingGraph::check_matrix`]
+    pub fn from_check_matrix(check_matrix: &[Vec<u8>], weights: Vec<f64>, boundary: Vec<usize>) -> Result<Self, String> {
+        let column_num = weights.len();
+        let mut columns = vec![vec![]; column_num];
+        for (row, entries) in check_matrix.iter().enumerate() {
+            if entries.len() != column_num {
+                return Err(format!(
+                    "row {row} has {} columns but {column_num} weights are given",
+                    entries.len()
+                ));
+            }
+            for (column_index, &entry) in entries.iter().enumerate() {
+                if entry != 0 {
+                    columns[column_index].push(row);
+                }
+            }
+        }
+        let graph = Self {
+            num_detectors: check_matrix.len(),
+            columns,
+            weights,
+            boundary,
+        };
+        graph.sanity_check()?;
+        Ok(graph)
+    }
+
+    /// check the column sizes, the detector indices and the weights
+    pub fn sanity_check(&self) -> Result<(), String> {
+        if self.columns.len() != self.weights.len() {
+            return Err(format!("{} columns but {} weights", self.columns.len(), self.weights.len()));
+        }
+        for (column_index, rows) in self.columns.iter().enumerate() {
+            if rows.is_empty() || rows.len() > 2 {
+                return Err(format!(
+                    "column {column_index} has {} non-zero entries, only 1 or 2 are supported",
+                    rows.len()
+                ));
+            }
+            if let Some(&row) = rows.iter().find(|&&row| row >= self.num_detectors) {
+                return Err(format!("column {column_index} refers to detector {row} out of range"));
+            }
+            let weight = self.weights[column_index];
+            if !weight.is_finite() || weight < 0. {
+                return Err(format!("column {column_index} has unsupported weight {weight}"));
+            }
+        }
+        if let Some(&detector) = self.boundary.iter().find(|&&detector| detector >= self.num_detectors) {
+            return Err(format!("boundary detector {detector} out of range"));
+        }
+        Ok(())
+    }
+
+    /// whether any column has a single non-zero entry, in which case [`PyMatchingGraph::to_initializer`] appends a virtual
+    /// vertex after the detectors
+    pub fn has_boundary_edges(&self) -> bool {
+        self.columns.iter().any(|rows| rows.len() == 1)
+    }
+
+    /// import into a decoding graph, where the largest weight is scaled to `2 * max_half_weight` and the others are rounded
+    /// proportionally; the boundary edges connect to an extra virtual vertex, see [`PyMatchingGraph::has_boundary_edges`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn to_initializer(&self, max_half_weight: Weight) -> Result<SolverInitializer, String> {
+        self.sanity_check()?;
+        let boundary_vertex = self.num_detectors as VertexIndex;
+        let mut vertex_num = self.num_detectors as VertexNum;
+        let mut virtual_vertices: Vec<VertexIndex> = self.boundary.iter().map(|&detector| detector as VertexIndex).collect();
+        if self.has_boundary_edges() {
+            vertex_num += 1;
+            virtual_vertices.push(boundary_vertex);
+        }
+        let max_weight = self.weights.iter().cloned().fold(0., f64::max);
+        let weighted_edges = self
+            .columns
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(rows, &weight)| {
+                let half_weight = if max_weight > 0. {
+                    (weight / max_weight * max_half_weight as f64).round() as Weight
+                } else {
+                    0
+                };
+                let right = rows.get(1).map(|&row| row as VertexIndex).unwrap_or(boundary_vertex);
+                (rows[0] as VertexIndex, right, 2 * half_weight)
+            })
+            .collect();
+        Ok(SolverInitializer::new(vertex_num, weighted_edges, virtual_vertices))
+    }
+
+    /// the NetworkX node-link JSON format, where each edge carries its column index in `fault_ids` and the boundary
+    /// detectors are marked by `is_boundary`; a column with a single non-zero entry is linked to an extra boundary node
+    /// `num_detectors`
+    pub fn to_node_link(&self) -> serde_json::Value {
+        let mut is_boundary = vec![false; self.num_detectors];
+        for &detector in self.boundary.iter() {
+            is_boundary[detector] = true;
+        }
+        if self.has_boundary_edges() {
+            is_boundary.push(true);
+        }
+        let nodes: Vec<_> = is_boundary
+            .iter()
+            .enumerate()
+            .map(|(id, is_boundary)| json!({ "id": id, "is_boundary": is_boundary }))
+            .collect();
+        let links: Vec<_> = self
+            .columns
+            .iter()
+            .zip(self.weights.iter())
+            .enumerate()
+            .map(|(column_index, (rows, weight))| {
+                json!({
+                    "source": rows[0],
+                    "target": rows.get(1).cloned().unwrap_or(self.num_detectors),
+                    "weight": weight,
+                    "fault_ids": [column_index],
+                })
+            })
+            .collect();
+        json!({
+            "directed": false,
+            "multigraph": false,
+            "graph": {},
+            "nodes": nodes,
+            "links": links,
+        })
+    }
+
+    /// import the NetworkX node-link JSON format, see [`PyMatchingGraph::to_node_link`]; the node ids must be
+    /// `0..num_nodes` and the links are the columns in order; every node becomes a detector, including the boundary ones
+    pub fn from_node_link(data: &serde_json::Value) -> Result<Self, String> {
+        let nodes = data["nodes"].as_array().ok_or("missing nodes")?;
+        let links = data["links"].as_array().ok_or("missing links")?;
+        let mut is_boundary = vec![false; nodes.len()];
+        for node in nodes.iter() {
+            let id = node["id"].as_u64().ok_or(format!("invalid node id in {node}"))? as usize;
+            if id >= nodes.len() {
+                return Err(format!("node id {id} out of range, ids must be 0..{}", nodes.len()));
+            }
+            is_boundary[id] = node["is_boundary"].as_bool().unwrap_or(false);
+        }
+        let mut columns = Vec::with_capacity(links.len());
+        let mut weights = Vec::with_capacity(links.len());
+        for link in links.iter() {
+            let endpoint = |key: &str| -> Result<usize, String> {
+                let id = link[key].as_u64().ok_or(format!("invalid {key} in {link}"))? as usize;
+                if id >= nodes.len() {
+                    return Err(format!("{key} {id} out of range"));
+                }
+                Ok(id)
+            };
+            columns.push(vec![endpoint("source")?, endpoint("target")?]);
+            weights.push(link["weight"].as_f64().unwrap_or(1.));
+        }
+        let graph = Self {
+            num_detectors: nodes.len(),
+            columns,
+            weights,
+            boundary: (0..nodes.len()).filter(|&id| is_boundary[id]).collect(),
+        };
+        graph.sanity_check()?;
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::*;
+
+    #[test]
+    fn pymatching_graph_round_trip() {
+        // cargo test pymatching_graph_round_trip -- --nocapture
+        let code = CodeCapacityPlanarCode::new(5, 0.1, 500);
+        let initializer = code.get_initializer();
+        let graph = PyMatchingGraph::from_initializer(&initializer);
+        assert!(!graph.has_boundary_edges());
+        let max_weight = initializer.weighted_edges.iter().map(|&(_, _, weight)| weight).max().unwrap();
+        let imported = graph.to_initializer(max_weight / 2).unwrap();
+        assert_eq!(imported.vertex_num, initializer.vertex_num);
+        assert_eq!(imported.weighted_edges, initializer.weighted_edges);
+        assert_eq!(imported.virtual_vertices, initializer.virtual_vertices);
+        // the dense check matrix and the node-link format describe the same graph
+        let check_matrix = graph.check_matrix();
+        assert!((0..graph.columns.len()).all(|column| check_matrix.iter().map(|row| row[column]).sum::<u8>() == 2));
+        let from_check_matrix =
+            PyMatchingGraph::from_check_matrix(&check_matrix, graph.weights.clone(), graph.boundary.clone()).unwrap();
+        assert_eq!(from_check_matrix, graph);
+        let node_link = graph.to_node_link();
+        assert_eq!(PyMatchingGraph::from_node_link(&node_link).unwrap(), graph);
+    }
+
+    #[test]
+    fn pymatching_graph_boundary_edges() {
+        // cargo test pymatching_graph_boundary_edges -- --nocapture
+        // a repetition code of 3 detectors with boundary edges at both ends, as given to `Matching.from_check_matrix`
+        let check_matrix = vec![vec![1, 1, 0, 0], vec![0, 1, 1, 0], vec![0, 0, 1, 1]];
+        let graph = PyMatchingGraph::from_check_matrix(&check_matrix, vec![1., 2., 2., 0.5], vec![]).unwrap();
+        assert!(graph.has_boundary_edges());
+        let initializer = graph.to_initializer(500).unwrap();
+        assert_eq!(initializer.vertex_num, 4);
+        assert_eq!(initializer.virtual_vertices, vec![3]);
+        assert_eq!(
+            initializer.weighted_edges,
+            vec![(0, 3, 500), (0, 1, 1000), (1, 2, 1000), (2, 3, 250)]
+        );
+        // the extra boundary node is an ordinary detector in the node-link format
+        let node_link = graph.to_node_link();
+        assert_eq!(node_link["nodes"][3]["is_boundary"], json!(true));
+        let reloaded = PyMatchingGraph::from_node_link(&node_link).unwrap();
+        assert_eq!(reloaded.boundary, vec![3]);
+        assert_eq!(
+            reloaded.to_initializer(500).unwrap().weighted_edges,
+            initializer.weighted_edges
+        );
+        assert!(PyMatchingGraph::from_check_matrix(&[vec![1, 1, 1]], vec![1.; 3], vec![]).is_ok());
+        // invalid graphs are rejected
+        assert!(PyMatchingGraph::from_check_matrix(&[vec![1], vec![1], vec![1]], vec![1.], vec![]).is_err());
+        assert!(PyMatchingGraph::from_check_matrix(&[vec![1, 0]], vec![1., 1.], vec![]).is_err());
+        assert!(PyMatchingGraph::from_check_matrix(&[vec![1]], vec![-1.], vec![]).is_err());
+    }
+}
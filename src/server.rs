@@ -0,0 +1,278 @@
+//! Decoding Server
+//!
+//! A long-running decoder that loads a [`SolverInitializer`] once and then decodes syndromes sent over TCP or Unix
+//! sockets, so that lab control software can call the decoder without FFI. Every connection is served by its own
+//! thread, and the requests borrow solvers from a pool of pre-cleared [`SolverSerial`]s.
+//!
+//! The protocol is binary and all integers are little-endian. Each message is a frame of a `u32` payload length followed
+//! by the payload; a connection carries any number of request frames, each answered by a response frame in order.
+//! - request payload: `u32` number of defect vertices, the `u32` defect vertices, `u32` number of erasures and the `u32`
+//!   erased edges
+//! - response payload: a `u8` status; if it's [`SERVER_STATUS_OK`], an `i64` weight of the correction, the `u32` number
+//!   of edges and the `u32` edges in the correction; otherwise a UTF-8 error message
+//!
+
+use super::mwpm_solver::*;
+use super::util::*;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Condvar, Mutex};
+
+pub const SERVER_STATUS_OK: u8 = 0;
+pub const SERVER_STATUS_ERROR: u8 = 1;
+
+/// a decoding server; cloning it shares the syndrome validator and the solver pool
+#[derive(Clone)]
+pub struct DecodingServer {
+    /// rejects invalid syndromes before they reach a solver, because a solver panics on them
+    validator: Arc<SyndromeValidator>,
+    /// pre-cleared solvers, waiting to be borrowed by a request
+    solvers: Arc<(Mutex<Vec<SolverSerial>>, Condvar)>,
+}
+
+/// a correction returned by the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerCorrection {
+    pub weight: Weight,
+    pub subgraph: Vec<EdgeIndex>,
+}
+
+fn read_u32(bytes: &[u8], cursor: &mut usize) -> Result<u32, String> {
+    let value = bytes
+        .get(*cursor..*cursor + 4)
+        .ok_or_else(|| format!("payload too short at byte {}", *cursor))?;
+    *cursor += 4;
+    Ok(u32::from_le_bytes(value.try_into().unwrap()))
+}
+
+fn read_u32_list(bytes: &[u8], cursor: &mut usize) -> Result<Vec<u32>, String> {
+    let length = read_u32(bytes, cursor)? as usize;
+    if length > (bytes.len() - *cursor) / 4 {
+        return Err(format!("list of {length} elements doesn't fit in the payload"));
+    }
+    (0..length).map(|_| read_u32(bytes, cursor)).collect()
+}
+
+fn write_u32_list(payload: &mut Vec<u8>, list: impl ExactSizeIterator<Item = u32>) {
+    payload.extend((list.len() as u32).to_le_bytes());
+    for value in list {
+        payload.extend(value.to_le_bytes());
+    }
+}
+
+/// read a frame, returning `None` if the connection is closed before the frame starts
+pub fn read_frame(reader: &mut impl Read) -> std::io::Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let mut payload = vec![0; u32::from_le_bytes(length) as usize];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+pub fn write_frame(writer: &mut impl Write, payload: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub fn encode_request(syndrome_pattern: &SyndromePattern) -> Vec<u8> {
+    let mut payload = vec![];
+    write_u32_list(&mut payload, syndrome_pattern.defect_vertices.iter().map(|&x| x as u32));
+    write_u32_list(&mut payload, syndrome_pattern.erasures.iter().map(|&x| x as u32));
+    payload
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub fn decode_request(payload: &[u8]) -> Result<SyndromePattern, String> {
+    let mut cursor = 0;
+    let defect_vertices = read_u32_list(payload, &mut cursor)?;
+    let erasures = read_u32_list(payload, &mut cursor)?;
+    if cursor != payload.len() {
+        return Err(format!("{} trailing bytes in the request", payload.len() - cursor));
+    }
+    Ok(SyndromePattern::new(
+        defect_vertices.into_iter().map(|x| x as VertexIndex).collect(),
+        erasures.into_iter().map(|x| x as EdgeIndex).collect(),
+    ))
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub fn encode_response(result: &Result<ServerCorrection, String>) -> Vec<u8> {
+    match result {
+        Ok(correction) => {
+            let mut payload = vec![SERVER_STATUS_OK];
+            payload.extend((correction.weight as i64).to_le_bytes());
+            write_u32_list(&mut payload, correction.subgraph.iter().map(|&x| x as u32));
+            payload
+        }
+        Err(message) => {
+            let mut payload = vec![SERVER_STATUS_ERROR];
+            payload.extend(message.as_bytes());
+            payload
+        }
+    }
+}
+
+#[allow(clippy::unnecessary_cast)]
+pub fn decode_response(payload: &[u8]) -> Result<ServerCorrection, String> {
+    match payload.first() {
+        Some(&SERVER_STATUS_OK) => {
+            let weight = payload.get(1..9).ok_or("payload too short for the weight")?;
+            let weight = i64::from_le_bytes(weight.try_into().unwrap()) as Weight;
+            let mut cursor = 9;
+            let subgraph = read_u32_list(payload, &mut cursor)?;
+            Ok(ServerCorrection {
+                weight,
+                subgraph: subgraph.into_iter().map(|x| x as EdgeIndex).collect(),
+            })
+        }
+        Some(_) => Err(String::from_utf8_lossy(&payload[1..]).to_string()),
+        None => Err("empty response".to_string()),
+    }
+}
+
+impl DecodingServer {
+    /// create a server with `solver_num` solvers, i.e. at most `solver_num` syndromes are decoded at the same time
+    pub fn new(initializer: &SolverInitializer, solver_num: usize) -> Self {
+        assert!(solver_num > 0, "at least one solver is required");
+        let solvers = (0..solver_num).map(|_| SolverSerial::new(initializer)).collect();
+        Self {
+            validator: Arc::new(SyndromeValidator::new(initializer)),
+            solvers: Arc::new((Mutex::new(solvers), Condvar::new())),
+        }
+    }
+
+    /// decode a syndrome with a solver borrowed from the pool
+    pub fn decode(&self, syndrome_pattern: &SyndromePattern) -> Result<ServerCorrection, String> {
+        self.validator.validate(syndrome_pattern).map_err(|err| err.to_string())?;
+        let (solvers, available) = &*self.solvers;
+        let mut solver = {
+            let mut solvers = solvers.lock().unwrap();
+            loop {
+                if let Some(solver) = solvers.pop() {
+                    break solver;
+                }
+                solvers = available.wait(solvers).unwrap();
+            }
+        };
+        solver.solve(syndrome_pattern);
+        let correction = ServerCorrection {
+            weight: solver.sum_dual_variables(),
+            subgraph: solver.subgraph(),
+        };
+        solver.clear(); // clear before returning, so that the next request doesn't pay for it
+        solvers.lock().unwrap().push(solver);
+        available.notify_one();
+        Ok(correction)
+    }
+
+    /// serve the requests of a connection until it's closed
+    pub fn serve_connection(&self, reader: impl Read, writer: impl Write) -> std::io::Result<()> {
+        let mut reader = BufReader::new(reader);
+        let mut writer = BufWriter::new(writer);
+        while let Some(payload) = read_frame(&mut reader)? {
+            let result = decode_request(&payload).and_then(|syndrome_pattern| self.decode(&syndrome_pattern));
+            write_frame(&mut writer, &encode_response(&result))?;
+        }
+        Ok(())
+    }
+
+    /// accept TCP connections forever, serving each of them in a new thread
+    pub fn serve_tcp(&self, listener: TcpListener) -> std::io::Result<()> {
+        for stream in listener.incoming() {
+            let stream: TcpStream = stream?;
+            stream.set_nodelay(true)?;
+            let server = self.clone();
+            std::thread::spawn(move || {
+                let reader = stream.try_clone()?;
+                server.serve_connection(reader, stream)
+            });
+        }
+        Ok(())
+    }
+
+    /// accept Unix socket connections forever, serving each of them in a new thread
+    #[cfg(unix)]
+    pub fn serve_unix(&self, listener: UnixListener) -> std::io::Result<()> {
+        for stream in listener.incoming() {
+            let stream: UnixStream = stream?;
+            let server = self.clone();
+            std::thread::spawn(move || {
+                let reader = stream.try_clone()?;
+                server.serve_connection(reader, stream)
+            });
+        }
+        Ok(())
+    }
+}
+
+/// a blocking client of [`DecodingServer`]
+pub struct DecodingClient<Stream: Read + Write> {
+    stream: Stream,
+}
+
+impl<Stream: Read + Write> DecodingClient<Stream> {
+    pub fn new(stream: Stream) -> Self {
+        Self { stream }
+    }
+
+    pub fn decode(&mut self, syndrome_pattern: &SyndromePattern) -> Result<ServerCorrection, String> {
+        write_frame(&mut self.stream, &encode_request(syndrome_pattern)).map_err(|error| error.to_string())?;
+        let payload = read_frame(&mut self.stream)
+            .map_err(|error| error.to_string())?
+            .ok_or("connection closed by the server")?;
+        decode_response(&payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::*;
+
+    #[test]
+    fn server_tcp_concurrent_clients() {
+        // cargo test server_tcp_concurrent_clients -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        code.set_erasure_probability(0.05);
+        let initializer = code.get_initializer();
+        let server = DecodingServer::new(&initializer, 2);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || server.serve_tcp(listener));
+        let syndrome_patterns: Vec<_> = (0..40).map(|seed| code.generate_random_errors(seed)).collect();
+        let clients: Vec<_> = (0..4)
+            .map(|client_index| {
+                let initializer = initializer.clone();
+                let syndrome_patterns: Vec<_> = syndrome_patterns.iter().skip(client_index).step_by(4).cloned().collect();
+                std::thread::spawn(move || {
+                    let mut client = DecodingClient::new(TcpStream::connect(address).unwrap());
+                    let mut solver = SolverSerial::new(&initializer);
+                    for syndrome_pattern in syndrome_patterns.iter() {
+                        let correction = client.decode(syndrome_pattern).unwrap();
+                        solver.solve(syndrome_pattern);
+                        assert_eq!(correction.weight, solver.sum_dual_variables());
+                        assert_eq!(correction.subgraph, solver.subgraph());
+                        solver.clear();
+                    }
+                    // invalid requests are reported without closing the connection
+                    let error = client.decode(&SyndromePattern::new_vertices(vec![1, 1])).unwrap_err();
+                    assert!(error.contains("duplicated"), "{error}");
+                    let error = client.decode(&SyndromePattern::new_vertices(vec![100000])).unwrap_err();
+                    assert!(error.contains("out of range"), "{error}");
+                    assert!(client.decode(&SyndromePattern::new_empty()).unwrap().subgraph.is_empty());
+                })
+            })
+            .collect();
+        for client in clients {
+            client.join().unwrap();
+        }
+    }
+}
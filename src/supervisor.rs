@@ -0,0 +1,248 @@
+//! Supervisor
+//!
+//! A wrapper for a long-lived decoding service that runs millions of solve-and-clear cycles on the same solver. It
+//! periodically re-verifies a sampled shot against a reference decoder and samples the size of the state that persists
+//! across clears, so that slow state leaks (e.g. a growing index space) or wrong results are reported as health metrics
+//! before they take down a production run.
+//!
+
+use super::mwpm_solver::*;
+use super::util::*;
+use crate::serde::{Deserialize, Serialize};
+use crate::serde_json;
+use std::collections::BTreeSet;
+use std::io::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SupervisorConfig {
+    /// verify one in every `audit_interval` shots against the reference decoder; 0 disables the audits
+    #[serde(default = "supervisor_default_configs::audit_interval")]
+    pub audit_interval: usize,
+    /// sample the state size and emit the health metrics once every `health_interval` shots; 0 disables them
+    #[serde(default = "supervisor_default_configs::health_interval")]
+    pub health_interval: usize,
+    /// the state is considered leaking if the allocated nodes grow beyond this ratio of the first sample
+    #[serde(default = "supervisor_default_configs::max_state_growth")]
+    pub max_state_growth: f64,
+    /// the decoder that the audits trust
+    #[serde(default = "supervisor_default_configs::reference")]
+    pub reference: SolverConfig,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod supervisor_default_configs {
+    use crate::mwpm_solver::SolverConfig;
+    pub fn audit_interval() -> usize {
+        1000
+    }
+    pub fn health_interval() -> usize {
+        10000
+    }
+    pub fn max_state_growth() -> f64 {
+        4.
+    }
+    pub fn reference() -> SolverConfig {
+        SolverConfig::Serial
+    }
+}
+
+/// the health metrics of a supervised solver
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SupervisorHealth {
+    pub shot_num: usize,
+    pub audit_num: usize,
+    pub audit_failures: usize,
+    /// the syndrome of the last failed audit, to reproduce it offline
+    pub last_audit_failure: Option<SyndromePattern>,
+    /// the first sample of the state size, or `None` if the solver doesn't measure it
+    pub initial_state_size: Option<SolverStateSize>,
+    /// the last sample of the state size
+    pub state_size: Option<SolverStateSize>,
+    pub peak_allocated_nodes: usize,
+    /// nodes retained after clear, or allocation beyond [`SupervisorConfig::max_state_growth`]
+    pub is_state_leaking: bool,
+    /// the total time of solving, building the subgraph and clearing, in seconds
+    pub decode_time: f64,
+}
+
+impl SupervisorHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.audit_failures == 0 && !self.is_state_leaking
+    }
+}
+
+/// a solver in a long-lived service, see module-level documentation
+pub struct SolverSupervisor<Solver: PrimalDualSolver> {
+    pub solver: Solver,
+    pub config: SupervisorConfig,
+    pub health: SupervisorHealth,
+    reference: Box<dyn SolverTrait>,
+    initializer: SolverInitializer,
+    /// the health metrics are written as a JSON line every [`SupervisorConfig::health_interval`] shots
+    health_writer: Option<Box<dyn Write + Send>>,
+}
+
+impl<Solver: PrimalDualSolver> SolverSupervisor<Solver> {
+    pub fn new(solver: Solver, initializer: &SolverInitializer, config: SupervisorConfig) -> Self {
+        Self {
+            solver,
+            reference: config.reference.build(initializer),
+            config,
+            health: SupervisorHealth::default(),
+            initializer: initializer.clone(),
+            health_writer: None,
+        }
+    }
+
+    pub fn with_health_writer(mut self, health_writer: impl Write + Send + 'static) -> Self {
+        self.health_writer = Some(Box::new(health_writer));
+        self
+    }
+
+    /// solve a shot and clear the solver, returning the subgraph
+    pub fn decode(&mut self, syndrome_pattern: &SyndromePattern) -> Vec<EdgeIndex> {
+        let begin = std::time::Instant::now();
+        self.solver.solve(syndrome_pattern);
+        let subgraph = self.solver.subgraph();
+        self.solver.clear();
+        self.health.decode_time += begin.elapsed().as_secs_f64();
+        self.health.shot_num += 1;
+        if self.health.shot_num.checked_rem(self.config.audit_interval) == Some(0) {
+            self.audit(syndrome_pattern, &subgraph);
+        }
+        if self.health.shot_num.checked_rem(self.config.health_interval) == Some(0) {
+            self.sample_state_size();
+            self.emit_health();
+        }
+        subgraph
+    }
+
+    /// the weight of a subgraph under the erasures and dynamic weights of the syndrome
+    #[allow(clippy::unnecessary_cast)]
+    fn subgraph_weight(&self, syndrome_pattern: &SyndromePattern, subgraph: &[EdgeIndex]) -> Weight {
+        let mut weights: Vec<Weight> = self.initializer.weighted_edges.iter().map(|(_, _, weight)| *weight).collect();
+        for &edge_index in syndrome_pattern.erasures.iter() {
+            weights[edge_index as usize] = 0;
+        }
+        for &(edge_index, weight) in syndrome_pattern.dynamic_weights.iter() {
+            weights[edge_index as usize] = weight;
+        }
+        subgraph.iter().map(|&edge_index| weights[edge_index as usize]).sum()
+    }
+
+    /// whether the subgraph explains the defects with the same weight as the reference decoder
+    pub fn audit(&mut self, syndrome_pattern: &SyndromePattern, subgraph: &[EdgeIndex]) -> bool {
+        let reference_subgraph = self.reference.decode(syndrome_pattern).subgraph;
+        self.reference.clear();
+        let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+        let passed = self.initializer.syndrome_of(subgraph) == defect_vertices
+            && self.subgraph_weight(syndrome_pattern, subgraph)
+                == self.subgraph_weight(syndrome_pattern, &reference_subgraph);
+        self.health.audit_num += 1;
+        if !passed {
+            self.health.audit_failures += 1;
+            self.health.last_audit_failure = Some(syndrome_pattern.clone());
+        }
+        passed
+    }
+
+    /// sample the state size of the solver, which is cleared after every shot
+    pub fn sample_state_size(&mut self) {
+        let Some(state_size) = self.solver.state_size() else {
+            return;
+        };
+        let initial_state_size = *self.health.initial_state_size.get_or_insert(state_size);
+        self.health.peak_allocated_nodes = std::cmp::max(self.health.peak_allocated_nodes, state_size.allocated_nodes);
+        let max_allocated_nodes = self.config.max_state_growth * std::cmp::max(initial_state_size.allocated_nodes, 1) as f64;
+        if state_size.retained_nodes > 0 || state_size.allocated_nodes as f64 > max_allocated_nodes {
+            self.health.is_state_leaking = true;
+        }
+        self.health.state_size = Some(state_size);
+    }
+
+    fn emit_health(&mut self) {
+        if let Some(health_writer) = self.health_writer.as_mut() {
+            let line = serde_json::to_string(&self.health).unwrap();
+            writeln!(health_writer, "{line}").unwrap();
+            health_writer.flush().unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::primal_module::PerfectMatching;
+    use super::super::visualize::*;
+    use super::*;
+
+    #[test]
+    fn supervisor_health_metrics() {
+        // cargo test supervisor_health_metrics -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(9, 0.05, 500);
+        let initializer = code.get_initializer();
+        let config: SupervisorConfig =
+            serde_json::from_value(json!({ "audit_interval": 10, "health_interval": 50 })).unwrap();
+        let filename = format!("{}supervisor_health_metrics.jsonl", visualize_data_folder());
+        let mut supervisor = SolverSupervisor::new(SolverSerial::new(&initializer), &initializer, config.clone())
+            .with_health_writer(std::fs::File::create(&filename).unwrap());
+        for seed in 0..300 {
+            supervisor.decode(&code.generate_random_errors(seed));
+        }
+        let health = &supervisor.health;
+        assert_eq!((health.shot_num, health.audit_num), (300, 30));
+        assert!(health.is_healthy(), "{health:?}");
+        assert_eq!(health.state_size.unwrap().retained_nodes, 0);
+        drop(supervisor);
+        assert_eq!(std::fs::read_to_string(&filename).unwrap().lines().count(), 6);
+        // a solver with wrong results and a growing state is caught
+        struct FaultySolver {
+            solver: SolverSerial,
+            leaked_nodes: usize,
+        }
+        impl PrimalDualSolver for FaultySolver {
+            fn clear(&mut self) {
+                self.solver.clear();
+                self.leaked_nodes += 100;
+            }
+            fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+                self.solver.solve_visualizer(syndrome_pattern, visualizer)
+            }
+            fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+                self.solver.perfect_matching_visualizer(visualizer)
+            }
+            fn subgraph_visualizer(&mut self, _visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+                vec![]
+            }
+            fn sum_dual_variables(&self) -> Weight {
+                self.solver.sum_dual_variables()
+            }
+            fn generate_profiler_report(&self) -> serde_json::Value {
+                self.solver.generate_profiler_report()
+            }
+            fn state_size(&self) -> Option<SolverStateSize> {
+                let mut state_size = self.solver.state_size().unwrap();
+                state_size.allocated_nodes += self.leaked_nodes;
+                Some(state_size)
+            }
+        }
+        let faulty_solver = FaultySolver {
+            solver: SolverSerial::new(&initializer),
+            leaked_nodes: 0,
+        };
+        let mut supervisor = SolverSupervisor::new(faulty_solver, &initializer, config);
+        for seed in 0..300 {
+            supervisor.decode(&code.generate_random_errors(seed));
+        }
+        let health = &supervisor.health;
+        assert!(health.audit_failures > 0 && health.last_audit_failure.is_some());
+        assert!(health.is_state_leaking);
+        assert!(!health.is_healthy());
+    }
+}
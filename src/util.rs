@@ -1,3 +1,5 @@
+use super::dual_module::DualModuleImpl;
+use super::example_codes;
 use super::mwpm_solver::PrimalDualSolver;
 use super::pointers::*;
 use super::rand_xoshiro;
@@ -5,7 +7,9 @@ use crate::rand_xoshiro::rand_core::RngCore;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::fs::File;
 use std::io::prelude::*;
 use std::time::Instant;
@@ -14,6 +18,9 @@ cfg_if::cfg_if! {
     if #[cfg(feature="i32_weight")] {
         /// use i32 to store weight to be compatible with blossom V library (c_int)
         pub type Weight = i32;
+    } else if #[cfg(feature="i16_weight")] {
+        /// use i16 to store weight on memory-constrained targets; weights must be small enough to avoid overflow
+        pub type Weight = i16;
     } else {
         pub type Weight = isize;
     }
@@ -40,6 +47,24 @@ cfg_if::cfg_if! {
     }
 }
 
+cfg_if::cfg_if! {
+    if #[cfg(feature = "tracing")] {
+        /// enter a `tracing` span until the end of the enclosing scope, e.g. `tracing_span!(DEBUG, "grow", length)`;
+        /// without the `tracing` feature nothing is evaluated
+        macro_rules! tracing_span {
+            ($level:ident, $($arguments:tt)*) => {
+                let _tracing_span = crate::tracing::span!(crate::tracing::Level::$level, $($arguments)*).entered();
+            };
+        }
+    } else {
+        macro_rules! tracing_span {
+            ($level:ident, $($arguments:tt)*) => {};
+        }
+    }
+}
+#[allow(unused_imports)]
+pub(crate) use tracing_span;
+
 #[cfg(feature = "python_binding")]
 macro_rules! bind_trait_python_json {
     ($struct_name:ident) => {
@@ -72,11 +97,62 @@ pub struct SolverInitializer {
     /// the virtual vertices
     #[cfg_attr(feature = "python_binding", pyo3(get, set))]
     pub virtual_vertices: Vec<VertexIndex>,
+    /// the expected size of a single decoding problem, used by the solvers to pre-reserve their containers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity_hint: Option<CapacityHint>,
+    /// groups of virtual vertices with a fixed parity of the number of matchings into each group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub virtual_vertex_groups: Vec<VirtualVertexGroup>,
+    /// user metadata of vertices, e.g. the qubit names, which are passed through to the visualizer snapshots
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vertex_annotations: BTreeMap<VertexIndex, serde_json::Value>,
+    /// user metadata of edges, e.g. the circuit fault locations, which are passed through to the visualizer snapshots
+    /// and the corrections, see [`SolverInitializer::subgraph_annotations`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub edge_annotations: BTreeMap<EdgeIndex, serde_json::Value>,
+    /// the circuit fault locations that flip each edge, e.g. the error indices of a Stim detector error model; a
+    /// correction flips the faults that appear an odd number of times, see [`SolverInitializer::subgraph_fault_ids`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub edge_fault_ids: BTreeMap<EdgeIndex, Vec<FaultId>>,
+}
+
+/// the distance of each vertex to the nearest virtual vertex, see [`SolverInitializer::boundary_distances`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundaryDistances {
+    /// `Weight::MAX` if no virtual vertex is reachable
+    pub distances: Vec<Weight>,
+    pub nearest_virtual: Vec<Option<VertexIndex>>,
+}
+
+/// a group of virtual vertices where the number of matchings into the group must have a fixed parity, which is needed
+/// for some boundary conditions like lattice-surgery seams
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VirtualVertexGroup {
+    pub vertices: Vec<VertexIndex>,
+    /// whether the number of matchings into this group must be odd
+    pub odd: bool,
 }
 
 #[cfg(feature = "python_binding")]
 bind_trait_python_json! {SolverInitializer}
 
+/// the expected maximum size of a decoding problem; solvers reserve capacities accordingly so that the first few shots
+/// don't suffer from reallocation, but a larger problem is still solved correctly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CapacityHint {
+    /// the expected maximum number of defect vertices in a single shot
+    pub max_defect_num: usize,
+    /// the expected maximum number of vertices covered by the dual nodes in a single shot
+    pub active_vertex_num: usize,
+}
+
+impl CapacityHint {
+    /// the number of dual nodes is bounded by twice the number of defect vertices (defect vertices and blossoms)
+    pub fn max_node_num(&self) -> usize {
+        2 * self.max_defect_num
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pyclass)]
@@ -157,12 +233,443 @@ impl SyndromePattern {
     pub fn new_empty() -> Self {
         Self::new(vec![], vec![])
     }
+    /// no defect vertices, erasures or dynamic weights, i.e. the decoding result is trivially empty
+    pub fn is_empty(&self) -> bool {
+        self.defect_vertices.is_empty() && self.erasures.is_empty() && self.dynamic_weights.is_empty()
+    }
     #[cfg(feature = "python_binding")]
     fn __repr__(&self) -> String {
         format!("{:?}", self)
     }
 }
 
+/// the reason of rejecting a syndrome pattern, see [`SyndromeValidator`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SyndromeError {
+    DefectOutOfRange {
+        vertex_index: VertexIndex,
+        vertex_num: VertexNum,
+    },
+    DuplicateDefect {
+        vertex_index: VertexIndex,
+    },
+    VirtualDefect {
+        vertex_index: VertexIndex,
+    },
+    ErasureOutOfRange {
+        edge_index: EdgeIndex,
+        edge_num: usize,
+    },
+    DynamicWeightOutOfRange {
+        edge_index: EdgeIndex,
+        edge_num: usize,
+    },
+    ErasuresWithDynamicWeights,
+    /// the dual module only supports non-negative weights, see [`NegativeWeightTransform`]
+    NegativeDynamicWeight {
+        edge_index: EdgeIndex,
+        weight: Weight,
+    },
+}
+
+impl std::fmt::Display for SyndromeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DefectOutOfRange {
+                vertex_index,
+                vertex_num,
+            } => {
+                write!(
+                    f,
+                    "defect vertex {vertex_index} out of range, there are only {vertex_num} vertices"
+                )
+            }
+            Self::DuplicateDefect { vertex_index } => write!(f, "defect vertex {vertex_index} is duplicated"),
+            Self::VirtualDefect { vertex_index } => write!(f, "defect vertex {vertex_index} is virtual"),
+            Self::ErasureOutOfRange { edge_index, edge_num } => {
+                write!(f, "erased edge {edge_index} out of range, there are only {edge_num} edges")
+            }
+            Self::DynamicWeightOutOfRange { edge_index, edge_num } => {
+                write!(
+                    f,
+                    "dynamic weight edge {edge_index} out of range, there are only {edge_num} edges"
+                )
+            }
+            Self::ErasuresWithDynamicWeights => {
+                write!(f, "erasures and dynamic_weights cannot be provided at the same time")
+            }
+            Self::NegativeDynamicWeight { edge_index, weight } => {
+                write!(f, "dynamic weight {weight} of edge {edge_index} is negative")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SyndromeError {}
+
+/// whether to run the sanity checks when taking snapshots and the invariant checks after every growth: always in debug
+/// builds, e.g. `cargo test` or `cargo run -- test`, and in release builds only with the `strict_checks` feature
+pub const STRICT_CHECKS: bool = cfg!(any(debug_assertions, feature = "strict_checks"));
+
+/// errors surfaced by the dual module and its interface instead of panicking, so that an embedding application can
+/// recover from unsupported operations or a corrupted solver state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FusionError {
+    /// the dual module implementation doesn't support this optional function
+    UnsupportedOperation(&'static str),
+    InvalidSyndrome(SyndromeError),
+    /// the internal state violates an invariant, the solver should be cleared before reuse
+    Corrupted(String),
+    /// an accumulated [`Weight`] overflowed under [`WeightOverflow::Error`], naming the accumulator
+    WeightOverflow(&'static str),
+    /// the dual variables cannot grow any further until the primal module resolves the conflict, which is printed
+    UnresolvedConflict(String),
+}
+
+impl std::fmt::Display for FusionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedOperation(operation) => write!(
+                f,
+                "the dual module implementation doesn't support {operation}, please use another dual module"
+            ),
+            Self::InvalidSyndrome(error) => write!(f, "invalid syndrome: {error}"),
+            Self::Corrupted(message) => write!(f, "corrupted state: {message}"),
+            Self::WeightOverflow(accumulator) => write!(f, "weight overflow in {accumulator}"),
+            Self::UnresolvedConflict(conflict) => write!(f, "cannot grow because of unresolved conflicts {conflict}"),
+        }
+    }
+}
+
+impl std::error::Error for FusionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidSyndrome(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<SyndromeError> for FusionError {
+    fn from(error: SyndromeError) -> Self {
+        Self::InvalidSyndrome(error)
+    }
+}
+
+/// run `f` and report a panic as an error
+pub fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|panic| {
+        if let Some(message) = panic.downcast_ref::<String>() {
+            format!("panic: {message}")
+        } else if let Some(message) = panic.downcast_ref::<&str>() {
+            format!("panic: {message}")
+        } else {
+            "panic".to_string()
+        }
+    })
+}
+
+/// the behavior when an accumulated [`Weight`] overflows, e.g. the sum of dual variables in a long streaming run with
+/// large weights, which would otherwise wrap around silently in release builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WeightOverflow {
+    /// keep the last valid value and report [`FusionError::WeightOverflow`] when checked
+    #[default]
+    Error,
+    /// clamp the value to [`Weight::MAX`] or [`Weight::MIN`]
+    Saturate,
+}
+
+impl WeightOverflow {
+    /// `a + b`, or `None` if it overflows and should be reported
+    #[inline]
+    pub fn add(self, a: Weight, b: Weight) -> Option<Weight> {
+        match self {
+            Self::Error => a.checked_add(b),
+            Self::Saturate => Some(a.saturating_add(b)),
+        }
+    }
+
+    /// `a + b * c`, or `None` if it overflows and should be reported
+    #[inline]
+    pub fn mul_add(self, a: Weight, b: Weight, c: Weight) -> Option<Weight> {
+        match self {
+            Self::Error => b.checked_mul(c).and_then(|product| a.checked_add(product)),
+            Self::Saturate => Some(a.saturating_add(b.saturating_mul(c))),
+        }
+    }
+}
+
+/// checks syndrome patterns against a decoding graph before they're loaded, because the dual module panics deep inside
+/// on invalid ones
+#[derive(Debug, Clone)]
+pub struct SyndromeValidator {
+    pub vertex_num: VertexNum,
+    pub edge_num: usize,
+    is_virtual: Vec<bool>,
+    /// remove duplicate defect vertices, see [`SyndromePattern::deduplicate`], instead of rejecting the syndrome
+    pub auto_deduplicate: bool,
+}
+
+impl SyndromeValidator {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        Self {
+            vertex_num: initializer.vertex_num,
+            edge_num: initializer.weighted_edges.len(),
+            is_virtual,
+            auto_deduplicate: false,
+        }
+    }
+
+    pub fn with_auto_deduplicate(mut self, auto_deduplicate: bool) -> Self {
+        self.auto_deduplicate = auto_deduplicate;
+        self
+    }
+
+    /// returns the syndrome pattern to be solved, which differs from the given one only if duplicates are removed
+    #[allow(clippy::unnecessary_cast)]
+    pub fn validate<'a>(&self, syndrome_pattern: &'a SyndromePattern) -> Result<Cow<'a, SyndromePattern>, SyndromeError> {
+        if !syndrome_pattern.erasures.is_empty() && !syndrome_pattern.dynamic_weights.is_empty() {
+            return Err(SyndromeError::ErasuresWithDynamicWeights);
+        }
+        let mut is_defect = vec![false; self.vertex_num as usize];
+        let mut has_duplicate = false;
+        for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+            if vertex_index >= self.vertex_num {
+                return Err(SyndromeError::DefectOutOfRange {
+                    vertex_index,
+                    vertex_num: self.vertex_num,
+                });
+            }
+            if self.is_virtual[vertex_index as usize] {
+                return Err(SyndromeError::VirtualDefect { vertex_index });
+            }
+            if is_defect[vertex_index as usize] {
+                if !self.auto_deduplicate {
+                    return Err(SyndromeError::DuplicateDefect { vertex_index });
+                }
+                has_duplicate = true;
+            }
+            is_defect[vertex_index as usize] = true;
+        }
+        for &edge_index in syndrome_pattern.erasures.iter() {
+            if edge_index as usize >= self.edge_num {
+                return Err(SyndromeError::ErasureOutOfRange {
+                    edge_index,
+                    edge_num: self.edge_num,
+                });
+            }
+        }
+        for &(edge_index, weight) in syndrome_pattern.dynamic_weights.iter() {
+            if edge_index as usize >= self.edge_num {
+                return Err(SyndromeError::DynamicWeightOutOfRange {
+                    edge_index,
+                    edge_num: self.edge_num,
+                });
+            }
+            if weight < 0 {
+                return Err(SyndromeError::NegativeDynamicWeight { edge_index, weight });
+            }
+        }
+        if has_duplicate {
+            let mut deduplicated = syndrome_pattern.clone();
+            deduplicated.deduplicate();
+            return Ok(Cow::Owned(deduplicated));
+        }
+        Ok(Cow::Borrowed(syndrome_pattern))
+    }
+}
+
+impl SyndromePattern {
+    /// check the syndrome pattern against the decoding graph, see [`SyndromeValidator`]
+    pub fn validate(&self, initializer: &SolverInitializer) -> Result<(), SyndromeError> {
+        SyndromeValidator::new(initializer).validate(self).map(|_| ())
+    }
+
+    /// remove the duplicate defect vertices and erasures, keeping the first occurrence of each
+    pub fn deduplicate(&mut self) {
+        let mut visited = HashSet::new();
+        self.defect_vertices.retain(|&vertex_index| visited.insert(vertex_index));
+        let mut visited = HashSet::new();
+        self.erasures.retain(|&edge_index| visited.insert(edge_index));
+    }
+}
+
+/// the adjacency list of the decoding graph of a [`SolverInitializer`], for shortest-path and neighborhood queries,
+/// e.g. when building reweighting schemes or verifiers; unlike [`crate::complete_graph::CompleteGraph`], the weights
+/// are fixed and all the queries take `&self`
+#[derive(Debug, Clone)]
+pub struct DecodingGraph {
+    pub vertex_num: VertexNum,
+    pub weighted_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+    /// `(peer, edge_index)` of each vertex, in the order of the edges
+    adjacency: Vec<Vec<(VertexIndex, EdgeIndex)>>,
+    is_virtual: Vec<bool>,
+}
+
+/// the result of Dijkstra's algorithm from a single source in a [`DecodingGraph`]
+#[derive(Debug, Clone)]
+pub struct ShortestPaths {
+    pub source: VertexIndex,
+    /// the vertices in the order they're reached, i.e. non-decreasing in distance
+    pub order: Vec<VertexIndex>,
+    /// `(distance, previous edge)` of the reached vertices
+    reached: BTreeMap<VertexIndex, (Weight, Option<EdgeIndex>)>,
+}
+
+impl ShortestPaths {
+    /// the minimum weight from the source, `None` if not reached
+    pub fn distance(&self, vertex_index: VertexIndex) -> Option<Weight> {
+        self.reached.get(&vertex_index).map(|&(distance, _)| distance)
+    }
+
+    /// the edges of a minimum-weight path from the source to `vertex_index`, `None` if not reached
+    #[allow(clippy::unnecessary_cast)]
+    pub fn path(&self, graph: &DecodingGraph, mut vertex_index: VertexIndex) -> Option<Vec<EdgeIndex>> {
+        let mut edges = vec![];
+        while let Some(edge_index) = self.reached.get(&vertex_index)?.1 {
+            edges.push(edge_index);
+            let (left, right, _) = graph.weighted_edges[edge_index as usize];
+            vertex_index = if left == vertex_index { right } else { left };
+        }
+        edges.reverse();
+        Some(edges)
+    }
+}
+
+impl DecodingGraph {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let mut adjacency = vec![vec![]; initializer.vertex_num as usize];
+        for (edge_index, &(left, right, _)) in initializer.weighted_edges.iter().enumerate() {
+            adjacency[left as usize].push((right, edge_index as EdgeIndex));
+            adjacency[right as usize].push((left, edge_index as EdgeIndex));
+        }
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        Self {
+            vertex_num: initializer.vertex_num,
+            weighted_edges: initializer.weighted_edges.clone(),
+            adjacency,
+            is_virtual,
+        }
+    }
+
+    /// the `(peer, edge_index)` of the edges incident to a vertex
+    #[allow(clippy::unnecessary_cast)]
+    pub fn neighbors(&self, vertex_index: VertexIndex) -> &[(VertexIndex, EdgeIndex)] {
+        &self.adjacency[vertex_index as usize]
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn is_virtual(&self, vertex_index: VertexIndex) -> bool {
+        self.is_virtual[vertex_index as usize]
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn edge_weight(&self, edge_index: EdgeIndex) -> Weight {
+        self.weighted_edges[edge_index as usize].2
+    }
+
+    /// the vertices within `hops` edges of a vertex, including itself, in the order of breadth-first search
+    #[allow(clippy::unnecessary_cast)]
+    pub fn neighborhood(&self, vertex_index: VertexIndex, hops: usize) -> Vec<VertexIndex> {
+        let mut visited = HashSet::from([vertex_index]);
+        let mut vertices = vec![vertex_index];
+        let mut layer_start = 0;
+        for _ in 0..hops {
+            let layer_end = vertices.len();
+            for index in layer_start..layer_end {
+                for &(peer, _) in self.adjacency[vertices[index] as usize].iter() {
+                    if visited.insert(peer) {
+                        vertices.push(peer);
+                    }
+                }
+            }
+            if vertices.len() == layer_end {
+                break;
+            }
+            layer_start = layer_end;
+        }
+        vertices
+    }
+
+    /// Dijkstra's algorithm that stops after reaching `terminate` or when the distance exceeds `max_distance`
+    #[allow(clippy::unnecessary_cast)]
+    fn search(
+        &self,
+        source: VertexIndex,
+        max_distance: Weight,
+        mut terminate: impl FnMut(VertexIndex) -> bool,
+    ) -> ShortestPaths {
+        let mut reached = BTreeMap::new();
+        let mut order = vec![];
+        let mut tentative: BTreeMap<VertexIndex, (Weight, Option<EdgeIndex>)> = BTreeMap::new();
+        let mut heap = BinaryHeap::new();
+        tentative.insert(source, (0, None));
+        heap.push(Reverse((0, source)));
+        while let Some(Reverse((distance, vertex_index))) = heap.pop() {
+            if distance > max_distance {
+                break;
+            }
+            if reached.contains_key(&vertex_index) || tentative[&vertex_index].0 != distance {
+                continue; // outdated entry
+            }
+            reached.insert(vertex_index, tentative[&vertex_index]);
+            order.push(vertex_index);
+            if terminate(vertex_index) {
+                break;
+            }
+            for &(peer, edge_index) in self.adjacency[vertex_index as usize].iter() {
+                let peer_distance = distance.saturating_add(self.edge_weight(edge_index));
+                if reached.contains_key(&peer) || matches!(tentative.get(&peer), Some(&(d, _)) if d <= peer_distance) {
+                    continue;
+                }
+                tentative.insert(peer, (peer_distance, Some(edge_index)));
+                heap.push(Reverse((peer_distance, peer)));
+            }
+        }
+        ShortestPaths { source, order, reached }
+    }
+
+    /// the minimum weight from `source` to every connected vertex
+    pub fn dijkstra(&self, source: VertexIndex) -> ShortestPaths {
+        self.search(source, Weight::MAX, |_| false)
+    }
+
+    /// the vertices within a distance of `max_distance`, in non-decreasing order of the distance
+    pub fn vertices_within(&self, source: VertexIndex, max_distance: Weight) -> Vec<(VertexIndex, Weight)> {
+        let shortest_paths = self.search(source, max_distance, |_| false);
+        shortest_paths
+            .order
+            .iter()
+            .map(|&vertex_index| (vertex_index, shortest_paths.distance(vertex_index).unwrap()))
+            .collect()
+    }
+
+    /// the edges and the weight of a minimum-weight path between two vertices, `None` if not connected
+    pub fn shortest_path(&self, a: VertexIndex, b: VertexIndex) -> Option<(Vec<EdgeIndex>, Weight)> {
+        let shortest_paths = self.search(a, Weight::MAX, |vertex_index| vertex_index == b);
+        Some((shortest_paths.path(self, b)?, shortest_paths.distance(b)?))
+    }
+
+    /// the closest virtual vertex other than itself and the distance to it, `None` if no virtual vertex is connected
+    pub fn nearest_virtual_vertex(&self, vertex_index: VertexIndex) -> Option<(VertexIndex, Weight)> {
+        let shortest_paths = self.search(vertex_index, Weight::MAX, |peer| {
+            peer != vertex_index && self.is_virtual(peer)
+        });
+        let &last = shortest_paths.order.last()?;
+        (last != vertex_index && self.is_virtual(last)).then(|| (last, shortest_paths.distance(last).unwrap()))
+    }
+}
+
 /// an efficient representation of partitioned vertices and erasures when they're ordered
 #[derive(Debug, Clone, Serialize)]
 pub struct PartitionedSyndromePattern<'a> {
@@ -439,116 +946,1111 @@ impl PartitionConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[cfg_attr(feature = "python_binding", cfg_eval)]
-#[cfg_attr(feature = "python_binding", pyclass)]
-pub struct PartitionInfo {
-    /// the initial configuration that creates this info
-    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
-    pub config: PartitionConfig,
-    /// individual info of each unit
-    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
-    pub units: Vec<PartitionUnitInfo>,
-    /// the mapping from vertices to the owning unit: serial unit (holding real vertices) as well as parallel units (holding interfacing vertices);
-    /// used for loading syndrome to the holding units
-    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
-    pub vertex_to_owning_unit: Vec<usize>,
-}
-
-#[cfg(feature = "python_binding")]
-bind_trait_python_json! {PartitionInfo}
-
-#[cfg_attr(feature = "python_binding", pymethods)]
-impl PartitionInfo {
-    /// split a sequence of syndrome into multiple parts, each corresponds to a unit;
-    /// this is a slow method and should only be used when the syndrome pattern is not well-ordered
+impl PartitionConfig {
+    /// move the partition boundaries so that each partition holds a similar number of defect vertices, keeping the
+    /// fusion tree unchanged; this requires a layered layout, e.g. the time partition of a phenomenological code, where
+    /// the vertices are grouped into layers of `layer_vertex_num` vertices, edges only connect the same or adjacent
+    /// layers, and consecutive partitions are separated by exactly one interface layer
     #[allow(clippy::unnecessary_cast)]
-    pub fn partition_syndrome_unordered(&self, syndrome_pattern: &SyndromePattern) -> Vec<SyndromePattern> {
-        let mut partitioned_syndrome: Vec<_> = (0..self.units.len()).map(|_| SyndromePattern::new_empty()).collect();
-        for defect_vertex in syndrome_pattern.defect_vertices.iter() {
-            let unit_index = self.vertex_to_owning_unit[*defect_vertex as usize];
-            partitioned_syndrome[unit_index].defect_vertices.push(*defect_vertex);
+    pub fn rebalance_layers(
+        &self,
+        layer_vertex_num: VertexNum,
+        syndrome_pattern: &SyndromePattern,
+    ) -> Result<PartitionConfig, String> {
+        let layer_num = self.layer_num(layer_vertex_num)?;
+        let mut layer_costs = vec![0.; layer_num];
+        for &defect_vertex in syndrome_pattern.defect_vertices.iter() {
+            layer_costs[defect_vertex as usize / layer_vertex_num as usize] += 1.;
         }
-        // TODO: partition edges
-        partitioned_syndrome
+        self.rebalance_layers_by_cost(layer_vertex_num, &layer_costs)
     }
 
-    #[cfg(feature = "python_binding")]
-    fn __repr__(&self) -> String {
-        format!("{:?}", self)
+    /// the number of layers in a layered layout, see [`PartitionConfig::rebalance_layers`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn layer_num(&self, layer_vertex_num: VertexNum) -> Result<usize, String> {
+        let layer_vertex_num = layer_vertex_num as usize;
+        let vertex_num = self.vertex_num as usize;
+        if layer_vertex_num == 0 || vertex_num / layer_vertex_num * layer_vertex_num != vertex_num {
+            return Err(format!(
+                "{vertex_num} vertices cannot be split into layers of {layer_vertex_num}"
+            ));
+        }
+        Ok(vertex_num / layer_vertex_num)
     }
-}
 
-impl<'a> PartitionedSyndromePattern<'a> {
-    /// partition the syndrome pattern into 2 partitioned syndrome pattern and my whole range
+    /// move the partition boundaries so that each partition holds a similar total cost, given the cost of each layer;
+    /// see [`PartitionConfig::rebalance_layers`] for the requirement of the layout
     #[allow(clippy::unnecessary_cast)]
-    pub fn partition(&self, partition_unit_info: &PartitionUnitInfo) -> (Self, (Self, Self)) {
-        // first binary search the start of owning defect vertices
-        let owning_start_index = {
-            let mut left_index = self.whole_defect_range.start();
-            let mut right_index = self.whole_defect_range.end();
-            while left_index != right_index {
-                let mid_index = (left_index + right_index) / 2;
-                let mid_defect_vertex = self.syndrome_pattern.defect_vertices[mid_index as usize];
-                if mid_defect_vertex < partition_unit_info.owning_range.start() {
-                    left_index = mid_index + 1;
-                } else {
-                    right_index = mid_index;
-                }
+    pub fn rebalance_layers_by_cost(
+        &self,
+        layer_vertex_num: VertexNum,
+        layer_costs: &[f64],
+    ) -> Result<PartitionConfig, String> {
+        let layer_num = self.layer_num(layer_vertex_num)?;
+        let layer_vertex_num = layer_vertex_num as usize;
+        let vertex_num = self.vertex_num as usize;
+        if layer_costs.len() != layer_num {
+            return Err(format!("{} layer costs given for {layer_num} layers", layer_costs.len()));
+        }
+        let partition_num = self.partitions.len();
+        // check the layout: partition `i` spans layers [start, end) and the interface layer is `end` if it's not the last
+        let mut next_start = 0;
+        for (partition_index, partition) in self.partitions.iter().enumerate() {
+            let (start, end) = (partition.start() as usize, partition.end() as usize);
+            if start != next_start || end % layer_vertex_num > 0 || end <= start {
+                return Err(format!("partition {partition_index} {partition:?} is not aligned to layers"));
             }
-            left_index
-        };
-        // second binary search the end of owning defect vertices
-        let owning_end_index = {
-            let mut left_index = self.whole_defect_range.start();
-            let mut right_index = self.whole_defect_range.end();
-            while left_index != right_index {
-                let mid_index = (left_index + right_index) / 2;
-                let mid_defect_vertex = self.syndrome_pattern.defect_vertices[mid_index as usize];
-                if mid_defect_vertex < partition_unit_info.owning_range.end() {
-                    left_index = mid_index + 1;
+            next_start = end + layer_vertex_num;
+        }
+        if next_start != vertex_num + layer_vertex_num {
+            return Err("partitions must cover all the layers".to_string());
+        }
+        let mut cost_before = vec![0.; layer_num + 1];
+        for layer_index in 0..layer_num {
+            cost_before[layer_index + 1] = cost_before[layer_index] + layer_costs[layer_index];
+        }
+        let total_cost = cost_before[layer_num];
+        let mut config = self.clone();
+        let mut start_layer = 0;
+        for partition_index in 0..partition_num {
+            let end_layer = if partition_index + 1 == partition_num {
+                layer_num
+            } else {
+                // leave at least 2 layers (a partition and an interface) for each of the remaining partitions
+                let max_end_layer = layer_num - 2 * (partition_num - 1 - partition_index);
+                let min_end_layer = start_layer + 1;
+                let target = if total_cost <= 0. {
+                    // evenly split the layers
+                    (partition_index + 1) * layer_num / partition_num
                 } else {
-                    right_index = mid_index;
-                }
-            }
-            left_index
-        };
-        (
-            Self {
-                syndrome_pattern: self.syndrome_pattern,
-                whole_defect_range: DefectRange::new(owning_start_index, owning_end_index),
-            },
-            (
-                Self {
-                    syndrome_pattern: self.syndrome_pattern,
-                    whole_defect_range: DefectRange::new(self.whole_defect_range.start(), owning_start_index),
-                },
-                Self {
-                    syndrome_pattern: self.syndrome_pattern,
-                    whole_defect_range: DefectRange::new(owning_end_index, self.whole_defect_range.end()),
-                },
-            ),
-        )
-    }
-
-    #[allow(clippy::unnecessary_cast)]
-    pub fn expand(&self) -> SyndromePattern {
-        let mut defect_vertices = Vec::with_capacity(self.whole_defect_range.len());
-        for defect_index in self.whole_defect_range.iter() {
-            defect_vertices.push(self.syndrome_pattern.defect_vertices[defect_index as usize]);
+                    (min_end_layer..max_end_layer)
+                        .find(|&layer_index| {
+                            cost_before[layer_index] * partition_num as f64 >= (partition_index + 1) as f64 * total_cost
+                        })
+                        .unwrap_or(max_end_layer)
+                };
+                target.clamp(min_end_layer, max_end_layer)
+            };
+            config.partitions[partition_index] = VertexRange::new(
+                (start_layer * layer_vertex_num) as VertexIndex,
+                (end_layer * layer_vertex_num) as VertexIndex,
+            );
+            start_layer = end_layer + 1;
         }
-        SyndromePattern::new(defect_vertices, vec![])
+        Ok(config)
     }
 }
 
+/// the file format of [`PartitionConfig`], so that partition layouts can be shared and versioned outside Rust code; the file
+/// is parsed as TOML if its name ends with `.toml` and as JSON otherwise
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[cfg_attr(feature = "python_binding", cfg_eval)]
-#[cfg_attr(feature = "python_binding", pyclass)]
-pub struct PartitionUnitInfo {
-    /// the whole range of units
-    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
-    pub whole_range: VertexRange,
-    /// the owning range of units, meaning vertices inside are exclusively belonging to the unit
+#[serde(deny_unknown_fields)]
+pub struct PartitionConfigFile {
+    /// the number of vertices
+    pub vertex_num: VertexNum,
+    /// the vertex range of each leaf partition, see [`PartitionConfig::partitions`]
+    pub partitions: Vec<VertexRange>,
+    /// the fusion tree, see [`PartitionConfig::fusions`]
+    #[serde(default)]
+    pub fusions: Vec<(usize, usize)>,
+    /// optional vertex reordering: `reordered_vertices[i]` is the original index of vertex `i` in the partitioned decoding
+    /// graph, see [`translated_defect_to_reordered`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reordered_vertices: Option<Vec<VertexIndex>>,
+}
+
+impl PartitionConfig {
+    /// check that the leaf partitions are disjoint ranges and the fusions form a binary tree covering all the vertices;
+    /// unlike [`PartitionConfig::info`], an invalid configuration is reported as an error instead of a panic
+    #[allow(clippy::unnecessary_cast)]
+    pub fn sanity_check(&self) -> Result<(), String> {
+        if self.partitions.is_empty() {
+            return Err("at least one partition must exist".to_string());
+        }
+        for (partition_index, partition) in self.partitions.iter().enumerate() {
+            if partition.start() > partition.end() || partition.end() > self.vertex_num as VertexIndex {
+                return Err(format!("invalid range {:?} of partition {}", partition, partition_index));
+            }
+        }
+        let mut sorted_partitions: Vec<_> = self.partitions.iter().enumerate().collect();
+        sorted_partitions.sort_by_key(|(_, partition)| partition.start());
+        for window in sorted_partitions.windows(2) {
+            let ((index_1, partition_1), (index_2, partition_2)) = (window[0], window[1]);
+            if partition_1.end() > partition_2.start() {
+                return Err(format!("partition {} and {} overlap", index_1, index_2));
+            }
+        }
+        let unit_count = self.partitions.len() + self.fusions.len();
+        let mut parents: Vec<Option<usize>> = vec![None; unit_count];
+        let mut whole_ranges = self.partitions.clone();
+        for (fusion_index, &(left_index, right_index)) in self.fusions.iter().enumerate() {
+            let unit_index = fusion_index + self.partitions.len();
+            for child_index in [left_index, right_index] {
+                if child_index >= unit_index {
+                    return Err(format!("dependency wrong, {} depending on {}", unit_index, child_index));
+                }
+                if parents[child_index].is_some() {
+                    return Err(format!("cannot fuse {} twice", child_index));
+                }
+                parents[child_index] = Some(unit_index);
+            }
+            let (left_range, right_range) = (whole_ranges[left_index], whole_ranges[right_index]);
+            if left_range.end() > right_range.start() {
+                return Err(format!(
+                    "unit {} must fuse a lower range with a higher range, got {:?} and {:?}",
+                    unit_index, left_range, right_range
+                ));
+            }
+            whole_ranges.push(VertexRange::new(left_range.start(), right_range.end()));
+        }
+        if let Some(unit_index) = (0..unit_count - 1).find(|&unit_index| parents[unit_index].is_none()) {
+            return Err(format!("found unit {} without being fused", unit_index));
+        }
+        let final_range = whole_ranges[unit_count - 1];
+        if final_range.start() != 0 || final_range.end() != self.vertex_num as VertexIndex {
+            return Err(format!("final range not covering all vertices {:?}", final_range));
+        }
+        Ok(())
+    }
+
+    /// load a partition configuration from a file, see [`PartitionConfigFile`]; returns the checked configuration and the
+    /// optional vertex reordering
+    pub fn from_file(filepath: &str) -> Result<(Self, Option<Vec<VertexIndex>>), String> {
+        let content =
+            std::fs::read_to_string(filepath).map_err(|err| format!("cannot read partition config {filepath}: {err}"))?;
+        let file: PartitionConfigFile = if filepath.ends_with(".toml") {
+            toml::from_str(&content).map_err(|err| format!("invalid partition config {filepath}: {err}"))?
+        } else {
+            serde_json::from_str(&content).map_err(|err| format!("invalid partition config {filepath}: {err}"))?
+        };
+        let config = Self {
+            vertex_num: file.vertex_num,
+            partitions: file.partitions,
+            fusions: file.fusions,
+        };
+        config.sanity_check()?;
+        if let Some(reordered_vertices) = file.reordered_vertices.as_ref() {
+            check_reordered_vertices(config.vertex_num, reordered_vertices)?;
+        }
+        Ok((config, file.reordered_vertices))
+    }
+
+    /// save the partition configuration to a file, see [`PartitionConfigFile`]
+    pub fn to_file(&self, filepath: &str, reordered_vertices: Option<Vec<VertexIndex>>) -> Result<(), String> {
+        let file = PartitionConfigFile {
+            vertex_num: self.vertex_num,
+            partitions: self.partitions.clone(),
+            fusions: self.fusions.clone(),
+            reordered_vertices,
+        };
+        let content = if filepath.ends_with(".toml") {
+            toml::to_string(&file).map_err(|err| err.to_string())?
+        } else {
+            serde_json::to_string_pretty(&file).map_err(|err| err.to_string())?
+        };
+        std::fs::write(filepath, content).map_err(|err| format!("cannot write partition config {filepath}: {err}"))
+    }
+}
+
+/// check that the vertex reordering is a permutation of all the vertices
+#[allow(clippy::unnecessary_cast)]
+fn check_reordered_vertices(vertex_num: VertexNum, reordered_vertices: &[VertexIndex]) -> Result<(), String> {
+    if reordered_vertices.len() != vertex_num as usize {
+        return Err(format!(
+            "{} reordered vertices given for {} vertices",
+            reordered_vertices.len(),
+            vertex_num
+        ));
+    }
+    let mut visited = vec![false; vertex_num as usize];
+    for &vertex_index in reordered_vertices.iter() {
+        if vertex_index >= vertex_num || visited[vertex_index as usize] {
+            return Err(format!("vertex {} is invalid or duplicated in the reordering", vertex_index));
+        }
+        visited[vertex_index as usize] = true;
+    }
+    Ok(())
+}
+
+/// a partition computed by external tools (e.g. METIS) given as arbitrary vertex sets, instead of the contiguous ranges required
+/// by [`PartitionConfig`]; [`ExternalPartition::build`] reorders the vertices so that every unit covers a contiguous range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalPartition {
+    /// the number of vertices
+    pub vertex_num: VertexNum,
+    /// the vertices of each leaf unit
+    pub partitions: Vec<Vec<VertexIndex>>,
+    /// the interface vertices owned by each fusion unit, in the same order as `fusions`
+    pub interfaces: Vec<Vec<VertexIndex>>,
+    /// the fusion plan, same as [`PartitionConfig::fusions`]
+    pub fusions: Vec<(usize, usize)>,
+}
+
+impl ExternalPartition {
+    pub fn new(
+        vertex_num: VertexNum,
+        partitions: Vec<Vec<VertexIndex>>,
+        interfaces: Vec<Vec<VertexIndex>>,
+        fusions: Vec<(usize, usize)>,
+    ) -> Self {
+        Self {
+            vertex_num,
+            partitions,
+            interfaces,
+            fusions,
+        }
+    }
+
+    /// partition an arbitrary decoding graph into at most `partition_num` leaf units by recursive bisection: the vertices
+    /// of a unit are layered by a breadth-first search and a middle layer becomes the interface between the two halves.
+    /// A layer only connects to itself and its adjacent layers, so the two halves are always separated by the interface
+    #[allow(clippy::unnecessary_cast)]
+    pub fn auto(initializer: &SolverInitializer, partition_num: usize) -> Self {
+        assert!(partition_num > 0, "at least one partition is required");
+        let vertex_num = initializer.vertex_num as usize;
+        let mut neighbors = vec![vec![]; vertex_num];
+        for &(i, j, _) in initializer.weighted_edges.iter() {
+            neighbors[i as usize].push(j);
+            neighbors[j as usize].push(i);
+        }
+        let root = AutoPartitionUnit::bisect((0..initializer.vertex_num).collect(), partition_num, &neighbors);
+        let mut partition = Self::new(initializer.vertex_num, vec![], vec![], vec![]);
+        let leaf_num = root.leaf_num();
+        root.flatten(&mut partition, leaf_num);
+        partition
+    }
+
+    /// the vertices of a unit, either a leaf unit or a fusion unit
+    fn unit_vertices(&self, unit_index: usize) -> &Vec<VertexIndex> {
+        if unit_index < self.partitions.len() {
+            &self.partitions[unit_index]
+        } else {
+            &self.interfaces[unit_index - self.partitions.len()]
+        }
+    }
+
+    /// check that the fusion plan forms a tree, every vertex is owned by exactly one unit, and every edge of the decoding graph
+    /// connects a unit with itself or with one of its ancestors
+    #[allow(clippy::unnecessary_cast)]
+    pub fn sanity_check(&self, initializer: &SolverInitializer) -> Result<(), String> {
+        if self.vertex_num != initializer.vertex_num {
+            return Err(format!(
+                "vertex number mismatch: {} in partition, {} in the decoding graph",
+                self.vertex_num, initializer.vertex_num
+            ));
+        }
+        if self.partitions.is_empty() {
+            return Err("at least one partition must exist".to_string());
+        }
+        if self.interfaces.len() != self.fusions.len() {
+            return Err(format!(
+                "{} interfaces given for {} fusions",
+                self.interfaces.len(),
+                self.fusions.len()
+            ));
+        }
+        let unit_count = self.partitions.len() + self.fusions.len();
+        let mut parents: Vec<Option<usize>> = vec![None; unit_count];
+        for (fusion_index, &(left_index, right_index)) in self.fusions.iter().enumerate() {
+            let unit_index = fusion_index + self.partitions.len();
+            for child_index in [left_index, right_index] {
+                if child_index >= unit_index {
+                    return Err(format!("dependency wrong, {} depending on {}", unit_index, child_index));
+                }
+                if parents[child_index].is_some() {
+                    return Err(format!("cannot fuse {} twice", child_index));
+                }
+                parents[child_index] = Some(unit_index);
+            }
+        }
+        if let Some(unit_index) = (0..unit_count - 1).find(|&unit_index| parents[unit_index].is_none()) {
+            return Err(format!("found unit {} without being fused", unit_index));
+        }
+        let mut vertex_to_owning_unit = vec![usize::MAX; self.vertex_num as usize];
+        for unit_index in 0..unit_count {
+            for &vertex_index in self.unit_vertices(unit_index).iter() {
+                if vertex_index >= self.vertex_num {
+                    return Err(format!("invalid vertex index {} in unit {}", vertex_index, unit_index));
+                }
+                let owning_unit = &mut vertex_to_owning_unit[vertex_index as usize];
+                if *owning_unit != usize::MAX {
+                    return Err(format!(
+                        "vertex {} is owned by both unit {} and unit {}",
+                        vertex_index, owning_unit, unit_index
+                    ));
+                }
+                *owning_unit = unit_index;
+            }
+        }
+        if let Some(vertex_index) = vertex_to_owning_unit.iter().position(|&unit_index| unit_index == usize::MAX) {
+            return Err(format!("vertex {} is not owned by any unit", vertex_index));
+        }
+        let is_ancestor = |ancestor: usize, mut unit_index: usize| {
+            while let Some(parent) = parents[unit_index] {
+                if parent == ancestor {
+                    return true;
+                }
+                unit_index = parent;
+            }
+            false
+        };
+        for &(i, j, _) in initializer.weighted_edges.iter() {
+            let unit_i = vertex_to_owning_unit[i as usize];
+            let unit_j = vertex_to_owning_unit[j as usize];
+            if unit_i != unit_j && !is_ancestor(unit_i, unit_j) && !is_ancestor(unit_j, unit_i) {
+                return Err(format!(
+                    "edge ({}, {}) connects unit {} and unit {}, which must be separated by an interface",
+                    i, j, unit_i, unit_j
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// the order of vertices such that every unit covers a contiguous range: `reordered_vertices[new_index] = old_index`
+    pub fn reordered_vertices(&self) -> Vec<VertexIndex> {
+        self.reorder().0
+    }
+
+    /// returns the reordered vertices and the range of each leaf unit in the new indices
+    #[allow(clippy::unnecessary_cast)]
+    fn reorder(&self) -> (Vec<VertexIndex>, Vec<VertexRange>) {
+        let mut reordered_vertices = Vec::with_capacity(self.vertex_num as usize);
+        let mut partitions = vec![VertexRange::new(0, 0); self.partitions.len()];
+        self.append_unit_vertices(
+            self.partitions.len() + self.fusions.len() - 1,
+            &mut reordered_vertices,
+            &mut partitions,
+        );
+        (reordered_vertices, partitions)
+    }
+
+    /// in-order traversal of the fusion tree: left child, interface, right child
+    #[allow(clippy::unnecessary_cast)]
+    fn append_unit_vertices(
+        &self,
+        unit_index: usize,
+        reordered_vertices: &mut Vec<VertexIndex>,
+        partitions: &mut [VertexRange],
+    ) {
+        if unit_index < self.partitions.len() {
+            let start = reordered_vertices.len() as VertexIndex;
+            reordered_vertices.extend(self.partitions[unit_index].iter());
+            partitions[unit_index] = VertexRange::new(start, reordered_vertices.len() as VertexIndex);
+        } else {
+            let (left_index, right_index) = self.fusions[unit_index - self.partitions.len()];
+            self.append_unit_vertices(left_index, reordered_vertices, partitions);
+            reordered_vertices.extend(self.interfaces[unit_index - self.partitions.len()].iter());
+            self.append_unit_vertices(right_index, reordered_vertices, partitions);
+        }
+    }
+
+    /// validate the partition and build the decoding graph and partition config in the reordered vertex indices;
+    /// the defect vertices should be translated accordingly using [`translated_defect_to_reordered`] and then sorted
+    #[allow(clippy::unnecessary_cast)]
+    pub fn build(
+        &self,
+        initializer: &SolverInitializer,
+    ) -> Result<(Vec<VertexIndex>, SolverInitializer, PartitionConfig), String> {
+        self.sanity_check(initializer)?;
+        let (reordered_vertices, partitions) = self.reorder();
+        let old_to_new: Vec<VertexIndex> = build_old_to_new(&reordered_vertices)
+            .into_iter()
+            .map(|new_index| new_index.unwrap())
+            .collect();
+        let mut reordered_initializer = SolverInitializer::new(
+            initializer.vertex_num,
+            initializer
+                .weighted_edges
+                .iter()
+                .map(|&(i, j, weight)| (old_to_new[i as usize], old_to_new[j as usize], weight))
+                .collect(),
+            initializer
+                .virtual_vertices
+                .iter()
+                .map(|&vertex_index| old_to_new[vertex_index as usize])
+                .collect(),
+        );
+        // the edges keep their indices, so only the vertex annotations are moved
+        reordered_initializer.edge_annotations = initializer.edge_annotations.clone();
+        reordered_initializer.edge_fault_ids = initializer.edge_fault_ids.clone();
+        reordered_initializer.vertex_annotations = (initializer.vertex_annotations.iter())
+            .map(|(&vertex_index, annotation)| (old_to_new[vertex_index as usize], annotation.clone()))
+            .collect();
+        let mut config = PartitionConfig::new(self.vertex_num);
+        config.partitions = partitions;
+        config.fusions = self.fusions.clone();
+        Ok((reordered_vertices, reordered_initializer, config))
+    }
+}
+
+/// the fusion tree built by [`ExternalPartition::auto`]
+enum AutoPartitionUnit {
+    Leaf(Vec<VertexIndex>),
+    Fusion(Box<AutoPartitionUnit>, Vec<VertexIndex>, Box<AutoPartitionUnit>),
+}
+
+impl AutoPartitionUnit {
+    #[allow(clippy::unnecessary_cast)]
+    fn bisect(vertices: Vec<VertexIndex>, partition_num: usize, neighbors: &[Vec<VertexIndex>]) -> Self {
+        if partition_num <= 1 {
+            return Self::Leaf(vertices);
+        }
+        let mut in_unit = vec![false; neighbors.len()];
+        for &vertex_index in vertices.iter() {
+            in_unit[vertex_index as usize] = true;
+        }
+        let mut visited = vec![false; neighbors.len()];
+        let bfs_layers = |start: VertexIndex, visited: &mut [bool]| {
+            visited[start as usize] = true;
+            let mut layers = vec![vec![start]];
+            loop {
+                let mut next_layer = vec![];
+                for &vertex_index in layers.last().unwrap().iter() {
+                    for &neighbor in neighbors[vertex_index as usize].iter() {
+                        if in_unit[neighbor as usize] && !visited[neighbor as usize] {
+                            visited[neighbor as usize] = true;
+                            next_layer.push(neighbor);
+                        }
+                    }
+                }
+                if next_layer.is_empty() {
+                    break layers;
+                }
+                layers.push(next_layer);
+            }
+        };
+        // layer each connected component from a pseudo-peripheral vertex, i.e. the farthest from an arbitrary vertex,
+        // and concatenate the layers of the components because they don't connect to each other
+        let mut layers = vec![];
+        for &vertex_index in vertices.iter() {
+            if visited[vertex_index as usize] {
+                continue;
+            }
+            let component_layers = bfs_layers(vertex_index, &mut visited);
+            for &component_vertex in component_layers.iter().flatten() {
+                visited[component_vertex as usize] = false;
+            }
+            let peripheral_vertex = component_layers.last().unwrap()[0];
+            layers.extend(bfs_layers(peripheral_vertex, &mut visited));
+        }
+        if layers.len() < 3 {
+            return Self::Leaf(vertices);
+        }
+        // split the vertices proportionally to the number of partitions on each side
+        let left_partition_num = partition_num / 2;
+        let target = vertices.len() * left_partition_num / partition_num;
+        let mut cut_layer = 1;
+        let mut left_vertex_num = layers[0].len();
+        while cut_layer + 2 < layers.len() && left_vertex_num + layers[cut_layer].len() <= target {
+            left_vertex_num += layers[cut_layer].len();
+            cut_layer += 1;
+        }
+        let left = layers[..cut_layer].concat();
+        let right = layers[cut_layer + 1..].concat();
+        Self::Fusion(
+            Box::new(Self::bisect(left, left_partition_num, neighbors)),
+            std::mem::take(&mut layers[cut_layer]),
+            Box::new(Self::bisect(right, partition_num - left_partition_num, neighbors)),
+        )
+    }
+
+    fn leaf_num(&self) -> usize {
+        match self {
+            Self::Leaf(_) => 1,
+            Self::Fusion(left, _, right) => left.leaf_num() + right.leaf_num(),
+        }
+    }
+
+    /// append the units in post-order, returning the unit index
+    fn flatten(self, partition: &mut ExternalPartition, leaf_num: usize) -> usize {
+        match self {
+            Self::Leaf(vertices) => {
+                partition.partitions.push(vertices);
+                partition.partitions.len() - 1
+            }
+            Self::Fusion(left, interface, right) => {
+                let left_index = left.flatten(partition, leaf_num);
+                let right_index = right.flatten(partition, leaf_num);
+                partition.interfaces.push(interface);
+                partition.fusions.push((left_index, right_index));
+                leaf_num + partition.fusions.len() - 1
+            }
+        }
+    }
+}
+
+/// contract zero-weight edges (e.g. erasures) of a decoding graph into super-vertices before decoding, so that a large
+/// erasure region becomes a single vertex; the correction is recovered by [`ZeroWeightContraction::expand_subgraph`]
+#[derive(Debug, Clone)]
+pub struct ZeroWeightContraction {
+    /// the decoding graph after contraction, a super-vertex is virtual if it contains any virtual vertex
+    pub initializer: SolverInitializer,
+    /// the super-vertex of each original vertex
+    pub super_vertices: Vec<VertexIndex>,
+    /// the original edge of each contracted edge; among parallel edges only the one with the smallest weight is kept
+    pub original_edges: Vec<EdgeIndex>,
+    /// the original vertices of each contracted edge
+    original_endpoints: Vec<(VertexIndex, VertexIndex)>,
+    /// the zero-weight edges spanning each super-vertex in BFS order from the root: (vertex, parent, edge);
+    /// the root is a virtual vertex whenever the super-vertex contains one
+    spanning_edges: Vec<(VertexIndex, VertexIndex, EdgeIndex)>,
+}
+
+impl ZeroWeightContraction {
+    /// contract all the zero-weight edges together with the erased edges
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, erasures: &[EdgeIndex]) -> Self {
+        let vertex_num = initializer.vertex_num as usize;
+        let mut is_virtual = vec![false; vertex_num];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        let mut is_contracted: Vec<bool> = initializer.weighted_edges.iter().map(|(_, _, weight)| *weight == 0).collect();
+        for &edge_index in erasures.iter() {
+            is_contracted[edge_index as usize] = true;
+        }
+        // union-find to build a spanning forest of the zero-weight edges
+        fn find(parents: &mut [usize], mut vertex: usize) -> usize {
+            while parents[vertex] != vertex {
+                parents[vertex] = parents[parents[vertex]];
+                vertex = parents[vertex];
+            }
+            vertex
+        }
+        let mut parents: Vec<usize> = (0..vertex_num).collect();
+        let mut forest_neighbors: Vec<Vec<(VertexIndex, EdgeIndex)>> = vec![vec![]; vertex_num];
+        for (edge_index, &(i, j, _)) in initializer.weighted_edges.iter().enumerate() {
+            if !is_contracted[edge_index] {
+                continue;
+            }
+            let (root_i, root_j) = (find(&mut parents, i as usize), find(&mut parents, j as usize));
+            if root_i != root_j {
+                parents[root_i] = root_j;
+                forest_neighbors[i as usize].push((j, edge_index as EdgeIndex));
+                forest_neighbors[j as usize].push((i, edge_index as EdgeIndex));
+            }
+        }
+        // pick a virtual vertex as the root of each tree whenever possible, otherwise the smallest vertex
+        let mut tree_roots: Vec<Option<usize>> = vec![None; vertex_num];
+        for vertex in 0..vertex_num {
+            let root = find(&mut parents, vertex);
+            match tree_roots[root] {
+                Some(tree_root) if is_virtual[tree_root] || !is_virtual[vertex] => {}
+                _ => tree_roots[root] = Some(vertex),
+            }
+        }
+        let mut super_vertices = vec![VertexIndex::MAX; vertex_num];
+        let mut spanning_edges = vec![];
+        let mut virtual_vertices = vec![];
+        let mut super_vertex_num: VertexNum = 0;
+        for vertex in 0..vertex_num {
+            let tree_root = tree_roots[find(&mut parents, vertex)].unwrap();
+            if super_vertices[tree_root] != VertexIndex::MAX {
+                continue;
+            }
+            let super_vertex = super_vertex_num as VertexIndex;
+            super_vertex_num += 1;
+            if is_virtual[tree_root] {
+                virtual_vertices.push(super_vertex);
+            }
+            super_vertices[tree_root] = super_vertex;
+            let mut head = spanning_edges.len();
+            let mut current = tree_root;
+            loop {
+                for &(peer, edge_index) in forest_neighbors[current].iter() {
+                    if super_vertices[peer as usize] == VertexIndex::MAX {
+                        super_vertices[peer as usize] = super_vertex;
+                        spanning_edges.push((peer, current as VertexIndex, edge_index));
+                    }
+                }
+                if head == spanning_edges.len() {
+                    break;
+                }
+                current = spanning_edges[head].0 as usize;
+                head += 1;
+            }
+        }
+        // keep the lightest edge between each pair of super-vertices
+        let mut lightest_edges = BTreeMap::<(VertexIndex, VertexIndex), EdgeIndex>::new();
+        for (edge_index, &(i, j, weight)) in initializer.weighted_edges.iter().enumerate() {
+            let (si, sj) = (super_vertices[i as usize], super_vertices[j as usize]);
+            if si == sj {
+                continue;
+            }
+            let key = (std::cmp::min(si, sj), std::cmp::max(si, sj));
+            let edge_index = edge_index as EdgeIndex;
+            let lightest = lightest_edges.entry(key).or_insert(edge_index);
+            if weight < initializer.weighted_edges[*lightest as usize].2 {
+                *lightest = edge_index;
+            }
+        }
+        let mut weighted_edges = Vec::with_capacity(lightest_edges.len());
+        let mut original_edges = Vec::with_capacity(lightest_edges.len());
+        let mut original_endpoints = Vec::with_capacity(lightest_edges.len());
+        for (&(si, sj), &edge_index) in lightest_edges.iter() {
+            let (i, j, weight) = initializer.weighted_edges[edge_index as usize];
+            weighted_edges.push((si, sj, weight));
+            original_edges.push(edge_index);
+            original_endpoints.push((i, j));
+        }
+        Self {
+            initializer: SolverInitializer::new(super_vertex_num, weighted_edges, virtual_vertices),
+            super_vertices,
+            original_edges,
+            original_endpoints,
+            spanning_edges,
+        }
+    }
+
+    /// the defect vertices on the contracted graph: defects in the same super-vertex cancel each other, and a defect
+    /// in a virtual super-vertex is matched to the boundary for free; the erasures must have been contracted
+    #[allow(clippy::unnecessary_cast)]
+    pub fn translate_syndrome(&self, syndrome_pattern: &SyndromePattern) -> SyndromePattern {
+        assert!(
+            syndrome_pattern.dynamic_weights.is_empty(),
+            "dynamic weights are not supported on a contracted graph"
+        );
+        let mut is_defect = BTreeSet::new();
+        for &defect_vertex in syndrome_pattern.defect_vertices.iter() {
+            let super_vertex = self.super_vertices[defect_vertex as usize];
+            if !is_defect.remove(&super_vertex) {
+                is_defect.insert(super_vertex);
+            }
+        }
+        for &virtual_vertex in self.initializer.virtual_vertices.iter() {
+            is_defect.remove(&virtual_vertex);
+        }
+        SyndromePattern::new_vertices(is_defect.into_iter().collect())
+    }
+
+    /// expand the subgraph on the contracted graph to a subgraph on the original graph with the same weight, by adding
+    /// zero-weight edges inside the super-vertices so that the parity of every original defect vertex is satisfied
+    #[allow(clippy::unnecessary_cast)]
+    pub fn expand_subgraph(&self, defect_vertices: &[VertexIndex], subgraph: &[EdgeIndex]) -> Vec<EdgeIndex> {
+        let mut odd = vec![false; self.super_vertices.len()];
+        for &defect_vertex in defect_vertices.iter() {
+            odd[defect_vertex as usize] ^= true;
+        }
+        let mut expanded = Vec::with_capacity(subgraph.len());
+        for &edge_index in subgraph.iter() {
+            let (i, j) = self.original_endpoints[edge_index as usize];
+            odd[i as usize] ^= true;
+            odd[j as usize] ^= true;
+            expanded.push(self.original_edges[edge_index as usize]);
+        }
+        // fix the parity from the leaves to the root, any remaining parity at a root is absorbed by the virtual vertex
+        for &(vertex, parent, edge_index) in self.spanning_edges.iter().rev() {
+            if odd[vertex as usize] {
+                odd[vertex as usize] = false;
+                odd[parent as usize] ^= true;
+                expanded.push(edge_index);
+            }
+        }
+        expanded.sort();
+        expanded
+    }
+}
+
+/// the peeling decoder over the erased edges, i.e. the linear-time erasure decoder of Delfosse and Zémor: a spanning
+/// forest of the erased edges is built, rooted at a virtual vertex whenever the tree contains one, and the parity of the
+/// defect vertices is pushed from the leaves to the root, where the virtual vertices absorb it. Since the erased edges
+/// have zero weight, it can also realize the part of a minimum-weight correction inside the erasures, see
+/// [`ErasurePeeler::peel_subgraph`]
+#[derive(Debug, Clone)]
+pub struct ErasurePeeler {
+    endpoints: Vec<(VertexIndex, VertexIndex)>,
+    is_virtual: Vec<bool>,
+}
+
+impl ErasurePeeler {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        Self {
+            endpoints: (initializer.weighted_edges.iter()).map(|&(i, j, _)| (i, j)).collect(),
+            is_virtual,
+        }
+    }
+
+    /// the spanning forest of the erased edges in BFS order from the root of each tree: (vertex, parent, edge)
+    #[allow(clippy::unnecessary_cast)]
+    fn spanning_forest(&self, erasures: &[EdgeIndex]) -> Vec<(VertexIndex, VertexIndex, EdgeIndex)> {
+        let mut neighbors = BTreeMap::<VertexIndex, Vec<(VertexIndex, EdgeIndex)>>::new();
+        for &edge_index in erasures.iter().collect::<BTreeSet<_>>() {
+            let (i, j) = self.endpoints[edge_index as usize];
+            neighbors.entry(i).or_default().push((j, edge_index));
+            neighbors.entry(j).or_default().push((i, edge_index));
+        }
+        let mut visited = BTreeSet::new();
+        let mut spanning_edges = vec![];
+        for &start in neighbors.keys() {
+            if visited.contains(&start) {
+                continue;
+            }
+            // find a virtual vertex in the tree to be the root
+            let mut tree = vec![start];
+            let mut tree_visited = BTreeSet::from([start]);
+            let mut head = 0;
+            while head < tree.len() {
+                for &(peer, _) in neighbors[&tree[head]].iter() {
+                    if tree_visited.insert(peer) {
+                        tree.push(peer);
+                    }
+                }
+                head += 1;
+            }
+            let root = (tree.iter().find(|&&vertex| self.is_virtual[vertex as usize]).cloned()).unwrap_or(start);
+            visited.insert(root);
+            let mut head = spanning_edges.len();
+            let mut current = root;
+            loop {
+                for &(peer, edge_index) in neighbors[&current].iter() {
+                    if visited.insert(peer) {
+                        spanning_edges.push((peer, current, edge_index));
+                    }
+                }
+                if head == spanning_edges.len() {
+                    break;
+                }
+                current = spanning_edges[head].0;
+                head += 1;
+            }
+        }
+        spanning_edges
+    }
+
+    /// the correction inside the erasures whose syndrome is the defect vertices, which is a subset of the spanning forest;
+    /// fails if a tree without virtual vertex has an odd number of defect vertices, or a defect vertex is not erased
+    #[allow(clippy::unnecessary_cast)]
+    pub fn peel(&self, erasures: &[EdgeIndex], defect_vertices: &[VertexIndex]) -> Result<Vec<EdgeIndex>, String> {
+        let mut odd = BTreeSet::new();
+        for &defect_vertex in defect_vertices.iter() {
+            if !odd.remove(&defect_vertex) {
+                odd.insert(defect_vertex);
+            }
+        }
+        let mut correction = vec![];
+        for &(vertex, parent, edge_index) in self.spanning_forest(erasures).iter().rev() {
+            if odd.remove(&vertex) && !self.is_virtual[vertex as usize] {
+                if !odd.remove(&parent) {
+                    odd.insert(parent);
+                }
+                correction.push(edge_index);
+            }
+        }
+        if let Some(&vertex) = odd.iter().find(|&&vertex| !self.is_virtual[vertex as usize]) {
+            return Err(format!("the parity at vertex {vertex} cannot be fixed inside the erasures"));
+        }
+        correction.sort();
+        Ok(correction)
+    }
+
+    /// replace the erased edges of a correction by the peeling of their syndrome, which has the same weight and the same
+    /// syndrome; the other edges are kept
+    #[allow(clippy::unnecessary_cast)]
+    pub fn peel_subgraph(&self, erasures: &[EdgeIndex], subgraph: &[EdgeIndex]) -> Vec<EdgeIndex> {
+        let erased: BTreeSet<EdgeIndex> = erasures.iter().cloned().collect();
+        let mut peeled = vec![];
+        let mut erased_endpoints = vec![];
+        for &edge_index in subgraph.iter() {
+            if erased.contains(&edge_index) {
+                let (i, j) = self.endpoints[edge_index as usize];
+                erased_endpoints.extend([i, j]);
+            } else {
+                peeled.push(edge_index);
+            }
+        }
+        let correction = self.peel(erasures, &erased_endpoints);
+        peeled.extend(correction.expect("the syndrome of erased edges can always be peeled"));
+        peeled.sort();
+        peeled
+    }
+}
+
+/// absorb the negative-weight edges of a decoding graph, e.g. from a correlated reweighting where `p > 0.5`, which the dual
+/// module doesn't support: each negative edge is assumed to be in the correction, so the parity of its endpoints is
+/// flipped in every syndrome and the edge gets the opposite weight, meaning that picking it removes it from the correction
+#[derive(Debug, Clone)]
+pub struct NegativeWeightTransform {
+    /// the decoding graph with non-negative weights and the same vertex and edge indices
+    pub initializer: SolverInitializer,
+    /// the edges with negative weights, in increasing order
+    pub negative_edges: Vec<EdgeIndex>,
+    /// the real vertices whose parity is flipped, i.e. adjacent to an odd number of negative edges
+    pub flipped_vertices: Vec<VertexIndex>,
+    /// the sum of the negative weights; the weight of a correction in the original graph is its weight in the transformed
+    /// graph plus this offset, as long as no negative edge is erased
+    pub offset: Weight,
+}
+
+impl NegativeWeightTransform {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let mut transformed = initializer.clone();
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        let mut is_flipped = vec![false; initializer.vertex_num as usize];
+        let mut negative_edges = vec![];
+        let mut offset = 0;
+        for (edge_index, (i, j, weight)) in transformed.weighted_edges.iter_mut().enumerate() {
+            if *weight < 0 {
+                negative_edges.push(edge_index as EdgeIndex);
+                offset += *weight;
+                *weight = -*weight;
+                is_flipped[*i as usize] ^= true;
+                is_flipped[*j as usize] ^= true;
+            }
+        }
+        let flipped_vertices = (0..initializer.vertex_num)
+            .filter(|&vertex_index| is_flipped[vertex_index as usize] && !is_virtual[vertex_index as usize])
+            .collect();
+        Self {
+            initializer: transformed,
+            negative_edges,
+            flipped_vertices,
+            offset,
+        }
+    }
+
+    /// the syndrome on the transformed graph, where the defect vertices are flipped; the erasures are kept
+    pub fn translate_syndrome(&self, syndrome_pattern: &SyndromePattern) -> SyndromePattern {
+        assert!(
+            syndrome_pattern.dynamic_weights.is_empty(),
+            "dynamic weights are not supported on a transformed graph"
+        );
+        let mut is_defect: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+        for vertex_index in self.flipped_vertices.iter() {
+            if !is_defect.remove(vertex_index) {
+                is_defect.insert(*vertex_index);
+            }
+        }
+        SyndromePattern::new(is_defect.into_iter().collect(), syndrome_pattern.erasures.clone())
+    }
+
+    /// the correction on the original graph, i.e. the symmetric difference between the subgraph and the negative edges
+    pub fn translate_subgraph(&self, subgraph: &[EdgeIndex]) -> Vec<EdgeIndex> {
+        let mut edges: BTreeSet<EdgeIndex> = self.negative_edges.iter().cloned().collect();
+        for edge_index in subgraph.iter() {
+            if !edges.remove(edge_index) {
+                edges.insert(*edge_index);
+            }
+        }
+        edges.into_iter().collect()
+    }
+}
+
+/// turn the analog confidence of repeated measurements into the weights of time-like edges, assuming the decoding
+/// graph is made of rounds of `vertex_num_per_round` vertices and a measurement error of vertex `i` in round `t` flips
+/// the vertices `t * vertex_num_per_round + i` and `(t + 1) * vertex_num_per_round + i`
+#[derive(Debug, Clone)]
+pub struct SoftInfoAdapter {
+    /// the number of vertices in each round
+    pub vertex_num_per_round: VertexNum,
+    /// the time-like edge from each vertex to the same vertex in the next round, indexed by `[round][vertex]`
+    timelike_edges: Vec<Vec<Option<EdgeIndex>>>,
+    /// the integer weight of a unit log-likelihood ratio, calibrated from the original time-like edges
+    pub weight_scale: f64,
+}
+
+impl SoftInfoAdapter {
+    /// the smallest measurement error rate considered, so that a fully confident measurement has a finite weight
+    pub const MIN_ERROR_RATE: f64 = 1e-10;
+
+    /// find the time-like edges, whose weights are assumed to encode a measurement error rate `measurement_error_rate`
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, vertex_num_per_round: VertexNum, measurement_error_rate: f64) -> Self {
+        assert!(vertex_num_per_round > 0, "each round must have at least one vertex");
+        let round_num = (initializer.vertex_num as usize).div_ceil(vertex_num_per_round as usize);
+        let mut timelike_edges = vec![vec![None; vertex_num_per_round as usize]; round_num.saturating_sub(1)];
+        let mut weight_sum = 0.;
+        let mut timelike_edge_num = 0;
+        for (edge_index, &(i, j, weight)) in initializer.weighted_edges.iter().enumerate() {
+            let (i, j) = if i < j { (i, j) } else { (j, i) };
+            if j - i != vertex_num_per_round {
+                continue;
+            }
+            let round = (i / vertex_num_per_round) as usize;
+            timelike_edges[round][(i % vertex_num_per_round) as usize] = Some(edge_index as EdgeIndex);
+            weight_sum += weight as f64;
+            timelike_edge_num += 1;
+        }
+        assert!(timelike_edge_num > 0, "no time-like edge found in the decoding graph");
+        let weight_scale = weight_sum / (timelike_edge_num as f64) / example_codes::weight_of_p(measurement_error_rate);
+        Self {
+            vertex_num_per_round,
+            timelike_edges,
+            weight_scale,
+        }
+    }
+
+    /// the weight of a time-like edge given the confidence in `[0.5, 1]` that the measurement is correct; the weight is
+    /// rounded to an even number like every other weight in the decoding graph
+    pub fn weight_of_confidence(&self, confidence: f64) -> Weight {
+        let error_rate = (1. - confidence).clamp(Self::MIN_ERROR_RATE, 0.5);
+        let half_weight = (self.weight_scale * example_codes::weight_of_p(error_rate) / 2.).round();
+        2 * half_weight as Weight
+    }
+
+    /// the edge modifier given the confidence of each measurement, indexed by `[round][vertex]`; vertices without a
+    /// time-like edge to the next round (e.g. virtual vertices or the last round) are ignored
+    pub fn edge_modifier(&self, confidences: &[Vec<f64>]) -> Vec<(EdgeIndex, Weight)> {
+        assert!(
+            confidences.len() <= self.timelike_edges.len(),
+            "{} rounds of confidences given but only {} rounds have time-like edges",
+            confidences.len(),
+            self.timelike_edges.len()
+        );
+        let mut edge_modifier = vec![];
+        for (round_confidences, round_edges) in confidences.iter().zip(self.timelike_edges.iter()) {
+            assert!(
+                round_confidences.len() <= round_edges.len(),
+                "too many confidences in a round"
+            );
+            for (&confidence, edge_index) in round_confidences.iter().zip(round_edges.iter()) {
+                if let Some(edge_index) = edge_index {
+                    edge_modifier.push((*edge_index, self.weight_of_confidence(confidence)));
+                }
+            }
+        }
+        edge_modifier
+    }
+
+    /// load the soft information into a dual module
+    pub fn load_into(&self, dual_module: &mut impl DualModuleImpl, confidences: &[Vec<f64>]) -> Result<(), FusionError> {
+        dual_module.load_edge_modifier(&self.edge_modifier(confidences))
+    }
+
+    /// a syndrome pattern carrying the soft information as dynamic weights, which can be given to any solver
+    pub fn syndrome_pattern(&self, defect_vertices: Vec<VertexIndex>, confidences: &[Vec<f64>]) -> SyndromePattern {
+        SyndromePattern::new_dynamic_weights(defect_vertices, vec![], self.edge_modifier(confidences))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct PartitionInfo {
+    /// the initial configuration that creates this info
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub config: PartitionConfig,
+    /// individual info of each unit
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub units: Vec<PartitionUnitInfo>,
+    /// the mapping from vertices to the owning unit: serial unit (holding real vertices) as well as parallel units (holding interfacing vertices);
+    /// used for loading syndrome to the holding units
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertex_to_owning_unit: Vec<usize>,
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_python_json! {PartitionInfo}
+
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl PartitionInfo {
+    /// split a sequence of syndrome into multiple parts, each corresponds to a unit;
+    /// this is a slow method and should only be used when the syndrome pattern is not well-ordered
+    #[allow(clippy::unnecessary_cast)]
+    pub fn partition_syndrome_unordered(&self, syndrome_pattern: &SyndromePattern) -> Vec<SyndromePattern> {
+        let mut partitioned_syndrome: Vec<_> = (0..self.units.len()).map(|_| SyndromePattern::new_empty()).collect();
+        for defect_vertex in syndrome_pattern.defect_vertices.iter() {
+            let unit_index = self.vertex_to_owning_unit[*defect_vertex as usize];
+            partitioned_syndrome[unit_index].defect_vertices.push(*defect_vertex);
+        }
+        // TODO: partition edges
+        partitioned_syndrome
+    }
+
+    /// the level of a unit in the fusion tree: base partitions are at level 0, and a fusion unit is one level above its highest child
+    pub fn tree_level(&self, unit_index: usize) -> usize {
+        match self.units[unit_index].children {
+            Some((left_index, right_index)) => 1 + self.tree_level(left_index).max(self.tree_level(right_index)),
+            None => 0,
+        }
+    }
+
+    #[cfg(feature = "python_binding")]
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl<'a> PartitionedSyndromePattern<'a> {
+    /// partition the syndrome pattern into 2 partitioned syndrome pattern and my whole range
+    #[allow(clippy::unnecessary_cast)]
+    pub fn partition(&self, partition_unit_info: &PartitionUnitInfo) -> (Self, (Self, Self)) {
+        // first binary search the start of owning defect vertices
+        let owning_start_index = {
+            let mut left_index = self.whole_defect_range.start();
+            let mut right_index = self.whole_defect_range.end();
+            while left_index != right_index {
+                let mid_index = (left_index + right_index) / 2;
+                let mid_defect_vertex = self.syndrome_pattern.defect_vertices[mid_index as usize];
+                if mid_defect_vertex < partition_unit_info.owning_range.start() {
+                    left_index = mid_index + 1;
+                } else {
+                    right_index = mid_index;
+                }
+            }
+            left_index
+        };
+        // second binary search the end of owning defect vertices
+        let owning_end_index = {
+            let mut left_index = self.whole_defect_range.start();
+            let mut right_index = self.whole_defect_range.end();
+            while left_index != right_index {
+                let mid_index = (left_index + right_index) / 2;
+                let mid_defect_vertex = self.syndrome_pattern.defect_vertices[mid_index as usize];
+                if mid_defect_vertex < partition_unit_info.owning_range.end() {
+                    left_index = mid_index + 1;
+                } else {
+                    right_index = mid_index;
+                }
+            }
+            left_index
+        };
+        (
+            Self {
+                syndrome_pattern: self.syndrome_pattern,
+                whole_defect_range: DefectRange::new(owning_start_index, owning_end_index),
+            },
+            (
+                Self {
+                    syndrome_pattern: self.syndrome_pattern,
+                    whole_defect_range: DefectRange::new(self.whole_defect_range.start(), owning_start_index),
+                },
+                Self {
+                    syndrome_pattern: self.syndrome_pattern,
+                    whole_defect_range: DefectRange::new(owning_end_index, self.whole_defect_range.end()),
+                },
+            ),
+        )
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn expand(&self) -> SyndromePattern {
+        let mut defect_vertices = Vec::with_capacity(self.whole_defect_range.len());
+        for defect_index in self.whole_defect_range.iter() {
+            defect_vertices.push(self.syndrome_pattern.defect_vertices[defect_index as usize]);
+        }
+        SyndromePattern::new(defect_vertices, vec![])
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct PartitionUnitInfo {
+    /// the whole range of units
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub whole_range: VertexRange,
+    /// the owning range of units, meaning vertices inside are exclusively belonging to the unit
     #[cfg_attr(feature = "python_binding", pyo3(get, set))]
     pub owning_range: VertexRange,
     /// left and right
@@ -599,6 +2101,47 @@ pub struct PartitionedSolverInitializer {
     pub virtual_vertices: Vec<VertexIndex>,
 }
 
+/// read shots in the b8 format of stim: each shot is `bit_num` bits packed into `ceil(bit_num / 8)` bytes, least significant
+/// bit first; returns the indices of the set bits of each shot
+pub fn read_b8_shots(bytes: &[u8], bit_num: usize) -> Result<Vec<Vec<usize>>, String> {
+    let shot_bytes = bit_num.div_ceil(8);
+    if shot_bytes == 0 {
+        return Err("cannot read shots of 0 bits".to_string());
+    }
+    if bytes.len().checked_rem(shot_bytes) != Some(0) {
+        return Err(format!(
+            "{} bytes is not a multiple of {shot_bytes} bytes per shot of {bit_num} bits",
+            bytes.len()
+        ));
+    }
+    bytes
+        .chunks(shot_bytes)
+        .enumerate()
+        .map(|(shot_index, shot)| {
+            let bits: Vec<usize> = (0..shot_bytes * 8)
+                .filter(|&bit_index| shot[bit_index / 8] & (1 << (bit_index % 8)) != 0)
+                .collect();
+            match bits.last() {
+                Some(&bit_index) if bit_index >= bit_num => Err(format!("shot {shot_index} sets padding bit {bit_index}")),
+                _ => Ok(bits),
+            }
+        })
+        .collect()
+}
+
+/// write shots in the b8 format of stim, see [`read_b8_shots`]
+pub fn write_b8_shots(shots: &[Vec<usize>], bit_num: usize) -> Vec<u8> {
+    let shot_bytes = bit_num.div_ceil(8);
+    let mut bytes = vec![0; shots.len() * shot_bytes];
+    for (shot, shot_bytes) in shots.iter().zip(bytes.chunks_mut(shot_bytes)) {
+        for &bit_index in shot.iter() {
+            assert!(bit_index < bit_num, "bit {bit_index} out of range");
+            shot_bytes[bit_index / 8] |= 1 << (bit_index % 8);
+        }
+    }
+    bytes
+}
+
 /// perform index transformation
 #[allow(clippy::unnecessary_cast)]
 pub fn build_old_to_new(reordered_vertices: &Vec<VertexIndex>) -> Vec<Option<VertexIndex>> {
@@ -607,44 +2150,285 @@ pub fn build_old_to_new(reordered_vertices: &Vec<VertexIndex>) -> Vec<Option<Ver
         assert_eq!(old_to_new[*old_index as usize], None, "duplicate vertex found {}", old_index);
         old_to_new[*old_index as usize] = Some(new_index as VertexIndex);
     }
-    old_to_new
-}
+    old_to_new
+}
+
+/// translate defect vertices into the current new index given reordered_vertices
+#[allow(clippy::unnecessary_cast)]
+pub fn translated_defect_to_reordered(
+    reordered_vertices: &Vec<VertexIndex>,
+    old_defect_vertices: &[VertexIndex],
+) -> Vec<VertexIndex> {
+    let old_to_new = build_old_to_new(reordered_vertices);
+    old_defect_vertices
+        .iter()
+        .map(|old_index| old_to_new[*old_index as usize].unwrap())
+        .collect()
+}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl SolverInitializer {
+    #[cfg_attr(feature = "python_binding", new)]
+    pub fn new(
+        vertex_num: VertexNum,
+        weighted_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+        virtual_vertices: Vec<VertexIndex>,
+    ) -> SolverInitializer {
+        SolverInitializer {
+            vertex_num,
+            weighted_edges,
+            virtual_vertices,
+            capacity_hint: None,
+            virtual_vertex_groups: vec![],
+            vertex_annotations: BTreeMap::new(),
+            edge_annotations: BTreeMap::new(),
+            edge_fault_ids: BTreeMap::new(),
+        }
+    }
+    #[cfg(feature = "python_binding")]
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "annotate_vertex")]
+    fn annotate_vertex_py(&mut self, vertex_index: VertexIndex, annotation: PyObject) {
+        let annotation = pyobject_to_json(annotation);
+        *self = std::mem::replace(self, SolverInitializer::new(0, vec![], vec![]))
+            .with_vertex_annotation(vertex_index, annotation);
+    }
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "annotate_edge")]
+    fn annotate_edge_py(&mut self, edge_index: EdgeIndex, annotation: PyObject) {
+        let annotation = pyobject_to_json(annotation);
+        *self =
+            std::mem::replace(self, SolverInitializer::new(0, vec![], vec![])).with_edge_annotation(edge_index, annotation);
+    }
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "set_edge_fault_ids")]
+    fn set_edge_fault_ids_py(&mut self, edge_index: EdgeIndex, fault_ids: Vec<FaultId>) {
+        *self =
+            std::mem::replace(self, SolverInitializer::new(0, vec![], vec![])).with_edge_fault_ids(edge_index, fault_ids);
+    }
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "subgraph_fault_ids")]
+    fn subgraph_fault_ids_py(&self, subgraph: Vec<EdgeIndex>) -> Vec<FaultId> {
+        self.subgraph_fault_ids(&subgraph)
+    }
+}
+
+impl SolverInitializer {
+    pub fn with_capacity_hint(mut self, capacity_hint: CapacityHint) -> Self {
+        self.capacity_hint = Some(capacity_hint);
+        self
+    }
+
+    pub fn with_virtual_vertex_groups(mut self, virtual_vertex_groups: Vec<VirtualVertexGroup>) -> Self {
+        self.virtual_vertex_groups = virtual_vertex_groups;
+        self
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn with_vertex_annotation(mut self, vertex_index: VertexIndex, annotation: serde_json::Value) -> Self {
+        assert!(vertex_index < self.vertex_num, "vertex {vertex_index} out of range");
+        self.vertex_annotations.insert(vertex_index, annotation);
+        self
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn with_edge_annotation(mut self, edge_index: EdgeIndex, annotation: serde_json::Value) -> Self {
+        assert!(
+            (edge_index as usize) < self.weighted_edges.len(),
+            "edge {edge_index} out of range"
+        );
+        self.edge_annotations.insert(edge_index, annotation);
+        self
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn with_edge_fault_ids(mut self, edge_index: EdgeIndex, fault_ids: Vec<FaultId>) -> Self {
+        assert!(
+            (edge_index as usize) < self.weighted_edges.len(),
+            "edge {edge_index} out of range"
+        );
+        self.edge_fault_ids.insert(edge_index, fault_ids);
+        self
+    }
+
+    /// the faults flipped by the subgraph in increasing order, see [`subgraph_fault_ids`]
+    pub fn subgraph_fault_ids(&self, subgraph: &[EdgeIndex]) -> Vec<FaultId> {
+        subgraph_fault_ids(&self.edge_fault_ids, subgraph)
+    }
+
+    /// the annotation of each edge in the subgraph, or `null` if it's not annotated
+    pub fn subgraph_annotations(&self, subgraph: &[EdgeIndex]) -> Vec<serde_json::Value> {
+        (subgraph.iter())
+            .map(|edge_index| self.edge_annotations.get(edge_index).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    /// enforce the virtual vertex groups with a constraint vertex for each group, appended after the existing vertices;
+    /// the group members become real vertices connected to the constraint vertex by zero-weight edges appended after the
+    /// existing edges, so that a constraint vertex must be matched an odd number of times if it's a defect.
+    /// Returns the expanded graph together with the constraint vertices that should be added as defects in every syndrome
+    #[allow(clippy::unnecessary_cast)]
+    pub fn expand_virtual_vertex_groups(&self) -> (SolverInitializer, Vec<VertexIndex>) {
+        let mut is_virtual = vec![false; self.vertex_num as usize];
+        for &virtual_vertex in self.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        let mut expanded = self.clone();
+        expanded.virtual_vertex_groups.clear();
+        let mut constraint_defects = vec![];
+        for group in self.virtual_vertex_groups.iter() {
+            let constraint_vertex = expanded.vertex_num;
+            expanded.vertex_num += 1;
+            for &vertex_index in group.vertices.iter() {
+                assert!(
+                    is_virtual[vertex_index as usize],
+                    "vertex {vertex_index} must be virtual and belong to at most one group"
+                );
+                is_virtual[vertex_index as usize] = false;
+                expanded.weighted_edges.push((vertex_index, constraint_vertex, 0));
+            }
+            if group.odd {
+                constraint_defects.push(constraint_vertex);
+            }
+        }
+        expanded
+            .virtual_vertices
+            .retain(|&vertex_index| is_virtual[vertex_index as usize]);
+        (expanded, constraint_defects)
+    }
+
+    /// the distance from each vertex to its nearest virtual vertex, computed by a Dijkstra search from all the virtual
+    /// vertices at once
+    #[allow(clippy::unnecessary_cast)]
+    pub fn boundary_distances(&self) -> BoundaryDistances {
+        let vertex_num = self.vertex_num as usize;
+        let mut neighbors = vec![vec![]; vertex_num];
+        for &(i, j, weight) in self.weighted_edges.iter() {
+            neighbors[i as usize].push((j, weight));
+            neighbors[j as usize].push((i, weight));
+        }
+        let mut distances = vec![Weight::MAX; vertex_num];
+        let mut nearest_virtual = vec![None; vertex_num];
+        let mut queue = std::collections::BinaryHeap::new();
+        for &virtual_vertex in self.virtual_vertices.iter() {
+            distances[virtual_vertex as usize] = 0;
+            nearest_virtual[virtual_vertex as usize] = Some(virtual_vertex);
+            queue.push(std::cmp::Reverse((0, virtual_vertex)));
+        }
+        while let Some(std::cmp::Reverse((distance, vertex))) = queue.pop() {
+            if distance > distances[vertex as usize] {
+                continue;
+            }
+            for &(neighbor, weight) in neighbors[vertex as usize].iter() {
+                if distance + weight < distances[neighbor as usize] {
+                    distances[neighbor as usize] = distance + weight;
+                    nearest_virtual[neighbor as usize] = nearest_virtual[vertex as usize];
+                    queue.push(std::cmp::Reverse((distance + weight, neighbor)));
+                }
+            }
+        }
+        BoundaryDistances {
+            distances,
+            nearest_virtual,
+        }
+    }
 
-/// translate defect vertices into the current new index given reordered_vertices
-#[allow(clippy::unnecessary_cast)]
-pub fn translated_defect_to_reordered(
-    reordered_vertices: &Vec<VertexIndex>,
-    old_defect_vertices: &[VertexIndex],
-) -> Vec<VertexIndex> {
-    let old_to_new = build_old_to_new(reordered_vertices);
-    old_defect_vertices
-        .iter()
-        .map(|old_index| old_to_new[*old_index as usize].unwrap())
-        .collect()
-}
+    /// append another graph after my vertices and edges, shifting its virtual vertices, groups, annotations and fault ids
+    #[allow(clippy::unnecessary_cast)]
+    fn append(&mut self, other: &SolverInitializer) {
+        let vertex_bias = self.vertex_num;
+        let edge_bias = self.weighted_edges.len() as EdgeIndex;
+        self.vertex_num += other.vertex_num;
+        (self.weighted_edges).extend(
+            other
+                .weighted_edges
+                .iter()
+                .map(|&(i, j, weight)| (i + vertex_bias, j + vertex_bias, weight)),
+        );
+        (self.virtual_vertices).extend(other.virtual_vertices.iter().map(|&vertex_index| vertex_index + vertex_bias));
+        self.virtual_vertex_groups
+            .extend(other.virtual_vertex_groups.iter().map(|group| {
+                VirtualVertexGroup {
+                    vertices: group
+                        .vertices
+                        .iter()
+                        .map(|&vertex_index| vertex_index + vertex_bias)
+                        .collect(),
+                    odd: group.odd,
+                }
+            }));
+        (self.vertex_annotations).extend(
+            (other.vertex_annotations.iter())
+                .map(|(&vertex_index, annotation)| (vertex_index + vertex_bias, annotation.clone())),
+        );
+        (self.edge_annotations).extend(
+            (other.edge_annotations.iter()).map(|(&edge_index, annotation)| (edge_index + edge_bias, annotation.clone())),
+        );
+        (self.edge_fault_ids).extend(
+            (other.edge_fault_ids.iter()).map(|(&edge_index, fault_ids)| (edge_index + edge_bias, fault_ids.clone())),
+        );
+        if let Some(other_hint) = other.capacity_hint {
+            let capacity_hint = self.capacity_hint.get_or_insert_with(CapacityHint::default);
+            capacity_hint.max_defect_num += other_hint.max_defect_num;
+            capacity_hint.active_vertex_num += other_hint.active_vertex_num;
+        }
+    }
 
-#[cfg_attr(feature = "python_binding", cfg_eval)]
-#[cfg_attr(feature = "python_binding", pymethods)]
-impl SolverInitializer {
-    #[cfg_attr(feature = "python_binding", new)]
-    pub fn new(
-        vertex_num: VertexNum,
-        weighted_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
-        virtual_vertices: Vec<VertexIndex>,
-    ) -> SolverInitializer {
-        SolverInitializer {
-            vertex_num,
-            weighted_edges,
-            virtual_vertices,
+    /// the graph of two independent patches, where the vertex `v` and the edge `e` of the other graph become
+    /// `self.vertex_num + v` and `self.weighted_edges.len() + e`; the capacity hints are added up
+    pub fn disjoint_union(&self, other: &SolverInitializer) -> SolverInitializer {
+        let mut union = self.clone();
+        union.append(other);
+        union
+    }
+
+    /// the space-time graph of `round_num` rounds of this graph, where the vertex `v` of round `r` becomes
+    /// `r * vertex_num + v` and each real vertex is connected to itself in the next round by a measurement edge; the
+    /// edges of each round are followed by the measurement edges from the previous round, in the order of the vertices
+    #[allow(clippy::unnecessary_cast)]
+    pub fn stack_rounds(&self, round_num: usize, measurement_edge_weight: Weight) -> SolverInitializer {
+        assert!(round_num > 0, "at least one round is required");
+        assert!(
+            measurement_edge_weight >= 0 && measurement_edge_weight % 2 == 0,
+            "measurement edge weight {measurement_edge_weight} should be non-negative and even"
+        );
+        let mut is_virtual = vec![false; self.vertex_num as usize];
+        for &virtual_vertex in self.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        let mut stacked = SolverInitializer::new(0, vec![], vec![]);
+        for round in 0..round_num {
+            stacked.append(self);
+            if round > 0 {
+                let round_bias = (round as VertexNum) * self.vertex_num;
+                for vertex_index in (0..self.vertex_num).filter(|&vertex_index| !is_virtual[vertex_index as usize]) {
+                    stacked.weighted_edges.push((
+                        round_bias - self.vertex_num + vertex_index,
+                        round_bias + vertex_index,
+                        measurement_edge_weight,
+                    ));
+                }
+            }
         }
+        stacked
     }
-    #[cfg(feature = "python_binding")]
-    fn __repr__(&self) -> String {
-        format!("{:?}", self)
+
+    /// check the decoding graph edge by edge like [`SolverInitializerBuilder`], where duplicate edges are rejected
+    pub fn validate(&self) -> Result<InitializerStatistics, InitializerError> {
+        let mut builder = SolverInitializerBuilder::new(self.vertex_num).with_merge_duplicates(false);
+        for &virtual_vertex in self.virtual_vertices.iter() {
+            builder.add_virtual_vertex(virtual_vertex)?;
+        }
+        for &(i, j, weight) in self.weighted_edges.iter() {
+            builder.add_edge(i, j, weight)?;
+        }
+        Ok(builder.statistics())
     }
-}
 
-impl SolverInitializer {
     #[allow(clippy::unnecessary_cast)]
     pub fn syndrome_of(&self, subgraph: &[EdgeIndex]) -> BTreeSet<VertexIndex> {
         let mut defects = BTreeSet::new();
@@ -666,9 +2450,326 @@ impl SolverInitializer {
     }
 }
 
+/// the reason of rejecting a decoding graph, see [`SolverInitializerBuilder`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum InitializerError {
+    VertexOutOfRange {
+        vertex_index: VertexIndex,
+        vertex_num: VertexNum,
+    },
+    SelfLoop {
+        vertex_index: VertexIndex,
+    },
+    /// the negative weights can be absorbed by [`NegativeWeightTransform`] before building the graph
+    NegativeWeight {
+        left: VertexIndex,
+        right: VertexIndex,
+        weight: Weight,
+    },
+    OddWeight {
+        left: VertexIndex,
+        right: VertexIndex,
+        weight: Weight,
+    },
+    DuplicateEdge {
+        left: VertexIndex,
+        right: VertexIndex,
+    },
+    /// a connected component without any virtual vertex, given by its smallest vertex
+    NoBoundary {
+        vertex_index: VertexIndex,
+    },
+}
+
+impl std::fmt::Display for InitializerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VertexOutOfRange {
+                vertex_index,
+                vertex_num,
+            } => {
+                write!(f, "vertex {vertex_index} out of range, there are only {vertex_num} vertices")
+            }
+            Self::SelfLoop { vertex_index } => write!(f, "invalid edge from and to the same vertex {vertex_index}"),
+            Self::NegativeWeight { left, right, weight } => {
+                write!(f, "edge ({left}, {right}) has negative weight {weight}")
+            }
+            Self::OddWeight { left, right, weight } => {
+                write!(f, "edge ({left}, {right}) has odd weight {weight}; weight should be even")
+            }
+            Self::DuplicateEdge { left, right } => write!(f, "edge ({left}, {right}) is duplicated"),
+            Self::NoBoundary { vertex_index } => {
+                write!(
+                    f,
+                    "the component of vertex {vertex_index} is not connected to any virtual vertex"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for InitializerError {}
+
+/// the statistics of a decoding graph, see [`SolverInitializerBuilder::statistics`]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct InitializerStatistics {
+    pub vertex_num: VertexNum,
+    pub virtual_vertex_num: usize,
+    pub edge_num: usize,
+    /// the number of duplicate edges merged into an existing edge
+    pub merged_edge_num: usize,
+    /// the number of real vertices without any edge
+    pub isolated_vertex_num: usize,
+    pub max_degree: usize,
+    pub min_weight: Option<Weight>,
+    pub max_weight: Option<Weight>,
+    /// the number of connected components, including the isolated vertices
+    pub component_num: usize,
+    /// the smallest vertex of each component without any virtual vertex, where an odd number of defects can't be matched
+    pub components_without_boundary: Vec<VertexIndex>,
+}
+
+/// builds a [`SolverInitializer`] edge by edge, checking each edge on insertion instead of panicking deep inside the dual
+/// module on a malformed decoding graph
+#[derive(Debug, Clone)]
+pub struct SolverInitializerBuilder {
+    pub vertex_num: VertexNum,
+    weighted_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+    is_virtual: Vec<bool>,
+    /// the edge index of each pair of vertices `(min, max)`
+    edge_indices: HashMap<(VertexIndex, VertexIndex), EdgeIndex>,
+    merged_edge_num: usize,
+    /// merge a duplicate edge into the existing one by keeping the smaller weight, instead of rejecting it
+    pub merge_duplicates: bool,
+    /// reject the graph in [`Self::build`] if any component has no virtual vertex
+    pub require_boundary: bool,
+}
+
+impl SolverInitializerBuilder {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(vertex_num: VertexNum) -> Self {
+        Self {
+            vertex_num,
+            weighted_edges: vec![],
+            is_virtual: vec![false; vertex_num as usize],
+            edge_indices: HashMap::new(),
+            merged_edge_num: 0,
+            merge_duplicates: true,
+            require_boundary: false,
+        }
+    }
+
+    pub fn with_merge_duplicates(mut self, merge_duplicates: bool) -> Self {
+        self.merge_duplicates = merge_duplicates;
+        self
+    }
+
+    pub fn with_require_boundary(mut self, require_boundary: bool) -> Self {
+        self.require_boundary = require_boundary;
+        self
+    }
+
+    fn check_vertex(&self, vertex_index: VertexIndex) -> Result<(), InitializerError> {
+        if vertex_index >= self.vertex_num {
+            return Err(InitializerError::VertexOutOfRange {
+                vertex_index,
+                vertex_num: self.vertex_num,
+            });
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn add_virtual_vertex(&mut self, vertex_index: VertexIndex) -> Result<(), InitializerError> {
+        self.check_vertex(vertex_index)?;
+        self.is_virtual[vertex_index as usize] = true;
+        Ok(())
+    }
+
+    /// returns the index of the edge, which is the existing one if the edge is merged
+    #[allow(clippy::unnecessary_cast)]
+    pub fn add_edge(&mut self, i: VertexIndex, j: VertexIndex, weight: Weight) -> Result<EdgeIndex, InitializerError> {
+        self.check_vertex(i)?;
+        self.check_vertex(j)?;
+        if i == j {
+            return Err(InitializerError::SelfLoop { vertex_index: i });
+        }
+        if weight < 0 {
+            return Err(InitializerError::NegativeWeight {
+                left: i,
+                right: j,
+                weight,
+            });
+        }
+        if weight % 2 != 0 {
+            return Err(InitializerError::OddWeight {
+                left: i,
+                right: j,
+                weight,
+            });
+        }
+        let key = (VertexIndex::min(i, j), VertexIndex::max(i, j));
+        if let Some(&edge_index) = self.edge_indices.get(&key) {
+            if !self.merge_duplicates {
+                return Err(InitializerError::DuplicateEdge { left: i, right: j });
+            }
+            let existing_weight = &mut self.weighted_edges[edge_index as usize].2;
+            *existing_weight = Weight::min(*existing_weight, weight);
+            self.merged_edge_num += 1;
+            return Ok(edge_index);
+        }
+        let edge_index = self.weighted_edges.len() as EdgeIndex;
+        self.weighted_edges.push((i, j, weight));
+        self.edge_indices.insert(key, edge_index);
+        Ok(edge_index)
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn statistics(&self) -> InitializerStatistics {
+        let vertex_num = self.vertex_num as usize;
+        let mut adjacency = vec![vec![]; vertex_num];
+        for &(i, j, _) in self.weighted_edges.iter() {
+            adjacency[i as usize].push(j);
+            adjacency[j as usize].push(i);
+        }
+        let mut statistics = InitializerStatistics {
+            vertex_num: self.vertex_num,
+            virtual_vertex_num: self.is_virtual.iter().filter(|&&is_virtual| is_virtual).count(),
+            edge_num: self.weighted_edges.len(),
+            merged_edge_num: self.merged_edge_num,
+            max_degree: adjacency.iter().map(Vec::len).max().unwrap_or(0),
+            min_weight: self.weighted_edges.iter().map(|&(_, _, weight)| weight).min(),
+            max_weight: self.weighted_edges.iter().map(|&(_, _, weight)| weight).max(),
+            ..Default::default()
+        };
+        let mut visited = vec![false; vertex_num];
+        for start in 0..vertex_num {
+            if visited[start] {
+                continue;
+            }
+            if adjacency[start].is_empty() && !self.is_virtual[start] {
+                statistics.isolated_vertex_num += 1;
+            }
+            statistics.component_num += 1;
+            visited[start] = true;
+            let mut has_boundary = false;
+            let mut stack = vec![start as VertexIndex];
+            while let Some(vertex_index) = stack.pop() {
+                has_boundary |= self.is_virtual[vertex_index as usize];
+                for &peer in adjacency[vertex_index as usize].iter() {
+                    if !visited[peer as usize] {
+                        visited[peer as usize] = true;
+                        stack.push(peer);
+                    }
+                }
+            }
+            if !has_boundary {
+                statistics.components_without_boundary.push(start as VertexIndex);
+            }
+        }
+        statistics
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn build(self) -> Result<SolverInitializer, InitializerError> {
+        if self.require_boundary {
+            if let Some(&vertex_index) = self.statistics().components_without_boundary.first() {
+                return Err(InitializerError::NoBoundary { vertex_index });
+            }
+        }
+        let virtual_vertices = (0..self.vertex_num)
+            .filter(|&vertex_index| self.is_virtual[vertex_index as usize])
+            .collect();
+        Ok(SolverInitializer::new(self.vertex_num, self.weighted_edges, virtual_vertices))
+    }
+}
+
+/// the index of a circuit fault location, see [`SolverInitializer::edge_fault_ids`]
+pub type FaultId = usize;
+
+/// the faults that appear an odd number of times along the subgraph in increasing order, i.e. the XOR of the fault ids
+/// of its edges, so that a fault shared by two edges of the correction cancels out just like the Pauli frame
+pub fn subgraph_fault_ids(edge_fault_ids: &BTreeMap<EdgeIndex, Vec<FaultId>>, subgraph: &[EdgeIndex]) -> Vec<FaultId> {
+    let mut fault_ids = BTreeSet::new();
+    for fault_id in subgraph
+        .iter()
+        .filter_map(|edge_index| edge_fault_ids.get(edge_index))
+        .flatten()
+    {
+        if !fault_ids.remove(fault_id) {
+            fault_ids.insert(*fault_id);
+        }
+    }
+    fault_ids.into_iter().collect()
+}
+
 /// timestamp type determines how many fast clear before a hard clear is required, see [`FastClear`]
 pub type FastClearTimestamp = usize;
 
+/// the estimated memory usage in bytes of a solver or a module, see e.g. [`crate::mwpm_solver::PrimalDualSolver::memory_usage`];
+/// allocations are counted by their capacity, and the partitioned modules report each unit in `units` as well
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MemoryReport {
+    pub vertices: usize,
+    pub edges: usize,
+    /// the dual nodes in the interfaces and the dual modules
+    pub dual_nodes: usize,
+    pub primal_nodes: usize,
+    /// the node indices, queues and temporary buffers
+    pub auxiliary: usize,
+    /// the report of each partition unit, indexed by the unit index; empty if not partitioned
+    pub units: Vec<MemoryReport>,
+}
+
+impl MemoryReport {
+    /// the size of an `Arc<RwLock<T>>` allocation, which holds two reference counters and a lock besides the object
+    pub fn shared<T>() -> usize {
+        std::mem::size_of::<T>() + 3 * std::mem::size_of::<usize>()
+    }
+
+    /// the size of the allocation of a vector
+    #[allow(clippy::ptr_arg)]
+    pub fn vec<T>(vector: &Vec<T>) -> usize {
+        vector.capacity() * std::mem::size_of::<T>()
+    }
+
+    pub fn total(&self) -> usize {
+        self.vertices + self.edges + self.dual_nodes + self.primal_nodes + self.auxiliary
+    }
+
+    /// a report of the whole module that keeps the reports of its units
+    pub fn from_units(units: Vec<MemoryReport>) -> Self {
+        let mut report = Self::default();
+        for unit in units.iter() {
+            report.vertices += unit.vertices;
+            report.edges += unit.edges;
+            report.dual_nodes += unit.dual_nodes;
+            report.primal_nodes += unit.primal_nodes;
+            report.auxiliary += unit.auxiliary;
+        }
+        report.units = units;
+        report
+    }
+}
+
+impl std::ops::AddAssign for MemoryReport {
+    /// the units are added one by one, e.g. to combine the dual and primal reports of the same partition
+    fn add_assign(&mut self, other: Self) {
+        self.vertices += other.vertices;
+        self.edges += other.edges;
+        self.dual_nodes += other.dual_nodes;
+        self.primal_nodes += other.primal_nodes;
+        self.auxiliary += other.auxiliary;
+        for (unit_index, unit) in other.units.into_iter().enumerate() {
+            if unit_index < self.units.len() {
+                self.units[unit_index] += unit;
+            } else {
+                self.units.push(unit);
+            }
+        }
+    }
+}
+
 #[allow(dead_code)]
 /// use Xoshiro256StarStar for deterministic random number generator
 pub type DeterministicRng = rand_xoshiro::Xoshiro256StarStar;
@@ -1006,4 +3107,724 @@ pub mod tests {
             assert_eq!(owned_partitioned.whole_defect_range, expected_defect_range);
         }
     }
+
+    #[test]
+    fn util_b8_shots() {
+        // cargo test util_b8_shots -- --nocapture
+        let shots = vec![vec![], vec![0, 7, 8], vec![9]];
+        let bytes = write_b8_shots(&shots, 10);
+        assert_eq!(bytes, vec![0, 0, 0b10000001, 0b1, 0, 0b10]);
+        assert_eq!(read_b8_shots(&bytes, 10).unwrap(), shots);
+        assert!(read_b8_shots(&bytes[..5], 10).unwrap_err().contains("not a multiple"));
+        assert!(read_b8_shots(&[0, 0b100], 10).unwrap_err().contains("padding bit 10"));
+    }
+
+    #[test]
+    fn util_partition_config_file() {
+        // cargo test util_partition_config_file -- --nocapture
+        let folder = std::env::temp_dir();
+        let mut config = PartitionConfig::new(10);
+        config.partitions = vec![VertexRange::new(0, 3), VertexRange::new(4, 7), VertexRange::new(8, 10)];
+        config.fusions = vec![(0, 1), (3, 2)];
+        config.sanity_check().unwrap();
+        let reordered_vertices: Vec<VertexIndex> = (0..10).rev().collect();
+        for extension in ["json", "toml"] {
+            let filepath = format!("{}/util_partition_config_file.{extension}", folder.display());
+            config.to_file(&filepath, Some(reordered_vertices.clone())).unwrap();
+            let (loaded, loaded_reordered_vertices) = PartitionConfig::from_file(&filepath).unwrap();
+            assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&config).unwrap());
+            assert_eq!(loaded_reordered_vertices, Some(reordered_vertices.clone()));
+            std::fs::remove_file(&filepath).unwrap();
+        }
+        let toml_filepath = format!("{}/util_partition_config_file_handwritten.toml", folder.display());
+        std::fs::write(
+            &toml_filepath,
+            "vertex_num = 10\npartitions = [[0, 4], [5, 10]]\nfusions = [[0, 1]]\n",
+        )
+        .unwrap();
+        let (loaded, loaded_reordered_vertices) = PartitionConfig::from_file(&toml_filepath).unwrap();
+        assert_eq!(loaded.partitions, vec![VertexRange::new(0, 4), VertexRange::new(5, 10)]);
+        assert_eq!(loaded_reordered_vertices, None);
+        std::fs::remove_file(&toml_filepath).unwrap();
+        // invalid configurations
+        let check = |partitions: Vec<VertexRange>, fusions: Vec<(usize, usize)>| {
+            let mut config = PartitionConfig::new(10);
+            config.partitions = partitions;
+            config.fusions = fusions;
+            config.sanity_check().unwrap_err()
+        };
+        assert!(check(vec![VertexRange::new(0, 5), VertexRange::new(4, 10)], vec![(0, 1)]).contains("overlap"));
+        assert!(check(vec![VertexRange::new(0, 4), VertexRange::new(5, 10)], vec![(1, 0)]).contains("lower range"));
+        assert!(check(vec![VertexRange::new(0, 4), VertexRange::new(5, 10)], vec![]).contains("without being fused"));
+        assert!(check(vec![VertexRange::new(0, 4), VertexRange::new(5, 9)], vec![(0, 1)]).contains("not covering"));
+        assert!(check(vec![VertexRange::new(0, 4), VertexRange::new(5, 11)], vec![(0, 1)]).contains("invalid range"));
+        let mut config = PartitionConfig::new(10);
+        config.partitions = vec![VertexRange::new(0, 4), VertexRange::new(5, 10)];
+        config.fusions = vec![(0, 1), (2, 3)];
+        assert!(config.sanity_check().unwrap_err().contains("dependency wrong"));
+        assert!(check_reordered_vertices(3, &[0, 1, 1]).unwrap_err().contains("duplicated"));
+    }
+
+    #[test]
+    fn util_external_partition_auto() {
+        // cargo test util_external_partition_auto -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(7, 6, 0.02, 500);
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for partition_num in [1, 2, 3, 4] {
+            let partition = ExternalPartition::auto(&initializer, partition_num);
+            assert_eq!(partition.partitions.len(), partition_num);
+            partition.sanity_check(&initializer).unwrap();
+            let (reordered_vertices, reordered_initializer, config) = partition.build(&initializer).unwrap();
+            let mut parallel_solver = SolverParallel::new(&reordered_initializer, &config.info(), json!({}));
+            for seed in 0..20 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                serial_solver.solve(&syndrome_pattern);
+                let mut defect_vertices =
+                    translated_defect_to_reordered(&reordered_vertices, &syndrome_pattern.defect_vertices);
+                defect_vertices.sort();
+                parallel_solver.solve(&SyndromePattern::new_vertices(defect_vertices));
+                assert_eq!(serial_solver.sum_dual_variables(), parallel_solver.sum_dual_variables());
+                serial_solver.clear();
+                parallel_solver.clear();
+            }
+        }
+        // a disconnected graph is layered component by component
+        let initializer = SolverInitializer::new(6, vec![(0, 1, 2), (1, 2, 2), (3, 4, 2), (4, 5, 2)], vec![]);
+        let partition = ExternalPartition::auto(&initializer, 2);
+        partition.sanity_check(&initializer).unwrap();
+        assert_eq!(partition.interfaces.len(), 1);
+    }
+
+    #[test]
+    fn util_external_partition() {
+        // cargo test util_external_partition -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let d = 7;
+        let mut code = CodeCapacityPlanarCode::new(d, 0.1, 500);
+        let initializer = code.get_initializer();
+        // split by rows, listing the bottom half first and the vertices out of order
+        let row = |r: VertexIndex| (0..d + 1).rev().map(move |c| r * (d + 1) + c);
+        let top: Vec<VertexIndex> = (0..3).flat_map(row).collect();
+        let bottom: Vec<VertexIndex> = (4..d).rev().flat_map(row).collect();
+        let interface: Vec<VertexIndex> = row(3).collect();
+        let partition = ExternalPartition::new(initializer.vertex_num, vec![bottom, top], vec![interface], vec![(0, 1)]);
+        let (reordered_vertices, reordered_initializer, config) = partition.build(&initializer).unwrap();
+        let partition_info = config.info();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut parallel_solver = SolverParallel::new(&reordered_initializer, &partition_info, json!({}));
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            let mut defect_vertices = translated_defect_to_reordered(&reordered_vertices, &syndrome_pattern.defect_vertices);
+            defect_vertices.sort();
+            parallel_solver.solve(&SyndromePattern::new_vertices(defect_vertices));
+            assert_eq!(serial_solver.sum_dual_variables(), parallel_solver.sum_dual_variables());
+            serial_solver.clear();
+            parallel_solver.clear();
+        }
+        // a vertex of the interface moved into a leaf connects the two leaves directly
+        let mut partition = partition;
+        let vertex_index = partition.interfaces[0].pop().unwrap();
+        partition.partitions[1].push(vertex_index);
+        let error = partition.sanity_check(&initializer).unwrap_err();
+        println!("{error}");
+        assert!(error.contains("must be separated by an interface"));
+        partition.partitions[1].pop();
+        assert!(partition
+            .sanity_check(&initializer)
+            .unwrap_err()
+            .contains("not owned by any unit"));
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_negative_weight_transform() {
+        // cargo test util_negative_weight_transform -- --nocapture
+        use crate::mwpm_solver::*;
+        // 0 (virtual) - 1 - 2 - 3 - 4 (virtual), with two shortcuts
+        let weighted_edges = vec![(0, 1, 100), (1, 2, -40), (2, 3, 60), (3, 4, 100), (1, 3, -20), (2, 4, 80)];
+        let initializer = SolverInitializer::new(5, weighted_edges.clone(), vec![0, 4]);
+        let transform = NegativeWeightTransform::new(&initializer);
+        assert_eq!(transform.negative_edges, vec![1, 4]);
+        assert_eq!(transform.flipped_vertices, vec![2, 3]);
+        assert_eq!(transform.offset, -60);
+        transform.initializer.validate().unwrap();
+        let mut solver = SolverSerial::new(&transform.initializer);
+        for syndrome_bits in 0..8 {
+            let defect_vertices: Vec<VertexIndex> =
+                (1..4).filter(|vertex| syndrome_bits & (1 << (vertex - 1)) != 0).collect();
+            let expected_defects: BTreeSet<VertexIndex> = defect_vertices.iter().cloned().collect();
+            // the minimum weight among all the corrections, by brute force
+            let subgraph_weight =
+                |subgraph: &[EdgeIndex]| subgraph.iter().map(|&edge| weighted_edges[edge as usize].2).sum::<Weight>();
+            let expected_weight = (0..1 << weighted_edges.len())
+                .map(|bits: usize| {
+                    (0..weighted_edges.len())
+                        .filter(|edge| bits & (1 << edge) != 0)
+                        .map(|edge| edge as EdgeIndex)
+                        .collect::<Vec<_>>()
+                })
+                .filter(|subgraph| initializer.syndrome_of(subgraph) == expected_defects)
+                .map(|subgraph| subgraph_weight(&subgraph))
+                .min()
+                .unwrap();
+            let syndrome_pattern = SyndromePattern::new_vertices(defect_vertices);
+            solver.solve(&transform.translate_syndrome(&syndrome_pattern));
+            assert_eq!(
+                solver.sum_dual_variables() + transform.offset,
+                expected_weight,
+                "{syndrome_bits}"
+            );
+            let subgraph = transform.translate_subgraph(&solver.subgraph());
+            assert_eq!(initializer.syndrome_of(&subgraph), expected_defects);
+            assert_eq!(subgraph_weight(&subgraph), expected_weight);
+            solver.clear();
+        }
+        // negative dynamic weights are rejected instead of being mis-decoded
+        let syndrome_pattern = SyndromePattern::new_dynamic_weights(vec![1], vec![], vec![(2, -10)]);
+        let error = SyndromeValidator::new(&transform.initializer)
+            .validate(&syndrome_pattern)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            SyndromeError::NegativeDynamicWeight {
+                edge_index: 2,
+                weight: -10
+            }
+        );
+        assert_eq!(
+            solver.solve_checked(&syndrome_pattern, &SyndromeValidator::new(&transform.initializer)),
+            Err(error.into())
+        );
+        let mut dual_module = crate::dual_module_serial::DualModuleSerial::new_empty(&transform.initializer);
+        let interface_ptr = crate::dual_module::DualModuleInterfacePtr::new_empty();
+        assert!(interface_ptr.try_load(&syndrome_pattern, &mut dual_module).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_zero_weight_contraction() {
+        // cargo test util_zero_weight_contraction -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let mut code = CodeCapacityPlanarCode::new(11, 0.05, 500);
+        code.set_erasure_probability(0.2);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let expected_weight = solver.sum_dual_variables();
+            solver.clear();
+            let contraction = ZeroWeightContraction::new(&initializer, &syndrome_pattern.erasures);
+            assert!(contraction.initializer.vertex_num <= initializer.vertex_num);
+            let mut contracted_solver = SolverSerial::new(&contraction.initializer);
+            contracted_solver.solve(&contraction.translate_syndrome(&syndrome_pattern));
+            assert_eq!(contracted_solver.sum_dual_variables(), expected_weight, "seed {seed}");
+            let subgraph = contraction.expand_subgraph(&syndrome_pattern.defect_vertices, &contracted_solver.subgraph());
+            // the expanded subgraph has the same weight and generates the same defects on the original graph
+            let erasures: BTreeSet<EdgeIndex> = syndrome_pattern.erasures.iter().cloned().collect();
+            let mut weight = 0;
+            let mut odd = vec![false; initializer.vertex_num as usize];
+            for &edge_index in subgraph.iter() {
+                let (i, j, edge_weight) = initializer.weighted_edges[edge_index as usize];
+                if !erasures.contains(&edge_index) {
+                    weight += edge_weight;
+                }
+                odd[i as usize] ^= true;
+                odd[j as usize] ^= true;
+            }
+            assert_eq!(weight, expected_weight, "seed {seed}");
+            for &virtual_vertex in initializer.virtual_vertices.iter() {
+                odd[virtual_vertex as usize] = false;
+            }
+            let defect_vertices: Vec<VertexIndex> =
+                (0..initializer.vertex_num).filter(|&vertex| odd[vertex as usize]).collect();
+            let mut expected_defect_vertices = syndrome_pattern.defect_vertices.clone();
+            expected_defect_vertices.sort();
+            assert_eq!(defect_vertices, expected_defect_vertices, "seed {seed}");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_erasure_peeling() {
+        // cargo test util_erasure_peeling -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let mut code = CodeCapacityPlanarCode::new(11, 0.01, 500);
+        code.set_erasure_probability(0.3);
+        let initializer = code.get_initializer();
+        let erasure_peeler = std::sync::Arc::new(ErasurePeeler::new(&initializer));
+        let mut solver = SolverSerial::new(&initializer);
+        let mut peeling_solver = SolverSerial::new(&initializer);
+        peeling_solver.subgraph_builder.use_erasure_peeling(erasure_peeler.clone());
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            peeling_solver.solve(&syndrome_pattern);
+            let subgraph = peeling_solver.subgraph();
+            // the peeled correction is still a minimum-weight correction, and its erased edges form a forest
+            let erasures: BTreeSet<EdgeIndex> = syndrome_pattern.erasures.iter().cloned().collect();
+            let weight: Weight = (subgraph.iter())
+                .filter(|edge_index| !erasures.contains(edge_index))
+                .map(|&edge_index| initializer.weighted_edges[edge_index as usize].2)
+                .sum();
+            assert_eq!(weight, solver.sum_dual_variables(), "seed {seed}");
+            let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(initializer.syndrome_of(&subgraph), defect_vertices, "seed {seed}");
+            let erased_subgraph: Vec<EdgeIndex> = subgraph
+                .iter()
+                .filter(|edge_index| erasures.contains(edge_index))
+                .cloned()
+                .collect();
+            let erased_defects: Vec<VertexIndex> = initializer.syndrome_of(&erased_subgraph).into_iter().collect();
+            assert_eq!(
+                erasure_peeler.peel(&syndrome_pattern.erasures, &erased_defects).unwrap(),
+                erased_subgraph
+            );
+            solver.clear();
+            peeling_solver.clear();
+        }
+        // a pure erasure shot is decoded by peeling alone
+        let syndrome_pattern = code.generate_random_errors(0);
+        let erasures = &syndrome_pattern.erasures;
+        let errors: Vec<EdgeIndex> = erasures.iter().step_by(2).cloned().collect();
+        let defect_vertices: Vec<VertexIndex> = initializer.syndrome_of(&errors).into_iter().collect();
+        let correction = erasure_peeler.peel(erasures, &defect_vertices).unwrap();
+        assert!(correction.iter().all(|edge_index| erasures.contains(edge_index)));
+        assert_eq!(
+            initializer.syndrome_of(&correction),
+            defect_vertices.iter().cloned().collect()
+        );
+        // an odd parity without boundary cannot be peeled
+        let (i, j, _) = initializer.weighted_edges[0];
+        let (left, right) = if initializer.virtual_vertices.contains(&i) {
+            (j, i)
+        } else {
+            (i, j)
+        };
+        assert!(!initializer.virtual_vertices.contains(&left));
+        assert!(erasure_peeler.peel(&[], &[left]).is_err());
+        assert_eq!(
+            erasure_peeler.peel(&[0], &[left]).is_ok(),
+            initializer.virtual_vertices.contains(&right)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_soft_info_adapter() {
+        // cargo test util_soft_info_adapter -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let (d, noisy_measurements, p) = (5, 4, 0.05);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, p, 500);
+        let initializer = code.get_initializer();
+        let vertex_num_per_round = initializer.vertex_num / (noisy_measurements + 1);
+        let adapter = SoftInfoAdapter::new(&initializer, vertex_num_per_round, p);
+        // the nominal confidence reproduces the original weights
+        let nominal_confidences = vec![vec![1. - p; vertex_num_per_round as usize]; noisy_measurements as usize];
+        let edge_modifier = adapter.edge_modifier(&nominal_confidences);
+        assert_eq!(edge_modifier.len(), (noisy_measurements * d * (d - 1)) as usize);
+        for &(edge_index, weight) in edge_modifier.iter() {
+            assert_eq!(weight, initializer.weighted_edges[edge_index as usize].2);
+        }
+        assert_eq!(adapter.weight_of_confidence(0.5), 0);
+        // the adjusted weights give the same result as a decoding graph built with those weights
+        use crate::rand_xoshiro::rand_core::SeedableRng;
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..20 {
+            let confidences: Vec<Vec<f64>> = (0..noisy_measurements)
+                .map(|_| (0..vertex_num_per_round).map(|_| 0.5 + 0.5 * rng.next_f64()).collect())
+                .collect();
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&adapter.syndrome_pattern(syndrome_pattern.defect_vertices.clone(), &confidences));
+            let mut adjusted_initializer = initializer.clone();
+            for (edge_index, weight) in adapter.edge_modifier(&confidences) {
+                adjusted_initializer.weighted_edges[edge_index as usize].2 = weight;
+            }
+            let mut adjusted_solver = SolverSerial::new(&adjusted_initializer);
+            adjusted_solver.solve(&syndrome_pattern);
+            assert_eq!(
+                solver.sum_dual_variables(),
+                adjusted_solver.sum_dual_variables(),
+                "seed {seed}"
+            );
+            solver.clear();
+        }
+    }
+
+    #[test]
+    fn util_syndrome_validator() {
+        // cargo test util_syndrome_validator -- --nocapture
+        use crate::dual_module::DualModuleInterfacePtr;
+        use crate::dual_module_serial::DualModuleSerial;
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let validator = SyndromeValidator::new(&initializer);
+        let (vertex_num, edge_num) = (initializer.vertex_num, initializer.weighted_edges.len());
+        let virtual_vertex = initializer.virtual_vertices[0];
+        let check = |syndrome_pattern: SyndromePattern| validator.validate(&syndrome_pattern).map(|_| ()).unwrap_err();
+        assert_eq!(
+            check(SyndromePattern::new_vertices(vec![1, vertex_num])),
+            SyndromeError::DefectOutOfRange {
+                vertex_index: vertex_num,
+                vertex_num
+            }
+        );
+        assert_eq!(
+            check(SyndromePattern::new_vertices(vec![1, 2, 1])),
+            SyndromeError::DuplicateDefect { vertex_index: 1 }
+        );
+        assert_eq!(
+            check(SyndromePattern::new_vertices(vec![virtual_vertex])),
+            SyndromeError::VirtualDefect {
+                vertex_index: virtual_vertex
+            }
+        );
+        assert_eq!(
+            check(SyndromePattern::new(vec![], vec![edge_num as EdgeIndex])),
+            SyndromeError::ErasureOutOfRange {
+                edge_index: edge_num as EdgeIndex,
+                edge_num
+            }
+        );
+        assert_eq!(
+            check(SyndromePattern::new_dynamic_weights(
+                vec![],
+                vec![],
+                vec![(edge_num as EdgeIndex, 0)]
+            )),
+            SyndromeError::DynamicWeightOutOfRange {
+                edge_index: edge_num as EdgeIndex,
+                edge_num
+            }
+        );
+        assert_eq!(
+            check(SyndromePattern::new_dynamic_weights(vec![], vec![0], vec![(1, 0)])),
+            SyndromeError::ErasuresWithDynamicWeights
+        );
+        assert!(SyndromePattern::new_vertices(vec![virtual_vertex])
+            .validate(&initializer)
+            .unwrap_err()
+            .to_string()
+            .contains("virtual"));
+        // nothing is loaded if the syndrome is invalid
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        let invalid = SyndromePattern::new_vertices(vec![1, vertex_num]);
+        assert!(interface_ptr.load_checked(&invalid, &validator, &mut dual_module).is_err());
+        assert_eq!(interface_ptr.read_recursive().nodes_length, 0);
+        // the duplicates are removed if enabled
+        let deduplicate_validator = validator.clone().with_auto_deduplicate(true);
+        let duplicated = SyndromePattern::new_vertices(vec![18, 19, 18, 26]);
+        assert_eq!(
+            deduplicate_validator.validate(&duplicated).unwrap().defect_vertices,
+            vec![18, 19, 26]
+        );
+        interface_ptr
+            .load_checked(&duplicated, &deduplicate_validator, &mut dual_module)
+            .unwrap();
+        assert_eq!(interface_ptr.read_recursive().nodes_length, 3);
+        let mut solver = SolverSerial::new(&initializer);
+        assert!(solver.solve_checked(&duplicated, &validator).is_err());
+        solver.solve_checked(&duplicated, &deduplicate_validator).unwrap();
+        let mut expected_solver = SolverSerial::new(&initializer);
+        expected_solver.solve(&SyndromePattern::new_vertices(vec![18, 19, 26]));
+        assert_eq!(solver.sum_dual_variables(), expected_solver.sum_dual_variables());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_decoding_graph() {
+        // cargo test util_decoding_graph -- --nocapture
+        use crate::complete_graph::ShortestPathTrees;
+        use crate::example_codes::*;
+        let code = PhenomenologicalPlanarCode::new(5, 3, 0.1, 500);
+        let initializer = code.get_initializer();
+        let graph = DecodingGraph::new(&initializer);
+        let trees = ShortestPathTrees::new(&initializer);
+        for source in [0, 7, 33, initializer.virtual_vertices[0]] {
+            let shortest_paths = graph.dijkstra(source);
+            for vertex_index in 0..initializer.vertex_num as VertexIndex {
+                if vertex_index == source {
+                    assert_eq!(shortest_paths.distance(vertex_index), Some(0));
+                    continue;
+                }
+                assert_eq!(
+                    shortest_paths.distance(vertex_index),
+                    trees.get_distance(source, vertex_index)
+                );
+                let (edges, weight) = graph.shortest_path(source, vertex_index).unwrap();
+                assert_eq!(weight, trees.get_distance(source, vertex_index).unwrap());
+                assert_eq!(edges.iter().map(|&e| graph.edge_weight(e)).sum::<Weight>(), weight);
+            }
+            // the vertices are visited in non-decreasing order of distance
+            let max_distance = 1000;
+            let within = graph.vertices_within(source, max_distance);
+            assert!(within.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+            let expected_num = (0..initializer.vertex_num as VertexIndex)
+                .filter(|&v| shortest_paths.distance(v).unwrap() <= max_distance)
+                .count();
+            assert_eq!(within.len(), expected_num);
+        }
+        // neighborhood queries
+        for &(peer, edge_index) in graph.neighbors(7) {
+            assert!(graph.neighbors(peer).contains(&(7, edge_index)));
+        }
+        assert_eq!(graph.neighborhood(7, 0), vec![7]);
+        assert_eq!(graph.neighborhood(7, 1).len(), graph.neighbors(7).len() + 1);
+        assert_eq!(
+            graph.neighborhood(7, usize::MAX).len(),
+            initializer.vertex_num as usize,
+            "the graph is connected"
+        );
+        let (virtual_vertex, weight) = graph.nearest_virtual_vertex(7).unwrap();
+        assert!(graph.is_virtual(virtual_vertex));
+        let expected_weight = initializer
+            .virtual_vertices
+            .iter()
+            .map(|&v| trees.get_distance(7, v).unwrap())
+            .min()
+            .unwrap();
+        assert_eq!(weight, expected_weight);
+    }
+
+    #[test]
+    fn util_fusion_error() {
+        // cargo test util_fusion_error -- --nocapture
+        use crate::dual_module::*;
+        use crate::dual_module_serial::DualModuleSerial;
+        use crate::example_codes::*;
+        /// a dual module without the optional edge modifier support
+        struct DualModuleWithoutModifier(DualModuleSerial);
+        impl DualModuleImpl for DualModuleWithoutModifier {
+            fn new_empty(initializer: &SolverInitializer) -> Self {
+                Self(DualModuleSerial::new_empty(initializer))
+            }
+            fn clear(&mut self) {
+                self.0.clear()
+            }
+            fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+                self.0.add_dual_node(dual_node_ptr)
+            }
+            fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+                self.0.remove_blossom(dual_node_ptr)
+            }
+            fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+                self.0.set_grow_state(dual_node_ptr, grow_state)
+            }
+            fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+                self.0.compute_maximum_update_length()
+            }
+            fn grow(&mut self, length: Weight) {
+                self.0.grow(length)
+            }
+        }
+        let code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let validator = SyndromeValidator::new(&initializer);
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        let mut dual_module = DualModuleWithoutModifier::new_empty(&initializer);
+        let error = interface_ptr
+            .load_checked(&SyndromePattern::new(vec![18, 19], vec![0]), &validator, &mut dual_module)
+            .unwrap_err();
+        assert_eq!(error, FusionError::UnsupportedOperation("load_edge_modifier"));
+        assert!(error.to_string().contains("doesn't support load_edge_modifier"));
+        assert_eq!(interface_ptr.read_recursive().nodes_length, 0, "no defect is loaded");
+        interface_ptr.load(&SyndromePattern::new_vertices(vec![18]), &mut dual_module);
+        assert!(dual_module
+            .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), 1)
+            .is_err());
+        assert!(dual_module.get_edge_growths(&[0, 1]).is_err());
+        // the syndrome is checked even without a validator
+        interface_ptr.clear();
+        dual_module.clear();
+        let error = interface_ptr
+            .try_load(
+                &SyndromePattern::new_dynamic_weights(vec![18], vec![0], vec![(1, 0)]),
+                &mut dual_module,
+            )
+            .unwrap_err();
+        assert_eq!(error, SyndromeError::ErasuresWithDynamicWeights.into());
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!(interface_ptr.read_recursive().nodes_length, 0);
+        // growing into a conflict is reported instead of panicking
+        interface_ptr.load(&SyndromePattern::new_vertices(vec![18, 19]), &mut dual_module);
+        let max_weight = initializer.weighted_edges.iter().map(|&(_, _, weight)| weight).max().unwrap();
+        let error = interface_ptr.grow_iterative(4 * max_weight, &mut dual_module).unwrap_err();
+        assert!(matches!(error, FusionError::UnresolvedConflict(_)), "{error}");
+    }
+
+    #[test]
+    fn util_subgraph_fault_ids() {
+        // cargo test util_subgraph_fault_ids -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let code = CodeCapacityRepetitionCode::new(9, 0.1, 500);
+        let mut initializer = code.get_initializer();
+        // every edge flips its own fault and a shared hook fault
+        for edge_index in 0..initializer.weighted_edges.len() as EdgeIndex {
+            initializer = initializer.with_edge_fault_ids(edge_index, vec![100, edge_index as FaultId]);
+        }
+        assert_eq!(initializer.subgraph_fault_ids(&[3]), vec![3, 100]);
+        assert_eq!(initializer.subgraph_fault_ids(&[4, 3]), vec![3, 4]);
+        assert_eq!(initializer.subgraph_fault_ids(&[3, 4, 5]), vec![3, 4, 5, 100]);
+        assert!(initializer.subgraph_fault_ids(&[]).is_empty());
+        // the decoding result carries the faults of the correction
+        let defect_vertices: Vec<VertexIndex> = initializer.syndrome_of(&[3, 4]).into_iter().collect();
+        let mut solver = SolverConfig::Serial.build(&initializer);
+        let result = solver.decode(&SyndromePattern::new_vertices(defect_vertices));
+        assert_eq!(result.fault_ids, initializer.subgraph_fault_ids(&result.subgraph));
+        assert_eq!(result.fault_ids, vec![3, 4]);
+        // the fault ids survive serialization of the decoding graph, and are omitted if absent
+        let deserialized: SolverInitializer = serde_json::from_value(serde_json::json!(initializer)).unwrap();
+        assert_eq!(deserialized.edge_fault_ids, initializer.edge_fault_ids);
+        assert!(serde_json::json!(code.get_initializer()).get("edge_fault_ids").is_none());
+    }
+
+    #[test]
+    fn util_solver_initializer_builder() {
+        // cargo test util_solver_initializer_builder -- --nocapture
+        use crate::example_codes::*;
+        // 0 (virtual) - 1 - 2 - 3 (virtual), and a separate pair 4 - 5 and an isolated vertex 6
+        let mut builder = SolverInitializerBuilder::new(7);
+        builder.add_virtual_vertex(0).unwrap();
+        builder.add_virtual_vertex(3).unwrap();
+        assert_eq!(builder.add_edge(0, 1, 100), Ok(0));
+        assert_eq!(builder.add_edge(1, 2, 300), Ok(1));
+        assert_eq!(builder.add_edge(2, 3, 100), Ok(2));
+        assert_eq!(builder.add_edge(4, 5, 100), Ok(3));
+        // a duplicate edge keeps the smaller weight
+        assert_eq!(builder.add_edge(2, 1, 200), Ok(1));
+        assert_eq!(builder.add_edge(1, 2, 400), Ok(1));
+        assert_eq!(
+            builder.add_edge(1, 7, 100),
+            Err(InitializerError::VertexOutOfRange {
+                vertex_index: 7,
+                vertex_num: 7
+            })
+        );
+        assert_eq!(
+            builder.add_edge(1, 1, 100),
+            Err(InitializerError::SelfLoop { vertex_index: 1 })
+        );
+        assert!(matches!(
+            builder.add_edge(1, 4, -2),
+            Err(InitializerError::NegativeWeight { .. })
+        ));
+        assert!(matches!(builder.add_edge(1, 4, 101), Err(InitializerError::OddWeight { .. })));
+        let statistics = builder.statistics();
+        println!("{}", serde_json::to_string_pretty(&statistics).unwrap());
+        assert_eq!(statistics.edge_num, 4);
+        assert_eq!(statistics.merged_edge_num, 2);
+        assert_eq!(statistics.isolated_vertex_num, 1);
+        assert_eq!(statistics.max_degree, 2);
+        assert_eq!((statistics.min_weight, statistics.max_weight), (Some(100), Some(200)));
+        assert_eq!(statistics.component_num, 3);
+        assert_eq!(statistics.components_without_boundary, vec![4, 6]);
+        let error = builder.clone().with_require_boundary(true).build().unwrap_err();
+        assert_eq!(error, InitializerError::NoBoundary { vertex_index: 4 });
+        println!("{error}");
+        let initializer = builder.build().unwrap();
+        assert_eq!(
+            initializer.weighted_edges,
+            vec![(0, 1, 100), (1, 2, 200), (2, 3, 100), (4, 5, 100)]
+        );
+        assert_eq!(initializer.virtual_vertices, vec![0, 3]);
+        // duplicates are rejected when validating an existing decoding graph
+        assert_eq!(
+            initializer.validate().unwrap(),
+            InitializerStatistics {
+                merged_edge_num: 0,
+                ..statistics
+            }
+        );
+        let duplicated = SolverInitializer::new(3, vec![(0, 1, 100), (1, 0, 200)], vec![]);
+        assert_eq!(
+            duplicated.validate(),
+            Err(InitializerError::DuplicateEdge { left: 1, right: 0 })
+        );
+        let code = CodeCapacityPlanarCode::new(5, 0.1, 500);
+        let statistics = code.get_initializer().validate().unwrap();
+        assert!(statistics.components_without_boundary.is_empty());
+        assert_eq!(statistics.component_num, 1);
+    }
+
+    #[test]
+    fn util_graph_composition() {
+        // cargo test util_graph_composition -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        // 0 (virtual) - 1 - 2 - 3 (virtual)
+        let round = SolverInitializer::new(4, vec![(0, 1, 100), (1, 2, 200), (2, 3, 100)], vec![0, 3])
+            .with_edge_annotation(1, serde_json::json!("middle"));
+        let stacked = round.stack_rounds(3, 60);
+        assert_eq!(stacked.vertex_num, 12);
+        assert_eq!(stacked.virtual_vertices, vec![0, 3, 4, 7, 8, 11]);
+        assert_eq!(stacked.weighted_edges.len(), 3 * 3 + 2 * 2);
+        assert_eq!(
+            &stacked.weighted_edges[3..7],
+            &[(4, 5, 100), (5, 6, 200), (6, 7, 100), (1, 5, 60)]
+        );
+        assert_eq!(stacked.weighted_edges[12], (6, 10, 60));
+        assert_eq!(stacked.edge_annotations.keys().cloned().collect::<Vec<_>>(), vec![1, 4, 9]);
+        stacked.validate().unwrap();
+        // a measurement error is matched through the measurement edge
+        let mut solver = SolverSerial::new(&stacked);
+        solver.solve(&SyndromePattern::new_vertices(vec![2, 6]));
+        assert_eq!(solver.sum_dual_variables(), 60);
+        assert_eq!(solver.subgraph(), vec![7]);
+        assert_eq!(round.stack_rounds(1, 0).weighted_edges, round.weighted_edges);
+        // the union of two patches is decoded as the two patches independently
+        let mut code_1 = CodeCapacityPlanarCode::new(5, 0.1, 500);
+        let mut code_2 = CodeCapacityRepetitionCode::new(7, 0.1, 500);
+        let (initializer_1, initializer_2) = (code_1.get_initializer(), code_2.get_initializer());
+        let union = initializer_1.disjoint_union(&initializer_2.clone().with_capacity_hint(CapacityHint {
+            max_defect_num: 4,
+            active_vertex_num: 10,
+        }));
+        assert_eq!(union.vertex_num, initializer_1.vertex_num + initializer_2.vertex_num);
+        assert_eq!(
+            union.virtual_vertices.len(),
+            initializer_1.virtual_vertices.len() + initializer_2.virtual_vertices.len()
+        );
+        assert_eq!(
+            union.capacity_hint.unwrap().max_defect_num,
+            initializer_1.capacity_hint.unwrap_or_default().max_defect_num + 4
+        );
+        assert_eq!(union.validate().unwrap().component_num, 2);
+        let mut solver = SolverSerial::new(&union);
+        let mut solver_1 = SolverSerial::new(&initializer_1);
+        let mut solver_2 = SolverSerial::new(&initializer_2);
+        for seed in 0..10 {
+            let (syndrome_1, syndrome_2) = (code_1.generate_random_errors(seed), code_2.generate_random_errors(seed));
+            solver_1.solve(&syndrome_1);
+            solver_2.solve(&syndrome_2);
+            let mut defect_vertices = syndrome_1.defect_vertices.clone();
+            (defect_vertices).extend(
+                syndrome_2
+                    .defect_vertices
+                    .iter()
+                    .map(|&vertex| vertex + initializer_1.vertex_num),
+            );
+            solver.solve(&SyndromePattern::new_vertices(defect_vertices));
+            assert_eq!(
+                solver.sum_dual_variables(),
+                solver_1.sum_dual_variables() + solver_2.sum_dual_variables(),
+                "seed {seed}"
+            );
+            for solver in [&mut solver, &mut solver_1, &mut solver_2] {
+                solver.clear();
+            }
+        }
+    }
 }
@@ -0,0 +1,231 @@
+//! Verifier
+//!
+//! Reference decoders that are slow but obviously correct, used to validate the serial and parallel solvers
+//!
+
+use super::complete_graph::*;
+use super::util::*;
+
+/// the default maximum number of defect vertices that brute force decoder accepts
+pub const BRUTE_FORCE_MAX_DEFECT_NUM: usize = 12;
+
+/// enumerate all possible pairings of the defect vertices (including matching to the boundary) and pick the one with minimum weight;
+/// the complexity grows super-exponentially with the number of defect vertices, so only use it for tiny instances
+pub struct BruteForceDecoder {
+    /// the decoding graph
+    pub initializer: SolverInitializer,
+    /// the maximum number of defect vertices to accept, to prevent accidentally running forever
+    pub max_defect_num: usize,
+    /// compute the shortest path between vertices
+    complete_graph: CompleteGraph,
+    /// whether a vertex is virtual
+    is_virtual: Vec<bool>,
+}
+
+/// the distances between defect vertices, computed using Dijkstra's algorithm
+struct BruteForceDistances {
+    /// `peer[i][j]` is the distance between defect `i` and `j`, `None` if not connected
+    peer: Vec<Vec<Option<Weight>>>,
+    /// the closest virtual vertex of each defect, `None` if not connected to any virtual vertex
+    boundary: Vec<Option<(VertexIndex, Weight)>>,
+}
+
+impl BruteForceDecoder {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        Self {
+            initializer: initializer.clone(),
+            max_defect_num: BRUTE_FORCE_MAX_DEFECT_NUM,
+            complete_graph: CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges),
+            is_virtual,
+        }
+    }
+
+    /// compute the minimum weight of the perfect matching, i.e. the ground truth of `sum_dual_variables`
+    pub fn solve_weight(&mut self, syndrome_pattern: &SyndromePattern) -> Weight {
+        self.solve(syndrome_pattern).0
+    }
+
+    /// returns the minimum weight and the matching in the same format as [`crate::blossom_v_mwpm`]:
+    /// for each defect vertex, the matched defect vertex or virtual vertex
+    #[allow(clippy::unnecessary_cast)]
+    pub fn solve(&mut self, syndrome_pattern: &SyndromePattern) -> (Weight, Vec<VertexIndex>) {
+        let defect_vertices = &syndrome_pattern.defect_vertices;
+        assert!(
+            defect_vertices.len() <= self.max_defect_num,
+            "brute force decoder only supports up to {} defect vertices, got {}",
+            self.max_defect_num,
+            defect_vertices.len()
+        );
+        self.complete_graph.reset();
+        if !syndrome_pattern.erasures.is_empty() {
+            self.complete_graph.load_erasures(&syndrome_pattern.erasures);
+        }
+        if !syndrome_pattern.dynamic_weights.is_empty() {
+            self.complete_graph.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        }
+        let distances = self.compute_distances(defect_vertices);
+        self.complete_graph.reset();
+        // `matched[i]` is `Some(j)` if matched to defect `j`, or `Some(usize::MAX)` if matched to the boundary
+        let mut matched: Vec<Option<usize>> = vec![None; defect_vertices.len()];
+        let mut best: Option<(Weight, Vec<Option<usize>>)> = None;
+        Self::enumerate(&distances, &mut matched, 0, &mut best);
+        let (weight, best_matched) = best.expect("no perfect matching exists");
+        let mwpm_result = best_matched
+            .iter()
+            .enumerate()
+            .map(|(i, j)| match j.unwrap() {
+                usize::MAX => distances.boundary[i].unwrap().0,
+                j => defect_vertices[j],
+            })
+            .collect();
+        (weight, mwpm_result)
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn compute_distances(&mut self, defect_vertices: &[VertexIndex]) -> BruteForceDistances {
+        let defect_num = defect_vertices.len();
+        let mut peer = vec![vec![None; defect_num]; defect_num];
+        let mut boundary = Vec::with_capacity(defect_num);
+        for (i, &defect_vertex) in defect_vertices.iter().enumerate() {
+            assert!(!self.is_virtual[defect_vertex as usize], "syndrome vertex cannot be virtual");
+            let complete_graph_edges = self.complete_graph.all_edges(defect_vertex);
+            let mut closest_boundary: Option<(VertexIndex, Weight)> = None;
+            for (&vertex, &(_, weight)) in complete_graph_edges.iter() {
+                if self.is_virtual[vertex as usize] && closest_boundary.map(|(_, w)| weight < w).unwrap_or(true) {
+                    closest_boundary = Some((vertex, weight));
+                }
+            }
+            boundary.push(closest_boundary);
+            for (j, defect_vertex_j) in defect_vertices.iter().enumerate() {
+                if i != j {
+                    peer[i][j] = complete_graph_edges.get(defect_vertex_j).map(|(_, weight)| *weight);
+                }
+            }
+        }
+        BruteForceDistances { peer, boundary }
+    }
+
+    /// recursively enumerate all pairings, always matching the first unmatched defect
+    fn enumerate(
+        distances: &BruteForceDistances,
+        matched: &mut Vec<Option<usize>>,
+        current_weight: Weight,
+        best: &mut Option<(Weight, Vec<Option<usize>>)>,
+    ) {
+        if let Some((best_weight, _)) = best.as_ref() {
+            if current_weight >= *best_weight {
+                return; // weights are non-negative, no need to continue
+            }
+        }
+        let Some(i) = matched.iter().position(|j| j.is_none()) else {
+            *best = Some((current_weight, matched.clone()));
+            return;
+        };
+        if let Some((_, weight)) = distances.boundary[i] {
+            matched[i] = Some(usize::MAX);
+            Self::enumerate(distances, matched, current_weight + weight, best);
+            matched[i] = None;
+        }
+        for j in (i + 1)..matched.len() {
+            if matched[j].is_some() {
+                continue;
+            }
+            if let Some(weight) = distances.peer[i][j] {
+                matched[i] = Some(j);
+                matched[j] = Some(i);
+                Self::enumerate(distances, matched, current_weight + weight, best);
+                matched[i] = None;
+                matched[j] = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::example_partition::*;
+    use super::super::mwpm_solver::*;
+    use super::super::primal_module::*;
+    use super::*;
+    use crate::rand_xoshiro::rand_core::{RngCore, SeedableRng};
+
+    /// generate a random connected graph with a few virtual vertices and even weights
+    pub fn random_small_graph(rng: &mut DeterministicRng, vertex_num: VertexNum) -> SolverInitializer {
+        let mut weighted_edges = vec![];
+        for i in 1..vertex_num {
+            // spanning tree to guarantee connectivity
+            let j = (rng.next_u64() % (i as u64)) as VertexIndex;
+            weighted_edges.push((j, i, 2 * (1 + (rng.next_u64() % 50) as Weight)));
+        }
+        for _ in 0..vertex_num {
+            let i = (rng.next_u64() % (vertex_num as u64)) as VertexIndex;
+            let j = (rng.next_u64() % (vertex_num as u64)) as VertexIndex;
+            if i != j
+                && !weighted_edges
+                    .iter()
+                    .any(|(a, b, _)| (*a, *b) == (i, j) || (*a, *b) == (j, i))
+            {
+                weighted_edges.push((i, j, 2 * (1 + (rng.next_u64() % 50) as Weight)));
+            }
+        }
+        let virtual_vertices = vec![0, vertex_num - 1];
+        SolverInitializer::new(vertex_num, weighted_edges, virtual_vertices)
+    }
+
+    #[test]
+    fn verifier_brute_force_random_graph_serial() {
+        // cargo test verifier_brute_force_random_graph_serial -- --nocapture
+        let mut rng = DeterministicRng::seed_from_u64(123);
+        for _ in 0..50 {
+            let vertex_num = 4 + (rng.next_u64() % 16) as VertexNum;
+            let initializer = random_small_graph(&mut rng, vertex_num);
+            let defect_vertices: Vec<VertexIndex> = (1..vertex_num - 1).filter(|_| rng.next_u64() % 2 == 0).collect();
+            if defect_vertices.len() > BRUTE_FORCE_MAX_DEFECT_NUM {
+                continue;
+            }
+            let syndrome_pattern = SyndromePattern::new_vertices(defect_vertices);
+            let mut brute_force = BruteForceDecoder::new(&initializer);
+            let (expected_weight, _) = brute_force.solve(&syndrome_pattern);
+            let mut solver = SolverSerial::new(&initializer);
+            solver.solve(&syndrome_pattern);
+            assert_eq!(
+                solver.sum_dual_variables(),
+                expected_weight,
+                "{initializer:?} {syndrome_pattern:?}"
+            );
+            let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+            subgraph_builder.load_perfect_matching(&solver.perfect_matching());
+            assert_eq!(subgraph_builder.total_weight(), expected_weight);
+        }
+    }
+
+    #[test]
+    fn verifier_brute_force_planar_code_parallel() {
+        // cargo test verifier_brute_force_planar_code_parallel -- --nocapture
+        let d = 7;
+        let mut code = CodeCapacityPlanarCode::new(d, 0.05, 500);
+        let partition_config = CodeCapacityPlanarCodeVerticalPartitionHalf::new(d, d / 2).build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let mut brute_force = BruteForceDecoder::new(&initializer);
+        let mut solver = SolverParallel::new(&initializer, &partition_config.info(), json!({}));
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            if syndrome_pattern.defect_vertices.len() > BRUTE_FORCE_MAX_DEFECT_NUM {
+                continue;
+            }
+            solver.solve(&syndrome_pattern);
+            assert_eq!(
+                solver.sum_dual_variables(),
+                brute_force.solve_weight(&syndrome_pattern),
+                "{syndrome_pattern:?}"
+            );
+            solver.clear();
+        }
+    }
+}
@@ -7,12 +7,16 @@ use crate::chrono::Local;
 use crate::serde::{Deserialize, Serialize};
 use crate::serde_json;
 use crate::urlencoding;
-#[cfg(feature = "python_binding")]
 use crate::util::*;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
+#[cfg(feature = "compressed_visualizer")]
+use std::io::Read;
 use std::io::{Seek, SeekFrom, Write};
+use std::sync::Mutex;
+use std::time::Instant;
 
 pub trait FusionVisualizer {
     /// take a snapshot, set `abbrev` to true to save space
@@ -64,21 +68,233 @@ impl VisualizePosition {
     }
 }
 
+/// the events during solving at which a snapshot may be taken, see [`SnapshotPolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotEvent {
+    /// the dual variables grow by a non-zero length
+    Grow,
+    /// a conflict is resolved by the primal module
+    Conflict,
+    /// a unit of the parallel solver has just fused its children or is solved
+    Fusion,
+}
+
+/// which snapshots to take during a solve; by default every event is snapshotted, which becomes unusable beyond small
+/// code distances especially for the parallel solver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotPolicy {
+    /// take only one in every `every` events that pass the filters below, starting from the first one; 0 disables them
+    #[serde(default = "snapshot_policy_default_configs::every")]
+    pub every: usize,
+    #[serde(default = "snapshot_policy_default_configs::on_grow")]
+    pub on_grow: bool,
+    #[serde(default = "snapshot_policy_default_configs::on_conflict")]
+    pub on_conflict: bool,
+    #[serde(default = "snapshot_policy_default_configs::on_fusion")]
+    pub on_fusion: bool,
+    /// the maximum number of event snapshots in a solve; the final snapshot of a solve is always taken
+    #[serde(default = "snapshot_policy_default_configs::max_snapshots")]
+    pub max_snapshots: Option<usize>,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod snapshot_policy_default_configs {
+    pub fn every() -> usize {
+        1
+    }
+    pub fn on_grow() -> bool {
+        true
+    }
+    pub fn on_conflict() -> bool {
+        true
+    }
+    pub fn on_fusion() -> bool {
+        true
+    }
+    pub fn max_snapshots() -> Option<usize> {
+        None
+    }
+}
+
+impl SnapshotPolicy {
+    /// snapshot only the conflicts
+    pub fn conflicts_only() -> Self {
+        Self {
+            on_grow: false,
+            on_fusion: false,
+            ..Default::default()
+        }
+    }
+
+    /// snapshot only the fusion of units in the parallel solver
+    pub fn fusion_only() -> Self {
+        Self {
+            on_grow: false,
+            on_conflict: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn accepts(&self, event: SnapshotEvent) -> bool {
+        match event {
+            SnapshotEvent::Grow => self.on_grow,
+            SnapshotEvent::Conflict => self.on_conflict,
+            SnapshotEvent::Fusion => self.on_fusion,
+        }
+    }
+}
+
+/// a snapshot taken by one of the concurrently running units, see [`SnapshotBuffer`]
+#[derive(Debug, Clone)]
+pub struct BufferedSnapshot {
+    /// the time since the creation of the buffer, in seconds
+    pub timestamp: f64,
+    pub unit_index: usize,
+    pub event: SnapshotEvent,
+    pub name: String,
+    pub value: serde_json::Value,
+}
+
+/// thread-safe snapshot collection for the units of a parallel solver that run concurrently: each unit appends to its own
+/// buffer without blocking the others, and the snapshots are ordered globally by their timestamps when saved to a
+/// [`Visualizer`], so that the interleaving of the units is visible
+#[derive(Debug)]
+pub struct SnapshotBuffer {
+    start_time: Instant,
+    /// only the events accepted by the policy are taken; the sampling is applied when saving, in the global order
+    snapshot_policy: SnapshotPolicy,
+    units: Vec<Mutex<Vec<BufferedSnapshot>>>,
+}
+
+impl SnapshotBuffer {
+    pub fn new(unit_count: usize, snapshot_policy: SnapshotPolicy) -> Self {
+        Self {
+            start_time: Instant::now(),
+            snapshot_policy,
+            units: (0..unit_count).map(|_| Mutex::new(vec![])).collect(),
+        }
+    }
+
+    pub fn accepts(&self, event: SnapshotEvent) -> bool {
+        !cfg!(feature = "disable_visualizer") && self.snapshot_policy.accepts(event)
+    }
+
+    /// take a snapshot in the thread running the unit
+    pub fn snapshot_combined(
+        &self,
+        unit_index: usize,
+        event: SnapshotEvent,
+        name: String,
+        fusion_algorithms: Vec<&dyn FusionVisualizer>,
+    ) {
+        if !self.accepts(event) {
+            return;
+        }
+        let abbrev = true;
+        let mut value = json!({});
+        for fusion_algorithm in fusion_algorithms.iter() {
+            snapshot_combine_values(&mut value, fusion_algorithm.snapshot(abbrev), abbrev);
+        }
+        let timestamp = self.start_time.elapsed().as_secs_f64();
+        self.units[unit_index].lock().unwrap().push(BufferedSnapshot {
+            timestamp,
+            unit_index,
+            event,
+            name,
+            value,
+        });
+    }
+
+    /// remove all the snapshots, ordered by timestamp; the snapshots of the same unit keep their order
+    pub fn take_ordered(&self) -> Vec<BufferedSnapshot> {
+        let mut snapshots: Vec<_> = (self.units.iter())
+            .flat_map(|unit| std::mem::take(&mut *unit.lock().unwrap()))
+            .collect();
+        snapshots.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        snapshots
+    }
+}
+
+/// the part of the decoding graph kept in the snapshots, see [`snapshot_filter_region`]; when debugging a single fusion
+/// boundary, the rest of the graph is only overhead in every snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRegion {
+    pub vertex_ranges: Vec<VertexRange>,
+}
+
+impl SnapshotRegion {
+    pub fn new(vertex_ranges: Vec<VertexRange>) -> Self {
+        Self { vertex_ranges }
+    }
+
+    /// the owning ranges of the given units, i.e. the interfacing vertices of a fusion unit or all the vertices of a leaf
+    pub fn from_units(partition_info: &PartitionInfo, unit_indices: &[usize]) -> Result<Self, String> {
+        let mut vertex_ranges = Vec::with_capacity(unit_indices.len());
+        for &unit_index in unit_indices.iter() {
+            let unit = (partition_info.units.get(unit_index))
+                .ok_or_else(|| format!("unit {unit_index} out of range, there are {}", partition_info.units.len()))?;
+            vertex_ranges.push(unit.owning_range);
+        }
+        Ok(Self { vertex_ranges })
+    }
+
+    pub fn contains(&self, vertex_index: VertexIndex) -> bool {
+        self.vertex_ranges.iter().any(|range| range.contains(vertex_index))
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pyclass)]
 pub struct Visualizer {
     /// save to file if applicable
     file: Option<File>,
+    /// save to a compressed snapshot container instead, see [`Visualizer::new_compressed`]
+    #[cfg(feature = "compressed_visualizer")]
+    container: Option<SnapshotContainerWriter>,
     /// if waiting for the first snapshot
     empty_snapshot: bool,
     /// names of the snapshots
     #[cfg_attr(feature = "python_binding", pyo3(get))]
     pub snapshots: Vec<String>,
+    /// store only the difference against the previous snapshot, see [`snapshot_compute_delta`]
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub delta_snapshot: bool,
+    /// the last full snapshot, used as the base of the delta
+    last_snapshot: Option<serde_json::Value>,
+    /// user metadata added to every snapshot, see [`Visualizer::annotate`]
+    vertex_annotations: BTreeMap<VertexIndex, serde_json::Value>,
+    edge_annotations: BTreeMap<EdgeIndex, serde_json::Value>,
+    /// see [`Visualizer::set_snapshot_policy`]
+    pub snapshot_policy: SnapshotPolicy,
+    /// only keep this region in the following snapshots, see [`snapshot_filter_region`]
+    pub snapshot_region: Option<SnapshotRegion>,
+    /// the number of events accepted by the policy and the number of them snapshotted in the current solve
+    policy_event_count: usize,
+    policy_snapshot_count: usize,
+}
+
+/// the version of the snapshot format, which must be increased whenever a key is added, renamed or changes its meaning;
+/// [`snapshots_upgrade`] converts the snapshots of older versions. The history of the versions:
+/// - 1: no explicit version; the non-abbreviated keys of defect vertices are `is_syndrome` and `syndrome_vertex`
+/// - 2: the version is given in the file header and in every snapshot as `schema`; `is_defect` and `defect_vertex`
+pub const SNAPSHOT_SCHEMA_VERSION: u64 = 2;
+
+/// the schema version of a snapshot or a visualization file, where the absence of it means version 1
+pub fn snapshot_schema_version(value: &serde_json::Value) -> u64 {
+    value.get("schema").and_then(|schema| schema.as_u64()).unwrap_or(1)
 }
 
+/// fill the fields that are omitted by the fusion algorithms and mark the schema version
 pub fn snapshot_fix_missing_fields(value: &mut serde_json::Value, abbrev: bool) {
     let value = value.as_object_mut().expect("snapshot must be an object");
+    value.insert("schema".to_string(), json!(SNAPSHOT_SCHEMA_VERSION));
     // fix vertices missing fields
     let vertices = value
         .get_mut("vertices")
@@ -127,6 +343,109 @@ pub fn snapshot_fix_missing_fields(value: &mut serde_json::Value, abbrev: bool)
     }
 }
 
+/// replace the elements outside the region by null, keeping the indices: the vertices outside the region, the edges with
+/// neither endpoint inside and the dual nodes of blossom trees that touch no vertex inside, where a dual node touches the
+/// vertices it is propagated to as well as its defect vertex
+#[allow(clippy::unnecessary_cast)]
+pub fn snapshot_filter_region(value: &mut serde_json::Value, region: &SnapshotRegion, abbrev: bool) {
+    let value = value.as_object_mut().expect("snapshot must be an object");
+    let key_propagated = [
+        if abbrev { "p" } else { "propagated_dual_node" },
+        if abbrev { "pg" } else { "propagated_grandson_dual_node" },
+    ];
+    let mut touched_nodes = BTreeSet::<usize>::new();
+    if let Some(vertices) = value.get_mut("vertices").and_then(|vertices| vertices.as_array_mut()) {
+        for (vertex_index, vertex) in vertices.iter_mut().enumerate() {
+            if !region.contains(vertex_index as VertexIndex) {
+                *vertex = json!(null);
+                continue;
+            }
+            for key in key_propagated {
+                if let Some(node_index) = vertex.get(key).and_then(|node_index| node_index.as_u64()) {
+                    touched_nodes.insert(node_index as usize);
+                }
+            }
+        }
+    }
+    if let Some(edges) = value.get_mut("edges").and_then(|edges| edges.as_array_mut()) {
+        let key_left = if abbrev { "l" } else { "left" };
+        let key_right = if abbrev { "r" } else { "right" };
+        for edge in edges.iter_mut() {
+            let inside = [key_left, key_right].iter().any(|key| {
+                (edge.get(key).and_then(|vertex_index| vertex_index.as_u64()))
+                    .map(|vertex_index| region.contains(vertex_index as VertexIndex))
+                    .unwrap_or(false)
+            });
+            if !inside {
+                *edge = json!(null);
+            }
+        }
+    }
+    let Some(dual_nodes) = value.get_mut("dual_nodes").and_then(|dual_nodes| dual_nodes.as_array_mut()) else {
+        return;
+    };
+    let key_defect_vertex = if abbrev { "s" } else { "defect_vertex" };
+    let key_blossom = if abbrev { "o" } else { "blossom" };
+    let key_parent_blossom = if abbrev { "p" } else { "parent_blossom" };
+    let mut kept = vec![false; dual_nodes.len()];
+    // a blossom always has a larger index than its children, so the parents are marked in the increasing order
+    for (node_index, dual_node) in dual_nodes.iter().enumerate() {
+        let defect_vertex = dual_node
+            .get(key_defect_vertex)
+            .and_then(|vertex_index| vertex_index.as_u64());
+        kept[node_index] |= touched_nodes.contains(&node_index)
+            || defect_vertex
+                .map(|vertex_index| region.contains(vertex_index as VertexIndex))
+                .unwrap_or(false);
+        if let Some(parent_index) = dual_node.get(key_parent_blossom).and_then(|node_index| node_index.as_u64()) {
+            if kept[node_index] && (parent_index as usize) < kept.len() {
+                kept[parent_index as usize] = true;
+            }
+        }
+    }
+    // the whole blossom tree is kept so that every kept blossom refers to existing children
+    for node_index in (0..dual_nodes.len()).rev() {
+        if !kept[node_index] {
+            continue;
+        }
+        if let Some(children) = dual_nodes[node_index]
+            .get(key_blossom)
+            .and_then(|children| children.as_array())
+        {
+            for child_index in children.iter().filter_map(|child_index| child_index.as_u64()) {
+                if let Some(child_kept) = kept.get_mut(child_index as usize) {
+                    *child_kept = true;
+                }
+            }
+        }
+    }
+    for (dual_node, kept) in dual_nodes.iter_mut().zip(kept) {
+        if !kept {
+            *dual_node = json!(null);
+        }
+    }
+}
+
+/// add the annotations as the field `a` of the vertices and edges present in the snapshot
+#[allow(clippy::unnecessary_cast)]
+pub fn snapshot_annotate(
+    value: &mut serde_json::Value,
+    vertex_annotations: &BTreeMap<VertexIndex, serde_json::Value>,
+    edge_annotations: &BTreeMap<EdgeIndex, serde_json::Value>,
+) {
+    let value = value.as_object_mut().expect("snapshot must be an object");
+    for (key, annotations) in [("vertices", vertex_annotations), ("edges", edge_annotations)] {
+        let Some(elements) = value.get_mut(key).and_then(|elements| elements.as_array_mut()) else {
+            continue;
+        };
+        for (&index, annotation) in annotations.iter() {
+            if let Some(element) = elements.get_mut(index as usize).and_then(|element| element.as_object_mut()) {
+                element.insert("a".to_string(), annotation.clone());
+            }
+        }
+    }
+}
+
 pub type ObjectMap = serde_json::Map<String, serde_json::Value>;
 pub fn snapshot_combine_object_known_key(obj: &mut ObjectMap, obj_2: &mut ObjectMap, key: &str) {
     match (obj.contains_key(key), obj_2.contains_key(key)) {
@@ -366,9 +685,400 @@ pub fn snapshot_combine_values(value: &mut serde_json::Value, mut value_2: serde
             value_2.remove("primal_nodes").unwrap();
         }
     }
+    match (
+        value.contains_key("alternating_trees"),
+        value_2.contains_key("alternating_trees"),
+    ) {
+        (_, false) => {} // do nothing
+        (false, true) => {
+            value.insert("alternating_trees".to_string(), value_2.remove("alternating_trees").unwrap());
+        }
+        (true, true) => {
+            // the trees of different units are disjoint, so simply concatenate them
+            let alternating_trees_2 = value_2.remove("alternating_trees").unwrap();
+            let alternating_trees_2 = alternating_trees_2.as_array().expect("alternating_trees must be an array");
+            let alternating_trees = value
+                .get_mut("alternating_trees")
+                .unwrap()
+                .as_array_mut()
+                .expect("alternating_trees must be an array");
+            alternating_trees.extend(alternating_trees_2.iter().cloned());
+        }
+    }
     snapshot_copy_remaining_fields(value, value_2);
 }
 
+/// compute the difference of `value` against `previous`, such that `snapshot_apply_delta(previous, delta)` recovers `value`;
+/// array fields (vertices, edges, dual nodes, ...) are compared element by element and only the changed elements are kept,
+/// in the form of `{"delta":{"arrays":{key:[length,[[index,element],...]]},"removed":[key,...]}, other changed fields...}`
+pub fn snapshot_compute_delta(previous: &serde_json::Value, value: &serde_json::Value) -> serde_json::Value {
+    let previous = previous.as_object().expect("snapshot must be an object");
+    let value = value.as_object().expect("snapshot must be an object");
+    let mut delta = ObjectMap::new();
+    let mut arrays = ObjectMap::new();
+    for (key, element) in value.iter() {
+        match (previous.get(key), element) {
+            (Some(previous_element), _) if previous_element == element => {} // unchanged
+            (Some(serde_json::Value::Array(previous_array)), serde_json::Value::Array(array)) => {
+                let mut changes = Vec::<serde_json::Value>::new();
+                for (index, element) in array.iter().enumerate() {
+                    if previous_array.get(index) != Some(element) {
+                        changes.push(json!([index, element]));
+                    }
+                }
+                arrays.insert(key.clone(), json!([array.len(), changes]));
+            }
+            _ => {
+                delta.insert(key.clone(), element.clone());
+            }
+        }
+    }
+    let removed: Vec<&String> = previous.keys().filter(|key| !value.contains_key(*key)).collect();
+    delta.insert(
+        "delta".to_string(),
+        json!({
+            "arrays": arrays,
+            "removed": removed,
+        }),
+    );
+    serde_json::Value::Object(delta)
+}
+
+pub fn snapshot_is_delta(value: &serde_json::Value) -> bool {
+    value.as_object().map(|value| value.contains_key("delta")).unwrap_or(false)
+}
+
+/// reconstruct the full snapshot from the previous full snapshot and a delta generated by [`snapshot_compute_delta`]
+pub fn snapshot_apply_delta(previous: &serde_json::Value, delta: &serde_json::Value) -> serde_json::Value {
+    let mut value = previous.clone();
+    let object = value.as_object_mut().expect("snapshot must be an object");
+    let delta = delta.as_object().expect("delta snapshot must be an object");
+    for (key, element) in delta.iter() {
+        if key != "delta" {
+            object.insert(key.clone(), element.clone());
+        }
+    }
+    let delta_info = delta.get("delta").expect("not a delta snapshot");
+    for key in delta_info["removed"].as_array().expect("removed must be an array") {
+        object.remove(key.as_str().expect("removed key must be a string"));
+    }
+    for (key, array_delta) in delta_info["arrays"].as_object().expect("arrays must be an object") {
+        let array = object
+            .get_mut(key)
+            .and_then(|array| array.as_array_mut())
+            .expect("delta array must exist in the previous snapshot");
+        let length = array_delta[0].as_u64().expect("array length must be an integer") as usize;
+        array.resize(length, serde_json::Value::Null);
+        for change in array_delta[1].as_array().expect("changes must be an array") {
+            let index = change[0].as_u64().expect("array index must be an integer") as usize;
+            array[index] = change[1].clone();
+        }
+    }
+    value
+}
+
+/// recover full snapshots in place, given the list of `(name, snapshot)` in a visualization file
+pub fn snapshots_reconstruct(snapshots: &mut [serde_json::Value]) {
+    let mut last_snapshot: Option<serde_json::Value> = None;
+    for snapshot in snapshots.iter_mut() {
+        let value = &mut snapshot[1];
+        if snapshot_is_delta(value) {
+            *value = snapshot_apply_delta(last_snapshot.as_ref().expect("delta snapshot must follow a full one"), value);
+        }
+        last_snapshot = Some(value.clone());
+    }
+}
+
+/// upgrade the full snapshots in place to [`SNAPSHOT_SCHEMA_VERSION`], where a snapshot without a version takes the
+/// version of the file; a version newer than this library is rejected instead of being silently misread
+pub fn snapshots_upgrade(snapshots: &mut [serde_json::Value], file_schema: u64) -> Result<(), String> {
+    for snapshot in snapshots.iter_mut() {
+        let value = snapshot[1].as_object_mut().ok_or("snapshot must be an object")?;
+        let schema = (value.get("schema").and_then(|schema| schema.as_u64())).unwrap_or(file_schema);
+        if schema > SNAPSHOT_SCHEMA_VERSION {
+            return Err(format!(
+                "snapshot schema version {schema} is newer than the supported version {SNAPSHOT_SCHEMA_VERSION}"
+            ));
+        }
+        if schema < 2 {
+            for (array_key, old_key, new_key) in [
+                ("vertices", "is_syndrome", "is_defect"),
+                ("dual_nodes", "syndrome_vertex", "defect_vertex"),
+            ] {
+                let Some(elements) = value.get_mut(array_key).and_then(|elements| elements.as_array_mut()) else {
+                    continue;
+                };
+                for element in elements.iter_mut().filter_map(|element| element.as_object_mut()) {
+                    if let Some(field) = element.remove(old_key) {
+                        element.insert(new_key.to_string(), field);
+                    }
+                }
+            }
+        }
+        value.insert("schema".to_string(), json!(SNAPSHOT_SCHEMA_VERSION));
+    }
+    Ok(())
+}
+
+/// load a visualization file, reconstruct all the delta snapshots and upgrade them to [`SNAPSHOT_SCHEMA_VERSION`];
+/// a compressed snapshot container is converted to the same format, see `SnapshotContainerReader`
+pub fn load_visualize_data(filepath: &str) -> std::io::Result<serde_json::Value> {
+    let bytes = std::fs::read(filepath)?;
+    if bytes.starts_with(SNAPSHOT_CONTAINER_MAGIC) {
+        #[cfg(feature = "compressed_visualizer")]
+        return SnapshotContainerReader::open(filepath)?.to_visualize_data();
+        #[cfg(not(feature = "compressed_visualizer"))]
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "compressed snapshot containers require the `compressed_visualizer` feature",
+        ));
+    }
+    let mut data: serde_json::Value = serde_json::from_slice(&bytes)?;
+    let file_schema = snapshot_schema_version(&data);
+    if data.get("format").and_then(|format| format.as_str()) != Some("fusion_blossom") {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "not a fusion_blossom visualization file",
+        ));
+    }
+    let snapshots = data
+        .get_mut("snapshots")
+        .and_then(|snapshots| snapshots.as_array_mut())
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "missing snapshots"))?;
+    snapshots_reconstruct(snapshots);
+    snapshots_upgrade(snapshots, file_schema)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+    data["schema"] = json!(SNAPSHOT_SCHEMA_VERSION);
+    Ok(data)
+}
+
+/// the magic bytes at the beginning of a compressed snapshot container. The layout of the container is
+/// `magic | record 0 | record 1 | ... | index`, where each record is the little-endian `u64` length of a zstd frame
+/// followed by the frame; record 0 is the JSON header (everything but the snapshots of a visualization file) and record
+/// `i + 1` is the JSON `[name, snapshot]` of snapshot `i`. The index is the `u64` offset of every record followed by
+/// the number of records and [`SNAPSHOT_CONTAINER_INDEX_MAGIC`]; it is written when the visualizer is dropped, and
+/// rebuilt by scanning the records if the process is interrupted before that.
+pub const SNAPSHOT_CONTAINER_MAGIC: &[u8; 8] = b"FBSNAPZ1";
+/// the magic bytes at the end of the index of a compressed snapshot container
+pub const SNAPSHOT_CONTAINER_INDEX_MAGIC: &[u8; 8] = b"FBINDEX1";
+/// with [`Visualizer::delta_snapshot`], a full snapshot is stored every this number of snapshots in a compressed
+/// snapshot container, so that reading any snapshot decompresses at most this number of records
+pub const SNAPSHOT_CONTAINER_KEYFRAME_INTERVAL: usize = 32;
+
+#[cfg(feature = "compressed_visualizer")]
+#[derive(Debug)]
+struct SnapshotContainerWriter {
+    file: File,
+    /// the zstd compression level
+    level: i32,
+    /// the offset of every record
+    offsets: Vec<u64>,
+    /// the end of the last record, where the index is written
+    end: u64,
+}
+
+#[cfg(feature = "compressed_visualizer")]
+impl SnapshotContainerWriter {
+    fn create(filepath: String, level: i32, header: &serde_json::Value) -> std::io::Result<Self> {
+        let mut file = File::create(filepath)?;
+        file.write_all(SNAPSHOT_CONTAINER_MAGIC)?;
+        let mut writer = Self {
+            file,
+            level,
+            offsets: vec![],
+            end: SNAPSHOT_CONTAINER_MAGIC.len() as u64,
+        };
+        writer.append(header)?;
+        Ok(writer)
+    }
+
+    fn append(&mut self, value: &serde_json::Value) -> std::io::Result<()> {
+        let frame = zstd::bulk::compress(value.to_string().as_bytes(), self.level)?;
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(&(frame.len() as u64).to_le_bytes())?;
+        self.file.write_all(&frame)?;
+        self.file.sync_all()?;
+        self.offsets.push(self.end);
+        self.end += 8 + frame.len() as u64;
+        Ok(())
+    }
+
+    fn write_index(&mut self) -> std::io::Result<()> {
+        let mut index = Vec::with_capacity(8 * self.offsets.len() + 16);
+        for offset in self.offsets.iter() {
+            index.extend_from_slice(&offset.to_le_bytes());
+        }
+        index.extend_from_slice(&(self.offsets.len() as u64).to_le_bytes());
+        index.extend_from_slice(SNAPSHOT_CONTAINER_INDEX_MAGIC);
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(&index)?;
+        self.file.sync_all()
+    }
+}
+
+#[cfg(feature = "compressed_visualizer")]
+impl Drop for SnapshotContainerWriter {
+    fn drop(&mut self) {
+        // an unwritten index is rebuilt by the reader
+        let _ = self.write_index();
+    }
+}
+
+/// random access to the snapshots of a compressed snapshot container, see [`SNAPSHOT_CONTAINER_MAGIC`]
+#[cfg(feature = "compressed_visualizer")]
+#[derive(Debug)]
+pub struct SnapshotContainerReader {
+    file: File,
+    /// the offset of every record, including the header
+    offsets: Vec<u64>,
+    /// the header of the visualization data, without the snapshots
+    pub header: serde_json::Value,
+}
+
+#[cfg(feature = "compressed_visualizer")]
+impl SnapshotContainerReader {
+    pub fn open(filepath: &str) -> std::io::Result<Self> {
+        let invalid_data = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string());
+        let mut file = File::open(filepath)?;
+        let file_len = file.metadata()?.len();
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_CONTAINER_MAGIC {
+            return Err(invalid_data("not a compressed snapshot container"));
+        }
+        let offsets = match Self::read_index(&mut file, file_len)? {
+            Some(offsets) => offsets,
+            None => Self::scan_records(&mut file, file_len)?,
+        };
+        if offsets.is_empty() {
+            return Err(invalid_data("missing header"));
+        }
+        let mut reader = Self {
+            file,
+            offsets,
+            header: json!({}),
+        };
+        reader.header = reader.read_record(0)?;
+        if reader.header.get("format").and_then(|format| format.as_str()) != Some("fusion_blossom") {
+            return Err(invalid_data("not a fusion_blossom visualization file"));
+        }
+        Ok(reader)
+    }
+
+    fn read_u64(file: &mut File, offset: u64) -> std::io::Result<u64> {
+        let mut bytes = [0; 8];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// the offsets in the index at the end of the file, if it is present and consistent
+    fn read_index(file: &mut File, file_len: u64) -> std::io::Result<Option<Vec<u64>>> {
+        let data_start = SNAPSHOT_CONTAINER_MAGIC.len() as u64;
+        if file_len < data_start + 16 {
+            return Ok(None);
+        }
+        let mut magic = [0; 8];
+        file.seek(SeekFrom::Start(file_len - 8))?;
+        file.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_CONTAINER_INDEX_MAGIC {
+            return Ok(None);
+        }
+        let count = Self::read_u64(file, file_len - 16)?;
+        let Some(index_start) = count
+            .checked_mul(8)
+            .and_then(|index_len| (file_len - 16).checked_sub(index_len))
+            .filter(|&index_start| index_start >= data_start)
+        else {
+            return Ok(None);
+        };
+        let mut bytes = vec![0; 8 * count as usize];
+        file.seek(SeekFrom::Start(index_start))?;
+        file.read_exact(&mut bytes)?;
+        let offsets: Vec<u64> = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        if offsets.first() != Some(&data_start) || offsets.iter().any(|&offset| offset >= index_start) {
+            return Ok(None);
+        }
+        Ok(Some(offsets))
+    }
+
+    /// the offsets of the complete records, ignoring a record truncated by an interrupted write
+    fn scan_records(file: &mut File, file_len: u64) -> std::io::Result<Vec<u64>> {
+        let mut offsets = vec![];
+        let mut offset = SNAPSHOT_CONTAINER_MAGIC.len() as u64;
+        while offset + 8 <= file_len {
+            let end = Self::read_u64(file, offset)?.saturating_add(offset + 8);
+            if end > file_len {
+                break;
+            }
+            offsets.push(offset);
+            offset = end;
+        }
+        Ok(offsets)
+    }
+
+    fn read_record(&mut self, record_index: usize) -> std::io::Result<serde_json::Value> {
+        let offset = self.offsets[record_index];
+        let frame_len = Self::read_u64(&mut self.file, offset)? as usize;
+        let mut frame = vec![0; frame_len];
+        self.file.read_exact(&mut frame)?;
+        let decoded = zstd::stream::decode_all(frame.as_slice())?;
+        Ok(serde_json::from_slice(&decoded)?)
+    }
+
+    /// the number of snapshots
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the `[name, snapshot]` of a snapshot, reconstructed from the previous full snapshot and upgraded to
+    /// [`SNAPSHOT_SCHEMA_VERSION`]
+    pub fn snapshot(&mut self, snapshot_index: usize) -> std::io::Result<serde_json::Value> {
+        if snapshot_index >= self.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("snapshot {snapshot_index} out of range, there are {} snapshots", self.len()),
+            ));
+        }
+        let mut snapshots = vec![];
+        for record_index in (1..=snapshot_index + 1).rev() {
+            let snapshot = self.read_record(record_index)?;
+            let is_delta = snapshot_is_delta(&snapshot[1]);
+            snapshots.push(snapshot);
+            if !is_delta {
+                break;
+            }
+        }
+        snapshots.reverse();
+        snapshots_reconstruct(&mut snapshots);
+        snapshots_upgrade(&mut snapshots, snapshot_schema_version(&self.header))
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+        Ok(snapshots.pop().unwrap())
+    }
+
+    /// all the snapshots in the format of [`load_visualize_data`]
+    pub fn to_visualize_data(&mut self) -> std::io::Result<serde_json::Value> {
+        let mut snapshots = Vec::with_capacity(self.len());
+        for record_index in 1..self.offsets.len() {
+            snapshots.push(self.read_record(record_index)?);
+        }
+        snapshots_reconstruct(&mut snapshots);
+        snapshots_upgrade(&mut snapshots, snapshot_schema_version(&self.header))
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+        let mut data = self.header.clone();
+        data["schema"] = json!(SNAPSHOT_SCHEMA_VERSION);
+        data["snapshots"] = json!(snapshots);
+        Ok(data)
+    }
+}
+
 #[cfg_attr(feature = "python_binding", pyfunction)]
 pub fn center_positions(mut positions: Vec<VisualizePosition>) -> Vec<VisualizePosition> {
     if !positions.is_empty() {
@@ -430,8 +1140,9 @@ impl Visualizer {
             file.seek(SeekFrom::Start(0))?; // move the cursor to the front
             file.write_all(
                 format!(
-                    "{{\"format\":\"fusion_blossom\",\"version\":\"{}\"",
-                    env!("CARGO_PKG_VERSION")
+                    "{{\"format\":\"fusion_blossom\",\"version\":\"{}\",\"schema\":{}",
+                    env!("CARGO_PKG_VERSION"),
+                    SNAPSHOT_SCHEMA_VERSION
                 )
                 .as_bytes(),
             )?;
@@ -442,11 +1153,36 @@ impl Visualizer {
         }
         Ok(Self {
             file,
+            #[cfg(feature = "compressed_visualizer")]
+            container: None,
             empty_snapshot: true,
             snapshots: vec![],
+            delta_snapshot: false,
+            last_snapshot: None,
+            vertex_annotations: BTreeMap::new(),
+            edge_annotations: BTreeMap::new(),
+            snapshot_policy: SnapshotPolicy::default(),
+            snapshot_region: None,
+            policy_event_count: 0,
+            policy_snapshot_count: 0,
         })
     }
 
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "set_snapshot_policy")]
+    pub fn set_snapshot_policy_py(&mut self, policy: PyObject) -> PyResult<()> {
+        let policy = serde_json::from_value(pyobject_to_json(policy))
+            .map_err(|err| pyo3::exceptions::PyValueError::new_err(format!("{err:?}")))?;
+        self.set_snapshot_policy(policy);
+        Ok(())
+    }
+
+    /// add the vertex and edge annotations of the decoding graph to every following snapshot, as the field `a`
+    pub fn annotate(&mut self, initializer: &SolverInitializer) {
+        self.vertex_annotations = initializer.vertex_annotations.clone();
+        self.edge_annotations = initializer.edge_annotations.clone();
+    }
+
     #[cfg(feature = "python_binding")]
     #[pyo3(name = "snapshot_combined")]
     pub fn snapshot_combined_py(&mut self, name: String, object_pys: Vec<&PyAny>) -> std::io::Result<()> {
@@ -492,15 +1228,109 @@ impl Visualizer {
 }
 
 impl Visualizer {
-    pub fn incremental_save(&mut self, name: String, value: serde_json::Value) -> std::io::Result<()> {
+    /// create a new visualizer that streams the snapshots into a compressed snapshot container at the given zstd
+    /// compression level (0 for the default), see [`SNAPSHOT_CONTAINER_MAGIC`]; each snapshot is compressed on its own
+    /// so that [`SnapshotContainerReader`] can read any of them without decompressing the whole file
+    #[cfg(feature = "compressed_visualizer")]
+    pub fn new_compressed(
+        filepath: Option<String>,
+        positions: Vec<VisualizePosition>,
+        center: bool,
+        level: i32,
+    ) -> std::io::Result<Self> {
+        let mut visualizer = Self::new(None, vec![], false)?;
+        if cfg!(feature = "disable_visualizer") {
+            return Ok(visualizer);
+        }
+        if let Some(filepath) = filepath {
+            let positions = if center { center_positions(positions) } else { positions };
+            let header = json!({
+                "format": "fusion_blossom",
+                "version": env!("CARGO_PKG_VERSION"),
+                "schema": SNAPSHOT_SCHEMA_VERSION,
+                "positions": positions,
+            });
+            visualizer.container = Some(SnapshotContainerWriter::create(filepath, level, &header)?);
+        }
+        Ok(visualizer)
+    }
+
+    /// whether the snapshots are saved to a file
+    fn has_output(&self) -> bool {
+        #[cfg(feature = "compressed_visualizer")]
+        if self.container.is_some() {
+            return true;
+        }
+        self.file.is_some()
+    }
+
+    /// set the snapshot policy of the following solves, which also restarts the sampling
+    pub fn set_snapshot_policy(&mut self, snapshot_policy: SnapshotPolicy) {
+        self.snapshot_policy = snapshot_policy;
+        self.begin_solve();
+    }
+
+    /// restart the sampling of [`Visualizer::snapshot_policy`], called by the solvers at the beginning of each solve
+    pub fn begin_solve(&mut self) {
+        self.policy_event_count = 0;
+        self.policy_snapshot_count = 0;
+    }
+
+    /// whether to snapshot this event according to [`Visualizer::snapshot_policy`]
+    pub fn sample(&mut self, event: SnapshotEvent) -> bool {
+        if !self.snapshot_policy.accepts(event) {
+            return false;
+        }
+        let index = self.policy_event_count;
+        self.policy_event_count += 1;
+        if index.checked_rem(self.snapshot_policy.every) != Some(0) {
+            return false;
+        }
+        if let Some(max_snapshots) = self.snapshot_policy.max_snapshots {
+            if self.policy_snapshot_count >= max_snapshots {
+                return false;
+            }
+        }
+        self.policy_snapshot_count += 1;
+        true
+    }
+
+    pub fn incremental_save(&mut self, name: String, mut value: serde_json::Value) -> std::io::Result<()> {
+        if let Some(region) = self.snapshot_region.as_ref().filter(|_| self.has_output()) {
+            snapshot_filter_region(&mut value, region, true);
+        }
+        #[cfg(feature = "compressed_visualizer")]
+        if let Some(container) = self.container.as_mut() {
+            snapshot_annotate(&mut value, &self.vertex_annotations, &self.edge_annotations);
+            let is_keyframe = self.snapshots.len().checked_rem(SNAPSHOT_CONTAINER_KEYFRAME_INTERVAL) == Some(0);
+            self.snapshots.push(name.clone());
+            let stored = match self.last_snapshot.as_ref() {
+                Some(last_snapshot) if self.delta_snapshot && !is_keyframe => snapshot_compute_delta(last_snapshot, &value),
+                _ => value.clone(),
+            };
+            container.append(&json!((name, stored)))?;
+            self.last_snapshot = if self.delta_snapshot { Some(value) } else { None };
+            return Ok(());
+        }
         if let Some(file) = self.file.as_mut() {
+            snapshot_annotate(&mut value, &self.vertex_annotations, &self.edge_annotations);
             self.snapshots.push(name.clone());
             file.seek(SeekFrom::End(-2))?; // move the cursor before the ending ]}
             if !self.empty_snapshot {
                 file.write_all(b",")?;
             }
             self.empty_snapshot = false;
-            file.write_all(json!((name, value)).to_string().as_bytes())?;
+            if self.delta_snapshot {
+                let stored = match self.last_snapshot.as_ref() {
+                    Some(last_snapshot) => snapshot_compute_delta(last_snapshot, &value),
+                    None => value.clone(),
+                };
+                file.write_all(json!((name, stored)).to_string().as_bytes())?;
+                self.last_snapshot = Some(value);
+            } else {
+                file.write_all(json!((name, value)).to_string().as_bytes())?;
+                self.last_snapshot = None;
+            }
             file.write_all(b"]}")?;
             file.sync_all()?;
         }
@@ -558,6 +1388,298 @@ impl Visualizer {
         self.incremental_save(name, value)?;
         Ok(())
     }
+
+    /// save the snapshots of concurrently running units in the order of their timestamps, sampled by the snapshot policy
+    pub fn save_buffered(&mut self, buffer: &SnapshotBuffer) -> std::io::Result<()> {
+        for snapshot in buffer.take_ordered() {
+            if self.sample(snapshot.event) {
+                let name = format!("[unit {} {:.3e}s] {}", snapshot.unit_index, snapshot.timestamp, snapshot.name);
+                self.snapshot_combined_value(name, vec![snapshot.value])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// accumulates the matched edges of many shots into an edge-usage heatmap, rendered over the decoding graph;
+/// systematic decoder biases and partition-boundary artifacts show up as unexpectedly hot edges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeHeatmap {
+    pub initializer: SolverInitializer,
+    /// the number of shots in which each edge is in the subgraph
+    pub edge_counts: Vec<usize>,
+    /// the number of accumulated shots
+    pub shot_num: usize,
+}
+
+impl EdgeHeatmap {
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        Self {
+            initializer: initializer.clone(),
+            edge_counts: vec![0; initializer.weighted_edges.len()],
+            shot_num: 0,
+        }
+    }
+
+    /// accumulate the subgraph of a shot
+    #[allow(clippy::unnecessary_cast)]
+    pub fn add_subgraph(&mut self, subgraph: &[EdgeIndex]) {
+        for &edge_index in subgraph.iter() {
+            self.edge_counts[edge_index as usize] += 1;
+        }
+        self.shot_num += 1;
+    }
+
+    /// the fraction of shots in which each edge is used
+    pub fn edge_frequencies(&self) -> Vec<f64> {
+        let shot_num = std::cmp::max(self.shot_num, 1) as f64;
+        self.edge_counts.iter().map(|&count| count as f64 / shot_num).collect()
+    }
+}
+
+impl FusionVisualizer for EdgeHeatmap {
+    #[allow(clippy::unnecessary_cast)]
+    fn snapshot(&self, abbrev: bool) -> serde_json::Value {
+        let mut vertices = vec![json!({ if abbrev { "v" } else { "is_virtual" }: 0 }); self.initializer.vertex_num as usize];
+        for &virtual_vertex in self.initializer.virtual_vertices.iter() {
+            vertices[virtual_vertex as usize] = json!({ if abbrev { "v" } else { "is_virtual" }: 1 });
+        }
+        let edges: Vec<serde_json::Value> = self
+            .initializer
+            .weighted_edges
+            .iter()
+            .zip(self.edge_counts.iter())
+            .map(|(&(left, right, weight), &count)| {
+                json!({
+                    if abbrev { "w" } else { "weight" }: weight,
+                    if abbrev { "l" } else { "left" }: left,
+                    if abbrev { "r" } else { "right" }: right,
+                    if abbrev { "h" } else { "heat" }: count,
+                })
+            })
+            .collect();
+        json!({
+            "vertices": vertices,
+            "edges": edges,
+            "heatmap_shots": self.shot_num,
+        })
+    }
+}
+
+/// the pixels per unit of [`VisualizePosition`] in [`render_static`]
+const STATIC_SCALE: f64 = 40.;
+/// the time axis is drawn diagonally up-right in [`render_static`]
+const STATIC_TIME_SKEW: f64 = 0.4;
+
+type StaticColor = (u8, u8, u8);
+const STATIC_EDGE_COLOR: StaticColor = (0xc0, 0xc0, 0xc0);
+const STATIC_CORRECTION_COLOR: StaticColor = (0x1e, 0x88, 0xe5);
+const STATIC_VERTEX_COLOR: StaticColor = (0x40, 0x40, 0x40);
+const STATIC_VIRTUAL_COLOR: StaticColor = (0xbd, 0xbd, 0xbd);
+const STATIC_DEFECT_COLOR: StaticColor = (0xe5, 0x39, 0x35);
+
+enum StaticShape {
+    Line {
+        from: (f64, f64),
+        to: (f64, f64),
+        width: f64,
+        color: StaticColor,
+    },
+    Circle {
+        center: (f64, f64),
+        radius: f64,
+        color: StaticColor,
+    },
+}
+
+/// render the decoding graph, the defect vertices and the correction `subgraph` to a static image, for papers and quick
+/// inspection without the web frontend; the format is chosen by the extension of `path`: `.png` is rasterized without
+/// anti-aliasing and anything else is written as SVG
+#[allow(clippy::unnecessary_cast)]
+pub fn render_static(
+    initializer: &SolverInitializer,
+    positions: &[VisualizePosition],
+    defect_vertices: &[VertexIndex],
+    subgraph: &[EdgeIndex],
+    path: &str,
+) -> std::io::Result<()> {
+    assert_eq!(
+        positions.len(),
+        initializer.vertex_num as usize,
+        "each vertex must have a position"
+    );
+    let projected: Vec<(f64, f64)> = (positions.iter())
+        .map(|position| {
+            (
+                position.j + STATIC_TIME_SKEW * position.t,
+                position.i - STATIC_TIME_SKEW * position.t,
+            )
+        })
+        .collect();
+    let min_x = projected.iter().map(|&(x, _)| x).fold(f64::INFINITY, f64::min);
+    let min_y = projected.iter().map(|&(_, y)| y).fold(f64::INFINITY, f64::min);
+    let max_x = projected.iter().map(|&(x, _)| x).fold(f64::NEG_INFINITY, f64::max);
+    let max_y = projected.iter().map(|&(_, y)| y).fold(f64::NEG_INFINITY, f64::max);
+    // one unit of margin around the graph
+    let pixel = |vertex_index: VertexIndex| {
+        let (x, y) = projected[vertex_index as usize];
+        ((x - min_x + 1.) * STATIC_SCALE, (y - min_y + 1.) * STATIC_SCALE)
+    };
+    let width = ((max_x - min_x + 2.) * STATIC_SCALE).ceil() as usize;
+    let height = ((max_y - min_y + 2.) * STATIC_SCALE).ceil() as usize;
+    let mut shapes = vec![];
+    let mut is_correction = vec![false; initializer.weighted_edges.len()];
+    for &edge_index in subgraph.iter() {
+        is_correction[edge_index as usize] = true;
+    }
+    // draw the correction on top of the other edges
+    for correction in [false, true] {
+        for (edge_index, &(left, right, _)) in initializer.weighted_edges.iter().enumerate() {
+            if is_correction[edge_index] == correction {
+                shapes.push(StaticShape::Line {
+                    from: pixel(left),
+                    to: pixel(right),
+                    width: if correction { 5. } else { 2. },
+                    color: if correction {
+                        STATIC_CORRECTION_COLOR
+                    } else {
+                        STATIC_EDGE_COLOR
+                    },
+                });
+            }
+        }
+    }
+    let mut vertex_colors = vec![STATIC_VERTEX_COLOR; initializer.vertex_num as usize];
+    for &vertex_index in initializer.virtual_vertices.iter() {
+        vertex_colors[vertex_index as usize] = STATIC_VIRTUAL_COLOR;
+    }
+    for &vertex_index in defect_vertices.iter() {
+        vertex_colors[vertex_index as usize] = STATIC_DEFECT_COLOR;
+    }
+    for (vertex_index, &color) in vertex_colors.iter().enumerate() {
+        shapes.push(StaticShape::Circle {
+            center: pixel(vertex_index as VertexIndex),
+            radius: if color == STATIC_DEFECT_COLOR { 8. } else { 5. },
+            color,
+        });
+    }
+    let content = if path.ends_with(".png") {
+        render_png(&shapes, width, height)
+    } else {
+        render_svg(&shapes, width, height).into_bytes()
+    };
+    std::fs::write(path, content)
+}
+
+fn render_svg(shapes: &[StaticShape], width: usize, height: usize) -> String {
+    let hex = |(r, g, b): StaticColor| format!("#{r:02x}{g:02x}{b:02x}");
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n");
+    for shape in shapes.iter() {
+        svg.push_str(&match shape {
+            StaticShape::Line { from, to, width, color } => format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{width}\" stroke-linecap=\"round\"/>\n",
+                from.0,
+                from.1,
+                to.0,
+                to.1,
+                hex(*color)
+            ),
+            StaticShape::Circle { center, radius, color } => format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{radius}\" fill=\"{}\"/>\n",
+                center.0,
+                center.1,
+                hex(*color)
+            ),
+        });
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// rasterize the shapes into an RGB PNG image, using uncompressed deflate blocks to avoid an image dependency
+fn render_png(shapes: &[StaticShape], width: usize, height: usize) -> Vec<u8> {
+    let mut pixels = vec![255u8; width * height * 3];
+    for shape in shapes.iter() {
+        let (min, max, color, inside): (_, _, _, Box<dyn Fn(f64, f64) -> bool>) = match *shape {
+            StaticShape::Line { from, to, width, color } => {
+                let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                let length_square = dx * dx + dy * dy;
+                let inside = move |x: f64, y: f64| {
+                    let ratio = if length_square == 0. {
+                        0.
+                    } else {
+                        (((x - from.0) * dx + (y - from.1) * dy) / length_square).clamp(0., 1.)
+                    };
+                    let (px, py) = (from.0 + ratio * dx - x, from.1 + ratio * dy - y);
+                    px * px + py * py <= width * width / 4.
+                };
+                let min = (from.0.min(to.0) - width, from.1.min(to.1) - width);
+                let max = (from.0.max(to.0) + width, from.1.max(to.1) + width);
+                (min, max, color, Box::new(inside))
+            }
+            StaticShape::Circle { center, radius, color } => {
+                let inside = move |x: f64, y: f64| {
+                    (x - center.0) * (x - center.0) + (y - center.1) * (y - center.1) <= radius * radius
+                };
+                let min = (center.0 - radius, center.1 - radius);
+                let max = (center.0 + radius, center.1 + radius);
+                (min, max, color, Box::new(inside))
+            }
+        };
+        let range = |low: f64, high: f64, size: usize| (low.max(0.) as usize)..(high.ceil().max(0.) as usize).min(size);
+        for y in range(min.1, max.1, height) {
+            for x in range(min.0, max.0, width) {
+                // sample at the pixel center
+                if inside(x as f64 + 0.5, y as f64 + 0.5) {
+                    let offset = (y * width + x) * 3;
+                    pixels[offset..offset + 3].copy_from_slice(&[color.0, color.1, color.2]);
+                }
+            }
+        }
+    }
+    // each scanline starts with filter type 0 (none)
+    let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+    for row in pixels.chunks(width * 3) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let mut zlib = vec![0x78, 0x01];
+    let block_num = raw.len().div_ceil(65535); // the image is never empty because of the margin
+    for (block_index, block) in raw.chunks(65535).enumerate() {
+        let length = block.len() as u16;
+        zlib.push((block_index + 1 == block_num) as u8);
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in raw.iter() {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    zlib.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    let mut ihdr = vec![];
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlace
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (chunk_type, data) in [(b"IHDR", ihdr), (b"IDAT", zlib), (b"IEND", vec![])] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let mut crc = !0u32;
+        for &byte in chunk_type.iter().chain(data.iter()) {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+            }
+        }
+        png.extend_from_slice(chunk_type);
+        png.extend_from_slice(&data);
+        png.extend_from_slice(&(!crc).to_be_bytes());
+    }
+    png
 }
 
 const DEFAULT_VISUALIZE_DATA_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/visualize/data/");
@@ -620,10 +1742,10 @@ mod tests {
     use super::super::dual_module::*;
     use super::super::dual_module_serial::*;
     use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
     use super::super::pointers::*;
     use super::super::primal_module::*;
     use super::super::primal_module_serial::*;
-    use super::super::*;
     use super::*;
 
     #[test]
@@ -653,32 +1775,42 @@ mod tests {
         // create dual nodes and grow them by half length
         // test basic grow and shrink of a single tree node
         for _ in 0..4 {
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), half_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("grow half weight".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
         }
         for _ in 0..4 {
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), -half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), -half_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("shrink half weight".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
         }
         for _ in 0..3 {
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), half_weight)
+                .unwrap();
         }
         visualizer
             .snapshot_combined("grow 3 half weight".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
         for _ in 0..3 {
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), -half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), -half_weight)
+                .unwrap();
         }
         visualizer
             .snapshot_combined("shrink 3 half weight".to_string(), vec![&interface_ptr, &dual_module])
             .unwrap();
         // test all
         for i in 0..interface_ptr.read_recursive().nodes_length {
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[i].clone().unwrap(), half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[i].clone().unwrap(), half_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("grow half weight".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
@@ -843,21 +1975,35 @@ mod tests {
                 .snapshot_combined("initial".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
             // first layer grow first
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), quarter_weight);
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[1].clone().unwrap(), quarter_weight);
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[2].clone().unwrap(), quarter_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), quarter_weight)
+                .unwrap();
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[1].clone().unwrap(), quarter_weight)
+                .unwrap();
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[2].clone().unwrap(), quarter_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("grow a quarter".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
             // merge and match
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), quarter_weight);
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[1].clone().unwrap(), quarter_weight);
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[2].clone().unwrap(), quarter_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), quarter_weight)
+                .unwrap();
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[1].clone().unwrap(), quarter_weight)
+                .unwrap();
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[2].clone().unwrap(), quarter_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("find a match".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
             // grow to boundary
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[0].clone().unwrap(), half_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("touch temporal boundary".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
@@ -872,13 +2018,21 @@ mod tests {
                 .snapshot_combined("add measurement #4".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
             // handle errors at measurement round 4
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[5].clone().unwrap(), half_weight);
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[4].clone().unwrap(), half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[5].clone().unwrap(), half_weight)
+                .unwrap();
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[4].clone().unwrap(), half_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("grow a half".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[5].clone().unwrap(), half_weight);
-            dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[4].clone().unwrap(), half_weight);
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[5].clone().unwrap(), half_weight)
+                .unwrap();
+            dual_module
+                .grow_dual_node(&interface_ptr.read_recursive().nodes[4].clone().unwrap(), half_weight)
+                .unwrap();
             visualizer
                 .snapshot_combined("temporary match".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
@@ -887,9 +2041,15 @@ mod tests {
                 .snapshot_combined("add measurement #5".to_string(), vec![&interface_ptr, &dual_module])
                 .unwrap();
             for _ in 0..4 {
-                dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[4].clone().unwrap(), -quarter_weight);
-                dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[5].clone().unwrap(), quarter_weight);
-                dual_module.grow_dual_node(&interface_ptr.read_recursive().nodes[6].clone().unwrap(), quarter_weight);
+                dual_module
+                    .grow_dual_node(&interface_ptr.read_recursive().nodes[4].clone().unwrap(), -quarter_weight)
+                    .unwrap();
+                dual_module
+                    .grow_dual_node(&interface_ptr.read_recursive().nodes[5].clone().unwrap(), quarter_weight)
+                    .unwrap();
+                dual_module
+                    .grow_dual_node(&interface_ptr.read_recursive().nodes[6].clone().unwrap(), quarter_weight)
+                    .unwrap();
                 visualizer
                     .snapshot_combined("grow or shrink a quarter".to_string(), vec![&interface_ptr, &dual_module])
                     .unwrap();
@@ -996,4 +2156,394 @@ mod tests {
                 .unwrap();
         }
     }
+
+    #[test]
+    fn visualize_delta_snapshot() {
+        // cargo test visualize_delta_snapshot -- --nocapture
+        let defect_vertices = vec![39, 52, 63, 90, 100];
+        let half_weight = 500;
+        let mut file_sizes = vec![];
+        let mut loaded_data = vec![];
+        for delta_snapshot in [false, true] {
+            let visualize_filename = format!("visualize_delta_snapshot_{}.json", delta_snapshot);
+            let code = CodeCapacityPlanarCode::new(11, 0.2, half_weight);
+            let filepath = visualize_data_folder() + visualize_filename.as_str();
+            let mut visualizer = Visualizer::new(Some(filepath.clone()), code.get_positions(), true).unwrap();
+            visualizer.delta_snapshot = delta_snapshot;
+            print_visualize_link(visualize_filename.clone());
+            let initializer = code.get_initializer();
+            let mut dual_module = DualModuleSerial::new_empty(&initializer);
+            let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            primal_module.solve_visualizer(
+                &interface_ptr,
+                &SyndromePattern::new_vertices(defect_vertices.clone()),
+                &mut dual_module,
+                Some(&mut visualizer),
+            );
+            file_sizes.push(std::fs::metadata(&filepath).unwrap().len());
+            loaded_data.push(load_visualize_data(&filepath).unwrap());
+        }
+        println!("file sizes: {:?}", file_sizes);
+        assert!(file_sizes[1] < file_sizes[0], "delta snapshots should be smaller");
+        assert_eq!(
+            loaded_data[0], loaded_data[1],
+            "delta snapshots must reconstruct the full ones"
+        );
+    }
+
+    #[test]
+    fn visualize_snapshot_schema() {
+        // cargo test visualize_snapshot_schema -- --nocapture
+        let visualize_filename = "visualize_snapshot_schema.json".to_string();
+        let code = CodeCapacityPlanarCode::new(5, 0.1, 500);
+        let filepath = visualize_data_folder() + visualize_filename.as_str();
+        let mut visualizer = Visualizer::new(Some(filepath.clone()), code.get_positions(), true).unwrap();
+        visualizer.delta_snapshot = true;
+        print_visualize_link(visualize_filename.clone());
+        let mut solver = SolverSerial::new(&code.get_initializer());
+        solver.solve_visualizer(&SyndromePattern::new_vertices(vec![7, 8]), Some(&mut visualizer));
+        let raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&filepath).unwrap()).unwrap();
+        assert_eq!(raw["schema"], json!(SNAPSHOT_SCHEMA_VERSION));
+        let data = load_visualize_data(&filepath).unwrap();
+        let snapshots = data["snapshots"].as_array().unwrap();
+        assert!(snapshots.len() > 1);
+        assert!(snapshots
+            .iter()
+            .all(|snapshot| snapshot_schema_version(&snapshot[1]) == SNAPSHOT_SCHEMA_VERSION));
+        // a file written before the schema version is upgraded
+        let legacy_filepath = visualize_data_folder() + "visualize_snapshot_schema_legacy.json";
+        let legacy = json!({
+            "format": "fusion_blossom",
+            "version": "0.2.9",
+            "positions": [],
+            "snapshots": [["legacy", {
+                "vertices": [{ "is_virtual": 0, "is_syndrome": 1 }],
+                "edges": [],
+                "dual_nodes": [{ "syndrome_vertex": 0 }, null],
+            }]],
+        });
+        std::fs::write(&legacy_filepath, legacy.to_string()).unwrap();
+        let data = load_visualize_data(&legacy_filepath).unwrap();
+        let snapshot = &data["snapshots"][0][1];
+        assert_eq!(snapshot["vertices"][0], json!({ "is_virtual": 0, "is_defect": 1 }));
+        assert_eq!(snapshot["dual_nodes"][0], json!({ "defect_vertex": 0 }));
+        assert_eq!(snapshot_schema_version(snapshot), SNAPSHOT_SCHEMA_VERSION);
+        // a newer file is rejected
+        let mut newer = legacy.clone();
+        newer["schema"] = json!(SNAPSHOT_SCHEMA_VERSION + 1);
+        std::fs::write(&legacy_filepath, newer.to_string()).unwrap();
+        let error = load_visualize_data(&legacy_filepath).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("newer"), "{error}");
+    }
+
+    #[cfg(feature = "compressed_visualizer")]
+    #[test]
+    fn visualize_compressed_container() {
+        // cargo test visualize_compressed_container --features compressed_visualizer -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(7, 7, 0.05, 500);
+        let initializer = code.get_initializer();
+        let json_filepath = visualize_data_folder() + "visualize_compressed_container.json";
+        let filepath = visualize_data_folder() + "visualize_compressed_container.fbz";
+        let mut json_visualizer = Visualizer::new(Some(json_filepath.clone()), code.get_positions(), true).unwrap();
+        let mut visualizer = Visualizer::new_compressed(Some(filepath.clone()), code.get_positions(), true, 0).unwrap();
+        visualizer.delta_snapshot = true;
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..5 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve_visualizer(&syndrome_pattern, Some(&mut json_visualizer));
+            solver.clear();
+            solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+            solver.clear();
+        }
+        let snapshot_num = visualizer.snapshots.len();
+        assert!(
+            snapshot_num > SNAPSHOT_CONTAINER_KEYFRAME_INTERVAL,
+            "{snapshot_num} snapshots"
+        );
+        // readable while the visualizer is still writing, by scanning the records
+        let expected = load_visualize_data(&json_filepath).unwrap();
+        let mut reader = SnapshotContainerReader::open(&filepath).unwrap();
+        assert_eq!(reader.len(), snapshot_num);
+        assert_eq!(reader.header["positions"], expected["positions"]);
+        assert_eq!(
+            reader.snapshot(snapshot_num - 1).unwrap(),
+            expected["snapshots"][snapshot_num - 1]
+        );
+        drop(visualizer);
+        // random access with the index
+        let mut reader = SnapshotContainerReader::open(&filepath).unwrap();
+        assert_eq!(reader.len(), snapshot_num);
+        for snapshot_index in [0, SNAPSHOT_CONTAINER_KEYFRAME_INTERVAL + 1, snapshot_num - 1, 1] {
+            assert_eq!(
+                reader.snapshot(snapshot_index).unwrap(),
+                expected["snapshots"][snapshot_index]
+            );
+        }
+        assert!(reader.snapshot(snapshot_num).is_err());
+        assert_eq!(load_visualize_data(&filepath).unwrap(), expected);
+        assert!(std::fs::metadata(&filepath).unwrap().len() < std::fs::metadata(&json_filepath).unwrap().len() / 4);
+        // a record truncated by an interrupted write is ignored
+        let bytes = std::fs::read(&filepath).unwrap();
+        let index_len = 8 * (snapshot_num as u64 + 1) + 16;
+        std::fs::write(&filepath, &bytes[..bytes.len() - index_len as usize - 3]).unwrap();
+        let reader = SnapshotContainerReader::open(&filepath).unwrap();
+        assert_eq!(reader.len(), snapshot_num - 1);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn visualize_snapshot_region() {
+        // cargo test visualize_snapshot_region -- --nocapture
+        use super::super::example_partition::*;
+        let (d, noisy_measurements) = (7, 20);
+        let mut code = PhenomenologicalRepetitionCode::new(d, noisy_measurements, 0.1, 500);
+        let mut partition = PhenomenologicalRepetitionCodeTimePartition::new(d, noisy_measurements, 2);
+        let partition_info = partition.build_apply(&mut code).info();
+        let fusion_unit = (partition_info.units.iter())
+            .position(|unit| unit.children.is_some())
+            .unwrap();
+        let region = SnapshotRegion::from_units(&partition_info, &[fusion_unit]).unwrap();
+        assert!(SnapshotRegion::from_units(&partition_info, &[partition_info.units.len()]).is_err());
+        let initializer = code.get_initializer();
+        let syndrome_pattern = code.generate_random_errors(0);
+        let primal_dual_config = json!({ "primal": { "debug_sequential": true } });
+        let mut solver = SolverParallel::new(&initializer, &partition_info, primal_dual_config);
+        let full_filepath = visualize_data_folder() + "visualize_snapshot_region_full.json";
+        let filepath = visualize_data_folder() + "visualize_snapshot_region.json";
+        let mut full_visualizer = Visualizer::new(Some(full_filepath.clone()), code.get_positions(), true).unwrap();
+        let mut visualizer = Visualizer::new(Some(filepath.clone()), code.get_positions(), true).unwrap();
+        visualizer.snapshot_region = Some(region.clone());
+        solver.solve_visualizer(&syndrome_pattern, Some(&mut full_visualizer));
+        solver.clear();
+        solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+        let full_data = load_visualize_data(&full_filepath).unwrap();
+        let data = load_visualize_data(&filepath).unwrap();
+        assert_eq!(visualizer.snapshots, full_visualizer.snapshots);
+        assert!(std::fs::metadata(&filepath).unwrap().len() < std::fs::metadata(&full_filepath).unwrap().len() / 2);
+        let mut kept_dual_node_num = 0;
+        for (snapshot, full_snapshot) in data["snapshots"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .zip(full_data["snapshots"].as_array().unwrap())
+        {
+            let (snapshot, full_snapshot) = (&snapshot[1], &full_snapshot[1]);
+            for (vertex_index, vertex) in snapshot["vertices"].as_array().unwrap().iter().enumerate() {
+                let inside = region.contains(vertex_index as VertexIndex);
+                assert_eq!(vertex.is_null(), !inside || full_snapshot["vertices"][vertex_index].is_null());
+            }
+            for edge in snapshot["edges"].as_array().unwrap().iter().filter(|edge| !edge.is_null()) {
+                assert!(
+                    region.contains(edge["l"].as_u64().unwrap() as VertexIndex)
+                        || region.contains(edge["r"].as_u64().unwrap() as VertexIndex)
+                );
+            }
+            // every kept blossom refers to kept children
+            for dual_node in snapshot["dual_nodes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|dual_node| !dual_node.is_null())
+            {
+                kept_dual_node_num += 1;
+                for child_index in dual_node["o"].as_array().into_iter().flatten() {
+                    assert!(!snapshot["dual_nodes"][child_index.as_u64().unwrap() as usize].is_null());
+                }
+            }
+        }
+        assert!(kept_dual_node_num > 0);
+    }
+
+    #[test]
+    fn visualize_snapshot_policy() {
+        // cargo test visualize_snapshot_policy -- --nocapture
+        use super::super::example_partition::*;
+        let (d, noisy_measurements) = (7, 20);
+        let mut code = PhenomenologicalRepetitionCode::new(d, noisy_measurements, 0.05, 500);
+        let mut partition = PhenomenologicalRepetitionCodeTimePartition::auto(d, noisy_measurements);
+        let partition_info = partition.build_apply(&mut code).info();
+        let unit_num = partition_info.units.len();
+        let initializer = code.get_initializer();
+        let syndrome_pattern = code.generate_random_errors(0);
+        let primal_dual_config = json!({ "primal": { "debug_sequential": true } });
+        let mut solver = SolverParallel::new(&initializer, &partition_info, primal_dual_config);
+        let filepath = visualize_data_folder() + "visualize_snapshot_policy.json";
+        let mut visualizer = Visualizer::new(Some(filepath), code.get_positions(), true).unwrap();
+        let mut snapshot_num = |visualizer: &mut Visualizer, snapshot_policy: SnapshotPolicy| {
+            visualizer.set_snapshot_policy(snapshot_policy);
+            let begin = visualizer.snapshots.len();
+            solver.solve_visualizer(&syndrome_pattern, Some(visualizer));
+            solver.clear();
+            visualizer.snapshots.len() - begin
+        };
+        let all = snapshot_num(&mut visualizer, SnapshotPolicy::default());
+        // one snapshot per solved unit and one right after each fusion, plus the final one
+        let fusion_num = partition_info.units.iter().filter(|unit| unit.children.is_some()).count();
+        let fusion_snapshot_num = unit_num + fusion_num;
+        assert_eq!(
+            snapshot_num(&mut visualizer, SnapshotPolicy::fusion_only()),
+            fusion_snapshot_num + 1
+        );
+        let conflicts = snapshot_num(&mut visualizer, SnapshotPolicy::conflicts_only());
+        assert!(conflicts > 1 && conflicts < all - fusion_snapshot_num);
+        let every_3 = snapshot_num(&mut visualizer, serde_json::from_value(json!({ "every": 3 })).unwrap());
+        assert_eq!(every_3, (all - 1).div_ceil(3) + 1);
+        let max_4 = snapshot_num(
+            &mut visualizer,
+            serde_json::from_value(json!({ "max_snapshots": 4 })).unwrap(),
+        );
+        assert_eq!(max_4, 5);
+        // the policy is kept for the following solves
+        let begin = visualizer.snapshots.len();
+        solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+        assert_eq!(visualizer.snapshots.len() - begin, 5);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn visualize_render_static() {
+        // cargo test visualize_render_static -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let syndrome_pattern = code.generate_random_errors(2);
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve(&syndrome_pattern);
+        let subgraph = solver.subgraph();
+        assert!(!subgraph.is_empty());
+        let positions = code.get_positions();
+        let svg_path = visualize_data_folder() + "visualize_render_static.svg";
+        render_static(
+            &initializer,
+            &positions,
+            &syndrome_pattern.defect_vertices,
+            &subgraph,
+            &svg_path,
+        )
+        .unwrap();
+        let svg = std::fs::read_to_string(&svg_path).unwrap();
+        assert_eq!(svg.matches("<line ").count(), initializer.weighted_edges.len());
+        assert_eq!(svg.matches("<circle ").count(), initializer.vertex_num as usize);
+        assert_eq!(svg.matches("stroke=\"#1e88e5\"").count(), subgraph.len());
+        assert_eq!(
+            svg.matches("fill=\"#e53935\"").count(),
+            syndrome_pattern.defect_vertices.len()
+        );
+        let png_path = visualize_data_folder() + "visualize_render_static.png";
+        render_static(
+            &initializer,
+            &positions,
+            &syndrome_pattern.defect_vertices,
+            &subgraph,
+            &png_path,
+        )
+        .unwrap();
+        let png = std::fs::read(&png_path).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap()) as usize;
+        let svg_size = format!("width=\"{width}\" height=\"{height}\"");
+        assert!(svg.contains(&svg_size), "the two formats should have the same size");
+        // the raw scanlines are stored uncompressed
+        assert!(png.len() > height * (width * 3 + 1));
+    }
+
+    #[test]
+    fn visualize_interface_snapshot_filtered() {
+        // cargo test visualize_interface_snapshot_filtered -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let mut blossom_count = 0;
+        for seed in 0..20 {
+            solver.solve(&code.generate_random_errors(seed));
+            let full = solver.interface_ptr.snapshot(true);
+            assert_eq!(solver.interface_ptr.snapshot_filtered(true, |_| true), full);
+            let blossoms = solver
+                .interface_ptr
+                .snapshot_filtered(true, |node| matches!(node.class, DualNodeClass::Blossom { .. }));
+            let (full_nodes, blossom_nodes) = (
+                full["dual_nodes"].as_array().unwrap(),
+                blossoms["dual_nodes"].as_array().unwrap(),
+            );
+            assert_eq!(full_nodes.len(), blossom_nodes.len(), "node indices are kept");
+            for (full_node, blossom_node) in full_nodes.iter().zip(blossom_nodes.iter()) {
+                if !full_node.is_null() && !full_node["o"].is_null() {
+                    assert_eq!(full_node, blossom_node);
+                    blossom_count += 1;
+                } else {
+                    assert!(blossom_node.is_null());
+                }
+            }
+            solver.clear();
+        }
+        assert!(blossom_count > 0);
+    }
+
+    #[test]
+    fn visualize_edge_heatmap() {
+        // cargo test visualize_edge_heatmap -- --nocapture
+        let visualize_filename = "visualize_edge_heatmap.json".to_string();
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let mut heatmap = EdgeHeatmap::new(&initializer);
+        let shot_num = 100;
+        for seed in 0..shot_num {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            heatmap.add_subgraph(&solver.subgraph());
+            solver.clear();
+        }
+        assert_eq!(heatmap.shot_num, shot_num as usize);
+        assert!(heatmap.edge_frequencies().iter().all(|&frequency| frequency <= 1.));
+        assert!(heatmap.edge_counts.iter().any(|&count| count > 0));
+        let filepath = visualize_data_folder() + visualize_filename.as_str();
+        let mut visualizer = Visualizer::new(Some(filepath.clone()), code.get_positions(), true).unwrap();
+        print_visualize_link(visualize_filename.clone());
+        visualizer.snapshot("heatmap".to_string(), &heatmap).unwrap();
+        let data = load_visualize_data(&filepath).unwrap();
+        let snapshot = &data["snapshots"][0][1];
+        assert_eq!(snapshot["heatmap_shots"], json!(shot_num));
+        for (edge_index, &count) in heatmap.edge_counts.iter().enumerate() {
+            assert_eq!(snapshot["edges"][edge_index]["h"], json!(count));
+        }
+    }
+
+    #[test]
+    fn visualize_annotations() {
+        // cargo test visualize_annotations -- --nocapture
+        let visualize_filename = "visualize_annotations.json".to_string();
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let mut initializer = code.get_initializer();
+        for edge_index in 0..initializer.weighted_edges.len() as EdgeIndex {
+            initializer = initializer.with_edge_annotation(edge_index, json!({ "fault": format!("E{edge_index}") }));
+        }
+        initializer = initializer.with_vertex_annotation(0, json!("Z0"));
+        // the annotations are preserved in the snapshots
+        let filepath = visualize_data_folder() + visualize_filename.as_str();
+        let mut visualizer = Visualizer::new(Some(filepath.clone()), code.get_positions(), true).unwrap();
+        print_visualize_link(visualize_filename.clone());
+        visualizer.annotate(&initializer);
+        let syndrome_pattern = code.generate_random_errors(1);
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+        let data = load_visualize_data(&filepath).unwrap();
+        let snapshot = &data["snapshots"][0][1];
+        assert_eq!(snapshot["vertices"][0]["a"], json!("Z0"));
+        assert!(snapshot["vertices"][1].get("a").is_none());
+        assert_eq!(snapshot["edges"][3]["a"], json!({ "fault": "E3" }));
+        // and in the correction
+        let mut configured_solver = SolverConfig::Serial.build(&initializer);
+        let result = configured_solver.decode(&syndrome_pattern);
+        assert_eq!(result.annotations, initializer.subgraph_annotations(&result.subgraph));
+        for (&edge_index, annotation) in result.subgraph.iter().zip(result.annotations.iter()) {
+            assert_eq!(annotation, &json!({ "fault": format!("E{edge_index}") }));
+        }
+        // the annotations survive serialization of the decoding graph, and are omitted if absent
+        let deserialized: SolverInitializer = serde_json::from_value(json!(initializer)).unwrap();
+        assert_eq!(deserialized.edge_annotations, initializer.edge_annotations);
+        assert!(json!(code.get_initializer()).get("edge_annotations").is_none());
+    }
 }
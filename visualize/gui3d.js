@@ -229,6 +229,18 @@ export const subgraph_edge_material = new THREE.MeshStandardMaterial({
     transparent: true,
     side: THREE.FrontSide,
 })
+// edge-usage heatmap materials, from cold (rarely matched) to hot (frequently matched)
+const heatmap_level_num = 16
+export const heatmap_edge_materials = []
+for (let level = 0; level < heatmap_level_num; ++level) {
+    const ratio = level / (heatmap_level_num - 1)
+    heatmap_edge_materials.push(new THREE.MeshStandardMaterial({
+        color: new THREE.Color().setHSL(0.66 * (1 - ratio), 1, 0.5),
+        opacity: 0.2 + 0.8 * ratio,
+        transparent: true,
+        side: THREE.FrontSide,
+    }))
+}
 export const hover_material = new THREE.MeshStandardMaterial({  // when mouse is on this object (vertex or edge)
     color: 0x6FDFDF,
     side: THREE.DoubleSide,
@@ -388,6 +400,14 @@ export async function refresh_snapshot_data() {
                 subgraph_set[edge_index] = true
             }
         }
+        let heatmap_max = 0
+        if (snapshot.heatmap_shots != null) {
+            for (let edge of snapshot.edges) {
+                if (edge != null && edge.h > heatmap_max) {
+                    heatmap_max = edge.h
+                }
+            }
+        }
         let edge_offset = 0
         if (scaled_edge_radius.value < scaled_vertex_outline_radius.value) {
             edge_offset = Math.sqrt(Math.pow(scaled_vertex_outline_radius.value, 2) - Math.pow(scaled_edge_radius.value, 2))
@@ -468,6 +488,10 @@ export async function refresh_snapshot_data() {
                     if (subgraph_set[i]) {
                         edge_mesh.material = subgraph_edge_material
                     }
+                    if (snapshot.heatmap_shots != null) {  // color by the usage of this edge relative to the hottest edge
+                        const level = heatmap_max == 0 ? 0 : Math.round(edge.h / heatmap_max * (heatmap_level_num - 1))
+                        edge_mesh.material = heatmap_edge_materials[level]
+                    }
                 }
             }
         }
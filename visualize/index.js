@@ -25,6 +25,32 @@ const urlParams = new URLSearchParams(window.location.search)
 const filename = urlParams.get('filename') || "visualizer.json"
 
 export var fusion_data
+
+// snapshots may only store the difference against the previous one, see `snapshot_compute_delta` in visualize.rs
+export function reconstruct_delta_snapshots(fusion_data) {
+    let last_snapshot = null
+    for (let entry of fusion_data.snapshots) {
+        let snapshot = entry[1]
+        if (snapshot.delta != null) {
+            const delta = snapshot.delta
+            let full = JSON.parse(JSON.stringify(last_snapshot))
+            for (const key in snapshot) {
+                if (key != "delta") full[key] = snapshot[key]
+            }
+            for (const key of delta.removed) delete full[key]
+            for (const key in delta.arrays) {
+                const [length, changes] = delta.arrays[key]
+                let array = full[key]
+                while (array.length < length) array.push(null)
+                array.length = length
+                for (const [index, element] of changes) array[index] = element
+            }
+            entry[1] = full
+            snapshot = full
+        }
+        last_snapshot = snapshot
+    }
+}
 var patch_done = ref(false)
 
 // alert(navigator.userAgent)
@@ -81,6 +107,12 @@ const App = {
                 this.error_message = `visualization file format error, get "${fusion_data.format}" expected "fusion_data"`
                 throw this.error_message
             }
+            const supported_schema = 2  // must match `SNAPSHOT_SCHEMA_VERSION` in visualize.rs
+            if ((fusion_data.schema ?? 1) > supported_schema) {
+                this.error_message = `visualization file schema ${fusion_data.schema} is newer than the supported ${supported_schema}, please update the visualizer`
+                throw this.error_message
+            }
+            reconstruct_delta_snapshots(fusion_data)
         } else {
             this.error_message = `fetch file error ${response.status}: ${response.statusText}`
             throw this.error_message
@@ -224,6 +256,9 @@ const App = {
                 if (vertex.pg != null) {
                     this.selected_vertex_attributes += `(grandson ${vertex.pg}) `
                 }
+                if (vertex.a != null) {
+                    this.selected_vertex_attributes += `(annotation ${JSON.stringify(vertex.a)}) `
+                }
                 this.selected_vertex_misc = null
                 if (this.snapshot.vertices_comb != null) {
                     this.selected_vertex_misc = this.construct_quasar_tree(this.snapshot.vertices_comb[vertex_index])
@@ -291,6 +326,12 @@ const App = {
                 if (edge.lgd != null || edge.rgd != null) {
                     this.selected_edge_attributes += `(grandson l: ${edge.lgd}, r: ${edge.rgd}) `
                 }
+                if (this.snapshot.heatmap_shots != null) {
+                    this.selected_edge_attributes += `(used in ${edge.h} of ${this.snapshot.heatmap_shots} shots) `
+                }
+                if (edge.a != null) {
+                    this.selected_edge_attributes += `(annotation ${JSON.stringify(edge.a)}) `
+                }
                 this.selected_edge_misc = null
                 if (this.snapshot.edges_comb != null) {
                     this.selected_edge_misc = this.construct_quasar_tree(this.snapshot.edges_comb[edge_index])